@@ -0,0 +1,139 @@
+//! Archive URL parsing for repoverlay.
+//!
+//! Recognizes `.tar.gz`, `.tgz`, and `.zip` URLs so overlays can be applied
+//! from released artifacts instead of git repositories.
+
+use anyhow::{Context, Result, bail};
+use url::Url;
+
+/// Supported archive formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    /// Gzip-compressed tarball (`.tar.gz` or `.tgz`)
+    TarGz,
+    /// Zip archive (`.zip`)
+    Zip,
+}
+
+impl ArchiveKind {
+    /// Detect the archive kind from a URL path.
+    #[allow(clippy::case_sensitive_file_extension_comparisons)] // path is lowercased first
+    fn from_path(path: &str) -> Option<Self> {
+        let lower = path.to_ascii_lowercase();
+        if lower.ends_with(".tar.gz") || lower.ends_with(".tgz") {
+            Some(Self::TarGz)
+        } else if lower.ends_with(".zip") {
+            Some(Self::Zip)
+        } else {
+            None
+        }
+    }
+
+    /// File extension used for the downloaded archive.
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::TarGz => "tar.gz",
+            Self::Zip => "zip",
+        }
+    }
+}
+
+/// Parsed archive URL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveSource {
+    pub url: String,
+    pub kind: ArchiveKind,
+}
+
+impl ArchiveSource {
+    /// Parse an archive URL.
+    ///
+    /// Supported formats:
+    /// - `https://example.com/configs.tar.gz`
+    /// - `https://example.com/configs.tgz`
+    /// - `https://example.com/configs.zip`
+    pub fn parse(input: &str) -> Result<Self> {
+        let url = Url::parse(input).with_context(|| format!("Invalid URL: {input}"))?;
+
+        if url.scheme() != "https" && url.scheme() != "http" {
+            bail!("Unsupported archive URL scheme: {input}");
+        }
+
+        let Some(kind) = ArchiveKind::from_path(url.path()) else {
+            bail!("Not an archive URL (expected .tar.gz, .tgz, or .zip): {input}");
+        };
+
+        Ok(Self {
+            url: input.to_string(),
+            kind,
+        })
+    }
+
+    /// Check if a string looks like an archive URL.
+    pub fn is_archive_url(input: &str) -> bool {
+        Url::parse(input).is_ok_and(|url| {
+            (url.scheme() == "https" || url.scheme() == "http")
+                && ArchiveKind::from_path(url.path()).is_some()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tar_gz() {
+        let source = ArchiveSource::parse("https://example.com/configs.tar.gz").unwrap();
+        assert_eq!(source.kind, ArchiveKind::TarGz);
+        assert_eq!(source.url, "https://example.com/configs.tar.gz");
+    }
+
+    #[test]
+    fn test_parse_tgz() {
+        let source = ArchiveSource::parse("https://example.com/configs.tgz").unwrap();
+        assert_eq!(source.kind, ArchiveKind::TarGz);
+    }
+
+    #[test]
+    fn test_parse_zip() {
+        let source = ArchiveSource::parse("https://example.com/path/configs.zip").unwrap();
+        assert_eq!(source.kind, ArchiveKind::Zip);
+    }
+
+    #[test]
+    fn test_parse_ignores_query_string() {
+        let source = ArchiveSource::parse("https://example.com/configs.zip?token=abc").unwrap();
+        assert_eq!(source.kind, ArchiveKind::Zip);
+    }
+
+    #[test]
+    fn test_parse_rejects_non_archive() {
+        assert!(ArchiveSource::parse("https://example.com/configs.txt").is_err());
+    }
+
+    #[test]
+    fn test_parse_rejects_non_http_scheme() {
+        assert!(ArchiveSource::parse("file:///tmp/configs.tar.gz").is_err());
+    }
+
+    #[test]
+    fn test_is_archive_url() {
+        assert!(ArchiveSource::is_archive_url(
+            "https://example.com/configs.tar.gz"
+        ));
+        assert!(ArchiveSource::is_archive_url(
+            "https://github.com/owner/repo/archive/refs/heads/main.zip"
+        ));
+        assert!(!ArchiveSource::is_archive_url(
+            "https://github.com/owner/repo"
+        ));
+        assert!(!ArchiveSource::is_archive_url("./configs.tar.gz"));
+    }
+
+    #[test]
+    fn test_archive_kind_extension() {
+        assert_eq!(ArchiveKind::TarGz.extension(), "tar.gz");
+        assert_eq!(ArchiveKind::Zip.extension(), "zip");
+    }
+}
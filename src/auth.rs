@@ -0,0 +1,126 @@
+//! GitHub authentication for cloning private overlay repositories.
+//!
+//! Tokens are read from `GITHUB_TOKEN`/`GH_TOKEN` or `gh auth token` and
+//! handed to git through a credential helper that reads an environment
+//! variable, so they never appear in command lines, clone URLs, or cache
+//! metadata.
+
+use log::debug;
+use std::process::Command;
+
+/// Environment variables checked for a token, in priority order.
+pub const TOKEN_ENV_VARS: &[&str] = &["GITHUB_TOKEN", "GH_TOKEN"];
+
+/// Environment variable used to pass the token to the credential helper.
+const HELPER_TOKEN_ENV: &str = "REPOVERLAY_GITHUB_TOKEN";
+
+/// Credential helper that answers git's credential request from the environment.
+const CREDENTIAL_HELPER: &str =
+    "!f() { echo username=x-access-token; echo \"password=$REPOVERLAY_GITHUB_TOKEN\"; }; f";
+
+/// Resolve a GitHub token for `host`.
+///
+/// Checks `$GITHUB_TOKEN` and `$GH_TOKEN` first, then falls back to
+/// `gh auth token --hostname <host>`.
+pub fn resolve_token(host: &str) -> Option<String> {
+    for var in TOKEN_ENV_VARS {
+        if let Ok(token) = std::env::var(var) {
+            let token = token.trim();
+            if !token.is_empty() {
+                debug!("using GitHub token from ${var}");
+                return Some(token.to_string());
+            }
+        }
+    }
+
+    let output = Command::new("gh")
+        .args(["auth", "token", "--hostname", host])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let token = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    if token.is_empty() {
+        return None;
+    }
+    debug!("using GitHub token from gh CLI for {host}");
+    Some(token)
+}
+
+/// Configure a git command to authenticate to `host` with `token`.
+///
+/// Uses `GIT_CONFIG_*` environment variables to install a host-scoped
+/// credential helper, replacing any helpers configured for that host.
+pub fn apply_token(cmd: &mut Command, host: &str, token: &str) {
+    let key = format!("credential.https://{host}.helper");
+    cmd.env("GIT_CONFIG_COUNT", "2")
+        .env("GIT_CONFIG_KEY_0", &key)
+        .env("GIT_CONFIG_VALUE_0", "")
+        .env("GIT_CONFIG_KEY_1", &key)
+        .env("GIT_CONFIG_VALUE_1", CREDENTIAL_HELPER)
+        .env(HELPER_TOKEN_ENV, token);
+}
+
+/// Check whether git stderr output indicates missing or rejected credentials.
+pub fn is_auth_error(stderr: &str) -> bool {
+    stderr.contains("could not read Username")
+        || stderr.contains("Authentication failed")
+        || stderr.contains("terminal prompts disabled")
+        || stderr.contains("returned error: 403")
+        || stderr.contains("returned error: 401")
+}
+
+/// Instructions shown when a GitHub operation needs credentials.
+pub fn auth_help(host: &str) -> String {
+    format!(
+        "To access private repositories on {host}, set GITHUB_TOKEN or GH_TOKEN,\n\
+         or authenticate the GitHub CLI with: gh auth login --hostname {host}"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_auth_error() {
+        assert!(is_auth_error(
+            "fatal: could not read Username for 'https://github.com': terminal prompts disabled"
+        ));
+        assert!(is_auth_error(
+            "remote: Invalid username or password.\nfatal: Authentication failed for 'https://github.com/o/r.git/'"
+        ));
+        assert!(!is_auth_error(
+            "fatal: Remote branch nope not found in upstream origin"
+        ));
+    }
+
+    #[test]
+    fn test_apply_token_keeps_token_out_of_args() {
+        let mut cmd = Command::new("git");
+        cmd.args(["clone", "https://github.com/owner/repo.git"]);
+        apply_token(&mut cmd, "github.com", "secret-token");
+
+        assert!(
+            cmd.get_args()
+                .all(|arg| !arg.to_string_lossy().contains("secret-token"))
+        );
+        let envs: Vec<_> = cmd.get_envs().collect();
+        assert!(envs.iter().any(|(k, v)| {
+            *k == "GIT_CONFIG_KEY_1"
+                && v.is_some_and(|v| v == "credential.https://github.com.helper")
+        }));
+        assert!(
+            envs.iter()
+                .any(|(k, v)| *k == HELPER_TOKEN_ENV && v.is_some_and(|v| v == "secret-token"))
+        );
+    }
+
+    #[test]
+    fn test_auth_help_mentions_host() {
+        let help = auth_help("github.mycorp.com");
+        assert!(help.contains("GITHUB_TOKEN"));
+        assert!(help.contains("gh auth login --hostname github.mycorp.com"));
+    }
+}
@@ -4,14 +4,37 @@
 
 use anyhow::{Context, Result, bail};
 use chrono::{DateTime, Utc};
+use colored::Colorize;
 use directories::ProjectDirs;
 use log::{debug, trace, warn};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Output};
 
-use crate::github::{GitHubSource, GitRef};
+use crate::archive::{ArchiveKind, ArchiveSource};
+use crate::auth;
+use crate::github::{self, DEFAULT_GITHUB_HOST, GitHubSource, GitRef};
+use crate::hash::{sha256_file, sha256_hex};
+use crate::offline;
+use crate::retry::{self, DEFAULT_NETWORK_RETRIES};
+use crate::state::RefKind;
+
+/// Describe a failure to start `git`, calling out a missing git CLI.
+///
+/// repoverlay shells out to git (see ADR 0001), so a missing binary is the
+/// most likely cause and deserves a clearer message than "No such file".
+pub fn git_spawn_error(err: std::io::Error, subcommand: &str) -> anyhow::Error {
+    if err.kind() == std::io::ErrorKind::NotFound {
+        anyhow::anyhow!(
+            "git is not installed or not on PATH (needed to run git {subcommand}). \
+             Install git and try again."
+        )
+    } else {
+        anyhow::Error::new(err).context(format!("Failed to execute git {subcommand}"))
+    }
+}
 
 /// Execute a git command in a directory and return the output.
 fn git_in_dir(repo_path: &Path, args: &[&str]) -> Result<Output> {
@@ -20,7 +43,14 @@ fn git_in_dir(repo_path: &Path, args: &[&str]) -> Result<Output> {
         .args(args)
         .current_dir(repo_path)
         .output()
-        .with_context(|| format!("Failed to execute git {}", args.first().unwrap_or(&""))) // unwrap_or not inlinable
+        .map_err(|e| git_spawn_error(e, args.first().unwrap_or(&"")))
+}
+
+/// Write cache metadata for a cached repository.
+fn write_meta(repo_path: &Path, meta: &CacheMeta) -> Result<()> {
+    let meta_path = repo_path.join(".repoverlay-cache-meta.ccl");
+    fs::write(&meta_path, sickle::to_string(meta)?)?;
+    Ok(())
 }
 
 /// Execute a git command in a directory and check for success.
@@ -36,6 +66,75 @@ fn git_run(repo_path: &Path, args: &[&str]) -> Result<()> {
     }
 }
 
+/// Execute a git command that talks to a GitHub remote.
+///
+/// Authenticates with a token for `host` when one is available.
+fn git_remote_in_dir(repo_path: &Path, host: &str, args: &[&str]) -> Result<Output> {
+    trace!("git {} in {}", args.join(" "), repo_path.display()); // args.join() not inlinable
+    let mut cmd = Command::new("git");
+    cmd.args(args).current_dir(repo_path);
+    if let Some(token) = auth::resolve_token(host) {
+        auth::apply_token(&mut cmd, host, &token);
+    }
+    cmd.output()
+        .map_err(|e| git_spawn_error(e, args.first().unwrap_or(&"")))
+}
+
+/// Execute a git command that talks to a GitHub remote and check for success.
+fn git_remote_run(repo_path: &Path, host: &str, args: &[&str]) -> Result<()> {
+    let output = git_remote_in_dir(repo_path, host, args)?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let cmd = args.first().unwrap_or(&"");
+        let msg = stderr.trim();
+        if auth::is_auth_error(&stderr) {
+            let help = auth::auth_help(host);
+            bail!("git {cmd} failed: {msg}\n{help}")
+        }
+        bail!("git {cmd} failed: {msg}")
+    }
+}
+
+/// Run a prepared `git clone`, turning its stderr into a helpful error.
+fn run_clone(cmd: &mut Command, source: &GitHubSource, has_token: bool) -> Result<()> {
+    let output = cmd.output().map_err(|e| git_spawn_error(e, "clone"))?;
+    if output.status.success() {
+        return Ok(());
+    }
+
+    let owner = &source.owner;
+    let repo = &source.repo;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if retry::Failure::classify(&stderr) == retry::Failure::RateLimited {
+        let msg = stderr.trim();
+        bail!("GitHub rate limit exceeded cloning {owner}/{repo}: {msg}");
+    }
+    if auth::is_auth_error(&stderr) {
+        let help = auth::auth_help(&source.host);
+        if has_token {
+            bail!(
+                "Authentication failed for {owner}/{repo}: the GitHub token was rejected or lacks access.\n{help}"
+            );
+        }
+        bail!("Authentication required to clone {owner}/{repo}.\n{help}");
+    }
+    if stderr.contains("not found") || stderr.contains("Repository not found") {
+        if !has_token {
+            let help = auth::auth_help(&source.host);
+            bail!("Repository not found: {owner}/{repo}\nIf the repository is private: {help}");
+        }
+        bail!("Repository not found: {owner}/{repo}");
+    }
+    if stderr.contains("could not find remote branch") {
+        let git_ref = source.git_ref.as_str();
+        bail!("Branch or tag not found: {git_ref} in {owner}/{repo}");
+    }
+    let msg = stderr.trim();
+    bail!("Failed to clone repository: {msg}");
+}
+
 /// Metadata about a cached repository.
 #[derive(Debug, Deserialize, Serialize)]
 pub struct CacheMeta {
@@ -47,8 +146,27 @@ pub struct CacheMeta {
     pub requested_ref: String,
     /// The resolved commit SHA
     pub commit: String,
+    /// Commits that applied overlays use from this repository
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pins: Vec<CachePin>,
+}
+
+/// An applied overlay's use of a cached repository.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CachePin {
+    /// Repository the overlay is applied to
+    pub target: PathBuf,
+    /// Overlay name
+    pub overlay: String,
+    /// The git ref the overlay was applied from
+    pub git_ref: String,
+    /// The commit SHA the overlay was applied at
+    pub commit: String,
 }
 
+/// Namespace for refs that keep pinned commits from being pruned.
+const PIN_REF_PREFIX: &str = "refs/repoverlay/pins/";
+
 /// Result of caching a GitHub repository.
 #[derive(Debug)]
 pub struct CachedOverlay {
@@ -56,14 +174,38 @@ pub struct CachedOverlay {
     pub path: PathBuf,
     /// The resolved commit SHA
     pub commit: String,
+    /// Whether the source's ref turned out to be a branch, tag, or commit
+    pub ref_kind: RefKind,
     /// When the cache was created/updated
     #[allow(dead_code)]
     pub cached_at: DateTime<Utc>,
 }
 
+/// Metadata about a cached archive download.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ArchiveCacheMeta {
+    /// The archive URL
+    pub url: String,
+    /// When the archive was last downloaded
+    pub last_fetched: DateTime<Utc>,
+    /// SHA-256 of the downloaded archive
+    pub sha256: String,
+}
+
+/// Result of caching an archive.
+#[derive(Debug)]
+pub struct CachedArchive {
+    /// Path to the extracted overlay files
+    pub path: PathBuf,
+    /// SHA-256 of the downloaded archive
+    pub sha256: String,
+}
+
 /// Information about a cached repository.
 #[derive(Debug)]
 pub struct CachedRepoInfo {
+    /// Host the repository was cloned from
+    pub host: String,
     /// Owner name
     pub owner: String,
     /// Repository name
@@ -74,9 +216,24 @@ pub struct CachedRepoInfo {
     pub meta: Option<CacheMeta>,
 }
 
+impl CachedRepoInfo {
+    /// `owner/repo`, prefixed with the host for GitHub Enterprise clones.
+    pub fn display_name(&self) -> String {
+        if self.host == DEFAULT_GITHUB_HOST {
+            format!("{}/{}", self.owner, self.repo)
+        } else {
+            format!("{}/{}/{}", self.host, self.owner, self.repo)
+        }
+    }
+}
+
 /// Manager for the overlay cache.
 pub struct CacheManager {
     cache_dir: PathBuf,
+    /// Refresh cached clones older than this many hours (`0` disables).
+    cache_ttl_hours: u64,
+    /// Retries for rate-limited or failed clones, fetches, and downloads.
+    network_retries: u32,
 }
 
 #[allow(clippy::unused_self)]
@@ -84,7 +241,16 @@ impl CacheManager {
     /// Create a new cache manager.
     pub fn new() -> Result<Self> {
         let cache_dir = cache_dir()?;
-        Ok(Self { cache_dir })
+        let config = crate::config::load_global_config().unwrap_or_default();
+        let manager = Self {
+            cache_dir,
+            cache_ttl_hours: config.cache_ttl_hours,
+            network_retries: config.network_retries.unwrap_or(DEFAULT_NETWORK_RETRIES),
+        };
+        if let Err(e) = manager.migrate_legacy_layout() {
+            warn!("failed to migrate cache layout: {e}");
+        }
+        Ok(manager)
     }
 
     /// Get the cache directory path.
@@ -92,6 +258,79 @@ impl CacheManager {
         &self.cache_dir
     }
 
+    /// Directory holding a cached clone: `github/<host>/<owner>/<repo>`.
+    ///
+    /// The host keeps a GitHub Enterprise repository apart from a
+    /// `github.com` repository with the same owner and name.
+    fn github_repo_dir(&self, host: &str, owner: &str, repo: &str) -> PathBuf {
+        self.cache_dir
+            .join("github")
+            .join(host.to_ascii_lowercase())
+            .join(owner)
+            .join(repo)
+    }
+
+    /// Move clones from the old `github/<owner>/<repo>` layout to
+    /// `github/<host>/<owner>/<repo>`.
+    ///
+    /// The host is taken from the clone URL in the cache metadata, falling
+    /// back to `github.com`. A clone whose new location is already taken is
+    /// left where it is.
+    pub fn migrate_legacy_layout(&self) -> Result<()> {
+        let github_dir = self.cache_dir.join("github");
+        if !github_dir.is_dir() {
+            return Ok(());
+        }
+
+        for owner_entry in fs::read_dir(&github_dir)? {
+            let owner_dir = owner_entry?.path();
+            if !owner_dir.is_dir() {
+                continue;
+            }
+            for repo_entry in fs::read_dir(&owner_dir)? {
+                let old_path = repo_entry?.path();
+                // In the old layout, a clone sits two levels below `github/`
+                if !old_path.join(".git").exists() {
+                    continue;
+                }
+                let host = self.load_meta(&old_path).map_or_else(
+                    || DEFAULT_GITHUB_HOST.to_string(),
+                    |meta| github::host_from_url(&meta.clone_url),
+                );
+                let (Some(owner), Some(repo)) = (
+                    owner_dir.file_name().and_then(|n| n.to_str()),
+                    old_path.file_name().and_then(|n| n.to_str()),
+                ) else {
+                    continue;
+                };
+                let new_path = self.github_repo_dir(&host, owner, repo);
+                if new_path.exists() {
+                    debug!(
+                        "not migrating {}: {} exists",
+                        old_path.display(),
+                        new_path.display()
+                    );
+                    continue;
+                }
+                debug!(
+                    "migrating cache {} -> {}",
+                    old_path.display(),
+                    new_path.display()
+                );
+                if let Some(parent) = new_path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::rename(&old_path, &new_path).with_context(|| {
+                    format!("Failed to move cached repository {}", old_path.display())
+                })?;
+            }
+            if owner_dir.read_dir()?.next().is_none() {
+                fs::remove_dir(&owner_dir)?;
+            }
+        }
+        Ok(())
+    }
+
     /// Ensure a GitHub repository is cached and at the correct ref.
     ///
     /// Returns the path to the overlay files.
@@ -103,16 +342,40 @@ impl CacheManager {
         debug!("ensure_cached: {owner}/{repo} at {git_ref} (update={update})");
 
         let path = repo_path.display();
-        if repo_path.exists() {
+        let fetched = if repo_path.exists() {
             debug!("cache hit: {path}");
-            if update {
-                self.update_repo(&repo_path)?;
-            }
+            let fetched = if offline::is_offline() {
+                debug!("offline, using cached {path} as is");
+                false
+            } else if update {
+                self.update_repo(&repo_path, &source.host)?;
+                true
+            } else if self.is_stale(&repo_path) {
+                // Auto-refresh is best effort: fall back to the cached commit offline
+                match self.update_repo(&repo_path, &source.host) {
+                    Ok(()) => true,
+                    Err(e) => {
+                        warn!("failed to refresh stale cache {path}: {e}");
+                        eprintln!(
+                            "  {} Using cached {owner}/{repo}; refresh failed: {e}",
+                            "Warning:".yellow()
+                        );
+                        false
+                    }
+                }
+            } else {
+                false
+            };
             self.checkout_ref(&repo_path, source)?;
+            fetched
         } else {
+            if offline::is_offline() {
+                bail!("{owner}/{repo} is not cached and can't be cloned in offline mode");
+            }
             debug!("cache miss, cloning to {path}");
             self.clone_repo(source, &repo_path)?;
-        }
+            true
+        };
 
         let overlay_path = match &source.subpath {
             Some(subpath) => {
@@ -121,7 +384,15 @@ impl CacheManager {
                     let subpath_display = subpath.display();
                     let owner = &source.owner;
                     let repo = &source.repo;
-                    bail!("Subpath '{subpath_display}' not found in repository {owner}/{repo}");
+                    let dirs = top_level_dirs(&repo_path);
+                    let available = if dirs.is_empty() {
+                        "The repository has no directories.".to_string()
+                    } else {
+                        format!("Available top-level directories: {}", dirs.join(", "))
+                    };
+                    bail!(
+                        "Subpath '{subpath_display}' not found in repository {owner}/{repo}\n\n{available}"
+                    );
                 }
                 path
             }
@@ -129,24 +400,42 @@ impl CacheManager {
         };
 
         let commit = self.get_current_commit(&repo_path)?;
+        let ref_kind = self.ref_kind(&repo_path, &source.git_ref);
         let cached_at = Utc::now();
 
         // Save cache metadata
-        self.save_meta(&repo_path, source, &commit)?;
+        self.save_meta(&repo_path, source, &commit, fetched)?;
 
         Ok(CachedOverlay {
             path: overlay_path,
             commit,
+            ref_kind,
             cached_at,
         })
     }
 
+    /// Resolve which part of a `/tree/<ref>/<subpath>` URL is the ref.
+    ///
+    /// Branches like `release/2024-q3` contain slashes, so the remote's
+    /// branches and tags are listed to find the longest matching ref. This is
+    /// best effort: if the remote can't be reached, the URL's first segment
+    /// stays the ref.
+    pub fn resolve_tree_ref(&self, source: GitHubSource) -> GitHubSource {
+        if !source.has_ambiguous_ref() || offline::is_offline() {
+            return source;
+        }
+        match remote_refs(&source) {
+            Ok(refs) => source.with_known_refs(&refs),
+            Err(e) => {
+                debug!("could not list remote refs: {e}");
+                source
+            }
+        }
+    }
+
     /// Get the path where a repository would be cached.
     pub fn repo_path(&self, source: &GitHubSource) -> PathBuf {
-        self.cache_dir
-            .join("github")
-            .join(&source.owner)
-            .join(&source.repo)
+        self.github_repo_dir(&source.host, &source.owner, &source.repo)
     }
 
     /// Clone a repository.
@@ -177,36 +466,35 @@ impl CacheManager {
         cmd.arg(source.clone_url());
         cmd.arg(target);
 
-        let output = cmd.output().context("Failed to execute git clone")?;
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            let owner = &source.owner;
-            let repo = &source.repo;
-            if stderr.contains("not found") || stderr.contains("Repository not found") {
-                bail!("Repository not found: {owner}/{repo}");
-            }
-            if stderr.contains("could not find remote branch") {
-                let git_ref = source.git_ref.as_str();
-                bail!("Branch or tag not found: {git_ref} in {owner}/{repo}");
-            }
-            let msg = stderr.trim();
-            bail!("Failed to clone repository: {msg}");
+        // Authenticate via a credential helper so the token never ends up in
+        // the clone URL, the cached repo's git config, or cache metadata
+        let token = auth::resolve_token(&source.host);
+        if let Some(token) = &token {
+            auth::apply_token(&mut cmd, &source.host, token);
         }
 
+        retry::retry(
+            &format!("clone {owner}/{repo}"),
+            self.network_retries,
+            || run_clone(&mut cmd, source, token.is_some()),
+        )?;
+
         // If a specific commit was requested, we need to fetch and checkout
         if let GitRef::Commit(sha) = &source.git_ref {
-            self.fetch_commit(target, sha)?;
+            self.fetch_commit(target, &source.host, sha)?;
         }
 
         Ok(())
     }
 
     /// Update an existing cached repository.
-    fn update_repo(&self, repo_path: &Path) -> Result<()> {
+    fn update_repo(&self, repo_path: &Path, host: &str) -> Result<()> {
         let path = repo_path.display();
         debug!("fetching updates for {path}");
-        git_run(repo_path, &["fetch", "--depth", "1", "origin"]).context("Failed to fetch updates")
+        retry::retry(&format!("fetch {path}"), self.network_retries, || {
+            git_remote_run(repo_path, host, &["fetch", "--depth", "1", "origin"])
+        })
+        .context("Failed to fetch updates")
     }
 
     /// Checkout a specific ref.
@@ -233,6 +521,27 @@ impl CacheManager {
         self.do_checkout(repo_path, ref_spec)
     }
 
+    /// Work out what kind of ref `git_ref` is in a cached repository. Tags
+    /// are parsed from URLs as branches, so a name that is a tag and not a
+    /// remote branch counts as a tag.
+    fn ref_kind(&self, repo_path: &Path, git_ref: &GitRef) -> RefKind {
+        match git_ref {
+            GitRef::Default => RefKind::Branch,
+            GitRef::Tag(_) => RefKind::Tag,
+            GitRef::Commit(_) => RefKind::Commit,
+            GitRef::Branch(name) => {
+                let is_ref = |spec: &str| self.ref_exists(repo_path, spec).unwrap_or(false);
+                if !is_ref(&format!("refs/remotes/origin/{name}"))
+                    && is_ref(&format!("refs/tags/{name}"))
+                {
+                    RefKind::Tag
+                } else {
+                    RefKind::Branch
+                }
+            }
+        }
+    }
+
     /// Check if a ref exists in the repository.
     fn ref_exists(&self, repo_path: &Path, ref_spec: &str) -> Result<bool> {
         let output = git_in_dir(repo_path, &["rev-parse", "--verify", ref_spec])?;
@@ -246,14 +555,18 @@ impl CacheManager {
     }
 
     /// Fetch a specific commit.
-    fn fetch_commit(&self, repo_path: &Path, sha: &str) -> Result<()> {
+    fn fetch_commit(&self, repo_path: &Path, host: &str, sha: &str) -> Result<()> {
         // First, unshallow if needed to access the commit (ignore errors - might already be complete)
-        let _ = git_in_dir(repo_path, &["fetch", "--unshallow", "origin"]);
+        let _ = git_remote_in_dir(repo_path, host, &["fetch", "--unshallow", "origin"]);
 
         // Fetch the specific commit
         let short_sha = &sha[..12.min(sha.len())];
-        git_run(repo_path, &["fetch", "origin", sha])
-            .with_context(|| format!("Failed to fetch commit {short_sha}"))?;
+        retry::retry(
+            &format!("fetch commit {short_sha}"),
+            self.network_retries,
+            || git_remote_run(repo_path, host, &["fetch", "origin", sha]),
+        )
+        .with_context(|| format!("Failed to fetch commit {short_sha}"))?;
 
         // Checkout the commit
         git_run(repo_path, &["checkout", sha])
@@ -261,7 +574,7 @@ impl CacheManager {
     }
 
     /// Get the current commit SHA.
-    fn get_current_commit(&self, repo_path: &Path) -> Result<String> {
+    pub fn get_current_commit(&self, repo_path: &Path) -> Result<String> {
         let output = git_in_dir(repo_path, &["rev-parse", "HEAD"])?;
         if !output.status.success() {
             bail!("Failed to get current commit");
@@ -269,19 +582,128 @@ impl CacheManager {
         Ok(String::from_utf8(output.stdout)?.trim().to_string())
     }
 
+    /// Check whether a cached repository is older than the configured TTL.
+    fn is_stale(&self, repo_path: &Path) -> bool {
+        if self.cache_ttl_hours == 0 {
+            debug!("cache ttl disabled, not refreshing");
+            return false;
+        }
+        let Some(meta) = self.load_meta(repo_path) else {
+            debug!("no cache metadata, refreshing");
+            return true;
+        };
+
+        let age_minutes =
+            u64::try_from((Utc::now() - meta.last_fetched).num_minutes()).unwrap_or(0);
+        let stale = age_minutes >= self.cache_ttl_hours.saturating_mul(60);
+        debug!(
+            "cache last fetched {age_minutes}m ago, ttl {}h: {}",
+            self.cache_ttl_hours,
+            if stale { "stale, refreshing" } else { "fresh" }
+        );
+        stale
+    }
+
     /// Save cache metadata.
-    fn save_meta(&self, repo_path: &Path, source: &GitHubSource, commit: &str) -> Result<()> {
+    ///
+    /// `last_fetched` is only advanced when the repository was actually
+    /// cloned or fetched, so the TTL measures time since the last network update.
+    fn save_meta(
+        &self,
+        repo_path: &Path,
+        source: &GitHubSource,
+        commit: &str,
+        fetched: bool,
+    ) -> Result<()> {
+        let last_fetched = if fetched {
+            Utc::now()
+        } else {
+            self.load_meta(repo_path)
+                .map_or_else(Utc::now, |meta| meta.last_fetched)
+        };
+        let pins = self
+            .load_meta(repo_path)
+            .map(|meta| meta.pins)
+            .unwrap_or_default();
         let meta = CacheMeta {
             clone_url: source.clone_url(),
-            last_fetched: Utc::now(),
+            last_fetched,
             requested_ref: source.git_ref.as_str().to_string(),
             commit: commit.to_string(),
+            pins,
         };
 
-        let meta_path = repo_path.join(".repoverlay-cache-meta.ccl");
-        fs::write(&meta_path, sickle::to_string(&meta)?)?;
+        write_meta(repo_path, &meta)
+    }
 
-        Ok(())
+    /// Record that an applied overlay uses a cached repository at a commit.
+    ///
+    /// Replaces any earlier pin for the same overlay and target. Does nothing
+    /// if the repository is not cached.
+    pub fn record_pin(&self, host: &str, owner: &str, repo: &str, pin: CachePin) -> Result<()> {
+        let repo_path = self.github_repo_dir(host, owner, repo);
+        let Some(mut meta) = self.load_meta(&repo_path) else {
+            return Ok(());
+        };
+        meta.pins
+            .retain(|p| p.target != pin.target || p.overlay != pin.overlay);
+        meta.pins.push(pin);
+        write_meta(&repo_path, &meta)
+    }
+
+    /// Drop pins for overlays that are no longer applied.
+    ///
+    /// `live` holds the `(overlay, commit)` pairs of applied overlays from
+    /// this repository. Returns the pins that remain.
+    pub fn retain_pins(
+        &self,
+        repo_path: &Path,
+        live: &[(String, String)],
+    ) -> Result<Vec<CachePin>> {
+        let Some(mut meta) = self.load_meta(repo_path) else {
+            return Ok(Vec::new());
+        };
+        let before = meta.pins.len();
+        meta.pins.retain(|p| {
+            live.iter()
+                .any(|(overlay, commit)| *overlay == p.overlay && *commit == p.commit)
+        });
+        if meta.pins.len() != before {
+            write_meta(repo_path, &meta)?;
+        }
+        Ok(meta.pins)
+    }
+
+    /// Prune objects no ref reaches from a cached repository.
+    ///
+    /// Fetching a moved branch leaves the previous commit unreachable, so it
+    /// would be pruned. Commits in `keep` are first anchored under
+    /// `refs/repoverlay/pins/` so overlays pinned to them stay intact.
+    pub fn compact_repo(&self, repo_path: &Path, keep: &[String]) -> Result<()> {
+        // Never let git fall back to an enclosing repository
+        if !repo_path.join(".git").exists() {
+            bail!("{} is not a git repository", repo_path.display());
+        }
+        let existing = git_in_dir(
+            repo_path,
+            &["for-each-ref", "--format=%(refname)", PIN_REF_PREFIX],
+        )?;
+        for pin_ref in String::from_utf8_lossy(&existing.stdout).lines() {
+            let sha = pin_ref.trim_start_matches(PIN_REF_PREFIX);
+            if !keep.iter().any(|k| k == sha) {
+                git_run(repo_path, &["update-ref", "-d", pin_ref])?;
+            }
+        }
+        for sha in keep {
+            let pin_ref = format!("{PIN_REF_PREFIX}{sha}");
+            if let Err(e) = git_run(repo_path, &["update-ref", &pin_ref, sha]) {
+                // The commit may already be gone, e.g. after `cache clear`
+                debug!("could not keep {sha}: {e}");
+            }
+        }
+
+        git_run(repo_path, &["reflog", "expire", "--expire=now", "--all"])?;
+        git_run(repo_path, &["gc", "--prune=now", "--quiet"])
     }
 
     /// Load cache metadata.
@@ -315,49 +737,142 @@ impl CacheManager {
 
         let mut repos = Vec::new();
 
-        for owner_entry in fs::read_dir(&github_dir)? {
-            let owner_entry = owner_entry?;
-            if !owner_entry.file_type()?.is_dir() {
+        for host_entry in fs::read_dir(&github_dir)? {
+            let host_entry = host_entry?;
+            if !host_entry.file_type()?.is_dir() {
                 continue;
             }
 
-            let owner = owner_entry.file_name().to_string_lossy().to_string();
+            let host = host_entry.file_name().to_string_lossy().to_string();
 
-            for repo_entry in fs::read_dir(owner_entry.path())? {
-                let repo_entry = repo_entry?;
-                if !repo_entry.file_type()?.is_dir() {
+            for owner_entry in fs::read_dir(host_entry.path())? {
+                let owner_entry = owner_entry?;
+                if !owner_entry.file_type()?.is_dir() {
                     continue;
                 }
 
-                let repo = repo_entry.file_name().to_string_lossy().to_string();
-                let path = repo_entry.path();
-                let meta = self.load_meta(&path);
+                let owner = owner_entry.file_name().to_string_lossy().to_string();
+
+                for repo_entry in fs::read_dir(owner_entry.path())? {
+                    let repo_entry = repo_entry?;
+                    if !repo_entry.file_type()?.is_dir() {
+                        continue;
+                    }
 
-                repos.push(CachedRepoInfo {
-                    owner: owner.clone(),
-                    repo,
-                    path,
-                    meta,
-                });
+                    let repo = repo_entry.file_name().to_string_lossy().to_string();
+                    let path = repo_entry.path();
+                    let meta = self.load_meta(&path);
+
+                    repos.push(CachedRepoInfo {
+                        host: host.clone(),
+                        owner: owner.clone(),
+                        repo,
+                        path,
+                        meta,
+                    });
+                }
             }
         }
 
-        repos.sort_by(|a, b| (&a.owner, &a.repo).cmp(&(&b.owner, &b.repo)));
+        repos.sort_by(|a, b| (&a.host, &a.owner, &a.repo).cmp(&(&b.host, &b.owner, &b.repo)));
 
         Ok(repos)
     }
 
+    /// Look up a single cached repository.
+    pub fn cached_repo(&self, host: &str, owner: &str, repo: &str) -> Option<CachedRepoInfo> {
+        let path = self.github_repo_dir(host, owner, repo);
+        if !path.is_dir() {
+            return None;
+        }
+        let meta = self.load_meta(&path);
+        Some(CachedRepoInfo {
+            host: host.to_ascii_lowercase(),
+            owner: owner.to_string(),
+            repo: repo.to_string(),
+            path,
+            meta,
+        })
+    }
+
+    /// List the remote branches and tags fetched into a cached repository.
+    pub fn cached_refs(&self, repo_path: &Path) -> Vec<String> {
+        let Ok(output) = git_in_dir(
+            repo_path,
+            &[
+                "for-each-ref",
+                "--format=%(refname:short)",
+                "refs/remotes/origin",
+                "refs/tags",
+            ],
+        ) else {
+            return Vec::new();
+        };
+        if !output.status.success() {
+            return Vec::new();
+        }
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter(|r| *r != "origin/HEAD" && *r != "origin")
+            .map(|r| r.strip_prefix("origin/").unwrap_or(r).to_string())
+            .collect()
+    }
+
+    /// List cached repositories not in `referenced` (`host`, `owner`,
+    /// `repo` triples).
+    ///
+    /// GitHub names are case-insensitive, so the comparison is too.
+    pub fn unreferenced_repos(
+        &self,
+        referenced: &[(String, String, String)],
+    ) -> Result<Vec<CachedRepoInfo>> {
+        let referenced: HashSet<(String, String, String)> = referenced
+            .iter()
+            .map(|(host, owner, repo)| {
+                (
+                    host.to_lowercase(),
+                    owner.to_lowercase(),
+                    repo.to_lowercase(),
+                )
+            })
+            .collect();
+
+        Ok(self
+            .list_cached()?
+            .into_iter()
+            .filter(|info| {
+                !referenced.contains(&(
+                    info.host.to_lowercase(),
+                    info.owner.to_lowercase(),
+                    info.repo.to_lowercase(),
+                ))
+            })
+            .collect())
+    }
+
+    /// List cached repositories last fetched before `cutoff`.
+    ///
+    /// Repositories without metadata have no known age and are left out.
+    pub fn repos_fetched_before(&self, cutoff: DateTime<Utc>) -> Result<Vec<CachedRepoInfo>> {
+        Ok(self
+            .list_cached()?
+            .into_iter()
+            .filter(|info| info.meta.as_ref().is_some_and(|m| m.last_fetched < cutoff))
+            .collect())
+    }
+
     /// Remove a specific cached repository.
-    pub fn remove_cached(&self, owner: &str, repo: &str) -> Result<bool> {
-        let path = self.cache_dir.join("github").join(owner).join(repo);
+    pub fn remove_cached(&self, host: &str, owner: &str, repo: &str) -> Result<bool> {
+        let path = self.github_repo_dir(host, owner, repo);
 
         if path.exists() {
             fs::remove_dir_all(&path)?;
 
-            // Clean up empty parent directories
-            let owner_dir = self.cache_dir.join("github").join(owner);
-            if owner_dir.exists() && owner_dir.read_dir()?.next().is_none() {
-                fs::remove_dir(&owner_dir)?;
+            // Clean up empty owner and host directories
+            for dir in path.ancestors().skip(1).take(2) {
+                if dir.exists() && dir.read_dir()?.next().is_none() {
+                    fs::remove_dir(dir)?;
+                }
             }
 
             Ok(true)
@@ -369,23 +884,134 @@ impl CacheManager {
     /// Clear the entire cache.
     pub fn clear_cache(&self) -> Result<usize> {
         let github_dir = self.cache_dir.join("github");
+        let archives_dir = self.cache_dir.join("archives");
+        let mut count = 0;
 
-        if !github_dir.exists() {
-            return Ok(0);
+        if github_dir.exists() {
+            count += self.list_cached()?.len();
+            fs::remove_dir_all(&github_dir)?;
         }
 
-        let repos = self.list_cached()?;
-        let count = repos.len();
-
-        fs::remove_dir_all(&github_dir)?;
+        if archives_dir.exists() {
+            count += fs::read_dir(&archives_dir)?.count();
+            fs::remove_dir_all(&archives_dir)?;
+        }
 
         Ok(count)
     }
 
+    /// Get the path where an archive would be cached.
+    ///
+    /// Archives are keyed by a hash of their URL.
+    pub fn archive_path(&self, source: &ArchiveSource) -> PathBuf {
+        let key = sha256_hex(source.url.as_bytes());
+        self.cache_dir.join("archives").join(&key[..16])
+    }
+
+    /// Ensure an archive is downloaded and extracted.
+    ///
+    /// Returns the path to the overlay files and the archive's SHA-256.
+    pub fn ensure_archive_cached(
+        &self,
+        source: &ArchiveSource,
+        update: bool,
+    ) -> Result<CachedArchive> {
+        let archive_dir = self.archive_path(source);
+        let contents_dir = archive_dir.join("contents");
+        let url = &source.url;
+        debug!("ensure_archive_cached: {url} (update={update})");
+
+        if (!update || offline::is_offline())
+            && contents_dir.exists()
+            && let Some(meta) = self.load_archive_meta(&archive_dir)
+        {
+            debug!("archive cache hit: {}", archive_dir.display());
+            return Ok(CachedArchive {
+                path: archive_root(&contents_dir)?,
+                sha256: meta.sha256,
+            });
+        }
+
+        if offline::is_offline() {
+            bail!("{url} is not cached and can't be downloaded in offline mode");
+        }
+        fs::create_dir_all(&archive_dir)?;
+        let download_path = archive_dir.join(format!("archive.{}", source.kind.extension()));
+        retry::retry(&format!("download {url}"), self.network_retries, || {
+            download_archive(url, &download_path)
+        })?;
+        let sha256 = sha256_file(&download_path)?;
+
+        if contents_dir.exists() {
+            fs::remove_dir_all(&contents_dir)?;
+        }
+        extract_archive(source.kind, &download_path, &contents_dir)?;
+        fs::remove_file(&download_path)?;
+
+        let meta = ArchiveCacheMeta {
+            url: url.clone(),
+            last_fetched: Utc::now(),
+            sha256: sha256.clone(),
+        };
+        fs::write(
+            archive_dir.join(".repoverlay-cache-meta.ccl"),
+            sickle::to_string(&meta)?,
+        )?;
+
+        Ok(CachedArchive {
+            path: archive_root(&contents_dir)?,
+            sha256,
+        })
+    }
+
+    /// Check whether a remote archive has changed.
+    ///
+    /// Downloads the archive and returns its SHA-256 if different from `current_sha256`.
+    pub fn check_archive_for_updates(
+        &self,
+        source: &ArchiveSource,
+        current_sha256: &str,
+    ) -> Result<Option<String>> {
+        offline::ensure_online("check for updates")?;
+        let archive_dir = self.archive_path(source);
+        fs::create_dir_all(&archive_dir)?;
+
+        let check_path = archive_dir.join(format!("check.{}", source.kind.extension()));
+        retry::retry(
+            &format!("download {}", source.url),
+            self.network_retries,
+            || download_archive(&source.url, &check_path),
+        )?;
+        let sha256 = sha256_file(&check_path);
+        fs::remove_file(&check_path)?;
+        let sha256 = sha256?;
+
+        if sha256 == current_sha256 {
+            Ok(None)
+        } else {
+            Ok(Some(sha256))
+        }
+    }
+
+    /// Load archive cache metadata.
+    fn load_archive_meta(&self, archive_dir: &Path) -> Option<ArchiveCacheMeta> {
+        let meta_path = archive_dir.join(".repoverlay-cache-meta.ccl");
+        let content = fs::read_to_string(&meta_path).ok()?;
+        match sickle::from_str(&content) {
+            Ok(meta) => Some(meta),
+            Err(e) => {
+                let path = meta_path.display();
+                warn!("failed to parse archive cache meta {path}: {e}");
+                None
+            }
+        }
+    }
+
     /// Check for updates to a cached repository.
     ///
     /// Returns the latest commit on the default branch if different from current.
     pub fn check_for_updates(&self, source: &GitHubSource) -> Result<Option<String>> {
+        offline::ensure_online("check for updates")?;
         let repo_path = self.repo_path(source);
 
         if !repo_path.exists() {
@@ -395,7 +1021,11 @@ impl CacheManager {
         let current_commit = self.get_current_commit(&repo_path)?;
 
         // Fetch latest
-        let output = git_in_dir(&repo_path, &["fetch", "--depth", "1", "origin"])?;
+        let output = git_remote_in_dir(
+            &repo_path,
+            &source.host,
+            &["fetch", "--depth", "1", "origin"],
+        )?;
         if !output.status.success() {
             return Ok(None);
         }
@@ -425,6 +1055,151 @@ impl CacheManager {
     }
 }
 
+/// List the branch and tag names of a GitHub repository's remote.
+fn remote_refs(source: &GitHubSource) -> Result<Vec<String>> {
+    let mut cmd = Command::new("git");
+    cmd.args(["ls-remote", "--heads", "--tags", &source.clone_url()]);
+    if let Some(token) = auth::resolve_token(&source.host) {
+        auth::apply_token(&mut cmd, &source.host, &token);
+    }
+    let output = cmd.output().map_err(|e| git_spawn_error(e, "ls-remote"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("git ls-remote failed: {}", stderr.trim());
+    }
+    Ok(parse_ls_remote_refs(&String::from_utf8_lossy(
+        &output.stdout,
+    )))
+}
+
+/// Extract branch and tag names from `git ls-remote` output.
+fn parse_ls_remote_refs(output: &str) -> Vec<String> {
+    let mut refs: Vec<String> = output
+        .lines()
+        .filter_map(|line| line.split_once('\t').map(|(_, name)| name))
+        .filter_map(|name| {
+            name.strip_prefix("refs/heads/")
+                .or_else(|| name.strip_prefix("refs/tags/"))
+        })
+        .map(|name| name.trim_end_matches("^{}").to_string())
+        .collect();
+    refs.sort();
+    refs.dedup();
+    refs
+}
+
+/// Sorted names of the directories at the top of a cached clone, without `.git`.
+fn top_level_dirs(repo_path: &Path) -> Vec<String> {
+    let mut dirs: Vec<String> = fs::read_dir(repo_path)
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+                .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                .filter(|name| name != ".git")
+                .collect()
+        })
+        .unwrap_or_default();
+    dirs.sort();
+    dirs
+}
+
+/// Total size in bytes of the files under a directory.
+pub fn dir_size(path: &Path) -> u64 {
+    walkdir::WalkDir::new(path)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.metadata().ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+/// Download a URL to a local file using `curl`.
+///
+/// When the server rate limits the download, the error says when the limit
+/// resets if the response headers tell.
+fn download_archive(url: &str, dest: &Path) -> Result<()> {
+    debug!("downloading {url} to {}", dest.display());
+    let mut headers_path = dest.as_os_str().to_owned();
+    headers_path.push(".headers");
+    let headers_path = PathBuf::from(headers_path);
+    let output = Command::new("curl")
+        .args([
+            "--fail",
+            "--silent",
+            "--show-error",
+            "--location",
+            "--dump-header",
+        ])
+        .arg(&headers_path)
+        .arg("--output")
+        .arg(dest)
+        .arg(url)
+        .output()
+        .context("Failed to execute curl (is it installed?)");
+    let headers = fs::read_to_string(&headers_path).unwrap_or_default();
+    let _ = fs::remove_file(&headers_path);
+    let output = output?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let msg = stderr.trim();
+        if (retry::Failure::classify(msg) == retry::Failure::RateLimited
+            || msg.contains("error: 403"))
+            && let Some(reset) = retry::rate_limit_reset(&headers, chrono::Local::now())
+        {
+            let reset = reset.format("%H:%M:%S");
+            bail!("Rate limit exceeded downloading {url} (resets at {reset}): {msg}");
+        }
+        bail!("Failed to download archive {url}: {msg}");
+    }
+    Ok(())
+}
+
+/// Extract an archive into a directory using `tar` or `unzip`.
+fn extract_archive(kind: ArchiveKind, archive: &Path, dest: &Path) -> Result<()> {
+    debug!("extracting {} to {}", archive.display(), dest.display());
+    fs::create_dir_all(dest)?;
+
+    let mut cmd = match kind {
+        ArchiveKind::TarGz => {
+            let mut cmd = Command::new("tar");
+            cmd.arg("-xzf").arg(archive).arg("-C").arg(dest);
+            cmd
+        }
+        ArchiveKind::Zip => {
+            let mut cmd = Command::new("unzip");
+            cmd.arg("-q").arg(archive).arg("-d").arg(dest);
+            cmd
+        }
+    };
+
+    let output = cmd
+        .output()
+        .context("Failed to execute archive extraction")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let msg = stderr.trim();
+        bail!("Failed to extract archive {}: {msg}", archive.display());
+    }
+    Ok(())
+}
+
+/// Find the overlay root within extracted archive contents.
+///
+/// Archives commonly wrap everything in a single top-level directory
+/// (e.g. `configs-1.0/`); in that case the wrapper directory is the root.
+fn archive_root(contents_dir: &Path) -> Result<PathBuf> {
+    let entries: Vec<_> = fs::read_dir(contents_dir)?.collect::<Result<_, _>>()?;
+    if let [entry] = entries.as_slice()
+        && entry.file_type()?.is_dir()
+    {
+        return Ok(entry.path());
+    }
+    Ok(contents_dir.to_path_buf())
+}
+
 /// Get the cache directory.
 pub fn cache_dir() -> Result<PathBuf> {
     let proj_dirs = ProjectDirs::from("", "", "repoverlay")
@@ -436,8 +1211,21 @@ pub fn cache_dir() -> Result<PathBuf> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::github::DEFAULT_GITHUB_HOST;
     use tempfile::TempDir;
 
+    #[test]
+    fn test_git_spawn_error_missing_git() {
+        let err = git_spawn_error(std::io::ErrorKind::NotFound.into(), "clone");
+        assert!(
+            err.to_string()
+                .contains("git is not installed or not on PATH")
+        );
+
+        let err = git_spawn_error(std::io::ErrorKind::PermissionDenied.into(), "clone");
+        assert_eq!(err.to_string(), "Failed to execute git clone");
+    }
+
     #[test]
     fn test_cache_manager_creation() {
         let manager = CacheManager::new();
@@ -450,7 +1238,7 @@ mod tests {
         let source = GitHubSource::parse("https://github.com/owner/repo").unwrap();
         let path = manager.repo_path(&source);
 
-        assert!(path.ends_with("github/owner/repo"));
+        assert!(path.ends_with("github/github.com/owner/repo"));
     }
 
     #[test]
@@ -460,7 +1248,84 @@ mod tests {
         let path = manager.repo_path(&source);
 
         // Subpath should not affect cache path (repo is cached, subpath is used at read time)
-        assert!(path.ends_with("github/owner/repo"));
+        assert!(path.ends_with("github/github.com/owner/repo"));
+    }
+
+    #[test]
+    fn test_repo_path_separates_hosts() {
+        let temp = TempDir::new().unwrap();
+        let manager = CacheManager {
+            cache_dir: temp.path().to_path_buf(),
+            cache_ttl_hours: 0,
+            network_retries: 0,
+        };
+        let public = GitHubSource::parse("https://github.com/owner/repo").unwrap();
+        let enterprise = GitHubSource::parse_with_hosts(
+            "https://ghe.example.com/owner/repo",
+            &["ghe.example.com".to_string()],
+        )
+        .unwrap();
+
+        let public_path = manager.repo_path(&public);
+        let enterprise_path = manager.repo_path(&enterprise);
+        assert_ne!(public_path, enterprise_path);
+        assert!(enterprise_path.ends_with("github/ghe.example.com/owner/repo"));
+
+        fs::create_dir_all(&public_path).unwrap();
+        fs::create_dir_all(&enterprise_path).unwrap();
+        let repos = manager.list_cached().unwrap();
+        let names: Vec<String> = repos.iter().map(CachedRepoInfo::display_name).collect();
+        assert_eq!(names, ["ghe.example.com/owner/repo", "owner/repo"]);
+
+        assert!(
+            manager
+                .remove_cached("ghe.example.com", "owner", "repo")
+                .unwrap()
+        );
+        assert!(public_path.exists());
+        assert!(!temp.path().join("github/ghe.example.com").exists());
+    }
+
+    #[test]
+    fn test_migrate_legacy_layout_moves_clones_under_host() {
+        let temp = TempDir::new().unwrap();
+        let manager = CacheManager {
+            cache_dir: temp.path().to_path_buf(),
+            cache_ttl_hours: 0,
+            network_retries: 0,
+        };
+
+        // Old layout: github/<owner>/<repo>, host only known from metadata
+        let public = temp.path().join("github/owner/public");
+        fs::create_dir_all(public.join(".git")).unwrap();
+        let enterprise = temp.path().join("github/owner/internal");
+        fs::create_dir_all(enterprise.join(".git")).unwrap();
+        let meta = CacheMeta {
+            clone_url: "https://ghe.example.com/owner/internal.git".to_string(),
+            last_fetched: Utc::now(),
+            requested_ref: "main".to_string(),
+            commit: "abc123".to_string(),
+            pins: Vec::new(),
+        };
+        write_meta(&enterprise, &meta).unwrap();
+
+        manager.migrate_legacy_layout().unwrap();
+
+        assert!(
+            temp.path()
+                .join("github/github.com/owner/public/.git")
+                .exists()
+        );
+        assert!(
+            temp.path()
+                .join("github/ghe.example.com/owner/internal/.git")
+                .exists()
+        );
+        assert!(!temp.path().join("github/owner").exists());
+
+        // Running again is a no-op
+        manager.migrate_legacy_layout().unwrap();
+        assert_eq!(manager.list_cached().unwrap().len(), 2);
     }
 
     #[test]
@@ -485,6 +1350,7 @@ mod tests {
             last_fetched: Utc::now(),
             requested_ref: "main".to_string(),
             commit: "abc123def456789012345678901234567890abcdef".to_string(),
+            pins: Vec::new(),
         };
 
         let serialized = sickle::to_string(&meta).unwrap();
@@ -500,6 +1366,8 @@ mod tests {
         let temp = TempDir::new().unwrap();
         let manager = CacheManager {
             cache_dir: temp.path().to_path_buf(),
+            cache_ttl_hours: 0,
+            network_retries: 0,
         };
 
         let repos = manager.list_cached().unwrap();
@@ -511,11 +1379,13 @@ mod tests {
         let temp = TempDir::new().unwrap();
         let manager = CacheManager {
             cache_dir: temp.path().to_path_buf(),
+            cache_ttl_hours: 0,
+            network_retries: 0,
         };
 
         // Create fake cached repos
-        let repo1 = temp.path().join("github/owner1/repo1");
-        let repo2 = temp.path().join("github/owner2/repo2");
+        let repo1 = temp.path().join("github/github.com/owner1/repo1");
+        let repo2 = temp.path().join("github/github.com/owner2/repo2");
         fs::create_dir_all(&repo1).unwrap();
         fs::create_dir_all(&repo2).unwrap();
 
@@ -534,10 +1404,12 @@ mod tests {
         let temp = TempDir::new().unwrap();
         let manager = CacheManager {
             cache_dir: temp.path().to_path_buf(),
+            cache_ttl_hours: 0,
+            network_retries: 0,
         };
 
         // Create fake cached repo with metadata
-        let repo_path = temp.path().join("github/owner/repo");
+        let repo_path = temp.path().join("github/github.com/owner/repo");
         fs::create_dir_all(&repo_path).unwrap();
 
         let meta = CacheMeta {
@@ -545,6 +1417,7 @@ mod tests {
             last_fetched: Utc::now(),
             requested_ref: "main".to_string(),
             commit: "abc123".to_string(),
+            pins: Vec::new(),
         };
         let meta_path = repo_path.join(".repoverlay-cache-meta.ccl");
         fs::write(&meta_path, sickle::to_string(&meta).unwrap()).unwrap();
@@ -560,6 +1433,8 @@ mod tests {
         let temp = TempDir::new().unwrap();
         let manager = CacheManager {
             cache_dir: temp.path().to_path_buf(),
+            cache_ttl_hours: 0,
+            network_retries: 0,
         };
 
         let count = manager.clear_cache().unwrap();
@@ -571,11 +1446,13 @@ mod tests {
         let temp = TempDir::new().unwrap();
         let manager = CacheManager {
             cache_dir: temp.path().to_path_buf(),
+            cache_ttl_hours: 0,
+            network_retries: 0,
         };
 
         // Create fake cached repos
-        let repo1 = temp.path().join("github/owner1/repo1");
-        let repo2 = temp.path().join("github/owner1/repo2");
+        let repo1 = temp.path().join("github/github.com/owner1/repo1");
+        let repo2 = temp.path().join("github/github.com/owner1/repo2");
         fs::create_dir_all(&repo1).unwrap();
         fs::create_dir_all(&repo2).unwrap();
 
@@ -586,14 +1463,192 @@ mod tests {
         assert!(!temp.path().join("github").exists());
     }
 
+    #[test]
+    fn test_clear_cache_removes_archives() {
+        let temp = TempDir::new().unwrap();
+        let manager = CacheManager {
+            cache_dir: temp.path().to_path_buf(),
+            cache_ttl_hours: 0,
+            network_retries: 0,
+        };
+
+        fs::create_dir_all(temp.path().join("github/github.com/owner/repo")).unwrap();
+        fs::create_dir_all(temp.path().join("archives/0123456789abcdef")).unwrap();
+
+        let count = manager.clear_cache().unwrap();
+        assert_eq!(count, 2);
+        assert!(!temp.path().join("archives").exists());
+    }
+
+    #[test]
+    fn test_unreferenced_repos_skips_referenced() {
+        let temp = TempDir::new().unwrap();
+        let manager = CacheManager {
+            cache_dir: temp.path().to_path_buf(),
+            cache_ttl_hours: 0,
+            network_retries: 0,
+        };
+
+        fs::create_dir_all(temp.path().join("github/github.com/owner/used")).unwrap();
+        fs::create_dir_all(temp.path().join("github/github.com/owner/stale")).unwrap();
+        fs::create_dir_all(temp.path().join("github/github.com/Other/Repo")).unwrap();
+
+        let referenced = vec![
+            (
+                "github.com".to_string(),
+                "owner".to_string(),
+                "used".to_string(),
+            ),
+            (
+                "GitHub.com".to_string(),
+                "other".to_string(),
+                "repo".to_string(),
+            ),
+        ];
+        let unused = manager.unreferenced_repos(&referenced).unwrap();
+
+        assert_eq!(unused.len(), 1);
+        assert_eq!(
+            (unused[0].owner.as_str(), unused[0].repo.as_str()),
+            ("owner", "stale")
+        );
+    }
+
+    #[test]
+    fn test_record_pin_replaces_pin_for_same_overlay() {
+        let temp = TempDir::new().unwrap();
+        let manager = CacheManager {
+            cache_dir: temp.path().to_path_buf(),
+            cache_ttl_hours: 0,
+            network_retries: 0,
+        };
+        let repo_path = temp.path().join("github/github.com/owner/repo");
+        fs::create_dir_all(&repo_path).unwrap();
+        write_meta_fetched_at(&repo_path, Utc::now());
+
+        let pin = |overlay: &str, commit: &str| CachePin {
+            target: PathBuf::from("/work/app"),
+            overlay: overlay.to_string(),
+            git_ref: "main".to_string(),
+            commit: commit.to_string(),
+        };
+        manager
+            .record_pin("github.com", "owner", "repo", pin("a", "111"))
+            .unwrap();
+        manager
+            .record_pin("github.com", "owner", "repo", pin("b", "222"))
+            .unwrap();
+        manager
+            .record_pin("github.com", "owner", "repo", pin("a", "333"))
+            .unwrap();
+
+        let pins = manager.load_meta(&repo_path).unwrap().pins;
+        assert_eq!(pins, vec![pin("b", "222"), pin("a", "333")]);
+
+        let live = vec![("a".to_string(), "333".to_string())];
+        assert_eq!(
+            manager.retain_pins(&repo_path, &live).unwrap(),
+            vec![pin("a", "333")]
+        );
+        assert_eq!(manager.load_meta(&repo_path).unwrap().pins.len(), 1);
+    }
+
+    #[test]
+    fn test_record_pin_ignores_uncached_repo() {
+        let temp = TempDir::new().unwrap();
+        let manager = CacheManager {
+            cache_dir: temp.path().to_path_buf(),
+            cache_ttl_hours: 0,
+            network_retries: 0,
+        };
+        let pin = CachePin {
+            target: PathBuf::from("/work/app"),
+            overlay: "a".to_string(),
+            git_ref: "main".to_string(),
+            commit: "111".to_string(),
+        };
+
+        manager
+            .record_pin("github.com", "owner", "repo", pin)
+            .unwrap();
+        assert!(!temp.path().join("github/github.com/owner/repo").exists());
+    }
+
+    #[test]
+    fn test_compact_repo_keeps_only_pinned_unreachable_commits() {
+        let temp = TempDir::new().unwrap();
+        let manager = CacheManager {
+            cache_dir: temp.path().to_path_buf(),
+            cache_ttl_hours: 0,
+            network_retries: 0,
+        };
+        let repo = temp.path().join("repo");
+        fs::create_dir_all(&repo).unwrap();
+        let git = |args: &[&str]| {
+            let output = Command::new("git")
+                .args(["-c", "user.name=t", "-c", "user.email=t@t"])
+                .args(args)
+                .current_dir(&repo)
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "git {args:?} failed");
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        };
+        let has_commit = |sha: &str| {
+            git_in_dir(&repo, &["cat-file", "-e", sha])
+                .unwrap()
+                .status
+                .success()
+        };
+
+        git(&["init", "-q"]);
+        fs::write(repo.join("file"), "v1").unwrap();
+        git(&["add", "file"]);
+        git(&["commit", "-qm", "one"]);
+        let pinned = git(&["rev-parse", "HEAD"]);
+        // Replace the commit, as a force-pushed branch would after a fetch
+        fs::write(repo.join("file"), "v2").unwrap();
+        git(&["commit", "-qa", "--amend", "-m", "two"]);
+
+        manager
+            .compact_repo(&repo, std::slice::from_ref(&pinned))
+            .unwrap();
+        assert!(has_commit(&pinned));
+
+        manager.compact_repo(&repo, &[]).unwrap();
+        assert!(!has_commit(&pinned));
+    }
+
+    #[test]
+    fn test_compact_repo_rejects_non_git_dir() {
+        let temp = TempDir::new().unwrap();
+        let manager = CacheManager {
+            cache_dir: temp.path().to_path_buf(),
+            cache_ttl_hours: 0,
+            network_retries: 0,
+        };
+
+        let result = manager.compact_repo(temp.path(), &[]);
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("not a git repository")
+        );
+    }
+
     #[test]
     fn test_remove_cached_nonexistent() {
         let temp = TempDir::new().unwrap();
         let manager = CacheManager {
             cache_dir: temp.path().to_path_buf(),
+            cache_ttl_hours: 0,
+            network_retries: 0,
         };
 
-        let removed = manager.remove_cached("owner", "repo").unwrap();
+        let removed = manager
+            .remove_cached("github.com", "owner", "repo")
+            .unwrap();
         assert!(!removed);
     }
 
@@ -602,13 +1657,17 @@ mod tests {
         let temp = TempDir::new().unwrap();
         let manager = CacheManager {
             cache_dir: temp.path().to_path_buf(),
+            cache_ttl_hours: 0,
+            network_retries: 0,
         };
 
         // Create fake cached repo
-        let repo_path = temp.path().join("github/owner/repo");
+        let repo_path = temp.path().join("github/github.com/owner/repo");
         fs::create_dir_all(&repo_path).unwrap();
 
-        let removed = manager.remove_cached("owner", "repo").unwrap();
+        let removed = manager
+            .remove_cached("github.com", "owner", "repo")
+            .unwrap();
         assert!(removed);
         assert!(!repo_path.exists());
     }
@@ -618,16 +1677,20 @@ mod tests {
         let temp = TempDir::new().unwrap();
         let manager = CacheManager {
             cache_dir: temp.path().to_path_buf(),
+            cache_ttl_hours: 0,
+            network_retries: 0,
         };
 
         // Create single repo for owner
-        let repo_path = temp.path().join("github/owner/repo");
+        let repo_path = temp.path().join("github/github.com/owner/repo");
         fs::create_dir_all(&repo_path).unwrap();
 
-        manager.remove_cached("owner", "repo").unwrap();
+        manager
+            .remove_cached("github.com", "owner", "repo")
+            .unwrap();
 
         // Owner directory should be removed since it's now empty
-        assert!(!temp.path().join("github/owner").exists());
+        assert!(!temp.path().join("github/github.com/owner").exists());
     }
 
     #[test]
@@ -635,18 +1698,22 @@ mod tests {
         let temp = TempDir::new().unwrap();
         let manager = CacheManager {
             cache_dir: temp.path().to_path_buf(),
+            cache_ttl_hours: 0,
+            network_retries: 0,
         };
 
         // Create two repos for same owner
-        let repo1 = temp.path().join("github/owner/repo1");
-        let repo2 = temp.path().join("github/owner/repo2");
+        let repo1 = temp.path().join("github/github.com/owner/repo1");
+        let repo2 = temp.path().join("github/github.com/owner/repo2");
         fs::create_dir_all(&repo1).unwrap();
         fs::create_dir_all(&repo2).unwrap();
 
-        manager.remove_cached("owner", "repo1").unwrap();
+        manager
+            .remove_cached("github.com", "owner", "repo1")
+            .unwrap();
 
         // Owner directory should still exist with repo2
-        assert!(temp.path().join("github/owner").exists());
+        assert!(temp.path().join("github/github.com/owner").exists());
         assert!(repo2.exists());
     }
 
@@ -655,6 +1722,8 @@ mod tests {
         let temp = TempDir::new().unwrap();
         let manager = CacheManager {
             cache_dir: temp.path().to_path_buf(),
+            cache_ttl_hours: 0,
+            network_retries: 0,
         };
 
         // Create github directory with a file (not a directory)
@@ -663,7 +1732,7 @@ mod tests {
         fs::write(github_dir.join("some_file.txt"), "content").unwrap();
 
         // Create actual repo directory
-        let repo_path = temp.path().join("github/owner/repo");
+        let repo_path = temp.path().join("github/github.com/owner/repo");
         fs::create_dir_all(&repo_path).unwrap();
 
         let repos = manager.list_cached().unwrap();
@@ -677,6 +1746,8 @@ mod tests {
         let temp = TempDir::new().unwrap();
         let manager = CacheManager {
             cache_dir: temp.path().to_path_buf(),
+            cache_ttl_hours: 0,
+            network_retries: 0,
         };
 
         let source = GitHubSource::parse("https://github.com/owner/repo").unwrap();
@@ -691,10 +1762,12 @@ mod tests {
         let temp = TempDir::new().unwrap();
         let manager = CacheManager {
             cache_dir: temp.path().to_path_buf(),
+            cache_ttl_hours: 0,
+            network_retries: 0,
         };
 
         // Create a fake cached repo
-        let repo_path = temp.path().join("github/owner/repo");
+        let repo_path = temp.path().join("github/github.com/owner/repo");
         fs::create_dir_all(&repo_path).unwrap();
 
         // Initialize as git repo
@@ -731,10 +1804,12 @@ mod tests {
 
         // Parse as tag source - tags don't have "updates"
         let source = GitHubSource {
+            host: DEFAULT_GITHUB_HOST.to_string(),
             owner: "owner".to_string(),
             repo: "repo".to_string(),
             git_ref: GitRef::Tag("v1.0.0".to_string()),
             subpath: None,
+            single_file: false,
         };
 
         let result = manager.check_for_updates(&source).unwrap();
@@ -747,10 +1822,12 @@ mod tests {
         let temp = TempDir::new().unwrap();
         let manager = CacheManager {
             cache_dir: temp.path().to_path_buf(),
+            cache_ttl_hours: 0,
+            network_retries: 0,
         };
 
         // Create a fake cached repo
-        let repo_path = temp.path().join("github/owner/repo");
+        let repo_path = temp.path().join("github/github.com/owner/repo");
         fs::create_dir_all(&repo_path).unwrap();
 
         // Initialize as git repo
@@ -787,10 +1864,12 @@ mod tests {
 
         // Parse as commit source - commits don't have "updates"
         let source = GitHubSource {
+            host: DEFAULT_GITHUB_HOST.to_string(),
             owner: "owner".to_string(),
             repo: "repo".to_string(),
             git_ref: GitRef::Commit("abc123def456".to_string()),
             subpath: None,
+            single_file: false,
         };
 
         let result = manager.check_for_updates(&source).unwrap();
@@ -798,15 +1877,106 @@ mod tests {
         assert!(result.is_none());
     }
 
+    #[test]
+    fn test_ensure_cached_missing_subpath_lists_top_level_dirs() {
+        let temp = TempDir::new().unwrap();
+        let manager = CacheManager {
+            cache_dir: temp.path().to_path_buf(),
+            cache_ttl_hours: 0,
+            network_retries: 0,
+        };
+
+        // Create a fake cached repo with a tag to check out
+        let repo_path = temp.path().join("github/github.com/owner/repo");
+        fs::create_dir_all(repo_path.join("overlays/rust")).unwrap();
+        fs::create_dir_all(repo_path.join("docs")).unwrap();
+        fs::write(repo_path.join("overlays/rust/file.txt"), "content").unwrap();
+        fs::write(repo_path.join("docs/index.md"), "docs").unwrap();
+        for args in [
+            &["init"][..],
+            &["config", "user.email", "test@test.com"],
+            &["config", "user.name", "Test"],
+            &["add", "."],
+            &["commit", "-m", "initial"],
+            &["tag", "v1.0.0"],
+        ] {
+            std::process::Command::new("git")
+                .args(args)
+                .current_dir(&repo_path)
+                .output()
+                .unwrap();
+        }
+
+        let source = GitHubSource {
+            host: DEFAULT_GITHUB_HOST.to_string(),
+            owner: "owner".to_string(),
+            repo: "repo".to_string(),
+            git_ref: GitRef::Tag("v1.0.0".to_string()),
+            subpath: Some(PathBuf::from("overlays/rust")),
+            single_file: false,
+        };
+        let cached = manager.ensure_cached(&source, false).unwrap();
+        assert_eq!(cached.path, repo_path.join("overlays/rust"));
+
+        let source = source.with_subpath("overlays/go");
+        let err = manager
+            .ensure_cached(&source, false)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Subpath 'overlays/go' not found in repository owner/repo"));
+        assert!(err.contains("Available top-level directories: docs, overlays"));
+    }
+
+    #[test]
+    fn test_ref_kind_tells_tags_from_branches() {
+        let temp = TempDir::new().unwrap();
+        let manager = CacheManager {
+            cache_dir: temp.path().to_path_buf(),
+            cache_ttl_hours: 0,
+            network_retries: 0,
+        };
+
+        let repo_path = temp.path().join("repo");
+        fs::create_dir_all(&repo_path).unwrap();
+        fs::write(repo_path.join("file.txt"), "content").unwrap();
+        for args in [
+            &["init", "--initial-branch=main"][..],
+            &["config", "user.email", "test@test.com"],
+            &["config", "user.name", "Test"],
+            &["add", "."],
+            &["commit", "-m", "initial"],
+            &["tag", "v1.0.0"],
+            &["update-ref", "refs/remotes/origin/main", "HEAD"],
+        ] {
+            std::process::Command::new("git")
+                .args(args)
+                .current_dir(&repo_path)
+                .output()
+                .unwrap();
+        }
+
+        let kind = |git_ref: GitRef| manager.ref_kind(&repo_path, &git_ref);
+        // URLs parse tags as branches
+        assert_eq!(kind(GitRef::Branch("v1.0.0".to_string())), RefKind::Tag);
+        assert_eq!(kind(GitRef::Branch("main".to_string())), RefKind::Branch);
+        assert_eq!(kind(GitRef::Default), RefKind::Branch);
+        assert_eq!(
+            kind(GitRef::Commit("abc123def456".to_string())),
+            RefKind::Commit
+        );
+    }
+
     #[test]
     fn test_load_meta_returns_none_for_missing_file() {
         let temp = TempDir::new().unwrap();
         let manager = CacheManager {
             cache_dir: temp.path().to_path_buf(),
+            cache_ttl_hours: 0,
+            network_retries: 0,
         };
 
         // Create a repo directory without metadata
-        let repo_path = temp.path().join("github/owner/repo");
+        let repo_path = temp.path().join("github/github.com/owner/repo");
         fs::create_dir_all(&repo_path).unwrap();
 
         let meta = manager.load_meta(&repo_path);
@@ -818,10 +1988,12 @@ mod tests {
         let temp = TempDir::new().unwrap();
         let manager = CacheManager {
             cache_dir: temp.path().to_path_buf(),
+            cache_ttl_hours: 0,
+            network_retries: 0,
         };
 
         // Create a repo directory with invalid metadata
-        let repo_path = temp.path().join("github/owner/repo");
+        let repo_path = temp.path().join("github/github.com/owner/repo");
         fs::create_dir_all(&repo_path).unwrap();
         fs::write(
             repo_path.join(".repoverlay-cache-meta.ccl"),
@@ -838,22 +2010,26 @@ mod tests {
         let temp = TempDir::new().unwrap();
         let manager = CacheManager {
             cache_dir: temp.path().to_path_buf(),
+            cache_ttl_hours: 0,
+            network_retries: 0,
         };
 
         // Create a repo directory
-        let repo_path = temp.path().join("github/owner/repo");
+        let repo_path = temp.path().join("github/github.com/owner/repo");
         fs::create_dir_all(&repo_path).unwrap();
 
         let source = GitHubSource {
+            host: DEFAULT_GITHUB_HOST.to_string(),
             owner: "owner".to_string(),
             repo: "repo".to_string(),
             git_ref: GitRef::Branch("main".to_string()),
             subpath: None,
+            single_file: false,
         };
 
         // Save metadata
         manager
-            .save_meta(&repo_path, &source, "abc123def456")
+            .save_meta(&repo_path, &source, "abc123def456", true)
             .unwrap();
 
         // Load and verify
@@ -863,11 +2039,209 @@ mod tests {
         assert!(meta.clone_url.contains("github.com"));
     }
 
+    fn write_meta_fetched_at(repo_path: &Path, last_fetched: DateTime<Utc>) {
+        let meta = CacheMeta {
+            clone_url: "https://github.com/owner/repo.git".to_string(),
+            last_fetched,
+            requested_ref: "main".to_string(),
+            commit: "abc123".to_string(),
+            pins: Vec::new(),
+        };
+        fs::write(
+            repo_path.join(".repoverlay-cache-meta.ccl"),
+            sickle::to_string(&meta).unwrap(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_is_stale_respects_ttl() {
+        let temp = TempDir::new().unwrap();
+        let repo_path = temp.path().join("github/github.com/owner/repo");
+        fs::create_dir_all(&repo_path).unwrap();
+        let manager = CacheManager {
+            cache_dir: temp.path().to_path_buf(),
+            cache_ttl_hours: 24,
+            network_retries: 0,
+        };
+
+        write_meta_fetched_at(&repo_path, Utc::now() - chrono::Duration::hours(1));
+        assert!(!manager.is_stale(&repo_path));
+
+        write_meta_fetched_at(&repo_path, Utc::now() - chrono::Duration::hours(25));
+        assert!(manager.is_stale(&repo_path));
+    }
+
+    #[test]
+    fn test_is_stale_disabled_with_zero_ttl() {
+        let temp = TempDir::new().unwrap();
+        let repo_path = temp.path().join("github/github.com/owner/repo");
+        fs::create_dir_all(&repo_path).unwrap();
+        let manager = CacheManager {
+            cache_dir: temp.path().to_path_buf(),
+            cache_ttl_hours: 0,
+            network_retries: 0,
+        };
+
+        write_meta_fetched_at(&repo_path, Utc::now() - chrono::Duration::days(365));
+        assert!(!manager.is_stale(&repo_path));
+    }
+
+    #[test]
+    fn test_is_stale_without_meta() {
+        let temp = TempDir::new().unwrap();
+        let manager = CacheManager {
+            cache_dir: temp.path().to_path_buf(),
+            cache_ttl_hours: 1,
+            network_retries: 0,
+        };
+        assert!(manager.is_stale(temp.path()));
+    }
+
+    #[test]
+    fn test_repos_fetched_before() {
+        let temp = TempDir::new().unwrap();
+        let manager = CacheManager {
+            cache_dir: temp.path().to_path_buf(),
+            cache_ttl_hours: 0,
+            network_retries: 0,
+        };
+        for (repo, age) in [("old", 40), ("new", 1)] {
+            let repo_path = temp.path().join("github/github.com/owner").join(repo);
+            fs::create_dir_all(&repo_path).unwrap();
+            write_meta_fetched_at(&repo_path, Utc::now() - chrono::Duration::days(age));
+        }
+        // Without metadata the age is unknown
+        fs::create_dir_all(temp.path().join("github/github.com/owner/unknown")).unwrap();
+
+        let stale = manager
+            .repos_fetched_before(Utc::now() - chrono::Duration::days(30))
+            .unwrap();
+        let names: Vec<&str> = stale.iter().map(|info| info.repo.as_str()).collect();
+        assert_eq!(names, vec!["old"]);
+    }
+
+    #[test]
+    fn test_save_meta_keeps_last_fetched_without_fetch() {
+        let temp = TempDir::new().unwrap();
+        let repo_path = temp.path().join("github/github.com/owner/repo");
+        fs::create_dir_all(&repo_path).unwrap();
+        let manager = CacheManager {
+            cache_dir: temp.path().to_path_buf(),
+            cache_ttl_hours: 0,
+            network_retries: 0,
+        };
+        let source = GitHubSource {
+            host: DEFAULT_GITHUB_HOST.to_string(),
+            owner: "owner".to_string(),
+            repo: "repo".to_string(),
+            git_ref: GitRef::Branch("main".to_string()),
+            subpath: None,
+            single_file: false,
+        };
+
+        let fetched_at = Utc::now() - chrono::Duration::hours(48);
+        write_meta_fetched_at(&repo_path, fetched_at);
+
+        manager
+            .save_meta(&repo_path, &source, "def456", false)
+            .unwrap();
+        let meta = manager.load_meta(&repo_path).unwrap();
+        assert_eq!(meta.last_fetched, fetched_at);
+        assert_eq!(meta.commit, "def456");
+
+        manager
+            .save_meta(&repo_path, &source, "def456", true)
+            .unwrap();
+        let meta = manager.load_meta(&repo_path).unwrap();
+        assert!(meta.last_fetched > fetched_at);
+    }
+
+    #[test]
+    fn test_cached_repo_lookup() {
+        let temp = TempDir::new().unwrap();
+        let manager = CacheManager {
+            cache_dir: temp.path().to_path_buf(),
+            cache_ttl_hours: 0,
+            network_retries: 0,
+        };
+        let repo_path = temp.path().join("github/github.com/owner/repo");
+        fs::create_dir_all(&repo_path).unwrap();
+        write_meta_fetched_at(&repo_path, Utc::now());
+
+        let info = manager.cached_repo("github.com", "owner", "repo").unwrap();
+        assert_eq!(info.path, repo_path);
+        assert_eq!(info.meta.unwrap().requested_ref, "main");
+        assert!(
+            manager
+                .cached_repo("github.com", "owner", "missing")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_dir_size_sums_files() {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join("a.txt"), "hello").unwrap();
+        fs::create_dir_all(temp.path().join("sub")).unwrap();
+        fs::write(temp.path().join("sub/b.txt"), "world!").unwrap();
+
+        assert_eq!(dir_size(temp.path()), 11);
+    }
+
+    #[test]
+    fn test_cached_refs_lists_branches_and_tags() {
+        let temp = TempDir::new().unwrap();
+        let manager = CacheManager {
+            cache_dir: temp.path().to_path_buf(),
+            cache_ttl_hours: 0,
+            network_retries: 0,
+        };
+        let repo = temp.path();
+        let git = |args: &[&str]| {
+            assert!(
+                Command::new("git")
+                    .args(args)
+                    .current_dir(repo)
+                    .output()
+                    .unwrap()
+                    .status
+                    .success()
+            );
+        };
+        git(&["init", "-q"]);
+        git(&[
+            "-c",
+            "user.name=Test",
+            "-c",
+            "user.email=test@example.com",
+            "commit",
+            "-q",
+            "--allow-empty",
+            "-m",
+            "init",
+        ]);
+        git(&["update-ref", "refs/remotes/origin/main", "HEAD"]);
+        git(&["update-ref", "refs/remotes/origin/dev", "HEAD"]);
+        git(&[
+            "symbolic-ref",
+            "refs/remotes/origin/HEAD",
+            "refs/remotes/origin/main",
+        ]);
+        git(&["tag", "v1.0"]);
+
+        let mut refs = manager.cached_refs(repo);
+        refs.sort();
+        assert_eq!(refs, vec!["dev", "main", "v1.0"]);
+    }
+
     #[test]
     fn test_get_current_commit_fails_on_non_git_dir() {
         let temp = TempDir::new().unwrap();
         let manager = CacheManager {
             cache_dir: temp.path().to_path_buf(),
+            cache_ttl_hours: 0,
+            network_retries: 0,
         };
 
         // Create a directory that is not a git repo
@@ -883,6 +2257,8 @@ mod tests {
         let temp = TempDir::new().unwrap();
         let manager = CacheManager {
             cache_dir: temp.path().to_path_buf(),
+            cache_ttl_hours: 0,
+            network_retries: 0,
         };
 
         // Create a git repo with a commit
@@ -924,11 +2300,110 @@ mod tests {
         assert_eq!(commit.len(), 40); // SHA-1 hash is 40 hex chars
     }
 
+    #[test]
+    fn test_parse_ls_remote_refs() {
+        let output = "abc\tHEAD\n\
+                      abc\trefs/heads/main\n\
+                      def\trefs/heads/release/2024-q3\n\
+                      123\trefs/tags/v1.0\n\
+                      456\trefs/tags/v1.0^{}\n";
+        assert_eq!(
+            parse_ls_remote_refs(output),
+            vec!["main", "release/2024-q3", "v1.0"]
+        );
+    }
+
+    #[test]
+    fn test_clone_and_checkout_branch_with_slash() {
+        let temp = TempDir::new().unwrap();
+        let manager = CacheManager {
+            cache_dir: temp.path().to_path_buf(),
+            cache_ttl_hours: 0,
+            network_retries: 0,
+        };
+        let git = |dir: &Path, args: &[&str]| {
+            let output = Command::new("git")
+                .args(args)
+                .current_dir(dir)
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "git {args:?} failed");
+        };
+
+        // Upstream repo with the overlay only on a slashed branch
+        let upstream = temp.path().join("upstream");
+        fs::create_dir_all(&upstream).unwrap();
+        git(&upstream, &["init", "-b", "main"]);
+        fs::write(upstream.join("README.md"), "main").unwrap();
+        git(&upstream, &["add", "."]);
+        git(
+            &upstream,
+            &[
+                "-c",
+                "user.name=Test",
+                "-c",
+                "user.email=t@t.com",
+                "commit",
+                "-m",
+                "init",
+            ],
+        );
+        git(&upstream, &["checkout", "-b", "release/2024-q3"]);
+        fs::write(upstream.join(".envrc"), "release").unwrap();
+        git(&upstream, &["add", "."]);
+        git(
+            &upstream,
+            &[
+                "-c",
+                "user.name=Test",
+                "-c",
+                "user.email=t@t.com",
+                "commit",
+                "-m",
+                "release",
+            ],
+        );
+        git(&upstream, &["checkout", "main"]);
+
+        // Clone the way clone_repo does, then check out the ref
+        let repo_path = temp.path().join("github/github.com/owner/repo");
+        fs::create_dir_all(repo_path.parent().unwrap()).unwrap();
+        git(
+            temp.path(),
+            &[
+                "clone",
+                "--depth",
+                "1",
+                "--branch",
+                "release/2024-q3",
+                &format!("file://{}", upstream.display()),
+                repo_path.to_str().unwrap(),
+            ],
+        );
+        let source = GitHubSource::parse("https://github.com/owner/repo")
+            .unwrap()
+            .with_ref_override(Some("release/2024-q3"));
+
+        manager.checkout_ref(&repo_path, &source).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(repo_path.join(".envrc")).unwrap(),
+            "release"
+        );
+        assert!(
+            manager
+                .ref_exists(&repo_path, "origin/release/2024-q3")
+                .unwrap()
+        );
+    }
+
     #[test]
     fn test_ref_exists_returns_true_for_existing_ref() {
         let temp = TempDir::new().unwrap();
         let manager = CacheManager {
             cache_dir: temp.path().to_path_buf(),
+            cache_ttl_hours: 0,
+            network_retries: 0,
         };
 
         // Create a git repo with a commit
@@ -974,6 +2449,8 @@ mod tests {
         let temp = TempDir::new().unwrap();
         let manager = CacheManager {
             cache_dir: temp.path().to_path_buf(),
+            cache_ttl_hours: 0,
+            network_retries: 0,
         };
 
         // Create a git repo with a commit
@@ -1016,20 +2493,121 @@ mod tests {
         assert!(!result);
     }
 
+    #[test]
+    fn test_archive_path_is_keyed_by_url() {
+        let temp = TempDir::new().unwrap();
+        let manager = CacheManager {
+            cache_dir: temp.path().to_path_buf(),
+            cache_ttl_hours: 0,
+            network_retries: 0,
+        };
+
+        let a = ArchiveSource::parse("https://example.com/a.tar.gz").unwrap();
+        let b = ArchiveSource::parse("https://example.com/b.tar.gz").unwrap();
+
+        assert!(
+            manager
+                .archive_path(&a)
+                .starts_with(temp.path().join("archives"))
+        );
+        assert_ne!(manager.archive_path(&a), manager.archive_path(&b));
+        assert_eq!(manager.archive_path(&a), manager.archive_path(&a));
+    }
+
+    #[test]
+    fn test_ensure_archive_cached_uses_existing_contents() {
+        let temp = TempDir::new().unwrap();
+        let manager = CacheManager {
+            cache_dir: temp.path().to_path_buf(),
+            cache_ttl_hours: 0,
+            network_retries: 0,
+        };
+        let source = ArchiveSource::parse("https://example.com/configs.tar.gz").unwrap();
+
+        // Pre-populate the cache so no download is needed
+        let archive_dir = manager.archive_path(&source);
+        fs::create_dir_all(archive_dir.join("contents/configs")).unwrap();
+        fs::write(archive_dir.join("contents/configs/.envrc"), "export A=1").unwrap();
+        let meta = ArchiveCacheMeta {
+            url: source.url.clone(),
+            last_fetched: Utc::now(),
+            sha256: "abc123".to_string(),
+        };
+        fs::write(
+            archive_dir.join(".repoverlay-cache-meta.ccl"),
+            sickle::to_string(&meta).unwrap(),
+        )
+        .unwrap();
+
+        let cached = manager.ensure_archive_cached(&source, false).unwrap();
+        assert_eq!(cached.sha256, "abc123");
+        assert_eq!(cached.path, archive_dir.join("contents/configs"));
+    }
+
+    #[test]
+    fn test_extract_tar_gz_archive() {
+        let temp = TempDir::new().unwrap();
+        let src = temp.path().join("src");
+        fs::create_dir_all(src.join("overlay")).unwrap();
+        fs::write(src.join("overlay/.envrc"), "export A=1").unwrap();
+
+        let archive = temp.path().join("overlay.tar.gz");
+        let status = Command::new("tar")
+            .arg("-czf")
+            .arg(&archive)
+            .arg("-C")
+            .arg(&src)
+            .arg("overlay")
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let dest = temp.path().join("contents");
+        extract_archive(ArchiveKind::TarGz, &archive, &dest).unwrap();
+
+        let root = archive_root(&dest).unwrap();
+        assert_eq!(root, dest.join("overlay"));
+        assert_eq!(
+            fs::read_to_string(root.join(".envrc")).unwrap(),
+            "export A=1"
+        );
+    }
+
+    #[test]
+    fn test_archive_root_without_wrapper_directory() {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join(".envrc"), "a").unwrap();
+        fs::write(temp.path().join(".env"), "b").unwrap();
+
+        assert_eq!(archive_root(temp.path()).unwrap(), temp.path());
+    }
+
+    #[test]
+    fn test_extract_archive_fails_on_invalid_file() {
+        let temp = TempDir::new().unwrap();
+        let archive = temp.path().join("bad.tar.gz");
+        fs::write(&archive, "not an archive").unwrap();
+
+        let result = extract_archive(ArchiveKind::TarGz, &archive, &temp.path().join("out"));
+        assert!(result.is_err());
+    }
+
     #[test]
     fn test_list_cached_skips_files_in_owner_directory() {
         let temp = TempDir::new().unwrap();
         let manager = CacheManager {
             cache_dir: temp.path().to_path_buf(),
+            cache_ttl_hours: 0,
+            network_retries: 0,
         };
 
         // Create owner directory with a file instead of repo dir
-        let owner_dir = temp.path().join("github/owner");
+        let owner_dir = temp.path().join("github/github.com/owner");
         fs::create_dir_all(&owner_dir).unwrap();
         fs::write(owner_dir.join("not-a-repo.txt"), "content").unwrap();
 
         // Also create a real repo
-        let repo_path = temp.path().join("github/owner/real-repo");
+        let repo_path = temp.path().join("github/github.com/owner/real-repo");
         fs::create_dir_all(&repo_path).unwrap();
 
         let repos = manager.list_cached().unwrap();
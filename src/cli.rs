@@ -2,16 +2,27 @@
 
 use anyhow::{Context, Result, bail};
 use clap::{CommandFactory, Parser, Subcommand};
-use colored::Colorize;
+use colored::{ColoredString, Colorize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::io::{self, Write};
+use std::io::{self, IsTerminal, Write};
 use std::path::PathBuf;
 use std::sync::LazyLock;
 
+use crate::cache::dir_size;
+use crate::completions::{self, Shell};
+use crate::export::{export_overlays, import_overlays};
+use crate::fetch::DEFAULT_JOBS;
+use crate::git_hooks::{install_hooks, uninstall_hooks};
+use crate::output::status;
+use crate::overlay_repo::AvailableOverlay;
 use crate::{
-    CONFIG_FILE, CacheManager, OVERLAYS_DIR, STATE_DIR, apply_overlay, canonicalize_path, config,
-    list_applied_overlays, parse_github_owner_repo, remove_overlay, remove_single_overlay,
-    restore_overlays, show_status, switch_overlay, update_overlays,
+    ApplyOptions, CONFIG_FILE, CacheManager, OVERLAYS_DIR, OnConflict, STATE_DIR, apply_bundle,
+    apply_overlay, bootstrap_overlays, canonicalize_path, config, diff_overlays, edit_overlay_file,
+    forget_removed_overlay, list_applied_overlays, move_overlay_file, parse_github_owner_repo,
+    prune, remove_overlay, remove_single_overlay, repair_overlays, restore_overlays,
+    show_overlay_info, show_status, source_with_subpath, switch_overlay, unlink_file,
+    update_overlays, verify_overlays, which_overlay,
 };
 
 /// Build version string with git info for local builds
@@ -47,19 +58,77 @@ struct Cli {
     /// Print help in markdown format (for documentation generation)
     #[arg(long, hide = true)]
     markdown_help: bool,
+
+    /// When to use colored output
+    #[arg(long, global = true, value_enum, default_value_t = ColorChoice::Auto)]
+    color: ColorChoice,
+
+    /// Only print warnings and errors
+    #[arg(short, long, global = true)]
+    quiet: bool,
+
+    /// Show debug logs on stderr (-vv for trace logs)
+    #[arg(short = 'v', action = clap::ArgAction::Count, global = true)]
+    verbosity: u8,
+
+    /// Also write logs to this file
+    #[arg(long, global = true, value_name = "PATH")]
+    log_file: Option<PathBuf>,
+
+    /// Use cached sources only, without network access (or set `REPOVERLAY_OFFLINE=1`)
+    #[arg(long, global = true)]
+    offline: bool,
+}
+
+/// Values for the global `--color` flag.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+enum ColorChoice {
+    /// Color when stdout is a terminal, honoring `NO_COLOR` and `CLICOLOR_FORCE`
+    #[default]
+    Auto,
+    /// Always color
+    Always,
+    /// Never color
+    Never,
+}
+
+impl ColorChoice {
+    /// Decide whether to color output.
+    ///
+    /// An explicit `always`/`never` wins. Otherwise a non-empty `NO_COLOR`
+    /// disables color, a `CLICOLOR_FORCE` other than `0` forces it, and
+    /// color is used only when stdout is a terminal.
+    fn should_colorize(
+        self,
+        no_color: Option<&str>,
+        clicolor_force: Option<&str>,
+        stdout_is_tty: bool,
+    ) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Never => false,
+            Self::Auto if no_color.is_some_and(|v| !v.is_empty()) => false,
+            Self::Auto if clicolor_force.is_some_and(|v| !v.is_empty() && v != "0") => true,
+            Self::Auto => stdout_is_tty,
+        }
+    }
 }
 
 #[derive(Subcommand)]
 enum Commands {
     /// Apply an overlay to a git repository
     Apply {
-        /// Path to overlay source directory OR GitHub URL
+        /// Path to overlay source directory, GitHub URL, or archive URL
+        ///
+        /// Multiple sources are applied in order.
         ///
         /// Examples:
         ///   ./my-overlay
         ///   <https://github.com/owner/repo>
         ///   <https://github.com/owner/repo/tree/main/overlays/rust>
-        source: String,
+        ///   <https://example.com/configs.tar.gz>
+        #[arg(required = true, value_name = "SOURCE")]
+        source: Vec<String>,
 
         /// Target repository directory (defaults to current directory)
         #[arg(short, long)]
@@ -81,13 +150,87 @@ enum Commands {
         #[arg(long, help_heading = "GitHub Options")]
         update: bool,
 
+        /// Directory inside the repository to apply, instead of a `/tree/` URL (GitHub sources only)
+        #[arg(long, value_name = "DIR", help_heading = "GitHub Options")]
+        subpath: Option<String>,
+
         /// Use a specific overlay source instead of priority order (multi-source configs only)
         #[arg(long = "from", value_name = "SOURCE", help_heading = "GitHub Options")]
         from_source: Option<String>,
 
-        /// Show what would be applied without making changes
+        /// Show the files that would be applied and any conflicts, without making changes
+        #[arg(long, visible_alias = "preview")]
+        dry_run: bool,
+
+        /// What to do when an overlay file already exists in the repo
+        #[arg(long, value_enum, default_value_t = OnConflict::Fail)]
+        on_conflict: OnConflict,
+
+        /// Replace conflicting repo files, backing them up until the overlay is removed
+        /// (same as --on-conflict backup)
+        #[arg(short, long, conflicts_with = "on_conflict")]
+        force: bool,
+
+        /// List every applied file, even for large overlays
+        #[arg(long)]
+        verbose: bool,
+
+        /// Set a template variable, overriding the overlay's `variables` (can be repeated)
+        #[arg(long = "set", value_name = "KEY=VALUE", value_parser = crate::template::parse_variable)]
+        set: Vec<(String, String)>,
+
+        /// Only apply overlay files matching a glob, e.g. `.vscode/*` (can be repeated)
+        ///
+        /// Matched against each file's path in the overlay and its target
+        /// path; a pattern matching a directory includes everything in it.
+        #[arg(long, value_name = "PATTERN")]
+        only: Vec<String>,
+
+        /// Don't apply the overlays listed in the overlay's `requires`
+        #[arg(long)]
+        no_deps: bool,
+
+        /// Run the overlay's `post_apply` hook after applying
+        #[arg(long)]
+        run_hooks: bool,
+
+        /// Place overlay files under this directory of the target, e.g. `packages/foo`
+        #[arg(long, value_name = "DIR")]
+        prefix: Option<PathBuf>,
+    },
+
+    /// Apply every overlay in a bundle defined by the overlay repository
+    ///
+    /// Bundles are named groups of `org/repo/name` overlays listed in
+    /// `bundles.ccl` at the overlay repository root.
+    ApplyBundle {
+        /// Name of the bundle to apply
+        bundle: String,
+
+        /// Target repository directory (defaults to current directory)
+        #[arg(short, long)]
+        target: Option<PathBuf>,
+
+        /// Force copy mode instead of symlinks (default on Windows)
+        #[arg(long)]
+        copy: bool,
+
+        /// Update the overlay repository before applying
         #[arg(long)]
+        update: bool,
+
+        /// Show the files that would be applied and any conflicts, without making changes
+        #[arg(long, visible_alias = "preview")]
         dry_run: bool,
+
+        /// What to do when an overlay file already exists in the repo
+        #[arg(long, value_enum, default_value_t = OnConflict::Fail)]
+        on_conflict: OnConflict,
+
+        /// Replace conflicting repo files, backing them up until the overlay is removed
+        /// (same as --on-conflict backup)
+        #[arg(short, long, conflicts_with = "on_conflict")]
+        force: bool,
     },
 
     /// Remove applied overlay(s)
@@ -110,6 +253,85 @@ enum Commands {
         /// Interactive selection mode
         #[arg(short, long)]
         interactive: bool,
+
+        /// Run each overlay's `pre_remove` hook before removing it
+        #[arg(long)]
+        run_hooks: bool,
+    },
+
+    /// Stop managing a file without removing it
+    ///
+    /// Replaces the overlay symlink with a real copy of the file and drops it
+    /// from the overlay. Removes the overlay if it was its last file.
+    Unlink {
+        /// Name of the applied overlay
+        overlay: String,
+
+        /// File to stop managing, relative to the target
+        file: PathBuf,
+
+        /// Unlink even if the overlay replaced a repo file, discarding the
+        /// repo's original
+        #[arg(short, long)]
+        force: bool,
+
+        /// Target repository directory (defaults to current directory)
+        #[arg(short, long)]
+        target: Option<PathBuf>,
+    },
+
+    /// Open the overlay source of an applied file in your editor
+    ///
+    /// Uses $VISUAL or $EDITOR. For symlinked overlays this edits the content
+    /// the repo sees; copied files change only when the overlay is re-applied.
+    ///
+    /// Examples:
+    ///   repoverlay edit my-overlay .envrc
+    Edit {
+        /// Name of the applied overlay
+        overlay: String,
+
+        /// File to edit, relative to the target
+        file: PathBuf,
+
+        /// Target repository directory (defaults to current directory)
+        #[arg(short, long)]
+        target: Option<PathBuf>,
+    },
+
+    /// Move a file of an applied overlay to another path in the repo
+    ///
+    /// The move is remembered, so restore and update keep the file at its new
+    /// path and sync still writes it back to its original path in the overlay.
+    ///
+    /// Examples:
+    ///   repoverlay mv my-overlay .envrc config/.envrc
+    Mv {
+        /// Name of the applied overlay
+        overlay: String,
+
+        /// File to move, relative to the target
+        from: PathBuf,
+
+        /// New path for the file, relative to the target
+        to: PathBuf,
+
+        /// Target repository directory (defaults to current directory)
+        #[arg(short, long)]
+        target: Option<PathBuf>,
+    },
+
+    /// Show which applied overlay manages a file
+    ///
+    /// Examples:
+    ///   repoverlay which .claude/settings.json
+    Which {
+        /// File to look up, relative to the target
+        file: PathBuf,
+
+        /// Target repository directory (defaults to current directory)
+        #[arg(short, long)]
+        target: Option<PathBuf>,
     },
 
     /// Show the status of applied overlays
@@ -121,6 +343,119 @@ enum Commands {
         /// Show only a specific overlay
         #[arg(short, long)]
         name: Option<String>,
+
+        /// Print status as a JSON array instead of human-readable output
+        #[arg(long)]
+        json: bool,
+
+        /// Re-hash copied files and report any modified since apply
+        #[arg(long)]
+        check_drift: bool,
+
+        /// Check remote sources for newer versions (requires network access)
+        #[arg(long)]
+        verify_remote: bool,
+
+        /// Show only overlays with missing or diverged files, exiting non-zero if there are any
+        #[arg(long)]
+        broken: bool,
+    },
+
+    /// Show differences between applied overlay files and their source
+    ///
+    /// Exits with a non-zero status if any file differs.
+    Diff {
+        /// Name of the overlay to diff (diffs all applied overlays if not specified)
+        name: Option<String>,
+
+        /// Target repository directory (defaults to current directory)
+        #[arg(short, long)]
+        target: Option<PathBuf>,
+    },
+
+    /// Recreate broken symlinks after a cache or overlay source has moved
+    Repair {
+        /// Name of the overlay to repair (repairs all applied overlays if not specified)
+        name: Option<String>,
+
+        /// Target repository directory (defaults to current directory)
+        #[arg(short, long)]
+        target: Option<PathBuf>,
+    },
+
+    /// Check that applied overlays are intact
+    ///
+    /// Verifies that every overlay file exists, symlinks point at their source,
+    /// and git exclude entries are present. Exits with a non-zero status on any
+    /// problem, for use in CI or pre-commit hooks.
+    Verify {
+        /// Target repository directory (defaults to current directory)
+        #[arg(short, long)]
+        target: Option<PathBuf>,
+    },
+
+    /// Check the environment for common setup problems
+    ///
+    /// Checks that git is installed, symlinks can be created, the overlay
+    /// repository is reachable, the cache directory is writable, and a GitHub
+    /// token is available, with a hint for each problem found.
+    Doctor,
+
+    /// Clean up orphaned overlay state and stale git exclude entries
+    ///
+    /// Removes the state of overlays whose files have all been deleted by
+    /// hand, and git exclude sections of overlays that are no longer applied.
+    Prune {
+        /// Target repository directory (defaults to current directory)
+        #[arg(short, long)]
+        target: Option<PathBuf>,
+
+        /// Show what would be removed without removing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Export applied overlays to a portable bundle
+    ///
+    /// Writes a .tar.gz with each overlay's state and its files as they
+    /// appear in the repository, with symlinks dereferenced. The bundle does
+    /// not depend on the original sources.
+    Export {
+        /// Path of the bundle to write (e.g. overlays.tar.gz)
+        output: PathBuf,
+
+        /// Target repository directory (defaults to current directory)
+        #[arg(short, long)]
+        target: Option<PathBuf>,
+    },
+
+    /// Apply the overlays in a bundle written by `export`
+    ///
+    /// The bundled files are unpacked into repoverlay's data directory and
+    /// applied as local overlays, so no access to the original sources is
+    /// needed.
+    Import {
+        /// Path of the bundle to read
+        archive: PathBuf,
+
+        /// Target repository directory (defaults to current directory)
+        #[arg(short, long)]
+        target: Option<PathBuf>,
+
+        /// Force copy mode instead of symlinks (default on Windows)
+        #[arg(long)]
+        copy: bool,
+    },
+
+    /// Apply overlays from the overlay repository that match this repo's origin
+    Bootstrap {
+        /// Target repository directory (defaults to current directory)
+        #[arg(short, long)]
+        target: Option<PathBuf>,
+
+        /// Apply all matching overlays without prompting
+        #[arg(short, long)]
+        yes: bool,
     },
 
     /// Restore overlays after git clean or other removal
@@ -132,6 +467,33 @@ enum Commands {
         /// Show what would be restored without applying
         #[arg(long)]
         dry_run: bool,
+
+        /// Maximum number of repositories to fetch concurrently
+        #[arg(short, long, default_value_t = DEFAULT_JOBS)]
+        jobs: usize,
+
+        /// Also restore overlays that were removed with `repoverlay remove`
+        #[arg(long)]
+        include_removed: bool,
+
+        /// Only restore these overlays (comma-separated or repeated)
+        #[arg(long, value_name = "NAMES", value_delimiter = ',')]
+        only: Vec<String>,
+
+        /// Permanently forget a removed overlay instead of restoring
+        #[arg(long, value_name = "NAME", conflicts_with_all = ["dry_run", "include_removed", "only"])]
+        forget: Option<String>,
+    },
+
+    /// Install git hooks that restore overlays after checkout and merge
+    InstallHook {
+        /// Target repository directory (defaults to current directory)
+        #[arg(short, long)]
+        target: Option<PathBuf>,
+
+        /// Remove the repoverlay lines from the hooks instead
+        #[arg(long)]
+        uninstall: bool,
     },
 
     /// Update applied overlays from remote sources
@@ -143,9 +505,42 @@ enum Commands {
         #[arg(short, long)]
         target: Option<PathBuf>,
 
+        /// Update every applied overlay (the default without a name)
+        #[arg(long, conflicts_with = "name")]
+        all: bool,
+
         /// Check for updates without applying them
         #[arg(long)]
         dry_run: bool,
+
+        /// Apply the updates found (the default without --dry-run)
+        #[arg(long, conflicts_with = "dry_run")]
+        apply: bool,
+
+        /// Also update overlays pinned to a tag or commit
+        #[arg(short, long)]
+        force: bool,
+
+        /// Maximum number of repositories to fetch concurrently
+        #[arg(short, long, default_value_t = DEFAULT_JOBS)]
+        jobs: usize,
+    },
+
+    /// Create a new, empty overlay repository
+    ///
+    /// Sets up a git repository with a README explaining the `org/repo/name`
+    /// layout and commits it.
+    ///
+    /// Examples:
+    ///   repoverlay init ~/src/repo-overlays
+    ///   repoverlay init ./overlays --bundles
+    Init {
+        /// Directory to create the overlay repository in (must be new or empty)
+        path: PathBuf,
+
+        /// Also add a `bundles.ccl` for named groups of overlays
+        #[arg(long)]
+        bundles: bool,
     },
 
     /// Create a new overlay from files in a repository
@@ -153,6 +548,7 @@ enum Commands {
     /// Examples:
     ///   repoverlay create my-overlay          # Detects org/repo from git remote
     ///   repoverlay create org/repo/my-overlay # Explicit target
+    ///   repoverlay create my-overlay --target org/repo
     Create {
         /// Overlay name or full path (org/repo/name)
         ///
@@ -160,10 +556,19 @@ enum Commands {
         /// Full form: `org/repo/name` - uses explicit target
         name: String,
 
-        /// Include specific files or directories (can be specified multiple times)
+        /// Target repository (format: org/repo) for a short overlay name,
+        /// instead of detecting it from the git remote
+        #[arg(short, long)]
+        target: Option<String>,
+
+        /// Include files, directories, or glob patterns (can be specified multiple times)
         #[arg(short, long)]
         include: Vec<PathBuf>,
 
+        /// Read more includes from a file, one per line (`#` starts a comment)
+        #[arg(long, value_name = "FILE")]
+        include_from: Option<PathBuf>,
+
         /// Source repository to extract files from (defaults to current directory)
         #[arg(short, long)]
         source: Option<PathBuf>,
@@ -179,6 +584,34 @@ enum Commands {
         /// Force overwrite if overlay already exists
         #[arg(short, long)]
         force: bool,
+
+        /// Skip scanning files for likely secrets (API keys, private keys, passwords)
+        #[arg(long)]
+        no_secrets_check: bool,
+
+        /// Don't preselect the files chosen the last time
+        #[arg(long)]
+        fresh: bool,
+
+        /// Copy the files symlinks point to instead of recreating the symlinks
+        #[arg(long)]
+        dereference: bool,
+
+        /// Use a specific overlay source instead of the highest-priority one (multi-source configs only)
+        #[arg(long = "from", value_name = "SOURCE")]
+        from_source: Option<String>,
+
+        /// Push to a new branch and open a pull request instead of pushing to the current branch
+        #[arg(long)]
+        pr: bool,
+
+        /// Commit message (overrides the `commit.message_template` setting)
+        #[arg(short, long)]
+        message: Option<String>,
+
+        /// Commit without pulling the overlay repo first or pushing after
+        #[arg(long, conflicts_with = "pr")]
+        no_fetch: bool,
     },
 
     /// Create a new overlay in a local directory
@@ -190,7 +623,7 @@ enum Commands {
         /// Output directory for the overlay
         output: PathBuf,
 
-        /// Include specific files or directories (can be specified multiple times)
+        /// Include files, directories, or glob patterns (can be specified multiple times)
         #[arg(short, long)]
         include: Vec<PathBuf>,
 
@@ -209,6 +642,18 @@ enum Commands {
         /// Force overwrite if output already exists
         #[arg(short, long)]
         force: bool,
+
+        /// Skip scanning files for likely secrets (API keys, private keys, passwords)
+        #[arg(long)]
+        no_secrets_check: bool,
+
+        /// Don't preselect the files chosen the last time
+        #[arg(long)]
+        fresh: bool,
+
+        /// Copy the files symlinks point to instead of recreating the symlinks
+        #[arg(long)]
+        dereference: bool,
     },
 
     /// Switch to a different overlay (removes all existing overlays first)
@@ -231,6 +676,14 @@ enum Commands {
         /// Git ref (branch, tag, or commit) to use (GitHub sources only)
         #[arg(short, long, value_name = "REF")]
         r#ref: Option<String>,
+
+        /// Directory inside the repository to apply (GitHub sources only)
+        #[arg(long, value_name = "DIR")]
+        subpath: Option<String>,
+
+        /// Show which overlays would be removed and applied without making changes
+        #[arg(long)]
+        dry_run: bool,
     },
 
     /// Manage the overlay cache
@@ -248,19 +701,72 @@ enum Commands {
         /// Update overlay repo before listing
         #[arg(long)]
         update: bool,
+
+        /// Only show overlays with this tag
+        #[arg(long)]
+        tag: Option<String>,
+
+        /// Fuzzy search overlay names, target repos, and descriptions
+        #[arg(short, long)]
+        search: Option<String>,
+
+        /// Use a specific overlay source instead of the highest-priority one (multi-source configs only)
+        #[arg(long = "from", value_name = "SOURCE")]
+        from_source: Option<String>,
+
+        /// Print the overlays as a JSON array instead of human-readable output
+        #[arg(long)]
+        json: bool,
+
+        /// List the overlays applied to a repository (defaults to current directory) instead
+        #[arg(
+            long,
+            value_name = "DIR",
+            num_args = 0..=1,
+            default_missing_value = ".",
+            conflicts_with_all = ["filter", "update", "tag", "search", "from_source", "json"]
+        )]
+        applied: Option<PathBuf>,
+    },
+
+    /// Show what an overlay contains without applying it
+    ///
+    /// Examples:
+    ///   repoverlay info org/repo/my-overlay
+    ///   repoverlay info <https://github.com/owner/repo>
+    Info {
+        /// Path to overlay source directory, GitHub URL, archive URL, or org/repo/name
+        source: String,
+
+        /// Git ref (branch, tag, or commit) to use (GitHub sources only)
+        #[arg(short, long, value_name = "REF", help_heading = "GitHub Options")]
+        r#ref: Option<String>,
+
+        /// Use a specific overlay source instead of priority order (multi-source configs only)
+        #[arg(long = "from", value_name = "SOURCE", help_heading = "GitHub Options")]
+        from_source: Option<String>,
     },
 
     /// Sync changes from an applied overlay back to the overlay repo
     ///
+    /// Files deleted from the repo are deleted from the overlay too, after
+    /// confirmation.
+    ///
     /// Examples:
     ///   repoverlay sync my-overlay          # Detects org/repo from git remote
     ///   repoverlay sync org/repo/my-overlay # Explicit target
+    ///   repoverlay sync --all               # Every overlay from the overlay repo
     Sync {
         /// Overlay name or full path (org/repo/name)
         ///
         /// Short form: `my-overlay` - detects org/repo from git remote
         /// Full form: `org/repo/name` - uses explicit values
-        name: String,
+        #[arg(required_unless_present = "all", conflicts_with = "all")]
+        name: Option<String>,
+
+        /// Sync every applied overlay from the overlay repo
+        #[arg(long)]
+        all: bool,
 
         /// Target repository directory (defaults to current directory)
         #[arg(short, long)]
@@ -269,6 +775,22 @@ enum Commands {
         /// Show what would be synced without making changes
         #[arg(long)]
         dry_run: bool,
+
+        /// Delete files removed from the repo without prompting
+        #[arg(short, long)]
+        yes: bool,
+
+        /// Overlay source to sync to (defaults to the one the overlay was applied from)
+        #[arg(long = "from", value_name = "SOURCE")]
+        from_source: Option<String>,
+
+        /// Commit message (overrides the `commit.message_template` setting)
+        #[arg(short, long)]
+        message: Option<String>,
+
+        /// Commit without pulling the overlay repo first or pushing after
+        #[arg(long)]
+        no_fetch: bool,
     },
 
     /// Add files to an existing applied overlay
@@ -277,6 +799,8 @@ enum Commands {
     ///   repoverlay add my-overlay newfile.txt
     ///   repoverlay add my-overlay file1.txt file2.txt
     ///   repoverlay add org/repo/my-overlay path/to/file.txt
+    ///   repoverlay add my-overlay .github/
+    ///   repoverlay add my-overlay '.github/workflows/*.yml'
     Add {
         /// Overlay name or full path (org/repo/name)
         ///
@@ -284,7 +808,7 @@ enum Commands {
         /// Full form: `org/repo/name` - uses explicit values
         name: String,
 
-        /// Files to add (relative paths from target repo)
+        /// Files, directories, or glob patterns to add (relative paths from target repo)
         files: Vec<PathBuf>,
 
         /// Target repository directory (defaults to current directory)
@@ -294,16 +818,73 @@ enum Commands {
         /// Show what would be added without making changes
         #[arg(long)]
         dry_run: bool,
-    },
 
-    /// Publish an overlay to the overlay repository
-    #[command(hide = true)] // Hidden: deprecated, use create instead
-    Publish {
-        /// Path to the overlay source directory
-        source: PathBuf,
-
-        /// Target repository (format: org/repo)
-        /// Auto-detected from current git remote if not specified
+        /// Commit message (overrides the `commit.message_template` setting)
+        #[arg(short, long)]
+        message: Option<String>,
+
+        /// Commit without pulling the overlay repo first or pushing after
+        #[arg(long)]
+        no_fetch: bool,
+    },
+
+    /// Delete an overlay from the overlay repository
+    ///
+    /// The deletion is committed and pushed. Repositories that have the
+    /// overlay applied with copies keep their files. Symlinked files would
+    /// break, so deleting an overlay applied with symlinks requires --force.
+    ///
+    /// Examples:
+    ///   repoverlay delete my-overlay          # Detects org/repo from git remote
+    ///   repoverlay delete org/repo/my-overlay # Explicit target
+    Delete {
+        /// Overlay name or full path (org/repo/name)
+        name: String,
+
+        /// Delete without prompting
+        #[arg(short, long)]
+        yes: bool,
+
+        /// Delete even if the overlay is applied with symlinks somewhere
+        #[arg(short, long)]
+        force: bool,
+
+        /// Overlay source to delete from (defaults to the highest-priority one)
+        #[arg(long = "from", value_name = "SOURCE")]
+        from_source: Option<String>,
+    },
+
+    /// Push overlay repo commits that haven't been pushed yet
+    ///
+    /// Commits made with --no-fetch, or whose push failed, stay in the local
+    /// clone until pushed.
+    ///
+    /// Examples:
+    ///   repoverlay push --dry-run  # List what would be pushed
+    ///   repoverlay push --all      # Push every configured overlay repository
+    #[command(hide = true)]
+    Push {
+        /// Overlay source to push (defaults to the highest-priority one)
+        #[arg(long = "from", value_name = "SOURCE", conflicts_with = "all")]
+        from_source: Option<String>,
+
+        /// Push every configured overlay repository that has been cloned
+        #[arg(long)]
+        all: bool,
+
+        /// List the commits that would be pushed without pushing them
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Publish an overlay to the overlay repository
+    #[command(hide = true)] // Hidden: deprecated, use create instead
+    Publish {
+        /// Path to the overlay source directory
+        source: PathBuf,
+
+        /// Target repository (format: org/repo)
+        /// Auto-detected from current git remote if not specified
         #[arg(short, long)]
         target: Option<String>,
 
@@ -316,12 +897,24 @@ enum Commands {
         message: Option<String>,
 
         /// Skip push to remote (just commit locally)
-        #[arg(long)]
+        #[arg(long, conflicts_with = "pr")]
         no_push: bool,
 
+        /// Push to a new branch and open a pull request instead of pushing to the current branch
+        #[arg(long)]
+        pr: bool,
+
         /// Show what would be published without making changes
         #[arg(long)]
         dry_run: bool,
+
+        /// Skip scanning files for likely secrets (API keys, private keys, passwords)
+        #[arg(long)]
+        no_secrets_check: bool,
+
+        /// Use a specific overlay source instead of the highest-priority one (multi-source configs only)
+        #[arg(long = "from", value_name = "SOURCE")]
+        from_source: Option<String>,
     },
 
     /// Manage overlay sources (for multi-source configurations)
@@ -329,6 +922,26 @@ enum Commands {
         #[command(subcommand)]
         command: SourceCommand,
     },
+
+    /// Generate a shell completion script
+    ///
+    /// Examples:
+    ///   repoverlay completions bash > ~/.local/share/bash-completion/completions/repoverlay
+    ///   repoverlay completions zsh > ~/.zfunc/_repoverlay
+    ///   repoverlay completions fish > ~/.config/fish/completions/repoverlay.fish
+    Completions {
+        /// Shell to generate completions for
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+
+    /// List applied overlay names (used by shell completion scripts)
+    #[command(name = "__overlay-names", hide = true)]
+    OverlayNames {
+        /// Target repository directory (defaults to current directory)
+        #[arg(short, long)]
+        target: Option<PathBuf>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -363,14 +976,37 @@ enum CacheCommand {
         /// Skip confirmation prompt
         #[arg(short = 'y', long)]
         yes: bool,
+
+        /// Only remove repositories last fetched longer ago than this
+        /// (e.g. 30d, 12h, 2w)
+        #[arg(long, value_name = "DURATION", value_parser = parse_age)]
+        older_than: Option<chrono::Duration>,
     },
 
     /// Remove a specific cached repository
     Remove {
-        /// Repository to remove (format: owner/repo)
+        /// Repository to remove (format: owner/repo or host/owner/repo)
+        repo: String,
+    },
+
+    /// Show details for a cached repository
+    Info {
+        /// Repository to show (format: owner/repo or host/owner/repo)
         repo: String,
     },
 
+    /// Remove cached repositories not used by any applied overlay
+    Gc {
+        /// Show what would be removed without deleting anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Keep commits that applied overlays are pinned to, even if their
+        /// branch has moved on
+        #[arg(long)]
+        keep_refs: bool,
+    },
+
     /// Show cache location
     Path,
 }
@@ -378,6 +1014,17 @@ enum CacheCommand {
 pub fn run() -> Result<()> {
     let cli = Cli::parse();
 
+    // Decide on color before anything is printed
+    let colorize = cli.color.should_colorize(
+        std::env::var("NO_COLOR").ok().as_deref(),
+        std::env::var("CLICOLOR_FORCE").ok().as_deref(),
+        io::stdout().is_terminal(),
+    );
+    colored::control::set_override(colorize);
+    crate::output::set_quiet(cli.quiet);
+    crate::offline::init(cli.offline);
+    crate::logging::init(cli.verbosity, cli.log_file.as_deref())?;
+
     // Handle markdown help generation (for documentation)
     if cli.markdown_help {
         clap_markdown::print_help_markdown::<Cli>();
@@ -398,20 +1045,94 @@ pub fn run() -> Result<()> {
             name,
             r#ref,
             update,
+            subpath,
             from_source,
             dry_run,
+            on_conflict,
+            force,
+            verbose,
+            set,
+            only,
+            no_deps,
+            run_hooks,
+            prefix,
         } => {
             let target = target.unwrap_or_else(|| PathBuf::from("."));
-            apply_overlay(
-                &source,
-                &target,
-                copy,
+            let variables: BTreeMap<String, String> = set.into_iter().collect();
+            let on_conflict = if force {
+                OnConflict::Backup
+            } else {
+                on_conflict
+            };
+            if source.len() > 1 && name.is_some() {
+                bail!("--name can only be used when applying a single overlay");
+            }
+            if source.len() > 1 && !only.is_empty() {
+                bail!("--only can only be used when applying a single overlay");
+            }
+            if source.len() > 1 && subpath.is_some() {
+                bail!("--subpath can only be used when applying a single overlay");
+            }
+            let source = match &subpath {
+                Some(subpath) => vec![source_with_subpath(&source[0], subpath)?],
+                None => source,
+            };
+
+            let options = ApplyOptions {
+                force_copy: copy,
                 name,
-                r#ref.as_deref(),
-                update,
-                from_source.as_deref(),
+                git_ref: r#ref.as_deref(),
+                update_cache: update,
+                source_filter: from_source.as_deref(),
                 dry_run,
-            )?;
+                on_conflict,
+                verbose,
+                variables,
+                only: &only,
+                no_deps,
+                run_hooks,
+                prefix: prefix.as_deref(),
+                ..Default::default()
+            };
+            let mut applied: Vec<&str> = Vec::new();
+            for (i, source_str) in source.iter().enumerate() {
+                if i > 0 {
+                    status!();
+                }
+                // State is saved after each apply, so conflict detection for
+                // later sources sees overlays applied earlier in this run
+                if let Err(e) = apply_overlay(source_str, &target, &options) {
+                    if !applied.is_empty() {
+                        eprintln!(
+                            "  {} Applied before failure: {}",
+                            "Note:".yellow(),
+                            applied.join(", ")
+                        );
+                    }
+                    if source.len() == 1 {
+                        return Err(e);
+                    }
+                    return Err(e.context(format!("Failed to apply '{source_str}'")));
+                }
+                applied.push(source_str);
+            }
+        }
+        Commands::ApplyBundle {
+            bundle,
+            target,
+            copy,
+            update,
+            dry_run,
+            on_conflict,
+            force,
+        } => {
+            let target = target.unwrap_or_else(|| PathBuf::from("."));
+            let on_conflict = if force {
+                OnConflict::Backup
+            } else {
+                on_conflict
+            };
+            apply_bundle(&target, &bundle, copy, update, dry_run, on_conflict)?;
         }
         Commands::Remove {
             name,
@@ -419,36 +1140,174 @@ pub fn run() -> Result<()> {
             all,
             dry_run,
             interactive,
+            run_hooks,
+        } => {
+            let target = target.unwrap_or_else(|| PathBuf::from("."));
+            handle_remove(&target, name, all, dry_run, interactive, run_hooks)?;
+        }
+        Commands::Unlink {
+            overlay,
+            file,
+            force,
+            target,
+        } => {
+            let target = target.unwrap_or_else(|| PathBuf::from("."));
+            unlink_file(&target, &overlay, &file, force)?;
+        }
+        Commands::Edit {
+            overlay,
+            file,
+            target,
+        } => {
+            let target = target.unwrap_or_else(|| PathBuf::from("."));
+            edit_overlay_file(&target, &overlay, &file, &editor_command())?;
+        }
+        Commands::Mv {
+            overlay,
+            from,
+            to,
+            target,
+        } => {
+            let target = target.unwrap_or_else(|| PathBuf::from("."));
+            move_overlay_file(&target, &overlay, &from, &to)?;
+        }
+        Commands::Which { file, target } => {
+            let target = target.unwrap_or_else(|| PathBuf::from("."));
+            which_overlay(&target, &file)?;
+        }
+        Commands::Status {
+            target,
+            name,
+            json,
+            check_drift,
+            verify_remote,
+            broken,
+        } => {
+            let target = target.unwrap_or_else(|| PathBuf::from("."));
+            if show_status(&target, name, json, check_drift, verify_remote, broken)? {
+                bail!("Some overlays have missing or diverged files");
+            }
+        }
+        Commands::Diff { name, target } => {
+            let target = target.unwrap_or_else(|| PathBuf::from("."));
+            if diff_overlays(&target, name)? {
+                bail!("Applied overlay files differ from their source");
+            }
+        }
+        Commands::Repair { name, target } => {
+            let target = target.unwrap_or_else(|| PathBuf::from("."));
+            repair_overlays(&target, name)?;
+        }
+        Commands::Verify { target } => {
+            let target = target.unwrap_or_else(|| PathBuf::from("."));
+            verify_overlays(&target)?;
+        }
+        Commands::Doctor => crate::doctor::run()?,
+        Commands::Prune { target, dry_run } => {
+            let target = target.unwrap_or_else(|| PathBuf::from("."));
+            prune(&target, dry_run)?;
+        }
+        Commands::Export { output, target } => {
+            let target = target.unwrap_or_else(|| PathBuf::from("."));
+            export_overlays(&target, &output)?;
+        }
+        Commands::Import {
+            archive,
+            target,
+            copy,
         } => {
             let target = target.unwrap_or_else(|| PathBuf::from("."));
-            handle_remove(&target, name, all, dry_run, interactive)?;
+            import_overlays(&archive, &target, copy)?;
+        }
+        Commands::Bootstrap { target, yes } => {
+            let target = target.unwrap_or_else(|| PathBuf::from("."));
+            bootstrap_overlays(&target, yes)?;
         }
-        Commands::Status { target, name } => {
+        Commands::Restore {
+            target,
+            dry_run,
+            jobs,
+            include_removed,
+            only,
+            forget,
+        } => {
             let target = target.unwrap_or_else(|| PathBuf::from("."));
-            show_status(&target, name)?;
+            if let Some(name) = forget {
+                forget_removed_overlay(&target, &name)?;
+            } else {
+                restore_overlays(&target, dry_run, jobs, include_removed, &only)?;
+            }
+        }
+        Commands::Init { path, bundles } => {
+            let path = crate::overlay_repo::init_overlay_repo(&path, bundles)?;
+            status!(
+                "{} Created overlay repository at {}",
+                "✓".green().bold(),
+                path.display()
+            );
+            status!("\nAdd overlays under <org>/<repo>/<name>/, then register the repository:");
+            status!("  repoverlay source add {}", path.display());
         }
-        Commands::Restore { target, dry_run } => {
+        Commands::InstallHook { target, uninstall } => {
             let target = target.unwrap_or_else(|| PathBuf::from("."));
-            restore_overlays(&target, dry_run)?;
+            if uninstall {
+                uninstall_hooks(&target)?;
+            } else {
+                install_hooks(&target)?;
+            }
         }
         Commands::Update {
             name,
             target,
             dry_run,
+            force,
+            jobs,
+            ..
         } => {
+            crate::offline::ensure_online("update overlays")?;
             let target = target.unwrap_or_else(|| PathBuf::from("."));
-            update_overlays(&target, name, dry_run)?;
+            update_overlays(&target, name, dry_run, force, jobs)?;
         }
         Commands::Create {
             name,
-            include,
+            target,
+            mut include,
+            include_from,
             source,
             dry_run,
             yes,
             force,
+            no_secrets_check,
+            fresh,
+            dereference,
+            from_source,
+            pr,
+            message,
+            no_fetch,
         } => {
             let source = source.unwrap_or_else(|| PathBuf::from("."));
-            create_overlay_command(&source, Some(name), None, &include, dry_run, yes, force)?;
+            if let Some(path) = include_from {
+                include.extend(read_include_file(&path)?);
+            }
+            create_overlay_command(
+                &source,
+                Some(name),
+                target.as_deref(),
+                None,
+                &include,
+                dry_run,
+                yes,
+                force,
+                !no_secrets_check,
+                fresh,
+                dereference,
+                from_source.as_deref(),
+                &CommitOptions {
+                    message,
+                    pr,
+                    no_fetch,
+                },
+            )?;
         }
         Commands::CreateLocal {
             output,
@@ -457,9 +1316,22 @@ pub fn run() -> Result<()> {
             dry_run,
             yes,
             force: _,
+            no_secrets_check,
+            fresh,
+            dereference,
         } => {
             let source = source.unwrap_or_else(|| PathBuf::from("."));
-            crate::create_overlay(&source, Some(output), &include, None, dry_run, yes)?;
+            crate::create_overlay(
+                &source,
+                Some(output),
+                &include,
+                None,
+                dry_run,
+                yes,
+                !no_secrets_check,
+                fresh,
+                dereference,
+            )?;
         }
         Commands::Switch {
             source,
@@ -467,32 +1339,113 @@ pub fn run() -> Result<()> {
             copy,
             name,
             r#ref,
+            subpath,
+            dry_run,
         } => {
             let target = target.unwrap_or_else(|| PathBuf::from("."));
-            switch_overlay(&source, &target, copy, name, r#ref.as_deref())?;
+            let source = match &subpath {
+                Some(subpath) => source_with_subpath(&source, subpath)?,
+                None => source,
+            };
+            switch_overlay(&source, &target, copy, name, r#ref.as_deref(), dry_run)?;
         }
         Commands::Cache { command } => {
             handle_cache_command(command)?;
         }
-        Commands::List { filter, update } => {
-            list_overlays(filter.as_deref(), update)?;
+        Commands::List {
+            applied: Some(target),
+            ..
+        } => {
+            list_applied(&target)?;
+        }
+        Commands::List {
+            filter,
+            update,
+            tag,
+            search,
+            from_source,
+            json,
+            applied: None,
+        } => {
+            list_overlays(
+                filter.as_deref(),
+                update,
+                tag.as_deref(),
+                search.as_deref(),
+                from_source.as_deref(),
+                json,
+            )?;
+        }
+        Commands::Info {
+            source,
+            r#ref,
+            from_source,
+        } => {
+            show_overlay_info(&source, r#ref.as_deref(), from_source.as_deref())?;
         }
         Commands::Sync {
             name,
+            all,
             target,
             dry_run,
+            yes,
+            from_source,
+            message,
+            no_fetch,
         } => {
+            if !no_fetch {
+                crate::offline::ensure_online("sync overlays")?;
+            }
             let target = target.unwrap_or_else(|| PathBuf::from("."));
-            sync_overlay(&name, &target, dry_run)?;
+            let commit = CommitOptions {
+                message,
+                no_fetch,
+                ..Default::default()
+            };
+            match name {
+                Some(name) if !all => {
+                    sync_overlay(
+                        &name,
+                        &target,
+                        dry_run,
+                        yes,
+                        from_source.as_deref(),
+                        &commit,
+                    )?;
+                }
+                _ => sync_all_overlays(&target, dry_run, yes, from_source.as_deref(), &commit)?,
+            }
         }
         Commands::Add {
             name,
             files,
             target,
             dry_run,
+            message,
+            no_fetch,
         } => {
             let target = target.unwrap_or_else(|| PathBuf::from("."));
-            add_files_to_overlay(&name, &target, &files, dry_run)?;
+            let commit = CommitOptions {
+                message,
+                no_fetch,
+                ..Default::default()
+            };
+            add_files_to_overlay(&name, &target, &files, dry_run, &commit)?;
+        }
+        Commands::Delete {
+            name,
+            yes,
+            force,
+            from_source,
+        } => {
+            delete_overlay(&name, yes, force, from_source.as_deref())?;
+        }
+        Commands::Push {
+            from_source,
+            all,
+            dry_run,
+        } => {
+            push_overlay_repo(from_source.as_deref(), all, dry_run)?;
         }
         Commands::Publish {
             source,
@@ -501,6 +1454,9 @@ pub fn run() -> Result<()> {
             message,
             no_push,
             dry_run,
+            no_secrets_check,
+            from_source,
+            pr,
         } => {
             eprintln!(
                 "{} 'repoverlay publish' is deprecated and will be removed in a future version.",
@@ -518,16 +1474,45 @@ pub fn run() -> Result<()> {
                 message.as_deref(),
                 no_push,
                 dry_run,
+                !no_secrets_check,
+                from_source.as_deref(),
+                pr,
             )?;
         }
         Commands::Source { command } => {
             handle_source_command(command)?;
         }
+        Commands::Completions { shell } => {
+            print!("{}", completions::generate(shell, Cli::command()));
+        }
+        Commands::OverlayNames { target } => {
+            // Completion must never print errors into the user's prompt
+            let target = target.unwrap_or_else(|| PathBuf::from("."));
+            for name in list_applied_overlays(&target).unwrap_or_default() {
+                println!("{name}");
+            }
+        }
     }
 
     Ok(())
 }
 
+/// Editor command line from `$VISUAL` or `$EDITOR`, falling back to the
+/// platform default.
+fn editor_command() -> String {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| {
+            if cfg!(windows) {
+                "notepad".to_string()
+            } else {
+                "vi".to_string()
+            }
+        })
+}
+
 /// Handle source subcommands.
 fn handle_source_command(command: SourceCommand) -> Result<()> {
     use colored::Colorize;
@@ -573,13 +1558,13 @@ fn handle_source_command(command: SourceCommand) -> Result<()> {
             config.sources.push(new_source);
             config::save_config(&config)?;
 
-            println!(
+            status!(
                 "{} source '{}' at position {}",
                 "Added".green().bold(),
                 source_name,
                 config.sources.len()
             );
-            println!("       URL: {url}");
+            status!("       URL: {url}");
         }
         SourceCommand::List => {
             if config.sources.is_empty() {
@@ -625,7 +1610,7 @@ fn handle_source_command(command: SourceCommand) -> Result<()> {
 
             config::save_config(&config)?;
 
-            println!("{} source '{}'", "Removed".red().bold(), name);
+            status!("{} source '{}'", "Removed".red().bold(), name);
         }
     }
 
@@ -633,16 +1618,18 @@ fn handle_source_command(command: SourceCommand) -> Result<()> {
 }
 
 /// Handle remove command with interactive selection support.
+#[allow(clippy::fn_params_excessive_bools)]
 fn handle_remove(
     target: &std::path::Path,
     name: Option<String>,
     remove_all: bool,
     dry_run: bool,
     interactive: bool,
+    run_hooks: bool,
 ) -> Result<()> {
     // If name or --all is specified, use direct removal
     if remove_all || name.is_some() {
-        return remove_overlay(target, name, remove_all, dry_run);
+        return remove_overlay(target, name, remove_all, dry_run, run_hooks);
     }
 
     // If not interactive and no name specified, require explicit action
@@ -692,25 +1679,20 @@ fn handle_remove(
         if selection == applied_overlays.len() + 1 {
             // Remove all
             if dry_run {
-                println!("\n{} Dry run - would remove all overlays", "Note:".yellow());
-                return Ok(());
+                return remove_overlay(&target, None, true, true, run_hooks);
             }
             for overlay_name in &applied_overlays {
-                remove_single_overlay(&target, &overlays_dir, overlay_name)?;
+                remove_single_overlay(&target, &overlays_dir, overlay_name, run_hooks)?;
             }
             fs::remove_dir_all(target.join(STATE_DIR))?;
-            println!("\n{} Removed all overlays", "✓".green().bold());
+            status!("\n{} Removed all overlays", "✓".green().bold());
         } else if selection >= 1 && selection <= applied_overlays.len() {
             let overlay_name = &applied_overlays[selection - 1];
             if dry_run {
-                println!(
-                    "\n{} Dry run - would remove overlay '{}'",
-                    "Note:".yellow(),
-                    overlay_name
-                );
-                return Ok(());
+                return remove_overlay(&target, Some(overlay_name.clone()), false, true, run_hooks);
             }
-            remove_single_overlay(&target, &overlays_dir, overlay_name)?;
+            crate::warn_dependents(&target, overlay_name)?;
+            remove_single_overlay(&target, &overlays_dir, overlay_name, run_hooks)?;
 
             let remaining = list_applied_overlays(&target)?;
             if remaining.is_empty() {
@@ -721,14 +1703,13 @@ fn handle_remove(
         }
     } else if input.eq_ignore_ascii_case("all") {
         if dry_run {
-            println!("\n{} Dry run - would remove all overlays", "Note:".yellow());
-            return Ok(());
+            return remove_overlay(&target, None, true, true, run_hooks);
         }
         for overlay_name in &applied_overlays {
-            remove_single_overlay(&target, &overlays_dir, overlay_name)?;
+            remove_single_overlay(&target, &overlays_dir, overlay_name, run_hooks)?;
         }
         fs::remove_dir_all(target.join(STATE_DIR))?;
-        println!("\n{} Removed all overlays", "✓".green().bold());
+        status!("\n{} Removed all overlays", "✓".green().bold());
     } else {
         bail!("Invalid selection: {input}");
     }
@@ -744,44 +1725,44 @@ fn handle_cache_command(command: CacheCommand) -> Result<()> {
             let repos = cache.list_cached()?;
 
             if repos.is_empty() {
-                println!("{} No repositories cached.", "Cache:".bold());
+                status!("{} No repositories cached.", "Cache:".bold());
                 return Ok(());
             }
 
-            println!("{} {} cached repository(s):", "Cache:".bold(), repos.len());
-            println!();
+            status!("{} {} cached repository(s):", "Cache:".bold(), repos.len());
+            status!();
 
             for repo in repos {
-                println!("  {}/{}", repo.owner.cyan(), repo.repo);
+                status!("  {}", repo.display_name().cyan());
                 if let Some(meta) = repo.meta {
-                    println!("    Ref:     {}", meta.requested_ref);
-                    println!("    Commit:  {}", &meta.commit[..12.min(meta.commit.len())]);
-                    println!(
+                    status!("    Ref:     {}", meta.requested_ref);
+                    status!("    Commit:  {}", &meta.commit[..12.min(meta.commit.len())]);
+                    status!(
                         "    Fetched: {}",
                         meta.last_fetched.format("%Y-%m-%d %H:%M UTC")
                     );
                 }
-                println!("    Path:    {}", repo.path.display());
-                println!();
+                status!("    Path:    {}", repo.path.display());
+                status!();
             }
         }
 
-        CacheCommand::Clear { yes } => {
-            if !yes {
-                print!("Clear entire cache? [y/N] ");
-                io::stdout().flush()?;
-
-                let mut input = String::new();
-                io::stdin().read_line(&mut input)?;
+        CacheCommand::Clear {
+            yes,
+            older_than: Some(age),
+        } => clear_stale_cache(&cache, age, yes)?,
 
-                if !input.trim().eq_ignore_ascii_case("y") {
-                    println!("Cancelled.");
-                    return Ok(());
-                }
+        CacheCommand::Clear {
+            yes,
+            older_than: None,
+        } => {
+            if !yes && !confirm("Clear entire cache?")? {
+                status!("Cancelled.");
+                return Ok(());
             }
 
             let count = cache.clear_cache()?;
-            println!(
+            status!(
                 "{} Cleared {} cached repository(s).",
                 "✓".green().bold(),
                 count
@@ -789,25 +1770,51 @@ fn handle_cache_command(command: CacheCommand) -> Result<()> {
         }
 
         CacheCommand::Remove { repo } => {
-            let parts: Vec<&str> = repo.split('/').collect();
-            if parts.len() != 2 {
-                bail!("Invalid repository format. Use: owner/repo");
+            let (host, owner, repo_name) = parse_cache_repo_arg(&repo)?;
+
+            if cache.remove_cached(host, owner, repo_name)? {
+                status!("{} Removed {} from cache.", "✓".green().bold(), repo);
+            } else {
+                status!("{repo} is not cached.");
             }
+        }
 
-            let (owner, repo_name) = (parts[0], parts[1]);
+        CacheCommand::Info { repo } => {
+            let (host, owner, repo_name) = parse_cache_repo_arg(&repo)?;
 
-            if cache.remove_cached(owner, repo_name)? {
+            let Some(info) = cache.cached_repo(host, owner, repo_name) else {
+                status!("{repo} is not cached.");
+                return Ok(());
+            };
+
+            println!("{} {}", "Cache:".bold(), info.display_name().cyan());
+            if let Some(meta) = &info.meta {
+                println!("  Ref:     {}", meta.requested_ref);
+                println!("  Commit:  {}", meta.commit);
                 println!(
-                    "{} Removed {}/{} from cache.",
-                    "✓".green().bold(),
-                    owner,
-                    repo_name
+                    "  Fetched: {}",
+                    meta.last_fetched.format("%Y-%m-%d %H:%M UTC")
                 );
-            } else {
-                println!("{owner}/{repo_name} is not cached.");
             }
+            println!("  Size:    {}", format_bytes(dir_size(&info.path)));
+            let refs = cache.cached_refs(&info.path);
+            if refs.len() > 1 {
+                println!("  Refs:    {} cached ({})", refs.len(), refs.join(", "));
+            }
+            for pin in info.meta.iter().flat_map(|meta| &meta.pins) {
+                println!(
+                    "  Used by: {} in {} at {} ({})",
+                    pin.overlay.cyan(),
+                    pin.target.display(),
+                    &pin.commit[..12.min(pin.commit.len())],
+                    pin.git_ref
+                );
+            }
+            println!("  Path:    {}", info.path.display());
         }
 
+        CacheCommand::Gc { dry_run, keep_refs } => gc_cache(&cache, dry_run, keep_refs)?,
+
         CacheCommand::Path => {
             println!("{}", cache.cache_dir().display());
         }
@@ -816,30 +1823,386 @@ fn handle_cache_command(command: CacheCommand) -> Result<()> {
     Ok(())
 }
 
-/// List available overlays from the overlay repository.
-fn list_overlays(target_filter: Option<&str>, update: bool) -> Result<()> {
-    use crate::config::load_config;
-    use crate::overlay_repo::OverlayRepoManager;
-
-    let config = load_config(None)?;
-
-    let overlay_config = config.overlay_repo.ok_or_else(|| {
-        anyhow::anyhow!(
-            "Overlay repository not configured.\n\n\
-             Run 'repoverlay source add <url>' to set up an overlay source.\n\
-             Example: repoverlay source add https://github.com/tylerbutler/repo-overlays"
-        )
-    })?;
-
-    let manager = OverlayRepoManager::new(overlay_config)?;
-    manager.ensure_cloned()?;
-
-    if update {
-        println!("{} overlay repository...", "Updating".blue().bold());
-        manager.pull()?;
+/// Remove cached repositories that no applied overlay refers to.
+///
+/// References are taken from the external state of every target repository,
+/// so overlays applied anywhere on this machine keep their clones. Clones
+/// that are kept have unreachable objects pruned; with `keep_refs`, commits
+/// that overlays are pinned to are kept as well.
+fn gc_cache(cache: &CacheManager, dry_run: bool, keep_refs: bool) -> Result<()> {
+    // (host, owner, repo, overlay, commit) for every applied GitHub overlay
+    let live: Vec<LiveRef> = crate::state::load_all_external_states()?
+        .into_iter()
+        .filter_map(|state| match state.source {
+            crate::state::OverlaySource::GitHub {
+                url,
+                owner,
+                repo,
+                commit,
+                ..
+            } => Some((
+                crate::github::host_from_url(&url),
+                owner,
+                repo,
+                state.name,
+                commit,
+            )),
+            _ => None,
+        })
+        .collect();
+    let referenced: Vec<(String, String, String)> = live
+        .iter()
+        .map(|(host, owner, repo, ..)| (host.clone(), owner.clone(), repo.clone()))
+        .collect();
+
+    let unused = cache.unreferenced_repos(&referenced)?;
+    compact_cached_repos(cache, &live, &unused, dry_run, keep_refs)?;
+    if unused.is_empty() {
+        status!("{} No unreferenced repositories cached.", "Cache:".bold());
+        return Ok(());
     }
 
-    let overlays = if let Some(filter) = target_filter {
+    let mut reclaimed = 0;
+    for info in &unused {
+        let size = dir_size(&info.path);
+        if dry_run {
+            status!(
+                "  Would remove {} ({})",
+                info.display_name().cyan(),
+                format_bytes(size)
+            );
+        } else {
+            cache.remove_cached(&info.host, &info.owner, &info.repo)?;
+            status!(
+                "  Removed {} ({})",
+                info.display_name().cyan(),
+                format_bytes(size)
+            );
+        }
+        reclaimed += size;
+    }
+
+    if dry_run {
+        status!(
+            "{} {} repository(s) would be removed, reclaiming {}.",
+            "Dry run:".yellow().bold(),
+            unused.len(),
+            format_bytes(reclaimed)
+        );
+    } else {
+        status!(
+            "{} Removed {} unreferenced repository(s), reclaimed {}.",
+            "✓".green().bold(),
+            unused.len(),
+            format_bytes(reclaimed)
+        );
+    }
+
+    Ok(())
+}
+
+/// Remove cached repositories last fetched longer ago than `age`.
+fn clear_stale_cache(cache: &CacheManager, age: chrono::Duration, yes: bool) -> Result<()> {
+    let cutoff = chrono::Utc::now()
+        .checked_sub_signed(age)
+        .unwrap_or(chrono::DateTime::<chrono::Utc>::MIN_UTC);
+    let stale = cache.repos_fetched_before(cutoff)?;
+    if stale.is_empty() {
+        status!(
+            "{} No cached repositories older than {}.",
+            "Cache:".bold(),
+            format_age(age)
+        );
+        return Ok(());
+    }
+
+    let prompt = format!(
+        "Remove {} cached repository(s) not fetched in {}?",
+        stale.len(),
+        format_age(age)
+    );
+    if !yes && !confirm(&prompt)? {
+        status!("Cancelled.");
+        return Ok(());
+    }
+
+    let mut reclaimed = 0;
+    for info in &stale {
+        let size = dir_size(&info.path);
+        cache.remove_cached(&info.host, &info.owner, &info.repo)?;
+        status!(
+            "  Removed {} ({})",
+            info.display_name().cyan(),
+            format_bytes(size)
+        );
+        reclaimed += size;
+    }
+    status!(
+        "{} Removed {} cached repository(s), reclaimed {}.",
+        "✓".green().bold(),
+        stale.len(),
+        format_bytes(reclaimed)
+    );
+    Ok(())
+}
+
+/// Parse an age like `30d`: a whole number followed by `s`, `m`, `h`, `d`,
+/// or `w`.
+fn parse_age(value: &str) -> Result<chrono::Duration, String> {
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: i64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration '{value}' (expected e.g. 30d or 12h)"))?;
+    let age = match unit {
+        "s" => chrono::Duration::try_seconds(number),
+        "m" => chrono::Duration::try_minutes(number),
+        "h" => chrono::Duration::try_hours(number),
+        "d" => chrono::Duration::try_days(number),
+        "w" => chrono::Duration::try_weeks(number),
+        _ => {
+            return Err(format!(
+                "invalid duration unit in '{value}' (use s, m, h, d, or w)"
+            ));
+        }
+    };
+    age.ok_or_else(|| format!("duration '{value}' is too long"))
+}
+
+/// Format an age in its largest whole unit, like `parse_age` accepts.
+fn format_age(age: chrono::Duration) -> String {
+    let seconds = age.num_seconds();
+    for (unit, size) in [("w", 604_800), ("d", 86_400), ("h", 3_600), ("m", 60)] {
+        if seconds >= size && seconds % size == 0 {
+            return format!("{}{unit}", seconds / size);
+        }
+    }
+    format!("{seconds}s")
+}
+
+/// Prune the clones `cache gc` keeps, updating their recorded pins.
+fn compact_cached_repos(
+    cache: &CacheManager,
+    live: &[LiveRef],
+    unused: &[crate::cache::CachedRepoInfo],
+    dry_run: bool,
+    keep_refs: bool,
+) -> Result<()> {
+    for info in cache.list_cached()? {
+        if unused.iter().any(|u| u.path == info.path) {
+            continue;
+        }
+        let used: Vec<(String, String)> = live
+            .iter()
+            .filter(|(host, owner, repo, ..)| {
+                host.eq_ignore_ascii_case(&info.host)
+                    && owner.eq_ignore_ascii_case(&info.owner)
+                    && repo.eq_ignore_ascii_case(&info.repo)
+            })
+            .map(|(_, _, _, overlay, commit)| (overlay.clone(), commit.clone()))
+            .collect();
+        let mut keep: Vec<String> = Vec::new();
+        if keep_refs {
+            keep.extend(used.iter().map(|(_, commit)| commit.clone()));
+            keep.sort();
+            keep.dedup();
+        }
+
+        if dry_run {
+            status!(
+                "  Would prune {} (keeping {} pinned commit(s))",
+                info.display_name().cyan(),
+                keep.len()
+            );
+            continue;
+        }
+
+        cache.retain_pins(&info.path, &used)?;
+        let before = dir_size(&info.path);
+        if let Err(e) = cache.compact_repo(&info.path, &keep) {
+            eprintln!(
+                "  {} Could not prune {}: {e}",
+                "Warning:".yellow(),
+                info.display_name()
+            );
+            continue;
+        }
+        let reclaimed = before.saturating_sub(dir_size(&info.path));
+        status!(
+            "  Pruned {} ({} reclaimed, {} pinned commit(s) kept)",
+            info.display_name().cyan(),
+            format_bytes(reclaimed),
+            keep.len()
+        );
+    }
+
+    Ok(())
+}
+
+/// A GitHub overlay applied somewhere: (host, owner, repo, overlay, commit).
+type LiveRef = (String, String, String, String, String);
+
+/// Split an `owner/repo` or `host/owner/repo` argument for cache commands.
+///
+/// The host defaults to github.com.
+fn parse_cache_repo_arg(repo: &str) -> Result<(&str, &str, &str)> {
+    let parts = repo.split('/').collect::<Vec<_>>();
+    if parts.iter().any(|part| part.is_empty()) {
+        bail!("Invalid repository format. Use: owner/repo or host/owner/repo");
+    }
+    match parts[..] {
+        [owner, repo] => Ok((crate::github::DEFAULT_GITHUB_HOST, owner, repo)),
+        [host, owner, repo] => Ok((host, owner, repo)),
+        _ => bail!("Invalid repository format. Use: owner/repo or host/owner/repo"),
+    }
+}
+
+/// Format a byte count in a human-readable way (e.g., 512 B, 1.5 MB).
+#[allow(clippy::cast_precision_loss)]
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = UNITS[0];
+    for next in &UNITS[1..] {
+        if size < 1024.0 {
+            break;
+        }
+        size /= 1024.0;
+        unit = next;
+    }
+    format!("{size:.1} {unit}")
+}
+
+/// Most misplaced files to name before summarizing the rest.
+const MAX_LAYOUT_PATHS: usize = 5;
+
+/// Warn about files in the overlay repository that no overlay can use.
+fn print_layout_warnings(layout: &crate::overlay_repo::LayoutReport) {
+    if layout.misplaced.is_empty() {
+        return;
+    }
+    eprintln!(
+        "{} {} file(s) in the overlay repository are outside an <org>/<repo>/<overlay-name>/ directory and will be ignored:",
+        "Warning:".yellow(),
+        layout.misplaced.len()
+    );
+    for path in layout.misplaced.iter().take(MAX_LAYOUT_PATHS) {
+        eprintln!("  {}", path.display());
+    }
+    if layout.misplaced.len() > MAX_LAYOUT_PATHS {
+        eprintln!(
+            "  ... and {} more",
+            layout.misplaced.len() - MAX_LAYOUT_PATHS
+        );
+    }
+}
+
+/// Explain the expected layout when an overlay repository has no overlays.
+fn print_layout_diagnostic(layout: &crate::overlay_repo::LayoutReport) {
+    println!();
+    println!("Overlays are expected at <org>/<repo>/<overlay-name>/, for example:");
+    println!("  microsoft/FluidFramework/claude-config/repoverlay.ccl");
+    if layout.top_level_dirs.is_empty() {
+        println!("The repository has no directories.");
+    } else {
+        println!(
+            "Found top-level directories: {}",
+            layout.top_level_dirs.join(", ")
+        );
+    }
+}
+
+/// List the overlays applied to a repository, one line each.
+fn list_applied(target: &std::path::Path) -> Result<()> {
+    use crate::load_overlay_state;
+
+    let target = canonicalize_path(target, "Target directory")?;
+    let states = list_applied_overlays(&target)?
+        .iter()
+        .map(|name| load_overlay_state(&target, name))
+        .collect::<Result<Vec<_>>>()?;
+    if states.is_empty() {
+        println!("{} No overlays are currently applied.", "Status:".bold());
+        return Ok(());
+    }
+
+    let width = states.iter().map(|s| s.name.len()).max().unwrap_or(0);
+    for state in &states {
+        println!(
+            "{}  {}  {}",
+            format!("{:<width$}", state.name).cyan(),
+            state.source.display(),
+            format!("{} file(s)", state.file_count()).dimmed()
+        );
+    }
+    Ok(())
+}
+
+/// Machine-readable entry of `list --json`.
+#[derive(serde::Serialize)]
+struct AvailableOverlayJson<'a> {
+    org: &'a str,
+    repo: &'a str,
+    name: &'a str,
+    has_config: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<&'a str>,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    tags: &'a [String],
+}
+
+impl<'a> From<&'a AvailableOverlay> for AvailableOverlayJson<'a> {
+    fn from(overlay: &'a AvailableOverlay) -> Self {
+        Self {
+            org: &overlay.org,
+            repo: &overlay.repo,
+            name: &overlay.name,
+            has_config: overlay.has_config,
+            description: overlay.meta.description.as_deref(),
+            tags: &overlay.meta.tags,
+        }
+    }
+}
+
+/// List available overlays from the overlay repository.
+fn list_overlays(
+    target_filter: Option<&str>,
+    update: bool,
+    tag: Option<&str>,
+    search: Option<&str>,
+    from_source: Option<&str>,
+    json: bool,
+) -> Result<()> {
+    use crate::config::load_config;
+    use crate::overlay_repo::OverlayRepoManager;
+
+    let config = load_config(None)?;
+
+    let manager = OverlayRepoManager::configured(&config, from_source)?.ok_or_else(|| {
+        anyhow::anyhow!(
+            "Overlay repository not configured.\n\n\
+             Run 'repoverlay source add <url>' to set up an overlay source.\n\
+             Example: repoverlay source add https://github.com/tylerbutler/repo-overlays"
+        )
+    })?;
+    manager.ensure_cloned()?;
+
+    if update {
+        if !json {
+            status!("{} overlay repository...", "Updating".blue().bold());
+        }
+        manager.pull()?;
+    }
+
+    let layout = manager.validate_layout()?;
+    print_layout_warnings(&layout);
+
+    let mut overlays = if let Some(filter) = target_filter {
         // Parse org/repo filter
         let parts: Vec<&str> = filter.split('/').collect();
         if parts.len() != 2 {
@@ -850,11 +2213,45 @@ fn list_overlays(target_filter: Option<&str>, update: bool) -> Result<()> {
         manager.list_overlays()?
     };
 
+    if let Some(tag) = tag {
+        overlays.retain(|overlay| overlay.meta.has_tag(tag));
+    }
+
+    // Pair each overlay with its search matches, dropping non-matches
+    let search = search.filter(|q| !q.is_empty());
+    let overlays: Vec<(AvailableOverlay, SearchMatches)> = overlays
+        .into_iter()
+        .filter_map(|overlay| {
+            let matches = match search {
+                Some(query) => SearchMatches::find(&overlay, query)?,
+                None => SearchMatches::default(),
+            };
+            Some((overlay, matches))
+        })
+        .collect();
+
+    if json {
+        let entries: Vec<AvailableOverlayJson> = overlays
+            .iter()
+            .map(|(overlay, _)| AvailableOverlayJson::from(overlay))
+            .collect();
+        println!(
+            "{}",
+            serde_json::to_string(&entries).context("Failed to serialize overlay list")?
+        );
+        return Ok(());
+    }
+
     if overlays.is_empty() {
-        if let Some(filter) = target_filter {
+        if let Some(query) = search {
+            println!("{} No overlays match '{}'.", "Status:".bold(), query);
+        } else if let Some(tag) = tag {
+            println!("{} No overlays found with tag '{}'.", "Status:".bold(), tag);
+        } else if let Some(filter) = target_filter {
             println!("{} No overlays found for {}.", "Status:".bold(), filter);
         } else {
             println!("{} No overlays found in repository.", "Status:".bold());
+            print_layout_diagnostic(&layout);
         }
         return Ok(());
     }
@@ -863,13 +2260,18 @@ fn list_overlays(target_filter: Option<&str>, update: bool) -> Result<()> {
 
     // Group by org/repo
     let mut current_group: Option<(String, String)> = None;
-    for overlay in &overlays {
+    for (overlay, matches) in &overlays {
         let group = (overlay.org.clone(), overlay.repo.clone());
         if current_group.as_ref() != Some(&group) {
             if current_group.is_some() {
                 println!();
             }
-            println!("{}{}{}:", overlay.org.cyan(), "/".dimmed(), overlay.repo);
+            println!(
+                "{}{}{}:",
+                highlight_matches(&overlay.org, &matches.org, |s| s.cyan()),
+                "/".dimmed(),
+                highlight_matches(&overlay.repo, &matches.repo, |s| s.normal())
+            );
             current_group = Some(group);
         }
         let config_marker = if overlay.has_config {
@@ -877,18 +2279,192 @@ fn list_overlays(target_filter: Option<&str>, update: bool) -> Result<()> {
         } else {
             " (no config)"
         };
-        println!("  - {}{}", overlay.name, config_marker.dimmed());
+        let description = overlay
+            .meta
+            .description
+            .as_ref()
+            .map(|d| {
+                format!(
+                    "{}{}",
+                    " - ".dimmed(),
+                    highlight_matches(d, &matches.description, |s| s.dimmed())
+                )
+            })
+            .unwrap_or_default();
+        let tags = if overlay.meta.tags.is_empty() {
+            String::new()
+        } else {
+            format!(" [{}]", overlay.meta.tags.join(", "))
+        };
+        println!(
+            "  - {}{}{}{}",
+            highlight_matches(&overlay.name, &matches.name, |s| s.normal()),
+            config_marker.dimmed(),
+            description,
+            tags.magenta()
+        );
+    }
+
+    // Bundles are only listed when not narrowing by tag or search; a target
+    // filter keeps bundles with a member for that repository
+    let mut bundles = if tag.is_none() && search.is_none() {
+        manager.list_bundles()?
+    } else {
+        Vec::new()
+    };
+    if let Some(filter) = target_filter {
+        let prefix = format!("{}/", filter.to_lowercase());
+        bundles.retain(|bundle| {
+            bundle
+                .overlays
+                .iter()
+                .any(|o| o.to_lowercase().starts_with(&prefix))
+        });
+    }
+
+    if !bundles.is_empty() {
+        println!("\n{}\n", "Bundles:".bold());
+        for bundle in &bundles {
+            println!(
+                "  - {} {}",
+                bundle.name.cyan(),
+                format!("({})", bundle.overlays.join(", ")).dimmed()
+            );
+        }
     }
 
     println!(
         "\nTo apply an overlay: repoverlay apply {}",
         "<org>/<repo>/<name>".dimmed()
     );
+    if !bundles.is_empty() {
+        println!(
+            "To apply a bundle: repoverlay apply-bundle {}",
+            "<bundle>".dimmed()
+        );
+    }
 
     Ok(())
 }
 
+/// Character positions in each overlay field that matched `list --search`.
+#[derive(Default)]
+struct SearchMatches {
+    org: Vec<usize>,
+    repo: Vec<usize>,
+    name: Vec<usize>,
+    description: Vec<usize>,
+}
+
+impl SearchMatches {
+    /// Match `query` against an overlay, returning `None` if no field matches.
+    ///
+    /// Names fall back to fuzzy (subsequence) matching; descriptions only
+    /// match as a substring, since short queries fuzzy-match most prose.
+    fn find(overlay: &AvailableOverlay, query: &str) -> Option<Self> {
+        let matches = Self {
+            org: fuzzy_match(&overlay.org, query).unwrap_or_default(),
+            repo: fuzzy_match(&overlay.repo, query).unwrap_or_default(),
+            name: fuzzy_match(&overlay.name, query).unwrap_or_default(),
+            description: overlay
+                .meta
+                .description
+                .as_deref()
+                .and_then(|d| substring_match(d, query))
+                .unwrap_or_default(),
+        };
+        let found = !(matches.org.is_empty()
+            && matches.repo.is_empty()
+            && matches.name.is_empty()
+            && matches.description.is_empty());
+        found.then_some(matches)
+    }
+}
+
+/// Compare two characters case-insensitively.
+fn chars_match(a: char, b: char) -> bool {
+    a == b || a.to_lowercase().eq(b.to_lowercase())
+}
+
+/// Find `query` as a contiguous, case-insensitive substring of `text`.
+///
+/// Returns the character positions of the match.
+fn substring_match(text: &str, query: &str) -> Option<Vec<usize>> {
+    let text: Vec<char> = text.chars().collect();
+    let query: Vec<char> = query.chars().collect();
+    if query.is_empty() || query.len() > text.len() {
+        return None;
+    }
+    (0..=text.len() - query.len())
+        .find(|&start| {
+            query
+                .iter()
+                .enumerate()
+                .all(|(i, &q)| chars_match(text[start + i], q))
+        })
+        .map(|start| (start..start + query.len()).collect())
+}
+
+/// Match `query` against `text`, preferring a substring and falling back to
+/// a case-insensitive subsequence (e.g. `clcfg` matches `claude-config`).
+///
+/// Returns the character positions of the matched characters.
+fn fuzzy_match(text: &str, query: &str) -> Option<Vec<usize>> {
+    if let Some(positions) = substring_match(text, query) {
+        return Some(positions);
+    }
+
+    let mut positions = Vec::new();
+    let mut query_chars = query.chars().peekable();
+    for (i, c) in text.chars().enumerate() {
+        match query_chars.peek() {
+            Some(&q) if chars_match(c, q) => {
+                positions.push(i);
+                query_chars.next();
+            }
+            Some(_) => {}
+            None => break,
+        }
+    }
+    (query_chars.peek().is_none() && !positions.is_empty()).then_some(positions)
+}
+
+/// Render `text` with the characters at `positions` highlighted.
+///
+/// Unmatched runs are styled with `base`.
+fn highlight_matches(
+    text: &str,
+    positions: &[usize],
+    base: impl Fn(&str) -> ColoredString,
+) -> String {
+    let style = |run: &str, matched: bool| {
+        if matched {
+            run.yellow().bold().to_string()
+        } else {
+            base(run).to_string()
+        }
+    };
+
+    let mut output = String::new();
+    let mut run = String::new();
+    let mut run_matched = false;
+    for (i, c) in text.chars().enumerate() {
+        let matched = positions.contains(&i);
+        if matched != run_matched && !run.is_empty() {
+            output.push_str(&style(&run, run_matched));
+            run.clear();
+        }
+        run_matched = matched;
+        run.push(c);
+    }
+    if !run.is_empty() {
+        output.push_str(&style(&run, run_matched));
+    }
+    output
+}
+
 /// Publish an overlay to the overlay repository.
+#[allow(clippy::too_many_arguments, clippy::fn_params_excessive_bools)]
 fn publish_overlay(
     source: &std::path::Path,
     target: Option<&str>,
@@ -896,6 +2472,9 @@ fn publish_overlay(
     message: Option<&str>,
     no_push: bool,
     dry_run: bool,
+    secrets_check: bool,
+    from_source: Option<&str>,
+    pr: bool,
 ) -> Result<()> {
     use crate::config::load_config;
     use crate::overlay_repo::OverlayRepoManager;
@@ -909,7 +2488,7 @@ fn publish_overlay(
 
     // Load config
     let config = load_config(None)?;
-    let overlay_config = config.overlay_repo.ok_or_else(|| {
+    let manager = OverlayRepoManager::configured(&config, from_source)?.ok_or_else(|| {
         anyhow::anyhow!(
             "Overlay repository not configured.\n\n\
              Run 'repoverlay source add <url>' to set up an overlay source."
@@ -947,59 +2526,76 @@ fn publish_overlay(
         }
     };
 
-    println!("{} Publishing overlay:", "Publish".blue().bold());
-    println!("  Source:  {}", source.display());
-    println!("  Target:  {org}/{repo}");
-    println!("  Name:    {overlay_name}");
+    status!("{} Publishing overlay:", "Publish".blue().bold());
+    status!("  Source:  {}", source.display());
+    status!("  Target:  {org}/{repo}");
+    status!("  Name:    {overlay_name}");
 
     if dry_run {
-        println!("\n{} Dry run - no changes made.", "Note:".yellow());
-        println!("\nWould publish to: {org}/{repo}/{overlay_name}");
+        status!("\n{} Dry run - no changes made.", "Note:".yellow());
+        status!("\nWould publish to: {org}/{repo}/{overlay_name}");
         return Ok(());
     }
 
-    // Create manager and ensure cloned
-    let manager = OverlayRepoManager::new(overlay_config)?;
+    if secrets_check {
+        crate::secrets::confirm_no_secrets(&crate::secrets::scan_dir(&source))?;
+    }
+
+    // Ensure cloned
     manager.ensure_cloned()?;
 
     // Pull latest
-    println!("\n{} latest changes...", "Pulling".blue().bold());
+    status!("\n{} latest changes...", "Pulling".blue().bold());
     manager.pull()?;
 
     // Stage the overlay
     let copying = "Copying".blue().bold();
-    println!("{copying} overlay files...");
+    status!("{copying} overlay files...");
     let dest = manager.stage_overlay(&org, &repo, &overlay_name, &source)?;
-    println!("  Copied to: {}", dest.display());
+    status!("  Copied to: {}", dest.display());
 
     // Check if there are changes
     if !manager.has_staged_changes()? {
-        println!("\n{} No changes to publish.", "Note:".yellow());
+        status!("\n{} No changes to publish.", "Note:".yellow());
         return Ok(());
     }
 
     // Commit
-    let commit_msg = message
-        .unwrap_or(&format!("Update overlay: {org}/{repo}/{overlay_name}"))
-        .to_string();
+    let commit_msg = message.map_or_else(
+        || {
+            config
+                .commit
+                .message(OverlayChange::Update.verb(), &org, &repo, &overlay_name)
+        },
+        str::to_string,
+    );
+
+    if pr {
+        return commit_for_review(
+            &manager,
+            &commit_msg,
+            &config.commit,
+            &review_branch_name(&org, &repo, &overlay_name),
+        );
+    }
 
-    println!("{} changes...", "Committing".blue().bold());
-    manager.commit(&commit_msg)?;
+    status!("{} changes...", "Committing".blue().bold());
+    manager.commit(&commit_msg, &config.commit)?;
 
     // Push
     if no_push {
-        println!(
+        status!(
             "\n{} Changes committed but not pushed (--no-push).",
             "Note:".yellow()
         );
     } else {
-        println!("{} to remote...", "Pushing".blue().bold());
+        status!("{} to remote...", "Pushing".blue().bold());
         manager.push()?;
         let check = "✓".green().bold();
-        println!("\n{check} Overlay published: {org}/{repo}/{overlay_name}");
+        status!("\n{check} Overlay published: {org}/{repo}/{overlay_name}");
     }
 
-    println!("\nTo apply: repoverlay apply {org}/{repo}/{overlay_name}");
+    status!("\nTo apply: repoverlay apply {org}/{repo}/{overlay_name}");
 
     Ok(())
 }
@@ -1017,7 +2613,8 @@ fn detect_target_repo(path: &std::path::Path) -> Result<(String, String)> {
     if !output.status.success() {
         bail!(
             "Could not detect target repository from git remote.\n\
-             Please specify explicitly: repoverlay create org/repo/name"
+             Please specify explicitly: repoverlay create org/repo/name\n\
+             Or pass the target repository: repoverlay create name --target org/repo"
         );
     }
 
@@ -1071,23 +2668,99 @@ fn parse_overlay_name_arg(
     }
 }
 
+/// Parse `create`'s name argument, taking org/repo from `--target` when it is
+/// given with a short name.
+///
+/// A full `org/repo/name` must agree with `--target` if both are given.
+fn parse_create_name_arg(
+    name_arg: &str,
+    target: Option<&str>,
+    source_path: &std::path::Path,
+) -> Result<(String, String, String)> {
+    let Some(target) = target else {
+        return parse_overlay_name_arg(name_arg, source_path);
+    };
+    let (org, repo) = match target.split_once('/') {
+        Some((org, repo)) if !org.is_empty() && !repo.is_empty() && !repo.contains('/') => {
+            (org.to_string(), repo.to_string())
+        }
+        _ => bail!("Invalid target format: {target}. Use: org/repo"),
+    };
+    if !name_arg.contains('/') {
+        return Ok((org, repo, name_arg.to_string()));
+    }
+
+    let parsed = parse_overlay_name_arg(name_arg, source_path)?;
+    if parsed.0 != org || parsed.1 != repo {
+        bail!(
+            "Overlay path {name_arg} doesn't match --target {target}.\n\
+             Pass either org/repo/name or a short name with --target."
+        );
+    }
+    Ok(parsed)
+}
+
+/// Read a list of includes for `create --include-from`: one path or glob per
+/// line, skipping blank lines and `#` comments.
+fn read_include_file(path: &std::path::Path) -> Result<Vec<PathBuf>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read include list: {}", path.display()))?;
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(PathBuf::from)
+        .collect())
+}
+
+/// Find an overlay in `repo_dir` (an `org/repo` directory of the overlay
+/// repo) other than `name` itself that normalizes to the same name.
+fn colliding_overlay(repo_dir: &std::path::Path, name: &str) -> Result<Option<String>> {
+    use crate::state::normalize_overlay_name;
+
+    if !repo_dir.is_dir() {
+        return Ok(None);
+    }
+    let normalized = normalize_overlay_name(name)?;
+    for entry in std::fs::read_dir(repo_dir)? {
+        let entry = entry?;
+        let existing = entry.file_name().to_string_lossy().to_string();
+        if existing != name
+            && !existing.starts_with('.')
+            && entry.path().is_dir()
+            && normalize_overlay_name(&existing).is_ok_and(|n| n == normalized)
+        {
+            return Ok(Some(existing));
+        }
+    }
+    Ok(None)
+}
+
 /// Handle the create command with the new argument structure.
 ///
 /// This function handles:
 /// - `create <name>` - create in overlay repo, auto-detect org/repo
 /// - `create org/repo/name` - create in overlay repo at explicit path
 /// - `create --local ./output` - create in local directory only
+#[allow(clippy::too_many_arguments, clippy::fn_params_excessive_bools)]
 fn create_overlay_command(
     source: &std::path::Path,
     name_arg: Option<String>,
+    target: Option<&str>,
     local: Option<PathBuf>,
     include: &[PathBuf],
     dry_run: bool,
     yes: bool,
     force: bool,
+    secrets_check: bool,
+    fresh: bool,
+    dereference: bool,
+    from_source: Option<&str>,
+    commit: &CommitOptions,
 ) -> Result<()> {
     use crate::config::load_config;
     use crate::overlay_repo::OverlayRepoManager;
+    use crate::state::{OVERLAY_NAME_RULES, normalize_overlay_name};
 
     // Validate source is a git repo
     if !source.join(".git").exists() {
@@ -1107,6 +2780,9 @@ fn create_overlay_command(
             None, // name derived from directory
             dry_run,
             yes,
+            secrets_check,
+            fresh,
+            dereference,
         );
     }
 
@@ -1122,11 +2798,11 @@ fn create_overlay_command(
     })?;
 
     // Parse the name argument
-    let (org, repo, overlay_name) = parse_overlay_name_arg(&name_arg, source)?;
+    let (org, repo, overlay_name) = parse_create_name_arg(&name_arg, target, source)?;
 
     // Load overlay repo config
     let config = load_config(None)?;
-    let overlay_config = config.overlay_repo.ok_or_else(|| {
+    let manager = OverlayRepoManager::configured(&config, from_source)?.ok_or_else(|| {
         anyhow::anyhow!(
             "Overlay repository not configured.\n\n\
              Run 'repoverlay source add <url>' to set up an overlay source.\n\
@@ -1134,8 +2810,7 @@ fn create_overlay_command(
         )
     })?;
 
-    // Create manager and ensure cloned
-    let manager = OverlayRepoManager::new(overlay_config)?;
+    // Ensure cloned
     manager.ensure_cloned()?;
 
     // Determine output path in overlay repo
@@ -1150,7 +2825,31 @@ fn create_overlay_command(
         );
     }
 
-    println!(
+    // Overlays are applied under their normalized name, so `My-Overlay` and
+    // `my-overlay` would share a state file in any repo that applies both
+    let normalized = normalize_overlay_name(&overlay_name)?;
+    if let Some(existing) =
+        colliding_overlay(&manager.path().join(&org).join(&repo), &overlay_name)?
+        && !force
+    {
+        bail!(
+            "Overlay '{org}/{repo}/{overlay_name}' would collide with '{org}/{repo}/{existing}': \
+             both are applied as '{normalized}'.\n\n\
+             {OVERLAY_NAME_RULES}\n\n\
+             Choose a different name, or create it anyway with --force."
+        );
+    }
+    if normalized != overlay_name {
+        status!(
+            "{} '{}' is applied as '{}'. {}",
+            "Note:".yellow(),
+            overlay_name,
+            normalized,
+            OVERLAY_NAME_RULES
+        );
+    }
+
+    status!(
         "{} Creating overlay: {}/{}/{}",
         "Create".blue().bold(),
         org,
@@ -1159,9 +2858,9 @@ fn create_overlay_command(
     );
 
     if dry_run {
-        println!("  Source:  {}", source.display());
-        println!("  Target:  {}", output_path.display());
-        println!("\n{} Dry run - no changes made.", "Note:".yellow());
+        status!("  Source:  {}", source.display());
+        status!("  Target:  {}", output_path.display());
+        status!("\n{} Dry run - no changes made.", "Note:".yellow());
         return Ok(());
     }
 
@@ -1175,28 +2874,36 @@ fn create_overlay_command(
             Some(overlay_name.clone()),
             dry_run,
             yes,
+            secrets_check,
+            fresh,
+            dereference,
         )
         .and_then(|()| {
             // Auto-commit after creating
-            auto_commit_overlay(&manager, &org, &repo, &overlay_name, true)
+            auto_commit_overlay(
+                &manager,
+                &org,
+                &repo,
+                &overlay_name,
+                OverlayChange::Add,
+                commit,
+                &config.commit,
+            )
         });
     }
 
-    // Validate all include paths exist
-    for path in include {
-        let full_path = source.join(path);
-        if !full_path.exists() {
-            bail!("Include path does not exist: {}", path.display());
-        }
+    let include = &crate::expand_include_patterns(source, include)?;
+    if secrets_check {
+        crate::check_included_secrets(source, include, dereference)?;
     }
 
     // If force and exists, remove existing first
     if output_path.exists() && force {
-        fs::remove_dir_all(&output_path)?;
+        manager.remove_overlay(&org, &repo, &overlay_name)?;
     }
 
     // Copy files and create overlay
-    let copied_files = crate::copy_files_to_overlay(source, &output_path, include)?;
+    let copied_files = crate::copy_files_to_overlay(source, &output_path, include, dereference)?;
 
     // Generate config
     fs::write(
@@ -1207,55 +2914,253 @@ fn create_overlay_command(
     crate::print_overlay_created(&output_path, &copied_files);
 
     // Auto-commit
-    auto_commit_overlay(&manager, &org, &repo, &overlay_name, true)?;
+    auto_commit_overlay(
+        &manager,
+        &org,
+        &repo,
+        &overlay_name,
+        OverlayChange::Add,
+        commit,
+        &config.commit,
+    )?;
 
     Ok(())
 }
 
-/// Auto-commit changes to an overlay in the overlay repo.
-fn auto_commit_overlay(
-    manager: &crate::overlay_repo::OverlayRepoManager,
-    org: &str,
-    repo: &str,
-    name: &str,
-    is_new: bool,
-) -> Result<()> {
-    use std::process::Command;
-
-    // Fetch latest from remote before committing to avoid divergence
-    println!("{} overlay repo...", "Syncing".blue().bold());
-    let fetch_output = Command::new("git")
-        .args(["fetch", "origin"])
-        .current_dir(manager.path())
-        .output()
-        .context("Failed to fetch from remote")?;
-
-    if fetch_output.status.success() {
-        // Try to pull/rebase to incorporate remote changes
-        let pull_output = Command::new("git")
-            .args(["pull", "--rebase", "--autostash"])
-            .current_dir(manager.path())
-            .output()
-            .context("Failed to pull from remote")?;
+/// Push commits in overlay repo clones that their remote doesn't have yet.
+///
+/// With `all`, every configured overlay source that has been cloned is
+/// pushed; otherwise the one named by `from_source`, or the highest-priority
+/// one. With `dry_run`, the pending commits are listed and nothing is pushed.
+fn push_overlay_repo(from_source: Option<&str>, all: bool, dry_run: bool) -> Result<()> {
+    use crate::config::load_config;
+    use crate::overlay_repo::OverlayRepoManager;
 
-        if !pull_output.status.success() {
-            let stderr = String::from_utf8_lossy(&pull_output.stderr);
-            // If pull fails due to conflicts, warn but continue
-            eprintln!(
-                "{} Could not pull latest changes: {}",
-                "Warning:".yellow(),
-                stderr.trim()
+    let config = load_config(None)?;
+    let mut managers = OverlayRepoManager::all_configured(&config)?;
+    if all {
+        managers.retain(|(_, manager)| !manager.needs_clone());
+    } else {
+        let Some(default) = managers.first().map(|(name, _)| name.clone()) else {
+            bail!(
+                "Overlay repository not configured.\n\n\
+                 Run 'repoverlay source add <url>' to set up an overlay source."
+            );
+        };
+        let available: Vec<String> = managers.iter().map(|(name, _)| name.clone()).collect();
+        let name = from_source.map_or(default, str::to_string);
+        managers.retain(|(source, _)| *source == name);
+        let Some((_, manager)) = managers.first() else {
+            bail!(
+                "Unknown source: {name}\nAvailable sources: {}",
+                available.join(", ")
+            );
+        };
+        if manager.needs_clone() {
+            status!(
+                "{} The overlay repository has not been cloned yet.",
+                "Status:".bold()
             );
+            return Ok(());
         }
-    } else {
-        // Fetch failed, but continue - might be offline
-        eprintln!(
-            "{} Could not fetch from remote (offline?), continuing...",
-            "Warning:".yellow()
+    }
+    if managers.is_empty() {
+        status!(
+            "{} No overlay repositories have been cloned.",
+            "Status:".bold()
         );
+        return Ok(());
     }
 
-    // Check if there are changes to commit
+    for (source, manager) in &managers {
+        let pending = manager
+            .unpushed_commits()
+            .with_context(|| format!("Failed to check overlay repo '{source}'"))?;
+        if pending.is_empty() {
+            status!("{} {}: nothing to push", "✓".green().bold(), source.cyan());
+            continue;
+        }
+
+        status!(
+            "{}: {} commit(s) to push:",
+            source.cyan().bold(),
+            pending.len()
+        );
+        for commit in &pending {
+            status!("  {commit}");
+        }
+        if !dry_run {
+            status!("{} to remote...", "Pushing".blue().bold());
+            manager
+                .push()
+                .with_context(|| format!("Failed to push overlay repo '{source}'"))?;
+            status!("{} Pushed {}", "✓".green().bold(), source.cyan());
+        }
+    }
+
+    if dry_run {
+        status!("\n{} Dry run; nothing was pushed.", "Note:".yellow());
+    }
+    Ok(())
+}
+
+/// Delete an overlay from the overlay repo, then commit and push.
+fn delete_overlay(name_arg: &str, yes: bool, force: bool, from_source: Option<&str>) -> Result<()> {
+    use crate::config::load_config;
+    use crate::overlay_repo::OverlayRepoManager;
+
+    let (org, repo, overlay_name) = parse_overlay_name_arg(name_arg, &PathBuf::from("."))?;
+
+    let config = load_config(None)?;
+    let manager = OverlayRepoManager::configured(&config, from_source)?.ok_or_else(|| {
+        anyhow::anyhow!(
+            "Overlay repository not configured.\n\n\
+             Run 'repoverlay source add <url>' to set up an overlay source."
+        )
+    })?;
+    manager.ensure_cloned()?;
+    manager.get_overlay_path(&org, &repo, &overlay_name)?;
+
+    let (applied, linked) = applied_count(&org, &repo, &overlay_name);
+    if linked > 0 && !force {
+        bail!(
+            "{org}/{repo}/{overlay_name} is applied with symlinks in {linked} place(s), \
+             and those files will break once it is deleted.\n\n\
+             Remove the overlay there first, or use --force to delete it anyway."
+        );
+    }
+    if linked > 0 {
+        eprintln!(
+            "{} {org}/{repo}/{overlay_name} is applied with symlinks in {linked} place(s). \
+             Those symlinked files will break.",
+            "Warning:".yellow()
+        );
+    }
+    if applied > linked {
+        eprintln!(
+            "{} {org}/{repo}/{overlay_name} is copied into {} place(s). \
+             Those copies keep working, but can no longer be updated or restored.",
+            "Warning:".yellow(),
+            applied - linked
+        );
+    }
+
+    let prompt = format!("Delete {org}/{repo}/{overlay_name} from the overlay repo?");
+    if !yes && !confirm(&prompt)? {
+        status!("Cancelled.");
+        return Ok(());
+    }
+
+    manager.remove_overlay(&org, &repo, &overlay_name)?;
+    auto_commit_overlay(
+        &manager,
+        &org,
+        &repo,
+        &overlay_name,
+        OverlayChange::Delete,
+        &CommitOptions::default(),
+        &config.commit,
+    )
+}
+
+/// How many target repositories on this machine have `org/repo/name`
+/// applied from an overlay repo, and how many of those use symlinks.
+fn applied_count(org: &str, repo: &str, name: &str) -> (usize, usize) {
+    let applied: Vec<_> = crate::state::load_all_external_states()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|state| {
+            matches!(
+                &state.source,
+                crate::state::OverlaySource::OverlayRepo {
+                    org: applied_org,
+                    repo: applied_repo,
+                    name: applied_name,
+                    ..
+                } if applied_name == name
+                    && applied_org.eq_ignore_ascii_case(org)
+                    && applied_repo.eq_ignore_ascii_case(repo)
+            )
+        })
+        .collect();
+    let linked = applied
+        .iter()
+        .filter(|state| {
+            state
+                .file_entries()
+                .iter()
+                .any(|entry| entry.link_type == crate::state::LinkType::Symlink)
+        })
+        .count();
+    (applied.len(), linked)
+}
+
+/// How `create`, `sync`, and `add` commit their changes to the overlay repo.
+#[derive(Debug, Default)]
+struct CommitOptions {
+    /// Message replacing the one from the `commit.message_template` setting
+    message: Option<String>,
+    /// Commit on a new branch and open a pull request instead of pushing
+    pr: bool,
+    /// Commit without pulling first or pushing after
+    no_fetch: bool,
+}
+
+/// Auto-commit changes to an overlay in the overlay repo.
+fn auto_commit_overlay(
+    manager: &crate::overlay_repo::OverlayRepoManager,
+    org: &str,
+    repo: &str,
+    name: &str,
+    change: OverlayChange,
+    options: &CommitOptions,
+    config: &crate::config::CommitConfig,
+) -> Result<()> {
+    use std::process::Command;
+
+    // Fetch latest from remote before committing to avoid divergence
+    if options.no_fetch {
+        log::debug!("skipping overlay repo fetch (--no-fetch)");
+    } else if crate::offline::is_offline() {
+        eprintln!(
+            "{} Offline, committing without fetching the overlay repo first",
+            "Note:".yellow()
+        );
+    } else {
+        status!("{} overlay repo...", "Syncing".blue().bold());
+        let fetch_output = Command::new("git")
+            .args(["fetch", "origin"])
+            .current_dir(manager.path())
+            .output()
+            .context("Failed to fetch from remote")?;
+
+        if fetch_output.status.success() {
+            // Try to pull/rebase to incorporate remote changes
+            let pull_output = Command::new("git")
+                .args(["pull", "--rebase", "--autostash"])
+                .current_dir(manager.path())
+                .output()
+                .context("Failed to pull from remote")?;
+
+            if !pull_output.status.success() {
+                let stderr = String::from_utf8_lossy(&pull_output.stderr);
+                // If pull fails due to conflicts, warn but continue
+                eprintln!(
+                    "{} Could not pull latest changes: {}",
+                    "Warning:".yellow(),
+                    stderr.trim()
+                );
+            }
+        } else {
+            // Fetch failed, but continue - might be offline
+            eprintln!(
+                "{} Could not fetch from remote (offline?), continuing...",
+                "Warning:".yellow()
+            );
+        }
+    }
+
+    // Check if there are changes to commit
     if !manager.has_staged_changes()? {
         // Stage all changes
         let output = Command::new("git")
@@ -1273,44 +3178,187 @@ fn auto_commit_overlay(
 
     // Check again if there are staged changes
     if !manager.has_staged_changes()? {
-        println!("{} No changes to commit.", "Note:".yellow());
+        status!("{} No changes to commit.", "Note:".yellow());
         return Ok(());
     }
 
-    let action = if is_new { "Add" } else { "Update" };
-    let commit_msg = format!("{action} overlay: {org}/{repo}/{name}");
+    let commit_msg = options
+        .message
+        .clone()
+        .unwrap_or_else(|| config.message(change.verb(), org, repo, name));
+
+    if options.pr {
+        return commit_for_review(
+            manager,
+            &commit_msg,
+            config,
+            &review_branch_name(org, repo, name),
+        );
+    }
+
+    status!("{} changes...", "Committing".blue().bold());
+    manager.commit(&commit_msg, config)?;
+
+    if options.no_fetch {
+        status!(
+            "\n{} Overlay {} locally: {org}/{repo}/{name}",
+            "✓".green().bold(),
+            change.done()
+        );
+        status!(
+            "Not pushed (--no-fetch); run 'repoverlay push' or the next create, add, or sync without it to push it."
+        );
+    } else {
+        // Auto-push to remote
+        status!("{} to remote...", "Pushing".blue().bold());
+        match manager.push() {
+            Ok(()) => {
+                let check = "✓".green().bold();
+                status!("\n{check} Overlay {}: {org}/{repo}/{name}", change.done());
+            }
+            Err(e) => {
+                let warn = "Warning:".yellow();
+                eprintln!("\n{warn} Committed locally but failed to push: {e}");
+                eprintln!("Run 'repoverlay push' to push manually when online.");
+            }
+        }
+    }
+
+    if !matches!(change, OverlayChange::Delete) {
+        status!("To apply: repoverlay apply {org}/{repo}/{name}");
+    }
+
+    Ok(())
+}
 
-    println!("{} changes...", "Committing".blue().bold());
-    manager.commit(&commit_msg)?;
+/// What a commit to the overlay repo does to an overlay.
+#[derive(Clone, Copy)]
+enum OverlayChange {
+    Add,
+    Update,
+    Delete,
+}
 
-    // Auto-push to remote
-    println!("{} to remote...", "Pushing".blue().bold());
-    match manager.push() {
-        Ok(()) => {
-            let check = "✓".green().bold();
-            let action_word = if is_new { "created" } else { "updated" };
-            println!("\n{check} Overlay {action_word}: {org}/{repo}/{name}");
+impl OverlayChange {
+    /// Verb starting the commit message.
+    const fn verb(self) -> &'static str {
+        match self {
+            Self::Add => "Add",
+            Self::Update => "Update",
+            Self::Delete => "Delete",
         }
-        Err(e) => {
-            let warn = "Warning:".yellow();
-            eprintln!("\n{warn} Committed locally but failed to push: {e}");
-            eprintln!("Run 'repoverlay push' to push manually when online.");
+    }
+
+    /// Past tense for the success message.
+    const fn done(self) -> &'static str {
+        match self {
+            Self::Add => "created",
+            Self::Update => "updated",
+            Self::Delete => "deleted",
         }
     }
+}
+
+/// Branch pushed for a pull request that adds or updates `org/repo/name`.
+fn review_branch_name(org: &str, repo: &str, name: &str) -> String {
+    format!(
+        "repoverlay/{org}/{repo}/{name}-{}",
+        chrono::Utc::now().format("%Y%m%d%H%M%S")
+    )
+}
 
-    println!("To apply: repoverlay apply {org}/{repo}/{name}");
+/// Commit the staged changes on a new branch, push it, and open a pull
+/// request with the GitHub CLI, or print where to open one.
+///
+/// The overlay repo clone goes back to its original branch afterwards, so the
+/// change shows up there once the pull request is merged.
+fn commit_for_review(
+    manager: &crate::overlay_repo::OverlayRepoManager,
+    message: &str,
+    author: &crate::config::CommitConfig,
+    branch: &str,
+) -> Result<()> {
+    use std::process::Command;
+
+    let base = manager.current_branch()?;
+    manager.create_branch(branch)?;
+    status!(
+        "{} changes on branch {branch}...",
+        "Committing".blue().bold()
+    );
+    let pushed = manager.commit(message, author).and_then(|()| {
+        status!("{} branch to remote...", "Pushing".blue().bold());
+        manager.push_branch(branch)
+    });
+    manager.checkout(&base)?;
+    pushed.with_context(|| {
+        format!(
+            "The changes are on branch '{branch}' in {}",
+            manager.path().display()
+        )
+    })?;
+
+    let gh = Command::new("gh")
+        .args(["pr", "create", "--fill", "--base", &base, "--head", branch])
+        .current_dir(manager.path())
+        .output();
+    match gh {
+        Ok(output) if output.status.success() => {
+            let url = String::from_utf8_lossy(&output.stdout);
+            status!(
+                "\n{} Opened pull request: {}",
+                "✓".green().bold(),
+                url.trim()
+            );
+            return Ok(());
+        }
+        Ok(output) => eprintln!(
+            "{} Could not open a pull request with gh: {}",
+            "Warning:".yellow(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+        Err(_) => eprintln!(
+            "{} The GitHub CLI (gh) is not installed; open the pull request yourself.",
+            "Note:".yellow()
+        ),
+    }
 
+    match manager
+        .remote_url()
+        .and_then(|url| crate::github::compare_url(&url, &base, branch))
+    {
+        Some(url) => status!(
+            "\n{} Pushed {branch}. Open a pull request: {url}",
+            "✓".green().bold()
+        ),
+        None => status!(
+            "\n{} Pushed {branch}. Open a pull request from it into {base}.",
+            "✓".green().bold()
+        ),
+    }
     Ok(())
 }
 
+/// Files copied to and deleted from the overlay repo by a sync.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct SyncSummary {
+    synced: usize,
+    deleted: usize,
+}
+
 /// Sync changes from an applied overlay back to the overlay repo.
 ///
-/// This copies changed files from the target repository back to the overlay repo
-/// and auto-commits the changes.
-fn sync_overlay(name_arg: &str, target: &std::path::Path, dry_run: bool) -> Result<()> {
-    use crate::config::load_config;
-    use crate::overlay_repo::OverlayRepoManager;
-    use crate::{load_overlay_state, normalize_overlay_name};
+/// This copies changed files from the target repository back to the overlay repo,
+/// deletes files that were removed from the target, and auto-commits the changes.
+fn sync_overlay(
+    name_arg: &str,
+    target: &std::path::Path,
+    dry_run: bool,
+    yes: bool,
+    from_source: Option<&str>,
+    commit: &CommitOptions,
+) -> Result<()> {
+    use crate::normalize_overlay_name;
 
     // Validate target is a git repo
     let target = canonicalize_path(target, "Target directory")?;
@@ -1333,71 +3381,239 @@ fn sync_overlay(name_arg: &str, target: &std::path::Path, dry_run: bool) -> Resu
         );
     }
 
+    sync_applied_overlay(
+        &target,
+        &normalized_name,
+        (&org, &repo, &overlay_name),
+        Some(name_arg),
+        dry_run,
+        yes,
+        from_source,
+        commit,
+    )
+}
+
+/// Sync every applied overlay that came from the overlay repo.
+fn sync_all_overlays(
+    target: &std::path::Path,
+    dry_run: bool,
+    yes: bool,
+    from_source: Option<&str>,
+    commit: &CommitOptions,
+) -> Result<()> {
+    use crate::load_overlay_state;
+    use crate::state::OverlaySource;
+
+    let target = canonicalize_path(target, "Target directory")?;
+    if !target.join(".git").exists() {
+        let target_display = target.display();
+        bail!("Target directory is not a git repository: {target_display}");
+    }
+
+    let applied_overlays = list_applied_overlays(&target)?;
+    if applied_overlays.is_empty() {
+        status!("{} No overlays are currently applied.", "Status:".bold());
+        return Ok(());
+    }
+
+    for name in &applied_overlays {
+        let state = load_overlay_state(&target, name)?;
+        let OverlaySource::OverlayRepo {
+            org,
+            repo,
+            name: overlay_name,
+            ..
+        } = &state.source
+        else {
+            status!(
+                "{} {} {}",
+                "Skipping".yellow().bold(),
+                state.name,
+                "(not from the overlay repo)".dimmed()
+            );
+            continue;
+        };
+
+        sync_applied_overlay(
+            &target,
+            name,
+            (org, repo, overlay_name),
+            None,
+            dry_run,
+            yes,
+            from_source,
+            commit,
+        )
+        .with_context(|| format!("Failed to sync overlay '{}'", state.name))?;
+    }
+
+    Ok(())
+}
+
+/// Sync one applied overlay, stored as `name` in the target, to
+/// `org/repo/overlay_name` in the overlay repo.
+#[allow(clippy::too_many_arguments)]
+fn sync_applied_overlay(
+    target: &std::path::Path,
+    name: &str,
+    (org, repo, overlay_name): (&str, &str, &str),
+    name_arg: Option<&str>,
+    dry_run: bool,
+    yes: bool,
+    from_source: Option<&str>,
+    commit: &CommitOptions,
+) -> Result<()> {
+    use crate::config::load_config;
+    use crate::load_overlay_state;
+    use crate::overlay_repo::OverlayRepoManager;
+
     // Load overlay state to get file mappings
-    let state = load_overlay_state(&target, &normalized_name)?;
+    let mut state = load_overlay_state(target, name)?;
 
-    // Load overlay repo config
+    // Sync to the overlay repo it was applied from unless one was named
     let config = load_config(None)?;
-    let overlay_config = config.overlay_repo.ok_or_else(|| {
+    let manager = OverlayRepoManager::configured(
+        &config,
+        from_source.or_else(|| state.source.source_filter()),
+    )?
+    .ok_or_else(|| {
         anyhow::anyhow!(
             "Overlay repository not configured.\n\n\
-             Run 'repoverlay source add <url>' to set up an overlay source."
+                     Run 'repoverlay source add <url>' to set up an overlay source."
         )
     })?;
-
-    // Create manager and ensure cloned
-    let manager = OverlayRepoManager::new(overlay_config)?;
     manager.ensure_cloned()?;
 
     // Get the overlay path in the overlay repo
-    let overlay_repo_path = manager.path().join(&org).join(&repo).join(&overlay_name);
+    let overlay_repo_path = manager.path().join(org).join(repo).join(overlay_name);
 
     if !overlay_repo_path.exists() {
-        bail!(
-            "Overlay '{org}/{repo}/{overlay_name}' does not exist in overlay repo.\n\n\
-             Did you mean to use 'repoverlay create {name_arg}' instead?"
-        );
+        let create_hint = name_arg.map_or_else(String::new, |name_arg| {
+            format!("\n\nDid you mean to use 'repoverlay create {name_arg}' instead?")
+        });
+        bail!("Overlay '{org}/{repo}/{overlay_name}' does not exist in overlay repo.{create_hint}");
     }
 
     let syncing = "Syncing".blue().bold();
-    println!("{syncing} overlay: {org}/{repo}/{overlay_name}");
+    status!("{syncing} overlay: {org}/{repo}/{overlay_name}");
 
     if dry_run {
-        println!("  Target: {}", target.display());
-        println!("  Repo:   {}", overlay_repo_path.display());
-        println!("\n{} Dry run - no changes made.", "Note:".yellow());
+        status!("  Target: {}", target.display());
+        status!("  Repo:   {}", overlay_repo_path.display());
+        status!("\n{} Dry run - no changes made.", "Note:".yellow());
+    }
 
-        // Show what would be synced
-        println!("\nFiles that would be synced:");
-        for entry in state.file_entries() {
-            let target_file = target.join(&entry.target);
+    let summary = sync_overlay_files(target, name, &mut state, &overlay_repo_path, dry_run, yes)?;
 
-            if target_file.exists() {
-                println!(
-                    "  {} {} -> {}",
-                    "→".cyan(),
-                    entry.target.display(),
-                    entry.source.display()
-                );
-            }
-        }
+    if dry_run {
+        return Ok(());
+    }
 
+    if summary == SyncSummary::default() {
+        status!("{} No files to sync.", "Note:".yellow());
         return Ok(());
     }
 
-    // Copy files from target back to overlay repo
-    let mut synced_count = 0;
-    for entry in state.file_entries() {
+    status!(
+        "{} Synced {} file(s), deleted {} file(s)",
+        "✓".green().bold(),
+        summary.synced,
+        summary.deleted
+    );
+
+    // Auto-commit
+    auto_commit_overlay(
+        &manager,
+        org,
+        repo,
+        overlay_name,
+        OverlayChange::Update,
+        commit,
+        &config.commit,
+    )?;
+
+    Ok(())
+}
+
+/// Copy changed files from the target to the overlay repo and delete overlay
+/// files whose target was removed from the repo.
+///
+/// Deletions are confirmed interactively unless `yes` is set; declining keeps
+/// them in the overlay and in the state. The state is saved when anything changed.
+fn sync_overlay_files(
+    target: &std::path::Path,
+    name: &str,
+    state: &mut crate::state::OverlayState,
+    overlay_repo_path: &std::path::Path,
+    dry_run: bool,
+    yes: bool,
+) -> Result<SyncSummary> {
+    use crate::hash::sha256_file;
+    use crate::overlay_repo::copy_dir_recursive;
+    use crate::save_remaining_entries;
+    use crate::state::EntryType;
+
+    let mut summary = SyncSummary::default();
+    let mut deleted = Vec::new();
+
+    if dry_run {
+        status!("\nFiles that would be synced:");
+    }
+
+    for (index, entry) in state.files.iter_mut().enumerate() {
         let target_file = target.join(&entry.target);
         let overlay_file = overlay_repo_path.join(&entry.source);
 
-        if target_file.exists() {
-            // Ensure parent directory exists
-            if let Some(parent) = overlay_file.parent() {
-                fs::create_dir_all(parent)?;
+        // Rendered templates can't be mapped back to their source
+        if entry.rendered {
+            if !dry_run {
+                status!(
+                    "  {} {} {}",
+                    "-".yellow(),
+                    entry.source.display(),
+                    "(rendered from template, not synced)".dimmed()
+                );
+            }
+            continue;
+        }
+
+        // Preserved symlinks have no content of their own to sync
+        if entry.entry_type == EntryType::Symlink {
+            continue;
+        }
+
+        // A dangling symlink means the source moved, not that the file was deleted
+        if !target_file.exists() {
+            if !target_file.is_symlink() {
+                deleted.push(index);
             }
+            continue;
+        }
+
+        // Symlinked entries already are the overlay repo's copy
+        if is_same_file(&target_file, &overlay_file) {
+            continue;
+        }
+
+        if dry_run {
+            status!(
+                "  {} {} -> {}",
+                "→".cyan(),
+                entry.target.display(),
+                entry.source.display()
+            );
+            continue;
+        }
+
+        // Ensure parent directory exists
+        if let Some(parent) = overlay_file.parent() {
+            fs::create_dir_all(parent)?;
+        }
 
-            // Copy file
+        if entry.entry_type == EntryType::Directory {
+            fs::create_dir_all(&overlay_file)?;
+            copy_dir_recursive(&target_file, &overlay_file)?;
+        } else {
             fs::copy(&target_file, &overlay_file).with_context(|| {
                 format!(
                     "Failed to copy {} to {}",
@@ -1406,35 +3622,147 @@ fn sync_overlay(name_arg: &str, target: &std::path::Path, dry_run: bool) -> Resu
                 )
             })?;
 
-            println!("  {} {}", "→".green(), entry.source.display());
-            synced_count += 1;
+            // The synced content is now the overlay's baseline for drift checks
+            if entry.sha256.is_some() {
+                entry.sha256 = Some(sha256_file(&target_file)?);
+            }
         }
+
+        status!("  {} {}", "→".green(), entry.source.display());
+        summary.synced += 1;
     }
 
-    if synced_count == 0 {
-        println!("{} No files to sync.", "Note:".yellow());
-        return Ok(());
+    if !deleted.is_empty() {
+        if dry_run {
+            status!("\nFiles that would be deleted from the overlay repo:");
+        } else {
+            status!("\nDeleted from the repo:");
+        }
+        for &index in &deleted {
+            status!("  {} {}", "-".red(), state.files[index].target.display());
+        }
+
+        if dry_run {
+            return Ok(summary);
+        }
+
+        let prompt = format!("Delete {} file(s) from the overlay repo?", deleted.len());
+        if !yes && !confirm(&prompt)? {
+            status!("Keeping deleted files in the overlay repo.");
+            deleted.clear();
+        }
     }
 
-    // Auto-commit
-    auto_commit_overlay(&manager, &org, &repo, &overlay_name, false)?;
+    // Remove from the back so earlier indices stay valid
+    for &index in deleted.iter().rev() {
+        let entry = state.files.remove(index);
+        let overlay_file = overlay_repo_path.join(&entry.source);
+        if overlay_file.is_dir() {
+            fs::remove_dir_all(&overlay_file)
+        } else if overlay_file.exists() {
+            fs::remove_file(&overlay_file)
+        } else {
+            Ok(())
+        }
+        .with_context(|| format!("Failed to delete {}", overlay_file.display()))?;
 
-    Ok(())
+        status!("  {} {}", "✗".red(), entry.source.display());
+        summary.deleted += 1;
+    }
+
+    if !dry_run && summary != SyncSummary::default() {
+        save_remaining_entries(target, name, state)?;
+    }
+
+    Ok(summary)
 }
 
-/// Add files to an existing applied overlay.
+/// Whether two paths resolve to the same file, e.g. through a symlink.
+fn is_same_file(a: &std::path::Path, b: &std::path::Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Ask a yes/no question on stdin, defaulting to no.
+fn confirm(prompt: &str) -> Result<bool> {
+    print!("{prompt} [y/N] ");
+    io::stdout().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    Ok(input.trim().eq_ignore_ascii_case("y"))
+}
+
+/// Resolve `add` arguments to the individual files to add, relative to the target.
 ///
-/// This adds new files to an overlay that is already applied to the target repository.
-/// The files are linked to the overlay repo and the overlay state is updated.
-fn add_files_to_overlay(
-    name_arg: &str,
-    target: &std::path::Path,
-    files: &[PathBuf],
-    dry_run: bool,
+/// Directories are walked (skipping `.git` and symlinks, which may already be
+/// overlay files) and glob patterns are expanded like `create --include`.
+fn expand_add_paths(target: &std::path::Path, paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    use crate::ignore::is_glob;
+    use crate::{expand_include_patterns, target_relative_path};
+
+    let mut files: Vec<PathBuf> = Vec::new();
+    for path in paths {
+        let matched = if is_glob(&path.to_string_lossy()) {
+            expand_include_patterns(target, std::slice::from_ref(path))?
+        } else {
+            let rel = target_relative_path(target, path);
+            if !target.join(&rel).exists() {
+                bail!(
+                    "File does not exist: {}\n\n\
+                     Create the file first, then add it to the overlay.",
+                    path.display()
+                );
+            }
+            vec![rel]
+        };
+
+        for rel in matched {
+            let full_path = target.join(&rel);
+            if !full_path.is_dir() || full_path.is_symlink() {
+                files.push(rel);
+                continue;
+            }
+            for entry in walkdir::WalkDir::new(&full_path)
+                .sort_by_file_name()
+                .into_iter()
+                .filter_entry(|e| e.file_name() != ".git")
+                .filter_map(std::result::Result::ok)
+                .filter(|e| e.file_type().is_file())
+            {
+                files.push(entry.path().strip_prefix(target)?.to_path_buf());
+            }
+        }
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    files.retain(|f| seen.insert(f.clone()));
+
+    if files.is_empty() {
+        bail!("No files found to add");
+    }
+    Ok(files)
+}
+
+/// Add files to an existing applied overlay.
+///
+/// Directories and glob patterns are expanded to individual files, each added
+/// as its own entry.
+///
+/// This adds new files to an overlay that is already applied to the target repository.
+/// The files are linked to the overlay repo and the overlay state is updated.
+fn add_files_to_overlay(
+    name_arg: &str,
+    target: &std::path::Path,
+    files: &[PathBuf],
+    dry_run: bool,
+    commit: &CommitOptions,
 ) -> Result<()> {
     use crate::config::load_config;
     use crate::overlay_repo::OverlayRepoManager;
-    use crate::state::{EntryType, FileEntry, LinkType};
+    use crate::state::{EntryType, FileEntry, LinkType, conflict_key};
     use crate::{
         load_all_overlay_targets, load_overlay_state, normalize_overlay_name, save_external_state,
         save_overlay_state, update_git_exclude,
@@ -1472,25 +3800,19 @@ fn add_files_to_overlay(
     // Load existing overlay state
     let mut state = load_overlay_state(&target, &normalized_name)?;
 
-    // Validate all files exist
-    for file in files {
-        let full_path = target.join(file);
-        if !full_path.exists() {
-            bail!(
-                "File does not exist: {}\n\n\
-                 Create the file first, then add it to the overlay.",
-                file.display()
-            );
-        }
-    }
+    // Expand directories and glob patterns into individual files
+    let files = &expand_add_paths(&target, files)?;
 
     // Load all existing overlay targets to check for conflicts
-    let existing_targets = load_all_overlay_targets(&target)?;
+    let case_insensitive = crate::is_case_insensitive_fs(&target);
+    let existing_targets = load_all_overlay_targets(&target, case_insensitive)?;
 
     // Check that files aren't already managed by an overlay
     for file in files {
         let file_str = file.to_string_lossy().replace('\\', "/");
-        if let Some(other_overlay) = existing_targets.get(&file_str) {
+        if let Some(other_overlay) =
+            existing_targets.get(&conflict_key(&file_str, case_insensitive))
+        {
             bail!(
                 "File '{}' is already managed by overlay '{}'.\n\
                  Remove it from that overlay first.",
@@ -1500,33 +3822,31 @@ fn add_files_to_overlay(
         }
     }
 
-    println!(
+    status!(
         "{} files to overlay: {}",
         "Adding".blue().bold(),
         overlay_name
     );
 
     if dry_run {
-        println!("  Target: {}", target.display());
-        println!("\n{} Dry run - no changes made.", "Note:".yellow());
-        println!("\nFiles that would be added:");
+        status!("  Target: {}", target.display());
+        status!("\n{} Dry run - no changes made.", "Note:".yellow());
+        status!("\nFiles that would be added:");
         for file in files {
-            println!("  {} {}", "+".green(), file.display());
+            status!("  {} {}", "+".green(), file.display());
         }
         return Ok(());
     }
 
-    // Load overlay repo config
+    // Load the overlay repo it was applied from
     let config = load_config(None)?;
-    let overlay_config = config.overlay_repo.ok_or_else(|| {
-        anyhow::anyhow!(
-            "Overlay repository not configured.\n\n\
-             Run 'repoverlay source add <url>' to set up an overlay source."
-        )
-    })?;
-
-    // Create manager and ensure cloned
-    let manager = OverlayRepoManager::new(overlay_config)?;
+    let manager = OverlayRepoManager::configured(&config, state.source.source_filter())?
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Overlay repository not configured.\n\n\
+                 Run 'repoverlay source add <url>' to set up an overlay source."
+            )
+        })?;
     manager.ensure_cloned()?;
 
     // Get the overlay path in the overlay repo
@@ -1539,12 +3859,7 @@ fn add_files_to_overlay(
         );
     }
 
-    // Determine link type (symlink unless on Windows)
-    let link_type = if cfg!(windows) {
-        LinkType::Copy
-    } else {
-        LinkType::Symlink
-    };
+    let link_type = crate::resolve_link_type(false)?;
 
     let mut exclude_entries: Vec<String> = Vec::new();
     let mut added_count = 0;
@@ -1588,13 +3903,16 @@ fn add_files_to_overlay(
             target: file.clone(),
             link_type,
             entry_type: EntryType::File,
+            backup: None,
+            sha256: None,
+            rendered: false,
         });
 
         // Add to exclude list
         let exclude_path = file.to_string_lossy().replace('\\', "/");
         exclude_entries.push(exclude_path);
 
-        println!("  {} {}", "+".green(), file.display());
+        status!("  {} {}", "+".green(), file.display());
         added_count += 1;
     }
 
@@ -1613,7 +3931,7 @@ fn add_files_to_overlay(
         );
     }
 
-    println!(
+    status!(
         "\n{} Added {} file(s) to overlay '{}'",
         "✓".green().bold(),
         added_count,
@@ -1621,7 +3939,15 @@ fn add_files_to_overlay(
     );
 
     // Auto-commit to overlay repo
-    auto_commit_overlay(&manager, &org, &repo, &overlay_name, false)?;
+    auto_commit_overlay(
+        &manager,
+        &org,
+        &repo,
+        &overlay_name,
+        OverlayChange::Update,
+        commit,
+        &config.commit,
+    )?;
 
     Ok(())
 }
@@ -1724,12 +4050,7 @@ mod tests {
             let result = apply_overlay(
                 overlay.path().to_str().unwrap(),
                 repo.path(),
-                false,
-                None,
-                None,
-                false,
-                None,
-                false,
+                &ApplyOptions::default(),
             );
             assert!(result.is_ok(), "apply_overlay failed: {result:?}");
 
@@ -1758,12 +4079,7 @@ mod tests {
             let result = apply_overlay(
                 overlay.path().to_str().unwrap(),
                 repo.path(),
-                false,
-                None,
-                None,
-                false,
-                None,
-                false,
+                &ApplyOptions::default(),
             );
             assert!(result.is_ok());
 
@@ -1771,6 +4087,56 @@ mod tests {
             assert!(repo.path().join(".vscode/settings.json").exists());
         }
 
+        #[test]
+        fn stores_description_and_tags_from_config() {
+            let repo = create_test_repo();
+            let overlay = create_test_overlay(&[
+                (".envrc", "export FOO=bar"),
+                (
+                    "repoverlay.ccl",
+                    "overlay =\n  name = tagged\n  description = Env setup\n  tags =\n    = env\n",
+                ),
+            ]);
+
+            apply_overlay(
+                overlay.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions::default(),
+            )
+            .unwrap();
+
+            let state = crate::load_overlay_state(repo.path(), "tagged").unwrap();
+            assert_eq!(state.description.as_deref(), Some("Env setup"));
+            assert_eq!(state.tags, vec!["env"]);
+            assert!(show_status(repo.path(), None, false, false, false, false).is_ok());
+        }
+
+        #[test]
+        #[cfg(unix)]
+        fn copy_mode_preserves_executable_bit() {
+            use std::os::unix::fs::PermissionsExt;
+
+            let repo = create_test_repo();
+            let overlay = create_test_overlay(&[(".husky/pre-commit", "#!/bin/sh\nexit 0\n")]);
+            let hook = overlay.path().join(".husky/pre-commit");
+            fs::set_permissions(&hook, fs::Permissions::from_mode(0o755)).unwrap();
+
+            apply_overlay(
+                overlay.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    force_copy: true,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+            let target_file = repo.path().join(".husky/pre-commit");
+            assert!(!target_file.is_symlink());
+            let mode = fs::metadata(&target_file).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o755);
+        }
+
         #[test]
         fn applies_with_copy_mode() {
             let repo = create_test_repo();
@@ -1779,12 +4145,10 @@ mod tests {
             let result = apply_overlay(
                 overlay.path().to_str().unwrap(),
                 repo.path(),
-                true, // copy mode
-                None,
-                None,
-                false,
-                None,
-                false,
+                &ApplyOptions {
+                    force_copy: true,
+                    ..Default::default()
+                },
             );
             assert!(result.is_ok());
 
@@ -1804,12 +4168,7 @@ mod tests {
             apply_overlay(
                 overlay.path().to_str().unwrap(),
                 repo.path(),
-                false,
-                None,
-                None,
-                false,
-                None,
-                false,
+                &ApplyOptions::default(),
             )
             .unwrap();
 
@@ -1842,12 +4201,7 @@ mod tests {
             apply_overlay(
                 overlay.path().to_str().unwrap(),
                 repo.path(),
-                false,
-                None,
-                None,
-                false,
-                None,
-                false,
+                &ApplyOptions::default(),
             )
             .unwrap();
 
@@ -1874,12 +4228,7 @@ mod tests {
             apply_overlay(
                 overlay.path().to_str().unwrap(),
                 repo.path(),
-                false,
-                None,
-                None,
-                false,
-                None,
-                false,
+                &ApplyOptions::default(),
             )
             .unwrap();
 
@@ -1898,12 +4247,10 @@ mod tests {
             apply_overlay(
                 overlay.path().to_str().unwrap(),
                 repo.path(),
-                false,
-                Some("custom-name".to_string()),
-                None,
-                false,
-                None,
-                false,
+                &ApplyOptions {
+                    name: Some("custom-name".to_string()),
+                    ..Default::default()
+                },
             )
             .unwrap();
 
@@ -1919,12 +4266,7 @@ mod tests {
             let result = apply_overlay(
                 overlay.path().to_str().unwrap(),
                 dir.path(),
-                false,
-                None,
-                None,
-                false,
-                None,
-                false,
+                &ApplyOptions::default(),
             );
             assert!(result.is_err());
             assert!(
@@ -1944,24 +4286,20 @@ mod tests {
             apply_overlay(
                 overlay1.path().to_str().unwrap(),
                 repo.path(),
-                false,
-                Some("my-overlay".to_string()),
-                None,
-                false,
-                None,
-                false,
+                &ApplyOptions {
+                    name: Some("my-overlay".to_string()),
+                    ..Default::default()
+                },
             )
             .unwrap();
 
             let result = apply_overlay(
                 overlay2.path().to_str().unwrap(),
                 repo.path(),
-                false,
-                Some("my-overlay".to_string()),
-                None,
-                false,
-                None,
-                false,
+                &ApplyOptions {
+                    name: Some("my-overlay".to_string()),
+                    ..Default::default()
+                },
             );
             assert!(result.is_err());
             assert!(result.unwrap_err().to_string().contains("already applied"));
@@ -1977,12 +4315,7 @@ mod tests {
             let result = apply_overlay(
                 overlay.path().to_str().unwrap(),
                 repo.path(),
-                false,
-                None,
-                None,
-                false,
-                None,
-                false,
+                &ApplyOptions::default(),
             );
             assert!(result.is_err());
             assert!(result.unwrap_err().to_string().contains("Conflict"));
@@ -1997,24 +4330,20 @@ mod tests {
             apply_overlay(
                 overlay1.path().to_str().unwrap(),
                 repo.path(),
-                false,
-                Some("first".to_string()),
-                None,
-                false,
-                None,
-                false,
+                &ApplyOptions {
+                    name: Some("first".to_string()),
+                    ..Default::default()
+                },
             )
             .unwrap();
 
             let result = apply_overlay(
                 overlay2.path().to_str().unwrap(),
                 repo.path(),
-                false,
-                Some("second".to_string()),
-                None,
-                false,
-                None,
-                false,
+                &ApplyOptions {
+                    name: Some("second".to_string()),
+                    ..Default::default()
+                },
             );
             assert!(result.is_err());
             let err = result.unwrap_err().to_string();
@@ -2022,288 +4351,455 @@ mod tests {
         }
 
         #[test]
-        fn fails_on_empty_overlay() {
+        fn force_backs_up_conflicting_repo_file() {
             let repo = create_test_repo();
-            let overlay = TempDir::new().unwrap();
+            fs::write(repo.path().join(".envrc"), "existing content").unwrap();
 
-            let result = apply_overlay(
+            let overlay = create_test_overlay(&[(".envrc", "new content")]);
+
+            apply_overlay(
                 overlay.path().to_str().unwrap(),
                 repo.path(),
-                false,
-                None,
-                None,
-                false,
-                None,
-                false,
+                &ApplyOptions {
+                    name: Some("test".to_string()),
+                    on_conflict: OnConflict::Backup,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+            assert_eq!(
+                fs::read_to_string(repo.path().join(".envrc")).unwrap(),
+                "new content"
+            );
+            let backup = repo.path().join(".repoverlay/backups/test/.envrc");
+            assert_eq!(fs::read_to_string(&backup).unwrap(), "existing content");
+
+            let state = crate::state::load_overlay_state(repo.path(), "test").unwrap();
+            assert_eq!(
+                state.file_entries()[0].backup,
+                Some(PathBuf::from(".repoverlay/backups/test/.envrc"))
             );
-            assert!(result.is_err());
-            assert!(result.unwrap_err().to_string().contains("No files found"));
         }
 
         #[test]
-        fn fails_on_nonexistent_source() {
+        fn force_still_fails_on_conflict_between_overlays() {
             let repo = create_test_repo();
+            let overlay1 = create_test_overlay(&[(".envrc", "first")]);
+            let overlay2 = create_test_overlay(&[(".envrc", "second")]);
+
+            apply_overlay(
+                overlay1.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    name: Some("first".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
             let result = apply_overlay(
-                "/nonexistent/path",
+                overlay2.path().to_str().unwrap(),
                 repo.path(),
-                false,
-                None,
-                None,
-                false,
-                None,
-                false,
+                &ApplyOptions {
+                    name: Some("second".to_string()),
+                    on_conflict: OnConflict::Backup,
+                    ..Default::default()
+                },
             );
             assert!(result.is_err());
+            assert!(result.unwrap_err().to_string().contains("already managed"));
+            assert!(!repo.path().join(".repoverlay/backups").exists());
         }
 
+        #[cfg(unix)]
         #[test]
-        fn applies_directory_symlink() {
+        fn reuses_existing_symlink_to_same_source() {
             let repo = create_test_repo();
-            let overlay = TempDir::new().unwrap();
+            let overlay = create_test_overlay(&[(".envrc", "content"), (".editorconfig", "root")]);
 
-            // Create a directory with files inside
-            fs::create_dir_all(overlay.path().join("scratch")).unwrap();
-            fs::write(overlay.path().join("scratch/notes.txt"), "notes").unwrap();
-            fs::write(overlay.path().join("scratch/todo.md"), "# TODO").unwrap();
+            // Left behind by an apply that failed before saving state
+            let source = overlay.path().canonicalize().unwrap().join(".envrc");
+            std::os::unix::fs::symlink(&source, repo.path().join(".envrc")).unwrap();
 
-            // Create config with directories list
-            fs::write(
-                overlay.path().join("repoverlay.ccl"),
-                "overlay =\n  name = test-overlay\n\ndirectories =\n  = scratch\n",
+            apply_overlay(
+                overlay.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    name: Some("test".to_string()),
+                    ..Default::default()
+                },
             )
             .unwrap();
 
-            let result = apply_overlay(
-                overlay.path().to_str().unwrap(),
-                repo.path(),
-                false,
-                None,
-                None,
-                false,
-                None,
-                false,
-            );
-            assert!(result.is_ok(), "apply_overlay failed: {result:?}");
+            let state = crate::state::load_overlay_state(repo.path(), "test").unwrap();
+            assert_eq!(state.file_count(), 2);
+            assert!(state.file_entries().iter().all(|e| e.backup.is_none()));
+            let exclude = fs::read_to_string(repo.path().join(".git/info/exclude")).unwrap();
+            assert!(exclude.contains(".envrc"));
+            assert!(repo.path().join(".editorconfig").is_symlink());
+        }
 
-            // Check directory symlink was created
-            let target_dir = repo.path().join("scratch");
-            assert!(target_dir.exists(), "scratch should exist");
-            assert!(
-                target_dir.is_symlink(),
-                "scratch should be a symlink to directory"
-            );
+        #[cfg(unix)]
+        #[test]
+        fn preserves_symlinks_in_source() {
+            let repo = create_test_repo();
+            let overlay = create_test_overlay(&[("shared/config.json", "{}")]);
+            std::os::unix::fs::symlink("shared/config.json", overlay.path().join("link.json"))
+                .unwrap();
 
-            // Check files inside are accessible
-            assert!(target_dir.join("notes.txt").exists());
-            assert!(target_dir.join("todo.md").exists());
+            for copy in [false, true] {
+                apply_overlay(
+                    overlay.path().to_str().unwrap(),
+                    repo.path(),
+                    &ApplyOptions {
+                        force_copy: copy,
+                        name: Some("test".to_string()),
+                        ..Default::default()
+                    },
+                )
+                .unwrap();
 
-            // Check content is correct
-            let content = fs::read_to_string(target_dir.join("notes.txt")).unwrap();
-            assert_eq!(content, "notes");
+                let link = repo.path().join("link.json");
+                assert_eq!(
+                    fs::read_link(&link).unwrap(),
+                    PathBuf::from("shared/config.json")
+                );
+                assert_eq!(fs::read_to_string(&link).unwrap(), "{}");
+
+                let state = crate::state::load_overlay_state(repo.path(), "test").unwrap();
+                let entry = state
+                    .file_entries()
+                    .iter()
+                    .find(|e| e.target == std::path::Path::new("link.json"))
+                    .unwrap();
+                assert_eq!(entry.entry_type, crate::state::EntryType::Symlink);
+
+                remove_overlay(repo.path(), Some("test".to_string()), false, false, false).unwrap();
+                assert!(!link.is_symlink());
+            }
         }
 
         #[test]
-        fn applies_directory_with_copy_mode() {
+        fn renders_templates_with_config_and_cli_variables() {
             let repo = create_test_repo();
-            let overlay = TempDir::new().unwrap();
-
-            // Create a directory with files inside
-            fs::create_dir_all(overlay.path().join("scratch")).unwrap();
-            fs::write(overlay.path().join("scratch/notes.txt"), "notes").unwrap();
+            let overlay = create_test_overlay(&[
+                (
+                    "repoverlay.ccl",
+                    "variables =\n  project = demo\n  author = Ada\n",
+                ),
+                (".envrc.tmpl", "export PROJECT={{ project }} # {{ author }}"),
+                ("README.md", "plain {{ project }}"),
+            ]);
 
-            // Create config with directories list
-            fs::write(
-                overlay.path().join("repoverlay.ccl"),
-                "overlay =\n  name = test-overlay\n\ndirectories =\n  = scratch\n",
-            )
-            .unwrap();
-
-            let result = apply_overlay(
+            let variables: BTreeMap<String, String> =
+                [("author".to_string(), "Grace".to_string())].into();
+            apply_overlay(
                 overlay.path().to_str().unwrap(),
                 repo.path(),
-                true, // copy mode
-                None,
-                None,
-                false,
-                None,
-                false,
-            );
-            assert!(result.is_ok(), "apply_overlay failed: {result:?}");
+                &ApplyOptions {
+                    name: Some("test".to_string()),
+                    variables: variables.clone(),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
 
-            // Check directory was copied (not symlinked)
-            let target_dir = repo.path().join("scratch");
-            assert!(target_dir.exists(), "scratch should exist");
-            assert!(
-                !target_dir.is_symlink(),
-                "scratch should NOT be a symlink in copy mode"
+            let envrc = repo.path().join(".envrc");
+            assert!(!envrc.is_symlink());
+            assert_eq!(
+                fs::read_to_string(&envrc).unwrap(),
+                "export PROJECT=demo # Grace"
             );
-            assert!(target_dir.is_dir(), "scratch should be a directory");
+            assert!(!repo.path().join(".envrc.tmpl").exists());
+            // Symlinked files can't be templated
+            assert!(repo.path().join("README.md").is_symlink());
+
+            let state = crate::state::load_overlay_state(repo.path(), "test").unwrap();
+            assert_eq!(state.variables, variables);
+            let entry = state
+                .file_entries()
+                .iter()
+                .find(|e| e.target == std::path::Path::new(".envrc"))
+                .unwrap();
+            assert!(entry.rendered);
+            assert_eq!(entry.link_type, crate::state::LinkType::Copy);
+            assert_eq!(entry.source, PathBuf::from(".envrc.tmpl"));
 
-            // Check files inside are accessible
-            assert!(target_dir.join("notes.txt").exists());
+            remove_overlay(repo.path(), Some("test".to_string()), false, false, false).unwrap();
+            assert!(!envrc.exists());
         }
 
         #[test]
-        fn directory_symlink_skips_files_inside() {
+        fn renders_files_using_variables_in_copy_mode() {
             let repo = create_test_repo();
-            let overlay = TempDir::new().unwrap();
-
-            // Create a directory with files and a standalone file
-            fs::create_dir_all(overlay.path().join("scratch")).unwrap();
-            fs::write(overlay.path().join("scratch/notes.txt"), "notes").unwrap();
-            fs::write(overlay.path().join(".envrc"), "export FOO=bar").unwrap();
+            let overlay = create_test_overlay(&[
+                ("repoverlay.ccl", "variables =\n  project = demo\n"),
+                ("config.yml", "name: {{ project }}\nsha: ${{ github.sha }}"),
+                ("plain.txt", "no variables here"),
+            ]);
 
-            // Create config with directories list
-            fs::write(
-                overlay.path().join("repoverlay.ccl"),
-                "overlay =\n  name = test-overlay\n\ndirectories =\n  = scratch\n",
+            apply_overlay(
+                overlay.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    force_copy: true,
+                    name: Some("test".to_string()),
+                    ..Default::default()
+                },
             )
             .unwrap();
 
+            assert_eq!(
+                fs::read_to_string(repo.path().join("config.yml")).unwrap(),
+                "name: demo\nsha: ${{ github.sha }}"
+            );
+            let state = crate::state::load_overlay_state(repo.path(), "test").unwrap();
+            let rendered: Vec<_> = state
+                .file_entries()
+                .iter()
+                .filter(|e| e.rendered)
+                .map(|e| e.target.clone())
+                .collect();
+            assert_eq!(rendered, vec![PathBuf::from("config.yml")]);
+
+            // The rendered copy is in sync with its template
+            assert!(!crate::diff_overlays(repo.path(), None).unwrap());
+        }
+
+        #[test]
+        fn fails_on_undefined_template_variable() {
+            let repo = create_test_repo();
+            let overlay = create_test_overlay(&[(".envrc.tmpl", "export X={{ missing }}")]);
+
             let result = apply_overlay(
                 overlay.path().to_str().unwrap(),
                 repo.path(),
-                false,
-                None,
-                None,
-                false,
-                None,
-                false,
+                &ApplyOptions {
+                    name: Some("test".to_string()),
+                    ..Default::default()
+                },
             );
-            assert!(result.is_ok(), "apply_overlay failed: {result:?}");
 
-            // Check directory symlink was created
-            let target_dir = repo.path().join("scratch");
-            assert!(target_dir.is_symlink(), "scratch should be a symlink");
+            let err = format!("{:#}", result.unwrap_err());
+            assert!(
+                err.contains("Undefined template variable 'missing'"),
+                "{err}"
+            );
+            assert!(!repo.path().join(".envrc").exists());
+        }
 
-            // Check standalone file was also symlinked
-            let envrc = repo.path().join(".envrc");
-            assert!(envrc.is_symlink(), ".envrc should be a symlink");
+        #[test]
+        fn apply_parses_set_variables() {
+            let cli = Cli::try_parse_from([
+                "repoverlay",
+                "apply",
+                "./o",
+                "--set",
+                "project=demo",
+                "--set",
+                "author=Ada",
+            ])
+            .unwrap();
+
+            match cli.command {
+                Some(Commands::Apply { set, .. }) => {
+                    assert_eq!(
+                        set,
+                        vec![
+                            ("project".to_string(), "demo".to_string()),
+                            ("author".to_string(), "Ada".to_string()),
+                        ]
+                    );
+                }
+                _ => panic!("Expected Apply command"),
+            }
+            assert!(Cli::try_parse_from(["repoverlay", "apply", "./o", "--set", "x"]).is_err());
         }
 
         #[test]
-        fn directory_symlink_warns_on_nonexistent() {
-            let repo = create_test_repo();
-            let overlay = TempDir::new().unwrap();
+        fn apply_parses_repeated_only() {
+            let cli = Cli::try_parse_from([
+                "repoverlay",
+                "apply",
+                "./o",
+                "--only",
+                ".envrc",
+                "--only",
+                ".vscode/*",
+            ])
+            .unwrap();
 
-            // Create a regular file so overlay isn't empty
-            fs::write(overlay.path().join(".envrc"), "export FOO=bar").unwrap();
+            match cli.command {
+                Some(Commands::Apply { only, .. }) => {
+                    assert_eq!(only, vec![".envrc".to_string(), ".vscode/*".to_string()]);
+                }
+                _ => panic!("Expected Apply command"),
+            }
+        }
 
-            // Create config with directories list including non-existent directory
-            fs::write(
-                overlay.path().join("repoverlay.ccl"),
-                "overlay =\n  name = test-overlay\n\ndirectories =\n  = nonexistent\n",
+        #[cfg(unix)]
+        #[test]
+        fn skips_source_symlinks_that_loop_back() {
+            let repo = create_test_repo();
+            let overlay = create_test_overlay(&[("nested/.envrc", "content")]);
+            std::os::unix::fs::symlink("..", overlay.path().join("nested/parent")).unwrap();
+
+            apply_overlay(
+                overlay.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    name: Some("test".to_string()),
+                    ..Default::default()
+                },
             )
             .unwrap();
 
+            assert!(repo.path().join("nested/.envrc").exists());
+            assert!(!repo.path().join("nested/parent").is_symlink());
+        }
+
+        #[cfg(unix)]
+        #[test]
+        fn symlink_to_other_source_is_a_conflict() {
+            let repo = create_test_repo();
+            let overlay = create_test_overlay(&[(".envrc", "content")]);
+            let other = create_test_overlay(&[(".envrc", "other")]);
+
+            std::os::unix::fs::symlink(other.path().join(".envrc"), repo.path().join(".envrc"))
+                .unwrap();
+
             let result = apply_overlay(
                 overlay.path().to_str().unwrap(),
                 repo.path(),
-                false,
-                None,
-                None,
-                false,
-                None,
-                false,
+                &ApplyOptions {
+                    name: Some("test".to_string()),
+                    ..Default::default()
+                },
             );
-            // Should succeed (just warns about missing directory)
-            assert!(result.is_ok(), "apply_overlay failed: {result:?}");
-
-            // Check regular file was still symlinked
-            assert!(repo.path().join(".envrc").is_symlink());
-
-            // Non-existent directory should not be created
-            assert!(!repo.path().join("nonexistent").exists());
+            assert!(result.unwrap_err().to_string().contains("already exists"));
         }
 
         #[test]
-        fn directory_conflict_with_existing_path() {
+        fn on_conflict_skip_leaves_repo_file() {
             let repo = create_test_repo();
+            fs::write(repo.path().join(".envrc"), "existing content").unwrap();
 
-            // Create a directory in the repo that conflicts
-            fs::create_dir_all(repo.path().join("scratch")).unwrap();
-            fs::write(repo.path().join("scratch/existing.txt"), "existing").unwrap();
+            let overlay =
+                create_test_overlay(&[(".envrc", "new content"), (".editorconfig", "root")]);
 
-            let overlay = TempDir::new().unwrap();
-            fs::create_dir_all(overlay.path().join("scratch")).unwrap();
-            fs::write(overlay.path().join("scratch/notes.txt"), "notes").unwrap();
-            fs::write(
-                overlay.path().join("repoverlay.ccl"),
-                "overlay =\n  name = test-overlay\n\ndirectories =\n  = scratch\n",
+            apply_overlay(
+                overlay.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    name: Some("test".to_string()),
+                    on_conflict: OnConflict::Skip,
+                    ..Default::default()
+                },
             )
             .unwrap();
 
+            assert_eq!(
+                fs::read_to_string(repo.path().join(".envrc")).unwrap(),
+                "existing content"
+            );
+            assert!(repo.path().join(".editorconfig").is_symlink());
+
+            let state = crate::state::load_overlay_state(repo.path(), "test").unwrap();
+            assert_eq!(state.file_count(), 1);
+            assert_eq!(
+                state.file_entries()[0].target,
+                PathBuf::from(".editorconfig")
+            );
+        }
+
+        #[test]
+        fn on_conflict_skip_fails_when_every_file_conflicts() {
+            let repo = create_test_repo();
+            fs::write(repo.path().join(".envrc"), "existing content").unwrap();
+
+            let overlay = create_test_overlay(&[(".envrc", "new content")]);
+
             let result = apply_overlay(
                 overlay.path().to_str().unwrap(),
                 repo.path(),
-                false,
-                None,
-                None,
-                false,
-                None,
-                false,
+                &ApplyOptions {
+                    name: Some("test".to_string()),
+                    on_conflict: OnConflict::Skip,
+                    ..Default::default()
+                },
             );
-
-            assert!(result.is_err());
-            assert!(result.unwrap_err().to_string().contains("already exists"));
+            assert!(result.unwrap_err().to_string().contains("Nothing to apply"));
+            assert!(!repo.path().join(".repoverlay/overlays/test.ccl").exists());
         }
 
         #[test]
-        fn directory_conflict_with_existing_overlay() {
+        fn on_conflict_overwrite_replaces_without_backup() {
             let repo = create_test_repo();
+            fs::write(repo.path().join(".envrc"), "existing content").unwrap();
 
-            // Apply first overlay with a directory
-            let overlay1 = TempDir::new().unwrap();
-            fs::create_dir_all(overlay1.path().join("scratch")).unwrap();
-            fs::write(overlay1.path().join("scratch/notes.txt"), "notes").unwrap();
-            fs::write(
-                overlay1.path().join("repoverlay.ccl"),
-                "overlay =\n  name = overlay-a\n\ndirectories =\n  = scratch\n",
-            )
-            .unwrap();
+            let overlay = create_test_overlay(&[(".envrc", "new content")]);
 
             apply_overlay(
-                overlay1.path().to_str().unwrap(),
+                overlay.path().to_str().unwrap(),
                 repo.path(),
-                false,
-                None,
-                None,
-                false,
-                None,
-                false,
+                &ApplyOptions {
+                    name: Some("test".to_string()),
+                    on_conflict: OnConflict::Overwrite,
+                    ..Default::default()
+                },
             )
             .unwrap();
 
-            // Try to apply second overlay with same directory
-            let overlay2 = TempDir::new().unwrap();
-            fs::create_dir_all(overlay2.path().join("scratch")).unwrap();
-            fs::write(overlay2.path().join("scratch/other.txt"), "other").unwrap();
-            fs::write(
-                overlay2.path().join("repoverlay.ccl"),
-                "overlay =\n  name = overlay-b\n\ndirectories =\n  = scratch\n",
+            assert_eq!(
+                fs::read_to_string(repo.path().join(".envrc")).unwrap(),
+                "new content"
+            );
+            assert!(!repo.path().join(".repoverlay/backups").exists());
+
+            let state = crate::state::load_overlay_state(repo.path(), "test").unwrap();
+            assert!(state.file_entries()[0].backup.is_none());
+
+            // Removing the overlay doesn't bring the original back
+            remove_overlay(repo.path(), Some("test".to_string()), false, false, false).unwrap();
+            assert!(!repo.path().join(".envrc").exists());
+        }
+
+        #[test]
+        fn on_conflict_skip_still_fails_on_conflict_between_overlays() {
+            let repo = create_test_repo();
+            let overlay1 = create_test_overlay(&[(".envrc", "first")]);
+            let overlay2 = create_test_overlay(&[(".envrc", "second")]);
+
+            apply_overlay(
+                overlay1.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    name: Some("first".to_string()),
+                    ..Default::default()
+                },
             )
             .unwrap();
 
             let result = apply_overlay(
                 overlay2.path().to_str().unwrap(),
                 repo.path(),
-                false,
-                None,
-                None,
-                false,
-                None,
-                false,
+                &ApplyOptions {
+                    name: Some("second".to_string()),
+                    on_conflict: OnConflict::Skip,
+                    ..Default::default()
+                },
             );
-
-            assert!(result.is_err());
             assert!(result.unwrap_err().to_string().contains("already managed"));
         }
 
         #[test]
-        fn directory_symlink_updates_git_exclude_with_trailing_slash() {
+        fn force_backs_up_conflicting_directory() {
             let repo = create_test_repo();
-            let overlay = TempDir::new().unwrap();
+            fs::create_dir_all(repo.path().join("scratch")).unwrap();
+            fs::write(repo.path().join("scratch/existing.txt"), "existing").unwrap();
 
+            let overlay = TempDir::new().unwrap();
             fs::create_dir_all(overlay.path().join("scratch")).unwrap();
             fs::write(overlay.path().join("scratch/notes.txt"), "notes").unwrap();
             fs::write(
@@ -2315,761 +4811,2245 @@ mod tests {
             apply_overlay(
                 overlay.path().to_str().unwrap(),
                 repo.path(),
-                false,
-                None,
-                None,
-                false,
-                None,
-                false,
+                &ApplyOptions {
+                    on_conflict: OnConflict::Backup,
+                    ..Default::default()
+                },
             )
             .unwrap();
 
-            let exclude_path = repo.path().join(".git/info/exclude");
-            let content = fs::read_to_string(&exclude_path).unwrap();
-
-            // Directory should have trailing slash in exclude
-            assert!(content.contains("scratch/"));
+            assert!(repo.path().join("scratch/notes.txt").exists());
+            assert!(!repo.path().join("scratch/existing.txt").exists());
+            assert!(
+                repo.path()
+                    .join(".repoverlay/backups/test-overlay/scratch/existing.txt")
+                    .exists()
+            );
         }
 
         #[test]
-        fn directory_path_not_a_directory_warning() {
+        fn fails_on_empty_overlay() {
             let repo = create_test_repo();
             let overlay = TempDir::new().unwrap();
 
-            // Create a file (not directory) that matches the directory name
-            fs::write(overlay.path().join("scratch"), "this is a file").unwrap();
-            fs::write(overlay.path().join(".envrc"), "export FOO=bar").unwrap();
-            fs::write(
-                overlay.path().join("repoverlay.ccl"),
-                "overlay =\n  name = test-overlay\n\ndirectories =\n  = scratch\n",
-            )
-            .unwrap();
-
-            // Should succeed (just warns about scratch not being a directory)
             let result = apply_overlay(
                 overlay.path().to_str().unwrap(),
                 repo.path(),
-                false,
-                None,
-                None,
-                false,
-                None,
-                false,
+                &ApplyOptions::default(),
             );
-            assert!(result.is_ok());
+            assert!(result.is_err());
+            assert!(result.unwrap_err().to_string().contains("No files found"));
+        }
 
-            // Regular file should still be symlinked
-            assert!(repo.path().join(".envrc").is_symlink());
-            // scratch as a directory symlink should not exist (it was a file in overlay)
-            assert!(!repo.path().join("scratch").is_symlink());
+        #[test]
+        fn fails_on_nonexistent_source() {
+            let repo = create_test_repo();
+            let result = apply_overlay("/nonexistent/path", repo.path(), &ApplyOptions::default());
+            assert!(result.is_err());
         }
 
         #[test]
-        #[ignore = "tylerbutler/santa#71: forward slashes in map keys cause parsing errors in sickle"]
-        fn mapping_supports_nested_paths_in_key_and_value() {
+        fn applies_directory_symlink() {
             let repo = create_test_repo();
             let overlay = TempDir::new().unwrap();
 
-            // Create nested source file
-            fs::create_dir_all(overlay.path().join("config")).unwrap();
-            fs::write(overlay.path().join("config/settings.json"), "{}").unwrap();
+            // Create a directory with files inside
+            fs::create_dir_all(overlay.path().join("scratch")).unwrap();
+            fs::write(overlay.path().join("scratch/notes.txt"), "notes").unwrap();
+            fs::write(overlay.path().join("scratch/todo.md"), "# TODO").unwrap();
 
-            // Map nested source path to nested destination path (forward slashes in both)
+            // Create config with directories list
             fs::write(
                 overlay.path().join("repoverlay.ccl"),
-                r"mappings =
-  config/settings.json = .vscode/settings.json
-",
+                "overlay =\n  name = test-overlay\n\ndirectories =\n  = scratch\n",
             )
             .unwrap();
 
             let result = apply_overlay(
                 overlay.path().to_str().unwrap(),
                 repo.path(),
+                &ApplyOptions::default(),
+            );
+            assert!(result.is_ok(), "apply_overlay failed: {result:?}");
+
+            // Check directory symlink was created
+            let target_dir = repo.path().join("scratch");
+            assert!(target_dir.exists(), "scratch should exist");
+            assert!(
+                target_dir.is_symlink(),
+                "scratch should be a symlink to directory"
+            );
+
+            // Check files inside are accessible
+            assert!(target_dir.join("notes.txt").exists());
+            assert!(target_dir.join("todo.md").exists());
+
+            // Check content is correct
+            let content = fs::read_to_string(target_dir.join("notes.txt")).unwrap();
+            assert_eq!(content, "notes");
+        }
+
+        #[test]
+        fn merge_directory_links_individual_files() {
+            let repo = create_test_repo();
+            let overlay = TempDir::new().unwrap();
+            fs::create_dir_all(overlay.path().join(".vscode")).unwrap();
+            fs::write(overlay.path().join(".vscode/settings.json"), "{}").unwrap();
+            fs::write(
+                overlay.path().join("repoverlay.ccl"),
+                "overlay =\n  name = test-overlay\n\ndirectories =\n  =\n    path = .vscode\n    mode = merge\n",
+            )
+            .unwrap();
+
+            apply_overlay(
+                overlay.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions::default(),
+            )
+            .unwrap();
+
+            let target_dir = repo.path().join(".vscode");
+            assert!(!target_dir.is_symlink());
+            assert!(target_dir.join("settings.json").is_symlink());
+
+            let state = crate::load_overlay_state(repo.path(), "test-overlay").unwrap();
+            assert_eq!(state.file_count(), 1);
+            assert_eq!(
+                state.file_entries()[0].entry_type,
+                crate::state::EntryType::File
+            );
+
+            // The repo can keep its own files next to the overlay's
+            fs::write(target_dir.join("launch.json"), "{}").unwrap();
+            remove_overlay(
+                repo.path(),
+                Some("test-overlay".to_string()),
                 false,
-                None,
-                None,
                 false,
-                None,
                 false,
+            )
+            .unwrap();
+
+            assert!(!target_dir.join("settings.json").exists());
+            assert!(target_dir.join("launch.json").exists());
+        }
+
+        #[test]
+        fn applies_directory_with_copy_mode() {
+            let repo = create_test_repo();
+            let overlay = TempDir::new().unwrap();
+
+            // Create a directory with files inside
+            fs::create_dir_all(overlay.path().join("scratch")).unwrap();
+            fs::write(overlay.path().join("scratch/notes.txt"), "notes").unwrap();
+
+            // Create config with directories list
+            fs::write(
+                overlay.path().join("repoverlay.ccl"),
+                "overlay =\n  name = test-overlay\n\ndirectories =\n  = scratch\n",
+            )
+            .unwrap();
+
+            let result = apply_overlay(
+                overlay.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    force_copy: true,
+                    ..Default::default()
+                },
             );
             assert!(result.is_ok(), "apply_overlay failed: {result:?}");
 
-            // Check that mapping was applied (file at nested destination)
+            // Check directory was copied (not symlinked)
+            let target_dir = repo.path().join("scratch");
+            assert!(target_dir.exists(), "scratch should exist");
             assert!(
-                repo.path().join(".vscode/settings.json").exists(),
-                "mapped target should exist at nested path"
+                !target_dir.is_symlink(),
+                "scratch should NOT be a symlink in copy mode"
             );
-            assert!(
-                !repo.path().join("config/settings.json").exists(),
-                "original path should not exist when mapped"
+            assert!(target_dir.is_dir(), "scratch should be a directory");
+
+            // Check files inside are accessible
+            assert!(target_dir.join("notes.txt").exists());
+        }
+
+        #[test]
+        fn directory_symlink_skips_files_inside() {
+            let repo = create_test_repo();
+            let overlay = TempDir::new().unwrap();
+
+            // Create a directory with files and a standalone file
+            fs::create_dir_all(overlay.path().join("scratch")).unwrap();
+            fs::write(overlay.path().join("scratch/notes.txt"), "notes").unwrap();
+            fs::write(overlay.path().join(".envrc"), "export FOO=bar").unwrap();
+
+            // Create config with directories list
+            fs::write(
+                overlay.path().join("repoverlay.ccl"),
+                "overlay =\n  name = test-overlay\n\ndirectories =\n  = scratch\n",
+            )
+            .unwrap();
+
+            let result = apply_overlay(
+                overlay.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions::default(),
             );
+            assert!(result.is_ok(), "apply_overlay failed: {result:?}");
+
+            // Check directory symlink was created
+            let target_dir = repo.path().join("scratch");
+            assert!(target_dir.is_symlink(), "scratch should be a symlink");
+
+            // Check standalone file was also symlinked
+            let envrc = repo.path().join(".envrc");
+            assert!(envrc.is_symlink(), ".envrc should be a symlink");
         }
 
         #[test]
-        fn nested_directory_symlinks_use_forward_slashes() {
+        fn directory_symlink_warns_on_nonexistent() {
             let repo = create_test_repo();
             let overlay = TempDir::new().unwrap();
 
-            // Create nested directory structure
-            fs::create_dir_all(overlay.path().join("config/editors")).unwrap();
+            // Create a regular file so overlay isn't empty
+            fs::write(overlay.path().join(".envrc"), "export FOO=bar").unwrap();
+
+            // Create config with directories list including non-existent directory
             fs::write(
-                overlay.path().join("config/editors/vscode.json"),
-                r#"{"editor": "vscode"}"#,
+                overlay.path().join("repoverlay.ccl"),
+                "overlay =\n  name = test-overlay\n\ndirectories =\n  = nonexistent\n",
+            )
+            .unwrap();
+
+            let result = apply_overlay(
+                overlay.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions::default(),
+            );
+            // Should succeed (just warns about missing directory)
+            assert!(result.is_ok(), "apply_overlay failed: {result:?}");
+
+            // Check regular file was still symlinked
+            assert!(repo.path().join(".envrc").is_symlink());
+
+            // Non-existent directory should not be created
+            assert!(!repo.path().join("nonexistent").exists());
+        }
+
+        #[test]
+        fn directory_conflict_with_existing_path() {
+            let repo = create_test_repo();
+
+            // Create a directory in the repo that conflicts
+            fs::create_dir_all(repo.path().join("scratch")).unwrap();
+            fs::write(repo.path().join("scratch/existing.txt"), "existing").unwrap();
+
+            let overlay = TempDir::new().unwrap();
+            fs::create_dir_all(overlay.path().join("scratch")).unwrap();
+            fs::write(overlay.path().join("scratch/notes.txt"), "notes").unwrap();
+            fs::write(
+                overlay.path().join("repoverlay.ccl"),
+                "overlay =\n  name = test-overlay\n\ndirectories =\n  = scratch\n",
+            )
+            .unwrap();
+
+            let result = apply_overlay(
+                overlay.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions::default(),
+            );
+
+            assert!(result.is_err());
+            assert!(result.unwrap_err().to_string().contains("already exists"));
+        }
+
+        #[test]
+        fn directory_conflict_with_existing_overlay() {
+            let repo = create_test_repo();
+
+            // Apply first overlay with a directory
+            let overlay1 = TempDir::new().unwrap();
+            fs::create_dir_all(overlay1.path().join("scratch")).unwrap();
+            fs::write(overlay1.path().join("scratch/notes.txt"), "notes").unwrap();
+            fs::write(
+                overlay1.path().join("repoverlay.ccl"),
+                "overlay =\n  name = overlay-a\n\ndirectories =\n  = scratch\n",
+            )
+            .unwrap();
+
+            apply_overlay(
+                overlay1.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions::default(),
+            )
+            .unwrap();
+
+            // Try to apply second overlay with same directory
+            let overlay2 = TempDir::new().unwrap();
+            fs::create_dir_all(overlay2.path().join("scratch")).unwrap();
+            fs::write(overlay2.path().join("scratch/other.txt"), "other").unwrap();
+            fs::write(
+                overlay2.path().join("repoverlay.ccl"),
+                "overlay =\n  name = overlay-b\n\ndirectories =\n  = scratch\n",
+            )
+            .unwrap();
+
+            let result = apply_overlay(
+                overlay2.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions::default(),
+            );
+
+            assert!(result.is_err());
+            assert!(result.unwrap_err().to_string().contains("already managed"));
+        }
+
+        #[test]
+        fn directory_symlink_updates_git_exclude_with_trailing_slash() {
+            let repo = create_test_repo();
+            let overlay = TempDir::new().unwrap();
+
+            fs::create_dir_all(overlay.path().join("scratch")).unwrap();
+            fs::write(overlay.path().join("scratch/notes.txt"), "notes").unwrap();
+            fs::write(
+                overlay.path().join("repoverlay.ccl"),
+                "overlay =\n  name = test-overlay\n\ndirectories =\n  = scratch\n",
+            )
+            .unwrap();
+
+            apply_overlay(
+                overlay.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions::default(),
             )
             .unwrap();
 
-            // Use forward slashes in directories list (portable across platforms)
-            fs::write(
-                overlay.path().join("repoverlay.ccl"),
-                r"overlay =
-  name = test-overlay
+            let exclude_path = repo.path().join(".git/info/exclude");
+            let content = fs::read_to_string(&exclude_path).unwrap();
+
+            // Directory should have trailing slash in exclude
+            assert!(content.contains("scratch/"));
+        }
+
+        #[test]
+        fn directory_path_not_a_directory_warning() {
+            let repo = create_test_repo();
+            let overlay = TempDir::new().unwrap();
+
+            // Create a file (not directory) that matches the directory name
+            fs::write(overlay.path().join("scratch"), "this is a file").unwrap();
+            fs::write(overlay.path().join(".envrc"), "export FOO=bar").unwrap();
+            fs::write(
+                overlay.path().join("repoverlay.ccl"),
+                "overlay =\n  name = test-overlay\n\ndirectories =\n  = scratch\n",
+            )
+            .unwrap();
+
+            // Should succeed (just warns about scratch not being a directory)
+            let result = apply_overlay(
+                overlay.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions::default(),
+            );
+            assert!(result.is_ok());
+
+            // Regular file should still be symlinked
+            assert!(repo.path().join(".envrc").is_symlink());
+            // scratch as a directory symlink should not exist (it was a file in overlay)
+            assert!(!repo.path().join("scratch").is_symlink());
+        }
+
+        #[test]
+        #[ignore = "tylerbutler/santa#71: forward slashes in map keys cause parsing errors in sickle"]
+        fn mapping_supports_nested_paths_in_key_and_value() {
+            let repo = create_test_repo();
+            let overlay = TempDir::new().unwrap();
+
+            // Create nested source file
+            fs::create_dir_all(overlay.path().join("config")).unwrap();
+            fs::write(overlay.path().join("config/settings.json"), "{}").unwrap();
+
+            // Map nested source path to nested destination path (forward slashes in both)
+            fs::write(
+                overlay.path().join("repoverlay.ccl"),
+                r"mappings =
+  config/settings.json = .vscode/settings.json
+",
+            )
+            .unwrap();
+
+            let result = apply_overlay(
+                overlay.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions::default(),
+            );
+            assert!(result.is_ok(), "apply_overlay failed: {result:?}");
+
+            // Check that mapping was applied (file at nested destination)
+            assert!(
+                repo.path().join(".vscode/settings.json").exists(),
+                "mapped target should exist at nested path"
+            );
+            assert!(
+                !repo.path().join("config/settings.json").exists(),
+                "original path should not exist when mapped"
+            );
+        }
+
+        #[test]
+        fn nested_directory_symlinks_use_forward_slashes() {
+            let repo = create_test_repo();
+            let overlay = TempDir::new().unwrap();
+
+            // Create nested directory structure
+            fs::create_dir_all(overlay.path().join("config/editors")).unwrap();
+            fs::write(
+                overlay.path().join("config/editors/vscode.json"),
+                r#"{"editor": "vscode"}"#,
+            )
+            .unwrap();
+
+            // Use forward slashes in directories list (portable across platforms)
+            fs::write(
+                overlay.path().join("repoverlay.ccl"),
+                r"overlay =
+  name = test-overlay
+
+directories =
+  = config/editors
+",
+            )
+            .unwrap();
+
+            let result = apply_overlay(
+                overlay.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions::default(),
+            );
+            assert!(result.is_ok(), "apply_overlay failed: {result:?}");
+
+            // Check directory symlink was created
+            let target_dir = repo.path().join("config/editors");
+            assert!(target_dir.exists(), "config/editors should exist");
+            assert!(
+                target_dir.is_symlink(),
+                "config/editors should be a symlink"
+            );
+
+            // Check files inside are accessible
+            assert!(target_dir.join("vscode.json").exists());
+        }
+
+        #[test]
+        fn dry_run_does_not_apply_overlay() {
+            let repo = create_test_repo();
+            let overlay = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+
+            let result = apply_overlay(
+                overlay.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    name: Some("test-overlay".to_string()),
+                    dry_run: true,
+                    ..Default::default()
+                },
+            );
+            assert!(result.is_ok(), "apply_overlay dry_run failed: {result:?}");
+
+            // Check no files were created
+            assert!(
+                !repo.path().join(".envrc").exists(),
+                ".envrc should not exist in dry run"
+            );
+            // Check no state was saved
+            assert!(
+                !repo.path().join(".repoverlay").exists(),
+                ".repoverlay dir should not exist in dry run"
+            );
+        }
+
+        #[test]
+        fn dry_run_fails_on_conflicts_without_changes() {
+            let repo = create_test_repo();
+            let first = create_test_overlay(&[("shared.txt", "first")]);
+            let second = create_test_overlay(&[("shared.txt", "second"), ("README.md", "overlay")]);
+            fs::write(repo.path().join("README.md"), "repo").unwrap();
+
+            apply_overlay(
+                first.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    name: Some("first".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+            let result = apply_overlay(
+                second.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    name: Some("second".to_string()),
+                    dry_run: true,
+                    ..Default::default()
+                },
+            );
+
+            let err = result.unwrap_err().to_string();
+            assert!(err.contains("2 conflict(s)"), "{err}");
+            assert_eq!(
+                fs::read_to_string(repo.path().join("README.md")).unwrap(),
+                "repo"
+            );
+            assert!(!repo.path().join(".repoverlay/overlays/second.ccl").exists());
+            let exclude = fs::read_to_string(repo.path().join(".git/info/exclude")).unwrap();
+            assert!(!exclude.contains("second"));
+        }
+
+        #[test]
+        fn dry_run_with_on_conflict_reports_without_moving_files() {
+            let repo = create_test_repo();
+            let overlay = create_test_overlay(&[("README.md", "overlay")]);
+            fs::write(repo.path().join("README.md"), "repo").unwrap();
+
+            apply_overlay(
+                overlay.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    name: Some("test".to_string()),
+                    dry_run: true,
+                    on_conflict: OnConflict::Backup,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+            assert!(!repo.path().join("README.md").is_symlink());
+            assert!(!repo.path().join(".repoverlay").exists());
+        }
+    }
+
+    // Integration tests for overlay requirements
+    mod requires {
+        use super::*;
+        use std::path::Path;
+
+        fn overlay_with_config(name: &str, file: &str, requires: &[&Path]) -> TempDir {
+            let mut config = format!("overlay =\n  name = {name}\n");
+            if !requires.is_empty() {
+                config.push_str("requires =\n");
+                for req in requires {
+                    config.push_str("  = ");
+                    config.push_str(&req.display().to_string());
+                    config.push('\n');
+                }
+            }
+            create_test_overlay(&[(file, "content"), ("repoverlay.ccl", &config)])
+        }
+
+        fn apply(source: &Path, target: &Path, no_deps: bool) -> Result<()> {
+            apply_overlay(
+                source.to_str().unwrap(),
+                target,
+                &ApplyOptions {
+                    no_deps,
+                    ..Default::default()
+                },
+            )
+        }
+
+        #[test]
+        fn applies_required_overlays_first() {
+            let repo = create_test_repo();
+            let base = overlay_with_config("base", ".envrc", &[]);
+            let main = overlay_with_config("main", ".editorconfig", &[base.path()]);
+
+            apply(main.path(), repo.path(), false).unwrap();
+
+            assert!(repo.path().join(".envrc").is_symlink());
+            assert!(repo.path().join(".editorconfig").is_symlink());
+            let state = crate::load_overlay_state(repo.path(), "main").unwrap();
+            assert_eq!(state.requires, vec!["base"]);
+            assert!(crate::load_overlay_state(repo.path(), "base").is_ok());
+        }
+
+        #[test]
+        fn skips_requirement_already_applied() {
+            let repo = create_test_repo();
+            let base = overlay_with_config("base", ".envrc", &[]);
+            let main = overlay_with_config("main", ".editorconfig", &[base.path()]);
+
+            apply(base.path(), repo.path(), false).unwrap();
+            // Re-applying base would fail on the existing .envrc
+            apply(main.path(), repo.path(), false).unwrap();
+
+            let state = crate::load_overlay_state(repo.path(), "main").unwrap();
+            assert_eq!(state.requires, vec!["base"]);
+        }
+
+        #[test]
+        fn no_deps_skips_requirements() {
+            let repo = create_test_repo();
+            let base = overlay_with_config("base", ".envrc", &[]);
+            let main = overlay_with_config("main", ".editorconfig", &[base.path()]);
+
+            apply(main.path(), repo.path(), true).unwrap();
+
+            assert!(!repo.path().join(".envrc").exists());
+            let state = crate::load_overlay_state(repo.path(), "main").unwrap();
+            assert!(state.requires.is_empty());
+        }
+
+        #[test]
+        fn rejects_requirement_cycle() {
+            let repo = create_test_repo();
+            let first = create_test_overlay(&[(".envrc", "content")]);
+            let second = overlay_with_config("second", ".editorconfig", &[first.path()]);
+            let config = format!(
+                "overlay =\n  name = first\nrequires =\n  = {}\n",
+                second.path().display()
+            );
+            fs::write(first.path().join("repoverlay.ccl"), config).unwrap();
+
+            let err = apply(first.path(), repo.path(), false).unwrap_err();
+
+            assert!(format!("{err:#}").contains("cycle"), "got: {err:#}");
+            assert!(!repo.path().join(".envrc").exists());
+            assert!(!repo.path().join(".editorconfig").exists());
+        }
+
+        #[test]
+        fn removing_required_overlay_still_succeeds() {
+            let repo = create_test_repo();
+            let base = overlay_with_config("base", ".envrc", &[]);
+            let main = overlay_with_config("main", ".editorconfig", &[base.path()]);
+            apply(main.path(), repo.path(), false).unwrap();
+
+            remove_overlay(repo.path(), Some("base".to_string()), false, false, false).unwrap();
+
+            assert!(!repo.path().join(".envrc").exists());
+            assert!(repo.path().join(".editorconfig").is_symlink());
+        }
+    }
+
+    // Integration tests for remove command
+    mod remove {
+        use super::*;
+
+        #[cfg(unix)]
+        fn apply_with_hooks(repo: &TempDir, overlay: &TempDir, run_hooks: bool) {
+            apply_overlay(
+                overlay.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    name: Some("hooked".to_string()),
+                    run_hooks,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        }
+
+        #[test]
+        #[cfg(unix)]
+        fn runs_hooks_only_when_asked() {
+            let repo = create_test_repo();
+            let overlay = create_test_overlay(&[
+                (".envrc", "export FOO=bar"),
+                (
+                    "repoverlay.ccl",
+                    "hooks =\n  post_apply = echo $REPOVERLAY_OVERLAY > applied.txt\n  pre_remove = touch removed.txt\n",
+                ),
+            ]);
+
+            apply_with_hooks(&repo, &overlay, false);
+            assert!(!repo.path().join("applied.txt").exists());
+            remove_overlay(repo.path(), Some("hooked".to_string()), false, false, false).unwrap();
+            assert!(!repo.path().join("removed.txt").exists());
+
+            apply_with_hooks(&repo, &overlay, true);
+            assert_eq!(
+                fs::read_to_string(repo.path().join("applied.txt")).unwrap(),
+                "hooked\n"
+            );
+            remove_overlay(repo.path(), Some("hooked".to_string()), false, false, true).unwrap();
+            assert!(repo.path().join("removed.txt").exists());
+            assert!(!repo.path().join(".envrc").exists());
+        }
+
+        #[test]
+        #[cfg(unix)]
+        fn failing_pre_remove_hook_keeps_overlay() {
+            let repo = create_test_repo();
+            let overlay = create_test_overlay(&[
+                (".envrc", "export FOO=bar"),
+                ("repoverlay.ccl", "hooks =\n  pre_remove = exit 3\n"),
+            ]);
+            apply_with_hooks(&repo, &overlay, true);
+
+            let err = remove_overlay(repo.path(), Some("hooked".to_string()), false, false, true)
+                .unwrap_err();
+            assert!(
+                err.to_string()
+                    .contains("pre_remove hook for 'hooked' failed")
+            );
+            assert!(repo.path().join(".envrc").exists());
+            assert!(repo.path().join(".repoverlay/overlays/hooked.ccl").exists());
+        }
+
+        #[test]
+        fn removes_overlay_by_name() {
+            let repo = create_test_repo();
+            let overlay = create_test_overlay(&[
+                (".envrc", "export FOO=bar"),
+                (".vscode/settings.json", r#"{"key": "value"}"#),
+            ]);
+
+            apply_overlay(
+                overlay.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    name: Some("test-overlay".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            remove_overlay(
+                repo.path(),
+                Some("test-overlay".to_string()),
+                false,
+                false,
+                false,
+            )
+            .unwrap();
+
+            assert!(!repo.path().join(".envrc").exists());
+            assert!(!repo.path().join(".vscode/settings.json").exists());
+            assert!(!repo.path().join(".repoverlay").exists());
+        }
+
+        #[test]
+        fn restores_backed_up_file() {
+            let repo = create_test_repo();
+            fs::create_dir_all(repo.path().join("config")).unwrap();
+            fs::write(repo.path().join("config/app.toml"), "original").unwrap();
+            let overlay = create_test_overlay(&[("config/app.toml", "overlay")]);
+
+            apply_overlay(
+                overlay.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    name: Some("test-overlay".to_string()),
+                    on_conflict: OnConflict::Backup,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            remove_overlay(
+                repo.path(),
+                Some("test-overlay".to_string()),
+                false,
+                false,
+                false,
+            )
+            .unwrap();
+
+            assert_eq!(
+                fs::read_to_string(repo.path().join("config/app.toml")).unwrap(),
+                "original"
+            );
+            assert!(!repo.path().join(".repoverlay").exists());
+        }
+
+        #[test]
+        fn restores_backup_and_cleans_backup_dir_with_other_overlays() {
+            let repo = create_test_repo();
+            fs::write(repo.path().join(".envrc"), "original").unwrap();
+            let overlay1 = create_test_overlay(&[(".envrc", "overlay")]);
+            let overlay2 = create_test_overlay(&[(".env.local", "LOCAL=true")]);
+
+            apply_overlay(
+                overlay1.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    name: Some("forced".to_string()),
+                    on_conflict: OnConflict::Backup,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            apply_overlay(
+                overlay2.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    name: Some("other".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            remove_overlay(repo.path(), Some("forced".to_string()), false, false, false).unwrap();
+
+            assert_eq!(
+                fs::read_to_string(repo.path().join(".envrc")).unwrap(),
+                "original"
+            );
+            assert!(!repo.path().join(".repoverlay/backups").exists());
+            assert!(repo.path().join(".env.local").exists());
+        }
+
+        #[test]
+        fn removes_all_overlays() {
+            let repo = create_test_repo();
+            let overlay1 = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+            let overlay2 = create_test_overlay(&[(".env.local", "LOCAL=true")]);
+
+            apply_overlay(
+                overlay1.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    name: Some("overlay-a".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            apply_overlay(
+                overlay2.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    name: Some("overlay-b".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+            assert!(repo.path().join(".envrc").exists());
+            assert!(repo.path().join(".env.local").exists());
+
+            remove_overlay(repo.path(), None, true, false, false).unwrap();
+
+            assert!(!repo.path().join(".envrc").exists());
+            assert!(!repo.path().join(".env.local").exists());
+            assert!(!repo.path().join(".repoverlay").exists());
+        }
+
+        #[test]
+        fn removes_one_overlay_preserves_others() {
+            let repo = create_test_repo();
+            let overlay1 = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+            let overlay2 = create_test_overlay(&[(".env.local", "LOCAL=true")]);
+
+            apply_overlay(
+                overlay1.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    name: Some("overlay-a".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            apply_overlay(
+                overlay2.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    name: Some("overlay-b".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+            remove_overlay(
+                repo.path(),
+                Some("overlay-a".to_string()),
+                false,
+                false,
+                false,
+            )
+            .unwrap();
+
+            assert!(!repo.path().join(".envrc").exists());
+            assert!(repo.path().join(".env.local").exists());
+            assert!(repo.path().join(".repoverlay").exists());
+        }
+
+        #[test]
+        fn removes_empty_parent_directories() {
+            let repo = create_test_repo();
+            let overlay = create_test_overlay(&[(".vscode/settings.json", r#"{"key": "value"}"#)]);
+
+            apply_overlay(
+                overlay.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    name: Some("test".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            assert!(repo.path().join(".vscode").exists());
+
+            remove_overlay(repo.path(), Some("test".to_string()), false, false, false).unwrap();
+            assert!(
+                !repo.path().join(".vscode").exists(),
+                ".vscode should be removed"
+            );
+        }
+
+        #[test]
+        fn preserves_non_empty_parent_directories() {
+            let repo = create_test_repo();
+            let overlay = create_test_overlay(&[(".vscode/settings.json", r#"{"key": "value"}"#)]);
+
+            // Create another file in .vscode that isn't from the overlay
+            fs::create_dir_all(repo.path().join(".vscode")).unwrap();
+            fs::write(repo.path().join(".vscode/other.json"), "{}").unwrap();
+
+            apply_overlay(
+                overlay.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    name: Some("test".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            remove_overlay(repo.path(), Some("test".to_string()), false, false, false).unwrap();
+
+            assert!(
+                repo.path().join(".vscode").exists(),
+                ".vscode should remain"
+            );
+            assert!(repo.path().join(".vscode/other.json").exists());
+        }
+
+        #[test]
+        fn cleans_git_exclude_for_overlay() {
+            let repo = create_test_repo();
+            let overlay = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+
+            apply_overlay(
+                overlay.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    name: Some("test".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            remove_overlay(repo.path(), Some("test".to_string()), false, false, false).unwrap();
+
+            let exclude_path = repo.path().join(".git/info/exclude");
+            let content = fs::read_to_string(&exclude_path).unwrap();
+
+            assert!(!content.contains("# repoverlay:test start"));
+            assert!(!content.contains(".envrc"));
+            assert!(!content.contains("# repoverlay:managed"));
+        }
+
+        #[test]
+        fn fails_when_no_overlay_applied() {
+            let repo = create_test_repo();
+
+            let result = remove_overlay(
+                repo.path(),
+                Some("nonexistent".to_string()),
+                false,
+                false,
+                false,
+            );
+            assert!(result.is_err());
+            assert!(result.unwrap_err().to_string().contains("No overlay"));
+        }
+
+        #[test]
+        fn fails_on_unknown_overlay_name() {
+            let repo = create_test_repo();
+            let overlay = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+
+            apply_overlay(
+                overlay.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    name: Some("real-overlay".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+            let result = remove_overlay(
+                repo.path(),
+                Some("fake-overlay".to_string()),
+                false,
+                false,
+                false,
+            );
+            assert!(result.is_err());
+            assert!(result.unwrap_err().to_string().contains("not found"));
+        }
+
+        #[test]
+        fn handles_already_deleted_files() {
+            let repo = create_test_repo();
+            let overlay = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+
+            apply_overlay(
+                overlay.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    name: Some("test".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+            // Manually delete the file
+            fs::remove_file(repo.path().join(".envrc")).unwrap();
+
+            // Remove should still succeed
+            let result = remove_overlay(repo.path(), Some("test".to_string()), false, false, false);
+            assert!(result.is_ok());
+        }
+
+        #[test]
+        fn removes_directory_symlink() {
+            let repo = create_test_repo();
+            let overlay = TempDir::new().unwrap();
+
+            // Create a directory with files inside
+            fs::create_dir_all(overlay.path().join("scratch")).unwrap();
+            fs::write(overlay.path().join("scratch/notes.txt"), "notes").unwrap();
+
+            // Create config with directories list
+            fs::write(
+                overlay.path().join("repoverlay.ccl"),
+                "overlay =\n  name = test-overlay\n\ndirectories =\n  = scratch\n",
+            )
+            .unwrap();
+
+            apply_overlay(
+                overlay.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions::default(),
+            )
+            .unwrap();
+
+            // Verify directory symlink exists
+            assert!(repo.path().join("scratch").is_symlink());
+
+            // Remove overlay
+            remove_overlay(
+                repo.path(),
+                Some("test-overlay".to_string()),
+                false,
+                false,
+                false,
+            )
+            .unwrap();
+
+            // Verify directory symlink was removed
+            assert!(!repo.path().join("scratch").exists());
+            assert!(!repo.path().join(".repoverlay").exists());
+        }
+
+        #[test]
+        fn removes_copied_directory() {
+            let repo = create_test_repo();
+            let overlay = TempDir::new().unwrap();
+
+            // Create a directory with files inside
+            fs::create_dir_all(overlay.path().join("scratch")).unwrap();
+            fs::write(overlay.path().join("scratch/notes.txt"), "notes").unwrap();
+
+            // Create config with directories list
+            fs::write(
+                overlay.path().join("repoverlay.ccl"),
+                "overlay =\n  name = test-overlay\n\ndirectories =\n  = scratch\n",
+            )
+            .unwrap();
+
+            apply_overlay(
+                overlay.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    force_copy: true,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+            // Verify directory was copied (not symlink)
+            let target_dir = repo.path().join("scratch");
+            assert!(!target_dir.is_symlink());
+            assert!(target_dir.is_dir());
+
+            // Remove overlay
+            remove_overlay(
+                repo.path(),
+                Some("test-overlay".to_string()),
+                false,
+                false,
+                false,
+            )
+            .unwrap();
+
+            // Verify directory was removed
+            assert!(!repo.path().join("scratch").exists());
+            assert!(!repo.path().join(".repoverlay").exists());
+        }
+
+        #[test]
+        fn dry_run_does_not_remove_overlay() {
+            let repo = create_test_repo();
+            let overlay = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+
+            apply_overlay(
+                overlay.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    name: Some("test-overlay".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+            // Dry run removal
+            let result = remove_overlay(
+                repo.path(),
+                Some("test-overlay".to_string()),
+                false,
+                true,
+                false,
+            );
+            assert!(result.is_ok(), "dry_run remove failed: {result:?}");
+
+            // Verify files are still present
+            assert!(
+                repo.path().join(".envrc").exists(),
+                ".envrc should still exist after dry run"
+            );
+            assert!(
+                repo.path().join(".repoverlay").exists(),
+                ".repoverlay should still exist after dry run"
+            );
+        }
+
+        #[test]
+        fn dry_run_all_does_not_remove_overlays() {
+            let repo = create_test_repo();
+            let overlay1 = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+            let overlay2 = create_test_overlay(&[(".env.local", "LOCAL=true")]);
+
+            apply_overlay(
+                overlay1.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    name: Some("overlay-a".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            apply_overlay(
+                overlay2.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    name: Some("overlay-b".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+            // Dry run removal of all
+            let result = remove_overlay(repo.path(), None, true, true, false);
+            assert!(result.is_ok(), "dry_run remove --all failed: {result:?}");
+
+            // Verify all files are still present
+            assert!(
+                repo.path().join(".envrc").exists(),
+                ".envrc should still exist after dry run"
+            );
+            assert!(
+                repo.path().join(".env.local").exists(),
+                ".env.local should still exist after dry run"
+            );
+        }
+
+        #[test]
+        fn handle_remove_requires_name_or_interactive_flag() {
+            let repo = create_test_repo();
+
+            // Calling handle_remove without name, --all, or --interactive should fail
+            let result = handle_remove(repo.path(), None, false, false, false, false);
+            assert!(result.is_err());
+            let err = result.unwrap_err().to_string();
+            assert!(
+                err.contains("No overlay name specified"),
+                "Expected usage error, got: {err}"
+            );
+        }
+
+        #[test]
+        fn handle_remove_with_name_succeeds() {
+            let repo = create_test_repo();
+            let overlay = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+
+            apply_overlay(
+                overlay.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    name: Some("test-overlay".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+            // Calling handle_remove with a name should succeed
+            let result = handle_remove(
+                repo.path(),
+                Some("test-overlay".to_string()),
+                false,
+                false,
+                false,
+                false,
+            );
+            assert!(result.is_ok(), "handle_remove with name failed: {result:?}");
+            assert!(!repo.path().join(".envrc").exists());
+        }
+
+        #[test]
+        fn handle_remove_with_all_flag_succeeds() {
+            let repo = create_test_repo();
+            let overlay = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+
+            apply_overlay(
+                overlay.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    name: Some("test-overlay".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+            // Calling handle_remove with --all should succeed
+            let result = handle_remove(repo.path(), None, true, false, false, false);
+            assert!(
+                result.is_ok(),
+                "handle_remove with --all failed: {result:?}"
+            );
+            assert!(!repo.path().join(".envrc").exists());
+        }
+    }
+
+    // Integration tests for status command
+    mod status {
+        use super::*;
+
+        #[test]
+        fn shows_no_overlay_when_none_applied() {
+            let repo = create_test_repo();
+            let result = show_status(repo.path(), None, false, false, false, false);
+            assert!(result.is_ok());
+        }
+
+        #[test]
+        fn shows_status_when_overlay_applied() {
+            let repo = create_test_repo();
+            let overlay = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+
+            apply_overlay(
+                overlay.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    name: Some("test".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+            let result = show_status(repo.path(), None, false, false, false, false);
+            assert!(result.is_ok());
+        }
+
+        #[test]
+        fn shows_status_for_multiple_overlays() {
+            let repo = create_test_repo();
+            let overlay1 = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+            let overlay2 = create_test_overlay(&[(".env.local", "LOCAL=true")]);
+
+            apply_overlay(
+                overlay1.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    name: Some("overlay-a".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            apply_overlay(
+                overlay2.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    name: Some("overlay-b".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+            let result = show_status(repo.path(), None, false, false, false, false);
+            assert!(result.is_ok());
+        }
+
+        #[test]
+        fn shows_status_for_specific_overlay() {
+            let repo = create_test_repo();
+            let overlay1 = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+            let overlay2 = create_test_overlay(&[(".env.local", "LOCAL=true")]);
+
+            apply_overlay(
+                overlay1.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    name: Some("overlay-a".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            apply_overlay(
+                overlay2.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    name: Some("overlay-b".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+            let result = show_status(
+                repo.path(),
+                Some("overlay-a".to_string()),
+                false,
+                false,
+                false,
+                false,
+            );
+            assert!(result.is_ok());
+        }
+
+        #[test]
+        fn fails_on_unknown_overlay_filter() {
+            let repo = create_test_repo();
+            let overlay = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+
+            apply_overlay(
+                overlay.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    name: Some("real".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+            let result = show_status(
+                repo.path(),
+                Some("fake".to_string()),
+                false,
+                false,
+                false,
+                false,
+            );
+            assert!(result.is_err());
+        }
+
+        #[test]
+        fn records_hash_only_for_copied_files() {
+            let repo = create_test_repo();
+            let copied = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+            let linked = create_test_overlay(&[(".env.local", "LOCAL=true")]);
+
+            for (overlay, name, copy) in [(&copied, "copied", true), (&linked, "linked", false)] {
+                apply_overlay(
+                    overlay.path().to_str().unwrap(),
+                    repo.path(),
+                    &ApplyOptions {
+                        force_copy: copy,
+                        name: Some(name.to_string()),
+                        ..Default::default()
+                    },
+                )
+                .unwrap();
+            }
+
+            let copied_state = crate::load_overlay_state(repo.path(), "copied").unwrap();
+            assert_eq!(
+                copied_state.file_entries()[0].sha256.as_deref(),
+                Some(crate::hash::sha256_hex(b"export FOO=bar").as_str())
+            );
+            let linked_state = crate::load_overlay_state(repo.path(), "linked").unwrap();
+            assert!(linked_state.file_entries()[0].sha256.is_none());
+        }
+
+        #[test]
+        fn check_drift_reports_modified_copies() {
+            let repo = create_test_repo();
+            let overlay =
+                create_test_overlay(&[(".envrc", "export FOO=bar"), (".env.local", "LOCAL=true")]);
+
+            apply_overlay(
+                overlay.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    force_copy: true,
+                    name: Some("test".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+            let target = repo.path().canonicalize().unwrap();
+            assert!(
+                crate::show_single_overlay_status(&target, "test", true, false)
+                    .unwrap()
+                    .is_empty()
+            );
+
+            fs::write(repo.path().join(".envrc"), "export FOO=changed").unwrap();
+
+            let drifted = crate::show_single_overlay_status(&target, "test", true, false).unwrap();
+            assert_eq!(drifted, vec![PathBuf::from(".envrc")]);
+            assert!(show_status(repo.path(), None, false, true, false, false).is_ok());
+        }
+    }
+
+    mod diff {
+        use super::*;
+
+        fn apply_test_overlay(repo: &TempDir, overlay: &TempDir, copy: bool) {
+            apply_overlay(
+                overlay.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    force_copy: copy,
+                    name: Some("test".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        }
+
+        #[test]
+        fn reports_no_differences_when_none_applied() {
+            let repo = create_test_repo();
+            let differs = diff_overlays(repo.path(), None).unwrap();
+            assert!(!differs);
+        }
+
+        #[test]
+        fn symlinked_overlay_is_in_sync() {
+            let repo = create_test_repo();
+            let overlay = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+            apply_test_overlay(&repo, &overlay, false);
+
+            let differs = diff_overlays(repo.path(), None).unwrap();
+            assert!(!differs);
+        }
+
+        #[test]
+        fn copied_overlay_is_in_sync() {
+            let repo = create_test_repo();
+            let overlay = create_test_overlay(&[(".envrc", "export FOO=bar\n")]);
+            apply_test_overlay(&repo, &overlay, true);
+
+            let differs = diff_overlays(repo.path(), None).unwrap();
+            assert!(!differs);
+        }
+
+        #[test]
+        fn detects_modified_copy() {
+            let repo = create_test_repo();
+            let overlay = create_test_overlay(&[(".envrc", "export FOO=bar\n")]);
+            apply_test_overlay(&repo, &overlay, true);
+
+            fs::write(repo.path().join(".envrc"), "export FOO=baz\n").unwrap();
+
+            let differs = diff_overlays(repo.path(), None).unwrap();
+            assert!(differs);
+        }
+
+        #[test]
+        fn detects_missing_file() {
+            let repo = create_test_repo();
+            let overlay = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+            apply_test_overlay(&repo, &overlay, false);
+
+            fs::remove_file(repo.path().join(".envrc")).unwrap();
+
+            let differs = diff_overlays(repo.path(), None).unwrap();
+            assert!(differs);
+        }
+
+        #[test]
+        #[cfg(unix)]
+        fn detects_symlink_pointing_elsewhere() {
+            let repo = create_test_repo();
+            let overlay = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+            apply_test_overlay(&repo, &overlay, false);
+
+            let elsewhere = create_test_overlay(&[("other", "other")]);
+            let link = repo.path().join(".envrc");
+            fs::remove_file(&link).unwrap();
+            std::os::unix::fs::symlink(elsewhere.path().join("other"), &link).unwrap();
+
+            let differs = diff_overlays(repo.path(), None).unwrap();
+            assert!(differs);
+        }
+
+        #[test]
+        fn fails_on_unknown_overlay_filter() {
+            let repo = create_test_repo();
+            let overlay = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+            apply_test_overlay(&repo, &overlay, false);
+
+            let result = diff_overlays(repo.path(), Some("fake".to_string()));
+            assert!(result.is_err());
+        }
+
+        #[test]
+        fn unified_diff_marks_changed_lines() {
+            let lines = crate::unified_diff("a\nb\nc\n", "a\nB\nc\n", ".envrc");
+            assert_eq!(lines[0], "--- a/.envrc");
+            assert_eq!(lines[1], "+++ b/.envrc");
+            assert!(lines[2].starts_with("@@"));
+            assert!(lines.contains(&"-b".to_string()));
+            assert!(lines.contains(&"+B".to_string()));
+        }
+    }
+
+    // Integration tests for repair command
+    #[cfg(unix)]
+    mod repair {
+        use super::*;
+
+        fn apply_test_overlay(repo: &TempDir, overlay: &TempDir, copy: bool) {
+            apply_overlay(
+                overlay.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    force_copy: copy,
+                    name: Some("test".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        }
+
+        #[test]
+        fn succeeds_when_none_applied() {
+            let repo = create_test_repo();
+            assert!(repair_overlays(repo.path(), None).is_ok());
+        }
+
+        #[test]
+        fn repoints_dangling_symlink() {
+            let repo = create_test_repo();
+            let overlay = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+            apply_test_overlay(&repo, &overlay, false);
+
+            let link = repo.path().join(".envrc");
+            fs::remove_file(&link).unwrap();
+            std::os::unix::fs::symlink("/nonexistent/old-cache/.envrc", &link).unwrap();
+            assert!(!link.exists());
+
+            repair_overlays(repo.path(), None).unwrap();
+
+            assert!(link.is_symlink());
+            assert_eq!(fs::read_to_string(&link).unwrap(), "export FOO=bar");
+            assert_eq!(
+                fs::read_link(&link).unwrap(),
+                overlay.path().canonicalize().unwrap().join(".envrc")
+            );
+        }
+
+        #[test]
+        fn recreates_missing_symlink() {
+            let repo = create_test_repo();
+            let overlay = create_test_overlay(&[(".vscode/settings.json", "{}")]);
+            apply_test_overlay(&repo, &overlay, false);
+
+            fs::remove_dir_all(repo.path().join(".vscode")).unwrap();
+
+            repair_overlays(repo.path(), Some("test".to_string())).unwrap();
+
+            let link = repo.path().join(".vscode/settings.json");
+            assert!(link.is_symlink());
+            assert_eq!(fs::read_to_string(&link).unwrap(), "{}");
+        }
 
-directories =
-  = config/editors
-",
-            )
-            .unwrap();
+        #[test]
+        fn leaves_valid_symlinks_untouched() {
+            let repo = create_test_repo();
+            let overlay = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+            apply_test_overlay(&repo, &overlay, false);
 
-            let result = apply_overlay(
-                overlay.path().to_str().unwrap(),
-                repo.path(),
-                false,
-                None,
-                None,
-                false,
-                None,
-                false,
-            );
-            assert!(result.is_ok(), "apply_overlay failed: {result:?}");
+            let link = repo.path().join(".envrc");
+            let before = fs::read_link(&link).unwrap();
 
-            // Check directory symlink was created
-            let target_dir = repo.path().join("config/editors");
-            assert!(target_dir.exists(), "config/editors should exist");
-            assert!(
-                target_dir.is_symlink(),
-                "config/editors should be a symlink"
-            );
+            repair_overlays(repo.path(), None).unwrap();
 
-            // Check files inside are accessible
-            assert!(target_dir.join("vscode.json").exists());
+            assert_eq!(fs::read_link(&link).unwrap(), before);
         }
 
         #[test]
-        fn dry_run_does_not_apply_overlay() {
+        fn ignores_copied_files() {
             let repo = create_test_repo();
             let overlay = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+            apply_test_overlay(&repo, &overlay, true);
 
-            let result = apply_overlay(
-                overlay.path().to_str().unwrap(),
-                repo.path(),
-                false,
-                Some("test-overlay".to_string()),
-                None,
-                false,
-                None,
-                true, // dry_run
-            );
-            assert!(result.is_ok(), "apply_overlay dry_run failed: {result:?}");
+            fs::remove_file(repo.path().join(".envrc")).unwrap();
 
-            // Check no files were created
-            assert!(
-                !repo.path().join(".envrc").exists(),
-                ".envrc should not exist in dry run"
-            );
-            // Check no state was saved
-            assert!(
-                !repo.path().join(".repoverlay").exists(),
-                ".repoverlay dir should not exist in dry run"
-            );
+            repair_overlays(repo.path(), None).unwrap();
+
+            assert!(!repo.path().join(".envrc").exists());
+        }
+
+        #[test]
+        fn fails_on_unknown_overlay_filter() {
+            let repo = create_test_repo();
+            let overlay = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+            apply_test_overlay(&repo, &overlay, false);
+
+            let result = repair_overlays(repo.path(), Some("missing".to_string()));
+            assert!(result.is_err());
+            assert!(result.unwrap_err().to_string().contains("not applied"));
         }
     }
 
-    // Integration tests for remove command
-    mod remove {
+    #[cfg(unix)]
+    mod unlink {
         use super::*;
+        use std::path::Path;
+
+        fn apply_test_overlay(repo: &TempDir, overlay: &TempDir, copy: bool) {
+            apply_overlay(
+                overlay.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    force_copy: copy,
+                    name: Some("test".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        }
 
         #[test]
-        fn removes_overlay_by_name() {
+        fn replaces_symlink_with_copy() {
+            let repo = create_test_repo();
+            let overlay =
+                create_test_overlay(&[(".envrc", "export FOO=bar"), (".env.local", "LOCAL=true")]);
+            apply_test_overlay(&repo, &overlay, false);
+
+            unlink_file(repo.path(), "test", Path::new(".envrc"), false).unwrap();
+
+            let file = repo.path().join(".envrc");
+            assert!(!file.is_symlink());
+            assert_eq!(fs::read_to_string(&file).unwrap(), "export FOO=bar");
+            assert!(overlay.path().join(".envrc").exists());
+
+            let state = crate::load_overlay_state(repo.path(), "test").unwrap();
+            assert_eq!(state.file_count(), 1);
+            assert_eq!(state.file_entries()[0].target, PathBuf::from(".env.local"));
+
+            let exclude = fs::read_to_string(repo.path().join(".git/info/exclude")).unwrap();
+            assert!(!exclude.lines().any(|l| l == ".envrc"));
+            assert!(exclude.lines().any(|l| l == ".env.local"));
+        }
+
+        #[test]
+        fn accepts_dot_relative_path() {
             let repo = create_test_repo();
             let overlay = create_test_overlay(&[
                 (".envrc", "export FOO=bar"),
-                (".vscode/settings.json", r#"{"key": "value"}"#),
+                (".vscode/settings.json", "{}"),
             ]);
+            apply_test_overlay(&repo, &overlay, false);
 
-            apply_overlay(
-                overlay.path().to_str().unwrap(),
+            unlink_file(
                 repo.path(),
-                false,
-                Some("test-overlay".to_string()),
-                None,
-                false,
-                None,
+                "test",
+                Path::new("./.vscode/settings.json"),
                 false,
             )
             .unwrap();
-            remove_overlay(repo.path(), Some("test-overlay".to_string()), false, false).unwrap();
 
-            assert!(!repo.path().join(".envrc").exists());
-            assert!(!repo.path().join(".vscode/settings.json").exists());
-            assert!(!repo.path().join(".repoverlay").exists());
+            let file = repo.path().join(".vscode/settings.json");
+            assert!(!file.is_symlink());
+            assert_eq!(fs::read_to_string(&file).unwrap(), "{}");
         }
 
         #[test]
-        fn removes_all_overlays() {
+        fn removes_overlay_after_last_file() {
             let repo = create_test_repo();
-            let overlay1 = create_test_overlay(&[(".envrc", "export FOO=bar")]);
-            let overlay2 = create_test_overlay(&[(".env.local", "LOCAL=true")]);
+            let overlay = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+            apply_test_overlay(&repo, &overlay, true);
 
-            apply_overlay(
-                overlay1.path().to_str().unwrap(),
-                repo.path(),
-                false,
-                Some("overlay-a".to_string()),
-                None,
-                false,
-                None,
-                false,
-            )
-            .unwrap();
-            apply_overlay(
-                overlay2.path().to_str().unwrap(),
-                repo.path(),
-                false,
-                Some("overlay-b".to_string()),
-                None,
-                false,
-                None,
-                false,
-            )
-            .unwrap();
+            unlink_file(repo.path(), "test", Path::new(".envrc"), false).unwrap();
 
-            assert!(repo.path().join(".envrc").exists());
-            assert!(repo.path().join(".env.local").exists());
+            assert_eq!(
+                fs::read_to_string(repo.path().join(".envrc")).unwrap(),
+                "export FOO=bar"
+            );
+            assert!(list_applied_overlays(repo.path()).unwrap().is_empty());
+            let exclude = fs::read_to_string(repo.path().join(".git/info/exclude")).unwrap();
+            assert!(!exclude.contains("repoverlay:test"));
+        }
 
-            remove_overlay(repo.path(), None, true, false).unwrap();
+        #[test]
+        fn fails_on_unmanaged_file() {
+            let repo = create_test_repo();
+            let overlay = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+            apply_test_overlay(&repo, &overlay, false);
 
-            assert!(!repo.path().join(".envrc").exists());
-            assert!(!repo.path().join(".env.local").exists());
-            assert!(!repo.path().join(".repoverlay").exists());
+            let result = unlink_file(repo.path(), "test", Path::new("README.md"), false);
+            assert!(result.is_err());
+            assert!(result.unwrap_err().to_string().contains("not managed"));
+            assert!(repo.path().join(".envrc").is_symlink());
         }
 
         #[test]
-        fn removes_one_overlay_preserves_others() {
+        fn fails_on_unknown_overlay() {
             let repo = create_test_repo();
-            let overlay1 = create_test_overlay(&[(".envrc", "export FOO=bar")]);
-            let overlay2 = create_test_overlay(&[(".env.local", "LOCAL=true")]);
+            let overlay = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+            apply_test_overlay(&repo, &overlay, false);
 
+            let result = unlink_file(repo.path(), "missing", Path::new(".envrc"), false);
+            assert!(result.is_err());
+            assert!(result.unwrap_err().to_string().contains("not applied"));
+        }
+    }
+
+    #[cfg(unix)]
+    mod edit {
+        use super::*;
+        use crate::edit_overlay_file;
+        use std::os::unix::fs::PermissionsExt;
+        use std::path::Path;
+
+        /// An "editor" that appends a line to the file it is given.
+        fn fake_editor(dir: &TempDir) -> String {
+            let script = dir.path().join("editor.sh");
+            fs::write(&script, "#!/bin/sh\necho edited >> \"$1\"\n").unwrap();
+            fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+            script.to_string_lossy().into_owned()
+        }
+
+        fn apply_test_overlay(repo: &TempDir, overlay: &TempDir, copy: bool) {
             apply_overlay(
-                overlay1.path().to_str().unwrap(),
-                repo.path(),
-                false,
-                Some("overlay-a".to_string()),
-                None,
-                false,
-                None,
-                false,
-            )
-            .unwrap();
-            apply_overlay(
-                overlay2.path().to_str().unwrap(),
+                overlay.path().to_str().unwrap(),
                 repo.path(),
-                false,
-                Some("overlay-b".to_string()),
-                None,
-                false,
-                None,
-                false,
+                &ApplyOptions {
+                    force_copy: copy,
+                    name: Some("test".to_string()),
+                    ..Default::default()
+                },
             )
             .unwrap();
+        }
 
-            remove_overlay(repo.path(), Some("overlay-a".to_string()), false, false).unwrap();
+        #[test]
+        fn opens_source_file() {
+            let repo = create_test_repo();
+            let overlay = create_test_overlay(&[(".envrc", "export FOO=bar\n")]);
+            apply_test_overlay(&repo, &overlay, false);
+            let editor = fake_editor(&overlay);
 
-            assert!(!repo.path().join(".envrc").exists());
-            assert!(repo.path().join(".env.local").exists());
-            assert!(repo.path().join(".repoverlay").exists());
+            edit_overlay_file(repo.path(), "test", Path::new("./.envrc"), &editor).unwrap();
+
+            assert_eq!(
+                fs::read_to_string(overlay.path().join(".envrc")).unwrap(),
+                "export FOO=bar\nedited\n"
+            );
         }
 
         #[test]
-        fn removes_empty_parent_directories() {
+        fn maps_file_inside_directory_entry() {
             let repo = create_test_repo();
-            let overlay = create_test_overlay(&[(".vscode/settings.json", r#"{"key": "value"}"#)]);
+            let overlay = create_test_overlay(&[
+                ("repoverlay.ccl", "directories =\n  = .claude\n"),
+                (".claude/settings.json", "{}\n"),
+            ]);
+            apply_test_overlay(&repo, &overlay, false);
+            let editor = fake_editor(&overlay);
 
-            apply_overlay(
-                overlay.path().to_str().unwrap(),
+            edit_overlay_file(
                 repo.path(),
-                false,
-                Some("test".to_string()),
-                None,
-                false,
-                None,
-                false,
+                "test",
+                Path::new(".claude/settings.json"),
+                &editor,
             )
             .unwrap();
-            assert!(repo.path().join(".vscode").exists());
 
-            remove_overlay(repo.path(), Some("test".to_string()), false, false).unwrap();
-            assert!(
-                !repo.path().join(".vscode").exists(),
-                ".vscode should be removed"
+            assert_eq!(
+                fs::read_to_string(overlay.path().join(".claude/settings.json")).unwrap(),
+                "{}\nedited\n"
             );
         }
 
         #[test]
-        fn preserves_non_empty_parent_directories() {
+        fn edits_source_of_copied_file() {
             let repo = create_test_repo();
-            let overlay = create_test_overlay(&[(".vscode/settings.json", r#"{"key": "value"}"#)]);
+            let overlay = create_test_overlay(&[(".envrc", "export FOO=bar\n")]);
+            apply_test_overlay(&repo, &overlay, true);
+            let editor = fake_editor(&overlay);
 
-            // Create another file in .vscode that isn't from the overlay
-            fs::create_dir_all(repo.path().join(".vscode")).unwrap();
-            fs::write(repo.path().join(".vscode/other.json"), "{}").unwrap();
-
-            apply_overlay(
-                overlay.path().to_str().unwrap(),
-                repo.path(),
-                false,
-                Some("test".to_string()),
-                None,
-                false,
-                None,
-                false,
-            )
-            .unwrap();
-            remove_overlay(repo.path(), Some("test".to_string()), false, false).unwrap();
+            edit_overlay_file(repo.path(), "test", Path::new(".envrc"), &editor).unwrap();
 
             assert!(
-                repo.path().join(".vscode").exists(),
-                ".vscode should remain"
+                fs::read_to_string(overlay.path().join(".envrc"))
+                    .unwrap()
+                    .ends_with("edited\n")
+            );
+            // The applied copy is left alone
+            assert_eq!(
+                fs::read_to_string(repo.path().join(".envrc")).unwrap(),
+                "export FOO=bar\n"
             );
-            assert!(repo.path().join(".vscode/other.json").exists());
         }
 
         #[test]
-        fn cleans_git_exclude_for_overlay() {
+        fn fails_on_unmanaged_file() {
             let repo = create_test_repo();
             let overlay = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+            apply_test_overlay(&repo, &overlay, false);
 
-            apply_overlay(
-                overlay.path().to_str().unwrap(),
-                repo.path(),
-                false,
-                Some("test".to_string()),
-                None,
-                false,
-                None,
-                false,
-            )
-            .unwrap();
-            remove_overlay(repo.path(), Some("test".to_string()), false, false).unwrap();
+            let result = edit_overlay_file(repo.path(), "test", Path::new("README.md"), "true");
+            assert!(result.unwrap_err().to_string().contains("not managed"));
+        }
 
-            let exclude_path = repo.path().join(".git/info/exclude");
-            let content = fs::read_to_string(&exclude_path).unwrap();
+        #[test]
+        fn fails_when_editor_fails() {
+            let repo = create_test_repo();
+            let overlay = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+            apply_test_overlay(&repo, &overlay, false);
 
-            assert!(!content.contains("# repoverlay:test start"));
-            assert!(!content.contains(".envrc"));
-            assert!(!content.contains("# repoverlay:managed"));
+            let result = edit_overlay_file(repo.path(), "test", Path::new(".envrc"), "false");
+            assert!(result.unwrap_err().to_string().contains("Editor exited"));
         }
+    }
+
+    mod add {
+        use super::*;
 
         #[test]
-        fn fails_when_no_overlay_applied() {
+        fn expands_directories_to_files() {
             let repo = create_test_repo();
+            fs::create_dir_all(repo.path().join(".github/workflows")).unwrap();
+            fs::write(repo.path().join(".github/workflows/ci.yml"), "ci").unwrap();
+            fs::write(repo.path().join(".github/CODEOWNERS"), "*").unwrap();
+            fs::write(repo.path().join(".envrc"), "env").unwrap();
 
-            let result = remove_overlay(repo.path(), Some("nonexistent".to_string()), false, false);
-            assert!(result.is_err());
-            assert!(result.unwrap_err().to_string().contains("No overlay"));
+            let files = expand_add_paths(
+                repo.path(),
+                &[PathBuf::from("./.github/"), PathBuf::from(".envrc")],
+            )
+            .unwrap();
+
+            assert_eq!(
+                files,
+                vec![
+                    PathBuf::from(".github/CODEOWNERS"),
+                    PathBuf::from(".github/workflows/ci.yml"),
+                    PathBuf::from(".envrc"),
+                ]
+            );
         }
 
         #[test]
-        fn fails_on_unknown_overlay_name() {
+        fn expands_glob_patterns() {
             let repo = create_test_repo();
-            let overlay = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+            fs::create_dir_all(repo.path().join(".github/workflows")).unwrap();
+            fs::write(repo.path().join(".github/workflows/ci.yml"), "ci").unwrap();
+            fs::write(repo.path().join(".github/workflows/notes.md"), "notes").unwrap();
 
-            apply_overlay(
-                overlay.path().to_str().unwrap(),
+            let files = expand_add_paths(
                 repo.path(),
-                false,
-                Some("real-overlay".to_string()),
-                None,
-                false,
-                None,
-                false,
+                &[
+                    PathBuf::from(".github/workflows/*.yml"),
+                    PathBuf::from(".github/workflows/ci.yml"),
+                ],
+            )
+            .unwrap();
+
+            assert_eq!(files, vec![PathBuf::from(".github/workflows/ci.yml")]);
+        }
+
+        #[cfg(unix)]
+        #[test]
+        fn skips_symlinks_inside_directories() {
+            let repo = create_test_repo();
+            fs::create_dir_all(repo.path().join("config")).unwrap();
+            fs::write(repo.path().join("config/local.toml"), "x").unwrap();
+            std::os::unix::fs::symlink(
+                repo.path().join("config/local.toml"),
+                repo.path().join("config/linked.toml"),
             )
             .unwrap();
 
-            let result =
-                remove_overlay(repo.path(), Some("fake-overlay".to_string()), false, false);
-            assert!(result.is_err());
-            assert!(result.unwrap_err().to_string().contains("not found"));
+            let files = expand_add_paths(repo.path(), &[PathBuf::from("config")]).unwrap();
+            assert_eq!(files, vec![PathBuf::from("config/local.toml")]);
         }
 
         #[test]
-        fn handles_already_deleted_files() {
+        fn fails_on_missing_path_or_empty_match() {
             let repo = create_test_repo();
-            let overlay = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+            fs::create_dir_all(repo.path().join("empty")).unwrap();
+
+            let err = expand_add_paths(repo.path(), &[PathBuf::from("missing.txt")]).unwrap_err();
+            assert!(err.to_string().contains("File does not exist"));
+
+            let err = expand_add_paths(repo.path(), &[PathBuf::from("*.nothing")]).unwrap_err();
+            assert!(err.to_string().contains("matched no files"));
+
+            let err = expand_add_paths(repo.path(), &[PathBuf::from("empty")]).unwrap_err();
+            assert!(err.to_string().contains("No files found"));
+        }
+    }
+
+    mod sync {
+        use super::*;
 
+        fn apply_test_overlay(repo: &TempDir, overlay: &TempDir, copy: bool) {
             apply_overlay(
                 overlay.path().to_str().unwrap(),
                 repo.path(),
-                false,
-                Some("test".to_string()),
-                None,
-                false,
-                None,
-                false,
+                &ApplyOptions {
+                    force_copy: copy,
+                    name: Some("test".to_string()),
+                    ..Default::default()
+                },
             )
             .unwrap();
+        }
 
-            // Manually delete the file
-            fs::remove_file(repo.path().join(".envrc")).unwrap();
+        #[test]
+        fn copies_changes_and_deletes_removed_files() {
+            let repo = create_test_repo();
+            let overlay =
+                create_test_overlay(&[(".envrc", "export FOO=bar"), (".env.local", "LOCAL=true")]);
+            apply_test_overlay(&repo, &overlay, true);
 
-            // Remove should still succeed
-            let result = remove_overlay(repo.path(), Some("test".to_string()), false, false);
-            assert!(result.is_ok());
+            fs::write(repo.path().join(".envrc"), "export FOO=changed").unwrap();
+            fs::remove_file(repo.path().join(".env.local")).unwrap();
+
+            let mut state = crate::load_overlay_state(repo.path(), "test").unwrap();
+            let summary =
+                sync_overlay_files(repo.path(), "test", &mut state, overlay.path(), false, true)
+                    .unwrap();
+
+            assert_eq!(
+                summary,
+                SyncSummary {
+                    synced: 1,
+                    deleted: 1
+                }
+            );
+            assert_eq!(
+                fs::read_to_string(overlay.path().join(".envrc")).unwrap(),
+                "export FOO=changed"
+            );
+            assert!(!overlay.path().join(".env.local").exists());
+
+            let state = crate::load_overlay_state(repo.path(), "test").unwrap();
+            assert_eq!(state.file_count(), 1);
+            let exclude = fs::read_to_string(repo.path().join(".git/info/exclude")).unwrap();
+            assert!(!exclude.lines().any(|l| l == ".env.local"));
         }
 
         #[test]
-        fn removes_directory_symlink() {
+        fn leaves_symlinked_files_intact() {
             let repo = create_test_repo();
-            let overlay = TempDir::new().unwrap();
+            let overlay = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+            apply_test_overlay(&repo, &overlay, false);
 
-            // Create a directory with files inside
-            fs::create_dir_all(overlay.path().join("scratch")).unwrap();
-            fs::write(overlay.path().join("scratch/notes.txt"), "notes").unwrap();
+            let mut state = crate::load_overlay_state(repo.path(), "test").unwrap();
+            let summary =
+                sync_overlay_files(repo.path(), "test", &mut state, overlay.path(), false, true)
+                    .unwrap();
 
-            // Create config with directories list
-            fs::write(
-                overlay.path().join("repoverlay.ccl"),
-                "overlay =\n  name = test-overlay\n\ndirectories =\n  = scratch\n",
-            )
-            .unwrap();
+            assert_eq!(summary, SyncSummary::default());
+            assert_eq!(
+                fs::read_to_string(overlay.path().join(".envrc")).unwrap(),
+                "export FOO=bar"
+            );
+        }
 
-            apply_overlay(
-                overlay.path().to_str().unwrap(),
-                repo.path(),
-                false,
-                None,
-                None,
-                false,
-                None,
-                false,
-            )
-            .unwrap();
+        #[test]
+        fn dry_run_changes_nothing() {
+            let repo = create_test_repo();
+            let overlay =
+                create_test_overlay(&[(".envrc", "export FOO=bar"), (".env.local", "LOCAL=true")]);
+            apply_test_overlay(&repo, &overlay, true);
 
-            // Verify directory symlink exists
-            assert!(repo.path().join("scratch").is_symlink());
+            fs::write(repo.path().join(".envrc"), "export FOO=changed").unwrap();
+            fs::remove_file(repo.path().join(".env.local")).unwrap();
 
-            // Remove overlay
-            remove_overlay(repo.path(), Some("test-overlay".to_string()), false, false).unwrap();
+            let mut state = crate::load_overlay_state(repo.path(), "test").unwrap();
+            sync_overlay_files(repo.path(), "test", &mut state, overlay.path(), true, true)
+                .unwrap();
 
-            // Verify directory symlink was removed
-            assert!(!repo.path().join("scratch").exists());
-            assert!(!repo.path().join(".repoverlay").exists());
+            assert_eq!(
+                fs::read_to_string(overlay.path().join(".envrc")).unwrap(),
+                "export FOO=bar"
+            );
+            assert!(overlay.path().join(".env.local").exists());
+            let state = crate::load_overlay_state(repo.path(), "test").unwrap();
+            assert_eq!(state.file_count(), 2);
         }
 
         #[test]
-        fn removes_copied_directory() {
+        fn removes_overlay_when_every_file_is_deleted() {
             let repo = create_test_repo();
-            let overlay = TempDir::new().unwrap();
+            let overlay = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+            apply_test_overlay(&repo, &overlay, true);
 
-            // Create a directory with files inside
-            fs::create_dir_all(overlay.path().join("scratch")).unwrap();
-            fs::write(overlay.path().join("scratch/notes.txt"), "notes").unwrap();
+            fs::remove_file(repo.path().join(".envrc")).unwrap();
 
-            // Create config with directories list
-            fs::write(
-                overlay.path().join("repoverlay.ccl"),
-                "overlay =\n  name = test-overlay\n\ndirectories =\n  = scratch\n",
-            )
-            .unwrap();
+            let mut state = crate::load_overlay_state(repo.path(), "test").unwrap();
+            sync_overlay_files(repo.path(), "test", &mut state, overlay.path(), false, true)
+                .unwrap();
+
+            assert!(list_applied_overlays(repo.path()).unwrap().is_empty());
+        }
+    }
+
+    #[cfg(unix)]
+    mod verify {
+        use super::*;
 
+        fn apply_test_overlay(repo: &TempDir, overlay: &TempDir, copy: bool) {
             apply_overlay(
                 overlay.path().to_str().unwrap(),
                 repo.path(),
-                true, // copy mode
-                None,
-                None,
-                false,
-                None,
-                false,
+                &ApplyOptions {
+                    force_copy: copy,
+                    name: Some("test".to_string()),
+                    ..Default::default()
+                },
             )
             .unwrap();
+        }
 
-            // Verify directory was copied (not symlink)
-            let target_dir = repo.path().join("scratch");
-            assert!(!target_dir.is_symlink());
-            assert!(target_dir.is_dir());
+        fn verify_error(repo: &TempDir) -> String {
+            verify_overlays(repo.path()).unwrap_err().to_string()
+        }
 
-            // Remove overlay
-            remove_overlay(repo.path(), Some("test-overlay".to_string()), false, false).unwrap();
+        #[test]
+        fn succeeds_when_none_applied() {
+            let repo = create_test_repo();
+            assert!(verify_overlays(repo.path()).is_ok());
+        }
 
-            // Verify directory was removed
-            assert!(!repo.path().join("scratch").exists());
-            assert!(!repo.path().join(".repoverlay").exists());
+        #[test]
+        fn succeeds_for_intact_overlay() {
+            let repo = create_test_repo();
+            let overlay = create_test_overlay(&[
+                (".envrc", "export FOO=bar"),
+                (".vscode/settings.json", "{}"),
+            ]);
+            apply_test_overlay(&repo, &overlay, false);
+
+            assert!(verify_overlays(repo.path()).is_ok());
         }
 
         #[test]
-        fn dry_run_does_not_remove_overlay() {
+        fn fails_on_missing_file() {
             let repo = create_test_repo();
             let overlay = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+            apply_test_overlay(&repo, &overlay, true);
 
-            apply_overlay(
-                overlay.path().to_str().unwrap(),
-                repo.path(),
-                false,
-                Some("test-overlay".to_string()),
-                None,
-                false,
-                None,
-                false,
-            )
-            .unwrap();
-
-            // Dry run removal
-            let result = remove_overlay(repo.path(), Some("test-overlay".to_string()), false, true);
-            assert!(result.is_ok(), "dry_run remove failed: {result:?}");
+            fs::remove_file(repo.path().join(".envrc")).unwrap();
 
-            // Verify files are still present
-            assert!(
-                repo.path().join(".envrc").exists(),
-                ".envrc should still exist after dry run"
-            );
-            assert!(
-                repo.path().join(".repoverlay").exists(),
-                ".repoverlay should still exist after dry run"
-            );
+            assert!(verify_error(&repo).contains("1 problem(s)"));
         }
 
         #[test]
-        fn dry_run_all_does_not_remove_overlays() {
+        fn fails_on_broken_symlink() {
             let repo = create_test_repo();
-            let overlay1 = create_test_overlay(&[(".envrc", "export FOO=bar")]);
-            let overlay2 = create_test_overlay(&[(".env.local", "LOCAL=true")]);
-
-            apply_overlay(
-                overlay1.path().to_str().unwrap(),
-                repo.path(),
-                false,
-                Some("overlay-a".to_string()),
-                None,
-                false,
-                None,
-                false,
-            )
-            .unwrap();
-            apply_overlay(
-                overlay2.path().to_str().unwrap(),
-                repo.path(),
-                false,
-                Some("overlay-b".to_string()),
-                None,
-                false,
-                None,
-                false,
-            )
-            .unwrap();
+            let overlay = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+            apply_test_overlay(&repo, &overlay, false);
 
-            // Dry run removal of all
-            let result = remove_overlay(repo.path(), None, true, true);
-            assert!(result.is_ok(), "dry_run remove --all failed: {result:?}");
+            let link = repo.path().join(".envrc");
+            fs::remove_file(&link).unwrap();
+            std::os::unix::fs::symlink("/nonexistent/.envrc", &link).unwrap();
 
-            // Verify all files are still present
-            assert!(
-                repo.path().join(".envrc").exists(),
-                ".envrc should still exist after dry run"
-            );
-            assert!(
-                repo.path().join(".env.local").exists(),
-                ".env.local should still exist after dry run"
-            );
+            assert!(verify_error(&repo).contains("1 problem(s)"));
         }
 
         #[test]
-        fn handle_remove_requires_name_or_interactive_flag() {
+        fn fails_on_symlink_to_wrong_source() {
             let repo = create_test_repo();
+            let overlay = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+            apply_test_overlay(&repo, &overlay, false);
 
-            // Calling handle_remove without name, --all, or --interactive should fail
-            let result = handle_remove(repo.path(), None, false, false, false);
-            assert!(result.is_err());
-            let err = result.unwrap_err().to_string();
-            assert!(
-                err.contains("No overlay name specified"),
-                "Expected usage error, got: {err}"
-            );
+            let elsewhere = TempDir::new().unwrap();
+            fs::write(elsewhere.path().join(".envrc"), "other").unwrap();
+            let link = repo.path().join(".envrc");
+            fs::remove_file(&link).unwrap();
+            std::os::unix::fs::symlink(elsewhere.path().join(".envrc"), &link).unwrap();
+
+            assert!(verify_error(&repo).contains("1 problem(s)"));
         }
 
         #[test]
-        fn handle_remove_with_name_succeeds() {
+        fn fails_when_symlink_replaced_by_file() {
             let repo = create_test_repo();
             let overlay = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+            apply_test_overlay(&repo, &overlay, false);
 
-            apply_overlay(
-                overlay.path().to_str().unwrap(),
-                repo.path(),
-                false,
-                Some("test-overlay".to_string()),
-                None,
-                false,
-                None,
-                false,
-            )
-            .unwrap();
+            let link = repo.path().join(".envrc");
+            fs::remove_file(&link).unwrap();
+            fs::write(&link, "export FOO=bar").unwrap();
 
-            // Calling handle_remove with a name should succeed
-            let result = handle_remove(
-                repo.path(),
-                Some("test-overlay".to_string()),
-                false,
-                false,
-                false,
-            );
-            assert!(result.is_ok(), "handle_remove with name failed: {result:?}");
-            assert!(!repo.path().join(".envrc").exists());
+            assert!(verify_error(&repo).contains("1 problem(s)"));
         }
 
         #[test]
-        fn handle_remove_with_all_flag_succeeds() {
+        fn fails_when_exclude_section_missing() {
             let repo = create_test_repo();
             let overlay = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+            apply_test_overlay(&repo, &overlay, false);
 
+            fs::write(repo.path().join(".git/info/exclude"), "").unwrap();
+
+            assert!(verify_error(&repo).contains("1 problem(s)"));
+        }
+    }
+
+    #[cfg(unix)]
+    mod prune {
+        use super::*;
+
+        fn apply_named(repo: &TempDir, overlay: &TempDir, name: &str) {
             apply_overlay(
                 overlay.path().to_str().unwrap(),
                 repo.path(),
-                false,
-                Some("test-overlay".to_string()),
-                None,
-                false,
-                None,
-                false,
+                &ApplyOptions {
+                    name: Some(name.to_string()),
+                    ..Default::default()
+                },
             )
             .unwrap();
+        }
 
-            // Calling handle_remove with --all should succeed
-            let result = handle_remove(repo.path(), None, true, false, false);
-            assert!(
-                result.is_ok(),
-                "handle_remove with --all failed: {result:?}"
-            );
-            assert!(!repo.path().join(".envrc").exists());
+        fn exclude(repo: &TempDir) -> String {
+            fs::read_to_string(repo.path().join(".git/info/exclude")).unwrap_or_default()
         }
-    }
 
-    // Integration tests for status command
-    mod status {
-        use super::*;
+        #[test]
+        fn removes_state_of_overlay_with_all_files_missing() {
+            let repo = create_test_repo();
+            let gone = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+            let kept = create_test_overlay(&[(".env.local", "LOCAL=true")]);
+            apply_named(&repo, &gone, "gone");
+            apply_named(&repo, &kept, "kept");
+
+            fs::remove_file(repo.path().join(".envrc")).unwrap();
+            prune(repo.path(), false).unwrap();
+
+            assert_eq!(list_applied_overlays(repo.path()).unwrap(), vec!["kept"]);
+            let exclude = exclude(&repo);
+            assert!(!exclude.contains("# repoverlay:gone start"));
+            assert!(exclude.contains("# repoverlay:kept start"));
+            assert!(exclude.contains("# repoverlay:managed start"));
+        }
 
         #[test]
-        fn shows_no_overlay_when_none_applied() {
+        fn keeps_overlay_with_some_files_present() {
             let repo = create_test_repo();
-            let result = show_status(repo.path(), None);
-            assert!(result.is_ok());
+            let overlay =
+                create_test_overlay(&[(".envrc", "export FOO=bar"), (".env.local", "LOCAL=true")]);
+            apply_named(&repo, &overlay, "test");
+
+            fs::remove_file(repo.path().join(".envrc")).unwrap();
+            prune(repo.path(), false).unwrap();
+
+            assert_eq!(list_applied_overlays(repo.path()).unwrap(), vec!["test"]);
         }
 
         #[test]
-        fn shows_status_when_overlay_applied() {
+        fn removes_stale_exclude_sections_and_managed_section() {
             let repo = create_test_repo();
             let overlay = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+            apply_named(&repo, &overlay, "test");
 
-            apply_overlay(
-                overlay.path().to_str().unwrap(),
-                repo.path(),
-                false,
-                Some("test".to_string()),
-                None,
-                false,
-                None,
-                false,
-            )
-            .unwrap();
+            // State removed by hand, exclude left behind
+            fs::remove_dir_all(repo.path().join(STATE_DIR)).unwrap();
+            fs::remove_file(repo.path().join(".envrc")).unwrap();
+            prune(repo.path(), false).unwrap();
+
+            let exclude = exclude(&repo);
+            assert!(!exclude.contains("repoverlay:"), "{exclude}");
+        }
 
-            let result = show_status(repo.path(), None);
-            assert!(result.is_ok());
+        #[test]
+        fn removes_state_dir_when_no_overlays_remain() {
+            let repo = create_test_repo();
+            let overlay = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+            apply_named(&repo, &overlay, "test");
+
+            fs::remove_file(repo.path().join(".envrc")).unwrap();
+            prune(repo.path(), false).unwrap();
+
+            assert!(!repo.path().join(STATE_DIR).exists());
+            assert!(!exclude(&repo).contains("repoverlay:"));
         }
 
         #[test]
-        fn shows_status_for_multiple_overlays() {
+        fn keeps_repo_config() {
             let repo = create_test_repo();
-            let overlay1 = create_test_overlay(&[(".envrc", "export FOO=bar")]);
-            let overlay2 = create_test_overlay(&[(".env.local", "LOCAL=true")]);
+            let overlay = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+            apply_named(&repo, &overlay, "test");
+            let config_path = crate::config::repo_config_path(repo.path());
+            fs::write(&config_path, "sources =\n").unwrap();
 
-            apply_overlay(
-                overlay1.path().to_str().unwrap(),
-                repo.path(),
-                false,
-                Some("overlay-a".to_string()),
-                None,
-                false,
-                None,
-                false,
-            )
-            .unwrap();
-            apply_overlay(
-                overlay2.path().to_str().unwrap(),
-                repo.path(),
-                false,
-                Some("overlay-b".to_string()),
-                None,
-                false,
-                None,
-                false,
-            )
-            .unwrap();
+            fs::remove_file(repo.path().join(".envrc")).unwrap();
+            prune(repo.path(), false).unwrap();
 
-            let result = show_status(repo.path(), None);
-            assert!(result.is_ok());
+            assert!(list_applied_overlays(repo.path()).unwrap().is_empty());
+            assert!(!repo.path().join(STATE_DIR).join(OVERLAYS_DIR).exists());
+            assert!(
+                !repo
+                    .path()
+                    .join(STATE_DIR)
+                    .join(crate::state::META_FILE)
+                    .exists()
+            );
+            assert_eq!(fs::read_to_string(&config_path).unwrap(), "sources =\n");
         }
 
         #[test]
-        fn shows_status_for_specific_overlay() {
+        fn leaves_repo_with_only_config_untouched() {
             let repo = create_test_repo();
-            let overlay1 = create_test_overlay(&[(".envrc", "export FOO=bar")]);
-            let overlay2 = create_test_overlay(&[(".env.local", "LOCAL=true")]);
+            let config_path = crate::config::repo_config_path(repo.path());
+            fs::create_dir_all(config_path.parent().unwrap()).unwrap();
+            fs::write(&config_path, "sources =\n").unwrap();
 
-            apply_overlay(
-                overlay1.path().to_str().unwrap(),
-                repo.path(),
-                false,
-                Some("overlay-a".to_string()),
-                None,
-                false,
-                None,
-                false,
-            )
-            .unwrap();
-            apply_overlay(
-                overlay2.path().to_str().unwrap(),
-                repo.path(),
-                false,
-                Some("overlay-b".to_string()),
-                None,
-                false,
-                None,
-                false,
-            )
-            .unwrap();
+            prune(repo.path(), false).unwrap();
 
-            let result = show_status(repo.path(), Some("overlay-a".to_string()));
-            assert!(result.is_ok());
+            assert_eq!(fs::read_to_string(&config_path).unwrap(), "sources =\n");
         }
 
         #[test]
-        fn fails_on_unknown_overlay_filter() {
+        fn dry_run_changes_nothing() {
             let repo = create_test_repo();
             let overlay = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+            apply_named(&repo, &overlay, "test");
+            let before = exclude(&repo);
 
-            apply_overlay(
-                overlay.path().to_str().unwrap(),
-                repo.path(),
-                false,
-                Some("real".to_string()),
-                None,
-                false,
-                None,
-                false,
-            )
-            .unwrap();
+            fs::remove_file(repo.path().join(".envrc")).unwrap();
+            prune(repo.path(), true).unwrap();
 
-            let result = show_status(repo.path(), Some("fake".to_string()));
-            assert!(result.is_err());
+            assert_eq!(list_applied_overlays(repo.path()).unwrap(), vec!["test"]);
+            assert_eq!(exclude(&repo), before);
         }
     }
 
@@ -3303,6 +7283,9 @@ directories =
                 None,
                 false,
                 false,
+                true,
+                false,
+                false,
             );
             assert!(result.is_ok(), "create_overlay failed: {result:?}");
 
@@ -3319,6 +7302,38 @@ directories =
             assert!(config_file.exists(), "repoverlay.ccl should exist");
         }
 
+        #[test]
+        fn refuses_likely_secrets_unless_check_is_skipped() {
+            let source = create_test_repo();
+            let output = TempDir::new().unwrap();
+            fs::create_dir_all(source.path().join("config")).unwrap();
+            fs::write(
+                source.path().join("config/.env"),
+                "API_SECRET=s3cr3tvalue\n",
+            )
+            .unwrap();
+            let create = |secrets_check: bool| {
+                create_overlay(
+                    source.path(),
+                    Some(output.path().join("test-overlay")),
+                    &[PathBuf::from("config")],
+                    None,
+                    false,
+                    false,
+                    secrets_check,
+                    false,
+                    false,
+                )
+            };
+
+            let err = create(true).unwrap_err();
+            assert!(err.to_string().contains("--no-secrets-check"));
+            assert!(!output.path().join("test-overlay").exists());
+
+            create(false).unwrap();
+            assert!(output.path().join("test-overlay/config/.env").exists());
+        }
+
         #[test]
         fn creates_overlay_with_directory() {
             let source = create_test_repo();
@@ -3340,6 +7355,9 @@ directories =
                 None,
                 false,
                 false,
+                true,
+                false,
+                false,
             );
             assert!(result.is_ok(), "create_overlay failed: {result:?}");
 
@@ -3364,6 +7382,9 @@ directories =
                 Some("my-custom-name".to_string()),
                 false,
                 false,
+                true,
+                false,
+                false,
             );
             assert!(result.is_ok());
 
@@ -3386,6 +7407,9 @@ directories =
                 None,
                 true, // dry_run
                 false,
+                true,
+                false,
+                false,
             );
             assert!(result.is_ok());
 
@@ -3406,6 +7430,9 @@ directories =
                 None,
                 false,
                 false,
+                true,
+                false,
+                false,
             );
             assert!(result.is_err());
             // Error message now mentions discovery
@@ -3433,6 +7460,9 @@ directories =
                 None,
                 true, // dry_run
                 false,
+                true,
+                false,
+                false,
             );
             // Should succeed (just prints discovery info)
             assert!(result.is_ok());
@@ -3450,11 +7480,70 @@ directories =
                 None,
                 false,
                 false,
+                true,
+                false,
+                false,
             );
             assert!(result.is_err());
             assert!(result.unwrap_err().to_string().contains("does not exist"));
         }
 
+        #[test]
+        fn expands_glob_include_patterns() {
+            let source = create_test_repo();
+            let output = TempDir::new().unwrap();
+            let overlay_dir = output.path().join("test-overlay");
+
+            fs::create_dir_all(source.path().join(".github/workflows")).unwrap();
+            fs::write(source.path().join(".github/workflows/ci.yml"), "ci").unwrap();
+            fs::write(source.path().join(".github/workflows/release.yml"), "rel").unwrap();
+            fs::write(source.path().join(".github/workflows/notes.txt"), "n").unwrap();
+            fs::create_dir_all(source.path().join("agents/reviewer")).unwrap();
+            fs::write(source.path().join("agents/reviewer/prompt.md"), "p").unwrap();
+
+            create_overlay(
+                source.path(),
+                Some(overlay_dir.clone()),
+                &[
+                    PathBuf::from(".github/workflows/*.yml"),
+                    PathBuf::from("agent?"),
+                ],
+                None,
+                false,
+                false,
+                true,
+                false,
+                false,
+            )
+            .unwrap();
+
+            assert!(overlay_dir.join(".github/workflows/ci.yml").exists());
+            assert!(overlay_dir.join(".github/workflows/release.yml").exists());
+            assert!(!overlay_dir.join(".github/workflows/notes.txt").exists());
+            // Matched directories are copied recursively
+            assert!(overlay_dir.join("agents/reviewer/prompt.md").exists());
+        }
+
+        #[test]
+        fn fails_when_glob_include_matches_nothing() {
+            let source = create_test_repo();
+            let output = TempDir::new().unwrap();
+
+            let result = create_overlay(
+                source.path(),
+                Some(output.path().join("test-overlay")),
+                &[PathBuf::from("*.nothing")],
+                None,
+                false,
+                false,
+                true,
+                false,
+                false,
+            );
+            let err = result.unwrap_err().to_string();
+            assert!(err.contains("matched no files"), "{err}");
+        }
+
         #[test]
         fn fails_on_non_git_source() {
             let source = TempDir::new().unwrap(); // Not a git repo
@@ -3469,6 +7558,9 @@ directories =
                 None,
                 false,
                 false,
+                true,
+                false,
+                false,
             );
             assert!(result.is_err());
             assert!(
@@ -3499,6 +7591,9 @@ directories =
                 None,
                 false,
                 false,
+                true,
+                false,
+                false,
             );
             assert!(result.is_ok(), "create_overlay failed: {result:?}");
 
@@ -3530,6 +7625,9 @@ directories =
                 None,
                 true, // dry_run
                 false,
+                true,
+                false,
+                false,
             );
             assert!(result.is_ok());
 
@@ -3541,6 +7639,109 @@ directories =
         }
     }
 
+    mod parse_create_name_arg_tests {
+        use super::*;
+
+        #[test]
+        fn short_name_uses_target() {
+            let source = create_test_repo();
+            let parsed = parse_create_name_arg("env", Some("acme/web"), source.path()).unwrap();
+            assert_eq!(
+                parsed,
+                ("acme".to_string(), "web".to_string(), "env".to_string())
+            );
+        }
+
+        #[test]
+        fn full_form_must_match_target() {
+            let source = create_test_repo();
+            assert!(parse_create_name_arg("acme/web/env", Some("acme/web"), source.path()).is_ok());
+
+            let err =
+                parse_create_name_arg("acme/api/env", Some("acme/web"), source.path()).unwrap_err();
+            assert!(err.to_string().contains("doesn't match --target"));
+        }
+
+        #[test]
+        fn rejects_malformed_target() {
+            let source = create_test_repo();
+            for target in ["acme", "acme/", "/web", "acme/web/env"] {
+                let err = parse_create_name_arg("env", Some(target), source.path()).unwrap_err();
+                assert!(
+                    err.to_string().contains("Invalid target format"),
+                    "{target}"
+                );
+            }
+        }
+    }
+
+    mod read_include_file_tests {
+        use super::*;
+
+        #[test]
+        fn skips_blank_lines_and_comments() {
+            let dir = TempDir::new().unwrap();
+            let list = dir.path().join("includes.txt");
+            fs::write(
+                &list,
+                "# AI config\n.claude/\n\n  CLAUDE.md  \n.github/*.yml\n  # indented comment\n",
+            )
+            .unwrap();
+
+            assert_eq!(
+                read_include_file(&list).unwrap(),
+                [
+                    PathBuf::from(".claude/"),
+                    PathBuf::from("CLAUDE.md"),
+                    PathBuf::from(".github/*.yml")
+                ]
+            );
+        }
+
+        #[test]
+        fn missing_file_is_an_error() {
+            let dir = TempDir::new().unwrap();
+            let err = read_include_file(&dir.path().join("missing.txt")).unwrap_err();
+            assert!(err.to_string().contains("Failed to read include list"));
+        }
+    }
+
+    mod colliding_overlay_tests {
+        use super::*;
+
+        #[test]
+        fn finds_overlay_with_same_normalized_name() {
+            let repo_dir = TempDir::new().unwrap();
+            fs::create_dir(repo_dir.path().join("my-overlay")).unwrap();
+            fs::create_dir(repo_dir.path().join("other")).unwrap();
+
+            assert_eq!(
+                colliding_overlay(repo_dir.path(), "My-Overlay").unwrap(),
+                Some("my-overlay".to_string())
+            );
+            assert_eq!(
+                colliding_overlay(repo_dir.path(), "my overlay!").unwrap(),
+                Some("my-overlay".to_string())
+            );
+        }
+
+        #[test]
+        fn ignores_the_overlay_itself_and_other_names() {
+            let repo_dir = TempDir::new().unwrap();
+            fs::create_dir(repo_dir.path().join("my-overlay")).unwrap();
+
+            assert_eq!(
+                colliding_overlay(repo_dir.path(), "my-overlay").unwrap(),
+                None
+            );
+            assert_eq!(colliding_overlay(repo_dir.path(), "other").unwrap(), None);
+            assert_eq!(
+                colliding_overlay(&repo_dir.path().join("missing"), "my-overlay").unwrap(),
+                None
+            );
+        }
+    }
+
     // Unit tests for parse_overlay_name_arg
     mod parse_overlay_name_arg_tests {
         use super::*;
@@ -3740,17 +7941,156 @@ directories =
         }
 
         #[test]
-        fn cache_remove_fails_on_too_many_slashes() {
-            let result = handle_cache_command(CacheCommand::Remove {
-                repo: "a/b/c".to_string(),
-            });
-            assert!(result.is_err());
-            assert!(
-                result
-                    .unwrap_err()
-                    .to_string()
-                    .contains("Invalid repository format")
-            );
+        fn cache_info_fails_on_invalid_format() {
+            let result = handle_cache_command(CacheCommand::Info {
+                repo: "owner/".to_string(),
+            });
+            assert!(result.is_err());
+            assert!(
+                result
+                    .unwrap_err()
+                    .to_string()
+                    .contains("Invalid repository format")
+            );
+        }
+
+        #[test]
+        fn parse_cache_repo_arg_splits_owner_and_repo() {
+            assert_eq!(
+                parse_cache_repo_arg("owner/repo").unwrap(),
+                ("github.com", "owner", "repo")
+            );
+            assert_eq!(
+                parse_cache_repo_arg("ghe.example.com/owner/repo").unwrap(),
+                ("ghe.example.com", "owner", "repo")
+            );
+            assert!(parse_cache_repo_arg("owner").is_err());
+            assert!(parse_cache_repo_arg("a/b/c/d").is_err());
+            assert!(parse_cache_repo_arg("/repo").is_err());
+        }
+
+        #[test]
+        fn parse_age_accepts_units() {
+            assert_eq!(parse_age("30d").unwrap(), chrono::Duration::days(30));
+            assert_eq!(parse_age("12h").unwrap(), chrono::Duration::hours(12));
+            assert_eq!(parse_age("2w").unwrap(), chrono::Duration::weeks(2));
+            assert_eq!(parse_age("90m").unwrap(), chrono::Duration::minutes(90));
+            assert!(parse_age("30").is_err());
+            assert!(parse_age("d").is_err());
+            assert!(parse_age("3y").is_err());
+            assert!(parse_age("99999999999999w").is_err());
+        }
+
+        #[test]
+        fn format_age_uses_largest_whole_unit() {
+            assert_eq!(format_age(chrono::Duration::days(14)), "2w");
+            assert_eq!(format_age(chrono::Duration::days(30)), "30d");
+            assert_eq!(format_age(chrono::Duration::minutes(90)), "90m");
+            assert_eq!(format_age(chrono::Duration::seconds(5)), "5s");
+        }
+
+        #[test]
+        fn format_bytes_uses_readable_units() {
+            assert_eq!(format_bytes(0), "0 B");
+            assert_eq!(format_bytes(1023), "1023 B");
+            assert_eq!(format_bytes(1536), "1.5 KB");
+            assert_eq!(format_bytes(5 * 1024 * 1024), "5.0 MB");
+        }
+
+        #[test]
+        fn cache_remove_fails_on_too_many_slashes() {
+            let result = handle_cache_command(CacheCommand::Remove {
+                repo: "a/b/c/d".to_string(),
+            });
+            assert!(result.is_err());
+            assert!(
+                result
+                    .unwrap_err()
+                    .to_string()
+                    .contains("Invalid repository format")
+            );
+        }
+    }
+
+    // Unit tests for list --search matching
+    mod list_search {
+        use super::*;
+        use crate::state::OverlayConfigMeta;
+
+        fn overlay(name: &str, description: Option<&str>) -> AvailableOverlay {
+            AvailableOverlay {
+                org: "microsoft".to_string(),
+                repo: "FluidFramework".to_string(),
+                name: name.to_string(),
+                has_config: true,
+                meta: OverlayConfigMeta {
+                    description: description.map(String::from),
+                    ..Default::default()
+                },
+            }
+        }
+
+        #[test]
+        fn fuzzy_match_prefers_substring() {
+            assert_eq!(
+                fuzzy_match("claude-config", "CONF"),
+                Some(vec![7, 8, 9, 10])
+            );
+        }
+
+        #[test]
+        fn fuzzy_match_falls_back_to_subsequence() {
+            assert_eq!(
+                fuzzy_match("claude-config", "clcfg"),
+                Some(vec![0, 1, 7, 10, 12])
+            );
+            assert_eq!(fuzzy_match("claude-config", "xyz"), None);
+            assert_eq!(fuzzy_match("claude", "claudes"), None);
+        }
+
+        #[test]
+        fn substring_match_requires_contiguous_text() {
+            assert_eq!(
+                substring_match("Cursor rules", "RULES"),
+                Some(vec![7, 8, 9, 10, 11])
+            );
+            assert_eq!(substring_match("Cursor rules", "crs"), None);
+            assert_eq!(substring_match("abc", ""), None);
+        }
+
+        #[test]
+        fn find_matches_any_field() {
+            let matches = SearchMatches::find(&overlay("claude-config", None), "fluid").unwrap();
+            assert_eq!(matches.repo, vec![0, 1, 2, 3, 4]);
+            assert!(matches.name.is_empty());
+
+            let matches =
+                SearchMatches::find(&overlay("ai", Some("Shared editor settings")), "editor")
+                    .unwrap();
+            assert_eq!(matches.description, vec![7, 8, 9, 10, 11, 12]);
+        }
+
+        #[test]
+        fn find_does_not_fuzzy_match_descriptions() {
+            let overlay = overlay("ai", Some("Shared editor settings"));
+            assert!(SearchMatches::find(&overlay, "sds").is_none());
+        }
+
+        #[test]
+        fn highlight_matches_preserves_text() {
+            // Strip ANSI escapes so the result doesn't depend on terminal colors
+            let highlighted = highlight_matches("claude-config", &[0, 1, 7], |s| s.normal());
+            let mut plain = String::new();
+            let mut in_escape = false;
+            for c in highlighted.chars() {
+                match c {
+                    '\x1b' => in_escape = true,
+                    'm' if in_escape => in_escape = false,
+                    _ if !in_escape => plain.push(c),
+                    _ => {}
+                }
+            }
+            assert_eq!(plain, "claude-config");
         }
     }
 
@@ -3768,12 +8108,10 @@ directories =
             apply_overlay(
                 overlay1.path().to_str().unwrap(),
                 repo.path(),
-                false,
-                Some("first-overlay".to_string()),
-                None,
-                false,
-                None,
-                false,
+                &ApplyOptions {
+                    name: Some("first-overlay".to_string()),
+                    ..Default::default()
+                },
             )
             .unwrap();
 
@@ -3787,6 +8125,7 @@ directories =
                 false,
                 Some("second-overlay".to_string()),
                 None,
+                false,
             );
             assert!(result.is_ok(), "switch_overlay failed: {result:?}");
 
@@ -3814,6 +8153,7 @@ directories =
                 false,
                 Some("new-overlay".to_string()),
                 None,
+                false,
             );
             assert!(result.is_ok());
 
@@ -3831,6 +8171,7 @@ directories =
                 false,
                 None,
                 None,
+                false,
             );
             assert!(result.is_err());
             assert!(
@@ -3852,23 +8193,19 @@ directories =
             apply_overlay(
                 overlay1.path().to_str().unwrap(),
                 repo.path(),
-                false,
-                Some("overlay-a".to_string()),
-                None,
-                false,
-                None,
-                false,
+                &ApplyOptions {
+                    name: Some("overlay-a".to_string()),
+                    ..Default::default()
+                },
             )
             .unwrap();
             apply_overlay(
                 overlay2.path().to_str().unwrap(),
                 repo.path(),
-                false,
-                Some("overlay-b".to_string()),
-                None,
-                false,
-                None,
-                false,
+                &ApplyOptions {
+                    name: Some("overlay-b".to_string()),
+                    ..Default::default()
+                },
             )
             .unwrap();
 
@@ -3883,6 +8220,7 @@ directories =
                 false,
                 Some("overlay-c".to_string()),
                 None,
+                false,
             )
             .unwrap();
 
@@ -3893,6 +8231,137 @@ directories =
             // Verify new overlay is applied
             assert!(repo.path().join(".env.prod").exists());
         }
+
+        #[test]
+        fn dry_run_changes_nothing() {
+            let repo = create_test_repo();
+            let overlay1 = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+            let overlay2 = create_test_overlay(&[(".env.local", "LOCAL=true")]);
+
+            apply_overlay(
+                overlay1.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    name: Some("overlay-a".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+            switch_overlay(
+                overlay2.path().to_str().unwrap(),
+                repo.path(),
+                false,
+                Some("overlay-b".to_string()),
+                None,
+                true,
+            )
+            .unwrap();
+
+            assert!(repo.path().join(".envrc").exists());
+            assert!(!repo.path().join(".env.local").exists());
+            assert_eq!(list_applied_overlays(repo.path()).unwrap(), ["overlay-a"]);
+        }
+
+        fn apply_named(source: &std::path::Path, target: &std::path::Path, name: &str) {
+            apply_overlay(
+                source.to_str().unwrap(),
+                target,
+                &ApplyOptions {
+                    name: Some(name.to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        }
+
+        #[test]
+        fn invalid_source_leaves_overlays_applied() {
+            let repo = create_test_repo();
+            let overlay = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+            apply_named(overlay.path(), repo.path(), "overlay-a");
+
+            let missing = overlay.path().join("does-not-exist");
+            let result = switch_overlay(
+                missing.to_str().unwrap(),
+                repo.path(),
+                false,
+                None,
+                None,
+                false,
+            );
+
+            assert!(result.is_err());
+            assert!(repo.path().join(".envrc").is_symlink());
+            assert_eq!(list_applied_overlays(repo.path()).unwrap(), ["overlay-a"]);
+            let exclude = fs::read_to_string(repo.path().join(".git/info/exclude")).unwrap();
+            assert!(exclude.contains("# repoverlay:overlay-a start"));
+        }
+
+        #[test]
+        fn failed_apply_restores_previous_overlays() {
+            let repo = create_test_repo();
+            let overlay1 = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+            let overlay2 = create_test_overlay(&[(".env", "A=1")]);
+            let overlay3 = create_test_overlay(&[(".envrc", "export FOO=baz"), ("README.md", "x")]);
+            apply_named(overlay1.path(), repo.path(), "overlay-a");
+            apply_named(overlay2.path(), repo.path(), "overlay-b");
+            fs::write(repo.path().join("README.md"), "# Repo").unwrap();
+
+            // README.md is a repo file, so applying overlay3 conflicts after
+            // the existing overlays were set aside.
+            let result = switch_overlay(
+                overlay3.path().to_str().unwrap(),
+                repo.path(),
+                false,
+                Some("overlay-c".to_string()),
+                None,
+                false,
+            );
+
+            assert!(result.is_err());
+            assert_eq!(
+                list_applied_overlays(repo.path()).unwrap(),
+                ["overlay-a", "overlay-b"]
+            );
+            assert_eq!(
+                fs::read_to_string(repo.path().join(".envrc")).unwrap(),
+                "export FOO=bar"
+            );
+            assert!(repo.path().join(".env").is_symlink());
+            assert_eq!(
+                fs::read_to_string(repo.path().join("README.md")).unwrap(),
+                "# Repo"
+            );
+            assert!(!repo.path().join(".repoverlay/stash").exists());
+        }
+
+        #[test]
+        fn successful_switch_drops_previous_overlays() {
+            let repo = create_test_repo();
+            let overlay1 = create_test_overlay(&[(".envrc", "export FOO=bar")]);
+            let overlay2 = create_test_overlay(&[(".envrc", "export FOO=baz")]);
+            apply_named(overlay1.path(), repo.path(), "overlay-a");
+
+            switch_overlay(
+                overlay2.path().to_str().unwrap(),
+                repo.path(),
+                false,
+                Some("overlay-b".to_string()),
+                None,
+                false,
+            )
+            .unwrap();
+
+            assert_eq!(list_applied_overlays(repo.path()).unwrap(), ["overlay-b"]);
+            assert_eq!(
+                fs::read_to_string(repo.path().join(".envrc")).unwrap(),
+                "export FOO=baz"
+            );
+            let exclude = fs::read_to_string(repo.path().join(".git/info/exclude")).unwrap();
+            assert!(!exclude.contains("# repoverlay:overlay-a start"));
+            assert!(!repo.path().join(".repoverlay/stash").exists());
+        }
     }
 
     // CLI structure and parsing tests using clap's try_parse_from()
@@ -3913,7 +8382,135 @@ directories =
 
             match cli.command {
                 Some(Commands::Apply { source, .. }) => {
-                    assert_eq!(source, "./my-overlay");
+                    assert_eq!(source, vec!["./my-overlay"]);
+                }
+                _ => panic!("Expected Apply command"),
+            }
+        }
+
+        #[test]
+        fn color_flag_is_global() {
+            let cli = Cli::try_parse_from(["repoverlay", "status", "--color", "never"]).unwrap();
+            assert_eq!(cli.color, ColorChoice::Never);
+
+            let cli = Cli::try_parse_from(["repoverlay", "--color", "always", "status"]).unwrap();
+            assert_eq!(cli.color, ColorChoice::Always);
+
+            let cli = Cli::try_parse_from(["repoverlay", "status"]).unwrap();
+            assert_eq!(cli.color, ColorChoice::Auto);
+        }
+
+        #[test]
+        fn verbosity_flags_are_global() {
+            let cli = Cli::try_parse_from(["repoverlay", "-vv", "status"]).unwrap();
+            assert_eq!(cli.verbosity, 2);
+            assert_eq!(cli.log_file, None);
+
+            // -v doesn't clash with apply's --verbose file listing
+            let cli = Cli::try_parse_from([
+                "repoverlay",
+                "apply",
+                "./overlay",
+                "-v",
+                "--verbose",
+                "--log-file",
+                "debug.log",
+            ])
+            .unwrap();
+            assert_eq!(cli.verbosity, 1);
+            assert_eq!(cli.log_file, Some(PathBuf::from("debug.log")));
+            assert!(matches!(
+                cli.command,
+                Some(Commands::Apply { verbose: true, .. })
+            ));
+        }
+
+        #[test]
+        fn quiet_flag_is_global() {
+            let cli = Cli::try_parse_from(["repoverlay", "apply", "./overlay", "-q"]).unwrap();
+            assert!(cli.quiet);
+
+            let cli = Cli::try_parse_from(["repoverlay", "--quiet", "remove", "--all"]).unwrap();
+            assert!(cli.quiet);
+
+            let cli = Cli::try_parse_from(["repoverlay", "update"]).unwrap();
+            assert!(!cli.quiet);
+        }
+
+        #[test]
+        fn offline_flag_is_global() {
+            let cli = Cli::try_parse_from(["repoverlay", "restore", "--offline"]).unwrap();
+            assert!(cli.offline);
+
+            let cli =
+                Cli::try_parse_from(["repoverlay", "--offline", "apply", "org/repo/name"]).unwrap();
+            assert!(cli.offline);
+        }
+
+        #[test]
+        fn color_choice_explicit_values_win() {
+            assert!(ColorChoice::Always.should_colorize(Some("1"), None, false));
+            assert!(!ColorChoice::Never.should_colorize(None, Some("1"), true));
+        }
+
+        #[test]
+        fn color_choice_auto_honors_environment() {
+            assert!(ColorChoice::Auto.should_colorize(None, None, true));
+            assert!(!ColorChoice::Auto.should_colorize(None, None, false));
+            assert!(!ColorChoice::Auto.should_colorize(Some("1"), None, true));
+            // An empty NO_COLOR is ignored
+            assert!(ColorChoice::Auto.should_colorize(Some(""), None, true));
+            assert!(ColorChoice::Auto.should_colorize(None, Some("1"), false));
+            assert!(!ColorChoice::Auto.should_colorize(None, Some("0"), false));
+            // NO_COLOR takes precedence over CLICOLOR_FORCE
+            assert!(!ColorChoice::Auto.should_colorize(Some("1"), Some("1"), true));
+        }
+
+        #[test]
+        fn apply_parses_preview_alias() {
+            let cli = Cli::try_parse_from(["repoverlay", "apply", "./o", "--preview"]).unwrap();
+
+            match cli.command {
+                Some(Commands::Apply { dry_run, .. }) => assert!(dry_run),
+                _ => panic!("Expected Apply command"),
+            }
+        }
+
+        #[test]
+        fn apply_parses_on_conflict() {
+            let cli = Cli::try_parse_from(["repoverlay", "apply", "./o", "--on-conflict", "skip"])
+                .unwrap();
+
+            match cli.command {
+                Some(Commands::Apply { on_conflict, .. }) => {
+                    assert_eq!(on_conflict, OnConflict::Skip);
+                }
+                _ => panic!("Expected Apply command"),
+            }
+        }
+
+        #[test]
+        fn apply_on_conflict_conflicts_with_force() {
+            let result = Cli::try_parse_from([
+                "repoverlay",
+                "apply",
+                "./o",
+                "--force",
+                "--on-conflict",
+                "overwrite",
+            ]);
+            assert!(result.is_err());
+        }
+
+        #[test]
+        fn apply_parses_multiple_sources() {
+            let cli =
+                Cli::try_parse_from(["repoverlay", "apply", "./one", "./two", "--copy"]).unwrap();
+
+            match cli.command {
+                Some(Commands::Apply { source, copy, .. }) => {
+                    assert_eq!(source, vec!["./one", "./two"]);
+                    assert!(copy);
                 }
                 _ => panic!("Expected Apply command"),
             }
@@ -3933,6 +8530,8 @@ directories =
                 "--ref",
                 "main",
                 "--update",
+                "--subpath",
+                "overlays",
             ])
             .unwrap();
 
@@ -3944,32 +8543,155 @@ directories =
                     name,
                     r#ref,
                     update,
+                    subpath,
                     from_source,
                     dry_run,
+                    on_conflict,
+                    force,
+                    verbose,
+                    set,
+                    only,
+                    no_deps,
+                    run_hooks,
+                    prefix,
                 }) => {
-                    assert_eq!(source, "./overlay");
+                    assert_eq!(source, vec!["./overlay"]);
                     assert_eq!(target, Some(PathBuf::from("/path/to/repo")));
                     assert!(copy);
                     assert_eq!(name, Some("my-name".to_string()));
                     assert_eq!(r#ref, Some("main".to_string()));
                     assert!(update);
+                    assert_eq!(subpath.as_deref(), Some("overlays"));
                     assert!(from_source.is_none());
                     assert!(!dry_run);
+                    assert_eq!(on_conflict, OnConflict::Fail);
+                    assert!(!force);
+                    assert!(!verbose);
+                    assert!(set.is_empty());
+                    assert!(only.is_empty());
+                    assert!(!no_deps);
+                    assert!(!run_hooks);
+                    assert!(prefix.is_none());
+                }
+                _ => panic!("Expected Apply command"),
+            }
+        }
+
+        #[test]
+        fn apply_parses_prefix() {
+            let cli = Cli::try_parse_from([
+                "repoverlay",
+                "apply",
+                "./overlay",
+                "--prefix",
+                "packages/foo",
+            ])
+            .unwrap();
+            match cli.command {
+                Some(Commands::Apply { prefix, .. }) => {
+                    assert_eq!(prefix, Some(PathBuf::from("packages/foo")));
+                }
+                _ => panic!("Expected Apply command"),
+            }
+        }
+
+        #[test]
+        fn apply_parses_dry_run() {
+            let cli =
+                Cli::try_parse_from(["repoverlay", "apply", "./overlay", "--dry-run"]).unwrap();
+
+            match cli.command {
+                Some(Commands::Apply { dry_run, .. }) => {
+                    assert!(dry_run);
+                }
+                _ => panic!("Expected Apply command"),
+            }
+        }
+
+        #[test]
+        fn diff_parses_name_and_target() {
+            let cli = Cli::try_parse_from([
+                "repoverlay",
+                "diff",
+                "my-overlay",
+                "--target",
+                "/path/to/repo",
+            ])
+            .unwrap();
+
+            match cli.command {
+                Some(Commands::Diff { name, target }) => {
+                    assert_eq!(name, Some("my-overlay".to_string()));
+                    assert_eq!(target, Some(PathBuf::from("/path/to/repo")));
+                }
+                _ => panic!("Expected Diff command"),
+            }
+        }
+
+        #[test]
+        fn repair_parses_name_and_target() {
+            let cli = Cli::try_parse_from([
+                "repoverlay",
+                "repair",
+                "my-overlay",
+                "--target",
+                "/path/to/repo",
+            ])
+            .unwrap();
+
+            match cli.command {
+                Some(Commands::Repair { name, target }) => {
+                    assert_eq!(name, Some("my-overlay".to_string()));
+                    assert_eq!(target, Some(PathBuf::from("/path/to/repo")));
+                }
+                _ => panic!("Expected Repair command"),
+            }
+        }
+
+        #[test]
+        fn unlink_parses_overlay_and_file() {
+            let cli =
+                Cli::try_parse_from(["repoverlay", "unlink", "my-overlay", ".envrc"]).unwrap();
+
+            match cli.command {
+                Some(Commands::Unlink {
+                    overlay,
+                    file,
+                    force,
+                    target,
+                }) => {
+                    assert_eq!(overlay, "my-overlay");
+                    assert_eq!(file, PathBuf::from(".envrc"));
+                    assert!(!force);
+                    assert!(target.is_none());
                 }
-                _ => panic!("Expected Apply command"),
+                _ => panic!("Expected Unlink command"),
             }
         }
 
         #[test]
-        fn apply_parses_dry_run() {
+        fn verify_parses_target() {
             let cli =
-                Cli::try_parse_from(["repoverlay", "apply", "./overlay", "--dry-run"]).unwrap();
+                Cli::try_parse_from(["repoverlay", "verify", "--target", "/path/to/repo"]).unwrap();
 
             match cli.command {
-                Some(Commands::Apply { dry_run, .. }) => {
+                Some(Commands::Verify { target }) => {
+                    assert_eq!(target, Some(PathBuf::from("/path/to/repo")));
+                }
+                _ => panic!("Expected Verify command"),
+            }
+        }
+
+        #[test]
+        fn prune_parses_dry_run() {
+            let cli = Cli::try_parse_from(["repoverlay", "prune", "--dry-run"]).unwrap();
+
+            match cli.command {
+                Some(Commands::Prune { target, dry_run }) => {
+                    assert!(target.is_none());
                     assert!(dry_run);
                 }
-                _ => panic!("Expected Apply command"),
+                _ => panic!("Expected Prune command"),
             }
         }
 
@@ -4047,14 +8769,35 @@ directories =
             let cli = Cli::try_parse_from(["repoverlay", "status"]).unwrap();
 
             match cli.command {
-                Some(Commands::Status { target, name }) => {
+                Some(Commands::Status {
+                    target,
+                    name,
+                    json,
+                    check_drift,
+                    verify_remote,
+                    broken,
+                }) => {
                     assert!(target.is_none());
                     assert!(name.is_none());
+                    assert!(!json);
+                    assert!(!check_drift);
+                    assert!(!verify_remote);
+                    assert!(!broken);
                 }
                 _ => panic!("Expected Status command"),
             }
         }
 
+        #[test]
+        fn status_parses_verify_remote() {
+            let cli = Cli::try_parse_from(["repoverlay", "status", "--verify-remote"]).unwrap();
+
+            match cli.command {
+                Some(Commands::Status { verify_remote, .. }) => assert!(verify_remote),
+                _ => panic!("Expected Status command"),
+            }
+        }
+
         #[test]
         fn status_parses_name_filter() {
             let cli =
@@ -4080,6 +8823,67 @@ directories =
             }
         }
 
+        #[test]
+        fn restore_and_update_parse_jobs() {
+            let cli = Cli::try_parse_from(["repoverlay", "restore"]).unwrap();
+            match cli.command {
+                Some(Commands::Restore { jobs, .. }) => assert_eq!(jobs, DEFAULT_JOBS),
+                _ => panic!("Expected Restore command"),
+            }
+
+            let cli = Cli::try_parse_from(["repoverlay", "update", "--jobs", "8"]).unwrap();
+            match cli.command {
+                Some(Commands::Update { jobs, .. }) => assert_eq!(jobs, 8),
+                _ => panic!("Expected Update command"),
+            }
+
+            let cli = Cli::try_parse_from(["repoverlay", "restore", "-j", "2"]).unwrap();
+            match cli.command {
+                Some(Commands::Restore { jobs, .. }) => assert_eq!(jobs, 2),
+                _ => panic!("Expected Restore command"),
+            }
+        }
+
+        #[test]
+        fn restore_parses_include_removed_and_forget() {
+            let cli = Cli::try_parse_from(["repoverlay", "restore", "--include-removed"]).unwrap();
+            match cli.command {
+                Some(Commands::Restore {
+                    include_removed,
+                    forget,
+                    ..
+                }) => {
+                    assert!(include_removed);
+                    assert!(forget.is_none());
+                }
+                _ => panic!("Expected Restore command"),
+            }
+
+            let cli = Cli::try_parse_from(["repoverlay", "restore", "--forget", "old"]).unwrap();
+            match cli.command {
+                Some(Commands::Restore { forget, .. }) => {
+                    assert_eq!(forget, Some("old".to_string()));
+                }
+                _ => panic!("Expected Restore command"),
+            }
+
+            assert!(
+                Cli::try_parse_from(["repoverlay", "restore", "--forget", "a", "--dry-run"])
+                    .is_err()
+            );
+        }
+
+        #[test]
+        fn restore_parses_only_list() {
+            let cli =
+                Cli::try_parse_from(["repoverlay", "restore", "--only", "a,b", "--only", "c"])
+                    .unwrap();
+            match cli.command {
+                Some(Commands::Restore { only, .. }) => assert_eq!(only, vec!["a", "b", "c"]),
+                _ => panic!("Expected Restore command"),
+            }
+        }
+
         #[test]
         fn update_parses_overlay_name() {
             let cli = Cli::try_parse_from(["repoverlay", "update", "my-overlay"]).unwrap();
@@ -4093,6 +8897,30 @@ directories =
             }
         }
 
+        #[test]
+        fn update_parses_all_and_apply() {
+            let cli = Cli::try_parse_from(["repoverlay", "update", "--all", "--apply"]).unwrap();
+            match cli.command {
+                Some(Commands::Update {
+                    name, all, apply, ..
+                }) => {
+                    assert_eq!(name, None);
+                    assert!(all);
+                    assert!(apply);
+                }
+                _ => panic!("Expected Update command"),
+            }
+
+            let cli = Cli::try_parse_from(["repoverlay", "update", "--force"]).unwrap();
+            assert!(matches!(
+                cli.command,
+                Some(Commands::Update { force: true, .. })
+            ));
+
+            assert!(Cli::try_parse_from(["repoverlay", "update", "x", "--all"]).is_err());
+            assert!(Cli::try_parse_from(["repoverlay", "update", "--apply", "--dry-run"]).is_err());
+        }
+
         #[test]
         fn create_parses_options() {
             let cli = Cli::try_parse_from([
@@ -4125,6 +8953,31 @@ directories =
             }
         }
 
+        #[test]
+        fn create_parses_dereference() {
+            let cli = Cli::try_parse_from(["repoverlay", "create", "my-overlay", "--dereference"])
+                .unwrap();
+
+            match cli.command {
+                Some(Commands::Create { dereference, .. }) => assert!(dereference),
+                _ => panic!("Expected Create command"),
+            }
+        }
+
+        #[test]
+        fn create_parses_target() {
+            let cli =
+                Cli::try_parse_from(["repoverlay", "create", "my-overlay", "--target", "acme/web"])
+                    .unwrap();
+
+            match cli.command {
+                Some(Commands::Create { target, .. }) => {
+                    assert_eq!(target.as_deref(), Some("acme/web"));
+                }
+                _ => panic!("Expected Create command"),
+            }
+        }
+
         #[test]
         fn create_requires_name() {
             let result = Cli::try_parse_from(["repoverlay", "create"]);
@@ -4170,6 +9023,36 @@ directories =
             }
         }
 
+        #[test]
+        fn switch_parses_subpath() {
+            let cli = Cli::try_parse_from([
+                "repoverlay",
+                "switch",
+                "https://github.com/owner/repo",
+                "--subpath",
+                "overlays/rust",
+            ])
+            .unwrap();
+
+            match cli.command {
+                Some(Commands::Switch { subpath, .. }) => {
+                    assert_eq!(subpath.as_deref(), Some("overlays/rust"));
+                }
+                _ => panic!("Expected Switch command"),
+            }
+        }
+
+        #[test]
+        fn switch_parses_dry_run() {
+            let cli = Cli::try_parse_from(["repoverlay", "switch", "./new-overlay", "--dry-run"])
+                .unwrap();
+
+            match cli.command {
+                Some(Commands::Switch { dry_run, .. }) => assert!(dry_run),
+                _ => panic!("Expected Switch command"),
+            }
+        }
+
         #[test]
         fn list_parses_filter() {
             let cli = Cli::try_parse_from(["repoverlay", "list", "--filter", "org/repo"]).unwrap();
@@ -4182,6 +9065,31 @@ directories =
             }
         }
 
+        #[test]
+        fn list_parses_applied_with_optional_dir() {
+            let cli = Cli::try_parse_from(["repoverlay", "list", "--applied"]).unwrap();
+            match cli.command {
+                Some(Commands::List { applied, .. }) => {
+                    assert_eq!(applied, Some(PathBuf::from(".")));
+                }
+                _ => panic!("Expected List command"),
+            }
+
+            let cli = Cli::try_parse_from(["repoverlay", "list", "--applied", "../app"]).unwrap();
+            match cli.command {
+                Some(Commands::List { applied, .. }) => {
+                    assert_eq!(applied, Some(PathBuf::from("../app")));
+                }
+                _ => panic!("Expected List command"),
+            }
+
+            assert!(
+                Cli::try_parse_from(["repoverlay", "list", "--applied", "--filter", "org/repo"])
+                    .is_err()
+            );
+            assert!(Cli::try_parse_from(["repoverlay", "list", "--applied", "--json"]).is_err());
+        }
+
         #[test]
         fn list_parses_target_alias() {
             // --target should work as an alias for --filter
@@ -4195,6 +9103,195 @@ directories =
             }
         }
 
+        #[test]
+        fn list_parses_tag() {
+            let cli = Cli::try_parse_from(["repoverlay", "list", "--tag", "ai"]).unwrap();
+
+            match cli.command {
+                Some(Commands::List { tag, .. }) => {
+                    assert_eq!(tag, Some("ai".to_string()));
+                }
+                _ => panic!("Expected List command"),
+            }
+        }
+
+        #[test]
+        fn overlay_repo_commands_parse_from_source() {
+            for args in [
+                vec!["repoverlay", "list", "--from", "private"],
+                vec!["repoverlay", "create", "my-overlay", "--from", "private"],
+                vec!["repoverlay", "sync", "--all", "--from", "private"],
+                vec!["repoverlay", "publish", "./overlay", "--from", "private"],
+                vec!["repoverlay", "delete", "org/repo/name", "--from", "private"],
+                vec!["repoverlay", "push", "--from", "private"],
+            ] {
+                let Some(
+                    Commands::List { from_source, .. }
+                    | Commands::Create { from_source, .. }
+                    | Commands::Sync { from_source, .. }
+                    | Commands::Publish { from_source, .. }
+                    | Commands::Delete { from_source, .. }
+                    | Commands::Push { from_source, .. },
+                ) = Cli::try_parse_from(&args).unwrap().command
+                else {
+                    panic!("Unexpected command for {args:?}");
+                };
+                assert_eq!(from_source.as_deref(), Some("private"), "{args:?}");
+            }
+        }
+
+        #[test]
+        fn info_parses_source_and_ref() {
+            let cli = Cli::try_parse_from(["repoverlay", "info", "org/repo/name", "--ref", "v1"])
+                .unwrap();
+
+            match cli.command {
+                Some(Commands::Info { source, r#ref, .. }) => {
+                    assert_eq!(source, "org/repo/name");
+                    assert_eq!(r#ref, Some("v1".to_string()));
+                }
+                _ => panic!("Expected Info command"),
+            }
+        }
+
+        #[test]
+        fn edit_parses_overlay_and_file() {
+            let cli = Cli::try_parse_from(["repoverlay", "edit", "my-overlay", ".envrc"]).unwrap();
+
+            match cli.command {
+                Some(Commands::Edit {
+                    overlay,
+                    file,
+                    target,
+                }) => {
+                    assert_eq!(overlay, "my-overlay");
+                    assert_eq!(file, PathBuf::from(".envrc"));
+                    assert!(target.is_none());
+                }
+                _ => panic!("Expected Edit command"),
+            }
+        }
+
+        #[test]
+        fn mv_parses_overlay_and_paths() {
+            let cli =
+                Cli::try_parse_from(["repoverlay", "mv", "my-overlay", ".envrc", "config/.envrc"])
+                    .unwrap();
+
+            match cli.command {
+                Some(Commands::Mv {
+                    overlay,
+                    from,
+                    to,
+                    target,
+                }) => {
+                    assert_eq!(overlay, "my-overlay");
+                    assert_eq!(from, PathBuf::from(".envrc"));
+                    assert_eq!(to, PathBuf::from("config/.envrc"));
+                    assert!(target.is_none());
+                }
+                _ => panic!("Expected Mv command"),
+            }
+        }
+
+        #[test]
+        fn which_parses_file_and_target() {
+            let cli = Cli::try_parse_from(["repoverlay", "which", ".envrc", "--target", "/repo"])
+                .unwrap();
+
+            match cli.command {
+                Some(Commands::Which { file, target }) => {
+                    assert_eq!(file, PathBuf::from(".envrc"));
+                    assert_eq!(target, Some(PathBuf::from("/repo")));
+                }
+                _ => panic!("Expected Which command"),
+            }
+        }
+
+        #[test]
+        fn sync_parses_all_and_yes() {
+            let cli = Cli::try_parse_from(["repoverlay", "sync", "--all", "-y"]).unwrap();
+
+            match cli.command {
+                Some(Commands::Sync { name, all, yes, .. }) => {
+                    assert!(name.is_none());
+                    assert!(all);
+                    assert!(yes);
+                }
+                _ => panic!("Expected Sync command"),
+            }
+        }
+
+        #[test]
+        fn sync_requires_name_or_all() {
+            assert!(Cli::try_parse_from(["repoverlay", "sync"]).is_err());
+            assert!(Cli::try_parse_from(["repoverlay", "sync", "my-overlay", "--all"]).is_err());
+        }
+
+        #[test]
+        fn completions_parses_shell() {
+            let cli = Cli::try_parse_from(["repoverlay", "completions", "powershell"]).unwrap();
+
+            match cli.command {
+                Some(Commands::Completions { shell }) => assert_eq!(shell, Shell::Powershell),
+                _ => panic!("Expected Completions command"),
+            }
+        }
+
+        #[test]
+        fn bootstrap_parses_yes_and_target() {
+            let cli = Cli::try_parse_from(["repoverlay", "bootstrap", "-y", "--target", "/repo"])
+                .unwrap();
+
+            match cli.command {
+                Some(Commands::Bootstrap { target, yes }) => {
+                    assert_eq!(target, Some(PathBuf::from("/repo")));
+                    assert!(yes);
+                }
+                _ => panic!("Expected Bootstrap command"),
+            }
+        }
+
+        #[test]
+        fn apply_bundle_parses_options() {
+            let cli = Cli::try_parse_from([
+                "repoverlay",
+                "apply-bundle",
+                "frontend-standard",
+                "--copy",
+                "--force",
+            ])
+            .unwrap();
+
+            match cli.command {
+                Some(Commands::ApplyBundle {
+                    bundle,
+                    copy,
+                    force,
+                    update,
+                    ..
+                }) => {
+                    assert_eq!(bundle, "frontend-standard");
+                    assert!(copy);
+                    assert!(force);
+                    assert!(!update);
+                }
+                _ => panic!("Expected ApplyBundle command"),
+            }
+        }
+
+        #[test]
+        fn list_parses_search() {
+            let cli = Cli::try_parse_from(["repoverlay", "list", "-s", "claude"]).unwrap();
+
+            match cli.command {
+                Some(Commands::List { search, .. }) => {
+                    assert_eq!(search, Some("claude".to_string()));
+                }
+                _ => panic!("Expected List command"),
+            }
+        }
+
         #[test]
         fn cache_list_subcommand() {
             let cli = Cli::try_parse_from(["repoverlay", "cache", "list"]).unwrap();
@@ -4214,7 +9311,7 @@ directories =
 
             match cli.command {
                 Some(Commands::Cache { command }) => match command {
-                    CacheCommand::Clear { yes } => {
+                    CacheCommand::Clear { yes, .. } => {
                         assert!(!yes, "default yes should be false");
                     }
                     _ => panic!("Expected Cache Clear subcommand"),
@@ -4223,13 +9320,29 @@ directories =
             }
         }
 
+        #[test]
+        fn cache_gc_subcommand() {
+            let cli = Cli::try_parse_from(["repoverlay", "cache", "gc", "--dry-run"]).unwrap();
+
+            match cli.command {
+                Some(Commands::Cache { command }) => match command {
+                    CacheCommand::Gc { dry_run, keep_refs } => {
+                        assert!(dry_run);
+                        assert!(!keep_refs);
+                    }
+                    _ => panic!("Expected Cache Gc subcommand"),
+                },
+                _ => panic!("Expected Cache command"),
+            }
+        }
+
         #[test]
         fn cache_clear_with_yes_flag() {
             let cli = Cli::try_parse_from(["repoverlay", "cache", "clear", "--yes"]).unwrap();
 
             match cli.command {
                 Some(Commands::Cache { command }) => match command {
-                    CacheCommand::Clear { yes } => {
+                    CacheCommand::Clear { yes, .. } => {
                         assert!(yes, "yes flag should be true");
                     }
                     _ => panic!("Expected Cache Clear subcommand"),
@@ -4238,6 +9351,23 @@ directories =
             }
         }
 
+        #[test]
+        fn cache_clear_parses_older_than() {
+            let cli = Cli::try_parse_from(["repoverlay", "cache", "clear", "--older-than", "30d"])
+                .unwrap();
+            match cli.command {
+                Some(Commands::Cache {
+                    command: CacheCommand::Clear { older_than, .. },
+                }) => assert_eq!(older_than, Some(chrono::Duration::days(30))),
+                _ => panic!("Expected Cache Clear subcommand"),
+            }
+
+            assert!(
+                Cli::try_parse_from(["repoverlay", "cache", "clear", "--older-than", "soon"])
+                    .is_err()
+            );
+        }
+
         #[test]
         fn cache_remove_requires_repo() {
             let result = Cli::try_parse_from(["repoverlay", "cache", "remove"]);
@@ -4332,6 +9462,8 @@ directories =
                     files,
                     target,
                     dry_run,
+                    message,
+                    ..
                 }) => {
                     assert_eq!(name, "my-overlay");
                     assert_eq!(files.len(), 2);
@@ -4339,6 +9471,7 @@ directories =
                     assert_eq!(files[1], PathBuf::from("file2.txt"));
                     assert!(target.is_none());
                     assert!(!dry_run);
+                    assert!(message.is_none());
                 }
                 _ => panic!("Expected Add command"),
             }
@@ -4354,6 +9487,8 @@ directories =
                 "--target",
                 "/repo",
                 "--dry-run",
+                "--message",
+                "Add newfile",
             ])
             .unwrap();
 
@@ -4363,11 +9498,14 @@ directories =
                     files,
                     target,
                     dry_run,
+                    message,
+                    ..
                 }) => {
                     assert_eq!(name, "org/repo/my-overlay");
                     assert_eq!(files, vec![PathBuf::from("newfile.txt")]);
                     assert_eq!(target, Some(PathBuf::from("/repo")));
                     assert!(dry_run);
+                    assert_eq!(message.as_deref(), Some("Add newfile"));
                 }
                 _ => panic!("Expected Add command"),
             }
@@ -0,0 +1,274 @@
+//! Shell completion scripts.
+//!
+//! Scripts are generated by `clap_complete` from the clap command definition,
+//! so new subcommands and flags are picked up automatically. Each script gets
+//! a small hook on top that completes overlay names dynamically by calling
+//! back into `repoverlay __overlay-names`, which lists the overlays applied in
+//! the current directory.
+
+use clap::Command;
+use std::fmt::Write;
+
+/// Hidden subcommand the scripts call to list applied overlay names.
+pub const OVERLAY_NAMES_COMMAND: &str = "__overlay-names";
+
+/// Subcommands whose positional argument is an applied overlay name.
+const OVERLAY_NAME_COMMANDS: &[&str] = &[
+    "remove", "unlink", "edit", "diff", "repair", "update", "sync", "add",
+];
+
+/// Subcommands that take an overlay name through `--name`.
+const OVERLAY_NAME_FLAG_COMMANDS: &[&str] = &["status"];
+
+/// Shells that completion scripts can be generated for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    Powershell,
+}
+
+impl From<Shell> for clap_complete::Shell {
+    fn from(shell: Shell) -> Self {
+        match shell {
+            Shell::Bash => Self::Bash,
+            Shell::Zsh => Self::Zsh,
+            Shell::Fish => Self::Fish,
+            Shell::Powershell => Self::PowerShell,
+        }
+    }
+}
+
+/// Generate the completion script for `shell`.
+pub fn generate(shell: Shell, mut cmd: Command) -> String {
+    let bin = cmd.get_name().to_string();
+    let mut buf = Vec::new();
+    clap_complete::generate(clap_complete::Shell::from(shell), &mut cmd, &bin, &mut buf);
+    let script = String::from_utf8_lossy(&buf).into_owned();
+
+    match shell {
+        Shell::Bash => bash(&bin, script),
+        Shell::Zsh => zsh(&bin, script),
+        Shell::Fish => fish(&bin, script),
+        Shell::Powershell => powershell(&bin, script),
+    }
+}
+
+/// Wrap the generated `_<bin>` function so overlay-name positions complete
+/// applied overlays, and register the wrapper instead.
+fn bash(bin: &str, mut s: String) -> String {
+    let func = format!("_{}", bin.replace('-', "_"));
+    let _ = writeln!(s, "\n{func}_overlay_names() {{");
+    s.push_str(
+        "    local cur=\"${COMP_WORDS[COMP_CWORD]}\" prev=\"${COMP_WORDS[COMP_CWORD-1]}\"\n",
+    );
+    s.push_str("    if [[ ${COMP_CWORD} -ge 2 && \"$cur\" != -* ]]; then\n");
+    s.push_str("        case \"${COMP_WORDS[1]}\" in\n");
+    let names = format!(
+        "COMPREPLY=( $(compgen -W \"$({bin} {OVERLAY_NAMES_COMMAND} 2>/dev/null)\" -- \"$cur\") )"
+    );
+    let _ = writeln!(s, "            {})", OVERLAY_NAME_COMMANDS.join("|"));
+    s.push_str("                if [[ \"$prev\" != -* ]]; then\n");
+    let _ = writeln!(
+        s,
+        "                    {names}\n                    return 0"
+    );
+    s.push_str("                fi\n                ;;\n");
+    let _ = writeln!(s, "            {})", OVERLAY_NAME_FLAG_COMMANDS.join("|"));
+    s.push_str("                if [[ \"$prev\" == \"--name\" || \"$prev\" == \"-n\" ]]; then\n");
+    let _ = writeln!(
+        s,
+        "                    {names}\n                    return 0"
+    );
+    s.push_str("                fi\n                ;;\n        esac\n    fi\n");
+    let _ = writeln!(s, "    {func} \"$@\"\n}}\n");
+    let _ = writeln!(
+        s,
+        "complete -F {func}_overlay_names -o bashdefault -o default {bin}"
+    );
+    s
+}
+
+/// Replace the generated dispatch block with one that goes through a wrapper
+/// completing overlay names, whether the script is autoloaded or sourced.
+fn zsh(bin: &str, mut s: String) -> String {
+    let func = format!("_{}", bin.replace('-', "_"));
+    if let Some(dispatch) = s.rfind("if [ \"$funcstack[1]\"") {
+        s.truncate(dispatch);
+    }
+
+    let _ = writeln!(s, "{func}_overlay_names() {{");
+    s.push_str("    if (( CURRENT > 2 )) && [[ $PREFIX != -* ]]; then\n");
+    s.push_str("        case $words[2] in\n");
+    let names = format!(
+        "compadd -- ${{(f)\"$({bin} {OVERLAY_NAMES_COMMAND} 2>/dev/null)\"}}\n                    return"
+    );
+    let _ = writeln!(s, "            {})", OVERLAY_NAME_COMMANDS.join("|"));
+    s.push_str("                if [[ $words[CURRENT-1] != -* ]]; then\n");
+    let _ = writeln!(s, "                    {names}");
+    s.push_str("                fi\n                ;;\n");
+    let _ = writeln!(s, "            {})", OVERLAY_NAME_FLAG_COMMANDS.join("|"));
+    s.push_str("                if [[ $words[CURRENT-1] == (--name|-n) ]]; then\n");
+    let _ = writeln!(s, "                    {names}");
+    s.push_str("                fi\n                ;;\n        esac\n    fi\n");
+    let _ = writeln!(s, "    {func} \"$@\"\n}}\n");
+
+    let _ = writeln!(s, "compdef {func}_overlay_names {bin}");
+    let _ = writeln!(s, "if [ \"$funcstack[1]\" = \"{func}\" ]; then");
+    let _ = writeln!(s, "    {func}_overlay_names \"$@\"\nfi");
+    s
+}
+
+/// Add overlay-name candidates on top of the generated completions.
+fn fish(bin: &str, mut s: String) -> String {
+    let using = format!("__fish_{}_using_subcommand", bin.replace('-', "_"));
+    let names = format!("({bin} {OVERLAY_NAMES_COMMAND} 2>/dev/null)");
+    let _ = writeln!(
+        s,
+        "complete -c {bin} -n \"{using} {}\" -f -a \"{names}\"",
+        OVERLAY_NAME_COMMANDS.join(" ")
+    );
+    let _ = writeln!(
+        s,
+        "complete -c {bin} -n \"{using} {}\" -s n -l name -r -f -a \"{names}\"",
+        OVERLAY_NAME_FLAG_COMMANDS.join(" ")
+    );
+    s
+}
+
+/// Add overlay-name candidates to the generated completions before they are
+/// filtered against the word being completed.
+fn powershell(bin: &str, mut s: String) -> String {
+    let quote = |items: &[&str]| {
+        items
+            .iter()
+            .map(|i| format!("'{bin};{i}'"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let mut hook = String::new();
+    hook.push_str("    $previous = if ($wordToComplete) { $commandElements[-2] } else { $commandElements[-1] }\n");
+    let _ = writeln!(
+        hook,
+        "    if (($command -in @({}) -and $wordToComplete -notlike '-*') -or",
+        quote(OVERLAY_NAME_COMMANDS)
+    );
+    let _ = writeln!(
+        hook,
+        "        ($command -in @({}) -and \"$previous\" -in @('--name', '-n'))) {{",
+        quote(OVERLAY_NAME_FLAG_COMMANDS)
+    );
+    let _ = writeln!(
+        hook,
+        "        $completions += @(& '{bin}' {OVERLAY_NAMES_COMMAND} 2>$null | ForEach-Object {{"
+    );
+    hook.push_str(
+        "            [CompletionResult]::new($_, $_, [CompletionResultType]::ParameterValue, $_)\n",
+    );
+    hook.push_str("        })\n    }\n\n");
+
+    if let Some(filter) = s.rfind("    $completions.Where") {
+        s.insert_str(filter, &hook);
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{Arg, ArgAction};
+
+    fn test_command() -> Command {
+        Command::new("repoverlay")
+            .arg(Arg::new("color").long("color").global(true))
+            .subcommand(
+                Command::new("apply")
+                    .about("Apply an overlay")
+                    .arg(Arg::new("source"))
+                    .arg(Arg::new("target").long("target").short('t'))
+                    .arg(Arg::new("copy").long("copy").action(ArgAction::SetTrue)),
+            )
+            .subcommand(
+                Command::new("remove")
+                    .about("Remove an overlay's files")
+                    .arg(Arg::new("name")),
+            )
+            .subcommand(Command::new("status").arg(Arg::new("name").long("name").short('n')))
+            .subcommand(
+                Command::new("cache")
+                    .about("Manage the cache")
+                    .subcommand(Command::new("list"))
+                    .subcommand(Command::new("clear")),
+            )
+    }
+
+    #[test]
+    fn bash_wraps_generated_function_with_overlay_names() {
+        let script = generate(Shell::Bash, test_command());
+        assert!(script.contains("_repoverlay() {"));
+        assert!(script.contains("--target"));
+        assert!(script.contains("repoverlay __overlay-names"));
+        assert!(script.contains("    _repoverlay \"$@\"\n}"));
+        assert!(script.ends_with(
+            "complete -F _repoverlay_overlay_names -o bashdefault -o default repoverlay\n"
+        ));
+    }
+
+    #[test]
+    fn zsh_dispatches_through_overlay_name_wrapper() {
+        let script = generate(Shell::Zsh, test_command());
+        assert!(script.starts_with("#compdef repoverlay"));
+        assert!(script.contains("Apply an overlay"));
+        assert!(script.contains("compdef _repoverlay_overlay_names repoverlay"));
+        assert!(!script.contains("compdef _repoverlay repoverlay"));
+        assert_eq!(script.matches("if [ \"$funcstack[1]\"").count(), 1);
+    }
+
+    #[test]
+    fn fish_completes_overlay_names() {
+        let script = generate(Shell::Fish, test_command());
+        assert!(script.contains("-a \"apply\" -d 'Apply an overlay'"));
+        assert!(script.contains(
+            "-n \"__fish_repoverlay_using_subcommand remove unlink edit diff repair update sync add\" -f -a \"(repoverlay __overlay-names 2>/dev/null)\""
+        ));
+        assert!(
+            script
+                .contains("-n \"__fish_repoverlay_using_subcommand status\" -s n -l name -r -f -a")
+        );
+    }
+
+    #[test]
+    fn powershell_adds_overlay_names_before_filtering() {
+        let script = generate(Shell::Powershell, test_command());
+        assert!(script.contains("Register-ArgumentCompleter -Native -CommandName 'repoverlay'"));
+        let hook = script.find("__overlay-names").unwrap();
+        assert!(hook < script.rfind("$completions.Where").unwrap());
+        assert!(script.contains("'repoverlay;remove'"));
+    }
+
+    #[cfg(unix)]
+    fn check_syntax(shell: &str, script: &str) {
+        let Ok(mut child) = std::process::Command::new(shell)
+            .arg("-n")
+            .stdin(std::process::Stdio::piped())
+            .spawn()
+        else {
+            return; // shell not installed
+        };
+        std::io::Write::write_all(child.stdin.as_mut().unwrap(), script.as_bytes()).unwrap();
+        assert!(child.wait().unwrap().success());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn bash_script_is_valid_syntax() {
+        check_syntax("bash", &generate(Shell::Bash, test_command()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn zsh_script_is_valid_syntax() {
+        check_syntax("zsh", &generate(Shell::Zsh, test_command()));
+    }
+}
@@ -20,6 +20,157 @@ pub struct RepoverlayConfig {
     /// New configs should use `sources` instead.
     #[serde(default)]
     pub overlay_repo: Option<OverlayRepoConfig>,
+    /// How symlinks to local overlay sources are written.
+    #[serde(default)]
+    pub symlink_style: SymlinkStyle,
+    /// Whether overlay files are symlinked or copied when `--copy` isn't
+    /// passed.
+    #[serde(default)]
+    pub link_style: LinkStyle,
+    /// Which file applied overlay files are excluded from git in.
+    #[serde(default)]
+    pub exclude_target: ExcludeTarget,
+    /// Additional GitHub Enterprise hosts recognized alongside `github.com`.
+    #[serde(default)]
+    pub github_hosts: Vec<String>,
+    /// Hours before a cached GitHub clone is refreshed automatically on apply.
+    /// `0` (the default) disables auto-refresh.
+    #[serde(default)]
+    pub cache_ttl_hours: u64,
+    /// Times a rate-limited or failed clone, fetch, or download is retried.
+    /// Defaults to 3; `0` disables retries.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network_retries: Option<u32>,
+    /// How `create` discovers candidate files.
+    #[serde(default)]
+    pub detection: DetectionConfig,
+    /// Author and message of commits made to the overlay repository.
+    #[serde(default)]
+    pub commit: CommitConfig,
+}
+
+/// File discovery settings for `create`.
+///
+/// ```text
+/// detection =
+///   ai_config_patterns =
+///     = .windsurf/
+///     = AGENTS.md
+///     = .github/instructions/*.md
+/// ```
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq, Eq)]
+pub struct DetectionConfig {
+    /// Gitignore-style patterns for AI configuration files, which are
+    /// preselected. Added to the built-in patterns.
+    #[serde(default, deserialize_with = "crate::state::string_or_list")]
+    pub ai_config_patterns: Vec<String>,
+    /// Use only `ai_config_patterns`, dropping the built-in patterns.
+    #[serde(default)]
+    pub replace_default_patterns: bool,
+}
+
+/// Commit message used when `message_template` isn't set.
+pub const DEFAULT_COMMIT_MESSAGE: &str = "{action} overlay: {org}/{repo}/{name}";
+
+/// Settings for the commits `create`, `sync`, `add`, and `delete` make in the
+/// overlay repository.
+///
+/// ```text
+/// commit =
+///   author_name = Overlay Bot
+///   author_email = overlays@example.com
+///   message_template = chore(overlays): {action} {org}/{repo}/{name}
+/// ```
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq, Eq)]
+pub struct CommitConfig {
+    /// Commit author name. Defaults to git's `user.name`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author_name: Option<String>,
+    /// Commit author email. Defaults to git's `user.email`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author_email: Option<String>,
+    /// Commit message, with `{action}` (Add, Update, or Delete), `{org}`,
+    /// `{repo}`, and `{name}` filled in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message_template: Option<String>,
+}
+
+impl CommitConfig {
+    /// The commit message for `action` on `org/repo/name`.
+    pub fn message(&self, action: &str, org: &str, repo: &str, name: &str) -> String {
+        let template = self
+            .message_template
+            .as_deref()
+            .unwrap_or(DEFAULT_COMMIT_MESSAGE);
+        [
+            ("action", action),
+            ("org", org),
+            ("repo", repo),
+            ("name", name),
+        ]
+        .iter()
+        .fold(template.to_string(), |message, (key, value)| {
+            message.replace(&format!("{{{key}}}"), value)
+        })
+    }
+
+    fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+/// Style of symlinks created when applying overlays.
+///
+/// Relative symlinks are only used for local overlays that live under the
+/// same parent directory as the target. Cache-backed sources (GitHub,
+/// archives, overlay repos) always use absolute symlinks.
+#[derive(Debug, Deserialize, Serialize, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SymlinkStyle {
+    /// Symlinks store the absolute source path (default).
+    #[default]
+    Absolute,
+    /// Symlinks store a path relative to the link's directory.
+    Relative,
+}
+
+/// How overlay files are placed in the target repository.
+#[derive(Debug, Deserialize, Serialize, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkStyle {
+    /// Symlink when the platform allows it, otherwise copy (default).
+    /// Windows only allows symlinks with Developer Mode or admin rights.
+    #[default]
+    Auto,
+    /// Always symlink, failing where symlinks can't be created.
+    Symlink,
+    /// Always copy, as if `--copy` were passed.
+    Copy,
+}
+
+/// Where the git exclude sections for applied overlays are written.
+#[derive(Debug, Deserialize, Serialize, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ExcludeTarget {
+    /// `.git/info/exclude`, which is local to the checkout (default).
+    #[default]
+    InfoExclude,
+    /// The repository's `.gitignore`.
+    Gitignore,
+    /// Nothing is written; ignores are managed by hand.
+    None,
+}
+
+impl ExcludeTarget {
+    /// The exclude file relative to the repository root, `None` when
+    /// excludes aren't managed.
+    pub const fn path(self) -> Option<&'static str> {
+        match self {
+            Self::InfoExclude => Some(crate::state::GIT_EXCLUDE),
+            Self::Gitignore => Some(".gitignore"),
+            Self::None => None,
+        }
+    }
 }
 
 /// An overlay source repository.
@@ -184,6 +335,85 @@ pub fn generate_sources_config_ccl(config: &RepoverlayConfig) -> String {
         }
     }
 
+    if !config.github_hosts.is_empty() {
+        output.push_str("\n/= GitHub Enterprise hosts recognized in addition to github.com.\n");
+        output.push_str("github_hosts =\n");
+        for host in &config.github_hosts {
+            let _ = writeln!(output, "  = {host}");
+        }
+    }
+
+    if config.symlink_style == SymlinkStyle::Relative {
+        output.push_str("\n/= Use relative symlinks for local overlays next to the target.\n");
+        output.push_str("symlink_style = relative\n");
+    }
+
+    match config.link_style {
+        LinkStyle::Auto => {}
+        LinkStyle::Symlink => {
+            output.push_str("\n/= Always symlink overlay files.\n");
+            output.push_str("link_style = symlink\n");
+        }
+        LinkStyle::Copy => {
+            output.push_str("\n/= Always copy overlay files instead of symlinking them.\n");
+            output.push_str("link_style = copy\n");
+        }
+    }
+
+    match config.exclude_target {
+        ExcludeTarget::InfoExclude => {}
+        ExcludeTarget::Gitignore => {
+            output.push_str("\n/= Exclude overlay files in the repository's .gitignore.\n");
+            output.push_str("exclude_target = gitignore\n");
+        }
+        ExcludeTarget::None => {
+            output.push_str(
+                "\n/= Don't exclude overlay files from git; ignores are managed by hand.\n",
+            );
+            output.push_str("exclude_target = none\n");
+        }
+    }
+
+    if config.cache_ttl_hours > 0 {
+        output.push_str("\n/= Refresh cached GitHub clones older than this many hours.\n");
+        let _ = writeln!(output, "cache_ttl_hours = {}", config.cache_ttl_hours);
+    }
+
+    if let Some(retries) = config.network_retries {
+        output.push_str("\n/= Retries for rate-limited or failed clones and downloads.\n");
+        let _ = writeln!(output, "network_retries = {retries}");
+    }
+
+    let detection = &config.detection;
+    if !detection.ai_config_patterns.is_empty() || detection.replace_default_patterns {
+        output.push_str("\n/= Files preselected as AI configuration by create.\n");
+        output.push_str("detection =\n");
+        if !detection.ai_config_patterns.is_empty() {
+            output.push_str("  ai_config_patterns =\n");
+            for pattern in &detection.ai_config_patterns {
+                let _ = writeln!(output, "    = {pattern}");
+            }
+        }
+        if detection.replace_default_patterns {
+            output.push_str("  replace_default_patterns = true\n");
+        }
+    }
+
+    let commit = &config.commit;
+    if !commit.is_empty() {
+        output.push_str("\n/= Author and message of commits to the overlay repository.\n");
+        output.push_str("commit =\n");
+        if let Some(ref name) = commit.author_name {
+            let _ = writeln!(output, "  author_name = {name}");
+        }
+        if let Some(ref email) = commit.author_email {
+            let _ = writeln!(output, "  author_email = {email}");
+        }
+        if let Some(ref template) = commit.message_template {
+            let _ = writeln!(output, "  message_template = {template}");
+        }
+    }
+
     output
 }
 
@@ -254,6 +484,7 @@ mod tests {
                 url: "https://github.com/test/overlays".to_string(),
                 local_path: None,
             }),
+            ..Default::default()
         };
 
         // Serialize to CCL
@@ -336,6 +567,7 @@ overlay_repo =
                 url: "https://github.com/test/overlays".to_string(),
                 local_path: Some(PathBuf::from("/custom/path")),
             }),
+            ..Default::default()
         };
 
         let ccl = sickle::to_string(&config).unwrap();
@@ -518,6 +750,7 @@ sources =
                 url: "https://github.com/org/overlays".to_string(),
                 local_path: None,
             }),
+            ..Default::default()
         };
         assert!(needs_migration(&old_config));
 
@@ -528,6 +761,7 @@ sources =
                 url: "https://github.com/org/overlays".to_string(),
             }],
             overlay_repo: None,
+            ..Default::default()
         };
         assert!(!needs_migration(&new_config));
 
@@ -550,6 +784,7 @@ sources =
                 },
             ],
             overlay_repo: None,
+            ..Default::default()
         };
 
         let ccl = sickle::to_string(&config).unwrap();
@@ -562,6 +797,239 @@ sources =
         assert_eq!(parsed.sources[1].url, "https://github.com/org/overlays");
     }
 
+    #[test]
+    fn test_symlink_style_defaults_to_absolute() {
+        let config: RepoverlayConfig =
+            sickle::from_str("overlay_repo =\n  url = https://github.com/org/overlays\n").unwrap();
+        assert_eq!(config.symlink_style, SymlinkStyle::Absolute);
+    }
+
+    #[test]
+    fn test_parse_symlink_style_relative() {
+        let config: RepoverlayConfig = sickle::from_str("symlink_style = relative\n").unwrap();
+        assert_eq!(config.symlink_style, SymlinkStyle::Relative);
+    }
+
+    #[test]
+    fn test_generated_config_preserves_symlink_style() {
+        let config = RepoverlayConfig {
+            sources: vec![Source {
+                name: "personal".to_string(),
+                url: "https://github.com/me/my-overlays".to_string(),
+            }],
+            symlink_style: SymlinkStyle::Relative,
+            ..Default::default()
+        };
+
+        let ccl = generate_sources_config_ccl(&config);
+        let parsed: RepoverlayConfig = sickle::from_str(&ccl).unwrap();
+
+        assert_eq!(parsed.symlink_style, SymlinkStyle::Relative);
+        assert_eq!(parsed.sources.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_link_style() {
+        let config: RepoverlayConfig = sickle::from_str("symlink_style = relative\n").unwrap();
+        assert_eq!(config.link_style, LinkStyle::Auto);
+
+        let config: RepoverlayConfig = sickle::from_str("link_style = copy\n").unwrap();
+        assert_eq!(config.link_style, LinkStyle::Copy);
+
+        let config: RepoverlayConfig = sickle::from_str("link_style = symlink\n").unwrap();
+        assert_eq!(config.link_style, LinkStyle::Symlink);
+    }
+
+    #[test]
+    fn test_generated_config_preserves_link_style() {
+        let config = RepoverlayConfig {
+            link_style: LinkStyle::Copy,
+            ..Default::default()
+        };
+
+        let ccl = generate_sources_config_ccl(&config);
+        let parsed: RepoverlayConfig = sickle::from_str(&ccl).unwrap();
+
+        assert_eq!(parsed.link_style, LinkStyle::Copy);
+    }
+
+    #[test]
+    fn test_parse_exclude_target() {
+        let config: RepoverlayConfig = sickle::from_str("link_style = copy\n").unwrap();
+        assert_eq!(config.exclude_target, ExcludeTarget::InfoExclude);
+
+        for (value, expected) in [
+            ("info-exclude", ExcludeTarget::InfoExclude),
+            ("gitignore", ExcludeTarget::Gitignore),
+            ("none", ExcludeTarget::None),
+        ] {
+            let config: RepoverlayConfig =
+                sickle::from_str(&format!("exclude_target = {value}\n")).unwrap();
+            assert_eq!(config.exclude_target, expected, "{value}");
+        }
+    }
+
+    #[test]
+    fn test_generated_config_preserves_exclude_target() {
+        for exclude_target in [ExcludeTarget::Gitignore, ExcludeTarget::None] {
+            let config = RepoverlayConfig {
+                exclude_target,
+                ..Default::default()
+            };
+
+            let ccl = generate_sources_config_ccl(&config);
+            let parsed: RepoverlayConfig = sickle::from_str(&ccl).unwrap();
+
+            assert_eq!(parsed.exclude_target, exclude_target);
+        }
+    }
+
+    #[test]
+    fn test_parse_github_hosts() {
+        let config: RepoverlayConfig =
+            sickle::from_str("github_hosts =\n  = github.mycorp.com\n  = ghe.example.org\n")
+                .unwrap();
+        assert_eq!(
+            config.github_hosts,
+            vec![
+                "github.mycorp.com".to_string(),
+                "ghe.example.org".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_generated_config_preserves_github_hosts() {
+        let config = RepoverlayConfig {
+            github_hosts: vec!["github.mycorp.com".to_string()],
+            ..Default::default()
+        };
+
+        let ccl = generate_sources_config_ccl(&config);
+        let parsed: RepoverlayConfig = sickle::from_str(&ccl).unwrap();
+
+        assert_eq!(parsed.github_hosts, vec!["github.mycorp.com".to_string()]);
+    }
+
+    #[test]
+    fn test_parse_cache_ttl_hours() {
+        let config: RepoverlayConfig = sickle::from_str("cache_ttl_hours = 24\n").unwrap();
+        assert_eq!(config.cache_ttl_hours, 24);
+
+        let config: RepoverlayConfig = sickle::from_str("symlink_style = relative\n").unwrap();
+        assert_eq!(config.cache_ttl_hours, 0);
+    }
+
+    #[test]
+    fn test_parse_network_retries() {
+        let config: RepoverlayConfig = sickle::from_str("network_retries = 5\n").unwrap();
+        assert_eq!(config.network_retries, Some(5));
+
+        let config: RepoverlayConfig = sickle::from_str("cache_ttl_hours = 1\n").unwrap();
+        assert_eq!(config.network_retries, None);
+    }
+
+    #[test]
+    fn test_generated_config_preserves_network_retries() {
+        let config = RepoverlayConfig {
+            network_retries: Some(0),
+            ..Default::default()
+        };
+
+        let ccl = generate_sources_config_ccl(&config);
+        let parsed: RepoverlayConfig = sickle::from_str(&ccl).unwrap();
+
+        assert_eq!(parsed.network_retries, Some(0));
+    }
+
+    #[test]
+    fn test_generated_config_preserves_cache_ttl_hours() {
+        let config = RepoverlayConfig {
+            cache_ttl_hours: 12,
+            ..Default::default()
+        };
+
+        let ccl = generate_sources_config_ccl(&config);
+        let parsed: RepoverlayConfig = sickle::from_str(&ccl).unwrap();
+
+        assert_eq!(parsed.cache_ttl_hours, 12);
+    }
+
+    #[test]
+    fn test_parse_detection() {
+        let config: RepoverlayConfig = sickle::from_str(
+            "detection =\n  ai_config_patterns =\n    = .windsurf/\n    = *.mdc\n",
+        )
+        .unwrap();
+        assert_eq!(
+            config.detection.ai_config_patterns,
+            vec![".windsurf/".to_string(), "*.mdc".to_string()]
+        );
+        assert!(!config.detection.replace_default_patterns);
+
+        let config: RepoverlayConfig = sickle::from_str(
+            "detection =\n  ai_config_patterns = AGENTS.md\n  replace_default_patterns = true\n",
+        )
+        .unwrap();
+        assert_eq!(config.detection.ai_config_patterns, vec!["AGENTS.md"]);
+        assert!(config.detection.replace_default_patterns);
+    }
+
+    #[test]
+    fn test_generated_config_preserves_detection() {
+        let config = RepoverlayConfig {
+            detection: DetectionConfig {
+                ai_config_patterns: vec![".windsurf/".to_string(), "!CLAUDE.md".to_string()],
+                replace_default_patterns: true,
+            },
+            ..Default::default()
+        };
+
+        let ccl = generate_sources_config_ccl(&config);
+        let parsed: RepoverlayConfig = sickle::from_str(&ccl).unwrap();
+
+        assert_eq!(parsed.detection, config.detection);
+    }
+
+    #[test]
+    fn test_parse_commit() {
+        let config: RepoverlayConfig = sickle::from_str(
+            "commit =\n  author_name = Overlay Bot\n  message_template = chore: {action} {org}/{repo}/{name}\n",
+        )
+        .unwrap();
+        assert_eq!(config.commit.author_name.as_deref(), Some("Overlay Bot"));
+        assert_eq!(config.commit.author_email, None);
+        assert_eq!(
+            config.commit.message("Add", "acme", "web", "eslint"),
+            "chore: Add acme/web/eslint"
+        );
+    }
+
+    #[test]
+    fn test_default_commit_message() {
+        assert_eq!(
+            CommitConfig::default().message("Update", "acme", "web", "eslint"),
+            "Update overlay: acme/web/eslint"
+        );
+    }
+
+    #[test]
+    fn test_generated_config_preserves_commit() {
+        let config = RepoverlayConfig {
+            commit: CommitConfig {
+                author_name: Some("Overlay Bot".to_string()),
+                author_email: Some("bot@example.com".to_string()),
+                message_template: Some("overlays: {action} {name}".to_string()),
+            },
+            ..Default::default()
+        };
+
+        let ccl = generate_sources_config_ccl(&config);
+        let parsed: RepoverlayConfig = sickle::from_str(&ccl).unwrap();
+
+        assert_eq!(parsed.commit, config.commit);
+    }
+
     // ==================== Migration tests ====================
 
     #[test]
@@ -572,6 +1040,7 @@ sources =
                 url: "https://github.com/org/overlays".to_string(),
                 local_path: None,
             }),
+            ..Default::default()
         };
 
         let message = migrate_config(&mut config);
@@ -593,6 +1062,7 @@ sources =
                 url: original_url.clone(),
                 local_path: None,
             }),
+            ..Default::default()
         };
 
         let _ = migrate_config(&mut config);
@@ -608,6 +1078,7 @@ sources =
                 url: "https://github.com/org/overlays".to_string(),
                 local_path: None,
             }),
+            ..Default::default()
         };
 
         // First migration
@@ -628,6 +1099,7 @@ sources =
                 url: "https://github.com/existing/repo".to_string(),
             }],
             overlay_repo: None,
+            ..Default::default()
         };
 
         let message = migrate_config(&mut config);
@@ -3,9 +3,12 @@
 //! This module provides functionality to detect files that are good candidates
 //! for overlays, including AI configuration files and gitignored/untracked files.
 
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 use std::process::Command;
 
+use crate::config::{DetectionConfig, load_global_config};
+use crate::ignore::{IgnoreFile, is_glob};
+
 /// Categories of detected files for overlay creation.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum FileCategory {
@@ -20,7 +23,7 @@ pub enum FileCategory {
 }
 
 /// A detected file with its category and path.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct DetectedFile {
     /// Relative path from repository root
     pub path: PathBuf,
@@ -66,40 +69,115 @@ pub const AI_CONFIG_PATTERNS: &[&str] = &[
 pub const AI_CONFIG_DIRECTORIES: &[&str] =
     &[".claude", ".cursor", ".continue", ".cody", ".aider", ".ai"];
 
-/// Check if a path matches any AI config pattern.
-pub fn is_ai_config(path: &Path) -> bool {
-    let path_str = path.to_string_lossy();
+/// The patterns that decide which files count as AI configuration.
+///
+/// Built from [`AI_CONFIG_PATTERNS`], which only match at the repository
+/// root, plus the gitignore-style `detection.ai_config_patterns` from the
+/// global config. Later patterns win, so a configured `!CLAUDE.md` drops a
+/// built-in one.
+#[derive(Debug, Clone)]
+pub struct AiConfigPatterns {
+    /// Patterns without wildcards, checked for at the repository root
+    literal: Vec<String>,
+    matcher: IgnoreFile,
+    /// Whether configured patterns may match below the root, so untracked
+    /// files have to be searched
+    search_untracked: bool,
+}
 
-    for pattern in AI_CONFIG_PATTERNS {
-        // Check exact match (e.g., ".claude" == ".claude")
-        if path_str == *pattern {
-            return true;
-        }
-        // Check if path is inside a pattern directory (e.g., ".claude/file" matches ".claude")
-        // Must use separator to avoid false matches like ".claude-backup" matching ".claude"
-        let pattern_with_sep = format!("{pattern}/");
-        if path_str.starts_with(&pattern_with_sep) {
-            return true;
+impl Default for AiConfigPatterns {
+    fn default() -> Self {
+        Self::new(&DetectionConfig::default())
+    }
+}
+
+impl AiConfigPatterns {
+    /// Combine the built-in patterns with configured ones.
+    pub fn new(config: &DetectionConfig) -> Self {
+        let builtin: &[&str] = if config.replace_default_patterns {
+            &[]
+        } else {
+            AI_CONFIG_PATTERNS
+        };
+        let rules: Vec<String> = builtin
+            .iter()
+            .map(|pattern| format!("/{pattern}"))
+            .chain(config.ai_config_patterns.iter().cloned())
+            .collect();
+        let literal = builtin
+            .iter()
+            .map(ToString::to_string)
+            .chain(
+                config
+                    .ai_config_patterns
+                    .iter()
+                    .filter(|p| !p.starts_with('!') && !is_glob(p))
+                    .map(|p| p.trim_matches('/').to_string()),
+            )
+            .filter(|p| !p.is_empty())
+            .collect();
+
+        Self {
+            literal,
+            matcher: IgnoreFile::parse(&rules.join("\n")),
+            search_untracked: !config.ai_config_patterns.is_empty(),
         }
     }
 
-    false
+    /// Patterns from the global config, or the built-in ones if it can't be read.
+    pub fn load() -> Self {
+        load_global_config()
+            .map(|config| Self::new(&config.detection))
+            .unwrap_or_default()
+    }
+
+    /// Check if a path matches an AI config pattern, or is inside a matching directory.
+    pub fn is_ai_config(&self, path: &Path) -> bool {
+        self.matcher.is_ignored(path, false)
+    }
+
+    /// The shortest leading part of `path` that matches a pattern, so a file
+    /// inside a matched directory is reported as the directory.
+    fn matching_prefix(&self, path: &Path) -> Option<PathBuf> {
+        let components: Vec<Component> = path.components().collect();
+        (1..=components.len()).find_map(|end| {
+            let prefix: PathBuf = components[..end].iter().collect();
+            self.matcher
+                .is_ignored_entry(&prefix, end < components.len())
+                .then_some(prefix)
+        })
+    }
 }
 
 /// Detect AI configuration files in a repository.
 ///
 /// Returns paths relative to the repository root.
-pub fn detect_ai_configs(repo_path: &Path) -> Vec<DetectedFile> {
-    let mut results = Vec::new();
+pub fn detect_ai_configs(repo_path: &Path, patterns: &AiConfigPatterns) -> Vec<DetectedFile> {
+    let ai_config = |path: PathBuf| DetectedFile {
+        path,
+        category: FileCategory::AiConfig,
+        preselected: true, // AI configs are pre-selected by default
+    };
+    let mut results: Vec<DetectedFile> = Vec::new();
+
+    for pattern in &patterns.literal {
+        let path = PathBuf::from(pattern);
+        if repo_path.join(&path).exists()
+            && patterns.is_ai_config(&path)
+            && !results.iter().any(|f| f.path == path)
+        {
+            results.push(ai_config(path));
+        }
+    }
 
-    for pattern in AI_CONFIG_PATTERNS {
-        let full_path = repo_path.join(pattern);
-        if full_path.exists() {
-            results.push(DetectedFile {
-                path: PathBuf::from(pattern),
-                category: FileCategory::AiConfig,
-                preselected: true, // AI configs are pre-selected by default
-            });
+    if patterns.search_untracked {
+        for line in git_ls_files(repo_path, &["--others"]) {
+            let Some(path) = patterns.matching_prefix(Path::new(&line)) else {
+                continue;
+            };
+            if !results.iter().any(|f| path.starts_with(&f.path)) {
+                results.push(ai_config(path));
+            }
         }
     }
 
@@ -131,54 +209,48 @@ pub fn detect_ai_config_directories(repo_path: &Path) -> Vec<DetectedFile> {
 ///
 /// Uses `git ls-files --others --ignored --exclude-standard` to find files
 /// that are ignored by git but still exist in the repository.
-pub fn detect_gitignored_files(repo_path: &Path) -> Vec<DetectedFile> {
-    let output = Command::new("git")
-        .args(["ls-files", "--others", "--ignored", "--exclude-standard"])
-        .current_dir(repo_path)
-        .output();
-
-    match output {
-        Ok(output) if output.status.success() => {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            stdout
-                .lines()
-                .filter(|line| !line.is_empty())
-                .filter(|line| !is_ai_config(Path::new(line))) // Don't duplicate AI configs
-                .map(|line| DetectedFile {
-                    path: PathBuf::from(line),
-                    category: FileCategory::Gitignored,
-                    preselected: false,
-                })
-                .collect()
-        }
-        _ => Vec::new(),
-    }
+pub fn detect_gitignored_files(repo_path: &Path, patterns: &AiConfigPatterns) -> Vec<DetectedFile> {
+    git_ls_files(repo_path, &["--others", "--ignored", "--exclude-standard"])
+        .into_iter()
+        .filter(|line| !patterns.is_ai_config(Path::new(line))) // Don't duplicate AI configs
+        .map(|line| DetectedFile {
+            path: PathBuf::from(line),
+            category: FileCategory::Gitignored,
+            preselected: false,
+        })
+        .collect()
 }
 
 /// Detect untracked files (not in git, not ignored).
 ///
 /// Uses `git ls-files --others --exclude-standard` without --ignored
 /// to find files that are neither tracked nor ignored.
-pub fn detect_untracked_files(repo_path: &Path) -> Vec<DetectedFile> {
+pub fn detect_untracked_files(repo_path: &Path, patterns: &AiConfigPatterns) -> Vec<DetectedFile> {
+    git_ls_files(repo_path, &["--others", "--exclude-standard"])
+        .into_iter()
+        .filter(|line| !patterns.is_ai_config(Path::new(line))) // Don't duplicate AI configs
+        .map(|line| DetectedFile {
+            path: PathBuf::from(line),
+            category: FileCategory::Untracked,
+            preselected: false,
+        })
+        .collect()
+}
+
+/// Run `git ls-files` with `args`, returning no paths if git fails.
+fn git_ls_files(repo_path: &Path, args: &[&str]) -> Vec<String> {
     let output = Command::new("git")
-        .args(["ls-files", "--others", "--exclude-standard"])
+        .arg("ls-files")
+        .args(args)
         .current_dir(repo_path)
         .output();
 
     match output {
-        Ok(output) if output.status.success() => {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            stdout
-                .lines()
-                .filter(|line| !line.is_empty())
-                .filter(|line| !is_ai_config(Path::new(line))) // Don't duplicate AI configs
-                .map(|line| DetectedFile {
-                    path: PathBuf::from(line),
-                    category: FileCategory::Untracked,
-                    preselected: false,
-                })
-                .collect()
-        }
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(ToString::to_string)
+            .collect(),
         _ => Vec::new(),
     }
 }
@@ -189,17 +261,25 @@ pub fn detect_untracked_files(repo_path: &Path) -> Vec<DetectedFile> {
 /// 1. AI configuration files (pre-selected)
 /// 2. Gitignored files
 /// 3. Untracked files
+///
+/// AI configuration is matched with [`AiConfigPatterns::load`]. Candidates
+/// matched by a `.repoverlayignore` at the repository root are left out.
 pub fn discover_files(repo_path: &Path) -> Vec<DetectedFile> {
+    discover_files_with(repo_path, &AiConfigPatterns::load())
+}
+
+/// [`discover_files`] with explicit AI config patterns.
+fn discover_files_with(repo_path: &Path, patterns: &AiConfigPatterns) -> Vec<DetectedFile> {
     let mut all_files = Vec::new();
 
     // First, add AI configs (these are pre-selected)
-    all_files.extend(detect_ai_configs(repo_path));
+    all_files.extend(detect_ai_configs(repo_path, patterns));
 
     // Then add gitignored files
-    all_files.extend(detect_gitignored_files(repo_path));
+    all_files.extend(detect_gitignored_files(repo_path, patterns));
 
     // Finally add untracked files (excluding those already found as gitignored)
-    let untracked = detect_untracked_files(repo_path);
+    let untracked = detect_untracked_files(repo_path, patterns);
     for file in untracked {
         // Only add if not already in the list (gitignored files might overlap)
         if !all_files.iter().any(|f| f.path == file.path) {
@@ -207,6 +287,11 @@ pub fn discover_files(repo_path: &Path) -> Vec<DetectedFile> {
         }
     }
 
+    let ignore = IgnoreFile::load(repo_path).unwrap_or_default();
+    if !ignore.is_empty() {
+        all_files.retain(|f| !ignore.is_ignored(&f.path, repo_path.join(&f.path).is_dir()));
+    }
+
     all_files
 }
 
@@ -243,6 +328,61 @@ pub fn group_by_category(files: &[DetectedFile]) -> Vec<(FileCategory, Vec<&Dete
     groups
 }
 
+/// A file, or untracked files collapsed under their top-level directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiscoveryEntry<'a> {
+    File(&'a DetectedFile),
+    Directory {
+        /// Top-level directory, relative to the repository root
+        path: PathBuf,
+        files: Vec<&'a DetectedFile>,
+    },
+}
+
+/// Collapse untracked files that share a top-level directory into one entry.
+///
+/// A directory takes the place of its first file. Other categories, files at
+/// the root, and directories with a single untracked file are left as files.
+pub fn group_untracked_by_dir<'a>(files: &[&'a DetectedFile]) -> Vec<DiscoveryEntry<'a>> {
+    let top_dir = |file: &DetectedFile| -> Option<PathBuf> {
+        if file.category != FileCategory::Untracked {
+            return None;
+        }
+        let mut components = file.path.components();
+        let first = components.next()?;
+        components.next().map(|_| PathBuf::from(first.as_os_str()))
+    };
+
+    let mut entries: Vec<DiscoveryEntry<'a>> = Vec::new();
+    for &file in files {
+        let Some(dir) = top_dir(file) else {
+            entries.push(DiscoveryEntry::File(file));
+            continue;
+        };
+        let existing = entries.iter_mut().find_map(|entry| match entry {
+            DiscoveryEntry::Directory { path, files } if *path == dir => Some(files),
+            _ => None,
+        });
+        match existing {
+            Some(group) => group.push(file),
+            None => entries.push(DiscoveryEntry::Directory {
+                path: dir,
+                files: vec![file],
+            }),
+        }
+    }
+
+    entries
+        .into_iter()
+        .map(|entry| match entry {
+            DiscoveryEntry::Directory { files, .. } if files.len() == 1 => {
+                DiscoveryEntry::File(files[0])
+            }
+            entry => entry,
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -260,26 +400,30 @@ mod tests {
         dir
     }
 
+    fn defaults() -> AiConfigPatterns {
+        AiConfigPatterns::default()
+    }
+
     #[test]
     fn test_is_ai_config_exact_match() {
-        assert!(is_ai_config(Path::new(".claude")));
-        assert!(is_ai_config(Path::new("CLAUDE.md")));
-        assert!(is_ai_config(Path::new(".cursorrules")));
-        assert!(is_ai_config(Path::new(".cursor")));
+        assert!(defaults().is_ai_config(Path::new(".claude")));
+        assert!(defaults().is_ai_config(Path::new("CLAUDE.md")));
+        assert!(defaults().is_ai_config(Path::new(".cursorrules")));
+        assert!(defaults().is_ai_config(Path::new(".cursor")));
     }
 
     #[test]
     fn test_is_ai_config_subdirectory() {
-        assert!(is_ai_config(Path::new(".claude/settings.json")));
-        assert!(is_ai_config(Path::new(".cursor/rules.md")));
-        assert!(is_ai_config(Path::new(".continue/config.json")));
+        assert!(defaults().is_ai_config(Path::new(".claude/settings.json")));
+        assert!(defaults().is_ai_config(Path::new(".cursor/rules.md")));
+        assert!(defaults().is_ai_config(Path::new(".continue/config.json")));
     }
 
     #[test]
     fn test_is_ai_config_non_match() {
-        assert!(!is_ai_config(Path::new(".envrc")));
-        assert!(!is_ai_config(Path::new("package.json")));
-        assert!(!is_ai_config(Path::new(".gitignore")));
+        assert!(!defaults().is_ai_config(Path::new(".envrc")));
+        assert!(!defaults().is_ai_config(Path::new("package.json")));
+        assert!(!defaults().is_ai_config(Path::new(".gitignore")));
     }
 
     #[test]
@@ -292,7 +436,7 @@ mod tests {
         fs::write(repo.path().join("CLAUDE.md"), "# Claude").unwrap();
         fs::write(repo.path().join(".cursorrules"), "rules").unwrap();
 
-        let configs = detect_ai_configs(repo.path());
+        let configs = detect_ai_configs(repo.path(), &defaults());
 
         assert!(configs.iter().any(|f| f.path == Path::new(".claude")));
         assert!(configs.iter().any(|f| f.path == Path::new("CLAUDE.md")));
@@ -321,7 +465,7 @@ mod tests {
             .output()
             .unwrap();
 
-        let ignored = detect_gitignored_files(repo.path());
+        let ignored = detect_gitignored_files(repo.path(), &defaults());
 
         assert!(ignored.iter().any(|f| f.path == Path::new(".envrc")));
         assert!(ignored.iter().any(|f| f.path == Path::new("debug.log")));
@@ -341,7 +485,7 @@ mod tests {
         fs::write(repo.path().join("scratch.txt"), "notes").unwrap();
         fs::write(repo.path().join("todo.md"), "# TODO").unwrap();
 
-        let untracked = detect_untracked_files(repo.path());
+        let untracked = detect_untracked_files(repo.path(), &defaults());
 
         assert!(untracked.iter().any(|f| f.path == Path::new("scratch.txt")));
         assert!(untracked.iter().any(|f| f.path == Path::new("todo.md")));
@@ -419,6 +563,36 @@ mod tests {
         assert_eq!(groups[2].0, FileCategory::Untracked);
     }
 
+    #[test]
+    fn test_group_untracked_by_dir() {
+        let file = |path: &str, category| DetectedFile {
+            path: PathBuf::from(path),
+            category,
+            preselected: false,
+        };
+        let files = [
+            file(".envrc", FileCategory::Gitignored),
+            file("scratch/a.txt", FileCategory::Untracked),
+            file("notes.txt", FileCategory::Untracked),
+            file("scratch/nested/b.txt", FileCategory::Untracked),
+            file("tools/run.sh", FileCategory::Untracked),
+        ];
+        let refs: Vec<&DetectedFile> = files.iter().collect();
+
+        assert_eq!(
+            group_untracked_by_dir(&refs),
+            vec![
+                DiscoveryEntry::File(&files[0]),
+                DiscoveryEntry::Directory {
+                    path: PathBuf::from("scratch"),
+                    files: vec![&files[1], &files[3]],
+                },
+                DiscoveryEntry::File(&files[2]),
+                DiscoveryEntry::File(&files[4]),
+            ]
+        );
+    }
+
     #[test]
     fn test_group_by_category_empty() {
         let files: Vec<DetectedFile> = vec![];
@@ -450,42 +624,108 @@ mod tests {
 
     #[test]
     fn test_is_ai_config_github_copilot() {
-        assert!(is_ai_config(Path::new(".github/copilot-instructions.md")));
+        assert!(defaults().is_ai_config(Path::new(".github/copilot-instructions.md")));
     }
 
     #[test]
     fn test_is_ai_config_windsurf() {
-        assert!(is_ai_config(Path::new(".windsurfrules")));
+        assert!(defaults().is_ai_config(Path::new(".windsurfrules")));
     }
 
     #[test]
     fn test_is_ai_config_aider() {
-        assert!(is_ai_config(Path::new(".aider")));
-        assert!(is_ai_config(Path::new(".aiderignore")));
+        assert!(defaults().is_ai_config(Path::new(".aider")));
+        assert!(defaults().is_ai_config(Path::new(".aiderignore")));
     }
 
     #[test]
     fn test_is_ai_config_continue() {
-        assert!(is_ai_config(Path::new(".continue")));
-        assert!(is_ai_config(Path::new(".continue/config.json")));
+        assert!(defaults().is_ai_config(Path::new(".continue")));
+        assert!(defaults().is_ai_config(Path::new(".continue/config.json")));
     }
 
     #[test]
     fn test_is_ai_config_cody() {
-        assert!(is_ai_config(Path::new(".cody")));
-        assert!(is_ai_config(Path::new("cody.json")));
+        assert!(defaults().is_ai_config(Path::new(".cody")));
+        assert!(defaults().is_ai_config(Path::new("cody.json")));
     }
 
     #[test]
     fn test_is_ai_config_generic() {
-        assert!(is_ai_config(Path::new(".ai")));
-        assert!(is_ai_config(Path::new("ai-instructions.md")));
+        assert!(defaults().is_ai_config(Path::new(".ai")));
+        assert!(defaults().is_ai_config(Path::new("ai-instructions.md")));
+    }
+
+    fn configured(patterns: &[&str], replace_default_patterns: bool) -> AiConfigPatterns {
+        AiConfigPatterns::new(&DetectionConfig {
+            ai_config_patterns: patterns.iter().map(ToString::to_string).collect(),
+            replace_default_patterns,
+        })
+    }
+
+    #[test]
+    fn test_configured_patterns_merge_with_defaults() {
+        let patterns = configured(&["AGENTS.md", ".windsurf/", "!cody.json"], false);
+        assert!(patterns.is_ai_config(Path::new("CLAUDE.md")));
+        assert!(patterns.is_ai_config(Path::new("AGENTS.md")));
+        assert!(patterns.is_ai_config(Path::new("docs/AGENTS.md")));
+        assert!(patterns.is_ai_config(Path::new(".windsurf/rules.md")));
+        assert!(!patterns.is_ai_config(Path::new("cody.json")));
+
+        // Built-in patterns only match at the root
+        assert!(!defaults().is_ai_config(Path::new("docs/CLAUDE.md")));
+    }
+
+    #[test]
+    fn test_configured_patterns_replace_defaults() {
+        let patterns = configured(&["*.mdc"], true);
+        assert!(!patterns.is_ai_config(Path::new("CLAUDE.md")));
+        assert!(patterns.is_ai_config(Path::new("rules/style.mdc")));
+    }
+
+    #[test]
+    fn test_detect_ai_configs_with_configured_globs() {
+        let repo = create_test_repo();
+        fs::write(
+            repo.path().join(".gitignore"),
+            ".windsurf/
+",
+        )
+        .unwrap();
+        fs::create_dir_all(repo.path().join(".windsurf/rules")).unwrap();
+        fs::write(repo.path().join(".windsurf/rules/style.md"), "rules").unwrap();
+        fs::create_dir_all(repo.path().join(".github/instructions")).unwrap();
+        fs::write(repo.path().join(".github/instructions/go.md"), "go").unwrap();
+        fs::write(repo.path().join("CLAUDE.md"), "# Claude").unwrap();
+        fs::write(repo.path().join("notes.txt"), "notes").unwrap();
+
+        let patterns = configured(&[".windsurf/", ".github/instructions/*.md"], false);
+        let files = discover_files_with(repo.path(), &patterns);
+        let ai_configs: Vec<&Path> = files
+            .iter()
+            .filter(|f| f.category == FileCategory::AiConfig)
+            .map(|f| f.path.as_path())
+            .collect();
+
+        assert_eq!(
+            ai_configs,
+            vec![
+                Path::new("CLAUDE.md"),
+                Path::new(".github/instructions/go.md"),
+                Path::new(".windsurf"),
+            ]
+        );
+        assert!(
+            files
+                .iter()
+                .any(|f| f.path == Path::new("notes.txt") && !f.preselected)
+        );
     }
 
     #[test]
     fn test_detect_ai_configs_empty_repo() {
         let repo = create_test_repo();
-        let configs = detect_ai_configs(repo.path());
+        let configs = detect_ai_configs(repo.path(), &defaults());
         assert!(configs.is_empty());
     }
 
@@ -507,7 +747,7 @@ mod tests {
             .output()
             .unwrap();
 
-        let ignored = detect_gitignored_files(repo.path());
+        let ignored = detect_gitignored_files(repo.path(), &defaults());
 
         // Should NOT include .claude/settings.json (it's an AI config)
         assert!(!ignored.iter().any(|f| f.path.starts_with(".claude")));
@@ -523,7 +763,7 @@ mod tests {
         fs::write(repo.path().join("CLAUDE.md"), "# Claude").unwrap();
         fs::write(repo.path().join("notes.txt"), "notes").unwrap();
 
-        let untracked = detect_untracked_files(repo.path());
+        let untracked = detect_untracked_files(repo.path(), &defaults());
 
         // Should NOT include CLAUDE.md
         assert!(!untracked.iter().any(|f| f.path == Path::new("CLAUDE.md")));
@@ -531,6 +771,23 @@ mod tests {
         assert!(untracked.iter().any(|f| f.path == Path::new("notes.txt")));
     }
 
+    #[test]
+    fn test_discover_files_respects_repoverlayignore() {
+        let repo = create_test_repo();
+
+        fs::write(repo.path().join(".repoverlayignore"), "*.log\nbuild/\n").unwrap();
+        fs::create_dir_all(repo.path().join("tools/build")).unwrap();
+        fs::write(repo.path().join("notes.txt"), "notes").unwrap();
+        fs::write(repo.path().join("tools/trace.log"), "log").unwrap();
+        fs::write(repo.path().join("tools/build/out.bin"), "bin").unwrap();
+
+        let all_files = discover_files(repo.path());
+
+        assert!(all_files.iter().any(|f| f.path == Path::new("notes.txt")));
+        assert!(!all_files.iter().any(|f| f.path.ends_with("trace.log")));
+        assert!(!all_files.iter().any(|f| f.path.starts_with("tools/build")));
+    }
+
     #[test]
     fn test_discover_files_deduplicates() {
         let repo = create_test_repo();
@@ -659,7 +916,7 @@ mod tests {
         // Don't initialize git - this should trigger the fallback
         fs::write(temp.path().join(".envrc"), "export FOO=bar").unwrap();
 
-        let ignored = detect_gitignored_files(temp.path());
+        let ignored = detect_gitignored_files(temp.path(), &defaults());
         // Should return empty vec when git fails
         assert!(ignored.is_empty());
     }
@@ -671,7 +928,7 @@ mod tests {
         // Don't initialize git - this should trigger the fallback
         fs::write(temp.path().join("notes.txt"), "notes").unwrap();
 
-        let untracked = detect_untracked_files(temp.path());
+        let untracked = detect_untracked_files(temp.path(), &defaults());
         // Should return empty vec when git fails
         assert!(untracked.is_empty());
     }
@@ -0,0 +1,339 @@
+//! Environment checks for `repoverlay doctor`.
+//!
+//! Each check reports what it found and, when something is wrong, how to fix
+//! it, so setup problems (no git, symlinks disabled, an unreachable overlay
+//! repo) show up in one place instead of as errors from whichever command
+//! hits them first. Missing optional pieces, like a GitHub token, are
+//! warnings; the command fails only when a required check fails.
+
+use crate::auth;
+use crate::cache;
+use crate::config::{self, RepoverlayConfig};
+use crate::github::DEFAULT_GITHUB_HOST;
+use crate::offline;
+use crate::overlay_repo::OverlayRepoManager;
+use anyhow::{Result, bail};
+use colored::Colorize;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// Outcome of a single check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    /// Something optional is missing; some features won't work
+    Warn,
+    /// Something required is missing or broken
+    Fail,
+}
+
+/// Result of a single check, with a fix for anything that didn't pass.
+#[derive(Debug)]
+pub struct Check {
+    pub name: String,
+    pub status: Status,
+    pub detail: String,
+    pub hint: Option<String>,
+}
+
+impl Check {
+    fn pass(name: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            status: Status::Pass,
+            detail: detail.into(),
+            hint: None,
+        }
+    }
+
+    fn warn(name: impl Into<String>, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            status: Status::Warn,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+        }
+    }
+
+    fn fail(name: impl Into<String>, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            status: Status::Fail,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+        }
+    }
+
+    fn print(&self) {
+        let mark = match self.status {
+            Status::Pass => "✓".green(),
+            Status::Warn => "!".yellow(),
+            Status::Fail => "✗".red(),
+        };
+        println!("  {} {}: {}", mark, self.name.bold(), self.detail);
+        if let Some(hint) = &self.hint {
+            for line in hint.lines() {
+                println!("      {}", line.dimmed());
+            }
+        }
+    }
+}
+
+/// Run every check, print a checklist, and fail if a required check failed.
+pub fn run() -> Result<()> {
+    println!("{} environment...\n", "Checking".blue().bold());
+
+    let mut checks = vec![check_git(), check_symlinks(&std::env::temp_dir())];
+    match config::load_global_config() {
+        Ok(config) => checks.extend(check_overlay_repos(&config)),
+        Err(e) => checks.push(Check::fail(
+            "config",
+            format!("{e:#}"),
+            "Fix or remove the global config file",
+        )),
+    }
+    checks.push(match cache::cache_dir() {
+        Ok(dir) => check_writable("cache directory", &dir),
+        Err(e) => Check::fail("cache directory", format!("{e:#}"), "Set HOME"),
+    });
+    checks.push(check_github_token());
+
+    for check in &checks {
+        check.print();
+    }
+    println!();
+
+    let failed = checks.iter().filter(|c| c.status == Status::Fail).count();
+    let warnings = checks.iter().filter(|c| c.status == Status::Warn).count();
+    if failed > 0 {
+        bail!("{failed} check(s) failed");
+    }
+    if warnings > 0 {
+        println!(
+            "{} No problems found ({} warning(s))",
+            "✓".green().bold(),
+            warnings
+        );
+    } else {
+        println!("{} No problems found", "✓".green().bold());
+    }
+    Ok(())
+}
+
+/// Check that git is installed and report its version.
+pub fn check_git() -> Check {
+    match Command::new("git").arg("--version").output() {
+        Ok(output) if output.status.success() => Check::pass(
+            "git",
+            String::from_utf8_lossy(&output.stdout).trim().to_string(),
+        ),
+        Ok(output) => Check::fail(
+            "git",
+            format!(
+                "git --version failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+            "Reinstall git",
+        ),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Check::fail(
+            "git",
+            "not installed or not on PATH",
+            "Install git and make sure it is on PATH",
+        ),
+        Err(e) => Check::fail("git", format!("could not run git: {e}"), "Reinstall git"),
+    }
+}
+
+/// Check that symlinks can be created in `dir`.
+pub fn check_symlinks(dir: &Path) -> Check {
+    match crate::probe_symlinks(dir) {
+        Ok(()) => Check::pass("symlinks", "can be created"),
+        Err(e) => Check::fail(
+            "symlinks",
+            format!("could not create a symlink: {e}"),
+            if cfg!(windows) {
+                "Enable Developer Mode in Windows settings, or apply overlays with --copy"
+            } else {
+                "Apply overlays with --copy"
+            },
+        ),
+    }
+}
+
+/// Check each configured overlay repository is cloned and its remote is
+/// reachable.
+pub fn check_overlay_repos(config: &RepoverlayConfig) -> Vec<Check> {
+    let managers = match OverlayRepoManager::all_configured(config) {
+        Ok(managers) => managers,
+        Err(e) => {
+            return vec![Check::fail(
+                "overlay repo",
+                format!("{e:#}"),
+                "Check the config file",
+            )];
+        }
+    };
+    if managers.is_empty() {
+        return vec![Check::warn(
+            "overlay repo",
+            "not configured",
+            "Needed for org/repo/name overlay references.\n\
+             Run 'repoverlay source add <url>' to set one up.",
+        )];
+    }
+
+    managers
+        .into_iter()
+        .map(|(source, manager)| {
+            let name = if config.sources.is_empty() {
+                "overlay repo".to_string()
+            } else {
+                format!("overlay source '{source}'")
+            };
+            let url = manager.url();
+            let cloned = !manager.needs_clone();
+
+            if offline::is_offline() {
+                return if cloned {
+                    Check::pass(name, format!("{url} (not contacted offline)"))
+                } else {
+                    Check::fail(
+                        name,
+                        format!("{url} is not cloned"),
+                        "Run without --offline to clone it",
+                    )
+                };
+            }
+
+            match ls_remote(url) {
+                Ok(()) => {
+                    let state = if cloned { "cloned" } else { "not cloned yet" };
+                    Check::pass(name, format!("{url} ({state})"))
+                }
+                Err(e) if cloned => Check::warn(
+                    name,
+                    format!("{url} is unreachable: {e}"),
+                    "The local clone still works, but pulls and pushes will fail.\n\
+                     Check the URL, your network, and your credentials.",
+                ),
+                Err(e) => Check::fail(
+                    name,
+                    format!("{url} is unreachable: {e}"),
+                    "Check the URL, your network, and your credentials",
+                ),
+            }
+        })
+        .collect()
+}
+
+/// Contact a git remote without cloning it.
+fn ls_remote(url: &str) -> Result<()> {
+    let output = Command::new("git")
+        .args(["ls-remote", "--heads", url])
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .output()
+        .map_err(|e| cache::git_spawn_error(e, "ls-remote"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("{}", stderr.lines().last().unwrap_or("").trim());
+    }
+    Ok(())
+}
+
+/// Check that files can be written in `dir`, creating it if needed.
+pub fn check_writable(name: &str, dir: &Path) -> Check {
+    let probe = dir.join(format!(".repoverlay-doctor-{}", std::process::id()));
+    let result = fs::create_dir_all(dir).and_then(|()| fs::write(&probe, ""));
+    let _ = fs::remove_file(&probe);
+
+    match result {
+        Ok(()) => Check::pass(name, format!("{} is writable", dir.display())),
+        Err(e) => Check::fail(
+            name,
+            format!("{} is not writable: {e}", dir.display()),
+            format!("Fix the permissions of {}", dir.display()),
+        ),
+    }
+}
+
+/// Check whether a token is available for private GitHub repositories.
+pub fn check_github_token() -> Check {
+    if let Some(var) = auth::TOKEN_ENV_VARS
+        .iter()
+        .find(|var| std::env::var(var).is_ok_and(|v| !v.trim().is_empty()))
+    {
+        return Check::pass("GitHub token", format!("found in ${var}"));
+    }
+    if auth::resolve_token(DEFAULT_GITHUB_HOST).is_some() {
+        return Check::pass("GitHub token", "found with the gh CLI");
+    }
+    Check::warn(
+        "GitHub token",
+        "not found (only public repositories can be used)",
+        auth::auth_help(DEFAULT_GITHUB_HOST),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OverlayRepoConfig;
+    use tempfile::TempDir;
+
+    #[test]
+    fn git_is_found() {
+        let check = check_git();
+        assert_eq!(check.status, Status::Pass);
+        assert!(check.detail.starts_with("git version"));
+    }
+
+    #[test]
+    fn symlinks_can_be_created_in_temp_dir() {
+        let dir = TempDir::new().unwrap();
+        assert_eq!(check_symlinks(dir.path()).status, Status::Pass);
+        // The probe is cleaned up
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn writable_dir_is_created() {
+        let dir = TempDir::new().unwrap();
+        let cache = dir.path().join("cache");
+        assert_eq!(check_writable("cache", &cache).status, Status::Pass);
+        assert!(cache.is_dir());
+        assert_eq!(fs::read_dir(&cache).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn file_in_place_of_dir_is_not_writable() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("cache");
+        fs::write(&file, "").unwrap();
+        let check = check_writable("cache", &file);
+        assert_eq!(check.status, Status::Fail);
+        assert!(check.hint.is_some());
+    }
+
+    #[test]
+    fn missing_overlay_repo_is_a_warning() {
+        let checks = check_overlay_repos(&RepoverlayConfig::default());
+        assert_eq!(checks.len(), 1);
+        assert_eq!(checks[0].status, Status::Warn);
+    }
+
+    #[test]
+    fn unreachable_overlay_repo_fails() {
+        let dir = TempDir::new().unwrap();
+        let config = RepoverlayConfig {
+            overlay_repo: Some(OverlayRepoConfig {
+                url: dir.path().join("missing").display().to_string(),
+                local_path: Some(dir.path().join("clone")),
+            }),
+            ..Default::default()
+        };
+        let checks = check_overlay_repos(&config);
+        assert_eq!(checks.len(), 1);
+        assert_eq!(checks[0].status, Status::Fail);
+    }
+}
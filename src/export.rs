@@ -0,0 +1,506 @@
+//! Portable bundles of a repository's applied overlays.
+//!
+//! `export` writes a `.tar.gz` holding each applied overlay's state and its
+//! files as they appear in the repository, with symlinks dereferenced. The
+//! bundle does not refer back to the original sources, so it can be shared
+//! with someone who has no access to them. `import` unpacks the files into the
+//! data directory and applies them as local overlays.
+//!
+//! Bundle layout:
+//!
+//! ```text
+//! manifest.ccl                  bundle format version and overlay names
+//! state/meta.ccl                the target's .repoverlay metadata
+//! state/overlays/<name>.ccl     state of each applied overlay
+//! files/<name>/<target path>    overlay files as applied
+//! ```
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use directories::ProjectDirs;
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::hash::sha256_file;
+use crate::output::status;
+use crate::state::{
+    EntryType, META_FILE, OVERLAYS_DIR, OverlayState, STATE_DIR, list_applied_overlays,
+    load_overlay_state, migrate_state, normalize_overlay_name, save_external_state,
+    save_overlay_state, state_dir_schema_version, state_file_name, string_or_list,
+};
+use crate::{ApplyOptions, apply_overlay, canonicalize_path};
+
+/// Name of the manifest at the root of a bundle.
+pub const MANIFEST_FILE: &str = "manifest.ccl";
+
+/// Directory in a bundle holding the target's `.repoverlay` state.
+pub const BUNDLE_STATE_DIR: &str = "state";
+
+/// Directory in a bundle holding each overlay's files.
+pub const BUNDLE_FILES_DIR: &str = "files";
+
+/// Bundle format written by this version of repoverlay.
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+/// Describes the contents of a bundle.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct BundleManifest {
+    /// Layout version of the bundle
+    pub format_version: u32,
+    /// Version of repoverlay that wrote the bundle
+    pub created_with_version: String,
+    /// When the bundle was written
+    pub exported_at: DateTime<Utc>,
+    /// Names of the exported overlays, in apply order
+    #[serde(default, deserialize_with = "string_or_list")]
+    pub overlays: Vec<String>,
+}
+
+/// Export every overlay applied to `target` into a `.tar.gz` bundle.
+pub fn export_overlays(target: &Path, output: &Path) -> Result<()> {
+    debug!(
+        "export_overlays: target={}, output={}",
+        target.display(),
+        output.display()
+    );
+    let target = canonicalize_path(target, "Target directory")?;
+
+    let names = list_applied_overlays(&target)?;
+    if names.is_empty() {
+        bail!("No overlays are applied to {}", target.display());
+    }
+
+    let staging = staging_dir("export")?;
+    let result = write_bundle(&target, &names, &staging)
+        .and_then(|file_count| create_tar_gz(&staging, output).map(|()| file_count));
+    if let Err(e) = fs::remove_dir_all(&staging) {
+        debug!("failed to remove {}: {e}", staging.display());
+    }
+    let file_count = result?;
+
+    status!(
+        "{} Exported {} overlay(s) with {} file(s) to {}",
+        "✓".green().bold(),
+        names.len(),
+        file_count,
+        output.display()
+    );
+    Ok(())
+}
+
+/// Apply every overlay in a bundle written by [`export_overlays`] to `target`.
+///
+/// Files are unpacked into the data directory, which becomes each overlay's
+/// local source, so symlinked overlays keep working after the bundle is gone.
+pub fn import_overlays(archive: &Path, target: &Path, force_copy: bool) -> Result<()> {
+    debug!(
+        "import_overlays: archive={}, target={}",
+        archive.display(),
+        target.display()
+    );
+    let target = canonicalize_path(target, "Target directory")?;
+    let archive = canonicalize_path(archive, "Bundle")?;
+
+    let staging = staging_dir("import")?;
+    let result = extract_tar_gz(&archive, &staging).and_then(|()| read_bundle(&staging));
+    let bundle = match result {
+        Ok(bundle) => bundle,
+        Err(e) => {
+            let _ = fs::remove_dir_all(&staging);
+            return Err(e);
+        }
+    };
+
+    // Keyed by the bundle's content so importing it again reuses the sources
+    let sources_dir = imports_dir()?.join(&sha256_file(&archive)?[..16]);
+    let result = install_sources(&staging, &bundle, &sources_dir);
+    if let Err(e) = fs::remove_dir_all(&staging) {
+        debug!("failed to remove {}: {e}", staging.display());
+    }
+    result?;
+
+    for state in &bundle {
+        let source = sources_dir.join(&state.name);
+        apply_overlay(
+            &source.to_string_lossy(),
+            &target,
+            &ApplyOptions {
+                force_copy,
+                name: Some(state.name.clone()),
+                no_deps: true,
+                ..Default::default()
+            },
+        )
+        .with_context(|| format!("Failed to import overlay '{}'", state.name))?;
+        keep_bundled_metadata(&target, state)?;
+    }
+
+    status!(
+        "\n{} Imported {} overlay(s) from {}",
+        "✓".green().bold(),
+        bundle.len(),
+        archive.display()
+    );
+    Ok(())
+}
+
+/// Read and validate an unpacked bundle, returning its overlay states in
+/// apply order.
+fn read_bundle(dir: &Path) -> Result<Vec<OverlayState>> {
+    let manifest_path = dir.join(MANIFEST_FILE);
+    let content = fs::read_to_string(&manifest_path).context(
+        "Invalid bundle: manifest.ccl is missing (was it written by 'repoverlay export'?)",
+    )?;
+    let manifest: BundleManifest =
+        sickle::from_str(&content).context("Invalid bundle: could not parse manifest.ccl")?;
+    if manifest.format_version > BUNDLE_FORMAT_VERSION {
+        bail!(
+            "Bundle format version {} is newer than this version of repoverlay supports ({}). Please upgrade repoverlay.",
+            manifest.format_version,
+            BUNDLE_FORMAT_VERSION
+        );
+    }
+    if manifest.overlays.is_empty() {
+        bail!("Invalid bundle: manifest.ccl lists no overlays");
+    }
+
+    let schema_version = state_dir_schema_version(&dir.join(BUNDLE_STATE_DIR))
+        .context("Invalid bundle: could not read state/meta.ccl")?;
+    let mut states = Vec::new();
+    for name in &manifest.overlays {
+        if normalize_overlay_name(name).ok().as_ref() != Some(name) {
+            bail!("Invalid bundle: '{name}' is not a valid overlay name");
+        }
+        let state_path = dir
+            .join(BUNDLE_STATE_DIR)
+            .join(OVERLAYS_DIR)
+            .join(state_file_name(name));
+        let content = fs::read_to_string(&state_path)
+            .with_context(|| format!("Invalid bundle: state for overlay '{name}' is missing"))?;
+        let state = migrate_state(&content, schema_version)
+            .with_context(|| format!("Invalid bundle: state for overlay '{name}' is corrupt"))?;
+        if !dir.join(BUNDLE_FILES_DIR).join(name).is_dir() {
+            bail!("Invalid bundle: files for overlay '{name}' are missing");
+        }
+        states.push(OverlayState {
+            name: name.clone(),
+            ..state
+        });
+    }
+    Ok(states)
+}
+
+/// Move each overlay's files from an unpacked bundle into `sources_dir`.
+fn install_sources(staging: &Path, bundle: &[OverlayState], sources_dir: &Path) -> Result<()> {
+    fs::create_dir_all(sources_dir)?;
+    for state in bundle {
+        let dest = sources_dir.join(&state.name);
+        if dest.exists() {
+            fs::remove_dir_all(&dest)?;
+        }
+        let from = staging.join(BUNDLE_FILES_DIR).join(&state.name);
+        if fs::rename(&from, &dest).is_err() {
+            // The staging area may be on another filesystem
+            for file in walkdir::WalkDir::new(&from) {
+                let file = file?;
+                if file.file_type().is_file() {
+                    copy_file(file.path(), &dest.join(file.path().strip_prefix(&from)?))?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Carry the description, tags, and requirements of a bundled overlay over
+/// to the state written when it was applied.
+fn keep_bundled_metadata(target: &Path, bundled: &OverlayState) -> Result<()> {
+    let mut state = load_overlay_state(target, &bundled.name)?;
+    state.description.clone_from(&bundled.description);
+    state.tags.clone_from(&bundled.tags);
+    state.requires.clone_from(&bundled.requires);
+    save_overlay_state(target, &state)?;
+    if let Err(e) = save_external_state(target, &state.name, &state) {
+        debug!("failed to update external state for {}: {e}", state.name);
+    }
+    Ok(())
+}
+
+/// Directory holding the sources of imported overlays.
+///
+/// Location: `~/.local/share/repoverlay/imports/` (Linux/macOS)
+/// or `%LOCALAPPDATA%\repoverlay\imports\` (Windows)
+fn imports_dir() -> Result<PathBuf> {
+    let proj_dirs = ProjectDirs::from("", "", "repoverlay")
+        .ok_or_else(|| anyhow::anyhow!("Could not determine data directory"))?;
+    Ok(proj_dirs.data_dir().join("imports"))
+}
+
+/// Create an empty, uniquely named directory for unpacking or staging a bundle.
+fn staging_dir(purpose: &str) -> Result<PathBuf> {
+    let dir = std::env::temp_dir().join(format!(
+        "repoverlay-{purpose}-{}-{}",
+        std::process::id(),
+        Utc::now().timestamp_nanos_opt().unwrap_or_default()
+    ));
+    if dir.exists() {
+        fs::remove_dir_all(&dir)?;
+    }
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// Lay out a bundle for the overlays in `names` under `dir`.
+///
+/// Returns the number of files written.
+fn write_bundle(target: &Path, names: &[String], dir: &Path) -> Result<usize> {
+    let state_dir = dir.join(BUNDLE_STATE_DIR);
+    fs::create_dir_all(state_dir.join(OVERLAYS_DIR))?;
+
+    let meta = target.join(STATE_DIR).join(META_FILE);
+    if meta.exists() {
+        fs::copy(&meta, state_dir.join(META_FILE))?;
+    }
+
+    let mut file_count = 0;
+    for name in names {
+        let state = load_overlay_state(target, name)?;
+        let file_name = state_file_name(name);
+        fs::copy(
+            target.join(STATE_DIR).join(OVERLAYS_DIR).join(&file_name),
+            state_dir.join(OVERLAYS_DIR).join(&file_name),
+        )?;
+        file_count += materialize_files(target, &state, &dir.join(BUNDLE_FILES_DIR).join(name))?;
+    }
+
+    let manifest = BundleManifest {
+        format_version: BUNDLE_FORMAT_VERSION,
+        created_with_version: env!("CARGO_PKG_VERSION").to_string(),
+        exported_at: Utc::now(),
+        overlays: names.to_vec(),
+    };
+    let content = sickle::to_string(&manifest).context("Failed to serialize bundle manifest")?;
+    fs::write(dir.join(MANIFEST_FILE), content)?;
+
+    Ok(file_count)
+}
+
+/// Copy an overlay's files from the target into `dest`, following symlinks.
+///
+/// Returns the number of files copied.
+fn materialize_files(target: &Path, state: &OverlayState, dest: &Path) -> Result<usize> {
+    let mut count = 0;
+    for entry in &state.files {
+        let from = target.join(&entry.target);
+        let to = dest.join(&entry.target);
+        if !from.exists() {
+            bail!(
+                "'{}' from overlay '{}' is missing (run 'repoverlay repair' first)",
+                entry.target.display(),
+                state.name
+            );
+        }
+
+        if entry.entry_type == EntryType::Directory {
+            for file in walkdir::WalkDir::new(&from).follow_links(true) {
+                let file = file?;
+                if !file.file_type().is_file() {
+                    continue;
+                }
+                let relative = file.path().strip_prefix(&from)?;
+                copy_file(file.path(), &to.join(relative))?;
+                count += 1;
+            }
+        } else {
+            copy_file(&from, &to)?;
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// Copy a file's content, creating parent directories.
+fn copy_file(from: &Path, to: &Path) -> Result<()> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::copy(from, to).with_context(|| format!("Failed to copy {}", from.display()))?;
+    Ok(())
+}
+
+/// Write the contents of `dir` to a gzip-compressed tarball at `output`.
+fn create_tar_gz(dir: &Path, output: &Path) -> Result<()> {
+    let output = absolute_path(output)?;
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let result = Command::new("tar")
+        .arg("-czf")
+        .arg(&output)
+        .arg("-C")
+        .arg(dir)
+        .arg(".")
+        .output()
+        .context("Failed to execute tar (is it installed?)")?;
+    if !result.status.success() {
+        let stderr = String::from_utf8_lossy(&result.stderr);
+        let msg = stderr.trim();
+        bail!("Failed to write bundle {}: {msg}", output.display());
+    }
+    Ok(())
+}
+
+/// Unpack a gzip-compressed tarball into `dir`.
+fn extract_tar_gz(archive: &Path, dir: &Path) -> Result<()> {
+    let result = Command::new("tar")
+        .arg("-xzf")
+        .arg(archive)
+        .arg("-C")
+        .arg(dir)
+        .output()
+        .context("Failed to execute tar (is it installed?)")?;
+    if !result.status.success() {
+        let stderr = String::from_utf8_lossy(&result.stderr);
+        let msg = stderr.trim();
+        bail!(
+            "Invalid bundle {}: not a readable .tar.gz ({msg})",
+            archive.display()
+        );
+    }
+    Ok(())
+}
+
+/// Resolve `path` against the current directory.
+fn absolute_path(path: &Path) -> Result<PathBuf> {
+    if path.is_absolute() {
+        Ok(path.to_path_buf())
+    } else {
+        Ok(std::env::current_dir()?.join(path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ApplyOptions, apply_overlay};
+    use tempfile::TempDir;
+
+    fn repo_with_overlay(files: &[(&str, &str)]) -> (TempDir, TempDir) {
+        let repo = TempDir::new().unwrap();
+        Command::new("git")
+            .arg("init")
+            .current_dir(repo.path())
+            .output()
+            .unwrap();
+        let overlay = TempDir::new().unwrap();
+        for (path, content) in files {
+            let path = overlay.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        apply_overlay(
+            overlay.path().to_str().unwrap(),
+            repo.path(),
+            &ApplyOptions {
+                name: Some("dev".to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        (repo, overlay)
+    }
+
+    #[test]
+    fn write_bundle_dereferences_symlinks() {
+        let (repo, _overlay) = repo_with_overlay(&[(".envrc", "use nix"), ("cfg/a.toml", "a")]);
+        let target = fs::canonicalize(repo.path()).unwrap();
+        let bundle = TempDir::new().unwrap();
+
+        let count = write_bundle(&target, &["dev".to_string()], bundle.path()).unwrap();
+
+        assert_eq!(count, 2);
+        let envrc = bundle.path().join("files/dev/.envrc");
+        assert!(!envrc.is_symlink());
+        assert_eq!(fs::read_to_string(envrc).unwrap(), "use nix");
+        assert_eq!(
+            fs::read_to_string(bundle.path().join("files/dev/cfg/a.toml")).unwrap(),
+            "a"
+        );
+        assert!(bundle.path().join("state/overlays/dev.ccl").exists());
+        assert!(bundle.path().join("state/meta.ccl").exists());
+
+        let manifest: BundleManifest =
+            sickle::from_str(&fs::read_to_string(bundle.path().join(MANIFEST_FILE)).unwrap())
+                .unwrap();
+        assert_eq!(manifest.format_version, BUNDLE_FORMAT_VERSION);
+        assert_eq!(manifest.overlays, vec!["dev".to_string()]);
+    }
+
+    #[test]
+    fn write_bundle_fails_when_file_is_missing() {
+        let (repo, _overlay) = repo_with_overlay(&[(".envrc", "use nix")]);
+        let target = fs::canonicalize(repo.path()).unwrap();
+        fs::remove_file(target.join(".envrc")).unwrap();
+        let bundle = TempDir::new().unwrap();
+
+        let err = write_bundle(&target, &["dev".to_string()], bundle.path()).unwrap_err();
+        assert!(err.to_string().contains("repoverlay repair"));
+    }
+
+    #[test]
+    fn read_bundle_round_trips_written_bundle() {
+        let (repo, _overlay) = repo_with_overlay(&[(".envrc", "use nix")]);
+        let target = fs::canonicalize(repo.path()).unwrap();
+        let bundle = TempDir::new().unwrap();
+        write_bundle(&target, &["dev".to_string()], bundle.path()).unwrap();
+
+        let states = read_bundle(bundle.path()).unwrap();
+
+        assert_eq!(states.len(), 1);
+        assert_eq!(states[0].name, "dev");
+        assert_eq!(states[0].files.len(), 1);
+    }
+
+    #[test]
+    fn read_bundle_rejects_invalid_bundles() {
+        let (repo, _overlay) = repo_with_overlay(&[(".envrc", "use nix")]);
+        let target = fs::canonicalize(repo.path()).unwrap();
+        let bundle = TempDir::new().unwrap();
+        write_bundle(&target, &["dev".to_string()], bundle.path()).unwrap();
+        let manifest = bundle.path().join(MANIFEST_FILE);
+        let original = fs::read_to_string(&manifest).unwrap();
+        let error = |dir: &Path| read_bundle(dir).unwrap_err().to_string();
+
+        fs::write(&manifest, original.replace("dev", "../escape")).unwrap();
+        assert!(error(bundle.path()).contains("not a valid overlay name"));
+
+        fs::write(
+            &manifest,
+            original.replace("format_version = 1", "format_version = 99"),
+        )
+        .unwrap();
+        assert!(error(bundle.path()).contains("Please upgrade repoverlay"));
+
+        fs::write(&manifest, &original).unwrap();
+        fs::remove_dir_all(bundle.path().join("files/dev")).unwrap();
+        assert!(error(bundle.path()).contains("files for overlay 'dev' are missing"));
+
+        fs::remove_file(&manifest).unwrap();
+        assert!(error(bundle.path()).contains("manifest.ccl is missing"));
+    }
+
+    #[test]
+    fn export_requires_applied_overlays() {
+        let repo = TempDir::new().unwrap();
+        let output = repo.path().join("out.tar.gz");
+
+        let err = export_overlays(repo.path(), &output).unwrap_err();
+        assert!(err.to_string().contains("No overlays are applied"));
+        assert!(!output.exists());
+    }
+}
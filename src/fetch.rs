@@ -0,0 +1,181 @@
+//! Concurrent fetching of GitHub overlay sources.
+//!
+//! `restore` and `update` may need to clone or pull many repositories. The
+//! network-bound cache step runs on a small pool of threads, while linking
+//! files into the target stays serial so conflict detection is deterministic.
+
+use anyhow::Result;
+use log::debug;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::thread;
+
+use crate::github::GitHubSource;
+
+/// Default number of concurrent fetches.
+pub const DEFAULT_JOBS: usize = 4;
+
+/// Key identifying a repository at a specific ref, e.g. `github.com/owner/repo@main`.
+pub fn fetch_key(source: &GitHubSource) -> String {
+    format!(
+        "{}/{}/{}@{}",
+        source.host,
+        source.owner,
+        source.repo,
+        source.git_ref.as_str()
+    )
+}
+
+/// Run `fetch` once per distinct repository and ref using up to `jobs` threads.
+///
+/// Sources are deduplicated by [`fetch_key`]. Different refs of the same
+/// repository share one cache checkout, so they are fetched in order by a
+/// single worker rather than concurrently. Results are keyed by [`fetch_key`].
+pub fn fetch_all<T, F>(
+    sources: &[GitHubSource],
+    jobs: usize,
+    fetch: F,
+) -> HashMap<String, Result<T>>
+where
+    T: Send,
+    F: Fn(&GitHubSource) -> Result<T> + Sync,
+{
+    // Group distinct sources by repository, preserving first-seen order
+    let mut groups: Vec<Vec<&GitHubSource>> = Vec::new();
+    let mut seen = Vec::new();
+    for source in sources {
+        let key = fetch_key(source);
+        if seen.contains(&key) {
+            continue;
+        }
+        seen.push(key);
+
+        let same_repo = |s: &&GitHubSource| {
+            s.host == source.host && s.owner == source.owner && s.repo == source.repo
+        };
+        match groups.iter_mut().find(|g| g.first().is_some_and(same_repo)) {
+            Some(group) => group.push(source),
+            None => groups.push(vec![source]),
+        }
+    }
+
+    let workers = jobs.clamp(1, groups.len().max(1));
+    debug!("fetch_all: {} repo(s), {} worker(s)", groups.len(), workers);
+
+    let queue = Mutex::new(groups.into_iter().collect::<VecDeque<_>>());
+    let results = Mutex::new(HashMap::new());
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| {
+                loop {
+                    let Some(group) = queue.lock().unwrap().pop_front() else {
+                        break;
+                    };
+                    for source in group {
+                        let result = fetch(source);
+                        results.lock().unwrap().insert(fetch_key(source), result);
+                    }
+                }
+            });
+        }
+    });
+
+    results.into_inner().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::github::GitRef;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn source(repo: &str, git_ref: GitRef) -> GitHubSource {
+        GitHubSource {
+            host: "github.com".to_string(),
+            owner: "owner".to_string(),
+            repo: repo.to_string(),
+            git_ref,
+            subpath: None,
+            single_file: false,
+        }
+    }
+
+    #[test]
+    fn test_fetch_key() {
+        assert_eq!(
+            fetch_key(&source("repo", GitRef::Branch("main".to_string()))),
+            "github.com/owner/repo@main"
+        );
+    }
+
+    #[test]
+    fn test_fetch_all_deduplicates_sources() {
+        let calls = AtomicUsize::new(0);
+        let mut with_subpath = source("a", GitRef::Default);
+        with_subpath.subpath = Some("overlays/dev".into());
+        let sources = vec![
+            source("a", GitRef::Default),
+            with_subpath,
+            source("b", GitRef::Default),
+        ];
+
+        let results = fetch_all(&sources, 4, |_| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        });
+
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(results.len(), 2);
+    }
+
+    #[test]
+    fn test_fetch_all_serializes_refs_of_same_repo() {
+        let active = AtomicUsize::new(0);
+        let overlapped = AtomicUsize::new(0);
+        let sources = vec![
+            source("a", GitRef::Branch("main".to_string())),
+            source("a", GitRef::Branch("dev".to_string())),
+            source("a", GitRef::Tag("v1".to_string())),
+        ];
+
+        let results = fetch_all(&sources, 4, |_| {
+            if active.fetch_add(1, Ordering::SeqCst) > 0 {
+                overlapped.fetch_add(1, Ordering::SeqCst);
+            }
+            thread::sleep(std::time::Duration::from_millis(10));
+            active.fetch_sub(1, Ordering::SeqCst);
+            Ok(())
+        });
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(overlapped.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn test_fetch_all_keeps_errors_per_source() {
+        let sources = vec![
+            source("good", GitRef::Default),
+            source("bad", GitRef::Default),
+        ];
+
+        let results = fetch_all(&sources, 0, |s| {
+            if s.repo == "bad" {
+                anyhow::bail!("clone failed");
+            }
+            Ok(s.repo.clone())
+        });
+
+        assert_eq!(
+            results["github.com/owner/good@HEAD"].as_ref().unwrap(),
+            "good"
+        );
+        assert!(results["github.com/owner/bad@HEAD"].is_err());
+    }
+
+    #[test]
+    fn test_fetch_all_empty() {
+        let results = fetch_all(&[], 4, |_| Ok(()));
+        assert!(results.is_empty());
+    }
+}
@@ -0,0 +1,263 @@
+//! Git hooks that restore overlays after a checkout (`repoverlay install-hook`).
+//!
+//! `git clean -fdx` deletes `.repoverlay/` along with the overlay files. The
+//! `post-checkout` and `post-merge` hooks run `repoverlay restore` when the
+//! state directory is missing. The restore lines are kept between marker
+//! comments right after the shebang, so an existing hook keeps working and
+//! the block can be found again to update or remove it.
+
+use anyhow::{Context, Result, bail};
+use colored::Colorize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::output::status;
+use crate::{STATE_DIR, canonicalize_path, validate_git_repo};
+
+/// Hooks that run after the working tree changes.
+const HOOK_NAMES: [&str; 2] = ["post-checkout", "post-merge"];
+
+const MARKER_START: &str = "# repoverlay:restore start";
+const MARKER_END: &str = "# repoverlay:restore end";
+
+const SHEBANG: &str = "#!/bin/sh";
+
+/// Write the restore block into each hook, creating or updating it.
+pub fn install_hooks(target: &Path) -> Result<()> {
+    let hooks_dir = hooks_dir(target)?;
+    fs::create_dir_all(&hooks_dir)
+        .with_context(|| format!("Failed to create {}", hooks_dir.display()))?;
+
+    for hook in HOOK_NAMES {
+        let path = hooks_dir.join(hook);
+        let existing = read_hook(&path)?;
+        if let Some(content) = &existing
+            && !is_shell_script(content)
+        {
+            bail!(
+                "{} is not a shell script. Add `repoverlay restore` to it manually.",
+                path.display()
+            );
+        }
+
+        let was_installed = existing.as_deref().is_some_and(has_block);
+        let content = with_block(existing.as_deref().unwrap_or_default());
+        fs::write(&path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+        make_executable(&path)?;
+
+        if was_installed {
+            status!("{} {} hook already installed", "✓".green().bold(), hook);
+        } else {
+            status!("{} Installed {} hook", "✓".green().bold(), hook);
+        }
+    }
+    Ok(())
+}
+
+/// Remove the restore block from each hook, deleting hooks left empty.
+pub fn uninstall_hooks(target: &Path) -> Result<()> {
+    let hooks_dir = hooks_dir(target)?;
+    let mut removed = 0;
+
+    for hook in HOOK_NAMES {
+        let path = hooks_dir.join(hook);
+        let Some(content) = read_hook(&path)? else {
+            continue;
+        };
+        if !has_block(&content) {
+            continue;
+        }
+
+        let remaining = without_block(&content);
+        if remaining.trim().is_empty() || remaining.trim() == SHEBANG {
+            fs::remove_file(&path)
+                .with_context(|| format!("Failed to remove {}", path.display()))?;
+        } else {
+            fs::write(&path, remaining)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+        }
+        status!("{} Removed {} hook", "✓".green().bold(), hook);
+        removed += 1;
+    }
+
+    if removed == 0 {
+        status!("No repoverlay hooks installed in {}", hooks_dir.display());
+    }
+    Ok(())
+}
+
+/// The repository's hooks directory, honoring `core.hooksPath` and worktrees.
+fn hooks_dir(target: &Path) -> Result<PathBuf> {
+    let target = canonicalize_path(target, "Target directory")?;
+    validate_git_repo(&target)?;
+
+    let output = Command::new("git")
+        .args(["rev-parse", "--git-path", "hooks"])
+        .current_dir(&target)
+        .output()
+        .context("Failed to run git")?;
+    if !output.status.success() {
+        bail!(
+            "Failed to find the hooks directory: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let path = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+    Ok(target.join(path))
+}
+
+fn read_hook(path: &Path) -> Result<Option<String>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    fs::read_to_string(path)
+        .map(Some)
+        .with_context(|| format!("Failed to read {}", path.display()))
+}
+
+/// Whether a hook can have shell lines added to it.
+fn is_shell_script(content: &str) -> bool {
+    let Some(shebang) = content.lines().next().and_then(|l| l.strip_prefix("#!")) else {
+        // No shebang: git runs it with sh
+        return true;
+    };
+    let mut words = shebang.split_whitespace();
+    let program = match words.next() {
+        Some(env) if env.ends_with("/env") => words.next(),
+        program => program,
+    };
+    program
+        .and_then(|p| p.rsplit('/').next())
+        .is_some_and(|p| matches!(p, "sh" | "bash" | "dash" | "zsh" | "ksh"))
+}
+
+fn has_block(content: &str) -> bool {
+    content.lines().any(|line| line.trim() == MARKER_START)
+}
+
+/// The lines run by each hook.
+fn block() -> String {
+    format!(
+        "{MARKER_START}\n\
+         # Restore overlays when {STATE_DIR}/ is missing, e.g. after git clean -fdx\n\
+         if [ ! -d \"$(git rev-parse --show-toplevel)/{STATE_DIR}\" ] && command -v repoverlay >/dev/null 2>&1; then\n\
+         \x20 REPOVERLAY_NON_INTERACTIVE=1 repoverlay restore --target \"$(git rev-parse --show-toplevel)\" || true\n\
+         fi\n\
+         {MARKER_END}\n"
+    )
+}
+
+/// `content` with the restore block placed after the shebang, replacing any
+/// existing block.
+fn with_block(content: &str) -> String {
+    let content = without_block(content);
+    let (shebang, rest) = match content.split_once('\n') {
+        Some((first, rest)) if first.starts_with("#!") => (first.to_string(), rest),
+        None if content.starts_with("#!") => (content.clone(), ""),
+        _ => (SHEBANG.to_string(), content.as_str()),
+    };
+    format!("{shebang}\n{}{rest}", block())
+}
+
+/// `content` without the restore block.
+fn without_block(content: &str) -> String {
+    let mut result = String::new();
+    let mut in_block = false;
+    for line in content.lines() {
+        match line.trim() {
+            MARKER_START => in_block = true,
+            MARKER_END => in_block = false,
+            _ if !in_block => {
+                result.push_str(line);
+                result.push('\n');
+            }
+            _ => {}
+        }
+    }
+    result
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    let mut permissions = fs::metadata(path)?.permissions();
+    permissions.set_mode(permissions.mode() | 0o755);
+    fs::set_permissions(path, permissions)
+        .with_context(|| format!("Failed to make {} executable", path.display()))
+}
+
+#[cfg(not(unix))]
+#[allow(clippy::unnecessary_wraps)]
+const fn make_executable(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::create_test_repo;
+
+    #[test]
+    fn with_block_keeps_existing_hook_after_the_block() {
+        let existing = "#!/bin/bash\necho existing\nexit 0\n";
+        let content = with_block(existing);
+        assert!(content.starts_with("#!/bin/bash\n# repoverlay:restore start\n"));
+        assert!(content.ends_with("# repoverlay:restore end\necho existing\nexit 0\n"));
+
+        // Installing again replaces the block instead of adding another
+        assert_eq!(with_block(&content), content);
+        assert_eq!(without_block(&content), existing);
+    }
+
+    #[test]
+    fn with_block_adds_shebang_to_new_hook() {
+        let content = with_block("");
+        assert!(content.starts_with("#!/bin/sh\n# repoverlay:restore start\n"));
+        assert!(content.contains("repoverlay restore"));
+    }
+
+    #[test]
+    fn detects_shell_scripts() {
+        assert!(is_shell_script("#!/bin/sh\n"));
+        assert!(is_shell_script("#!/usr/bin/env bash\n"));
+        assert!(is_shell_script("echo no shebang\n"));
+        assert!(!is_shell_script("#!/usr/bin/env python3\n"));
+        assert!(!is_shell_script("#!/usr/bin/node\n"));
+    }
+
+    #[test]
+    fn install_and_uninstall_round_trip() {
+        let repo = create_test_repo();
+        let hooks = repo.path().join(".git/hooks");
+        fs::create_dir_all(&hooks).unwrap();
+        fs::write(hooks.join("post-merge"), "#!/bin/sh\necho merged\n").unwrap();
+
+        install_hooks(repo.path()).unwrap();
+        install_hooks(repo.path()).unwrap();
+
+        let checkout = fs::read_to_string(hooks.join("post-checkout")).unwrap();
+        assert_eq!(checkout.matches(MARKER_START).count(), 1);
+        let merge = fs::read_to_string(hooks.join("post-merge")).unwrap();
+        assert_eq!(merge.matches(MARKER_START).count(), 1);
+        assert!(merge.contains("echo merged"));
+
+        uninstall_hooks(repo.path()).unwrap();
+        assert!(!hooks.join("post-checkout").exists());
+        assert_eq!(
+            fs::read_to_string(hooks.join("post-merge")).unwrap(),
+            "#!/bin/sh\necho merged\n"
+        );
+    }
+
+    #[test]
+    fn install_refuses_non_shell_hook() {
+        let repo = create_test_repo();
+        let hooks = repo.path().join(".git/hooks");
+        fs::create_dir_all(&hooks).unwrap();
+        fs::write(hooks.join("post-checkout"), "#!/usr/bin/env python3\n").unwrap();
+
+        let err = install_hooks(repo.path()).unwrap_err();
+        assert!(err.to_string().contains("not a shell script"));
+    }
+}
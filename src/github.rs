@@ -1,19 +1,29 @@
 //! GitHub URL parsing for repoverlay.
 //!
 //! Parses GitHub repository URLs into structured components for cloning and caching.
+//! In addition to `github.com`, GitHub Enterprise hosts listed under
+//! `github_hosts` in the global config are recognized.
 
 use anyhow::{Context, Result, anyhow, bail};
 use std::path::PathBuf;
 use std::str::FromStr;
 use url::Url;
 
+/// Host name of public GitHub.
+pub const DEFAULT_GITHUB_HOST: &str = "github.com";
+
 /// Parsed GitHub URL components.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct GitHubSource {
+    /// Host the repository lives on (`github.com` or an enterprise host).
+    pub host: String,
     pub owner: String,
     pub repo: String,
     pub git_ref: GitRef,
     pub subpath: Option<PathBuf>,
+    /// Whether `subpath` names a single file (from a `/blob/` URL) rather
+    /// than a directory.
+    pub single_file: bool,
 }
 
 /// Git reference type.
@@ -40,12 +50,26 @@ impl GitHubSource {
     /// - `https://github.com/owner/repo/tree/branch/path/to/subdir`
     /// - `https://github.com/owner/repo/tree/v1.0.0`
     /// - `https://github.com/owner/repo/tree/abc123...` (commit SHA)
+    /// - `https://github.com/owner/repo/blob/main/path/to/file` (single file)
+    ///
+    /// A ref containing slashes can't be told apart from a subpath in a
+    /// `/tree/` URL, so the first segment is taken as the ref. Use
+    /// [`Self::with_ref_override`] or [`Self::with_known_refs`] to re-split it.
+    ///
+    /// Enterprise hosts from the global `github_hosts` config are accepted in
+    /// place of `github.com`.
     pub fn parse(input: &str) -> Result<Self> {
+        Self::parse_with_hosts(input, &configured_hosts())
+    }
+
+    /// Parse a GitHub URL, accepting `github.com` plus the given extra hosts.
+    pub fn parse_with_hosts(input: &str, extra_hosts: &[String]) -> Result<Self> {
         let url = Url::parse(input).with_context(|| format!("Invalid URL: {input}"))?;
 
-        if url.host_str() != Some("github.com") {
-            bail!("Not a GitHub URL: {input}");
-        }
+        let host = match url.host_str() {
+            Some(host) if is_github_host(host, extra_hosts) => host.to_ascii_lowercase(),
+            _ => bail!("Not a GitHub URL: {input}"),
+        };
 
         // Extract path segments: /owner/repo[/tree/ref/subpath]
         let path = url.path().trim_start_matches('/');
@@ -58,6 +82,7 @@ impl GitHubSource {
         let owner = segments[0].to_string();
         let repo = segments[1].trim_end_matches(".git").to_string();
 
+        let mut single_file = false;
         let (git_ref, subpath) = if segments.len() > 2 {
             if segments[2] == "tree" {
                 // Has ref and possibly subpath
@@ -71,12 +96,23 @@ impl GitHubSource {
                     None
                 };
 
-                (ref_str.parse().unwrap(), subpath)
+                // `HEAD` is how `display_url` writes the default branch
+                let git_ref = if *ref_str == "HEAD" {
+                    GitRef::Default
+                } else {
+                    ref_str.parse().unwrap()
+                };
+                (git_ref, subpath)
             } else if segments[2] == "blob" {
-                // User pasted a file URL instead of tree URL
-                bail!(
-                    "Invalid GitHub URL: use /tree/ URLs for directories, not /blob/ URLs for files"
-                );
+                // A single file: /blob/ref/path/to/file
+                if segments.len() < 5 || segments[4..].iter().all(|s| s.is_empty()) {
+                    bail!("Missing file path after /blob/<ref>/ in URL: {input}");
+                }
+                single_file = true;
+                (
+                    segments[3].parse().unwrap(),
+                    Some(PathBuf::from(segments[4..].join("/"))),
+                )
             } else {
                 // Unknown path component, treat as default ref
                 (GitRef::Default, None)
@@ -86,16 +122,28 @@ impl GitHubSource {
         };
 
         Ok(Self {
+            host,
             owner,
             repo,
             git_ref,
             subpath,
+            single_file,
         })
     }
 
-    /// Check if a string looks like a GitHub URL.
+    /// Check if a string looks like a GitHub URL (including configured enterprise hosts).
     pub fn is_github_url(input: &str) -> bool {
-        input.starts_with("https://github.com/") || input.starts_with("http://github.com/")
+        Self::is_github_url_with_hosts(input, &configured_hosts())
+    }
+
+    /// Check if a string looks like a GitHub URL on `github.com` or one of the given hosts.
+    pub fn is_github_url_with_hosts(input: &str, extra_hosts: &[String]) -> bool {
+        Url::parse(input).is_ok_and(|url| {
+            (url.scheme() == "https" || url.scheme() == "http")
+                && url
+                    .host_str()
+                    .is_some_and(|host| is_github_host(host, extra_hosts))
+        })
     }
 
     /// Generate a unique cache directory name.
@@ -112,28 +160,91 @@ impl GitHubSource {
 
     /// Full clone URL for the repository.
     pub fn clone_url(&self) -> String {
-        format!("https://github.com/{}/{}.git", self.owner, self.repo)
+        format!("https://{}/{}/{}.git", self.host, self.owner, self.repo)
     }
 
     /// Human-readable display of the source.
     #[allow(dead_code)]
     pub fn display_url(&self) -> String {
-        let base = format!("https://github.com/{}/{}", self.owner, self.repo);
+        let base = format!("https://{}/{}/{}", self.host, self.owner, self.repo);
+        let kind = if self.single_file { "blob" } else { "tree" };
         match (&self.git_ref, &self.subpath) {
             (GitRef::Default, None) => base,
-            (GitRef::Default, Some(path)) => format!("{}/tree/HEAD/{}", base, path.display()),
-            (ref_, None) => format!("{}/tree/{}", base, ref_.as_str()),
-            (ref_, Some(path)) => format!("{}/tree/{}/{}", base, ref_.as_str(), path.display()),
+            (GitRef::Default, Some(path)) => format!("{}/{kind}/HEAD/{}", base, path.display()),
+            (ref_, None) => format!("{}/{kind}/{}", base, ref_.as_str()),
+            (ref_, Some(path)) => {
+                format!("{}/{kind}/{}/{}", base, ref_.as_str(), path.display())
+            }
         }
     }
 
     /// Apply a ref override from CLI.
+    ///
+    /// If the URL's `/tree/` path starts with the override (e.g. `--ref
+    /// release/2024-q3` for `/tree/release/2024-q3/overlays`), the rest of the
+    /// path becomes the subpath.
     pub fn with_ref_override(mut self, ref_override: Option<&str>) -> Self {
         if let Some(ref_str) = ref_override {
+            if let Some(rest) = self.tree_path_after_ref(ref_str) {
+                self.subpath = subpath_from(&rest);
+            }
+            self.git_ref = ref_str.parse().unwrap();
+        }
+        self
+    }
+
+    /// Re-split a `/tree/<ref>/<subpath>` URL using the refs that exist in
+    /// the repository, preferring the longest ref that matches.
+    pub fn with_known_refs(mut self, refs: &[String]) -> Self {
+        let best = refs
+            .iter()
+            .filter_map(|r| self.tree_path_after_ref(r).map(|rest| (r, rest)))
+            .max_by_key(|(r, _)| r.len());
+        if let Some((ref_str, rest)) = best {
+            self.subpath = subpath_from(&rest);
             self.git_ref = ref_str.parse().unwrap();
         }
         self
     }
+
+    /// Replace the subpath with a directory inside the repository (`--subpath`).
+    pub fn with_subpath(mut self, subpath: &str) -> Self {
+        self.subpath = subpath_from(subpath.trim_matches('/'));
+        self.single_file = false;
+        self
+    }
+
+    /// Whether the ref and subpath came from a `/tree/` URL path that could
+    /// be split differently (a branch followed by a subpath).
+    pub const fn has_ambiguous_ref(&self) -> bool {
+        matches!(self.git_ref, GitRef::Branch(_)) && self.subpath.is_some()
+    }
+
+    /// The rest of the `/tree/` path after `ref_str`, if the path starts with
+    /// it. Empty when the path is exactly `ref_str`.
+    fn tree_path_after_ref(&self, ref_str: &str) -> Option<String> {
+        let GitRef::Branch(branch) = &self.git_ref else {
+            return None;
+        };
+        let mut tree_path = branch.clone();
+        if let Some(subpath) = &self.subpath {
+            tree_path.push('/');
+            tree_path.push_str(&subpath.to_string_lossy());
+        }
+
+        if tree_path == ref_str {
+            return Some(String::new());
+        }
+        tree_path
+            .strip_prefix(ref_str)
+            .and_then(|rest| rest.strip_prefix('/'))
+            .map(str::to_string)
+    }
+}
+
+/// Subpath for the rest of a `/tree/` path, `None` if nothing is left.
+fn subpath_from(rest: &str) -> Option<PathBuf> {
+    (!rest.is_empty()).then(|| PathBuf::from(rest))
 }
 
 impl FromStr for GitRef {
@@ -185,28 +296,33 @@ impl std::fmt::Display for GitRef {
 /// Parse owner/repo from a git remote URL (HTTPS or SSH format).
 ///
 /// Returns `None` if the URL is not a GitHub URL or cannot be parsed.
+/// Enterprise hosts from the global `github_hosts` config are recognized.
 pub fn parse_remote_url(url: &str) -> Option<(String, String)> {
-    // Must be a GitHub URL
-    if !url.contains("github.com") {
-        return None;
-    }
+    parse_remote_url_with_hosts(url, &configured_hosts())
+}
 
-    // Handle SSH format: git@github.com:owner/repo.git
-    if let Some(path) = url.strip_prefix("git@github.com:") {
-        let path = path.trim_end_matches(".git");
-        let parts: Vec<&str> = path.split('/').collect();
-        if parts.len() >= 2 && !parts[0].is_empty() && !parts[1].is_empty() {
-            return Some((parts[0].to_string(), parts[1].to_string()));
+/// Parse owner/repo from a git remote URL on `github.com` or one of the given hosts.
+pub fn parse_remote_url_with_hosts(url: &str, extra_hosts: &[String]) -> Option<(String, String)> {
+    // Handle SSH format: git@host:owner/repo.git
+    let path = if let Some(rest) = url.strip_prefix("git@") {
+        let (host, path) = rest.split_once(':')?;
+        if !is_github_host(host, extra_hosts) {
+            return None;
         }
-        return None;
-    }
-
-    // Handle HTTPS format: https://github.com/owner/repo.git
-    let path = url
-        .trim_start_matches("https://github.com/")
-        .trim_start_matches("http://github.com/")
-        .trim_end_matches(".git");
+        path
+    } else {
+        // Handle HTTPS format: https://host/owner/repo.git
+        let rest = url
+            .strip_prefix("https://")
+            .or_else(|| url.strip_prefix("http://"))?;
+        let (host, path) = rest.split_once('/')?;
+        if !is_github_host(host, extra_hosts) {
+            return None;
+        }
+        path
+    };
 
+    let path = path.trim_end_matches(".git");
     let parts: Vec<&str> = path.split('/').collect();
     if parts.len() >= 2 && !parts[0].is_empty() && !parts[1].is_empty() {
         return Some((parts[0].to_string(), parts[1].to_string()));
@@ -215,6 +331,63 @@ pub fn parse_remote_url(url: &str) -> Option<(String, String)> {
     None
 }
 
+/// Parse the `owner/repo` shorthand for a repository on `github.com`.
+///
+/// Returns `None` for anything else, including relative paths such as
+/// `./owner/repo` and `org/repo/name` overlay references.
+pub fn parse_shorthand(s: &str) -> Option<(String, String)> {
+    let (owner, repo) = s.split_once('/')?;
+    let repo = repo.strip_suffix(".git").unwrap_or(repo);
+    let owner_ok =
+        !owner.is_empty() && owner.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+    let repo_ok = !repo.is_empty()
+        && repo != "."
+        && repo != ".."
+        && repo
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    (owner_ok && repo_ok).then(|| (owner.to_string(), repo.to_string()))
+}
+
+/// Web URL that opens a pull request from `head` into `base` for the GitHub
+/// repository behind a git remote URL, or `None` for non-GitHub remotes.
+pub fn compare_url(remote_url: &str, base: &str, head: &str) -> Option<String> {
+    let (owner, repo) = parse_remote_url(remote_url)?;
+    let host = remote_url.strip_prefix("git@").map_or_else(
+        || host_from_url(remote_url),
+        |rest| {
+            rest.split(':')
+                .next()
+                .unwrap_or(DEFAULT_GITHUB_HOST)
+                .to_string()
+        },
+    );
+    Some(format!(
+        "https://{host}/{owner}/{repo}/compare/{base}...{head}?expand=1"
+    ))
+}
+
+/// Get the host of a GitHub URL, falling back to `github.com`.
+pub fn host_from_url(input: &str) -> String {
+    Url::parse(input)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_ascii_lowercase))
+        .unwrap_or_else(|| DEFAULT_GITHUB_HOST.to_string())
+}
+
+/// Check whether `host` is `github.com` or one of the given extra hosts.
+fn is_github_host(host: &str, extra_hosts: &[String]) -> bool {
+    host.eq_ignore_ascii_case(DEFAULT_GITHUB_HOST)
+        || extra_hosts.iter().any(|h| h.eq_ignore_ascii_case(host))
+}
+
+/// Enterprise hosts configured in the global config.
+fn configured_hosts() -> Vec<String> {
+    crate::config::load_global_config()
+        .map(|config| config.github_hosts)
+        .unwrap_or_default()
+}
+
 /// Sanitize a string for use in a filesystem path.
 #[allow(dead_code)]
 fn sanitize_for_path(s: &str) -> String {
@@ -233,6 +406,28 @@ fn sanitize_for_path(s: &str) -> String {
 mod tests {
     use super::*;
 
+    #[test]
+    fn parses_owner_repo_shorthand() {
+        assert_eq!(
+            parse_shorthand("owner/repo"),
+            Some(("owner".to_string(), "repo".to_string()))
+        );
+        assert_eq!(
+            parse_shorthand("my-org/.github"),
+            Some(("my-org".to_string(), ".github".to_string()))
+        );
+        assert_eq!(
+            parse_shorthand("owner/repo.git"),
+            Some(("owner".to_string(), "repo".to_string()))
+        );
+        assert_eq!(parse_shorthand("org/repo/name"), None);
+        assert_eq!(parse_shorthand("./repo"), None);
+        assert_eq!(parse_shorthand("owner/.."), None);
+        assert_eq!(parse_shorthand("/repo"), None);
+        assert_eq!(parse_shorthand("owner/"), None);
+        assert_eq!(parse_shorthand("overlay"), None);
+    }
+
     #[test]
     fn test_parse_basic_url() {
         let source = GitHubSource::parse("https://github.com/owner/repo").unwrap();
@@ -297,8 +492,26 @@ mod tests {
     }
 
     #[test]
-    fn test_reject_blob_url() {
-        let result = GitHubSource::parse("https://github.com/owner/repo/blob/main/file.txt");
+    fn test_parse_blob_url() {
+        let source =
+            GitHubSource::parse("https://github.com/owner/repo/blob/main/dotfiles/.gitignore")
+                .unwrap();
+        assert_eq!(source.git_ref, GitRef::Branch("main".to_string()));
+        assert_eq!(source.subpath, Some(PathBuf::from("dotfiles/.gitignore")));
+        assert!(source.single_file);
+        assert_eq!(
+            source.display_url(),
+            "https://github.com/owner/repo/blob/main/dotfiles/.gitignore"
+        );
+
+        let source =
+            GitHubSource::parse("https://github.com/owner/repo/tree/main/dotfiles").unwrap();
+        assert!(!source.single_file);
+    }
+
+    #[test]
+    fn test_reject_blob_url_without_file() {
+        let result = GitHubSource::parse("https://github.com/owner/repo/blob/main");
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("blob"));
     }
@@ -325,6 +538,77 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_parse_enterprise_host() {
+        let hosts = vec!["github.mycorp.com".to_string()];
+        let source = GitHubSource::parse_with_hosts(
+            "https://github.mycorp.com/team/configs/tree/main/rust",
+            &hosts,
+        )
+        .unwrap();
+        assert_eq!(source.host, "github.mycorp.com");
+        assert_eq!(source.owner, "team");
+        assert_eq!(source.repo, "configs");
+        assert_eq!(source.subpath, Some(PathBuf::from("rust")));
+        assert_eq!(
+            source.clone_url(),
+            "https://github.mycorp.com/team/configs.git"
+        );
+    }
+
+    #[test]
+    fn test_reject_unconfigured_enterprise_host() {
+        let result = GitHubSource::parse_with_hosts("https://github.mycorp.com/team/configs", &[]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_is_github_url_with_hosts() {
+        let hosts = vec!["github.mycorp.com".to_string()];
+        assert!(GitHubSource::is_github_url_with_hosts(
+            "https://github.mycorp.com/team/configs",
+            &hosts
+        ));
+        assert!(GitHubSource::is_github_url_with_hosts(
+            "https://github.com/owner/repo",
+            &hosts
+        ));
+        assert!(!GitHubSource::is_github_url_with_hosts(
+            "https://github.mycorp.com/team/configs",
+            &[]
+        ));
+        assert!(!GitHubSource::is_github_url_with_hosts(
+            "https://github.com.evil.example/owner/repo",
+            &hosts
+        ));
+    }
+
+    #[test]
+    fn test_parse_remote_url_enterprise_host() {
+        let hosts = vec!["github.mycorp.com".to_string()];
+        assert_eq!(
+            parse_remote_url_with_hosts("git@github.mycorp.com:team/configs.git", &hosts),
+            Some(("team".to_string(), "configs".to_string()))
+        );
+        assert_eq!(
+            parse_remote_url_with_hosts("https://github.mycorp.com/team/configs.git", &hosts),
+            Some(("team".to_string(), "configs".to_string()))
+        );
+        assert_eq!(
+            parse_remote_url_with_hosts("git@github.mycorp.com:team/configs.git", &[]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_host_from_url() {
+        assert_eq!(
+            host_from_url("https://GitHub.MyCorp.com/team/configs"),
+            "github.mycorp.com"
+        );
+        assert_eq!(host_from_url("not a url"), DEFAULT_GITHUB_HOST);
+    }
+
     #[test]
     fn test_clone_url() {
         let source = GitHubSource::parse("https://github.com/owner/repo/tree/main/subdir").unwrap();
@@ -384,6 +668,37 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_tree_head_is_default_ref() {
+        let source =
+            GitHubSource::parse("https://github.com/owner/repo/tree/HEAD/some/path").unwrap();
+        assert_eq!(source.git_ref, GitRef::Default);
+        assert_eq!(source.subpath, Some(PathBuf::from("some/path")));
+    }
+
+    #[test]
+    fn test_with_subpath() {
+        let source = GitHubSource::parse("https://github.com/owner/repo")
+            .unwrap()
+            .with_subpath("/overlays/rust/");
+        assert_eq!(source.subpath, Some(PathBuf::from("overlays/rust")));
+        assert_eq!(
+            source.display_url(),
+            "https://github.com/owner/repo/tree/HEAD/overlays/rust"
+        );
+        assert_eq!(GitHubSource::parse(&source.display_url()).unwrap(), source);
+    }
+
+    #[test]
+    fn test_with_subpath_replaces_url_subpath() {
+        let source = GitHubSource::parse("https://github.com/owner/repo/blob/main/file.txt")
+            .unwrap()
+            .with_subpath("overlays");
+        assert_eq!(source.git_ref, GitRef::Branch("main".to_string()));
+        assert_eq!(source.subpath, Some(PathBuf::from("overlays")));
+        assert!(!source.single_file);
+    }
+
     #[test]
     fn test_git_ref_is_default() {
         assert!(GitRef::Default.is_default());
@@ -439,6 +754,69 @@ mod tests {
         assert_eq!(sanitize_for_path("special!@#chars"), "special___chars");
     }
 
+    #[test]
+    fn test_with_ref_override_keeps_slashes() {
+        let source = GitHubSource::parse("https://github.com/owner/repo")
+            .unwrap()
+            .with_ref_override(Some("release/2024-q3"));
+        assert_eq!(
+            source.git_ref,
+            GitRef::Branch("release/2024-q3".to_string())
+        );
+        assert_eq!(source.subpath, None);
+    }
+
+    #[test]
+    fn test_with_ref_override_splits_slashed_tree_ref() {
+        let url = "https://github.com/owner/repo/tree/release/2024-q3/overlays/rust";
+        let source = GitHubSource::parse(url).unwrap();
+        assert_eq!(source.git_ref, GitRef::Branch("release".to_string()));
+        assert!(source.has_ambiguous_ref());
+
+        let source = source.with_ref_override(Some("release/2024-q3"));
+        assert_eq!(
+            source.git_ref,
+            GitRef::Branch("release/2024-q3".to_string())
+        );
+        assert_eq!(source.subpath, Some(PathBuf::from("overlays/rust")));
+
+        let source = GitHubSource::parse("https://github.com/owner/repo/tree/release/2024-q3")
+            .unwrap()
+            .with_ref_override(Some("release/2024-q3"));
+        assert_eq!(source.subpath, None);
+
+        // An unrelated override keeps the URL's subpath
+        let source = GitHubSource::parse(url)
+            .unwrap()
+            .with_ref_override(Some("main"));
+        assert_eq!(source.git_ref, GitRef::Branch("main".to_string()));
+        assert_eq!(source.subpath, Some(PathBuf::from("2024-q3/overlays/rust")));
+    }
+
+    #[test]
+    fn test_with_known_refs_prefers_longest_match() {
+        let url = "https://github.com/owner/repo/tree/release/2024-q3/overlays";
+        let refs = vec![
+            "main".to_string(),
+            "release".to_string(),
+            "release/2024-q3".to_string(),
+        ];
+
+        let source = GitHubSource::parse(url).unwrap().with_known_refs(&refs);
+        assert_eq!(
+            source.git_ref,
+            GitRef::Branch("release/2024-q3".to_string())
+        );
+        assert_eq!(source.subpath, Some(PathBuf::from("overlays")));
+
+        // No matching ref leaves the URL split as parsed
+        let source = GitHubSource::parse(url)
+            .unwrap()
+            .with_known_refs(&["main".to_string()]);
+        assert_eq!(source.git_ref, GitRef::Branch("release".to_string()));
+        assert_eq!(source.subpath, Some(PathBuf::from("2024-q3/overlays")));
+    }
+
     #[test]
     fn test_with_ref_override_none() {
         let source = GitHubSource::parse("https://github.com/owner/repo/tree/main")
@@ -537,6 +915,22 @@ mod tests {
         assert_eq!(result, None);
     }
 
+    #[test]
+    fn test_compare_url() {
+        assert_eq!(
+            compare_url("git@github.com:acme/overlays.git", "main", "repoverlay/x").as_deref(),
+            Some("https://github.com/acme/overlays/compare/main...repoverlay/x?expand=1")
+        );
+        assert_eq!(
+            compare_url("https://github.com/acme/overlays", "main", "topic").as_deref(),
+            Some("https://github.com/acme/overlays/compare/main...topic?expand=1")
+        );
+        assert_eq!(
+            compare_url("git@gitlab.com:acme/overlays.git", "main", "topic"),
+            None
+        );
+    }
+
     #[test]
     fn test_parse_remote_url_ssh_invalid_format() {
         // SSH URL with empty owner
@@ -592,13 +986,9 @@ mod tests {
         // HTTP(S) URLs are case-insensitive for the host
         assert!(GitHubSource::is_github_url("https://github.com/owner/repo"));
         assert!(GitHubSource::is_github_url("http://github.com/owner/repo"));
-        // But our simple check is case-sensitive (only lowercase matches)
-        assert!(!GitHubSource::is_github_url(
-            "https://GitHub.com/owner/repo"
-        ));
-        assert!(!GitHubSource::is_github_url(
-            "https://GITHUB.COM/owner/repo"
-        ));
+        // Hosts are normalized by URL parsing, matching `parse`
+        assert!(GitHubSource::is_github_url("https://GitHub.com/owner/repo"));
+        assert!(GitHubSource::is_github_url("https://GITHUB.COM/owner/repo"));
     }
 
     #[test]
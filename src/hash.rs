@@ -0,0 +1,45 @@
+//! SHA-256 hashing for content verification.
+//!
+//! Thin wrappers around the `sha2` crate for hashing downloaded archives and
+//! overlay files as lowercase hex strings.
+
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Compute the SHA-256 digest of `data` as a lowercase hex string.
+pub fn sha256_hex(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+/// Compute the SHA-256 digest of a file's contents as a lowercase hex string.
+pub fn sha256_file(path: &Path) -> Result<String> {
+    let mut file =
+        fs::File::open(path).with_context(|| format!("Failed to read file: {}", path.display()))?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)
+        .with_context(|| format!("Failed to read file: {}", path.display()))?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_sha256_file() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("file.txt");
+        fs::write(&path, "abc").unwrap();
+        assert_eq!(sha256_file(&path).unwrap(), sha256_hex(b"abc"));
+    }
+
+    #[test]
+    fn test_sha256_file_missing() {
+        let temp = TempDir::new().unwrap();
+        assert!(sha256_file(&temp.path().join("missing")).is_err());
+    }
+}
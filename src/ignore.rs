@@ -0,0 +1,264 @@
+//! `.repoverlayignore` support for overlay creation.
+//!
+//! Lets `create` skip files such as `node_modules/` or `*.log`. The file uses
+//! gitignore syntax and is matched with the `ignore` crate's gitignore
+//! matcher, so comments, negation, directory-only, anchored, and `**`
+//! patterns behave as they do in git. `create --include` and `apply --only`
+//! globs are matched with the `glob` crate.
+
+use anyhow::{Context, Result};
+use glob::{MatchOptions, Pattern};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Name of the ignore file read from the root of the directory being captured.
+pub const IGNORE_FILE_NAME: &str = ".repoverlayignore";
+
+/// Parsed contents of a `.repoverlayignore` file.
+#[derive(Debug, Clone)]
+pub struct IgnoreFile {
+    matcher: Gitignore,
+}
+
+impl Default for IgnoreFile {
+    fn default() -> Self {
+        Self {
+            matcher: Gitignore::empty(),
+        }
+    }
+}
+
+impl IgnoreFile {
+    /// Load the ignore file from `root`, or an empty matcher if there is none.
+    pub fn load(root: &Path) -> Result<Self> {
+        let path = root.join(IGNORE_FILE_NAME);
+        if !path.is_file() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Ok(Self::parse(&content))
+    }
+
+    /// Parse ignore rules from gitignore-style text. As in git, lines that
+    /// are not valid patterns are skipped.
+    pub fn parse(content: &str) -> Self {
+        let mut builder = GitignoreBuilder::new("");
+        for line in content.lines() {
+            let _ = builder.add_line(None, line);
+        }
+        builder
+            .build()
+            .map(|matcher| Self { matcher })
+            .unwrap_or_default()
+    }
+
+    /// Whether the matcher has no rules.
+    pub fn is_empty(&self) -> bool {
+        self.matcher.is_empty()
+    }
+
+    /// Check whether a path relative to the root is ignored.
+    ///
+    /// As with git, a path inside an ignored directory is ignored even if a
+    /// later negated pattern would match the path itself.
+    pub fn is_ignored(&self, rel_path: &Path, is_dir: bool) -> bool {
+        if self.is_empty() {
+            return false;
+        }
+
+        let components = path_components(rel_path);
+        (1..=components.len()).any(|end| {
+            let is_last = end == components.len();
+            let prefix: PathBuf = components[..end].iter().collect();
+            self.is_ignored_entry(&prefix, !is_last || is_dir)
+        })
+    }
+
+    /// Check a path against the rules without looking at its parent
+    /// directories, for walks that already prune ignored directories.
+    pub fn is_ignored_entry(&self, rel_path: &Path, is_dir: bool) -> bool {
+        !rel_path.as_os_str().is_empty() && self.matcher.matched(rel_path, is_dir).is_ignore()
+    }
+}
+/// Glob options for `--include` and `--only`: `*` and `?` stay within a path
+/// segment, while `**` spans directories.
+const GLOB_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// Whether a `--include` value contains glob metacharacters.
+pub fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?', '['])
+}
+
+/// Match a relative path against a glob anchored at the root, such as
+/// `.github/workflows/*.yml`. An invalid pattern matches nothing.
+pub fn glob_matches(pattern: &str, rel_path: &Path) -> bool {
+    Pattern::new(pattern.trim_start_matches("./"))
+        .is_ok_and(|pattern| pattern.matches_path_with(rel_path, GLOB_OPTIONS))
+}
+
+/// Expand a glob relative to `root`, returning the matching paths relative to
+/// `root` in sorted order.
+pub fn expand_glob(root: &Path, pattern: &str) -> Result<Vec<PathBuf>> {
+    let full =
+        Path::new(&Pattern::escape(&root.to_string_lossy())).join(pattern.trim_start_matches("./"));
+    let paths = glob::glob_with(&full.to_string_lossy(), GLOB_OPTIONS)
+        .with_context(|| format!("Invalid glob pattern: {pattern}"))?;
+    Ok(paths
+        .filter_map(std::result::Result::ok)
+        .filter_map(|path| path.strip_prefix(root).ok().map(Path::to_path_buf))
+        .collect())
+}
+
+fn path_components(path: &Path) -> Vec<String> {
+    path.components()
+        .filter_map(|c| match c {
+            Component::Normal(s) => Some(s.to_string_lossy().into_owned()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn ignored(patterns: &str, path: &str) -> bool {
+        IgnoreFile::parse(patterns).is_ignored(Path::new(path), false)
+    }
+
+    #[test]
+    fn empty_file_ignores_nothing() {
+        let ignore = IgnoreFile::parse("# just a comment\n\n");
+        assert!(ignore.is_empty());
+        assert!(!ignore.is_ignored(Path::new("anything"), false));
+    }
+
+    #[test]
+    fn unanchored_pattern_matches_at_any_depth() {
+        assert!(ignored("*.log", "debug.log"));
+        assert!(ignored("*.log", "logs/nested/debug.log"));
+        assert!(!ignored("*.log", "debug.txt"));
+    }
+
+    #[test]
+    fn directory_pattern_ignores_contents() {
+        assert!(ignored("node_modules/", "node_modules/pkg/index.js"));
+        assert!(ignored("node_modules/", "web/node_modules/pkg/index.js"));
+        // A file with the same name is not a directory
+        assert!(!ignored("node_modules/", "node_modules"));
+    }
+
+    #[test]
+    fn anchored_pattern_matches_from_root_only() {
+        assert!(ignored("/build", "build/out.bin"));
+        assert!(!ignored("/build", "src/build/out.bin"));
+        assert!(ignored("docs/tmp", "docs/tmp/notes.md"));
+        assert!(!ignored("docs/tmp", "other/docs/tmp/notes.md"));
+    }
+
+    #[test]
+    fn double_star_spans_directories() {
+        assert!(ignored("**/cache", "a/b/cache/file"));
+        assert!(ignored("**/cache", "cache/file"));
+        assert!(ignored("docs/**/*.bak", "docs/a/b/file.bak"));
+        assert!(ignored("docs/**/*.bak", "docs/file.bak"));
+        assert!(ignored("out/**", "out/deep/file"));
+        assert!(!ignored("docs/**/*.bak", "src/file.bak"));
+    }
+
+    #[test]
+    fn single_star_stays_within_segment() {
+        assert!(ignored("docs/*.md", "docs/readme.md"));
+        assert!(!ignored("docs/*.md", "docs/nested/readme.md"));
+    }
+
+    #[test]
+    fn negation_reincludes_files() {
+        let patterns = "*.log\n!keep.log\n";
+        assert!(ignored(patterns, "debug.log"));
+        assert!(!ignored(patterns, "keep.log"));
+        assert!(!ignored(patterns, "nested/keep.log"));
+    }
+
+    #[test]
+    fn negation_cannot_reinclude_inside_ignored_directory() {
+        let patterns = "vendor/\n!vendor/keep.txt\n";
+        assert!(ignored(patterns, "vendor/keep.txt"));
+    }
+
+    #[test]
+    fn entry_check_ignores_parent_directories() {
+        let ignore = IgnoreFile::parse("scratch/\n");
+        assert!(ignore.is_ignored(Path::new("scratch/notes.md"), false));
+        assert!(!ignore.is_ignored_entry(Path::new("scratch/notes.md"), false));
+        assert!(ignore.is_ignored_entry(Path::new("a/scratch"), true));
+    }
+
+    #[test]
+    fn character_classes_and_question_mark() {
+        assert!(ignored("file[0-9].txt", "file3.txt"));
+        assert!(!ignored("file[0-9].txt", "fileA.txt"));
+        assert!(ignored("file[!0-9].txt", "fileA.txt"));
+        assert!(ignored("?.tmp", "a.tmp"));
+        assert!(!ignored("?.tmp", "ab.tmp"));
+    }
+
+    #[test]
+    fn escaped_leading_characters_are_literal() {
+        assert!(ignored("\\#notes", "#notes"));
+        assert!(ignored("\\!important", "!important"));
+    }
+
+    #[test]
+    fn glob_matches_is_anchored_at_root() {
+        assert!(is_glob(".github/workflows/*.yml"));
+        assert!(!is_glob(".github/workflows/ci.yml"));
+        assert!(glob_matches(
+            ".github/workflows/*.yml",
+            Path::new(".github/workflows/ci.yml")
+        ));
+        assert!(glob_matches("./*.md", Path::new("README.md")));
+        assert!(!glob_matches("*.md", Path::new("docs/README.md")));
+        assert!(glob_matches("**/*.md", Path::new("docs/README.md")));
+        assert!(glob_matches("**/*.md", Path::new("README.md")));
+    }
+
+    #[test]
+    fn expand_glob_returns_paths_relative_to_root() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("docs/nested")).unwrap();
+        fs::write(dir.path().join("docs/a.md"), "").unwrap();
+        fs::write(dir.path().join("docs/nested/b.md"), "").unwrap();
+        fs::write(dir.path().join("docs/c.txt"), "").unwrap();
+
+        assert_eq!(
+            expand_glob(dir.path(), "docs/*.md").unwrap(),
+            vec![PathBuf::from("docs/a.md")]
+        );
+        assert_eq!(
+            expand_glob(dir.path(), "./docs/**/*.md").unwrap(),
+            vec![
+                PathBuf::from("docs/a.md"),
+                PathBuf::from("docs/nested/b.md")
+            ]
+        );
+        assert!(expand_glob(dir.path(), "docs/[").is_err());
+    }
+
+    #[test]
+    fn load_reads_file_from_root() {
+        let dir = TempDir::new().unwrap();
+        assert!(IgnoreFile::load(dir.path()).unwrap().is_empty());
+
+        fs::write(dir.path().join(IGNORE_FILE_NAME), "*.log\n").unwrap();
+        let ignore = IgnoreFile::load(dir.path()).unwrap();
+        assert!(ignore.is_ignored(Path::new("a/b.log"), false));
+    }
+}
@@ -2,15 +2,30 @@
 //!
 //! This is a CLI tool. There is no public library API.
 
+mod archive;
+mod auth;
 mod cache;
 mod cli;
+mod completions;
 mod config;
 mod detection;
+mod doctor;
+mod export;
+mod fetch;
+mod git_hooks;
 mod github;
+mod hash;
+mod ignore;
+mod logging;
+mod offline;
+mod output;
 mod overlay_repo;
+mod retry;
+mod secrets;
 mod selection;
 mod sources;
 mod state;
+mod template;
 #[cfg(test)]
 mod testutil;
 mod upstream;
@@ -24,22 +39,32 @@ pub fn run() -> anyhow::Result<()> {
 
 // Internal imports for use within the crate
 use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
 use colored::Colorize;
 use log::{debug, trace};
+use serde::Serialize;
 
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
-use cache::CacheManager;
+use archive::ArchiveSource;
+use cache::{CacheManager, CachePin};
+use config::{LinkStyle, SymlinkStyle};
+use fetch::{fetch_all, fetch_key};
 use github::GitHubSource;
+use hash::sha256_file;
+use output::{Progress, status};
 use overlay_repo::copy_dir_recursive;
 use state::{
-    CONFIG_FILE, EntryType, FileEntry, GIT_EXCLUDE, GlobalMeta, LinkType, MANAGED_SECTION_NAME,
-    META_FILE, OVERLAYS_DIR, OverlayConfig, OverlaySource, OverlayState, STATE_DIR,
-    exclude_marker_end, exclude_marker_start, list_applied_overlays, load_all_overlay_targets,
-    load_external_states, load_overlay_state, normalize_overlay_name, remove_external_state,
-    save_external_state, save_overlay_state,
+    BACKUPS_DIR, CONFIG_FILE, DirectoryMode, EntryType, FileEntry, GIT_EXCLUDE, GlobalMeta,
+    LinkType, MANAGED_SECTION_NAME, META_FILE, MovedFile, OVERLAYS_DIR, OverlayConfig,
+    OverlaySource, OverlayState, RefKind, STASH_DIR, STATE_DIR, conflict_key, exclude_marker_end,
+    exclude_marker_start, forget_external_state, list_applied_overlays, load_all_overlay_targets,
+    load_external_states, load_overlay_state, load_remembered_selection,
+    load_removed_external_states, normalize_overlay_name, remove_external_state,
+    save_external_state, save_overlay_state, save_remembered_selection, state_file_name,
 };
 use upstream::detect_upstream;
 
@@ -57,22 +82,88 @@ pub(crate) fn validate_git_repo(path: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Whether the filesystem holding a git repository ignores case, detected by
+/// looking up its `.git` as `.GIT`.
+pub(crate) fn is_case_insensitive_fs(repo: &Path) -> bool {
+    repo.join(".git").exists() && repo.join(".GIT").exists()
+}
+
+/// Record a target path of the overlay being applied, failing if it differs
+/// only in case from one recorded before, since on a case-insensitive
+/// filesystem both would be the same file.
+fn check_case_collision(
+    seen: &mut std::collections::HashMap<String, PathBuf>,
+    path: &Path,
+    case_insensitive: bool,
+) -> Result<()> {
+    if !case_insensitive {
+        return Ok(());
+    }
+    let key = conflict_key(&path.to_string_lossy(), true);
+    if let Some(other) = seen.get(&key)
+        && other != path
+    {
+        bail!(
+            "Conflict: '{}' and '{}' differ only in case and would be the same file \
+             on this case-insensitive filesystem",
+            other.display(),
+            path.display()
+        );
+    }
+    seen.insert(key, path.to_path_buf());
+    Ok(())
+}
+
 /// Resolved source information for applying an overlay.
 pub(crate) struct ResolvedSource {
     /// Local path to the overlay files
     pub path: PathBuf,
     /// Source information for state tracking
     pub source_info: OverlaySource,
+    /// For a source that points at one file, its name within `path`
+    pub file: Option<PathBuf>,
+}
+
+impl ResolvedSource {
+    /// Where to look for overlay files: the single file, or all of `path`.
+    fn walk_root(&self) -> PathBuf {
+        self.file
+            .as_ref()
+            .map_or_else(|| self.path.clone(), |file| self.path.join(file))
+    }
+
+    /// Load the overlay config for this source.
+    ///
+    /// A single file only takes `mappings` and `variables` from the config
+    /// next to it; the rest describes that directory as an overlay.
+    fn load_config(&self) -> Result<OverlayConfig> {
+        let config = load_overlay_config(&self.path)?;
+        if self.file.is_none() {
+            return Ok(config);
+        }
+        Ok(OverlayConfig {
+            mappings: config.mappings,
+            variables: config.variables,
+            ..OverlayConfig::default()
+        })
+    }
+
+    /// Overlay name used without `--name`: the config name, else the
+    /// directory name, or the file name for a single file.
+    fn default_name(&self, config: &OverlayConfig) -> String {
+        default_overlay_name(self.file.as_deref().unwrap_or(&self.path), config)
+    }
 }
 
 /// Resolve a source string to a local path.
 ///
 /// Resolution order:
-/// 1. GitHub URL (`https://github.com/...`) - downloads to cache, returns cached path
-/// 2. Local path (`./path` or `/path`) - returns path directly after validation
-/// 3. Overlay repo reference (`org/repo/name`) - resolves from configured shared repository
+/// 1. Archive URL (`https://.../*.tar.gz`, `.tgz`, `.zip`) - downloads and extracts to cache
+/// 2. GitHub URL (`https://github.com/...`) - downloads to cache, returns cached path
+/// 3. Local path (`./path`, `/path`, `~/path`, or `$VAR/path`) - returns the canonical path
+/// 4. Overlay repo reference (`org/repo/name`) - resolves from configured shared repository
 ///    - If `sources` are configured, checks each source in priority order
-///    - If `source_filter` is provided, only that source is checked
+///    - If `source_filter` or a `source:` prefix is given, only that source is checked
 ///    - Falls back to legacy `overlay_repo` if no sources configured
 ///    - Falls back to upstream if `target_path` has an upstream remote
 ///
@@ -81,7 +172,7 @@ pub(crate) struct ResolvedSource {
 /// Returns an error if:
 /// - The source doesn't match any valid format
 /// - A local path doesn't exist
-/// - GitHub fetch fails
+/// - GitHub fetch or archive download fails
 /// - Overlay repo is not configured (for org/repo/name format)
 /// - `source_filter` specifies an unknown source
 pub(crate) fn resolve_source(
@@ -95,20 +186,45 @@ pub(crate) fn resolve_source(
         "resolve_source: {source_str} (ref_override={ref_override:?}, update={update}, source_filter={source_filter:?})"
     );
 
+    // Try to parse as archive URL (checked first since archives may be hosted on GitHub)
+    if ArchiveSource::is_archive_url(source_str) {
+        debug!("detected archive URL");
+        let archive_source = ArchiveSource::parse(source_str)?;
+        let cache = CacheManager::new()?;
+
+        status!(
+            "{} archive: {}",
+            if update { "Updating" } else { "Fetching" }.blue().bold(),
+            archive_source.url
+        );
+
+        let cached = cache.ensure_archive_cached(&archive_source, update)?;
+
+        return Ok(ResolvedSource {
+            path: cached.path,
+            file: None,
+            source_info: OverlaySource::archive(archive_source.url, cached.sha256),
+        });
+    }
+
     // Try to parse as GitHub URL
     if GitHubSource::is_github_url(source_str) {
         debug!("detected GitHub URL");
         let mut github_source = GitHubSource::parse(source_str)?;
 
-        // Apply ref override if provided
+        let cache = CacheManager::new()?;
+
+        // Apply ref override if provided; otherwise work out whether a
+        // `/tree/` URL names a ref containing slashes
         if let Some(ref_str) = ref_override {
             github_source = github_source.with_ref_override(Some(ref_str));
+        } else {
+            github_source = cache.resolve_tree_ref(github_source);
         }
 
         // Ensure cached and get path
-        let cache = CacheManager::new()?;
 
-        println!(
+        status!(
             "{} repository: {}/{}",
             if update { "Updating" } else { "Fetching" }.blue().bold(),
             github_source.owner,
@@ -117,8 +233,31 @@ pub(crate) fn resolve_source(
 
         let cached = cache.ensure_cached(&github_source, update)?;
 
+        // A single file is applied from its directory so entries stay
+        // relative to an overlay root like any other source
+        let (path, file) = if github_source.single_file {
+            if !cached.path.is_file() {
+                bail!(
+                    "'{}' is not a file in {}/{}",
+                    cached.path.display(),
+                    github_source.owner,
+                    github_source.repo
+                );
+            }
+            let file = cached.path.file_name().map(PathBuf::from);
+            let dir = cached
+                .path
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default();
+            (dir, file)
+        } else {
+            (cached.path, None)
+        };
+
         return Ok(ResolvedSource {
-            path: cached.path,
+            path,
+            file,
             source_info: OverlaySource::github(
                 source_str.to_string(),
                 github_source.owner,
@@ -128,12 +267,13 @@ pub(crate) fn resolve_source(
                 github_source
                     .subpath
                     .map(|p| p.to_string_lossy().to_string()),
-            ),
+            )
+            .with_ref_kind(cached.ref_kind),
         });
     }
 
-    // Try to parse as local path first
-    let path = PathBuf::from(source_str);
+    // Try to parse as local path first, expanding `~` and `$VAR` as a shell would
+    let path = PathBuf::from(expand_path(source_str));
     if path.exists() {
         debug!("resolved as local path: {}", path.display());
         let canonical = path
@@ -142,13 +282,23 @@ pub(crate) fn resolve_source(
 
         return Ok(ResolvedSource {
             path: canonical.clone(),
+            file: None,
             source_info: OverlaySource::local(canonical),
         });
     }
 
-    // Try to parse as overlay repo reference (org/repo/name)
-    if let Some((org, repo, name)) = overlay_repo::parse_overlay_reference(source_str) {
-        debug!("parsed as overlay repo reference: {org}/{repo}/{name}");
+    // Try to parse as overlay repo reference ([source:]org/repo/name)
+    let (source_prefix, reference) = overlay_repo::split_source_prefix(source_str);
+    if let Some((org, repo, name)) = overlay_repo::parse_overlay_reference(reference) {
+        debug!("parsed as overlay repo reference: {org}/{repo}/{name} (source={source_prefix:?})");
+        // `source:` in the reference is the same as `--from source`
+        let source_filter = match (source_prefix, source_filter) {
+            (Some(prefix), Some(filter)) if prefix != filter => {
+                bail!("'{source_str}' names source '{prefix}' but --from names '{filter}'")
+            }
+            (prefix, filter) => prefix.or(filter),
+        };
+
         // Load config
         let config = config::load_config(None)?;
 
@@ -173,20 +323,19 @@ pub(crate) fn resolve_source(
         }
 
         // Fall back to legacy overlay_repo config
-        let overlay_config = config.overlay_repo.ok_or_else(|| {
-            anyhow::anyhow!(
-                "Overlay repository not configured.\n\n\
-                 To apply overlays from a shared repository, first run:\n\
-                 repoverlay source add <url>\n\n\
-                 Or use a local path or GitHub URL instead."
-            )
-        })?;
-
-        let manager = overlay_repo::OverlayRepoManager::new(overlay_config)?;
+        let manager = overlay_repo::OverlayRepoManager::configured(&config, source_filter)?
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Overlay repository not configured.\n\n\
+                     To apply overlays from a shared repository, first run:\n\
+                     repoverlay source add <url>\n\n\
+                     Or use a local path or GitHub URL instead."
+                )
+            })?;
         manager.ensure_cloned()?;
 
         if update {
-            println!("{} overlay repository...", "Updating".blue().bold());
+            status!("{} overlay repository...", "Updating".blue().bold());
             manager.pull()?;
         }
 
@@ -208,7 +357,7 @@ pub(crate) fn resolve_source(
         } else {
             String::new()
         };
-        println!(
+        status!(
             "{} overlay: {}/{}/{}{}",
             "Resolving".blue().bold(),
             actual_org,
@@ -219,6 +368,7 @@ pub(crate) fn resolve_source(
 
         return Ok(ResolvedSource {
             path: overlay_path,
+            file: None,
             source_info: OverlaySource::overlay_repo_with_resolution(
                 actual_org,
                 actual_repo,
@@ -229,16 +379,152 @@ pub(crate) fn resolve_source(
         });
     }
 
+    // A path starting with `./` or `../`, or with an existing directory as
+    // its first segment, is a mistyped local path rather than `owner/repo`
+    if looks_like_local_path(source_str) {
+        bail!("Local path not found: {}", path.display());
+    }
+
+    // `owner/repo` is shorthand for the root of a GitHub repository, unless
+    // the overlay repository has overlays for it
+    if let Some((owner, repo)) = github::parse_shorthand(source_str) {
+        let overlays = overlay_repo_overlays_for(&owner, &repo)?;
+        if !overlays.is_empty() {
+            bail!(
+                "'{source_str}' is ambiguous: the overlay repository has overlays for it ({}).\n\n\
+                 To apply one of them: repoverlay apply {source_str}/<name>\n\
+                 To apply the GitHub repository itself: repoverlay apply https://github.com/{owner}/{repo}",
+                overlays.join(", ")
+            );
+        }
+        debug!("treating {source_str} as GitHub shorthand");
+        return resolve_source(
+            &format!("https://github.com/{owner}/{repo}"),
+            ref_override,
+            update,
+            target_path,
+            source_filter,
+        );
+    }
+
     // Nothing matched
     bail!(
         "Overlay source not found: {source_str}\n\n\
-         Valid formats:\n\
-         - Local path: ./my-overlay\n\
-         - GitHub URL: https://github.com/owner/repo\n\
-         - Overlay repo: org/repo/name"
+         Sources are recognized in this order:\n\
+         1. Archive URL: https://example.com/overlay.tar.gz\n\
+         2. GitHub URL: https://github.com/owner/repo\n\
+         3. Local path: ./my-overlay (if it exists)\n\
+         4. Overlay repo: org/repo/name or source:org/repo/name\n\
+         5. GitHub shorthand: owner/repo (the repository root)"
     )
 }
 
+/// Whether a source that didn't resolve to anything was meant as a local path:
+/// it starts with `./` or `../`, or its first segment is an existing directory.
+fn looks_like_local_path(source_str: &str) -> bool {
+    source_str.starts_with("./")
+        || source_str.starts_with("../")
+        || source_str
+            .split_once('/')
+            .is_some_and(|(first, _)| !first.is_empty() && Path::new(first).is_dir())
+}
+
+/// Expand a leading `~` and `$VAR`/`${VAR}` tokens in a local path.
+///
+/// Unset variables are left as written, so the error names what was typed.
+fn expand_path(input: &str) -> String {
+    expand_path_with(input, dirs::home_dir().as_deref(), |name| {
+        std::env::var(name).ok()
+    })
+}
+
+/// [`expand_path`] with the home directory and variable lookup supplied.
+fn expand_path_with(
+    input: &str,
+    home: Option<&Path>,
+    var: impl Fn(&str) -> Option<String>,
+) -> String {
+    let mut rest = input;
+    let mut out = String::new();
+    if let Some(home) = home
+        && let Some(after) = rest.strip_prefix('~')
+        && (after.is_empty() || after.starts_with('/') || after.starts_with('\\'))
+    {
+        out.push_str(&home.to_string_lossy());
+        rest = after;
+    }
+
+    while let Some(start) = rest.find('$') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let (name, len) = variable_token(after);
+        match (!name.is_empty()).then(|| var(name)).flatten() {
+            Some(value) => out.push_str(&value),
+            None => out.push_str(&rest[start..=start + len]),
+        }
+        rest = &after[len..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// The variable name after a `$`, and how many bytes the token spans.
+/// The name is empty if there isn't a valid one.
+fn variable_token(after: &str) -> (&str, usize) {
+    after.strip_prefix('{').map_or_else(
+        || {
+            let end = after
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(after.len());
+            (&after[..end], end)
+        },
+        |braced| {
+            braced
+                .find('}')
+                .map_or(("", 0), |end| (&braced[..end], end + 2))
+        },
+    )
+}
+
+/// Rewrite a GitHub source (a URL or `owner/repo` shorthand) to apply
+/// `subpath` inside the repository, for `--subpath`.
+///
+/// The result is a `/tree/` URL, so the subpath is recorded with the source
+/// and kept by `update` and `restore`.
+///
+/// # Errors
+///
+/// Returns an error if the source is not a GitHub repository.
+pub(crate) fn source_with_subpath(source_str: &str, subpath: &str) -> Result<String> {
+    let url = if GitHubSource::is_github_url(source_str) {
+        source_str.to_string()
+    } else if let Some((owner, repo)) = github::parse_shorthand(source_str) {
+        format!("https://github.com/{owner}/{repo}")
+    } else {
+        bail!("--subpath only applies to GitHub sources, not '{source_str}'");
+    };
+    Ok(GitHubSource::parse(&url)?
+        .with_subpath(subpath)
+        .display_url())
+}
+
+/// Names of the overlays for `org/repo` in the highest-priority overlay source,
+/// if one is configured and cloned.
+fn overlay_repo_overlays_for(org: &str, repo: &str) -> Result<Vec<String>> {
+    let config = config::load_config(None)?;
+    let Some(manager) = overlay_repo::OverlayRepoManager::configured(&config, None)? else {
+        return Ok(Vec::new());
+    };
+    if manager.needs_clone() {
+        return Ok(Vec::new());
+    }
+    Ok(manager
+        .list_overlays_for_repo(org, repo)?
+        .into_iter()
+        .map(|overlay| overlay.name)
+        .collect())
+}
+
 /// Resolve an overlay from configured sources using priority-based resolution.
 fn resolve_from_sources(
     sources: &[config::Source],
@@ -255,7 +541,7 @@ fn resolve_from_sources(
     manager.ensure_all_cloned()?;
 
     if update {
-        println!("{} overlay sources...", "Updating".blue().bold());
+        status!("{} overlay sources...", "Updating".blue().bold());
         manager.pull_all()?;
     }
 
@@ -285,7 +571,7 @@ fn resolve_from_sources(
     };
     let source_suffix = format!(" [{}]", resolved.source.name).cyan().to_string();
 
-    println!(
+    status!(
         "{} overlay: {}/{}/{}{}{}",
         "Resolving".blue().bold(),
         actual_org,
@@ -297,6 +583,7 @@ fn resolve_from_sources(
 
     Ok(ResolvedSource {
         path: resolved.path,
+        file: None,
         source_info: OverlaySource::overlay_repo_full(
             actual_org,
             actual_repo,
@@ -308,6 +595,293 @@ fn resolve_from_sources(
     })
 }
 
+/// How `apply` handles overlay files that collide with existing repo files.
+///
+/// Collisions with files managed by another overlay always fail.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub(crate) enum OnConflict {
+    /// Abort the apply
+    #[default]
+    Fail,
+    /// Leave the repo file in place and don't apply that overlay file
+    Skip,
+    /// Move the repo file to `.repoverlay/backups/<name>/` and restore it on remove
+    Backup,
+    /// Delete the repo file
+    Overwrite,
+}
+
+/// Outcome of resolving a conflict with an existing repo file.
+enum Resolution {
+    /// The overlay entry should not be applied
+    Skipped,
+    /// The repo file was moved aside (recording its backup location, if any)
+    Cleared { backup: Option<PathBuf> },
+}
+
+/// Entries created so far by an apply in progress.
+///
+/// Unless [`PartialApply::finish`] is called, dropping it removes those
+/// entries again and puts back any repo files they displaced, so an apply
+/// that fails partway leaves the repo as it found it.
+struct PartialApply<'a> {
+    target: &'a Path,
+    /// Target paths, with the backup of the repo file each replaced
+    entries: Vec<(PathBuf, Option<PathBuf>)>,
+    finished: bool,
+}
+
+impl<'a> PartialApply<'a> {
+    const fn new(target: &'a Path) -> Self {
+        Self {
+            target,
+            entries: Vec::new(),
+            finished: false,
+        }
+    }
+
+    /// Record an entry about to be created at `rel_path`.
+    fn record(&mut self, rel_path: &Path, backup: Option<&PathBuf>) {
+        self.entries.push((rel_path.to_path_buf(), backup.cloned()));
+    }
+
+    /// Keep everything created; the apply succeeded.
+    const fn finish(&mut self) {
+        self.finished = true;
+    }
+
+    fn undo(&self, rel_path: &Path, backup: Option<&PathBuf>) -> Result<()> {
+        let path = self.target.join(rel_path);
+        if path.is_symlink() || path.is_file() {
+            fs::remove_file(&path)?;
+        } else if path.is_dir() {
+            fs::remove_dir_all(&path)?;
+        }
+        remove_empty_parents(self.target, &path);
+        if let Some(backup) = backup {
+            restore_backup(self.target, backup, rel_path)?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for PartialApply<'_> {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        for (rel_path, backup) in self.entries.iter().rev() {
+            if let Err(e) = self.undo(rel_path, backup.as_ref()) {
+                eprintln!(
+                    "  {} Could not undo {}: {:#}",
+                    "Warning:".yellow(),
+                    rel_path.display(),
+                    e
+                );
+            }
+        }
+    }
+}
+
+/// Repo files affected by conflict handling during a single apply.
+#[derive(Default)]
+struct ConflictSummary {
+    skipped: Vec<PathBuf>,
+    backed_up: Vec<PathBuf>,
+    overwritten: Vec<PathBuf>,
+}
+
+impl ConflictSummary {
+    /// Resolve a conflict at `rel_path` according to `on_conflict`.
+    ///
+    /// On success the target path is either left alone ([`Resolution::Skipped`])
+    /// or cleared so the overlay entry can be created.
+    fn resolve(
+        &mut self,
+        on_conflict: OnConflict,
+        target: &Path,
+        overlay_name: &str,
+        rel_path: &Path,
+    ) -> Result<Resolution> {
+        match on_conflict {
+            OnConflict::Fail => bail!(
+                "Conflict: target path already exists: {}\n\
+                 Remove it first, add a mapping to rename the overlay file, \
+                 or use --on-conflict to skip, back up, or overwrite it.",
+                target.join(rel_path).display()
+            ),
+            OnConflict::Skip => {
+                self.skipped.push(rel_path.to_path_buf());
+                Ok(Resolution::Skipped)
+            }
+            OnConflict::Backup => {
+                let backup = backup_conflicting_path(target, overlay_name, rel_path)?;
+                self.backed_up.push(rel_path.to_path_buf());
+                Ok(Resolution::Cleared {
+                    backup: Some(backup),
+                })
+            }
+            OnConflict::Overwrite => {
+                let path = target.join(rel_path);
+                if path.is_dir() && !path.is_symlink() {
+                    fs::remove_dir_all(&path)
+                } else {
+                    fs::remove_file(&path)
+                }
+                .with_context(|| format!("Failed to remove: {}", path.display()))?;
+                self.overwritten.push(rel_path.to_path_buf());
+                Ok(Resolution::Cleared { backup: None })
+            }
+        }
+    }
+
+    fn print(&self) {
+        for (label, paths) in [
+            ("Skipped", &self.skipped),
+            ("Backed up", &self.backed_up),
+            ("Overwrote", &self.overwritten),
+        ] {
+            if paths.is_empty() {
+                continue;
+            }
+            let list: Vec<String> = paths.iter().map(|p| p.display().to_string()).collect();
+            status!(
+                "  {} {} existing file(s): {}",
+                label.yellow(),
+                paths.len(),
+                list.join(", ")
+            );
+        }
+    }
+}
+
+/// What `apply --dry-run` found, reported per file instead of failing on the
+/// first conflict.
+#[derive(Default)]
+struct Preview {
+    files: usize,
+    conflicts: usize,
+}
+
+impl Preview {
+    /// Report a path already managed by another overlay.
+    fn overlay_conflict(&mut self, progress: &Progress, path: &str, overlay: &str) {
+        self.conflicts += 1;
+        progress.item(format!(
+            "  {} {} {}",
+            "✗".red(),
+            path,
+            format!("(managed by overlay '{overlay}')").red()
+        ));
+    }
+
+    /// Report what would happen to a single file or directory.
+    fn entry(
+        &mut self,
+        progress: &Progress,
+        path: &str,
+        linked: bool,
+        exists: bool,
+        on_conflict: OnConflict,
+    ) {
+        let note = match (linked, exists, on_conflict) {
+            (true, _, _) => "(already linked)",
+            (false, false, _) => "",
+            (false, true, OnConflict::Fail) => {
+                self.conflicts += 1;
+                progress.item(format!(
+                    "  {} {} {}",
+                    "✗".red(),
+                    path,
+                    "(exists in repo)".red()
+                ));
+                return;
+            }
+            (false, true, OnConflict::Skip) => {
+                progress.item(format!(
+                    "  {} {} {}",
+                    "-".yellow(),
+                    path,
+                    "(would skip, exists in repo)".dimmed()
+                ));
+                return;
+            }
+            (false, true, OnConflict::Backup) => "(would back up existing file)",
+            (false, true, OnConflict::Overwrite) => "(would overwrite existing file)",
+        };
+        self.files += 1;
+        if note.is_empty() {
+            progress.item(format!("  {} {}", "+".green(), path));
+        } else {
+            progress.item(format!("  {} {} {}", "+".green(), path, note.dimmed()));
+        }
+    }
+
+    /// Print the summary, failing if apply would fail.
+    fn finish(self) -> Result<()> {
+        status!("\n{} Dry run - no changes made.", "Note:".yellow());
+        if self.conflicts > 0 {
+            bail!(
+                "{} conflict(s) found; apply would fail. Use --on-conflict to skip, back up, or overwrite repo files.",
+                self.conflicts
+            );
+        }
+        status!("  {} file(s) would be applied", self.files);
+        Ok(())
+    }
+}
+
+/// Load an overlay source's `repoverlay.ccl`, or the defaults if it has none.
+fn load_overlay_config(source: &Path) -> Result<OverlayConfig> {
+    let config_path = source.join(CONFIG_FILE);
+    if !config_path.exists() {
+        return Ok(OverlayConfig::default());
+    }
+    let content = fs::read_to_string(&config_path)
+        .with_context(|| format!("Failed to read config: {}", config_path.display()))?;
+    sickle::from_str(&content)
+        .with_context(|| format!("Failed to parse config: {}", config_path.display()))
+}
+
+/// How [`apply_overlay`] applies an overlay.
+#[derive(Debug, Clone, Default)]
+#[allow(clippy::struct_excessive_bools)]
+pub(crate) struct ApplyOptions<'a> {
+    /// Copy files instead of symlinking them
+    pub force_copy: bool,
+    /// Name to apply the overlay under, instead of the config's or the
+    /// source directory's
+    pub name: Option<String>,
+    /// Git ref of a GitHub source, overriding the one in the URL
+    pub git_ref: Option<&'a str>,
+    /// Fetch the latest version of a cached source
+    pub update_cache: bool,
+    /// Overlay source to resolve overlay repo references from
+    pub source_filter: Option<&'a str>,
+    /// List what would happen without changing anything
+    pub dry_run: bool,
+    /// How to handle overlay files that collide with repo files
+    pub on_conflict: OnConflict,
+    /// List every file as it is applied
+    pub verbose: bool,
+    /// Template variables, overriding the config's `variables`
+    pub variables: BTreeMap<String, String>,
+    /// Only apply these files (glob patterns); empty applies everything
+    pub only: &'a [String],
+    /// Don't apply the overlays listed in the config's `requires`
+    pub no_deps: bool,
+    /// Run the config's `post_apply` hook
+    pub run_hooks: bool,
+    /// Place every file under this directory of the target
+    pub prefix: Option<&'a Path>,
+    /// Files moved with `mv`, which are placed at their moved-to path
+    pub moved: &'a [MovedFile],
+    /// Target paths whose conflicts are skipped whatever `on_conflict` is
+    pub skip: &'a [PathBuf],
+    /// Target paths unlinked from the overlay, which are not applied
+    pub unlinked: &'a [PathBuf],
+}
+
 /// Apply an overlay to a target git repository.
 ///
 /// # Workflow
@@ -316,11 +890,19 @@ fn resolve_from_sources(
 /// 2. Validate target is a git repository
 /// 3. Load overlay config (`repoverlay.ccl`) if present
 /// 4. Determine overlay name (CLI override > config > directory name)
-/// 5. Check for conflicts with existing overlays and files
-/// 6. Create symlinks or copies for each file
-/// 7. Update `.git/info/exclude` with overlay section
-/// 8. Save state to `.repoverlay/overlays/<name>.ccl`
-/// 9. Save external backup for restore capability
+/// 5. Apply the overlays listed in the config's `requires` that aren't
+///    applied yet, unless [`ApplyOptions::no_deps`] is set
+/// 6. Check for conflicts with existing overlays and files (repo files are
+///    handled according to `on_conflict`)
+/// 7. Create symlinks or copies for each file, rendering templates with
+///    the config's `variables` and the `variables` overrides
+/// 8. Update `.git/info/exclude` with overlay section
+/// 9. Save state to `.repoverlay/overlays/<name>.ccl`
+/// 10. Save external backup for restore capability
+/// 11. Run the config's `post_apply` hook, if `run_hooks` is set
+///
+/// With [`ApplyOptions::dry_run`], steps 7-11 are skipped: each file is listed with what would
+/// happen to it, and all conflicts are reported before failing.
 ///
 /// # Errors
 ///
@@ -328,26 +910,49 @@ fn resolve_from_sources(
 /// - Source resolution fails
 /// - Target is not a git repository
 /// - Overlay with same name already exists
-/// - File conflicts with existing overlay or repo file
+/// - File conflicts with existing overlay, or with a repo file and `on_conflict` is `Fail`
 /// - No files found in overlay source
-#[allow(clippy::too_many_arguments)]
-pub(crate) fn apply_overlay(
+/// - A required overlay fails to apply, or requirements form a cycle
+/// - The `post_apply` hook fails (the overlay stays applied)
+pub(crate) fn apply_overlay(source_str: &str, target: &Path, options: &ApplyOptions) -> Result<()> {
+    let mut requirement_chain = (!options.no_deps).then(Vec::new);
+    apply_overlay_with_requirements(source_str, target, options, requirement_chain.as_mut())
+}
+
+/// [`apply_overlay`], tracking the overlays being applied for `requires` so
+/// that cycles are detected. `None` skips requirements entirely.
+fn apply_overlay_with_requirements(
     source_str: &str,
     target: &Path,
-    force_copy: bool,
-    name_override: Option<String>,
-    ref_override: Option<&str>,
-    update_cache: bool,
-    source_filter: Option<&str>,
-    dry_run: bool,
+    options: &ApplyOptions,
+    requirement_chain: Option<&mut Vec<String>>,
 ) -> Result<()> {
+    let ApplyOptions {
+        force_copy,
+        git_ref: ref_override,
+        update_cache,
+        source_filter,
+        dry_run,
+        on_conflict,
+        verbose,
+        ref variables,
+        only,
+        run_hooks,
+        prefix,
+        moved,
+        skip,
+        unlinked,
+        ..
+    } = *options;
+    let name_override = options.name.clone();
     debug!(
-        "apply_overlay: source={}, target={}, force_copy={}, name_override={:?}, dry_run={}",
+        "apply_overlay: source={}, target={}, force_copy={}, name_override={:?}, dry_run={}, on_conflict={:?}",
         source_str,
         target.display(),
         force_copy,
         name_override,
-        dry_run
+        dry_run,
+        on_conflict
     );
 
     // Resolve source (handles GitHub URLs and local paths)
@@ -360,11 +965,6 @@ pub(crate) fn apply_overlay(
         source_filter,
     )?;
 
-    if dry_run {
-        println!("{} Dry run - no changes made.", "Note:".yellow());
-        println!("\nWould apply overlay from: {}", resolved.path.display());
-        return Ok(());
-    }
     let source = &resolved.path;
     debug!("resolved source path: {}", source.display());
 
@@ -372,59 +972,144 @@ pub(crate) fn apply_overlay(
     let target = canonicalize_path(target, "Target directory")?;
     validate_git_repo(&target)?;
 
-    // Determine link type
-    let link_type = if force_copy || cfg!(windows) {
-        LinkType::Copy
-    } else {
-        LinkType::Symlink
+    let link_type = resolve_link_type(force_copy)?;
+
+    // Everything is placed under `--prefix` when given
+    let prefix = prefix
+        .map(normalize_prefix)
+        .transpose()?
+        .filter(|p| !p.as_os_str().is_empty());
+    // Conflicts skipped by an earlier apply stay skipped
+    let policy = |rel: &Path| {
+        if skip.iter().any(|p| p == rel) {
+            OnConflict::Skip
+        } else {
+            on_conflict
+        }
     };
-
-    // Load overlay config (optional)
-    let config_path = source.join(CONFIG_FILE);
-    let config: OverlayConfig = if config_path.exists() {
-        let content = fs::read_to_string(&config_path)
-            .with_context(|| format!("Failed to read config: {}", config_path.display()))?;
-        sickle::from_str(&content)
-            .with_context(|| format!("Failed to parse config: {}", config_path.display()))?
-    } else {
-        OverlayConfig::default()
+    // Files moved with `mv` stay where they were moved to
+    let place = |source_rel: &Path, rel: &Path| {
+        moved.iter().find(|m| m.source == source_rel).map_or_else(
+            || {
+                prefix
+                    .as_ref()
+                    .map_or_else(|| rel.to_path_buf(), |p| p.join(rel))
+            },
+            |m| m.target.clone(),
+        )
     };
 
+    let relative_links = link_type == LinkType::Symlink
+        && use_relative_symlinks(&resolved.source_info, source, &target)?;
+
+    let config = resolved.load_config()?;
+    let walk_root = resolved.walk_root();
+
     // Determine overlay name (priority: CLI override > config > directory name)
-    let overlay_name = name_override
-        .or_else(|| config.overlay.name.clone())
-        .unwrap_or_else(|| {
-            source.file_name().map_or_else(
-                || "unnamed".to_string(),
-                |n| n.to_string_lossy().to_string(),
-            )
-        });
+    let overlay_name = name_override.unwrap_or_else(|| resolved.default_name(&config));
     let normalized_name = normalize_overlay_name(&overlay_name)?;
 
+    if let Some(chain) = &requirement_chain
+        && chain.contains(&normalized_name)
+    {
+        bail!(
+            "Overlay requirements form a cycle: {} -> {}",
+            chain.join(" -> "),
+            normalized_name
+        );
+    }
+
     // Check if this specific overlay already exists
     let overlays_dir = target.join(STATE_DIR).join(OVERLAYS_DIR);
-    let overlay_state_path = overlays_dir.join(format!("{normalized_name}.ccl"));
+    let overlay_state_path = overlays_dir.join(state_file_name(&normalized_name));
     if overlay_state_path.exists() {
         bail!(
             "Overlay '{overlay_name}' is already applied. Run 'repoverlay remove {normalized_name}' first."
         );
     }
 
-    // Load all existing overlay targets to check for conflicts
-    let existing_targets = load_all_overlay_targets(&target)?;
+    // Required overlays go first so their files count as existing targets
+    let mut requires = Vec::new();
+    if let Some(chain) = requirement_chain {
+        chain.push(normalized_name.clone());
+        for requirement in &config.requires {
+            let requirement_options = ApplyOptions {
+                name: None,
+                git_ref: None,
+                source_filter: None,
+                only: &[],
+                prefix: prefix.as_deref(),
+                moved: &[],
+                skip: &[],
+                unlinked: &[],
+                ..options.clone()
+            };
+            let name = apply_requirement(requirement, &target, &requirement_options, chain)
+                .with_context(|| {
+                    format!("Failed to apply '{requirement}', required by '{overlay_name}'")
+                })?;
+            requires.push(name);
+        }
+        chain.pop();
+    }
+
+    // Load all existing overlay targets to check for conflicts; on a
+    // case-insensitive filesystem, paths that differ only in case collide
+    let case_insensitive = is_case_insensitive_fs(&target);
+    let existing_targets = load_all_overlay_targets(&target, case_insensitive)?;
+    let mut own_targets = std::collections::HashMap::new();
 
-    println!("{} overlay: {}", "Applying".green().bold(), overlay_name);
+    if dry_run {
+        status!(
+            "{} Would apply overlay: {}",
+            "Dry run:".yellow().bold(),
+            overlay_name
+        );
+    } else {
+        status!("{} overlay: {}", "Applying".green().bold(), overlay_name);
+    }
 
     // Collect files to overlay and build state
     let mut state = OverlayState::new(overlay_name.clone(), resolved.source_info);
+    state.description.clone_from(&config.overlay.description);
+    state.tags.clone_from(&config.overlay.tags);
+    state.variables.clone_from(variables);
+    state.only = only.to_vec();
+    state.prefix.clone_from(&prefix);
+    state.moved = moved.to_vec();
+    state.unlinked = unlinked.to_vec();
+    state.requires = requires;
+    state.hooks.clone_from(&config.hooks);
+    let template_vars = template::merge_variables(&config.variables, variables);
     let mut exclude_entries: Vec<String> = Vec::new();
+    let mut conflicts = ConflictSummary::default();
+    let mut preview = Preview::default();
+    let mut only_matched = false;
+    let mut partial = PartialApply::new(&target);
+
+    // Build set of directories to symlink as units; merged directories are
+    // walked file by file like the rest of the overlay
+    let dir_set: std::collections::HashSet<PathBuf> = config
+        .directories
+        .iter()
+        .filter(|d| d.mode == DirectoryMode::Unit)
+        .map(|d| PathBuf::from(&d.path))
+        .collect();
+
+    // Symlinks inside the source are not followed, so links back into the
+    // source tree can't make the walk loop
+    let source_files: Vec<walkdir::DirEntry> = WalkDir::new(walk_root)
+        .into_iter()
+        .filter_map(std::result::Result::ok)
+        .filter(|e| e.file_type().is_file() || (e.depth() > 0 && e.path_is_symlink()))
+        .collect();
 
-    // Build set of directories to symlink as units
-    let dir_set: std::collections::HashSet<PathBuf> =
-        config.directories.iter().map(PathBuf::from).collect();
+    // Large overlays get a progress bar instead of one line per file
+    let progress = Progress::new(dir_set.len() + source_files.len(), verbose || dry_run);
 
     // Process directories first (symlink as units)
-    for dir_name in &config.directories {
+    for dir in &config.directories {
+        let dir_name = &dir.path;
         let dir_path = PathBuf::from(dir_name);
         let source_dir = source.join(&dir_path);
 
@@ -447,28 +1132,71 @@ pub(crate) fn apply_overlay(
             continue;
         }
 
+        if dir.mode == DirectoryMode::Merge || !only_includes(only, &[&dir_path]) {
+            continue;
+        }
+        only_matched = true;
+        let dir_target = place(&dir_path, &dir_path);
+        if unlinked.contains(&dir_target) {
+            continue;
+        }
+
         // Check for conflicts with existing overlays
-        let dir_rel_str = dir_path.to_string_lossy().to_string();
-        if let Some(conflicting_overlay) = existing_targets.get(&dir_rel_str) {
+        let dir_rel_str = dir_target.to_string_lossy().to_string();
+        let dir_display = format!("{}/", dir_target.display());
+        if let Some(conflicting_overlay) =
+            existing_targets.get(&conflict_key(&dir_rel_str, case_insensitive))
+        {
+            if dry_run {
+                preview.overlay_conflict(&progress, &dir_display, conflicting_overlay);
+                continue;
+            }
             bail!(
                 "Conflict: directory '{}' is already managed by overlay '{}'\n\
                  Remove that overlay first or use different file mappings.",
-                dir_path.display(),
+                dir_target.display(),
                 conflicting_overlay
             );
         }
+        check_case_collision(&mut own_targets, &dir_target, case_insensitive)?;
 
-        let target_dir = target.join(&dir_path);
+        let target_dir = target.join(&dir_target);
 
-        // Check for conflicts with existing files/dirs in repo
-        if target_dir.exists() {
-            bail!(
-                "Conflict: target path already exists: {}\n\
-                 Remove it first to apply the overlay.",
-                target_dir.display()
+        // A symlink left by an earlier, interrupted apply is reused as-is
+        let linked = link_type == LinkType::Symlink && already_linked(&target_dir, &source_dir);
+
+        if dry_run {
+            preview.entry(
+                &progress,
+                &dir_display,
+                linked,
+                target_dir.exists(),
+                policy(&dir_target),
             );
+            continue;
         }
 
+        // Check for conflicts with existing files/dirs in repo
+        let backup = if linked {
+            None
+        } else if target_dir.exists() {
+            match conflicts.resolve(policy(&dir_target), &target, &normalized_name, &dir_target)? {
+                Resolution::Cleared { backup } => backup,
+                Resolution::Skipped => {
+                    progress.item(format!(
+                        "  {} {}/ {}",
+                        "-".yellow(),
+                        dir_target.display(),
+                        "(skipped, exists in repo)".dimmed()
+                    ));
+                    continue;
+                }
+            }
+        } else {
+            None
+        };
+        partial.record(&dir_target, backup.as_ref());
+
         // Create parent directories if needed
         if let Some(parent) = target_dir.parent() {
             fs::create_dir_all(parent)
@@ -477,7 +1205,13 @@ pub(crate) fn apply_overlay(
 
         // Create directory symlink or copy
         match link_type {
+            LinkType::Symlink if linked => {}
             LinkType::Symlink => {
+                let source_dir = if relative_links {
+                    relative_symlink_target(&source_dir, &target_dir)
+                } else {
+                    source_dir
+                };
                 #[cfg(unix)]
                 std::os::unix::fs::symlink(&source_dir, &target_dir).with_context(|| {
                     format!(
@@ -504,25 +1238,40 @@ pub(crate) fn apply_overlay(
             }
         }
 
-        println!("  {} {}/", "+".green(), dir_path.display());
+        if backup.is_some() {
+            progress.item(format!(
+                "  {} {}/ {}",
+                "+".green(),
+                dir_target.display(),
+                "(original backed up)".dimmed()
+            ));
+        } else if linked {
+            progress.item(format!(
+                "  {} {}/ {}",
+                "+".green(),
+                dir_target.display(),
+                "(already linked)".dimmed()
+            ));
+        } else {
+            progress.item(format!("  {} {}/", "+".green(), dir_target.display()));
+        }
 
         state.add_file(FileEntry {
             source: dir_path.clone(),
-            target: dir_path.clone(),
+            target: dir_target.clone(),
             link_type,
             entry_type: EntryType::Directory,
+            backup,
+            sha256: None,
+            rendered: false,
         });
 
         // Add to exclude list with trailing slash for directories
-        let exclude_path = format!("{}/", dir_path.to_string_lossy().replace('\\', "/"));
+        let exclude_path = format!("{}/", dir_target.to_string_lossy().replace('\\', "/"));
         exclude_entries.push(exclude_path);
     }
 
-    for entry in WalkDir::new(source)
-        .into_iter()
-        .filter_map(std::result::Result::ok)
-        .filter(|e| e.file_type().is_file())
-    {
+    for entry in &source_files {
         let rel_path = entry.path().strip_prefix(source)?;
 
         // Skip the config file
@@ -552,13 +1301,47 @@ pub(crate) fn apply_overlay(
             continue;
         }
 
+        // Symlinks in the source are recreated as-is rather than followed
+        let source_link =
+            if entry.path_is_symlink() {
+                if link_loops_back(entry.path()) {
+                    eprintln!(
+                        "  {} Symlink points back into its own directory, skipping: {}",
+                        "Warning:".yellow(),
+                        rel_path.display()
+                    );
+                    continue;
+                }
+                Some(fs::read_link(entry.path()).with_context(|| {
+                    format!("Failed to read symlink: {}", entry.path().display())
+                })?)
+            } else {
+                None
+            };
+
         let rel_str = rel_path.to_string_lossy().to_string();
 
-        // Apply path mapping if defined
-        let target_rel = config
-            .mappings
-            .get(&rel_str)
-            .map_or_else(|| rel_path.to_path_buf(), PathBuf::from);
+        // Apply path mapping if defined; templates otherwise lose their `.tmpl` suffix
+        let is_template = source_link.is_none() && template::is_template(rel_path);
+        let mapped = config.mappings.get(&rel_str).map_or_else(
+            || {
+                if is_template {
+                    template::strip_template_suffix(rel_path)
+                } else {
+                    rel_path.to_path_buf()
+                }
+            },
+            PathBuf::from,
+        );
+
+        if !only_includes(only, &[rel_path, &mapped]) {
+            continue;
+        }
+        only_matched = true;
+        let target_rel = place(rel_path, &mapped);
+        if unlinked.contains(&target_rel) {
+            continue;
+        }
 
         let target_rel_str = target_rel.to_string_lossy().to_string();
         let source_file = entry.path().to_path_buf();
@@ -609,7 +1392,13 @@ pub(crate) fn apply_overlay(
         }
 
         // Check for conflicts with existing overlays
-        if let Some(conflicting_overlay) = existing_targets.get(&target_rel_str) {
+        if let Some(conflicting_overlay) =
+            existing_targets.get(&conflict_key(&target_rel_str, case_insensitive))
+        {
+            if dry_run {
+                preview.overlay_conflict(&progress, &target_rel_str, conflicting_overlay);
+                continue;
+            }
             bail!(
                 "Conflict: file '{}' is already managed by overlay '{}'\n\
                  Remove that overlay first or use different file mappings.",
@@ -617,15 +1406,53 @@ pub(crate) fn apply_overlay(
                 conflicting_overlay
             );
         }
+        check_case_collision(&mut own_targets, &target_rel, case_insensitive)?;
 
-        // Check for conflicts with existing files in repo
-        if target_file.exists() {
-            bail!(
-                "Conflict: target file already exists: {}\n\
-                 Remove it first or add a mapping to rename the overlay file.",
-                target_file.display()
-            );
-        }
+        // Templates are always rendered; in copy mode, so are files using a variable
+        let rendered = if is_template || (link_type == LinkType::Copy && source_link.is_none()) {
+            template::render_file(&source_file, &template_vars)?
+        } else {
+            None
+        };
+
+        // A symlink left by an earlier, interrupted apply is reused as-is
+        let linked = rendered.is_none()
+            && source_link.as_ref().map_or_else(
+                || link_type == LinkType::Symlink && already_linked(&target_file, &source_file),
+                |link| fs::read_link(&target_file).is_ok_and(|l| &l == link),
+            );
+
+        if dry_run {
+            preview.entry(
+                &progress,
+                &target_rel.display().to_string(),
+                linked,
+                target_file.exists() || target_file.is_symlink(),
+                policy(&target_rel),
+            );
+            continue;
+        }
+
+        // Check for conflicts with existing files in repo
+        let backup = if linked {
+            None
+        } else if target_file.exists() {
+            match conflicts.resolve(policy(&target_rel), &target, &normalized_name, &target_rel)? {
+                Resolution::Cleared { backup } => backup,
+                Resolution::Skipped => {
+                    progress.item(format!(
+                        "  {} {} {}",
+                        "-".yellow(),
+                        target_rel.display(),
+                        "(skipped, exists in repo)".dimmed()
+                    ));
+                    continue;
+                }
+            }
+        } else {
+            None
+        };
+        partial.record(&target_rel, backup.as_ref());
 
         // Create parent directories if needed
         if let Some(parent) = target_file.parent() {
@@ -640,8 +1467,20 @@ pub(crate) fn apply_overlay(
             target_file.display(),
             link_type
         );
-        match link_type {
-            LinkType::Symlink => {
+        match (link_type, &source_link, &rendered) {
+            _ if linked => {}
+            (_, Some(link), _) => create_symlink(link, &target_file, EntryType::Symlink)?,
+            (_, None, Some(content)) => {
+                fs::write(&target_file, content)
+                    .with_context(|| format!("Failed to write file: {}", target_file.display()))?;
+                copy_permissions(&source_file, &target_file)?;
+            }
+            (LinkType::Symlink, None, None) => {
+                let source_file = if relative_links {
+                    relative_symlink_target(&source_file, &target_file)
+                } else {
+                    source_file
+                };
                 #[cfg(unix)]
                 std::os::unix::fs::symlink(&source_file, &target_file).with_context(|| {
                     format!("Failed to create symlink: {}", target_file.display())
@@ -651,27 +1490,97 @@ pub(crate) fn apply_overlay(
                     || format!("Failed to create symlink: {}", target_file.display()),
                 )?;
             }
-            LinkType::Copy => {
+            (LinkType::Copy, None, None) => {
                 fs::copy(&source_file, &target_file)
                     .with_context(|| format!("Failed to copy file: {}", target_file.display()))?;
+                copy_permissions(&source_file, &target_file)?;
             }
         }
 
-        println!("  {} {}", "+".green(), target_rel.display());
+        // Record content hashes of copies so local edits can be detected later
+        let sha256 = if (link_type == LinkType::Copy || rendered.is_some()) && source_link.is_none()
+        {
+            Some(sha256_file(&target_file)?)
+        } else {
+            None
+        };
+
+        if backup.is_some() {
+            progress.item(format!(
+                "  {} {} {}",
+                "+".green(),
+                target_rel.display(),
+                "(original backed up)".dimmed()
+            ));
+        } else if linked {
+            progress.item(format!(
+                "  {} {} {}",
+                "+".green(),
+                target_rel.display(),
+                "(already linked)".dimmed()
+            ));
+        } else if let Some(link) = &source_link {
+            progress.item(format!(
+                "  {} {} {}",
+                "+".green(),
+                target_rel.display(),
+                format!("-> {}", link.display()).dimmed()
+            ));
+        } else if rendered.is_some() {
+            progress.item(format!(
+                "  {} {} {}",
+                "+".green(),
+                target_rel.display(),
+                "(rendered)".dimmed()
+            ));
+        } else {
+            progress.item(format!("  {} {}", "+".green(), target_rel.display()));
+        }
 
+        // A preserved symlink is a verbatim copy of the link, not a link to the source
+        // Rendered output can't be linked, so it is always recorded as a copy
+        let (link_type, entry_type) = if source_link.is_some() {
+            (LinkType::Copy, EntryType::Symlink)
+        } else if rendered.is_some() {
+            (LinkType::Copy, EntryType::File)
+        } else {
+            (link_type, EntryType::File)
+        };
         state.add_file(FileEntry {
             source: rel_path.to_path_buf(),
             target: target_rel.clone(),
             link_type,
-            entry_type: EntryType::File,
+            entry_type,
+            backup,
+            sha256,
+            rendered: rendered.is_some(),
         });
 
         // Add to exclude list (use forward slashes for git)
         let exclude_path = target_rel.to_string_lossy().replace('\\', "/");
         exclude_entries.push(exclude_path);
     }
+    progress.finish();
+
+    if !only.is_empty() && !only_matched {
+        bail!(
+            "--only {} matched no files in overlay '{}'",
+            only.join(", "),
+            overlay_name
+        );
+    }
+
+    if dry_run {
+        return preview.finish();
+    }
 
     if state.file_count() == 0 {
+        if !conflicts.skipped.is_empty() {
+            bail!(
+                "Nothing to apply: all {} overlay file(s) already exist in the repo",
+                conflicts.skipped.len()
+            );
+        }
         bail!("No files found in overlay source: {}", source.display());
     }
 
@@ -691,7 +1600,9 @@ pub(crate) fn apply_overlay(
     }
 
     // Save overlay state to in-repo location
+    state.skipped.clone_from(&conflicts.skipped);
     save_overlay_state(&target, &state)?;
+    partial.finish();
 
     // Save external backup for restore capability
     if let Err(e) = save_external_state(&target, &normalized_name, &state) {
@@ -701,13 +1612,27 @@ pub(crate) fn apply_overlay(
             e
         );
     }
+    record_cache_pin(&target, &state);
 
-    println!(
+    status!(
         "\n{} Applied {} file(s) from '{}'",
         "✓".green().bold(),
         state.file_count(),
         overlay_name
     );
+    conflicts.print();
+
+    if let Some(command) = &state.hooks.post_apply {
+        if run_hooks {
+            run_hook(&target, &state.name, "post_apply", command)?;
+        } else {
+            status!(
+                "{} Skipped post_apply hook (pass --run-hooks to run it): {}",
+                "Note:".yellow(),
+                command
+            );
+        }
+    }
 
     Ok(())
 }
@@ -728,6 +1653,7 @@ pub(crate) fn remove_overlay(
     name: Option<String>,
     remove_all: bool,
     dry_run: bool,
+    run_hooks: bool,
 ) -> Result<()> {
     debug!(
         "remove_overlay: target={}, name={:?}, remove_all={}, dry_run={}",
@@ -741,18 +1667,35 @@ pub(crate) fn remove_overlay(
         let target = canonicalize_path(target, "Target directory")?;
         let applied_overlays = list_applied_overlays(&target)?;
 
-        if remove_all {
-            println!("{} Dry run - would remove all overlays:", "Note:".yellow());
-            for overlay_name in &applied_overlays {
-                println!("  - {overlay_name}");
+        let names = if remove_all {
+            applied_overlays
+        } else if let Some(name) = name {
+            let normalized_name = normalize_overlay_name(&name)?;
+            if !applied_overlays.contains(&normalized_name) {
+                if applied_overlays.is_empty() {
+                    bail!("No overlays are currently applied");
+                }
+                bail!(
+                    "Overlay '{}' not found. Available overlays: {}",
+                    name,
+                    applied_overlays.join(", ")
+                );
             }
-        } else if let Some(ref name) = name {
-            println!(
-                "{} Dry run - would remove overlay '{}'",
-                "Note:".yellow(),
-                name
+            vec![normalized_name]
+        } else {
+            bail!(
+                "No overlay name specified. Use --all to remove all overlays, or specify a name."
             );
+        };
+        if names.is_empty() {
+            status!("{} No overlays are currently applied.", "Status:".bold());
+            return Ok(());
         }
+
+        for overlay_name in &names {
+            preview_overlay_removal(&target, overlay_name, run_hooks)?;
+        }
+        status!("\n{} Dry run - no changes made.", "Note:".yellow());
         return Ok(());
     }
     let target = canonicalize_path(target, "Target directory")?;
@@ -770,16 +1713,17 @@ pub(crate) fn remove_overlay(
     if remove_all {
         // Remove all overlays
         for overlay_name in &applied_overlays {
-            remove_single_overlay(&target, &overlays_dir, overlay_name)?;
+            remove_single_overlay(&target, &overlays_dir, overlay_name, run_hooks)?;
         }
 
         // Clean up .repoverlay directory entirely
         fs::remove_dir_all(target.join(STATE_DIR))?;
 
-        println!("\n{} Removed all overlays", "✓".green().bold());
+        status!("\n{} Removed all overlays", "✓".green().bold());
     } else if let Some(name) = name {
         let normalized_name = normalize_overlay_name(&name)?;
-        remove_single_overlay(&target, &overlays_dir, &normalized_name)?;
+        warn_dependents(&target, &normalized_name)?;
+        remove_single_overlay(&target, &overlays_dir, &normalized_name, run_hooks)?;
 
         // Check if any overlays remain
         let remaining = list_applied_overlays(&target)?;
@@ -795,10 +1739,386 @@ pub(crate) fn remove_overlay(
     Ok(())
 }
 
+/// Print what removing an applied overlay would delete and restore, without
+/// changing anything.
+fn preview_overlay_removal(target: &Path, name: &str, run_hooks: bool) -> Result<()> {
+    let state = load_overlay_state(target, name)?;
+
+    status!("{} overlay: {}", "Would remove".red().bold(), state.name);
+    if let Some(command) = &state.hooks.pre_remove {
+        let when = if run_hooks {
+            "runs first"
+        } else {
+            "skipped without --run-hooks"
+        };
+        status!("  {} pre_remove hook ({when}): {command}", "!".yellow());
+    }
+    for entry in state.file_entries() {
+        let path = target.join(&entry.target);
+        if path.exists() || path.is_symlink() {
+            let slash = if entry.entry_type == EntryType::Directory {
+                "/"
+            } else {
+                ""
+            };
+            status!("  {} {}{slash}", "-".red(), entry.target.display());
+        }
+        if entry.backup.is_some() {
+            status!(
+                "  {} {} {}",
+                "+".green(),
+                entry.target.display(),
+                "(original restored)".dimmed()
+            );
+        }
+    }
+    if let Some(file) = exclude_file()? {
+        status!(
+            "  {} {} section in {file}",
+            "-".red(),
+            exclude_marker_start(name)
+        );
+    }
+    Ok(())
+}
+
+/// Whether a file passes `apply --only`: with no patterns everything does,
+/// otherwise a pattern must match one of `paths` (the file's source and target
+/// path) or a directory containing it.
+fn only_includes(only: &[String], paths: &[&Path]) -> bool {
+    only.is_empty()
+        || paths.iter().any(|path| {
+            path.ancestors()
+                .filter(|p| !p.as_os_str().is_empty())
+                .any(|p| only.iter().any(|pattern| ignore::glob_matches(pattern, p)))
+        })
+}
+
+/// Name an overlay gets without `--name`: the config name, else the source
+/// directory name.
+fn default_overlay_name(source: &Path, config: &OverlayConfig) -> String {
+    config.overlay.name.clone().unwrap_or_else(|| {
+        source.file_name().map_or_else(
+            || "unnamed".to_string(),
+            |n| n.to_string_lossy().to_string(),
+        )
+    })
+}
+
+/// Apply an overlay listed in another overlay's `requires`, unless it is
+/// already applied. Returns the required overlay's normalized name.
+fn apply_requirement(
+    requirement: &str,
+    target: &Path,
+    options: &ApplyOptions,
+    chain: &mut Vec<String>,
+) -> Result<String> {
+    let name = resolved_overlay_name(requirement, target, options.update_cache)?;
+
+    if list_applied_overlays(target)?.contains(&name) {
+        status!(
+            "  {} Requirement '{}' is already applied",
+            "✓".green(),
+            name
+        );
+        return Ok(name);
+    }
+
+    status!(
+        "{} required overlay: {} {}",
+        "Resolving".blue().bold(),
+        requirement,
+        format!("(for {})", chain.last().map_or("", String::as_str)).dimmed()
+    );
+    apply_overlay_with_requirements(requirement, target, options, Some(chain))?;
+    Ok(name)
+}
+
+/// Check an `apply --prefix` directory, which must be a relative path inside
+/// the repository, and return it without `.` components.
+fn normalize_prefix(prefix: &Path) -> Result<PathBuf> {
+    let mut normalized = PathBuf::new();
+    for component in prefix.components() {
+        match component {
+            std::path::Component::Normal(c) => normalized.push(c),
+            std::path::Component::CurDir => {}
+            _ => bail!(
+                "--prefix must be a relative path inside the repository: {}",
+                prefix.display()
+            ),
+        }
+    }
+    Ok(normalized)
+}
+
+/// Resolve `source` and return the normalized name it would be applied under.
+fn resolved_overlay_name(source: &str, target: &Path, update_cache: bool) -> Result<String> {
+    let resolved = resolve_source(source, None, update_cache, Some(target), None)?;
+    let config = resolved.load_config()?;
+    normalize_overlay_name(&resolved.default_name(&config))
+}
+
+/// Warn when other applied overlays require `name`, which is being removed.
+pub(crate) fn warn_dependents(target: &Path, name: &str) -> Result<()> {
+    let mut dependents = Vec::new();
+    for other in list_applied_overlays(target)? {
+        if other != name
+            && load_overlay_state(target, &other)?
+                .requires
+                .iter()
+                .any(|r| r == name)
+        {
+            dependents.push(other);
+        }
+    }
+
+    if !dependents.is_empty() {
+        eprintln!(
+            "  {} '{}' is required by: {}",
+            "Warning:".yellow(),
+            name,
+            dependents.join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// Decide whether overlay files are symlinked or copied, from `--copy` and
+/// the `link_style` setting.
+///
+/// When `auto` has to fall back to copying, a note explaining why is shown
+/// once per run.
+pub(crate) fn resolve_link_type(force_copy: bool) -> Result<LinkType> {
+    static COPY_NOTE: std::sync::Once = std::sync::Once::new();
+
+    let style = config::load_global_config()?.link_style;
+    let link_type = link_type_for(force_copy, style, symlinks_supported);
+    if link_type == LinkType::Copy && !force_copy && style == LinkStyle::Auto {
+        COPY_NOTE.call_once(|| {
+            status!(
+                "{} Symlinks aren't available on this system, so overlay files are copied.\n  \
+                 Copies don't follow later edits to the overlay source. To use symlinks,\n  \
+                 enable Developer Mode in Windows settings. Set link_style = copy to hide this note.",
+                "Note:".yellow()
+            );
+        });
+    }
+    Ok(link_type)
+}
+
+/// Link type for `--copy`, the `link_style` setting, and whether the platform
+/// can create symlinks (only asked for `auto`).
+fn link_type_for(
+    force_copy: bool,
+    style: LinkStyle,
+    symlinks_supported: impl FnOnce() -> bool,
+) -> LinkType {
+    if force_copy {
+        return LinkType::Copy;
+    }
+    match style {
+        LinkStyle::Symlink => LinkType::Symlink,
+        LinkStyle::Auto if symlinks_supported() => LinkType::Symlink,
+        LinkStyle::Auto | LinkStyle::Copy => LinkType::Copy,
+    }
+}
+
+/// Whether symlinks can be created. Always true on Unix; on Windows it
+/// depends on Developer Mode or admin rights, so it is probed once per run.
+fn symlinks_supported() -> bool {
+    static SUPPORTED: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
+
+    if cfg!(unix) {
+        return true;
+    }
+    *SUPPORTED.get_or_init(|| match probe_symlinks(&std::env::temp_dir()) {
+        Ok(()) => true,
+        Err(e) => {
+            debug!("symlink probe failed: {e}");
+            false
+        }
+    })
+}
+
+/// Try to create a file symlink in a scratch directory under `dir`, cleaning
+/// up afterwards.
+pub(crate) fn probe_symlinks(dir: &Path) -> std::io::Result<()> {
+    let probe = dir.join(format!("repoverlay-symlink-probe-{}", std::process::id()));
+    let result = (|| {
+        fs::create_dir_all(&probe)?;
+        fs::write(probe.join("source"), "")?;
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(probe.join("source"), probe.join("link"))?;
+        #[cfg(windows)]
+        std::os::windows::fs::symlink_file(probe.join("source"), probe.join("link"))?;
+        Ok(())
+    })();
+    let _ = fs::remove_dir_all(&probe);
+    result
+}
+
+/// Decide whether symlinks for an overlay should be written as relative paths.
+///
+/// Relative symlinks only make sense for local overlays that sit next to
+/// the target; cache-backed sources always get absolute links.
+fn use_relative_symlinks(
+    source_info: &OverlaySource,
+    source: &Path,
+    target: &Path,
+) -> Result<bool> {
+    Ok(matches!(source_info, OverlaySource::Local { .. })
+        && target
+            .parent()
+            .is_some_and(|parent| source.starts_with(parent))
+        && config::load_global_config()?.symlink_style == SymlinkStyle::Relative)
+}
+
+/// Compute the path to store in a symlink at `link_path` so that it points
+/// at `source_path` relative to the link's own directory.
+///
+/// Both paths are expected to be absolute.
+pub(crate) fn relative_symlink_target(source_path: &Path, link_path: &Path) -> PathBuf {
+    let link_dir = link_path.parent().unwrap_or(link_path);
+
+    let source_components: Vec<_> = source_path.components().collect();
+    let link_components: Vec<_> = link_dir.components().collect();
+    let common = source_components
+        .iter()
+        .zip(&link_components)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut relative = PathBuf::new();
+    for _ in common..link_components.len() {
+        relative.push("..");
+    }
+    for component in &source_components[common..] {
+        relative.push(component);
+    }
+    relative
+}
+
+/// Check whether a symlink's stored path resolves to `source_path`.
+///
+/// Relative links are resolved against the directory containing the link.
+fn symlink_points_to(link: &Path, link_path: &Path, source_path: &Path) -> bool {
+    let resolved = if link.is_relative() {
+        link_path
+            .parent()
+            .map_or_else(|| link.to_path_buf(), |dir| dir.join(link))
+    } else {
+        link.to_path_buf()
+    };
+
+    resolved == source_path
+        || matches!(
+            (resolved.canonicalize(), source_path.canonicalize()),
+            (Ok(a), Ok(b)) if a == b
+        )
+}
+
+/// Check whether a symlink resolves to a directory containing itself, which
+/// would make recursive walks of the target loop forever.
+fn link_loops_back(link_path: &Path) -> bool {
+    let (Ok(resolved), Some(Ok(parent))) = (
+        fs::canonicalize(link_path),
+        link_path.parent().map(fs::canonicalize),
+    ) else {
+        return false;
+    };
+    resolved.is_dir() && parent.starts_with(&resolved)
+}
+
+/// Check whether `path` is already a symlink resolving to `source_path`.
+fn already_linked(path: &Path, source_path: &Path) -> bool {
+    fs::read_link(path).is_ok_and(|link| symlink_points_to(&link, path, source_path))
+}
+
+/// Move a conflicting repo file or directory aside so an overlay can replace it.
+///
+/// Returns the backup location relative to the target.
+fn backup_conflicting_path(target: &Path, overlay_name: &str, rel_path: &Path) -> Result<PathBuf> {
+    let backup_rel = Path::new(STATE_DIR)
+        .join(BACKUPS_DIR)
+        .join(overlay_name)
+        .join(rel_path);
+    let backup_path = target.join(&backup_rel);
+
+    if let Some(parent) = backup_path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+    fs::rename(target.join(rel_path), &backup_path).with_context(|| {
+        format!(
+            "Failed to back up {} to {}",
+            rel_path.display(),
+            backup_path.display()
+        )
+    })?;
+
+    Ok(backup_rel)
+}
+
+/// Move a backed-up repo file or directory back to its original location.
+fn restore_backup(target: &Path, backup_rel: &Path, rel_path: &Path) -> Result<()> {
+    let backup_path = target.join(backup_rel);
+    let original_path = target.join(rel_path);
+
+    if !backup_path.exists() && !backup_path.is_symlink() {
+        eprintln!(
+            "  {} Backup not found, cannot restore: {}",
+            "Warning:".yellow(),
+            backup_path.display()
+        );
+        return Ok(());
+    }
+
+    if let Some(parent) = original_path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+    fs::rename(&backup_path, &original_path).with_context(|| {
+        format!(
+            "Failed to restore {} from {}",
+            original_path.display(),
+            backup_path.display()
+        )
+    })?;
+
+    // Remove empty directories left behind in the backups tree
+    let backups_root = target.join(STATE_DIR).join(BACKUPS_DIR);
+    let mut parent = backup_path.parent();
+    while let Some(dir) = parent {
+        if !dir.starts_with(&backups_root) {
+            break;
+        }
+        if dir
+            .read_dir()
+            .map(|mut d| d.next().is_none())
+            .unwrap_or(false)
+        {
+            fs::remove_dir(dir).ok();
+            parent = dir.parent();
+        } else {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
 /// Remove a single overlay by name.
-pub(crate) fn remove_single_overlay(target: &Path, overlays_dir: &Path, name: &str) -> Result<()> {
+///
+/// With `run_hooks`, the overlay's `pre_remove` hook runs first, and a failing
+/// hook leaves the overlay applied.
+pub(crate) fn remove_single_overlay(
+    target: &Path,
+    overlays_dir: &Path,
+    name: &str,
+    run_hooks: bool,
+) -> Result<()> {
     debug!("remove_single_overlay: {name}");
-    let state_file = overlays_dir.join(format!("{name}.ccl"));
+    let state_file = overlays_dir.join(state_file_name(name));
 
     if !state_file.exists() {
         // List available overlays for helpful error message
@@ -816,7 +2136,19 @@ pub(crate) fn remove_single_overlay(target: &Path, overlays_dir: &Path, name: &s
 
     let state = load_overlay_state(target, name)?;
 
-    println!("{} overlay: {}", "Removing".red().bold(), state.name);
+    if let Some(command) = &state.hooks.pre_remove {
+        if run_hooks {
+            run_hook(target, &state.name, "pre_remove", command)?;
+        } else {
+            status!(
+                "{} Skipped pre_remove hook (pass --run-hooks to run it): {}",
+                "Note:".yellow(),
+                command
+            );
+        }
+    }
+
+    status!("{} overlay: {}", "Removing".red().bold(), state.name);
 
     // Remove files and directories
     for entry in state.file_entries() {
@@ -849,49 +2181,32 @@ pub(crate) fn remove_single_overlay(target: &Path, overlays_dir: &Path, name: &s
                             format!("Failed to remove directory: {}", file_path.display())
                         })?;
                     }
-                    println!("  {} {}/", "-".red(), entry.target.display());
+                    status!("  {} {}/", "-".red(), entry.target.display());
                 }
-                EntryType::File => {
+                EntryType::File | EntryType::Symlink => {
                     fs::remove_file(&file_path)
                         .with_context(|| format!("Failed to remove: {}", file_path.display()))?;
-                    println!("  {} {}", "-".red(), entry.target.display());
+                    status!("  {} {}", "-".red(), entry.target.display());
                 }
             }
 
-            // Remove empty parent directories (but not the target itself)
-            let mut parent = file_path.parent();
-            while let Some(dir) = parent {
-                if dir == target {
-                    break;
-                }
-                if dir
-                    .read_dir()
-                    .map(|mut d| d.next().is_none())
-                    .unwrap_or(false)
-                {
-                    fs::remove_dir(dir).ok();
-                    parent = dir.parent();
-                } else {
-                    break;
-                }
-            }
+            remove_empty_parents(target, &file_path);
+        }
+
+        // Put back any repo file that was replaced by `apply --force`
+        if let Some(backup) = &entry.backup {
+            restore_backup(target, backup, &entry.target)?;
+            status!(
+                "  {} {} {}",
+                "+".green(),
+                entry.target.display(),
+                "(original restored)".dimmed()
+            );
         }
     }
 
     // Update git exclude (remove this overlay's section)
-    let exclude_entries: Vec<String> = state
-        .file_entries()
-        .iter()
-        .map(|e| {
-            let path = e.target.to_string_lossy().replace('\\', "/");
-            // Add trailing slash for directories in git exclude
-            match e.entry_type {
-                EntryType::Directory => format!("{path}/"),
-                EntryType::File => path,
-            }
-        })
-        .collect();
-    update_git_exclude(target, name, &exclude_entries, false)?;
+    update_git_exclude(target, name, &exclude_entries(&state), false)?;
 
     // Remove state file
     fs::remove_file(&state_file)?;
@@ -905,7 +2220,7 @@ pub(crate) fn remove_single_overlay(target: &Path, overlays_dir: &Path, name: &s
         );
     }
 
-    println!(
+    status!(
         "\n{} Removed {} file(s) from '{}'",
         "✓".green().bold(),
         state.file_count(),
@@ -915,183 +2230,2230 @@ pub(crate) fn remove_single_overlay(target: &Path, overlays_dir: &Path, name: &s
     Ok(())
 }
 
-/// Show the status of applied overlays.
-pub(crate) fn show_status(target: &Path, filter_name: Option<String>) -> Result<()> {
-    let target = canonicalize_path(target, "Target directory")?;
+/// An applied overlay set aside while it is re-applied, so a failed re-apply
+/// can put it back as it was.
+///
+/// Taking the snapshot moves the overlay's files into `.repoverlay/stash/` and
+/// drops its state file. Repo files it backed up stay in the backups
+/// directory, and their paths are free for the re-apply.
+struct OverlaySnapshot {
+    state: OverlayState,
+    stash: PathBuf,
+}
 
-    let overlays_dir = target.join(STATE_DIR).join(OVERLAYS_DIR);
+impl OverlaySnapshot {
+    fn take(target: &Path, name: &str) -> Result<Self> {
+        let state = load_overlay_state(target, name)?;
+        let stash = target.join(STATE_DIR).join(STASH_DIR).join(name);
+        if stash.exists() {
+            fs::remove_dir_all(&stash)
+                .with_context(|| format!("Failed to clear {}", stash.display()))?;
+        }
 
-    if !overlays_dir.exists() {
-        println!("{} No overlays are currently applied.", "Status:".bold());
-        return Ok(());
-    }
+        for entry in state.file_entries() {
+            let path = target.join(&entry.target);
+            if !path.exists() && !path.is_symlink() {
+                continue;
+            }
+            let stashed = stash.join(&entry.target);
+            if let Some(parent) = stashed.parent() {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+            }
+            fs::rename(&path, &stashed)
+                .with_context(|| format!("Failed to set aside {}", entry.target.display()))?;
+            remove_empty_parents(target, &path);
+        }
 
-    let applied_overlays = list_applied_overlays(&target)?;
-    if applied_overlays.is_empty() {
-        println!("{} No overlays are currently applied.", "Status:".bold());
-        return Ok(());
-    }
+        let state_file = target
+            .join(STATE_DIR)
+            .join(OVERLAYS_DIR)
+            .join(state_file_name(name));
+        fs::remove_file(&state_file)
+            .with_context(|| format!("Failed to remove {}", state_file.display()))?;
 
-    // If filtering by name, show just that overlay
-    if let Some(filter) = filter_name {
-        let normalized = normalize_overlay_name(&filter)?;
+        Ok(Self { state, stash })
+    }
 
-        if !applied_overlays.contains(&normalized) {
-            bail!(
-                "Overlay '{}' is not applied. Available: {}",
-                filter,
-                applied_overlays.join(", ")
-            );
+    /// Put the overlay back after a failed re-apply, replacing anything the
+    /// re-apply left at its paths.
+    fn restore(self, target: &Path) -> Result<()> {
+        let name = normalize_overlay_name(&self.state.name)?;
+        for entry in self.state.file_entries() {
+            let stashed = self.stash.join(&entry.target);
+            if !stashed.exists() && !stashed.is_symlink() {
+                continue;
+            }
+            let path = target.join(&entry.target);
+            if path.is_symlink() || path.is_file() {
+                fs::remove_file(&path)?;
+            } else if path.is_dir() {
+                fs::remove_dir_all(&path)?;
+            }
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+            }
+            fs::rename(&stashed, &path)
+                .with_context(|| format!("Failed to restore {}", entry.target.display()))?;
         }
 
-        show_single_overlay_status(&target, &normalized)?;
-        return Ok(());
+        fs::create_dir_all(target.join(STATE_DIR).join(OVERLAYS_DIR))?;
+        save_overlay_state(target, &self.state)?;
+        update_git_exclude(target, &name, &exclude_entries(&self.state), true)?;
+        remove_stash(&self.stash);
+        Ok(())
     }
 
-    // Show summary header
-    println!(
-        "{} ({} overlay(s) applied)",
-        "Overlay Status".bold(),
-        applied_overlays.len()
-    );
-    println!();
-
-    for overlay_name in &applied_overlays {
-        show_single_overlay_status(&target, overlay_name)?;
-        println!();
+    /// Finish after a successful re-apply: backups of repo files move to the
+    /// new state, and repo files the new version no longer covers come back.
+    fn finish(self, target: &Path) -> Result<()> {
+        let name = normalize_overlay_name(&self.state.name)?;
+        let mut state = load_overlay_state(target, &name)?;
+        for old in self.state.file_entries() {
+            let Some(backup) = &old.backup else {
+                continue;
+            };
+            if let Some(entry) = state.files.iter_mut().find(|e| e.target == old.target) {
+                entry.backup = Some(backup.clone());
+            } else {
+                restore_backup(target, backup, &old.target)?;
+            }
+        }
+        save_overlay_state(target, &state)?;
+        if let Err(e) = save_external_state(target, &name, &state) {
+            eprintln!(
+                "  {} Could not save external backup: {}",
+                "Warning:".yellow(),
+                e
+            );
+        }
+        remove_stash(&self.stash);
+        Ok(())
     }
 
-    Ok(())
-}
+    /// Remove the overlay for good once whatever replaced it is applied:
+    /// repo files it backed up come back where nothing else now lives.
+    fn discard(self, target: &Path) -> Result<()> {
+        let name = normalize_overlay_name(&self.state.name)?;
+        if let Some(command) = &self.state.hooks.pre_remove {
+            status!(
+                "{} Skipped pre_remove hook (pass --run-hooks to run it): {}",
+                "Note:".yellow(),
+                command
+            );
+        }
+        for entry in self.state.file_entries() {
+            let Some(backup) = &entry.backup else {
+                continue;
+            };
+            let path = target.join(&entry.target);
+            if path.exists() || path.is_symlink() {
+                eprintln!(
+                    "  {} {} is now covered by another overlay; the original stays at {}",
+                    "Warning:".yellow(),
+                    entry.target.display(),
+                    backup.display()
+                );
+            } else {
+                restore_backup(target, backup, &entry.target)?;
+            }
+        }
+        update_git_exclude(target, &name, &exclude_entries(&self.state), false)?;
+        if let Err(e) = remove_external_state(target, &name) {
+            eprintln!(
+                "  {} Could not remove external backup: {}",
+                "Warning:".yellow(),
+                e
+            );
+        }
+        remove_stash(&self.stash);
+        status!(
+            "{} Removed {} file(s) from '{}'",
+            "✓".green().bold(),
+            self.state.file_count(),
+            self.state.name
+        );
+        Ok(())
+    }
+}
+
+/// Delete an overlay's stash, and the stash directory once it is empty.
+fn remove_stash(stash: &Path) {
+    if let Err(e) = fs::remove_dir_all(stash) {
+        debug!("failed to remove {}: {e}", stash.display());
+    }
+    if let Some(parent) = stash.parent() {
+        fs::remove_dir(parent).ok();
+    }
+}
+
+/// Remove the empty directories above `path`, stopping at `root`.
+fn remove_empty_parents(root: &Path, path: &Path) {
+    let mut parent = path.parent();
+    while let Some(dir) = parent {
+        if dir == root {
+            break;
+        }
+        if dir
+            .read_dir()
+            .map(|mut d| d.next().is_none())
+            .unwrap_or(false)
+        {
+            fs::remove_dir(dir).ok();
+            parent = dir.parent();
+        } else {
+            break;
+        }
+    }
+}
+
+/// Build the git exclude entries for an overlay's files.
+fn exclude_entries(state: &OverlayState) -> Vec<String> {
+    state
+        .file_entries()
+        .iter()
+        .map(|e| {
+            let path = e.target.to_string_lossy().replace('\\', "/");
+            // Add trailing slash for directories in git exclude
+            match e.entry_type {
+                EntryType::Directory => format!("{path}/"),
+                EntryType::File | EntryType::Symlink => path,
+            }
+        })
+        .collect()
+}
+
+/// Stop managing a single file of an applied overlay, keeping its content.
+///
+/// A symlinked entry is replaced with a real copy of what it points to, the
+/// entry is dropped from the overlay state and git exclude section, and the
+/// overlay is removed entirely if it has no files left.
+///
+/// An entry that replaced a repo file holds the only copy of the original in
+/// its backup, so it is only unlinked with `force`, which discards the backup.
+pub(crate) fn unlink_file(
+    target: &Path,
+    overlay_name: &str,
+    file: &Path,
+    force: bool,
+) -> Result<()> {
+    debug!(
+        "unlink_file: target={}, overlay={}, file={}",
+        target.display(),
+        overlay_name,
+        file.display()
+    );
+    let target = canonicalize_path(target, "Target directory")?;
+
+    let name = normalize_overlay_name(overlay_name)?;
+    let applied_overlays = list_applied_overlays(&target)?;
+    if !applied_overlays.contains(&name) {
+        bail!(
+            "Overlay '{}' is not applied. Available: {}",
+            overlay_name,
+            applied_overlays.join(", ")
+        );
+    }
+
+    let file = target_relative_path(&target, file);
+    let mut state = load_overlay_state(&target, &name)?;
+    let Some(index) = state.files.iter().position(|e| e.target == file) else {
+        bail!(
+            "'{}' is not managed by overlay '{}'",
+            file.display(),
+            state.name
+        );
+    };
+    if state.files[index].backup.is_some() && !force {
+        bail!(
+            "'{}' replaced a repo file that is backed up until '{}' is removed. \
+             Run 'repoverlay remove {}' to get the original back, or pass --force to keep \
+             the overlay's copy and discard the original.",
+            file.display(),
+            state.name,
+            name
+        );
+    }
+    let entry = state.files.remove(index);
+    state.unlinked.push(entry.target.clone());
+
+    // Preserved source symlinks are kept as they are
+    let path = target.join(&entry.target);
+    if path.is_symlink() && entry.entry_type != EntryType::Symlink {
+        let resolved = path.canonicalize().with_context(|| {
+            format!(
+                "Cannot keep '{}': symlink is broken (run 'repoverlay repair' first)",
+                entry.target.display()
+            )
+        })?;
+        remove_symlink(&path, entry.entry_type)?;
+        match entry.entry_type {
+            EntryType::Directory => copy_dir_recursive(&resolved, &path)?,
+            EntryType::File | EntryType::Symlink => {
+                fs::copy(&resolved, &path)
+                    .with_context(|| format!("Failed to copy file: {}", path.display()))?;
+            }
+        }
+    }
+
+    status!(
+        "{} {} from '{}' (file kept)",
+        "Unlinked".green().bold(),
+        entry.target.display(),
+        state.name
+    );
+
+    if let Some(backup) = &entry.backup {
+        let backup_path = target.join(backup);
+        if backup_path.is_dir() && !backup_path.is_symlink() {
+            fs::remove_dir_all(&backup_path)
+        } else {
+            fs::remove_file(&backup_path)
+        }
+        .with_context(|| format!("Failed to discard backup: {}", backup_path.display()))?;
+        remove_empty_parents(&target.join(STATE_DIR).join(BACKUPS_DIR), &backup_path);
+        eprintln!(
+            "  {} Discarded the repo's original {}",
+            "Warning:".yellow(),
+            entry.target.display()
+        );
+    }
+
+    save_remaining_entries(&target, &name, &state)
+}
+
+/// Save an overlay after some of its entries were dropped from `state`.
+///
+/// Updates the git exclude section and both state copies. If no entries are
+/// left, the overlay is removed instead, leaving the repo's files in place.
+pub(crate) fn save_remaining_entries(
+    target: &Path,
+    name: &str,
+    state: &OverlayState,
+) -> Result<()> {
+    if state.files.is_empty() {
+        // Nothing left to manage: drop the overlay without touching the kept file
+        update_git_exclude(target, name, &[], false)?;
+        fs::remove_file(
+            target
+                .join(STATE_DIR)
+                .join(OVERLAYS_DIR)
+                .join(state_file_name(name)),
+        )?;
+        if let Err(e) = remove_external_state(target, name) {
+            eprintln!(
+                "  {} Could not remove external backup: {}",
+                "Warning:".yellow(),
+                e
+            );
+        }
+        status!(
+            "{} Removed overlay '{}' (no files left)",
+            "✓".green().bold(),
+            state.name
+        );
+        return Ok(());
+    }
+
+    update_git_exclude(target, name, &exclude_entries(state), true)?;
+    save_overlay_state(target, state)?;
+    if let Err(e) = save_external_state(target, name, state) {
+        eprintln!(
+            "  {} Could not save external backup: {}",
+            "Warning:".yellow(),
+            e
+        );
+    }
+
+    Ok(())
+}
+
+/// Accept paths relative to the target, with or without a leading "./".
+pub(crate) fn target_relative_path(target: &Path, file: &Path) -> PathBuf {
+    file.strip_prefix(target)
+        .unwrap_or(file)
+        .components()
+        .filter(|c| !matches!(c, std::path::Component::CurDir))
+        .collect()
+}
+
+/// Open the overlay source of an applied file in an editor.
+///
+/// `file` is the path in the target repo; files inside a directory entry map
+/// to the matching path under the source directory. `editor` is a command
+/// line such as `code --wait`, run with the source path appended.
+pub(crate) fn edit_overlay_file(
+    target: &Path,
+    overlay_name: &str,
+    file: &Path,
+    editor: &str,
+) -> Result<()> {
+    debug!(
+        "edit_overlay_file: target={}, overlay={}, file={}",
+        target.display(),
+        overlay_name,
+        file.display()
+    );
+    let target = canonicalize_path(target, "Target directory")?;
+
+    let name = normalize_overlay_name(overlay_name)?;
+    let applied_overlays = list_applied_overlays(&target)?;
+    if !applied_overlays.contains(&name) {
+        bail!(
+            "Overlay '{}' is not applied. Available: {}",
+            overlay_name,
+            applied_overlays.join(", ")
+        );
+    }
+
+    let file = target_relative_path(&target, file);
+    let state = load_overlay_state(&target, &name)?;
+    let Some((entry, source_rel)) = state.file_entries().iter().find_map(|e| {
+        if e.target == file {
+            Some((e, e.source.clone()))
+        } else if e.entry_type == EntryType::Directory {
+            file.strip_prefix(&e.target)
+                .ok()
+                .map(|rest| (e, e.source.join(rest)))
+        } else {
+            None
+        }
+    }) else {
+        bail!(
+            "'{}' is not managed by overlay '{}'",
+            file.display(),
+            state.name
+        );
+    };
+
+    let resolved = resolve_source(
+        &state.source.source_string(),
+        state.source.ref_override(),
+        false,
+        Some(&target),
+        state.source.source_filter(),
+    )?;
+    let source_path = resolved.path.join(&source_rel);
+    if !source_path.exists() {
+        bail!(
+            "Overlay source file no longer exists: {}",
+            source_path.display()
+        );
+    }
+
+    if entry.link_type == LinkType::Copy {
+        eprintln!(
+            "  {} '{}' was applied as a copy; the repo file won't change until the overlay is re-applied",
+            "Warning:".yellow(),
+            file.display()
+        );
+    }
+    if matches!(
+        state.source,
+        OverlaySource::GitHub { .. } | OverlaySource::Archive { .. }
+    ) {
+        eprintln!(
+            "  {} This file is in the cache and will be replaced when the overlay is updated",
+            "Note:".yellow()
+        );
+    }
+
+    let mut parts = editor.split_whitespace();
+    let Some(program) = parts.next() else {
+        bail!("No editor configured. Set $VISUAL or $EDITOR.");
+    };
+    status!("{} {}", "Editing".green().bold(), source_path.display());
+    let status = std::process::Command::new(program)
+        .args(parts)
+        .arg(&source_path)
+        .status()
+        .with_context(|| format!("Failed to launch editor: {program}"))?;
+    if !status.success() {
+        bail!("Editor exited with {status}");
+    }
+
+    Ok(())
+}
+
+/// Print which applied overlay manages a repo file, and its source.
+///
+/// Files inside a directory entry belong to the overlay that applied the
+/// directory. Fails if no overlay manages the file.
+pub(crate) fn which_overlay(target: &Path, file: &Path) -> Result<()> {
+    debug!(
+        "which_overlay: target={}, file={}",
+        target.display(),
+        file.display()
+    );
+    let target = canonicalize_path(target, "Target directory")?;
+    let file = target_relative_path(&target, file);
+
+    let case_insensitive = is_case_insensitive_fs(&target);
+    let targets = load_all_overlay_targets(&target, case_insensitive)?;
+    let Some((owned, name)) = file
+        .ancestors()
+        .filter(|p| !p.as_os_str().is_empty())
+        .find_map(|p| {
+            targets
+                .get(&conflict_key(&p.to_string_lossy(), case_insensitive))
+                .map(|name| (p, name))
+        })
+    else {
+        bail!("'{}' is not managed by any overlay", file.display());
+    };
+
+    let state = load_overlay_state(&target, &normalize_overlay_name(name)?)?;
+    println!("{}: {}", file.display(), state.name.bold());
+    println!("  Source:   {}", state.source.display());
+    let path = target.join(&file);
+    if path.is_symlink() || target.join(owned).is_symlink() {
+        match fs::canonicalize(&path) {
+            Ok(resolved) => println!("  Links to: {}", resolved.display()),
+            Err(_) => println!("  Links to: {}", "(broken link)".red()),
+        }
+    }
+    Ok(())
+}
+
+/// Move a file managed by an applied overlay to another path in the repo.
+///
+/// The link (or copy) is recreated at `to`, the old location's empty parent
+/// directories are pruned, and the move is recorded in the overlay's state so
+/// `restore` and `update` keep the file there and `sync` still writes it back
+/// to its original path in the overlay. A repo file that was backed up when
+/// the overlay was applied is put back at the old location.
+pub(crate) fn move_overlay_file(target: &Path, name: &str, from: &Path, to: &Path) -> Result<()> {
+    debug!(
+        "move_overlay_file: target={}, name={}, from={}, to={}",
+        target.display(),
+        name,
+        from.display(),
+        to.display()
+    );
+    let target = canonicalize_path(target, "Target directory")?;
+    let name = normalize_overlay_name(name)?;
+    let mut state = load_overlay_state(&target, &name)?;
+    let from = target_relative_path(&target, from);
+    let to = target_relative_path(&target, to);
+
+    if to.as_os_str().is_empty()
+        || !to
+            .components()
+            .all(|c| matches!(c, std::path::Component::Normal(_)))
+    {
+        bail!(
+            "Destination must be a relative path inside the repository: {}",
+            to.display()
+        );
+    }
+    if from == to {
+        bail!("'{}' is already at that path", from.display());
+    }
+
+    let Some(index) = state.files.iter().position(|e| e.target == from) else {
+        bail!(
+            "'{}' is not a file of overlay '{}'",
+            from.display(),
+            state.name
+        );
+    };
+
+    // The destination must be free, and not inside anything an overlay manages
+    let case_insensitive = is_case_insensitive_fs(&target);
+    let targets = load_all_overlay_targets(&target, case_insensitive)?;
+    if let Some((owned, owner)) = to
+        .ancestors()
+        .filter(|p| !p.as_os_str().is_empty())
+        .find_map(|p| {
+            targets
+                .get(&conflict_key(&p.to_string_lossy(), case_insensitive))
+                .map(|owner| (p, owner))
+        })
+    {
+        bail!(
+            "Conflict: '{}' is already managed by overlay '{}'",
+            owned.display(),
+            owner
+        );
+    }
+    let from_path = target.join(&from);
+    let to_path = target.join(&to);
+    if to_path.exists() || to_path.is_symlink() {
+        bail!("'{}' already exists in the repository", to.display());
+    }
+
+    if let Some(parent) = to_path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+    let entry = &mut state.files[index];
+    if entry.link_type == LinkType::Symlink && entry.entry_type != EntryType::Symlink {
+        // Relative links have to be recomputed for their new directory
+        let link = fs::read_link(&from_path)
+            .with_context(|| format!("Failed to read symlink: {}", from_path.display()))?;
+        let source_path = fs::canonicalize(&from_path).with_context(|| {
+            format!(
+                "'{}' is a broken symlink; run 'repoverlay repair' first",
+                from.display()
+            )
+        })?;
+        let link_source = if link.is_relative() {
+            relative_symlink_target(&source_path, &to_path)
+        } else {
+            source_path
+        };
+        create_symlink(&link_source, &to_path, entry.entry_type)?;
+        remove_symlink(&from_path, entry.entry_type)?;
+    } else {
+        fs::rename(&from_path, &to_path).with_context(|| {
+            format!(
+                "Failed to move {} to {}",
+                from_path.display(),
+                to_path.display()
+            )
+        })?;
+    }
+    remove_empty_parents(&target, &from_path);
+
+    if let Some(backup) = entry.backup.take() {
+        restore_backup(&target, &backup, &from)?;
+    }
+    entry.target.clone_from(&to);
+    let source = entry.source.clone();
+    state.moved.retain(|m| m.source != source);
+    state.moved.push(MovedFile {
+        source,
+        target: to.clone(),
+    });
+    save_remaining_entries(&target, &name, &state)?;
+
+    status!(
+        "{} Moved {} -> {} ({})",
+        "✓".green().bold(),
+        from.display(),
+        to.display(),
+        state.name
+    );
+    Ok(())
+}
+
+/// Machine-readable status of a single applied overlay (`status --json`).
+#[derive(Serialize)]
+struct OverlayStatusJson<'a> {
+    name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<&'a str>,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    tags: &'a [String],
+    source: &'a OverlaySource,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    prefix: Option<&'a Path>,
+    applied_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "str::is_empty")]
+    created_with_version: &'a str,
+    files: Vec<FileStatusJson<'a>>,
+    /// Only reported with `--verify-remote`, for non-local sources.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    remote: Option<RemoteStatusJson>,
+}
+
+/// Machine-readable result of `status --verify-remote` for one overlay.
+#[derive(Serialize)]
+struct RemoteStatusJson {
+    up_to_date: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    latest: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl From<RemoteStatus> for RemoteStatusJson {
+    fn from(status: RemoteStatus) -> Self {
+        match status {
+            RemoteStatus::UpToDate => Self {
+                up_to_date: Some(true),
+                latest: None,
+                error: None,
+            },
+            RemoteStatus::Behind { latest } => Self {
+                up_to_date: Some(false),
+                latest: Some(latest),
+                error: None,
+            },
+            RemoteStatus::Unknown(error) => Self {
+                up_to_date: None,
+                latest: None,
+                error: Some(error),
+            },
+        }
+    }
+}
+
+/// Whether an applied overlay matches the latest version of its source.
+enum RemoteStatus {
+    UpToDate,
+    /// A newer commit (or archive SHA-256) is available
+    Behind {
+        latest: String,
+    },
+    /// The remote could not be checked
+    Unknown(String),
+}
+
+/// Run an overlay hook command through the shell in the target repository.
+///
+/// Output goes straight to the terminal. The overlay name and target path are
+/// passed in `REPOVERLAY_OVERLAY` and `REPOVERLAY_TARGET`.
+fn run_hook(target: &Path, overlay: &str, kind: &str, command: &str) -> Result<()> {
+    status!("{} {kind} hook: {command}", "Running".green().bold());
+    #[cfg(unix)]
+    let mut cmd = {
+        let mut cmd = std::process::Command::new("sh");
+        cmd.arg("-c").arg(command);
+        cmd
+    };
+    #[cfg(windows)]
+    let mut cmd = {
+        let mut cmd = std::process::Command::new("cmd");
+        cmd.arg("/C").arg(command);
+        cmd
+    };
+    let status = cmd
+        .current_dir(target)
+        .env("REPOVERLAY_OVERLAY", overlay)
+        .env("REPOVERLAY_TARGET", target)
+        .status()
+        .with_context(|| format!("Failed to run {kind} hook for '{overlay}'"))?;
+    if !status.success() {
+        bail!("{kind} hook for '{overlay}' failed ({status})");
+    }
+    Ok(())
+}
+
+/// Record in cache metadata which commit a GitHub overlay was applied at.
+///
+/// Lets `cache gc --keep-refs` keep the commit after its branch moves on.
+/// Best effort: the overlay is already applied.
+fn record_cache_pin(target: &Path, state: &OverlayState) {
+    let OverlaySource::GitHub {
+        url,
+        owner,
+        repo,
+        git_ref,
+        commit,
+        ..
+    } = &state.source
+    else {
+        return;
+    };
+    let pin = CachePin {
+        target: target.to_path_buf(),
+        overlay: state.name.clone(),
+        git_ref: git_ref.clone(),
+        commit: commit.clone(),
+    };
+    if let Err(e) = CacheManager::new()
+        .and_then(|cache| cache.record_pin(&crate::github::host_from_url(url), owner, repo, pin))
+    {
+        debug!("failed to record cache pin for {}: {e}", state.name);
+    }
+}
+
+/// Check an overlay's source for a newer version than the one applied.
+///
+/// GitHub sources are fetched and compared with the applied commit. Overlay
+/// repo sources are compared with the overlay repo checkout, and archives are
+/// re-downloaded and hashed. Returns `None` for local overlays.
+fn check_remote_status(source: &OverlaySource) -> Option<RemoteStatus> {
+    if matches!(source, OverlaySource::Local { .. }) {
+        return None;
+    }
+    Some(match newer_remote_version(source) {
+        Ok(None) => RemoteStatus::UpToDate,
+        Ok(Some(latest)) => RemoteStatus::Behind { latest },
+        Err(e) => RemoteStatus::Unknown(format!("{e:#}")),
+    })
+}
+
+/// The newer commit or archive hash available for a source, if any.
+fn newer_remote_version(source: &OverlaySource) -> Result<Option<String>> {
+    match source {
+        OverlaySource::Local { .. } => Ok(None),
+        OverlaySource::GitHub { commit, .. } => {
+            let github = github_source(source).context("Invalid GitHub source")?;
+            let cache = CacheManager::new()?;
+            let repo_path = cache.repo_path(&github);
+            if !repo_path.exists() {
+                bail!("repository is not cached (run 'repoverlay repair' or 'update')");
+            }
+            // The cache may also have moved past the applied commit already
+            let latest = match cache.check_for_updates(&github)? {
+                Some(latest) => latest,
+                None => cache.get_current_commit(&repo_path)?,
+            };
+            Ok((latest != *commit).then_some(latest))
+        }
+        OverlaySource::OverlayRepo { commit, .. } => {
+            let manager = overlay_repo::OverlayRepoManager::configured(
+                &config::load_config(None)?,
+                source.source_filter(),
+            )?
+            .context("Overlay repository not configured")?;
+            let latest = manager.get_current_commit()?;
+            Ok((latest != *commit).then_some(latest))
+        }
+        OverlaySource::Archive { url, sha256 } => {
+            let archive = ArchiveSource::parse(url)?;
+            CacheManager::new()?.check_archive_for_updates(&archive, sha256)
+        }
+    }
+}
+
+/// Print the `Remote:` line of `status --verify-remote`.
+fn print_remote_status(status: &RemoteStatus) {
+    match status {
+        RemoteStatus::UpToDate => println!("    Remote:  {}", "up to date".green()),
+        RemoteStatus::Behind { latest } => {
+            let short = &latest[..12.min(latest.len())];
+            println!(
+                "    Remote:  {} (latest: {short})",
+                "update available".yellow()
+            );
+        }
+        RemoteStatus::Unknown(e) => {
+            println!("    Remote:  {} ({e})", "could not check".yellow());
+        }
+    }
+}
+
+/// Machine-readable status of a single overlay file entry.
+#[derive(Serialize)]
+struct FileStatusJson<'a> {
+    target: &'a Path,
+    link_type: LinkType,
+    entry_type: EntryType,
+    present: bool,
+    /// Only reported with `--check-drift`, for entries that have a recorded hash.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    modified: Option<bool>,
+    state: FileState,
+}
+
+/// What is at an overlay entry's target path compared to what apply left there.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum FileState {
+    /// As applied
+    Ok,
+    /// Present, but a symlinked entry is no longer a symlink into the overlay
+    /// (replaced by a regular file, or re-pointed somewhere else)
+    Diverged,
+    /// A symlink into the overlay whose source no longer exists, e.g. after
+    /// the cached clone was removed
+    Broken,
+    /// Nothing at the target path
+    Missing,
+}
+
+/// Check an entry's target path without resolving the overlay source.
+///
+/// A symlinked entry is a symlink ending in the entry's source path, since
+/// the source root itself may only be known after fetching, and it must
+/// still resolve. Symlinks kept from the source only need to still be
+/// symlinks.
+fn file_state(target: &Path, entry: &FileEntry) -> FileState {
+    let path = target.join(&entry.target);
+    if !path.exists() && !path.is_symlink() {
+        return FileState::Missing;
+    }
+    if entry.link_type == LinkType::Copy {
+        return FileState::Ok;
+    }
+    let Ok(link) = fs::read_link(&path) else {
+        return FileState::Diverged;
+    };
+    if entry.entry_type == EntryType::Symlink {
+        FileState::Ok
+    } else if !link.ends_with(&entry.source) {
+        FileState::Diverged
+    } else if path.exists() {
+        FileState::Ok
+    } else {
+        FileState::Broken
+    }
+}
+
+/// Check whether a copied file no longer matches the hash recorded at apply time.
+///
+/// Returns `None` when no hash was recorded for the entry or the file is missing.
+fn file_drifted(target: &Path, entry: &FileEntry) -> Result<Option<bool>> {
+    let Some(expected) = &entry.sha256 else {
+        return Ok(None);
+    };
+    let path = target.join(&entry.target);
+    if !path.is_file() {
+        return Ok(None);
+    }
+    Ok(Some(sha256_file(&path)? != *expected))
+}
+
+/// Show the status of applied overlays.
+///
+/// With `json`, prints a single JSON array describing each overlay instead of
+/// the colored human-readable output. With `check_drift`, copied files are
+/// re-hashed and any that were edited since apply are reported. With
+/// `verify_remote`, remote sources are fetched to report whether a newer
+/// version is available. With `broken`, only overlays with a missing or
+/// diverged file are shown.
+///
+/// Returns `true` if `broken` is set and any broken overlay was shown.
+#[allow(clippy::fn_params_excessive_bools)]
+pub(crate) fn show_status(
+    target: &Path,
+    filter_name: Option<String>,
+    json: bool,
+    check_drift: bool,
+    verify_remote: bool,
+    broken: bool,
+) -> Result<bool> {
+    let target = canonicalize_path(target, "Target directory")?;
+
+    if json {
+        return show_status_json(&target, filter_name, check_drift, verify_remote, broken);
+    }
+
+    let overlays_dir = target.join(STATE_DIR).join(OVERLAYS_DIR);
+
+    if !overlays_dir.exists() {
+        println!("{} No overlays are currently applied.", "Status:".bold());
+        return Ok(false);
+    }
+
+    let applied_overlays = list_applied_overlays(&target)?;
+    if applied_overlays.is_empty() {
+        println!("{} No overlays are currently applied.", "Status:".bold());
+        return Ok(false);
+    }
+
+    // If filtering by name, show just that overlay
+    if let Some(filter) = filter_name {
+        let normalized = normalize_overlay_name(&filter)?;
+
+        if !applied_overlays.contains(&normalized) {
+            bail!(
+                "Overlay '{}' is not applied. Available: {}",
+                filter,
+                applied_overlays.join(", ")
+            );
+        }
+
+        if broken && !overlay_is_broken(&target, &normalized)? {
+            println!(
+                "{} Overlay '{}' has no missing or diverged files",
+                "✓".green().bold(),
+                normalized
+            );
+            return Ok(false);
+        }
+
+        let drifted = show_single_overlay_status(&target, &normalized, check_drift, verify_remote)?;
+        if check_drift {
+            print_drift_report(&[(normalized, drifted)]);
+        }
+        return Ok(broken);
+    }
+
+    let shown = if broken {
+        let mut shown = Vec::new();
+        for overlay_name in &applied_overlays {
+            if overlay_is_broken(&target, overlay_name)? {
+                shown.push(overlay_name.clone());
+            }
+        }
+        if shown.is_empty() {
+            println!(
+                "{} No overlays have missing or diverged files ({} overlay(s) applied)",
+                "✓".green().bold(),
+                applied_overlays.len()
+            );
+            return Ok(false);
+        }
+        println!(
+            "{} ({} of {} overlay(s) broken)",
+            "Overlay Status".bold(),
+            shown.len(),
+            applied_overlays.len()
+        );
+        shown
+    } else {
+        println!(
+            "{} ({} overlay(s) applied)",
+            "Overlay Status".bold(),
+            applied_overlays.len()
+        );
+        applied_overlays
+    };
+    println!();
+
+    let mut drift = Vec::new();
+    for overlay_name in &shown {
+        let drifted =
+            show_single_overlay_status(&target, overlay_name, check_drift, verify_remote)?;
+        drift.push((overlay_name.clone(), drifted));
+        println!();
+    }
+
+    if check_drift {
+        print_drift_report(&drift);
+    }
+
+    Ok(broken)
+}
+
+/// Whether any of an applied overlay's files is missing or diverged.
+fn overlay_is_broken(target: &Path, name: &str) -> Result<bool> {
+    let state = load_overlay_state(target, name)?;
+    Ok(state
+        .file_entries()
+        .iter()
+        .any(|entry| file_state(target, entry) != FileState::Ok))
+}
+
+/// Print the paths of copied files that were modified since apply.
+fn print_drift_report(drift: &[(String, Vec<PathBuf>)]) {
+    let total: usize = drift.iter().map(|(_, paths)| paths.len()).sum();
+    if total == 0 {
+        println!("{} No drift detected in copied files", "✓".green().bold());
+        return;
+    }
+
+    println!(
+        "{} {} copied file(s) modified since apply:",
+        "Drift:".yellow().bold(),
+        total
+    );
+    for (overlay_name, paths) in drift {
+        for path in paths {
+            println!("  {} {}", overlay_name.cyan(), path.display());
+        }
+    }
+    println!(
+        "\nUse 'repoverlay diff' to review changes or 'repoverlay sync <name>' to save them to the overlay repo."
+    );
+}
+
+/// Print the status of applied overlays as a JSON array.
+///
+/// Returns `true` if `broken` is set and any broken overlay was printed.
+fn show_status_json(
+    target: &Path,
+    filter_name: Option<String>,
+    check_drift: bool,
+    verify_remote: bool,
+    broken: bool,
+) -> Result<bool> {
+    let applied_overlays = list_applied_overlays(target)?;
+
+    let mut overlay_names = if let Some(filter) = filter_name {
+        let normalized = normalize_overlay_name(&filter)?;
+        if !applied_overlays.contains(&normalized) {
+            bail!(
+                "Overlay '{}' is not applied. Available: {}",
+                filter,
+                applied_overlays.join(", ")
+            );
+        }
+        vec![normalized]
+    } else {
+        applied_overlays
+    };
+    if broken {
+        let mut broken_names = Vec::new();
+        for name in overlay_names {
+            if overlay_is_broken(target, &name)? {
+                broken_names.push(name);
+            }
+        }
+        overlay_names = broken_names;
+    }
+
+    let states = overlay_names
+        .iter()
+        .map(|name| load_overlay_state(target, name))
+        .collect::<Result<Vec<_>>>()?;
+
+    let statuses = states
+        .iter()
+        .map(|state| {
+            Ok(OverlayStatusJson {
+                name: &state.name,
+                description: state.description.as_deref(),
+                tags: &state.tags,
+                source: &state.source,
+                prefix: state.prefix.as_deref(),
+                applied_at: state.applied_at,
+                created_with_version: &state.created_with_version,
+                files: state
+                    .file_entries()
+                    .iter()
+                    .map(|entry| {
+                        let target_path = target.join(&entry.target);
+                        Ok(FileStatusJson {
+                            target: &entry.target,
+                            link_type: entry.link_type,
+                            entry_type: entry.entry_type,
+                            present: target_path.exists() || target_path.is_symlink(),
+                            modified: if check_drift {
+                                file_drifted(target, entry)?
+                            } else {
+                                None
+                            },
+                            state: file_state(target, entry),
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?,
+                remote: if verify_remote {
+                    check_remote_status(&state.source).map(RemoteStatusJson::from)
+                } else {
+                    None
+                },
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    println!(
+        "{}",
+        serde_json::to_string(&statuses).context("Failed to serialize overlay status")?
+    );
+    Ok(broken && !statuses.is_empty())
+}
+
+/// Print the source lines shared by `status` and `info`.
+fn print_source_details(source: &OverlaySource) {
+    match source {
+        OverlaySource::Local { path } => {
+            println!("    Source:  {}", path.display());
+        }
+        OverlaySource::GitHub {
+            url,
+            git_ref,
+            commit,
+            subpath,
+            ..
+        } => {
+            println!("    Source:  {} {}", url, "(GitHub)".dimmed());
+            match pinned_ref(source) {
+                Some((kind, _)) => {
+                    println!("    Ref:     {git_ref} {}", format!("({kind})").dimmed());
+                }
+                None => println!("    Ref:     {git_ref}"),
+            }
+            let short_commit = &commit[..12.min(commit.len())];
+            println!("    Commit:  {short_commit}");
+            if let Some(sp) = subpath {
+                println!("    Subpath: {sp}");
+            }
+        }
+        OverlaySource::OverlayRepo {
+            org,
+            repo,
+            name: overlay_name,
+            commit,
+            resolved_via,
+            source_name,
+        } => {
+            let via_upstream = matches!(resolved_via, Some(state::ResolvedVia::Upstream));
+            let via_str = if via_upstream {
+                format!(" {}", "(via upstream)".yellow())
+            } else {
+                String::new()
+            };
+            println!("    Source:  {org}/{repo}/{overlay_name}{via_str}");
+            let short_commit = &commit[..12.min(commit.len())];
+            println!("    Commit:  {short_commit}");
+            if let Some(source) = source_name {
+                println!("    From:    {}", source.cyan());
+            }
+        }
+        OverlaySource::Archive { url, sha256 } => {
+            println!("    Source:  {} {}", url, "(archive)".dimmed());
+            let short_sha = &sha256[..12.min(sha256.len())];
+            println!("    SHA256:  {short_sha}");
+        }
+    }
+}
+
+/// Show what an overlay source contains without applying it.
+///
+/// Resolves the source the same way as `apply` (fetching GitHub sources into
+/// the cache if needed) but never touches a target repository.
+pub(crate) fn show_overlay_info(
+    source_str: &str,
+    ref_override: Option<&str>,
+    source_filter: Option<&str>,
+) -> Result<()> {
+    let resolved = resolve_source(source_str, ref_override, false, None, source_filter)?;
+    let source = &resolved.path;
+    let config = resolved.load_config()?;
+    let name = resolved.default_name(&config);
+
+    println!("  {} {}", "Overlay:".bold(), name.cyan());
+    if let Some(description) = &config.overlay.description {
+        println!("    About:   {description}");
+    }
+    if !config.overlay.tags.is_empty() {
+        println!("    Tags:    {}", config.overlay.tags.join(", ").magenta());
+    }
+    print_source_details(&resolved.source_info);
+
+    if !config.directories.is_empty() {
+        println!("    {}", "Directories:".bold());
+        for dir in &config.directories {
+            match dir.mode {
+                DirectoryMode::Unit => println!("      {}/", dir.path),
+                DirectoryMode::Merge => println!("      {}/ {}", dir.path, "(merge)".dimmed()),
+            }
+        }
+    }
+
+    if !config.mappings.is_empty() {
+        println!("    {}", "Mappings:".bold());
+        let mut mappings: Vec<_> = config.mappings.iter().collect();
+        mappings.sort();
+        for (from, to) in mappings {
+            println!("      {from} -> {to}");
+        }
+    }
+
+    let files: Vec<PathBuf> = WalkDir::new(resolved.walk_root())
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| e.file_name() != ".git")
+        .filter_map(std::result::Result::ok)
+        .filter(|e| e.file_type().is_file() || (e.depth() > 0 && e.path_is_symlink()))
+        .filter_map(|e| e.path().strip_prefix(source).ok().map(Path::to_path_buf))
+        .filter(|rel| {
+            rel != Path::new(CONFIG_FILE) && rel != Path::new(".repoverlay-cache-meta.ccl")
+        })
+        .collect();
+
+    println!("    {} {}", "Files:".bold(), files.len());
+    for file in &files {
+        let rel_str = file.to_string_lossy().replace('\\', "/");
+        if let Some(dest) = config.mappings.get(&rel_str) {
+            println!("      {rel_str} {}", format!("-> {dest}").dimmed());
+        } else {
+            println!("      {rel_str}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Show status for a single overlay.
+///
+/// Returns the copied files whose content changed since apply when
+/// `check_drift` is set.
+pub(crate) fn show_single_overlay_status(
+    target: &Path,
+    name: &str,
+    check_drift: bool,
+    verify_remote: bool,
+) -> Result<Vec<PathBuf>> {
+    let state = load_overlay_state(target, name)?;
+
+    println!("  {} {}", "Overlay:".bold(), state.name.cyan());
+    if let Some(description) = &state.description {
+        println!("    About:   {description}");
+    }
+    if !state.tags.is_empty() {
+        println!("    Tags:    {}", state.tags.join(", ").magenta());
+    }
+
+    print_source_details(&state.source);
+    if let Some(prefix) = &state.prefix {
+        println!("    Prefix:  {}", prefix.display());
+    }
+
+    println!(
+        "    Applied: {}",
+        state.applied_at.format("%Y-%m-%d %H:%M:%S UTC")
+    );
+    if !state.created_with_version.is_empty() {
+        println!(
+            "    {}",
+            format!("Managed by repoverlay {}", state.created_with_version).dimmed()
+        );
+    }
+    if verify_remote && let Some(status) = check_remote_status(&state.source) {
+        print_remote_status(&status);
+    }
+    println!("    Files:   {}", state.file_count());
+
+    let mut drifted = Vec::new();
+    for entry in state.file_entries() {
+        let modified = check_drift && file_drifted(target, entry)? == Some(true);
+        let file_state = file_state(target, entry);
+        let status = match file_state {
+            _ if modified => "~".yellow(),
+            FileState::Ok => "✓".green(),
+            FileState::Diverged => "⚠".yellow(),
+            FileState::Broken | FileState::Missing => "✗".red(),
+        };
+
+        let type_str = match entry.link_type {
+            LinkType::Symlink => "symlink",
+            LinkType::Copy => "copy",
+        };
+
+        // Add trailing slash and [dir] marker for directories
+        let (path_display, dir_marker) = match entry.entry_type {
+            EntryType::Directory => (format!("{}/", entry.target.display()), " [dir]"),
+            EntryType::Symlink => (entry.target.display().to_string(), " [link]"),
+            EntryType::File => (entry.target.display().to_string(), ""),
+        };
+
+        let state_marker = if modified {
+            " modified"
+        } else if file_state == FileState::Diverged {
+            " diverged"
+        } else if file_state == FileState::Broken {
+            " broken"
+        } else {
+            ""
+        };
+
+        println!(
+            "      {} {}{} ({}){}",
+            status,
+            path_display,
+            dir_marker.magenta(),
+            type_str.dimmed(),
+            state_marker.yellow()
+        );
+
+        if modified {
+            drifted.push(entry.target.clone());
+        }
+    }
+
+    Ok(drifted)
+}
+
+/// Show differences between applied overlay files and their source.
+///
+/// Symlinked entries are in sync as long as they point at the source file.
+/// Copied entries are compared line by line and printed as a unified diff.
+///
+/// Returns `true` if any applied file differs from its overlay source.
+pub(crate) fn diff_overlays(target: &Path, filter_name: Option<String>) -> Result<bool> {
+    debug!(
+        "diff_overlays: target={}, filter_name={:?}",
+        target.display(),
+        filter_name
+    );
+    let target = canonicalize_path(target, "Target directory")?;
+
+    let applied_overlays = list_applied_overlays(&target)?;
+    if applied_overlays.is_empty() {
+        println!("{} No overlays are currently applied.", "Status:".bold());
+        return Ok(false);
+    }
+
+    let overlays_to_diff = if let Some(filter) = filter_name {
+        let normalized = normalize_overlay_name(&filter)?;
+        if !applied_overlays.contains(&normalized) {
+            bail!(
+                "Overlay '{}' is not applied. Available: {}",
+                filter,
+                applied_overlays.join(", ")
+            );
+        }
+        vec![normalized]
+    } else {
+        applied_overlays
+    };
+
+    let mut differing = 0;
+    for overlay_name in &overlays_to_diff {
+        let state = load_overlay_state(&target, overlay_name)?;
+        let resolved = resolve_source(
+            &state.source.source_string(),
+            state.source.ref_override(),
+            false,
+            Some(&target),
+            state.source.source_filter(),
+        )?;
+
+        println!("{} {}", "Overlay:".bold(), state.name.cyan());
+
+        // Rendered files are compared against the template rendered with the same variables
+        let template_vars = if state.file_entries().iter().any(|e| e.rendered) {
+            let config = load_overlay_config(&resolved.path)?;
+            Some(template::merge_variables(
+                &config.variables,
+                &state.variables,
+            ))
+        } else {
+            None
+        };
+
+        for entry in state.file_entries() {
+            let source_path = resolved.path.join(&entry.source);
+            let target_path = target.join(&entry.target);
+
+            if entry.link_type == LinkType::Symlink {
+                if diff_symlink_entry(&entry.target, &source_path, &target_path) {
+                    differing += 1;
+                }
+                continue;
+            }
+
+            match entry.entry_type {
+                EntryType::File => {
+                    let variables = template_vars.as_ref().filter(|_| entry.rendered);
+                    if diff_copied_file(&entry.target, &source_path, &target_path, variables)? {
+                        differing += 1;
+                    }
+                }
+                EntryType::Symlink => {
+                    if diff_preserved_link(&entry.target, &source_path, &target_path) {
+                        differing += 1;
+                    }
+                }
+                EntryType::Directory => {
+                    for file in WalkDir::new(&source_path)
+                        .into_iter()
+                        .filter_map(std::result::Result::ok)
+                        .filter(|e| e.file_type().is_file())
+                    {
+                        let rel = file.path().strip_prefix(&source_path)?;
+                        if diff_copied_file(
+                            &entry.target.join(rel),
+                            file.path(),
+                            &target_path.join(rel),
+                            None,
+                        )? {
+                            differing += 1;
+                        }
+                    }
+                }
+            }
+        }
+        println!();
+    }
+
+    if differing == 0 {
+        println!(
+            "{} All applied files match their overlay source",
+            "✓".green().bold()
+        );
+    } else {
+        println!(
+            "{} {} file(s) differ from their overlay source",
+            "✗".red().bold(),
+            differing
+        );
+    }
+
+    Ok(differing > 0)
+}
+
+/// Report whether a symlinked entry still points at its overlay source.
+///
+/// Returns `true` if the entry is out of sync.
+fn diff_symlink_entry(display_path: &Path, source_path: &Path, target_path: &Path) -> bool {
+    match fs::read_link(target_path) {
+        Ok(link) if symlink_points_to(&link, target_path, source_path) => {
+            println!(
+                "  {} {} {}",
+                "✓".green(),
+                display_path.display(),
+                "in sync (symlink)".dimmed()
+            );
+            false
+        }
+        Ok(link) => {
+            println!(
+                "  {} {} {}",
+                "✗".red(),
+                display_path.display(),
+                format!(
+                    "symlink points to {} (expected {})",
+                    link.display(),
+                    source_path.display()
+                )
+                .yellow()
+            );
+            true
+        }
+        Err(_) if target_path.exists() => {
+            println!(
+                "  {} {} {}",
+                "✗".red(),
+                display_path.display(),
+                "replaced by a regular file (expected symlink)".yellow()
+            );
+            true
+        }
+        Err(_) => {
+            println!(
+                "  {} {} {}",
+                "✗".red(),
+                display_path.display(),
+                "missing".yellow()
+            );
+            true
+        }
+    }
+}
+
+/// Report whether a symlink preserved from the source still has the same link path.
+///
+/// Returns `true` if the entry is out of sync.
+fn diff_preserved_link(display_path: &Path, source_path: &Path, target_path: &Path) -> bool {
+    let Ok(expected) = fs::read_link(source_path) else {
+        println!(
+            "  {} {} {}",
+            "✗".red(),
+            display_path.display(),
+            "no longer a symlink in overlay source".yellow()
+        );
+        return true;
+    };
+    match fs::read_link(target_path) {
+        Ok(link) if link == expected => {
+            println!(
+                "  {} {} {}",
+                "✓".green(),
+                display_path.display(),
+                "in sync (link)".dimmed()
+            );
+            false
+        }
+        Ok(link) => {
+            println!(
+                "  {} {} {}",
+                "✗".red(),
+                display_path.display(),
+                format!(
+                    "link points to {} (expected {})",
+                    link.display(),
+                    expected.display()
+                )
+                .yellow()
+            );
+            true
+        }
+        Err(_) => {
+            println!(
+                "  {} {} {}",
+                "✗".red(),
+                display_path.display(),
+                "missing".yellow()
+            );
+            true
+        }
+    }
+}
+
+/// Compare a copied file against its overlay source, printing a unified diff.
+///
+/// Returns `true` if the files differ.
+fn diff_copied_file(
+    display_path: &Path,
+    source_path: &Path,
+    target_path: &Path,
+    variables: Option<&BTreeMap<String, String>>,
+) -> Result<bool> {
+    if !target_path.exists() {
+        println!(
+            "  {} {} {}",
+            "✗".red(),
+            display_path.display(),
+            "missing".yellow()
+        );
+        return Ok(true);
+    }
+    if !source_path.exists() {
+        println!(
+            "  {} {} {}",
+            "✗".red(),
+            display_path.display(),
+            "no longer in overlay source".yellow()
+        );
+        return Ok(true);
+    }
+
+    let rendered = match variables {
+        Some(variables) => template::render_file(source_path, variables)?,
+        None => None,
+    };
+    let source_bytes = match rendered {
+        Some(content) => content.into_bytes(),
+        None => fs::read(source_path)
+            .with_context(|| format!("Failed to read: {}", source_path.display()))?,
+    };
+    let target_bytes = fs::read(target_path)
+        .with_context(|| format!("Failed to read: {}", target_path.display()))?;
+
+    if source_bytes == target_bytes {
+        println!(
+            "  {} {} {}",
+            "✓".green(),
+            display_path.display(),
+            "in sync (copy)".dimmed()
+        );
+        return Ok(false);
+    }
+
+    println!(
+        "  {} {} {}",
+        "✗".red(),
+        display_path.display(),
+        "modified".yellow()
+    );
+
+    let (Ok(source_text), Ok(target_text)) = (
+        String::from_utf8(source_bytes),
+        String::from_utf8(target_bytes),
+    ) else {
+        println!("    Binary files differ");
+        return Ok(true);
+    };
+
+    let path_str = display_path.to_string_lossy().replace('\\', "/");
+    for line in unified_diff(&source_text, &target_text, &path_str) {
+        let colored_line = if line.starts_with("---") || line.starts_with("+++") {
+            line.bold()
+        } else if line.starts_with("@@") {
+            line.cyan()
+        } else if line.starts_with('-') {
+            line.red()
+        } else if line.starts_with('+') {
+            line.green()
+        } else {
+            line.normal()
+        };
+        println!("    {colored_line}");
+    }
+
+    Ok(true)
+}
+
+/// Build a unified diff between overlay source content and applied content.
+///
+/// Lines are returned without trailing newlines.
+fn unified_diff(source: &str, applied: &str, path: &str) -> Vec<String> {
+    let source_lines: Vec<&str> = source.lines().collect();
+    let applied_lines: Vec<&str> = applied.lines().collect();
+
+    difflib::unified_diff(
+        &source_lines,
+        &applied_lines,
+        &format!("a/{path}"),
+        &format!("b/{path}"),
+        "",
+        "",
+        3,
+    )
+    .into_iter()
+    .map(|line| {
+        line.strip_suffix("\t\n")
+            .or_else(|| line.strip_suffix('\n'))
+            .unwrap_or(&line)
+            .to_string()
+    })
+    .collect()
+}
+
+/// Recreate dangling or missing symlinks for applied overlays.
+///
+/// Each symlinked entry whose link no longer resolves is re-pointed at the
+/// overlay's current source location (cache, overlay repo, or local path).
+/// Sources are only re-resolved for overlays that have broken links, and
+/// cached sources are reused unless the cache is missing entirely.
+pub(crate) fn repair_overlays(target: &Path, filter_name: Option<String>) -> Result<()> {
+    debug!(
+        "repair_overlays: target={}, filter_name={:?}",
+        target.display(),
+        filter_name
+    );
+    let target = canonicalize_path(target, "Target directory")?;
 
-/// Show status for a single overlay.
-pub(crate) fn show_single_overlay_status(target: &Path, name: &str) -> Result<()> {
-    let state = load_overlay_state(target, name)?;
+    let applied_overlays = list_applied_overlays(&target)?;
+    if applied_overlays.is_empty() {
+        status!("{} No overlays are currently applied.", "Status:".bold());
+        return Ok(());
+    }
 
-    println!("  {} {}", "Overlay:".bold(), state.name.cyan());
+    let overlays_to_repair = if let Some(filter) = filter_name {
+        let normalized = normalize_overlay_name(&filter)?;
+        if !applied_overlays.contains(&normalized) {
+            bail!(
+                "Overlay '{}' is not applied. Available: {}",
+                filter,
+                applied_overlays.join(", ")
+            );
+        }
+        vec![normalized]
+    } else {
+        applied_overlays
+    };
 
-    // Display source based on type
-    match &state.source {
-        OverlaySource::Local { path } => {
-            println!("    Source:  {}", path.display());
+    let mut repaired = 0;
+    for overlay_name in &overlays_to_repair {
+        let state = load_overlay_state(&target, overlay_name)?;
+
+        // A path that exists resolves through its symlink; anything else
+        // (dangling link or missing path) needs to be recreated.
+        let broken: Vec<&FileEntry> = state
+            .file_entries()
+            .iter()
+            .filter(|e| e.link_type == LinkType::Symlink && !target.join(&e.target).exists())
+            .collect();
+
+        if broken.is_empty() {
+            continue;
         }
-        OverlaySource::GitHub {
-            url,
-            git_ref,
-            commit,
-            subpath,
-            ..
-        } => {
-            println!("    Source:  {} {}", url, "(GitHub)".dimmed());
-            println!("    Ref:     {git_ref}");
-            let short_commit = &commit[..12.min(commit.len())];
-            println!("    Commit:  {short_commit}");
-            if let Some(sp) = subpath {
-                println!("    Subpath: {sp}");
+
+        status!("{} overlay: {}", "Repairing".green().bold(), state.name);
+
+        let resolved = resolve_source(
+            &state.source.source_string(),
+            state.source.ref_override(),
+            false,
+            Some(&target),
+            state.source.source_filter(),
+        )?;
+        let relative_links = use_relative_symlinks(&state.source, &resolved.path, &target)?;
+
+        for entry in broken {
+            let source_path = resolved.path.join(&entry.source);
+            let link_path = target.join(&entry.target);
+
+            if !source_path.exists() {
+                eprintln!(
+                    "  {} Source no longer exists, skipping: {}",
+                    "Warning:".yellow(),
+                    source_path.display()
+                );
+                continue;
             }
-        }
-        OverlaySource::OverlayRepo {
-            org,
-            repo,
-            name: overlay_name,
-            commit,
-            resolved_via,
-            source_name,
-        } => {
-            let via_upstream = matches!(resolved_via, Some(state::ResolvedVia::Upstream));
-            let via_str = if via_upstream {
-                format!(" {}", "(via upstream)".yellow())
+
+            if link_path.is_symlink() {
+                remove_symlink(&link_path, entry.entry_type)?;
+            }
+            if let Some(parent) = link_path.parent() {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+            }
+
+            let link_source = if relative_links {
+                relative_symlink_target(&source_path, &link_path)
             } else {
-                String::new()
+                source_path.clone()
             };
-            println!("    Source:  {org}/{repo}/{overlay_name}{via_str}");
-            let short_commit = &commit[..12.min(commit.len())];
-            println!("    Commit:  {short_commit}");
-            if let Some(source) = source_name {
-                println!("    From:    {}", source.cyan());
+            create_symlink(&link_source, &link_path, entry.entry_type)?;
+
+            status!(
+                "  {} {} -> {}",
+                "✓".green(),
+                entry.target.display(),
+                link_source.display()
+            );
+            repaired += 1;
+        }
+    }
+
+    if repaired == 0 {
+        status!("{} No broken symlinks found", "✓".green().bold());
+    } else {
+        status!("\n{} Repaired {} symlink(s)", "✓".green().bold(), repaired);
+    }
+
+    Ok(())
+}
+
+/// Verify that every applied overlay is intact.
+///
+/// Checks that each file entry exists, that symlinks resolve to the expected
+/// overlay source path, and that `.git/info/exclude` still contains the
+/// overlay's section. Returns an error if any problem is found so the
+/// command exits non-zero (for CI and pre-commit hooks).
+pub(crate) fn verify_overlays(target: &Path) -> Result<()> {
+    debug!("verify_overlays: target={}", target.display());
+    let target = canonicalize_path(target, "Target directory")?;
+
+    let applied_overlays = list_applied_overlays(&target)?;
+    if applied_overlays.is_empty() {
+        println!("{} No overlays are currently applied.", "Status:".bold());
+        return Ok(());
+    }
+
+    let exclude_file = exclude_file()?;
+    let exclude_content = exclude_file
+        .and_then(|file| fs::read_to_string(target.join(file)).ok())
+        .unwrap_or_default();
+
+    let mut problems = 0;
+    for overlay_name in &applied_overlays {
+        let state = load_overlay_state(&target, overlay_name)?;
+        let mut overlay_problems: Vec<(String, String)> = Vec::new();
+
+        let has_section = exclude_content
+            .lines()
+            .any(|l| l == exclude_marker_start(overlay_name))
+            && exclude_content
+                .lines()
+                .any(|l| l == exclude_marker_end(overlay_name));
+        if let Some(file) = exclude_file
+            && !has_section
+        {
+            overlay_problems.push((
+                file.to_string(),
+                "overlay section missing from git exclude".to_string(),
+            ));
+        }
+
+        // Only resolve the source when symlinks need checking
+        let needs_source = state
+            .file_entries()
+            .iter()
+            .any(|e| e.link_type == LinkType::Symlink);
+        let source_root = if needs_source {
+            match resolve_source(
+                &state.source.source_string(),
+                state.source.ref_override(),
+                false,
+                Some(&target),
+                state.source.source_filter(),
+            ) {
+                Ok(resolved) => Some(resolved.path),
+                Err(e) => {
+                    overlay_problems.push((
+                        state.source.source_string(),
+                        format!("overlay source could not be resolved: {e}"),
+                    ));
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        for entry in state.file_entries() {
+            let path = target.join(&entry.target);
+            let display = entry.target.display().to_string();
+
+            if entry.link_type == LinkType::Symlink {
+                let Ok(link) = fs::read_link(&path) else {
+                    let problem = if path.exists() {
+                        "expected a symlink but found a regular file"
+                    } else {
+                        "missing"
+                    };
+                    overlay_problems.push((display, problem.to_string()));
+                    continue;
+                };
+                if !path.exists() {
+                    overlay_problems
+                        .push((display, format!("broken symlink to {}", link.display())));
+                    continue;
+                }
+                if let Some(root) = &source_root {
+                    let expected = root.join(&entry.source);
+                    if !symlink_points_to(&link, &path, &expected) {
+                        overlay_problems.push((
+                            display,
+                            format!(
+                                "symlink points to {} (expected {})",
+                                link.display(),
+                                expected.display()
+                            ),
+                        ));
+                    }
+                }
+            } else {
+                // Preserved source symlinks may legitimately dangle
+                let present =
+                    path.exists() || (entry.entry_type == EntryType::Symlink && path.is_symlink());
+                if !present {
+                    overlay_problems.push((display, "missing".to_string()));
+                }
+            }
+        }
+
+        println!("{} {}", "Overlay:".bold(), state.name.cyan());
+        if overlay_problems.is_empty() {
+            println!("  {} {} file(s) OK", "✓".green(), state.file_count());
+        } else {
+            for (path, problem) in &overlay_problems {
+                println!("  {} {} {}", "✗".red(), path, problem.yellow());
             }
         }
+        problems += overlay_problems.len();
     }
 
+    println!();
+    if problems > 0 {
+        bail!("Verification failed: {problems} problem(s) found in applied overlays");
+    }
     println!(
-        "    Applied: {}",
-        state.applied_at.format("%Y-%m-%d %H:%M:%S UTC")
+        "{} All {} overlay(s) verified",
+        "✓".green().bold(),
+        applied_overlays.len()
     );
-    println!("    Files:   {}", state.file_count());
 
-    for entry in state.file_entries() {
-        let target_path = target.join(&entry.target);
-        let status = if target_path.exists() || target_path.is_symlink() {
-            "✓".green()
+    Ok(())
+}
+
+/// Clean up overlay state left inconsistent by manual edits.
+///
+/// Removes the state of overlays whose files are all gone (restoring any
+/// repo files they had backed up), drops git exclude sections of overlays
+/// that have no state, and removes the managed section and the overlay state
+/// in `.repoverlay` once no overlays remain. The per-repo config and any
+/// backups or stashes still holding files are kept.
+pub(crate) fn prune(target: &Path, dry_run: bool) -> Result<()> {
+    debug!("prune: target={}, dry_run={}", target.display(), dry_run);
+    let target = canonicalize_path(target, "Target directory")?;
+    if !target.join(".git").exists() {
+        bail!("Target is not a git repository: {}", target.display());
+    }
+
+    let verb = |action: &str| {
+        if dry_run {
+            format!("Would {}", action.to_lowercase())
         } else {
-            "✗".red()
-        };
+            action.to_string()
+        }
+    };
+    let overlays_dir = target.join(STATE_DIR).join(OVERLAYS_DIR);
+    let mut pruned = 0;
+    let mut remaining = Vec::new();
+
+    for overlay_name in list_applied_overlays(&target)? {
+        let state = load_overlay_state(&target, &overlay_name)?;
+        let entries = state.file_entries();
+        let orphaned = !entries.is_empty()
+            && entries.iter().all(|e| {
+                let path = target.join(&e.target);
+                !path.exists() && !path.is_symlink()
+            });
+        if !orphaned {
+            remaining.push(overlay_name);
+            continue;
+        }
 
-        let type_str = match entry.link_type {
-            LinkType::Symlink => "symlink",
-            LinkType::Copy => "copy",
-        };
+        status!(
+            "  {} {} state for '{}' ({} file(s) missing)",
+            "-".red(),
+            verb("Remove"),
+            overlay_name,
+            entries.len()
+        );
+        pruned += 1;
+        if dry_run {
+            continue;
+        }
 
-        // Add trailing slash and [dir] marker for directories
-        let (path_display, dir_marker) = match entry.entry_type {
-            EntryType::Directory => (format!("{}/", entry.target.display()), " [dir]"),
-            EntryType::File => (entry.target.display().to_string(), ""),
-        };
+        for entry in entries {
+            if let Some(backup) = &entry.backup {
+                restore_backup(&target, backup, &entry.target)?;
+                status!(
+                    "  {} {} {}",
+                    "+".green(),
+                    entry.target.display(),
+                    "(original restored)".dimmed()
+                );
+            }
+        }
+        fs::remove_file(overlays_dir.join(state_file_name(&overlay_name)))?;
+        if let Err(e) = remove_external_state(&target, &overlay_name) {
+            eprintln!(
+                "  {} Could not remove external backup: {}",
+                "Warning:".yellow(),
+                e
+            );
+        }
+    }
 
-        println!(
-            "      {} {}{} ({})",
-            status,
-            path_display,
-            dir_marker.magenta(),
-            type_str.dimmed()
+    let exclude_path = exclude_file()?.map(|file| target.join(file));
+    let original = exclude_path
+        .as_ref()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .unwrap_or_default();
+    let mut content = original.clone();
+    for name in exclude_section_names(&original) {
+        if name == MANAGED_SECTION_NAME || remaining.contains(&name) {
+            continue;
+        }
+        status!(
+            "  {} {} git exclude section for '{}'",
+            "-".red(),
+            verb("Remove"),
+            name
+        );
+        content = remove_overlay_section(&content, &name);
+        pruned += 1;
+    }
+    if remaining.is_empty()
+        && content
+            .lines()
+            .any(|l| l.trim() == exclude_marker_start(MANAGED_SECTION_NAME))
+    {
+        status!(
+            "  {} {} managed git exclude section",
+            "-".red(),
+            verb("Remove")
+        );
+        content = remove_overlay_section(&content, MANAGED_SECTION_NAME);
+        pruned += 1;
+    }
+    if let Some(exclude_path) = exclude_path
+        && !dry_run
+        && content != original
+    {
+        fs::write(&exclude_path, content)
+            .with_context(|| format!("Failed to write {}", exclude_path.display()))?;
+    }
+
+    let state_dir = target.join(STATE_DIR);
+    if remaining.is_empty() && state_dir.is_dir() {
+        let is_empty_dir = |path: &Path| path.read_dir().is_ok_and(|mut d| d.next().is_none());
+        let mut removed = Vec::new();
+        for name in [OVERLAYS_DIR, META_FILE, BACKUPS_DIR, STASH_DIR] {
+            let path = state_dir.join(name);
+            let removable = match name {
+                OVERLAYS_DIR | META_FILE => path.exists(),
+                _ => is_empty_dir(&path),
+            };
+            if !removable {
+                continue;
+            }
+            status!("  {} {} {}/{}", "-".red(), verb("Remove"), STATE_DIR, name);
+            if !dry_run {
+                if path.is_dir() {
+                    fs::remove_dir_all(&path)?;
+                } else {
+                    fs::remove_file(&path)?;
+                }
+            }
+            removed.push(path);
+            pruned += 1;
+        }
+
+        // The directory itself goes only once nothing else is left in it
+        let left = fs::read_dir(&state_dir)?
+            .filter_map(Result::ok)
+            .any(|e| !removed.contains(&e.path()));
+        if !left && !removed.is_empty() {
+            status!("  {} {} {}/", "-".red(), verb("Remove"), STATE_DIR);
+            if !dry_run {
+                fs::remove_dir(&state_dir)?;
+            }
+        }
+    }
+
+    if pruned == 0 {
+        status!("{} Nothing to prune", "✓".green().bold());
+    } else if dry_run {
+        status!(
+            "\n{} Dry run - would prune {} item(s)",
+            "Note:".yellow(),
+            pruned
         );
+    } else {
+        status!("\n{} Pruned {} item(s)", "✓".green().bold(), pruned);
+    }
+
+    Ok(())
+}
+
+/// Names of the overlay sections in git exclude content, in order.
+fn exclude_section_names(content: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for line in content.lines() {
+        if let Some(name) = line
+            .trim()
+            .strip_prefix("# repoverlay:")
+            .and_then(|rest| rest.strip_suffix(" start"))
+            && !names.iter().any(|n| n == name)
+        {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
+
+/// Reapply the source file's permissions (including the executable bit) to a copy.
+///
+/// `fs::copy` normally carries permissions over, but this makes copied
+/// scripts like `.husky/pre-commit` stay executable regardless of how the
+/// copy was made. Windows has no mode bits, so this is a no-op there.
+#[cfg_attr(not(unix), allow(unused_variables, clippy::unnecessary_wraps))]
+fn copy_permissions(source_path: &Path, target_path: &Path) -> Result<()> {
+    #[cfg(unix)]
+    {
+        let permissions = fs::metadata(source_path)
+            .with_context(|| format!("Failed to read permissions: {}", source_path.display()))?
+            .permissions();
+        fs::set_permissions(target_path, permissions)
+            .with_context(|| format!("Failed to set permissions: {}", target_path.display()))?;
     }
+    Ok(())
+}
 
+/// Create a file or directory symlink at `link_path` pointing to `source_path`.
+#[cfg_attr(unix, allow(unused_variables))]
+fn create_symlink(source_path: &Path, link_path: &Path, entry_type: EntryType) -> Result<()> {
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(source_path, link_path)
+        .with_context(|| format!("Failed to create symlink: {}", link_path.display()))?;
+    #[cfg(windows)]
+    match entry_type {
+        EntryType::Directory => std::os::windows::fs::symlink_dir(source_path, link_path)
+            .with_context(|| {
+                format!(
+                    "Failed to create directory symlink: {}",
+                    link_path.display()
+                )
+            })?,
+        EntryType::File | EntryType::Symlink => {
+            std::os::windows::fs::symlink_file(source_path, link_path)
+                .with_context(|| format!("Failed to create symlink: {}", link_path.display()))?
+        }
+    }
     Ok(())
 }
 
+/// Remove a file or directory symlink without following it.
+#[cfg_attr(unix, allow(unused_variables))]
+fn remove_symlink(link_path: &Path, entry_type: EntryType) -> Result<()> {
+    #[cfg(windows)]
+    if entry_type == EntryType::Directory {
+        return fs::remove_dir(link_path).with_context(|| {
+            format!(
+                "Failed to remove directory symlink: {}",
+                link_path.display()
+            )
+        });
+    }
+    fs::remove_file(link_path)
+        .with_context(|| format!("Failed to remove symlink: {}", link_path.display()))
+}
+
 /// Restore overlays after git clean or other removal.
 ///
 /// Uses external state backup (`~/.local/share/repoverlay/applied/`) to recover
 /// overlays that were removed by `git clean -fdx` or similar operations.
 ///
+/// Overlays removed with `repoverlay remove` leave a tombstone and are skipped
+/// unless `include_removed` is set. With `only`, just the named overlays are
+/// restored; otherwise the user picks from the backups when there is a TTY.
+///
 /// # Workflow
 ///
 /// 1. Load external state backup for the target repository
 /// 2. For each saved overlay state, re-apply using original source
-pub(crate) fn restore_overlays(target: &Path, dry_run: bool) -> Result<()> {
+pub(crate) fn restore_overlays(
+    target: &Path,
+    dry_run: bool,
+    jobs: usize,
+    include_removed: bool,
+    only: &[String],
+) -> Result<()> {
     debug!(
-        "restore_overlays: target={}, dry_run={}",
+        "restore_overlays: target={}, dry_run={}, jobs={}, include_removed={}, only={:?}",
         target.display(),
-        dry_run
+        dry_run,
+        jobs,
+        include_removed,
+        only
     );
     let target = canonicalize_path(target, "Target directory")?;
     validate_git_repo(&target)?;
 
-    // Load external state
-    let external_states = load_external_states(&target)?;
-    debug!("found {} external states to restore", external_states.len());
+    // Load external state, plus removed overlays if requested
+    let mut external_states = load_external_states(&target)?;
+    let removed_states = load_removed_external_states(&target)?;
+    debug!(
+        "found {} external states to restore, {} removed",
+        external_states.len(),
+        removed_states.len()
+    );
+
+    let removed_names: Vec<String> = removed_states.iter().map(|s| s.name.clone()).collect();
+    if include_removed {
+        external_states.extend(removed_states);
+    }
 
     if external_states.is_empty() {
-        println!("{} No overlays to restore.", "Status:".bold());
-        println!("  No external backup found for this repository.");
+        status!("{} No overlays to restore.", "Status:".bold());
+        if removed_names.is_empty() {
+            status!("  No external backup found for this repository.");
+        } else {
+            status!(
+                "  {} removed overlay(s) skipped: {} (use --include-removed to restore them)",
+                removed_names.len(),
+                removed_names.join(", ")
+            );
+        }
         return Ok(());
     }
 
-    println!(
+    // Narrow down to the requested overlays, by name or interactively
+    let state_key = |name: &str| normalize_overlay_name(name).unwrap_or_else(|_| name.to_string());
+    if only.is_empty() {
+        if external_states.len() > 1 {
+            let names: Vec<String> = external_states.iter().map(|s| s.name.clone()).collect();
+            let selected = select_overlay_names(&names, "Select overlays to restore".to_string())?;
+            external_states.retain(|s| selected.contains(&s.name));
+        }
+    } else {
+        let wanted: Vec<String> = only.iter().map(|name| state_key(name)).collect();
+        let unknown: Vec<&str> = only
+            .iter()
+            .zip(&wanted)
+            .filter(|(_, key)| !external_states.iter().any(|s| state_key(&s.name) == **key))
+            .map(|(name, _)| name.as_str())
+            .collect();
+        if !unknown.is_empty() {
+            let available: Vec<&str> = external_states.iter().map(|s| s.name.as_str()).collect();
+            bail!(
+                "No backup found for overlay(s): {}. Available: {}",
+                unknown.join(", "),
+                available.join(", ")
+            );
+        }
+        external_states.retain(|s| wanted.contains(&state_key(&s.name)));
+    }
+
+    if external_states.is_empty() {
+        status!("{} No overlays selected.", "Status:".bold());
+        return Ok(());
+    }
+
+    status!(
         "{} {} overlay(s) to restore:",
         "Found".blue().bold(),
         external_states.len()
     );
 
     for state in &external_states {
-        println!("  - {}", state.name);
+        if include_removed && removed_names.contains(&state.name) {
+            status!("  - {} {}", state.name, "(removed)".dimmed());
+        } else {
+            status!("  - {}", state.name);
+        }
         match &state.source {
             OverlaySource::Local { path } => {
-                println!("    Source: {}", path.display());
+                status!("    Source: {}", path.display());
             }
             OverlaySource::GitHub { url, git_ref, .. } => {
-                println!("    Source: {url} ({git_ref})");
+                status!("    Source: {url} ({git_ref})");
             }
             OverlaySource::OverlayRepo {
                 org,
@@ -1099,50 +4461,87 @@ pub(crate) fn restore_overlays(target: &Path, dry_run: bool) -> Result<()> {
                 name: overlay_name,
                 ..
             } => {
-                println!("    Source: {org}/{repo}/{overlay_name} (overlay repo)");
+                status!("    Source: {org}/{repo}/{overlay_name} (overlay repo)");
+            }
+            OverlaySource::Archive { url, .. } => {
+                status!("    Source: {url} (archive)");
             }
         }
     }
 
+    if !include_removed && !removed_names.is_empty() {
+        status!(
+            "  {} removed overlay(s) skipped: {}",
+            removed_names.len(),
+            removed_names.join(", ")
+        );
+    }
+
     if dry_run {
-        println!("\n{} Dry run - no changes made.", "Note:".yellow());
+        status!("\n{} Dry run - no changes made.", "Note:".yellow());
         return Ok(());
     }
 
-    println!();
+    status!();
 
-    // Restore each overlay
-    for state in external_states {
-        let source_str = match &state.source {
-            OverlaySource::Local { path } => path.to_string_lossy().to_string(),
-            OverlaySource::GitHub { url, .. } => url.clone(),
-            OverlaySource::OverlayRepo {
-                org,
-                repo,
-                name: overlay_name,
-                ..
-            } => {
-                format!("{org}/{repo}/{overlay_name}")
-            }
-        };
+    // Fetch GitHub sources concurrently; linking below stays serial
+    let cache = CacheManager::new()?;
+    let github_sources: Vec<GitHubSource> = external_states
+        .iter()
+        .filter_map(|state| github_source(&state.source))
+        .collect();
+    let fetched = fetch_all(&github_sources, jobs, |source| {
+        cache.ensure_cached(source, true).map(|_| ())
+    });
+
+    // Restore each overlay, carrying on past failures
+    let applied = list_applied_overlays(&target)?;
+    let mut restored = Vec::new();
+    let mut failed = Vec::new();
+    let mut already_applied = 0;
+    for state in external_states {
+        if applied.contains(&state_key(&state.name)) {
+            status!(
+                "  {} {} {}",
+                "-".dimmed(),
+                state.name,
+                "(already applied)".dimmed()
+            );
+            already_applied += 1;
+            continue;
+        }
+        let source_str = state.source.source_string();
 
-        let ref_override = match &state.source {
-            OverlaySource::GitHub { git_ref, .. } => Some(git_ref.as_str()),
-            OverlaySource::Local { .. } | OverlaySource::OverlayRepo { .. } => None,
-        };
+        let prefetched = github_source(&state.source).and_then(|s| fetched.get(&fetch_key(&s)));
+        if let Some(Err(e)) = prefetched {
+            eprintln!(
+                "  {} Failed to restore '{}': {}",
+                "Error:".red(),
+                state.name,
+                e
+            );
+            failed.push(state.name);
+            continue;
+        }
 
         // Re-apply the overlay
         match apply_overlay(
             &source_str,
             &target,
-            false, // Use symlinks by default
-            Some(state.name.clone()),
-            ref_override,
-            true,  // Update cache
-            None,  // Use default source resolution for restore
-            false, // Not a dry run
+            &ApplyOptions {
+                name: Some(state.name.clone()),
+                git_ref: state.source.ref_override(),
+                update_cache: prefetched.is_none(), // Update cache unless already fetched
+                variables: state.variables.clone(),
+                only: &state.only,
+                prefix: state.prefix.as_deref(),
+                moved: &state.moved,
+                skip: &state.skipped,
+                unlinked: &state.unlinked,
+                ..Default::default()
+            },
         ) {
-            Ok(()) => {}
+            Ok(()) => restored.push(state.name),
             Err(e) => {
                 eprintln!(
                     "  {} Failed to restore '{}': {}",
@@ -1150,30 +4549,123 @@ pub(crate) fn restore_overlays(target: &Path, dry_run: bool) -> Result<()> {
                     state.name,
                     e
                 );
+                failed.push(state.name);
             }
         }
     }
 
+    let mut summary = vec![format!("Restored {}", restored.len())];
+    if !failed.is_empty() {
+        summary.push(format!("failed {} (see above)", failed.len()));
+    }
+    if already_applied > 0 {
+        summary.push(format!("{already_applied} already applied"));
+    }
+    if !include_removed && !removed_names.is_empty() {
+        summary.push(format!("skipped {} removed", removed_names.len()));
+    }
+    let summary = summary.join(", ");
+    if failed.is_empty() {
+        status!("\n{} {}", "✓".green().bold(), summary);
+        Ok(())
+    } else {
+        status!("\n{} {}", "Status:".bold(), summary);
+        bail!("Failed to restore: {}", failed.join(", "))
+    }
+}
+
+/// Permanently clear the tombstone of a removed overlay.
+///
+/// Afterwards `restore --include-removed` no longer offers the overlay.
+pub(crate) fn forget_removed_overlay(target: &Path, name: &str) -> Result<()> {
+    let target = canonicalize_path(target, "Target directory")?;
+    let normalized_name = normalize_overlay_name(name)?;
+
+    if !forget_external_state(&target, &normalized_name)? {
+        let removed: Vec<String> = load_removed_external_states(&target)?
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+        if removed.is_empty() {
+            bail!("No removed overlay named '{name}'");
+        }
+        bail!(
+            "No removed overlay named '{}'. Removed: {}",
+            name,
+            removed.join(", ")
+        );
+    }
+
+    status!("{} Forgot removed overlay '{}'", "✓".green().bold(), name);
     Ok(())
 }
 
+/// Build the cache source for a GitHub-backed overlay.
+fn github_source(source: &OverlaySource) -> Option<GitHubSource> {
+    match source {
+        OverlaySource::GitHub {
+            owner,
+            repo,
+            git_ref,
+            subpath,
+            url,
+            ..
+        } => Some(GitHubSource {
+            host: github::host_from_url(url),
+            owner: owner.clone(),
+            repo: repo.clone(),
+            git_ref: git_ref.parse().unwrap(),
+            subpath: subpath.as_ref().map(PathBuf::from),
+            single_file: GitHubSource::parse(url).is_ok_and(|s| s.single_file),
+        }),
+        OverlaySource::Local { .. }
+        | OverlaySource::OverlayRepo { .. }
+        | OverlaySource::Archive { .. } => None,
+    }
+}
+
+/// The tag or commit a GitHub overlay is pinned to, if it doesn't track a branch.
+fn pinned_ref(source: &OverlaySource) -> Option<(&'static str, &str)> {
+    match source {
+        OverlaySource::GitHub {
+            git_ref, ref_kind, ..
+        } => match ref_kind {
+            RefKind::Branch => None,
+            RefKind::Tag => Some(("tag", git_ref.as_str())),
+            RefKind::Commit => Some(("commit", &git_ref[..12.min(git_ref.len())])),
+        },
+        _ => None,
+    }
+}
+
 /// Update applied overlays from remote sources.
 ///
-/// Only GitHub-sourced overlays can be updated. Local overlays are skipped.
+/// Only GitHub- and archive-sourced overlays can be updated. Local overlays are skipped.
 ///
 /// # Workflow
 ///
 /// 1. List applied overlays (optionally filtered by name)
-/// 2. For each GitHub overlay, check remote for new commits
+/// 2. For each GitHub overlay, check remote for new commits (archives: a changed SHA-256);
+///    overlays pinned to a tag or commit are skipped unless `force`
 /// 3. Report available updates
-/// 4. If not dry-run, remove and re-apply each overlay with updated cache
+/// 4. If not dry-run, re-apply each overlay with updated cache, putting back
+///    the previous version of any that fail
+/// 5. Summarize what was updated, what failed, and what was already current
 #[allow(clippy::needless_pass_by_value)]
-pub(crate) fn update_overlays(target: &Path, name: Option<String>, dry_run: bool) -> Result<()> {
+pub(crate) fn update_overlays(
+    target: &Path,
+    name: Option<String>,
+    dry_run: bool,
+    force: bool,
+    jobs: usize,
+) -> Result<()> {
     debug!(
-        "update_overlays: target={}, name={:?}, dry_run={}",
+        "update_overlays: target={}, name={:?}, dry_run={}, force={}, jobs={}",
         target.display(),
         name,
-        dry_run
+        dry_run,
+        force,
+        jobs
     );
     let target = canonicalize_path(target, "Target directory")?;
     let overlays_dir = target.join(STATE_DIR).join(OVERLAYS_DIR);
@@ -1204,109 +4696,207 @@ pub(crate) fn update_overlays(target: &Path, name: Option<String>, dry_run: bool
 
     let cache = CacheManager::new()?;
     let mut updates_available = Vec::new();
+    let mut up_to_date = Vec::new();
 
-    // Check for updates
-    for overlay_name in &overlays_to_check {
-        let state = load_overlay_state(&target, overlay_name)?;
+    let states = overlays_to_check
+        .iter()
+        .map(|name| load_overlay_state(&target, name))
+        .collect::<Result<Vec<_>>>()?;
 
-        if let OverlaySource::GitHub {
-            owner,
-            repo,
-            git_ref,
-            commit,
-            subpath,
-            url,
-            ..
-        } = &state.source
-        {
-            let source = GitHubSource {
-                owner: owner.clone(),
-                repo: repo.clone(),
-                git_ref: git_ref.parse().unwrap(),
-                subpath: subpath.as_ref().map(PathBuf::from),
-            };
+    // Check GitHub remotes concurrently, then report in order
+    let github_sources: Vec<GitHubSource> = states
+        .iter()
+        .filter(|state| force || pinned_ref(&state.source).is_none())
+        .filter_map(|state| github_source(&state.source))
+        .collect();
+    let checks = fetch_all(&github_sources, jobs, |source| {
+        cache.check_for_updates(source)
+    });
 
-            match cache.check_for_updates(&source) {
-                Ok(Some(new_commit)) => {
-                    updates_available.push((
-                        overlay_name.clone(),
-                        state.name.clone(),
-                        url.clone(),
-                        commit.clone(),
-                        new_commit,
-                    ));
-                }
-                Ok(None) => {
-                    println!("  {} {} is up to date", "✓".green(), state.name);
-                }
-                Err(e) => {
-                    println!(
-                        "  {} Could not check {} for updates: {}",
-                        "?".yellow(),
-                        state.name,
-                        e
-                    );
-                }
-            }
-        } else {
-            println!(
-                "  {} {} is a local overlay (not updatable)",
+    // Check for updates
+    for (overlay_name, state) in overlays_to_check.iter().zip(&states) {
+        if let Some((kind, git_ref)) = pinned_ref(&state.source).filter(|_| !force) {
+            status!(
+                "  {} {} is pinned to {kind} {git_ref} (pass --force to update)",
                 "-".dimmed(),
                 state.name
             );
+            continue;
+        }
+        let check_result = match &state.source {
+            OverlaySource::GitHub { url, commit, .. } => {
+                let key = github_source(&state.source).map(|s| fetch_key(&s));
+                // Several overlays may share one repo check, so copy the result out
+                let check = match key.and_then(|k| checks.get(&k)) {
+                    Some(Ok(found)) => Ok(found.clone()),
+                    Some(Err(e)) => Err(anyhow::anyhow!("{e:#}")),
+                    None => Ok(None),
+                };
+                Some((url, commit, check))
+            }
+            OverlaySource::Archive { url, sha256 } => {
+                let check = ArchiveSource::parse(url)
+                    .and_then(|source| cache.check_archive_for_updates(&source, sha256));
+                Some((url, sha256, check))
+            }
+            OverlaySource::Local { .. } | OverlaySource::OverlayRepo { .. } => None,
+        };
+
+        match check_result {
+            Some((url, current, Ok(Some(new_commit)))) => {
+                updates_available.push((
+                    overlay_name.clone(),
+                    state.name.clone(),
+                    url.clone(),
+                    current.clone(),
+                    new_commit,
+                ));
+            }
+            Some((_, _, Ok(None))) => {
+                status!("  {} {} is up to date", "✓".green(), state.name);
+                up_to_date.push(state.name.clone());
+            }
+            Some((_, _, Err(e))) => {
+                status!(
+                    "  {} Could not check {} for updates: {}",
+                    "?".yellow(),
+                    state.name,
+                    e
+                );
+            }
+            None => {
+                status!(
+                    "  {} {} is a local overlay (not updatable)",
+                    "-".dimmed(),
+                    state.name
+                );
+            }
         }
     }
 
     if updates_available.is_empty() {
-        println!("\n{} All overlays are up to date.", "Status:".bold());
+        status!("\n{} All overlays are up to date.", "Status:".bold());
         return Ok(());
     }
 
-    println!(
+    status!(
         "\n{} {} update(s) available:",
         "Found".blue().bold(),
         updates_available.len()
     );
 
     for (_, name, url, old_commit, new_commit) in &updates_available {
-        println!("  {} {}", "↑".cyan(), name);
-        println!("    {}  →  {}", &old_commit[..7], &new_commit[..7]);
-        println!("    {}", url.dimmed());
+        status!("  {} {}", "↑".cyan(), name);
+        status!("    {}  →  {}", &old_commit[..7], &new_commit[..7]);
+        status!("    {}", url.dimmed());
     }
 
     if dry_run {
-        println!("\n{} Dry run - no changes made.", "Note:".yellow());
+        status!("\n{} Dry run - no changes made.", "Note:".yellow());
         return Ok(());
     }
 
-    println!();
+    status!();
 
-    // Apply updates
-    for (normalized_name, _, _, _, _) in &updates_available {
-        let state = load_overlay_state(&target, normalized_name)?;
+    // Pull updated GitHub repos concurrently before relinking serially. States
+    // are found by the key they were loaded under, not their display name.
+    let update_sources: Vec<GitHubSource> = updates_available
+        .iter()
+        .filter_map(|(normalized_name, ..)| {
+            let (_, state) = overlays_to_check
+                .iter()
+                .zip(&states)
+                .find(|(key, _)| *key == normalized_name)?;
+            github_source(&state.source)
+        })
+        .collect();
+    let fetched = fetch_all(&update_sources, jobs, |source| {
+        cache.ensure_cached(source, true).map(|_| ())
+    });
 
-        if let OverlaySource::GitHub { url, git_ref, .. } = &state.source {
-            // Remove old overlay
-            let overlays_dir = target.join(STATE_DIR).join(OVERLAYS_DIR);
-            remove_single_overlay(&target, &overlays_dir, normalized_name)?;
+    // Apply updates, carrying on past failures; each overlay is set aside
+    // first so a failed re-apply can put it back
+    let mut outcomes = Vec::new();
+    for (normalized_name, name, _, old_commit, new_commit) in &updates_available {
+        let result = update_one_overlay(&target, normalized_name, &fetched);
+        if let Err(e) = &result {
+            eprintln!("  {} Failed to update '{}': {:#}", "Error:".red(), name, e);
+        }
+        outcomes.push((name, old_commit, new_commit, result));
+    }
 
-            // Re-apply with update
-            apply_overlay(
-                url,
-                &target,
-                false,
-                Some(state.name.clone()),
-                Some(git_ref.as_str()),
-                true,
-                None,  // Use default source resolution for update
-                false, // Not a dry run
-            )?;
+    let width = outcomes
+        .iter()
+        .map(|(name, ..)| name.len())
+        .chain(up_to_date.iter().map(String::len))
+        .max()
+        .unwrap_or(0);
+    status!("\n{}", "Summary:".bold());
+    for (name, old_commit, new_commit, result) in &outcomes {
+        if result.is_ok() {
+            status!(
+                "  {} {:width$}  updated {} → {}",
+                "✓".green(),
+                name,
+                &old_commit[..7],
+                &new_commit[..7]
+            );
+        } else {
+            status!("  {} {:width$}  failed (left as it was)", "✗".red(), name);
         }
     }
+    for name in &up_to_date {
+        status!("  {} {:width$}  up to date", "-".dimmed(), name);
+    }
 
+    let failed = outcomes.iter().filter(|(.., r)| r.is_err()).count();
+    if failed > 0 {
+        bail!("{failed} of {} update(s) failed", outcomes.len());
+    }
     Ok(())
 }
 
+/// Re-apply one overlay from its updated source, putting the previous version
+/// back if anything goes wrong.
+fn update_one_overlay(
+    target: &Path,
+    name: &str,
+    fetched: &std::collections::HashMap<String, Result<()>>,
+) -> Result<()> {
+    let state = load_overlay_state(target, name)?;
+    let prefetched = github_source(&state.source).and_then(|s| fetched.get(&fetch_key(&s)));
+    if let Some(Err(e)) = prefetched {
+        bail!("Failed to fetch update: {e:#}");
+    }
+
+    let snapshot = OverlaySnapshot::take(target, name)?;
+    let applied = apply_overlay(
+        &state.source.source_string(),
+        target,
+        &ApplyOptions {
+            name: Some(state.name.clone()),
+            git_ref: state.source.ref_override(),
+            update_cache: prefetched.is_none(),
+            variables: state.variables.clone(),
+            only: &state.only,
+            prefix: state.prefix.as_deref(),
+            moved: &state.moved,
+            skip: &state.skipped,
+            unlinked: &state.unlinked,
+            ..Default::default()
+        },
+    );
+    match applied {
+        Ok(()) => snapshot.finish(target),
+        Err(e) => match snapshot.restore(target) {
+            Ok(()) => Err(e),
+            Err(restore_err) => Err(e.context(format!(
+                "and the previous version could not be put back: {restore_err:#}"
+            ))),
+        },
+    }
+}
+
 /// Detect org/repo from git remote origin.
 ///
 /// Returns `None` if the remote cannot be detected (e.g., no remote, non-GitHub).
@@ -1327,6 +4917,188 @@ fn detect_target_from_git_remote(repo_path: &Path) -> Option<(String, String)> {
     parse_github_owner_repo(&url).ok()
 }
 
+/// Apply overlays from the overlay repository that target this repository.
+///
+/// Detects `org/repo` from the `origin` remote and offers every overlay
+/// published for it, skipping ones already applied. With `yes`, all of them
+/// are applied without prompting.
+pub(crate) fn bootstrap_overlays(target: &Path, yes: bool) -> Result<()> {
+    let target = canonicalize_path(target, "Target directory")?;
+
+    let Some((org, repo)) = detect_target_from_git_remote(&target) else {
+        bail!(
+            "Could not detect target repository from git remote.\n\
+             Bootstrap requires an 'origin' remote pointing at GitHub."
+        );
+    };
+
+    let config = config::load_config(None)?;
+    let manager =
+        overlay_repo::OverlayRepoManager::configured(&config, None)?.ok_or_else(|| {
+            anyhow::anyhow!(
+                "Overlay repository not configured.\n\n\
+             Run 'repoverlay source add <url>' to set up an overlay source."
+            )
+        })?;
+    manager.ensure_cloned()?;
+
+    let applied = list_applied_overlays(&target)?;
+    let available: Vec<String> = manager
+        .list_overlays_for_repo(&org, &repo)?
+        .into_iter()
+        .map(|overlay| overlay.name)
+        .filter(|name| !applied.contains(name))
+        .collect();
+
+    if available.is_empty() {
+        status!(
+            "{} No overlays to apply for {}/{}.",
+            "Status:".bold(),
+            org,
+            repo
+        );
+        return Ok(());
+    }
+
+    let selected = if yes {
+        available
+    } else {
+        select_overlay_names(
+            &available,
+            format!("Select overlays to apply from {org}/{repo}"),
+        )?
+    };
+
+    if selected.is_empty() {
+        status!("{} No overlays selected.", "Status:".bold());
+        return Ok(());
+    }
+
+    status!(
+        "{} {} overlay(s) for {}/{}",
+        "Bootstrapping".green().bold(),
+        selected.len(),
+        org.cyan(),
+        repo.cyan()
+    );
+
+    for name in &selected {
+        status!();
+        apply_overlay(
+            &format!("{org}/{repo}/{name}"),
+            &target,
+            &ApplyOptions::default(),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Apply every overlay in a bundle from the overlay repository's `bundles.ccl`.
+///
+/// Members are applied in the order listed; ones already applied are skipped.
+/// Stops at the first member that fails to apply.
+pub(crate) fn apply_bundle(
+    target: &Path,
+    bundle_name: &str,
+    force_copy: bool,
+    update: bool,
+    dry_run: bool,
+    on_conflict: OnConflict,
+) -> Result<()> {
+    let target = canonicalize_path(target, "Target directory")?;
+
+    let config = config::load_config(None)?;
+    let manager =
+        overlay_repo::OverlayRepoManager::configured(&config, None)?.ok_or_else(|| {
+            anyhow::anyhow!(
+                "Overlay repository not configured.\n\n\
+             Run 'repoverlay source add <url>' to set up an overlay source."
+            )
+        })?;
+    manager.ensure_cloned()?;
+
+    if update {
+        status!("{} overlay repository...", "Updating".blue().bold());
+        manager.pull()?;
+    }
+
+    let bundle = manager.get_bundle(bundle_name)?;
+    status!(
+        "{} bundle {} ({} overlay(s))",
+        "Applying".green().bold(),
+        bundle.name.cyan(),
+        bundle.overlays.len()
+    );
+
+    let mut applied = Vec::new();
+    for member in &bundle.overlays {
+        status!();
+        let name = resolved_overlay_name(member, &target, false)?;
+        if list_applied_overlays(&target)?.contains(&name) {
+            status!("  {} '{}' is already applied", "✓".green(), name);
+            continue;
+        }
+
+        if let Err(e) = apply_overlay(
+            member,
+            &target,
+            &ApplyOptions {
+                force_copy,
+                dry_run,
+                on_conflict,
+                ..Default::default()
+            },
+        ) {
+            if !applied.is_empty() {
+                eprintln!(
+                    "  {} Applied before failure: {}",
+                    "Note:".yellow(),
+                    applied.join(", ")
+                );
+            }
+            return Err(e.context(format!(
+                "Failed to apply '{member}' from bundle '{bundle_name}'"
+            )));
+        }
+        applied.push(name);
+    }
+
+    Ok(())
+}
+
+/// Let the user pick from a list of overlay names, all selected by default.
+///
+/// Without a TTY every name is returned.
+fn select_overlay_names(names: &[String], prompt: String) -> Result<Vec<String>> {
+    use detection::{DetectedFile, FileCategory};
+    use selection::{SelectionConfig, select_files};
+
+    let candidates: Vec<DetectedFile> = names
+        .iter()
+        .map(|name| DetectedFile {
+            path: PathBuf::from(name),
+            category: FileCategory::AiConfig,
+            preselected: true,
+        })
+        .collect();
+    let config = SelectionConfig {
+        prompt,
+        ..SelectionConfig::default()
+    };
+    let result = select_files(&candidates, config)?;
+
+    if result.cancelled {
+        bail!("Selection cancelled.");
+    }
+
+    Ok(result
+        .selected_files
+        .iter()
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect())
+}
+
 /// Create a new overlay from files in a repository.
 ///
 /// # Modes
@@ -1348,10 +5120,16 @@ fn detect_target_from_git_remote(repo_path: &Path) -> Option<(String, String)> {
 ///
 /// 1. Validate source is a git repository
 /// 2. If no includes specified, discover candidate files
-/// 3. Interactive selection or use pre-selected AI configs (with `--yes`)
+/// 3. Interactive selection or use pre-selected AI configs (with `--yes`).
+///    Files chosen in the previous interactive selection are preselected
+///    too, unless `fresh` is set
 /// 4. Copy selected files to output directory
 /// 5. Generate `repoverlay.ccl` config file
-#[allow(clippy::needless_pass_by_value)]
+#[allow(
+    clippy::needless_pass_by_value,
+    clippy::too_many_arguments,
+    clippy::fn_params_excessive_bools
+)]
 pub(crate) fn create_overlay(
     source: &Path,
     output: Option<PathBuf>,
@@ -1359,6 +5137,9 @@ pub(crate) fn create_overlay(
     name: Option<String>,
     dry_run: bool,
     yes: bool,
+    secrets_check: bool,
+    fresh: bool,
+    dereference: bool,
 ) -> Result<()> {
     // Verify source is a git repository
     if !source.join(".git").exists() {
@@ -1376,10 +5157,13 @@ pub(crate) fn create_overlay(
             (p.clone(), None)
         } else {
             // Check if overlay repo is configured
-            let config = config::load_config(None).ok();
-            let overlay_repo_config = config.as_ref().and_then(|c| c.overlay_repo.as_ref());
+            let manager = config::load_config(None).ok().and_then(|c| {
+                overlay_repo::OverlayRepoManager::configured(&c, None)
+                    .ok()
+                    .flatten()
+            });
 
-            if let Some(repo_config) = overlay_repo_config {
+            if let Some(manager) = manager {
                 // Try to detect org/repo from git remote
                 if let Some((org, repo)) = detect_target_from_git_remote(source) {
                     // Determine overlay name
@@ -1392,8 +5176,6 @@ pub(crate) fn create_overlay(
                     });
 
                     // Use overlay repo path: <repo_path>/<org>/<repo>/<name>
-                    let manager = overlay_repo::OverlayRepoManager::new(repo_config.clone())
-                        .expect("Failed to create overlay repo manager");
                     manager
                         .ensure_cloned()
                         .expect("Failed to ensure overlay repo is cloned");
@@ -1450,7 +5232,7 @@ pub(crate) fn create_overlay(
             .iter()
             .filter(|f| f.category == detection::FileCategory::Untracked)
             .count();
-        println!(
+        status!(
             " found {} AI, {} gitignored, {} untracked",
             selection::humanize_count(ai_count).green(),
             selection::humanize_count(gi_count).yellow(),
@@ -1467,12 +5249,12 @@ pub(crate) fn create_overlay(
 
         // In dry-run mode without includes, show discovered files
         if dry_run {
-            println!(
+            status!(
                 "{} Discovered files in: {}",
                 "Discovery:".cyan().bold(),
                 source.display()
             );
-            println!();
+            status!();
 
             let groups = detection::group_by_category(&discovered);
             for (category, files) in groups {
@@ -1487,15 +5269,26 @@ pub(crate) fn create_overlay(
                 } else {
                     ""
                 };
-                println!("{}{}:", category_name.bold(), preselected_note.dimmed());
-                for file in files {
-                    let marker = if file.preselected { "[x]" } else { "[ ]" };
-                    println!("  {} {}", marker, file.path.display());
+                status!("{}{}:", category_name.bold(), preselected_note.dimmed());
+                for entry in detection::group_untracked_by_dir(&files) {
+                    match entry {
+                        detection::DiscoveryEntry::File(file) => {
+                            let marker = if file.preselected { "[x]" } else { "[ ]" };
+                            status!("  {} {}", marker, file.path.display());
+                        }
+                        detection::DiscoveryEntry::Directory { path, files } => {
+                            status!(
+                                "  [ ] {}/ {}",
+                                path.display(),
+                                format!("({} files)", files.len()).dimmed()
+                            );
+                        }
+                    }
                 }
-                println!();
+                status!();
             }
 
-            println!(
+            status!(
                 "{}",
                 "Use --include to specify which files to include:".dimmed()
             );
@@ -1509,7 +5302,7 @@ pub(crate) fn create_overlay(
                     .iter()
                     .map(|f| format!("--include {}", f.path.display()))
                     .collect();
-                println!("  repoverlay create my-overlay {}", includes.join(" "));
+                status!("  repoverlay create my-overlay {}", includes.join(" "));
             }
             return Ok(());
         }
@@ -1518,7 +5311,11 @@ pub(crate) fn create_overlay(
         if !yes {
             use selection::{SelectionConfig, select_files};
 
-            let config = SelectionConfig::default();
+            let mut config = SelectionConfig::default();
+            if !fresh {
+                config.previously_selected =
+                    load_remembered_selection(source).into_iter().collect();
+            }
             let result = select_files(&discovered, config)?;
 
             if result.cancelled {
@@ -1529,23 +5326,32 @@ pub(crate) fn create_overlay(
                 bail!("No files selected. Aborting.");
             }
 
+            if let Err(e) = save_remembered_selection(source, &result.selected_files) {
+                debug!("failed to remember selection: {e}");
+            }
+
             // Get output directory from user if not specified
             let final_output = if output.is_none() {
                 use dialoguer::Input;
 
                 if let Some((repo_root, org, repo, default_name)) = &overlay_repo_info {
                     // Show overlay repo context
-                    println!("{} {}/{}", "Target:".bold(), org.cyan(), repo.cyan());
-
-                    let overlay_name: String = Input::new()
-                        .with_prompt("Overlay name")
-                        .default(default_name.clone())
-                        .interact_text()?;
+                    status!("{} {}/{}", "Target:".bold(), org.cyan(), repo.cyan());
+
+                    let overlay_name = loop {
+                        let entered: String = Input::new()
+                            .with_prompt("Overlay name")
+                            .default(default_name.clone())
+                            .interact_text()?;
+                        if confirm_overlay_slug(&entered)? {
+                            break entered;
+                        }
+                    };
 
                     repo_root.join(org).join(repo).join(overlay_name)
                 } else {
                     // Local storage - show full path
-                    println!(
+                    status!(
                         "Where should the overlay be created?\n\
                          (This directory will contain the overlay files and config)"
                     );
@@ -1562,7 +5368,14 @@ pub(crate) fn create_overlay(
             };
 
             // Now create the overlay with selected files
-            return create_overlay_with_files(source, &final_output, &result.selected_files, name);
+            return create_overlay_with_files(
+                source,
+                &final_output,
+                &result.selected_files,
+                name,
+                secrets_check,
+                dereference,
+            );
         }
 
         // With --yes flag but no includes, use pre-selected files (AI configs)
@@ -1579,94 +5392,228 @@ pub(crate) fn create_overlay(
             );
         }
 
-        println!(
+        status!(
             "{} Using {} pre-selected AI config file(s)",
             "Auto-select:".cyan().bold(),
             preselected.len()
         );
 
-        return create_overlay_with_files(source, &output_dir, &preselected, name);
-    }
+        return create_overlay_with_files(
+            source,
+            &output_dir,
+            &preselected,
+            name,
+            secrets_check,
+            dereference,
+        );
+    }
+
+    let include = &expand_include_patterns(source, include)?;
+
+    if dry_run {
+        status!(
+            "{} Would create overlay at: {}",
+            "Dry run:".yellow().bold(),
+            output_dir.display()
+        );
+        status!();
+        status!("Files to include:");
+        let ignore = ignore::IgnoreFile::load(source)?;
+        for path in include {
+            let full_path = source.join(path);
+            if full_path.is_dir() && (dereference || !full_path.is_symlink()) {
+                for entry in walk_included_dir(source, &full_path, &ignore, dereference) {
+                    let rel = entry
+                        .path()
+                        .strip_prefix(source)
+                        .unwrap_or_else(|_| entry.path());
+                    status!("  + {}", rel.display());
+                }
+            } else {
+                status!("  + {}", path.display());
+            }
+        }
+        return Ok(());
+    }
+
+    // Use shared helper to copy files and generate config
+    create_overlay_with_files(
+        source,
+        &output_dir,
+        include,
+        name,
+        secrets_check,
+        dereference,
+    )
+}
+
+/// Show the name an entered overlay name is applied under and, if it
+/// differs, ask whether to keep it. Returns `false` to ask for another name.
+fn confirm_overlay_slug(name: &str) -> Result<bool> {
+    let slug = match normalize_overlay_name(name) {
+        Ok(slug) => slug,
+        Err(e) => {
+            eprintln!("{} {e}", "Error:".red());
+            return Ok(false);
+        }
+    };
+    if slug == name {
+        return Ok(true);
+    }
+
+    println!(
+        "{} '{}' is applied as '{}'. {}",
+        "Note:".yellow(),
+        name,
+        slug,
+        state::OVERLAY_NAME_RULES
+    );
+    Ok(dialoguer::Confirm::new()
+        .with_prompt(format!("Use '{name}' (applied as '{slug}')?"))
+        .default(true)
+        .interact()?)
+}
+
+/// Resolve `--include` values against the source directory.
+///
+/// Plain paths must exist. Values containing glob metacharacters are expanded
+/// relative to `source` and must match at least one file or directory;
+/// matched directories are kept as-is so they are copied recursively.
+pub(crate) fn expand_include_patterns(source: &Path, include: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let ignore = ignore::IgnoreFile::load(source)?;
+    let mut expanded: Vec<PathBuf> = Vec::new();
+
+    for path in include {
+        let pattern = path.to_string_lossy();
+        if !ignore::is_glob(&pattern) {
+            if !source.join(path).exists() {
+                bail!("Include path does not exist: {}", path.display());
+            }
+            if !expanded.contains(path) {
+                expanded.push(path.clone());
+            }
+            continue;
+        }
+
+        let mut matched = false;
+        for rel in ignore::expand_glob(source, &pattern)? {
+            if rel.components().any(|c| c.as_os_str() == ".git")
+                || ignore.is_ignored(&rel, source.join(&rel).is_dir())
+            {
+                continue;
+            }
+            matched = true;
+            if !expanded.iter().any(|p| rel.starts_with(p)) {
+                expanded.push(rel);
+            }
+        }
 
-    // Validate all include paths exist
-    for path in include {
-        let full_path = source.join(path);
-        if !full_path.exists() {
-            bail!("Include path does not exist: {}", path.display());
+        if !matched {
+            bail!("Include pattern matched no files: {pattern}");
         }
     }
 
-    if dry_run {
-        println!(
-            "{} Would create overlay at: {}",
-            "Dry run:".yellow().bold(),
-            output_dir.display()
-        );
-        println!();
-        println!("Files to include:");
-        for path in include {
-            let full_path = source.join(path);
-            if full_path.is_dir() {
-                for entry in walkdir::WalkDir::new(&full_path)
-                    .into_iter()
-                    .filter_map(std::result::Result::ok)
-                    .filter(|e| e.file_type().is_file())
-                {
-                    let rel = entry
-                        .path()
-                        .strip_prefix(source)
-                        .unwrap_or_else(|_| entry.path());
-                    println!("  + {}", rel.display());
-                }
-            } else {
-                println!("  + {}", path.display());
+    Ok(expanded)
+}
+
+/// Walk the files under an included directory, skipping anything matched by
+/// the source's `.repoverlayignore`.
+///
+/// Ignored directories are pruned as the walk descends, so each entry is only
+/// checked on its own; the included directory itself is never filtered.
+fn walk_included_dir<'a>(
+    source: &'a Path,
+    dir: &Path,
+    ignore: &'a ignore::IgnoreFile,
+    dereference: bool,
+) -> impl Iterator<Item = walkdir::DirEntry> + 'a {
+    let is_ignored = move |e: &walkdir::DirEntry| {
+        e.path()
+            .strip_prefix(source)
+            .is_ok_and(|rel| ignore.is_ignored_entry(rel, e.file_type().is_dir()))
+    };
+    walkdir::WalkDir::new(dir)
+        .follow_links(dereference)
+        .into_iter()
+        .filter_entry(move |e| e.depth() == 0 || !is_ignored(e))
+        .filter_map(std::result::Result::ok)
+        .filter(|e| e.file_type().is_file() || e.file_type().is_symlink())
+}
+
+/// List the files that including `include` from `source` would copy.
+///
+/// Explicitly included paths are always listed; files found by expanding an
+/// included directory are filtered through the source's `.repoverlayignore`.
+/// Symlinks are listed as-is unless `dereference` is set, in which case the
+/// files they lead to are listed instead.
+pub(crate) fn included_files(
+    source: &Path,
+    include: &[PathBuf],
+    dereference: bool,
+) -> Result<Vec<PathBuf>> {
+    let ignore = ignore::IgnoreFile::load(source)?;
+
+    let mut files = Vec::new();
+    for path in include {
+        let src_path = source.join(path);
+        if src_path.is_dir() && (dereference || !src_path.is_symlink()) {
+            for entry in walk_included_dir(source, &src_path, &ignore, dereference) {
+                files.push(entry.path().strip_prefix(source)?.to_path_buf());
             }
+        } else {
+            files.push(path.clone());
         }
-        return Ok(());
     }
 
-    // Use shared helper to copy files and generate config
-    create_overlay_with_files(source, &output_dir, include, name)
+    Ok(files)
 }
 
 /// Copy files from source to output directory.
+///
+/// See [`included_files`] for which files are copied. Symlinks are recreated
+/// with their link target stored verbatim unless `dereference` is set.
 pub(crate) fn copy_files_to_overlay(
     source: &Path,
     output_dir: &Path,
     include: &[PathBuf],
+    dereference: bool,
 ) -> Result<Vec<PathBuf>> {
     fs::create_dir_all(output_dir)?;
 
-    let mut copied_files = Vec::new();
-    for path in include {
-        let src_path = source.join(path);
-        if src_path.is_dir() {
-            for entry in walkdir::WalkDir::new(&src_path)
-                .into_iter()
-                .filter_map(std::result::Result::ok)
-                .filter(|e| e.file_type().is_file())
-            {
-                let rel_path = entry.path().strip_prefix(source)?;
-                let dest_path = output_dir.join(rel_path);
-                if let Some(parent) = dest_path.parent() {
-                    fs::create_dir_all(parent)?;
-                }
-                fs::copy(entry.path(), &dest_path)?;
-                copied_files.push(rel_path.to_path_buf());
-            }
+    let copied_files = included_files(source, include, dereference)?;
+    for rel_path in &copied_files {
+        let src_path = source.join(rel_path);
+        let dest_path = output_dir.join(rel_path);
+        if let Some(parent) = dest_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        if !dereference && src_path.is_symlink() {
+            let link = fs::read_link(&src_path)
+                .with_context(|| format!("Failed to read symlink: {}", src_path.display()))?;
+            create_symlink(&link, &dest_path, EntryType::Symlink)?;
         } else {
-            let dest_path = output_dir.join(path);
-            if let Some(parent) = dest_path.parent() {
-                fs::create_dir_all(parent)?;
-            }
             fs::copy(&src_path, &dest_path)?;
-            copied_files.push(path.clone());
         }
     }
 
     Ok(copied_files)
 }
 
+/// Scan the files `include` would copy for likely secrets, asking before
+/// continuing if any are found. Preserved symlinks carry no content and are
+/// not scanned.
+pub(crate) fn check_included_secrets(
+    source: &Path,
+    include: &[PathBuf],
+    dereference: bool,
+) -> Result<()> {
+    let mut files = included_files(source, include, dereference)?;
+    if !dereference {
+        files.retain(|f| !source.join(f).is_symlink());
+    }
+    secrets::confirm_no_secrets(&secrets::scan_files(source, &files))
+}
+
 /// Generate overlay config file content.
 pub(crate) fn generate_overlay_config(name: &str) -> String {
     format!(
@@ -1689,18 +5636,18 @@ overlay =
 
 /// Print overlay creation success message.
 pub(crate) fn print_overlay_created(output_dir: &Path, copied_files: &[PathBuf]) {
-    println!(
+    status!(
         "{} overlay at: {}",
         "Created".green().bold(),
         output_dir.display()
     );
-    println!();
-    println!("Files included:");
+    status!();
+    status!("Files included:");
     for file in copied_files {
-        println!("  + {}", file.display());
+        status!("  + {}", file.display());
     }
-    println!();
-    println!(
+    status!();
+    status!(
         "Apply with: {} {} {}",
         "repoverlay apply".cyan(),
         output_dir.display(),
@@ -1714,8 +5661,13 @@ pub(crate) fn create_overlay_with_files(
     output_dir: &Path,
     include: &[PathBuf],
     name: Option<String>,
+    secrets_check: bool,
+    dereference: bool,
 ) -> Result<()> {
-    let copied_files = copy_files_to_overlay(source, output_dir, include)?;
+    if secrets_check {
+        check_included_secrets(source, include, dereference)?;
+    }
+    let copied_files = copy_files_to_overlay(source, output_dir, include, dereference)?;
 
     let overlay_name = name.unwrap_or_else(|| {
         output_dir
@@ -1741,14 +5693,20 @@ pub(crate) fn create_overlay_with_files(
 ///
 /// # Workflow
 ///
-/// 1. Remove all existing overlays (if any)
-/// 2. Apply the new overlay
+/// 1. Resolve the new source, so a bad source leaves everything untouched
+/// 2. Set the existing overlays aside
+/// 3. Apply the new overlay, putting the previous overlays back if it fails
+/// 4. Drop the previous overlays
+///
+/// With `dry_run`, prints the overlays that would be removed and the one that
+/// would be applied without changing anything.
 pub(crate) fn switch_overlay(
     source: &str,
     target: &Path,
     copy: bool,
     name: Option<String>,
     ref_override: Option<&str>,
+    dry_run: bool,
 ) -> Result<()> {
     validate_git_repo(target)?;
 
@@ -1756,20 +5714,90 @@ pub(crate) fn switch_overlay(
     let state_dir = target.join(STATE_DIR).join(OVERLAYS_DIR);
     let has_overlays = state_dir.exists() && fs::read_dir(&state_dir)?.next().is_some();
 
+    if dry_run {
+        let target = canonicalize_path(target, "Target directory")?;
+        for overlay_name in list_applied_overlays(&target)? {
+            preview_overlay_removal(&target, &overlay_name, false)?;
+        }
+        let resolved = resolve_source(source, ref_override, false, Some(&target), None)?;
+        let config = resolved.load_config()?;
+        let name = name.unwrap_or_else(|| resolved.default_name(&config));
+        status!(
+            "{} overlay: {} {}",
+            "Would apply".blue().bold(),
+            name,
+            format!("(from {source})").dimmed()
+        );
+        status!("\n{} Dry run - no changes made.", "Note:".yellow());
+        return Ok(());
+    }
+
+    resolve_source(source, ref_override, false, Some(target), None)
+        .and_then(|resolved| resolved.load_config())
+        .context("Not switching; the current overlays are unchanged")?;
+
+    let mut snapshots = Vec::new();
     if has_overlays {
-        println!("{} existing overlays...", "Removing".yellow().bold());
-        // Remove all existing overlays
-        remove_overlay(target, None, true, false)?;
+        status!("{} existing overlays...", "Removing".yellow().bold());
+        for overlay_name in list_applied_overlays(target)? {
+            match OverlaySnapshot::take(target, &overlay_name) {
+                Ok(snapshot) => snapshots.push(snapshot),
+                Err(e) => return Err(restore_snapshots(target, snapshots, e)),
+            }
+        }
     }
 
     // Apply the new overlay
-    println!("{} new overlay...", "Applying".blue().bold());
-    apply_overlay(source, target, copy, name, ref_override, false, None, false)?;
+    status!("{} new overlay...", "Applying".blue().bold());
+    let applied = apply_overlay(
+        source,
+        target,
+        &ApplyOptions {
+            force_copy: copy,
+            name,
+            git_ref: ref_override,
+            ..Default::default()
+        },
+    );
+    if let Err(e) = applied {
+        return Err(restore_snapshots(target, snapshots, e));
+    }
 
+    for snapshot in snapshots {
+        snapshot.discard(target)?;
+    }
     Ok(())
 }
 
-/// Update .git/info/exclude file.
+/// Put back overlays set aside by a switch that failed with `error`, returning
+/// the error to report.
+fn restore_snapshots(
+    target: &Path,
+    snapshots: Vec<OverlaySnapshot>,
+    error: anyhow::Error,
+) -> anyhow::Error {
+    let mut error = error;
+    for snapshot in snapshots.into_iter().rev() {
+        let name = snapshot.state.name.clone();
+        match snapshot.restore(target) {
+            Ok(()) => status!("  {} {} {}", "+".green(), name, "(restored)".dimmed()),
+            Err(e) => {
+                error = error.context(format!("and overlay '{name}' could not be put back: {e:#}"));
+            }
+        }
+    }
+    error
+}
+
+/// The file that overlay exclude sections are written to, relative to the
+/// repository root, from the `exclude_target` setting. `None` when excludes
+/// aren't managed.
+fn exclude_file() -> Result<Option<&'static str>> {
+    Ok(config::load_global_config()?.exclude_target.path())
+}
+
+/// Update the overlay's section in the exclude file (`.git/info/exclude`
+/// unless `exclude_target` says otherwise).
 pub(crate) fn update_git_exclude(
     target: &Path,
     overlay_name: &str,
@@ -1782,7 +5810,14 @@ pub(crate) fn update_git_exclude(
         add,
         entries.len()
     );
-    let exclude_path = target.join(GIT_EXCLUDE);
+    let Some(exclude_file) = exclude_file()? else {
+        debug!("exclude_target = none, leaving excludes alone");
+        return Ok(());
+    };
+    let exclude_path = target.join(exclude_file);
+    if !add && !exclude_path.exists() {
+        return Ok(());
+    }
 
     // Ensure the .git/info directory exists
     if let Some(parent) = exclude_path.parent() {
@@ -1830,6 +5865,12 @@ pub(crate) fn update_git_exclude(
         content.pop();
     }
 
+    // A .gitignore that only held overlay sections goes with them
+    if content.trim().is_empty() && exclude_file != GIT_EXCLUDE {
+        fs::remove_file(&exclude_path)?;
+        return Ok(());
+    }
+
     fs::write(&exclude_path, content)?;
     Ok(())
 }
@@ -1979,6 +6020,34 @@ mod tests {
         }
     }
 
+    // Tests for check_remote_status
+    mod check_remote_status_tests {
+        use super::*;
+
+        #[test]
+        fn local_sources_are_not_checked() {
+            let source = OverlaySource::local(PathBuf::from("/overlays/local"));
+            assert!(check_remote_status(&source).is_none());
+        }
+
+        #[test]
+        fn uncached_github_source_is_reported_unknown() {
+            let source = OverlaySource::github(
+                "https://github.com/repoverlay-test/never-cached".to_string(),
+                "repoverlay-test".to_string(),
+                "never-cached".to_string(),
+                "main".to_string(),
+                "0123456789abcdef".to_string(),
+                None,
+            );
+
+            match check_remote_status(&source) {
+                Some(RemoteStatus::Unknown(e)) => assert!(e.contains("not cached")),
+                _ => panic!("Expected an unknown remote status"),
+            }
+        }
+    }
+
     // Tests for any_overlay_sections_remain
     mod any_overlay_sections_remain_tests {
         use super::*;
@@ -2158,6 +6227,77 @@ mod tests {
     mod copy_files_to_overlay_tests {
         use super::*;
 
+        #[cfg(unix)]
+        fn symlinked_source() -> TempDir {
+            use std::os::unix::fs::symlink;
+
+            let source = TempDir::new().unwrap();
+            fs::create_dir_all(source.path().join("dotfiles/claude")).unwrap();
+            fs::write(source.path().join("dotfiles/claude/settings.json"), "{}").unwrap();
+            fs::write(source.path().join("dotfiles/envrc"), "use nix").unwrap();
+            symlink("dotfiles/claude", source.path().join(".claude")).unwrap();
+            fs::create_dir_all(source.path().join("config")).unwrap();
+            symlink("../dotfiles/envrc", source.path().join("config/.envrc")).unwrap();
+            source
+        }
+
+        #[cfg(unix)]
+        #[test]
+        fn preserves_symlinks() {
+            let source = symlinked_source();
+            let output = TempDir::new().unwrap();
+
+            let mut copied = copy_files_to_overlay(
+                source.path(),
+                output.path(),
+                &[PathBuf::from(".claude"), PathBuf::from("config")],
+                false,
+            )
+            .unwrap();
+            copied.sort();
+
+            assert_eq!(
+                copied,
+                vec![PathBuf::from(".claude"), PathBuf::from("config/.envrc")]
+            );
+            assert_eq!(
+                fs::read_link(output.path().join(".claude")).unwrap(),
+                PathBuf::from("dotfiles/claude")
+            );
+            assert_eq!(
+                fs::read_link(output.path().join("config/.envrc")).unwrap(),
+                PathBuf::from("../dotfiles/envrc")
+            );
+        }
+
+        #[cfg(unix)]
+        #[test]
+        fn dereference_copies_link_contents() {
+            let source = symlinked_source();
+            let output = TempDir::new().unwrap();
+
+            let mut copied = copy_files_to_overlay(
+                source.path(),
+                output.path(),
+                &[PathBuf::from(".claude"), PathBuf::from("config")],
+                true,
+            )
+            .unwrap();
+            copied.sort();
+
+            assert_eq!(
+                copied,
+                vec![
+                    PathBuf::from(".claude/settings.json"),
+                    PathBuf::from("config/.envrc"),
+                ]
+            );
+            let envrc = output.path().join("config/.envrc");
+            assert!(!envrc.is_symlink());
+            assert_eq!(fs::read_to_string(envrc).unwrap(), "use nix");
+            assert!(!output.path().join(".claude").is_symlink());
+        }
+
         #[test]
         fn copies_single_file() {
             let source = TempDir::new().unwrap();
@@ -2165,9 +6305,13 @@ mod tests {
 
             fs::write(source.path().join("file.txt"), "content").unwrap();
 
-            let copied =
-                copy_files_to_overlay(source.path(), output.path(), &[PathBuf::from("file.txt")])
-                    .unwrap();
+            let copied = copy_files_to_overlay(
+                source.path(),
+                output.path(),
+                &[PathBuf::from("file.txt")],
+                false,
+            )
+            .unwrap();
 
             assert_eq!(copied.len(), 1);
             assert!(output.path().join("file.txt").exists());
@@ -2183,7 +6327,7 @@ mod tests {
             fs::write(source.path().join("dir/subdir/file2.txt"), "content2").unwrap();
 
             let copied =
-                copy_files_to_overlay(source.path(), output.path(), &[PathBuf::from("dir")])
+                copy_files_to_overlay(source.path(), output.path(), &[PathBuf::from("dir")], false)
                     .unwrap();
 
             assert_eq!(copied.len(), 2);
@@ -2203,11 +6347,72 @@ mod tests {
                 source.path(),
                 output.path(),
                 &[PathBuf::from("deep/nested/file.txt")],
+                false,
             )
             .unwrap();
 
             assert!(output.path().join("deep/nested/file.txt").exists());
         }
+
+        #[test]
+        fn directory_expansion_respects_ignore_file() {
+            let source = TempDir::new().unwrap();
+            let output = TempDir::new().unwrap();
+
+            fs::write(
+                source.path().join(".repoverlayignore"),
+                "node_modules/\n*.log\n!keep.log\nconfig/**/cache\n",
+            )
+            .unwrap();
+            fs::create_dir_all(source.path().join("config/web/node_modules/pkg")).unwrap();
+            fs::create_dir_all(source.path().join("config/a/b/cache")).unwrap();
+            fs::write(source.path().join("config/settings.json"), "{}").unwrap();
+            fs::write(source.path().join("config/web/node_modules/pkg/i.js"), "").unwrap();
+            fs::write(source.path().join("config/a/b/cache/blob"), "").unwrap();
+            fs::write(source.path().join("config/a/debug.log"), "").unwrap();
+            fs::write(source.path().join("config/a/keep.log"), "").unwrap();
+
+            let mut copied = copy_files_to_overlay(
+                source.path(),
+                output.path(),
+                &[PathBuf::from("config")],
+                false,
+            )
+            .unwrap();
+            copied.sort();
+
+            assert_eq!(
+                copied,
+                vec![
+                    PathBuf::from("config/a/keep.log"),
+                    PathBuf::from("config/settings.json"),
+                ]
+            );
+            assert!(!output.path().join("config/web/node_modules").exists());
+        }
+
+        #[test]
+        fn explicit_include_wins_over_ignore_file() {
+            let source = TempDir::new().unwrap();
+            let output = TempDir::new().unwrap();
+
+            fs::write(source.path().join(".repoverlayignore"), "*.log\nscratch/\n").unwrap();
+            fs::create_dir_all(source.path().join("scratch")).unwrap();
+            fs::write(source.path().join("debug.log"), "log").unwrap();
+            fs::write(source.path().join("scratch/notes.md"), "notes").unwrap();
+
+            let copied = copy_files_to_overlay(
+                source.path(),
+                output.path(),
+                &[PathBuf::from("debug.log"), PathBuf::from("scratch")],
+                false,
+            )
+            .unwrap();
+
+            assert_eq!(copied.len(), 2);
+            assert!(output.path().join("debug.log").exists());
+            assert!(output.path().join("scratch/notes.md").exists());
+        }
     }
 
     // Tests for generate_overlay_config
@@ -2234,6 +6439,20 @@ mod tests {
         }
     }
 
+    mod confirm_overlay_slug_tests {
+        use super::*;
+
+        #[test]
+        fn accepts_normalized_name_without_asking() {
+            assert!(confirm_overlay_slug("my-config").unwrap());
+        }
+
+        #[test]
+        fn asks_again_for_invalid_name() {
+            assert!(!confirm_overlay_slug("!!!").unwrap());
+        }
+    }
+
     // Tests for remove_overlay_section (additional edge cases)
     mod remove_overlay_section_additional_tests {
         use super::*;
@@ -2405,6 +6624,7 @@ mod tests {
                     PathBuf::from("b.txt"),
                     PathBuf::from("c.txt"),
                 ],
+                false,
             )
             .unwrap();
 
@@ -2432,7 +6652,7 @@ mod tests {
             fs::write(source.path().join("file.txt"), "content").unwrap();
 
             let copied =
-                copy_files_to_overlay(source.path(), &output, &[PathBuf::from("file.txt")])
+                copy_files_to_overlay(source.path(), &output, &[PathBuf::from("file.txt")], false)
                     .unwrap();
 
             assert_eq!(copied.len(), 1);
@@ -2447,8 +6667,13 @@ mod tests {
             let content = "line1\nline2\nline3\n特殊字符\n";
             fs::write(source.path().join("file.txt"), content).unwrap();
 
-            copy_files_to_overlay(source.path(), output.path(), &[PathBuf::from("file.txt")])
-                .unwrap();
+            copy_files_to_overlay(
+                source.path(),
+                output.path(),
+                &[PathBuf::from("file.txt")],
+                false,
+            )
+            .unwrap();
 
             let read_content = fs::read_to_string(output.path().join("file.txt")).unwrap();
             assert_eq!(read_content, content);
@@ -2486,6 +6711,7 @@ mod tests {
         fn resolved_source_struct_fields() {
             let source = ResolvedSource {
                 path: PathBuf::from("/some/path"),
+                file: None,
                 source_info: OverlaySource::local(PathBuf::from("/origin")),
             };
 
@@ -2497,6 +6723,32 @@ mod tests {
                 _ => panic!("Expected Local source"),
             }
         }
+
+        #[test]
+        fn single_file_source_uses_file_name_and_mappings_only() {
+            let dir = tempfile::TempDir::new().unwrap();
+            fs::write(dir.path().join(".gitignore"), "target/\n").unwrap();
+            fs::write(
+                dir.path().join(CONFIG_FILE),
+                "overlay =\n  name = dotfiles\nmappings =\n  .gitignore = .config/ignore\ndirectories =\n  = scratch\n",
+            )
+            .unwrap();
+            let source = ResolvedSource {
+                path: dir.path().to_path_buf(),
+                file: Some(PathBuf::from(".gitignore")),
+                source_info: OverlaySource::local(dir.path().to_path_buf()),
+            };
+
+            let config = source.load_config().unwrap();
+
+            assert_eq!(source.walk_root(), dir.path().join(".gitignore"));
+            assert_eq!(source.default_name(&config), ".gitignore");
+            assert_eq!(
+                config.mappings.get(".gitignore").map(String::as_str),
+                Some(".config/ignore")
+            );
+            assert!(config.directories.is_empty());
+        }
     }
 
     // Additional edge case tests for line ending handling
@@ -2515,42 +6767,124 @@ mod tests {
         }
 
         #[test]
-        fn remove_overlay_section_with_only_crlf() {
-            let content = "*.log\r\n# repoverlay:test start\r\n.envrc\r\n# repoverlay:test end\r\n";
-            let result = remove_overlay_section(content, "test");
-            assert!(result.contains("*.log"));
-            assert!(!result.contains(".envrc"));
+        fn remove_overlay_section_with_only_crlf() {
+            let content = "*.log\r\n# repoverlay:test start\r\n.envrc\r\n# repoverlay:test end\r\n";
+            let result = remove_overlay_section(content, "test");
+            assert!(result.contains("*.log"));
+            assert!(!result.contains(".envrc"));
+        }
+
+        #[test]
+        fn remove_overlay_section_preserves_trailing_newline() {
+            let content = "before\n# repoverlay:test start\n.envrc\n# repoverlay:test end\n";
+            let result = remove_overlay_section(content, "test");
+            assert!(result.ends_with('\n'));
+        }
+
+        #[test]
+        fn remove_overlay_section_with_no_trailing_newline() {
+            let content = "# repoverlay:test start\n.envrc\n# repoverlay:test end";
+            let result = remove_overlay_section(content, "test");
+            // Should handle content without trailing newline
+            assert!(!result.contains(".envrc"));
+        }
+
+        #[test]
+        fn update_git_exclude_with_existing_crlf_content() {
+            let repo = create_test_repo();
+            let exclude_path = repo.path().join(".git/info/exclude");
+
+            // Create exclude file with CRLF line endings
+            fs::create_dir_all(exclude_path.parent().unwrap()).unwrap();
+            fs::write(&exclude_path, "*.log\r\n.DS_Store\r\n").unwrap();
+
+            update_git_exclude(repo.path(), "test", &[".envrc".to_string()], true).unwrap();
+
+            let content = fs::read_to_string(&exclude_path).unwrap();
+            assert!(content.contains(".envrc"));
+            assert!(content.contains("# repoverlay:test start"));
+        }
+    }
+
+    mod expand_path_tests {
+        use super::*;
+
+        fn expand(input: &str) -> String {
+            expand_path_with(input, Some(Path::new("/home/me")), |name| match name {
+                "HOME" => Some("/home/me".to_string()),
+                "OVERLAYS" => Some("/srv/overlays".to_string()),
+                _ => None,
+            })
+        }
+
+        #[test]
+        fn expands_leading_tilde() {
+            assert_eq!(expand("~"), "/home/me");
+            assert_eq!(expand("~/overlays/foo"), "/home/me/overlays/foo");
+        }
+
+        #[test]
+        fn leaves_other_tildes() {
+            assert_eq!(expand("~other/foo"), "~other/foo");
+            assert_eq!(expand("./a~b"), "./a~b");
+        }
+
+        #[test]
+        fn expands_variables() {
+            assert_eq!(expand("$HOME/overlays/foo"), "/home/me/overlays/foo");
+            assert_eq!(expand("${OVERLAYS}/rust"), "/srv/overlays/rust");
+            assert_eq!(
+                expand("$OVERLAYS-old/${HOME}"),
+                "/srv/overlays-old//home/me"
+            );
         }
 
         #[test]
-        fn remove_overlay_section_preserves_trailing_newline() {
-            let content = "before\n# repoverlay:test start\n.envrc\n# repoverlay:test end\n";
-            let result = remove_overlay_section(content, "test");
-            assert!(result.ends_with('\n'));
+        fn keeps_unset_or_malformed_variables() {
+            assert_eq!(expand("$MISSING/foo"), "$MISSING/foo");
+            assert_eq!(expand("${MISSING}/foo"), "${MISSING}/foo");
+            assert_eq!(expand("${HOME/foo"), "${HOME/foo");
+            assert_eq!(expand("cost$/x"), "cost$/x");
         }
 
         #[test]
-        fn remove_overlay_section_with_no_trailing_newline() {
-            let content = "# repoverlay:test start\n.envrc\n# repoverlay:test end";
-            let result = remove_overlay_section(content, "test");
-            // Should handle content without trailing newline
-            assert!(!result.contains(".envrc"));
+        fn leaves_paths_without_tokens_unchanged() {
+            assert_eq!(expand("./overlay"), "./overlay");
+            assert_eq!(expand("org/repo/name"), "org/repo/name");
         }
+    }
+
+    mod source_with_subpath_tests {
+        use super::*;
 
         #[test]
-        fn update_git_exclude_with_existing_crlf_content() {
-            let repo = create_test_repo();
-            let exclude_path = repo.path().join(".git/info/exclude");
+        fn adds_subpath_to_repo_url() {
+            assert_eq!(
+                source_with_subpath("https://github.com/owner/repo", "overlays/rust").unwrap(),
+                "https://github.com/owner/repo/tree/HEAD/overlays/rust"
+            );
+        }
 
-            // Create exclude file with CRLF line endings
-            fs::create_dir_all(exclude_path.parent().unwrap()).unwrap();
-            fs::write(&exclude_path, "*.log\r\n.DS_Store\r\n").unwrap();
+        #[test]
+        fn keeps_ref_from_tree_url() {
+            assert_eq!(
+                source_with_subpath("https://github.com/owner/repo/tree/main/old", "new").unwrap(),
+                "https://github.com/owner/repo/tree/main/new"
+            );
+        }
 
-            update_git_exclude(repo.path(), "test", &[".envrc".to_string()], true).unwrap();
+        #[test]
+        fn expands_shorthand() {
+            assert_eq!(
+                source_with_subpath("owner/repo", "overlays").unwrap(),
+                "https://github.com/owner/repo/tree/HEAD/overlays"
+            );
+        }
 
-            let content = fs::read_to_string(&exclude_path).unwrap();
-            assert!(content.contains(".envrc"));
-            assert!(content.contains("# repoverlay:test start"));
+        #[test]
+        fn rejects_non_github_sources() {
+            let err = source_with_subpath("./local-overlay", "overlays").unwrap_err();
+            assert!(err.to_string().contains("only applies to GitHub sources"));
         }
     }
 
@@ -2662,4 +6996,545 @@ mod tests {
             assert!(result.is_ok());
         }
     }
+
+    mod relative_symlink_tests {
+        use super::*;
+
+        #[test]
+        fn sibling_directories() {
+            let relative = relative_symlink_target(
+                Path::new("/work/overlays/rust/.envrc"),
+                Path::new("/work/project/.envrc"),
+            );
+            assert_eq!(relative, PathBuf::from("../overlays/rust/.envrc"));
+        }
+
+        #[test]
+        fn nested_link() {
+            let relative = relative_symlink_target(
+                Path::new("/work/overlay/.vscode/settings.json"),
+                Path::new("/work/project/.vscode/settings.json"),
+            );
+            assert_eq!(
+                relative,
+                PathBuf::from("../../overlay/.vscode/settings.json")
+            );
+        }
+
+        #[test]
+        fn same_directory() {
+            let relative =
+                relative_symlink_target(Path::new("/work/source.txt"), Path::new("/work/link.txt"));
+            assert_eq!(relative, PathBuf::from("source.txt"));
+        }
+
+        #[test]
+        fn symlink_points_to_resolves_relative_links() {
+            let temp = TempDir::new().unwrap();
+            let source = temp.path().join("overlay/.envrc");
+            fs::create_dir_all(source.parent().unwrap()).unwrap();
+            fs::write(&source, "content").unwrap();
+            fs::create_dir_all(temp.path().join("repo")).unwrap();
+            let link_path = temp.path().join("repo/.envrc");
+
+            assert!(symlink_points_to(
+                Path::new("../overlay/.envrc"),
+                &link_path,
+                &source
+            ));
+            assert!(symlink_points_to(&source, &link_path, &source));
+            assert!(!symlink_points_to(
+                Path::new("../other/.envrc"),
+                &link_path,
+                &source
+            ));
+        }
+    }
+
+    mod case_collision_tests {
+        use super::*;
+
+        #[test]
+        fn paths_differing_in_case_collide_when_insensitive() {
+            let mut seen = std::collections::HashMap::new();
+            check_case_collision(&mut seen, Path::new("docs/README.md"), true).unwrap();
+            let err = check_case_collision(&mut seen, Path::new("docs/readme.md"), true)
+                .unwrap_err()
+                .to_string();
+            assert!(err.contains("differ only in case"), "{err}");
+        }
+
+        #[test]
+        fn paths_differing_in_case_are_distinct_when_sensitive() {
+            let mut seen = std::collections::HashMap::new();
+            check_case_collision(&mut seen, Path::new("README.md"), false).unwrap();
+            check_case_collision(&mut seen, Path::new("readme.md"), false).unwrap();
+        }
+    }
+
+    mod only_includes_tests {
+        use super::*;
+
+        fn only(patterns: &[&str]) -> Vec<String> {
+            patterns.iter().map(ToString::to_string).collect()
+        }
+
+        #[test]
+        fn empty_filter_includes_everything() {
+            assert!(only_includes(&[], &[Path::new(".envrc")]));
+        }
+
+        #[test]
+        fn matches_globs_and_parent_directories() {
+            let filter = only(&[".vscode", "*.md"]);
+            assert!(only_includes(
+                &filter,
+                &[Path::new(".vscode/settings.json")]
+            ));
+            assert!(only_includes(&filter, &[Path::new("CLAUDE.md")]));
+            assert!(!only_includes(&filter, &[Path::new(".envrc")]));
+        }
+
+        #[test]
+        fn matches_target_path_of_templates() {
+            let filter = only(&[".envrc"]);
+            assert!(only_includes(
+                &filter,
+                &[Path::new(".envrc.tmpl"), Path::new(".envrc")]
+            ));
+        }
+    }
+
+    mod link_type_tests {
+        use super::*;
+
+        #[test]
+        fn copy_flag_wins() {
+            for style in [LinkStyle::Auto, LinkStyle::Symlink, LinkStyle::Copy] {
+                assert_eq!(link_type_for(true, style, || true), LinkType::Copy);
+            }
+        }
+
+        #[test]
+        fn auto_symlinks_when_supported() {
+            assert_eq!(
+                link_type_for(false, LinkStyle::Auto, || true),
+                LinkType::Symlink
+            );
+            assert_eq!(
+                link_type_for(false, LinkStyle::Auto, || false),
+                LinkType::Copy
+            );
+        }
+
+        #[test]
+        fn forced_styles_skip_the_probe() {
+            let probe = || panic!("probed symlink support");
+            assert_eq!(
+                link_type_for(false, LinkStyle::Symlink, probe),
+                LinkType::Symlink
+            );
+            assert_eq!(link_type_for(false, LinkStyle::Copy, probe), LinkType::Copy);
+        }
+
+        #[test]
+        fn probe_cleans_up() {
+            let temp = TempDir::new().unwrap();
+            probe_symlinks(temp.path()).unwrap();
+            assert_eq!(fs::read_dir(temp.path()).unwrap().count(), 0);
+        }
+    }
+
+    #[cfg(unix)]
+    mod file_state_tests {
+        use super::*;
+        use std::os::unix::fs::symlink;
+
+        fn symlink_entry(source: &str, target: &str) -> FileEntry {
+            FileEntry {
+                source: PathBuf::from(source),
+                target: PathBuf::from(target),
+                link_type: LinkType::Symlink,
+                entry_type: EntryType::File,
+                backup: None,
+                sha256: None,
+                rendered: false,
+            }
+        }
+
+        #[test]
+        fn distinguishes_linked_diverged_and_missing() {
+            let temp = TempDir::new().unwrap();
+            let overlay = temp.path().join("overlay");
+            let repo = temp.path().join("repo");
+            fs::create_dir_all(&overlay).unwrap();
+            fs::create_dir_all(&repo).unwrap();
+            fs::write(overlay.join(".envrc"), "use nix").unwrap();
+            fs::write(overlay.join("other"), "other").unwrap();
+
+            symlink(overlay.join(".envrc"), repo.join(".envrc")).unwrap();
+            assert_eq!(
+                file_state(&repo, &symlink_entry(".envrc", ".envrc")),
+                FileState::Ok
+            );
+
+            // Mapped to another target path, still linked to its source
+            symlink("../overlay/.envrc", repo.join("mapped")).unwrap();
+            assert_eq!(
+                file_state(&repo, &symlink_entry(".envrc", "mapped")),
+                FileState::Ok
+            );
+
+            // Replaced by a regular file
+            fs::write(repo.join("replaced"), "local edit").unwrap();
+            assert_eq!(
+                file_state(&repo, &symlink_entry("replaced", "replaced")),
+                FileState::Diverged
+            );
+
+            // Re-pointed at another file
+            symlink(overlay.join("other"), repo.join("repointed")).unwrap();
+            assert_eq!(
+                file_state(&repo, &symlink_entry("repointed", "repointed")),
+                FileState::Diverged
+            );
+
+            assert_eq!(
+                file_state(&repo, &symlink_entry("gone", "gone")),
+                FileState::Missing
+            );
+        }
+
+        #[test]
+        fn dangling_link_into_deleted_source_is_broken() {
+            let temp = TempDir::new().unwrap();
+            let overlay = temp.path().join("overlay");
+            let repo = temp.path().join("repo");
+            fs::create_dir_all(&overlay).unwrap();
+            fs::create_dir_all(&repo).unwrap();
+            fs::write(overlay.join(".envrc"), "use nix").unwrap();
+            symlink(overlay.join(".envrc"), repo.join(".envrc")).unwrap();
+            let entry = symlink_entry(".envrc", ".envrc");
+            assert_eq!(file_state(&repo, &entry), FileState::Ok);
+
+            // The cached source is deleted out from under the link
+            fs::remove_dir_all(&overlay).unwrap();
+            assert_eq!(file_state(&repo, &entry), FileState::Broken);
+        }
+
+        #[test]
+        fn copies_only_need_to_exist() {
+            let temp = TempDir::new().unwrap();
+            fs::write(temp.path().join("copied"), "content").unwrap();
+            let entry = FileEntry {
+                link_type: LinkType::Copy,
+                ..symlink_entry("copied", "copied")
+            };
+            assert_eq!(file_state(temp.path(), &entry), FileState::Ok);
+
+            let entry = FileEntry {
+                target: PathBuf::from("gone"),
+                ..entry
+            };
+            assert_eq!(file_state(temp.path(), &entry), FileState::Missing);
+        }
+    }
+
+    mod overlay_snapshot_tests {
+        use super::*;
+
+        fn apply(repo: &Path, overlay: &Path, on_conflict: OnConflict) {
+            apply_overlay(
+                overlay.to_str().unwrap(),
+                repo,
+                &ApplyOptions {
+                    name: Some("snap".to_string()),
+                    on_conflict,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        }
+
+        fn setup() -> (TempDir, TempDir) {
+            let repo = create_test_repo();
+            let overlay = TempDir::new().unwrap();
+            fs::create_dir_all(overlay.path().join("config")).unwrap();
+            fs::write(overlay.path().join(".envrc"), "use nix").unwrap();
+            fs::write(overlay.path().join("config/settings.json"), "{}").unwrap();
+            (repo, overlay)
+        }
+
+        #[test]
+        fn restore_puts_overlay_back() {
+            let (repo, overlay) = setup();
+            apply(repo.path(), overlay.path(), OnConflict::Fail);
+            let snapshot = OverlaySnapshot::take(repo.path(), "snap").unwrap();
+            assert!(!repo.path().join(".envrc").is_symlink());
+            assert!(!repo.path().join("config").exists());
+            assert!(load_overlay_state(repo.path(), "snap").is_err());
+
+            // A failed re-apply may leave files behind
+            fs::write(repo.path().join(".envrc"), "partial").unwrap();
+
+            snapshot.restore(repo.path()).unwrap();
+            assert!(repo.path().join(".envrc").is_symlink());
+            assert!(repo.path().join("config/settings.json").is_symlink());
+            assert_eq!(
+                fs::read_to_string(repo.path().join(".envrc")).unwrap(),
+                "use nix"
+            );
+            assert_eq!(
+                load_overlay_state(repo.path(), "snap").unwrap().files.len(),
+                2
+            );
+            let exclude = fs::read_to_string(repo.path().join(".git/info/exclude")).unwrap();
+            assert!(exclude.contains(&exclude_marker_start("snap")));
+            assert!(exclude.contains(".envrc\n"));
+            assert!(!repo.path().join(STATE_DIR).join(STASH_DIR).exists());
+        }
+
+        #[test]
+        fn finish_keeps_backups_of_repo_files() {
+            let (repo, overlay) = setup();
+            fs::write(repo.path().join(".envrc"), "repo version").unwrap();
+            apply(repo.path(), overlay.path(), OnConflict::Backup);
+
+            // The new version no longer has config/settings.json
+            fs::remove_dir_all(overlay.path().join("config")).unwrap();
+            let snapshot = OverlaySnapshot::take(repo.path(), "snap").unwrap();
+            apply(repo.path(), overlay.path(), OnConflict::Fail);
+            snapshot.finish(repo.path()).unwrap();
+
+            let state = load_overlay_state(repo.path(), "snap").unwrap();
+            assert_eq!(state.files.len(), 1);
+            assert!(state.files[0].backup.is_some());
+            assert!(!repo.path().join("config").exists());
+            assert!(!repo.path().join(STATE_DIR).join(STASH_DIR).exists());
+
+            // Removing the overlay still brings back the repo's file
+            let overlays_dir = repo.path().join(STATE_DIR).join(OVERLAYS_DIR);
+            remove_single_overlay(repo.path(), &overlays_dir, "snap", false).unwrap();
+            assert_eq!(
+                fs::read_to_string(repo.path().join(".envrc")).unwrap(),
+                "repo version"
+            );
+        }
+
+        #[test]
+        fn failed_apply_removes_what_it_created() {
+            let (repo, overlay) = setup();
+            fs::write(overlay.path().join("conflict.txt"), "overlay").unwrap();
+            fs::write(repo.path().join("conflict.txt"), "repo").unwrap();
+
+            let result = apply_overlay(
+                overlay.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    name: Some("snap".to_string()),
+                    ..Default::default()
+                },
+            );
+            assert!(result.is_err());
+            assert!(!repo.path().join(".envrc").exists());
+            assert!(!repo.path().join("config").exists());
+            assert_eq!(
+                fs::read_to_string(repo.path().join("conflict.txt")).unwrap(),
+                "repo"
+            );
+        }
+
+        #[test]
+        fn failed_update_keeps_previous_version() {
+            let (repo, overlay) = setup();
+            apply(repo.path(), overlay.path(), OnConflict::Fail);
+
+            // The new version adds a file the repo already has
+            fs::write(overlay.path().join("conflict.txt"), "overlay").unwrap();
+            fs::write(repo.path().join("conflict.txt"), "repo").unwrap();
+
+            let result = update_one_overlay(repo.path(), "snap", &std::collections::HashMap::new());
+            assert!(result.is_err());
+
+            let state = load_overlay_state(repo.path(), "snap").unwrap();
+            assert_eq!(state.files.len(), 2);
+            assert!(repo.path().join(".envrc").is_symlink());
+            assert!(repo.path().join("config/settings.json").is_symlink());
+            assert_eq!(
+                fs::read_to_string(repo.path().join("conflict.txt")).unwrap(),
+                "repo"
+            );
+            assert!(!repo.path().join(STATE_DIR).join(STASH_DIR).exists());
+        }
+    }
+
+    mod pinned_ref_tests {
+        use super::*;
+
+        fn github(git_ref: &str, kind: RefKind) -> OverlaySource {
+            OverlaySource::github(
+                format!("https://github.com/owner/repo/tree/{git_ref}"),
+                "owner".to_string(),
+                "repo".to_string(),
+                git_ref.to_string(),
+                "abc123def4567890".to_string(),
+                None,
+            )
+            .with_ref_kind(kind)
+        }
+
+        #[test]
+        fn branches_are_not_pinned() {
+            assert_eq!(pinned_ref(&github("main", RefKind::Branch)), None);
+            assert_eq!(
+                pinned_ref(&OverlaySource::local(PathBuf::from("/overlay"))),
+                None
+            );
+        }
+
+        #[test]
+        fn tags_and_commits_are_pinned() {
+            assert_eq!(
+                pinned_ref(&github("v1.2.0", RefKind::Tag)),
+                Some(("tag", "v1.2.0"))
+            );
+            let sha = "0123456789abcdef0123456789abcdef01234567";
+            assert_eq!(
+                pinned_ref(&github(sha, RefKind::Commit)),
+                Some(("commit", "0123456789ab"))
+            );
+        }
+
+        #[test]
+        fn update_skips_pinned_overlays() {
+            let repo = create_test_repo();
+            fs::create_dir_all(repo.path().join(STATE_DIR).join(OVERLAYS_DIR)).unwrap();
+            let state = OverlayState::new("pinned".to_string(), github("v1.2.0", RefKind::Tag));
+            save_overlay_state(repo.path(), &state).unwrap();
+
+            // Without --force the tag is never checked, so nothing is fetched
+            update_overlays(repo.path(), None, false, false, 1).unwrap();
+            assert_eq!(
+                load_overlay_state(repo.path(), "pinned")
+                    .unwrap()
+                    .source
+                    .display(),
+                state.source.display()
+            );
+        }
+    }
+
+    mod prefix_tests {
+        use super::*;
+
+        #[test]
+        fn normalize_prefix_drops_current_dir() {
+            assert_eq!(
+                normalize_prefix(Path::new("./packages/./foo")).unwrap(),
+                PathBuf::from("packages/foo")
+            );
+            assert_eq!(normalize_prefix(Path::new(".")).unwrap(), PathBuf::new());
+        }
+
+        #[test]
+        fn normalize_prefix_rejects_paths_outside_repo() {
+            assert!(normalize_prefix(Path::new("../elsewhere")).is_err());
+            assert!(normalize_prefix(Path::new("packages/../../elsewhere")).is_err());
+            assert!(normalize_prefix(Path::new("/abs/path")).is_err());
+        }
+
+        #[test]
+        fn apply_places_files_under_prefix() {
+            let repo = create_test_repo();
+            let overlay = TempDir::new().unwrap();
+            fs::create_dir_all(overlay.path().join("config")).unwrap();
+            fs::write(overlay.path().join(".envrc"), "use nix").unwrap();
+            fs::write(overlay.path().join("config/settings.json"), "{}").unwrap();
+
+            apply_overlay(
+                overlay.path().to_str().unwrap(),
+                repo.path(),
+                &ApplyOptions {
+                    name: Some("nested".to_string()),
+                    prefix: Some(Path::new("./packages/foo")),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+            let nested = repo.path().join("packages/foo");
+            assert!(nested.join(".envrc").is_symlink());
+            assert!(nested.join("config/settings.json").is_symlink());
+            assert!(!repo.path().join(".envrc").exists());
+
+            let state = load_overlay_state(repo.path(), "nested").unwrap();
+            assert_eq!(state.prefix, Some(PathBuf::from("packages/foo")));
+            assert!(
+                state
+                    .file_entries()
+                    .iter()
+                    .all(|entry| entry.target.starts_with("packages/foo"))
+            );
+            let exclude = fs::read_to_string(repo.path().join(".git/info/exclude")).unwrap();
+            assert!(exclude.contains("packages/foo/.envrc\n"));
+        }
+    }
+
+    mod move_overlay_file_tests {
+        use super::*;
+
+        fn apply(repo: &Path, overlay: &Path, moved: &[MovedFile]) {
+            apply_overlay(
+                overlay.to_str().unwrap(),
+                repo,
+                &ApplyOptions {
+                    name: Some("moving".to_string()),
+                    moved,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        }
+
+        #[test]
+        fn reapply_keeps_moved_files_in_place() {
+            let repo = create_test_repo();
+            let overlay = TempDir::new().unwrap();
+            fs::create_dir_all(overlay.path().join(".vscode")).unwrap();
+            fs::write(overlay.path().join(".envrc"), "use nix").unwrap();
+            fs::write(overlay.path().join(".vscode/settings.json"), "{}").unwrap();
+            apply(repo.path(), overlay.path(), &[]);
+
+            move_overlay_file(
+                repo.path(),
+                "moving",
+                Path::new(".vscode/settings.json"),
+                Path::new("editor/settings.json"),
+            )
+            .unwrap();
+            let state = load_overlay_state(repo.path(), "moving").unwrap();
+            assert_eq!(
+                state.moved,
+                vec![MovedFile {
+                    source: PathBuf::from(".vscode/settings.json"),
+                    target: PathBuf::from("editor/settings.json"),
+                }]
+            );
+            let entry = state
+                .files
+                .iter()
+                .find(|e| e.target == Path::new("editor/settings.json"))
+                .unwrap();
+            // Sync still writes back to the original overlay path
+            assert_eq!(entry.source, PathBuf::from(".vscode/settings.json"));
+
+            remove_overlay(repo.path(), Some("moving".to_string()), false, false, false).unwrap();
+            assert!(!repo.path().join("editor").exists());
+
+            apply(repo.path(), overlay.path(), &state.moved);
+            assert!(repo.path().join("editor/settings.json").is_symlink());
+            assert!(!repo.path().join(".vscode").exists());
+            assert!(repo.path().join(".envrc").is_symlink());
+        }
+    }
 }
@@ -0,0 +1,77 @@
+//! Log setup for `-v`/`-vv` and `--log-file`.
+//!
+//! The crate logs its decisions (how a source was resolved, why a file was
+//! linked or copied) with `debug!` and `trace!`. `-v` shows debug logs and
+//! `-vv` trace logs on stderr; without either, `RUST_LOG` still works.
+//! `--log-file` writes the same lines to a file as well, so a log can be
+//! attached to a bug report.
+
+use anyhow::{Context, Result};
+use log::LevelFilter;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Log level for a `-v` count, or `None` to defer to `RUST_LOG`.
+pub const fn level_for(verbosity: u8) -> Option<LevelFilter> {
+    match verbosity {
+        0 => None,
+        1 => Some(LevelFilter::Debug),
+        _ => Some(LevelFilter::Trace),
+    }
+}
+
+/// Initialize the logger. Must be called once, before anything is logged.
+pub fn init(verbosity: u8, log_file: Option<&Path>) -> Result<()> {
+    let mut builder = env_logger::Builder::from_default_env();
+    if let Some(level) = level_for(verbosity) {
+        // Only our own logs; dependencies stay at the RUST_LOG level
+        builder.filter_module("repoverlay", level);
+    }
+
+    if let Some(path) = log_file {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let file = File::create(path)
+            .with_context(|| format!("Failed to create log file: {}", path.display()))?;
+        builder
+            .target(env_logger::Target::Pipe(Box::new(Tee { file })))
+            .write_style(env_logger::WriteStyle::Never);
+    }
+
+    builder.try_init().context("Failed to initialize logging")
+}
+
+/// Writes log lines to stderr and to the log file.
+struct Tee {
+    file: File,
+}
+
+impl Write for Tee {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // Losing a line on stderr shouldn't keep it out of the file
+        let _ = io::stderr().write_all(buf);
+        self.file.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let _ = io::stderr().flush();
+        self.file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verbosity_maps_to_levels() {
+        assert_eq!(level_for(0), None);
+        assert_eq!(level_for(1), Some(LevelFilter::Debug));
+        assert_eq!(level_for(2), Some(LevelFilter::Trace));
+        assert_eq!(level_for(5), Some(LevelFilter::Trace));
+    }
+}
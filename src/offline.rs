@@ -0,0 +1,39 @@
+//! Offline mode (`--offline` or `REPOVERLAY_OFFLINE`).
+//!
+//! Offline, nothing touches the network: GitHub clones, archives, and overlay
+//! repos are used as they are in the cache, and anything that isn't cached is
+//! an error instead of a clone or download. Commands whose only job is
+//! talking to a remote (`update`, `sync`) refuse to run.
+
+use anyhow::{Result, bail};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Environment variable that turns on offline mode like `--offline`.
+pub const OFFLINE_ENV: &str = "REPOVERLAY_OFFLINE";
+
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Turn on offline mode if `flag` is set or `REPOVERLAY_OFFLINE` is set to
+/// anything other than empty or `0`.
+pub fn init(flag: bool) {
+    let from_env = std::env::var(OFFLINE_ENV).is_ok_and(|v| !v.is_empty() && v != "0");
+    set_offline(flag || from_env);
+}
+
+/// Enable or disable offline mode for the rest of the process.
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::Relaxed);
+}
+
+/// Whether network access is disabled.
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
+/// Fail with a clear error if offline, for operations that need the network.
+pub fn ensure_online(action: &str) -> Result<()> {
+    if is_offline() {
+        bail!("Cannot {action} in offline mode. Run again without --offline or {OFFLINE_ENV}.");
+    }
+    Ok(())
+}
@@ -0,0 +1,109 @@
+//! Status output that `--quiet` can silence.
+//!
+//! Commands report what they did on stdout ("Applying ...", "✓ Removed ...").
+//! Scripts and CI jobs that only care about the exit code pass `--quiet` to
+//! drop those lines. Warnings and errors go to stderr and are always shown.
+//!
+//! Long-running file operations print one line per item until the number of
+//! items exceeds [`PROGRESS_THRESHOLD`], then show an `indicatif` progress bar
+//! on stderr instead so large overlays don't flood the terminal.
+
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use std::fmt::Display;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Number of items above which per-item output switches to a progress bar.
+pub const PROGRESS_THRESHOLD: usize = 50;
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Suppress status output for the rest of the process.
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+/// Whether `--quiet` was passed.
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// Like `println!`, but prints nothing under `--quiet`.
+macro_rules! status {
+    ($($arg:tt)*) => {
+        if !$crate::output::is_quiet() {
+            println!($($arg)*);
+        }
+    };
+}
+
+pub(crate) use status;
+
+/// Reports progress for a fixed number of items.
+pub struct Progress {
+    /// Bar shown instead of per-item lines, if there are enough items.
+    bar: Option<ProgressBar>,
+}
+
+impl Progress {
+    /// Create a progress reporter for `total` items.
+    ///
+    /// `verbose` forces per-item output regardless of `total`. The bar is
+    /// hidden under `--quiet` and when stderr is not a terminal.
+    pub fn new(total: usize, verbose: bool) -> Self {
+        let bar = (!verbose && total > PROGRESS_THRESHOLD).then(|| {
+            let target = if is_quiet() {
+                ProgressDrawTarget::hidden()
+            } else {
+                ProgressDrawTarget::stderr()
+            };
+            #[allow(clippy::literal_string_with_formatting_args)] // indicatif template
+            let template = "  [{bar:30}] {pos}/{len}";
+            let bar = ProgressBar::with_draw_target(Some(total as u64), target);
+            if let Ok(style) = ProgressStyle::with_template(template) {
+                bar.set_style(style.progress_chars("## "));
+            }
+            bar
+        });
+        Self { bar }
+    }
+
+    /// Record a completed item, printing `line` when listing items individually.
+    pub fn item(&self, line: impl Display) {
+        match &self.bar {
+            Some(bar) => bar.inc(1),
+            None => status!("{line}"),
+        }
+    }
+
+    /// Finish reporting, clearing the progress bar if one was drawn.
+    pub fn finish(&self) {
+        if let Some(bar) = &self.bar {
+            bar.finish_and_clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_counts_list_items() {
+        assert!(Progress::new(PROGRESS_THRESHOLD, false).bar.is_none());
+    }
+
+    #[test]
+    fn large_counts_use_bar() {
+        let progress = Progress::new(PROGRESS_THRESHOLD + 1, false);
+        progress.item("  + file");
+        progress.item("  + file");
+        let bar = progress.bar.as_ref().unwrap();
+        assert_eq!(bar.position(), 2);
+        assert_eq!(bar.length(), Some(PROGRESS_THRESHOLD as u64 + 1));
+    }
+
+    #[test]
+    fn verbose_forces_item_listing() {
+        assert!(Progress::new(PROGRESS_THRESHOLD * 10, true).bar.is_none());
+    }
+}
@@ -6,13 +6,18 @@
 
 use anyhow::{Context, Result, bail};
 use chrono::{DateTime, Utc};
+use colored::Colorize;
+use log::debug;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
-use crate::config::OverlayRepoConfig;
-use crate::state::ResolvedVia;
+use crate::cache::git_spawn_error;
+use crate::config::{CommitConfig, OverlayRepoConfig, RepoverlayConfig};
+use crate::offline;
+use crate::state::{CONFIG_FILE, OverlayConfig, OverlayConfigMeta, ResolvedVia, string_or_list};
 use crate::upstream::UpstreamInfo;
 
 /// Default subdirectory name for the overlay repo clone.
@@ -21,6 +26,9 @@ const OVERLAY_REPO_DIR: &str = "overlay-repo";
 /// Metadata file name for the overlay repo.
 const OVERLAY_REPO_META: &str = ".repoverlay-overlay-repo-meta.ccl";
 
+/// Bundle definitions file at the overlay repo root.
+const BUNDLES_FILE: &str = "bundles.ccl";
+
 /// Metadata about the overlay repository clone.
 #[derive(Debug, Deserialize, Serialize)]
 pub struct OverlayRepoMeta {
@@ -43,6 +51,36 @@ pub struct AvailableOverlay {
     pub name: String,
     /// Whether the overlay has a repoverlay.ccl config file
     pub has_config: bool,
+    /// Metadata from the overlay's config (description and tags)
+    pub meta: OverlayConfigMeta,
+}
+
+/// A named group of overlays defined in the overlay repo's `bundles.ccl`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OverlayBundle {
+    /// Bundle name (e.g., "frontend-standard")
+    pub name: String,
+    /// Member overlay references (`org/repo/name`), in apply order
+    pub overlays: Vec<String>,
+}
+
+/// Member list of one bundle, written as a single value or a list.
+#[derive(Deserialize)]
+#[serde(transparent)]
+struct BundleMembers(#[serde(deserialize_with = "string_or_list")] Vec<String>);
+
+/// What an overlay repository contains compared to the expected
+/// `org/repo/name/` layout.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct LayoutReport {
+    /// Number of `org/repo/name` overlay directories
+    pub overlays: usize,
+    /// Top-level directories (the `org` level)
+    pub top_level_dirs: Vec<String>,
+    /// Files that can't belong to an overlay: files directly inside an `org`
+    /// or `repo` directory, and `repoverlay.ccl` files anywhere but directly
+    /// inside an overlay directory
+    pub misplaced: Vec<PathBuf>,
 }
 
 /// Manager for the overlay repository.
@@ -64,6 +102,71 @@ impl OverlayRepoManager {
         Ok(Self { repo_path, config })
     }
 
+    /// Create a manager for the overlay source named `source`, or the
+    /// highest-priority one. Without `sources`, the legacy `overlay_repo` is
+    /// used, under the name `default` it migrates to. Returns `None` if no
+    /// source was named and nothing is configured.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `source` names a source that isn't configured.
+    pub fn configured(config: &RepoverlayConfig, source: Option<&str>) -> Result<Option<Self>> {
+        if config.sources.is_empty() {
+            return match (source, &config.overlay_repo) {
+                (Some(name), Some(_)) if name != "default" => {
+                    bail!("Unknown source: {name}\nAvailable sources: default")
+                }
+                (Some(name), None) => bail!("Unknown source: {name}\nNo sources are configured"),
+                (_, legacy) => legacy.clone().map(Self::new).transpose(),
+            };
+        }
+
+        let source = match source {
+            Some(name) => config
+                .sources
+                .iter()
+                .find(|s| s.name == name)
+                .ok_or_else(|| {
+                    let available: Vec<&str> =
+                        config.sources.iter().map(|s| s.name.as_str()).collect();
+                    anyhow::anyhow!(
+                        "Unknown source: {name}\nAvailable sources: {}",
+                        available.join(", ")
+                    )
+                })?,
+            None => &config.sources[0],
+        };
+        crate::sources::source_repo_manager(source).map(Some)
+    }
+
+    /// Managers for every configured overlay source by name, in priority
+    /// order, or for the legacy `overlay_repo` as `default`.
+    pub fn all_configured(config: &RepoverlayConfig) -> Result<Vec<(String, Self)>> {
+        if config.sources.is_empty() {
+            return config
+                .overlay_repo
+                .clone()
+                .map(|legacy| Ok(("default".to_string(), Self::new(legacy)?)))
+                .into_iter()
+                .collect();
+        }
+        config
+            .sources
+            .iter()
+            .map(|source| {
+                Ok((
+                    source.name.clone(),
+                    crate::sources::source_repo_manager(source)?,
+                ))
+            })
+            .collect()
+    }
+
+    /// Git URL of the overlay repository.
+    pub fn url(&self) -> &str {
+        &self.config.url
+    }
+
     /// Get the path to the overlay repository.
     pub fn path(&self) -> &Path {
         &self.repo_path
@@ -77,6 +180,12 @@ impl OverlayRepoManager {
     /// Ensure the overlay repo is cloned.
     pub fn ensure_cloned(&self) -> Result<()> {
         if self.needs_clone() {
+            if offline::is_offline() {
+                bail!(
+                    "Overlay repository {} is not cloned and can't be cloned in offline mode",
+                    self.config.url
+                );
+            }
             self.clone_repo()?;
         }
         Ok(())
@@ -93,7 +202,7 @@ impl OverlayRepoManager {
             .args(["clone", "--depth", "1", &self.config.url])
             .arg(&self.repo_path)
             .output()
-            .context("Failed to execute git clone")?;
+            .map_err(|e| git_spawn_error(e, "clone"))?;
 
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
@@ -112,12 +221,16 @@ impl OverlayRepoManager {
         if !self.repo_path.exists() {
             bail!("Overlay repository not cloned. Run 'repoverlay source add <url>' first.");
         }
+        if offline::is_offline() {
+            debug!("offline, not pulling {}", self.repo_path.display());
+            return Ok(());
+        }
 
         let output = Command::new("git")
             .args(["pull", "--ff-only"])
             .current_dir(&self.repo_path)
             .output()
-            .context("Failed to execute git pull")?;
+            .map_err(|e| git_spawn_error(e, "pull"))?;
 
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
@@ -134,7 +247,7 @@ impl OverlayRepoManager {
             .args(["rev-parse", "HEAD"])
             .current_dir(&self.repo_path)
             .output()
-            .context("Failed to execute git rev-parse")?;
+            .map_err(|e| git_spawn_error(e, "rev-parse"))?;
 
         if !output.status.success() {
             bail!("Failed to get current commit");
@@ -202,13 +315,20 @@ impl OverlayRepoManager {
                     let overlay_name = overlay_entry.file_name().to_string_lossy().to_string();
 
                     // Check if it has a config file
-                    let has_config = overlay_path.join("repoverlay.ccl").exists();
+                    let config_path = overlay_path.join(CONFIG_FILE);
+                    let has_config = config_path.exists();
+                    let meta = if has_config {
+                        read_overlay_meta(&config_path)
+                    } else {
+                        OverlayConfigMeta::default()
+                    };
 
                     overlays.push(AvailableOverlay {
                         org: org_name.clone(),
                         repo: repo_name.clone(),
                         name: overlay_name,
                         has_config,
+                        meta,
                     });
                 }
             }
@@ -220,6 +340,44 @@ impl OverlayRepoManager {
         Ok(overlays)
     }
 
+    /// Check the clone against the `org/repo/name/` layout.
+    pub fn validate_layout(&self) -> Result<LayoutReport> {
+        if !self.repo_path.exists() {
+            bail!("Overlay repository not cloned. Run 'repoverlay source add <url>' first.");
+        }
+
+        let mut report = LayoutReport {
+            overlays: self.list_overlays()?.len(),
+            ..LayoutReport::default()
+        };
+        let walker = walkdir::WalkDir::new(&self.repo_path)
+            .min_depth(1)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_entry(|e| !e.file_name().to_string_lossy().starts_with('.'));
+        for entry in walker {
+            let entry = entry?;
+            let rel = entry.path().strip_prefix(&self.repo_path)?.to_path_buf();
+            let depth = entry.depth();
+            if entry.file_type().is_dir() {
+                if depth == 1 {
+                    report
+                        .top_level_dirs
+                        .push(entry.file_name().to_string_lossy().to_string());
+                }
+                continue;
+            }
+            let name = entry.file_name().to_string_lossy().to_uppercase();
+            let is_config = entry.file_name() == CONFIG_FILE;
+            // READMEs describing an org or repo are fine
+            let is_readme = name.starts_with("README") || name.starts_with("LICENSE");
+            if (matches!(depth, 2 | 3) && !is_readme) || (is_config && depth != 4) {
+                report.misplaced.push(rel);
+            }
+        }
+        Ok(report)
+    }
+
     /// List overlays for a specific target repository.
     pub fn list_overlays_for_repo(&self, org: &str, repo: &str) -> Result<Vec<AvailableOverlay>> {
         let all = self.list_overlays()?;
@@ -229,8 +387,57 @@ impl OverlayRepoManager {
             .collect())
     }
 
+    /// List the bundles defined in `bundles.ccl`, sorted by name.
+    ///
+    /// A repository without a bundles file has no bundles.
+    pub fn list_bundles(&self) -> Result<Vec<OverlayBundle>> {
+        if !self.repo_path.exists() {
+            bail!("Overlay repository not cloned. Run 'repoverlay source add <url>' first.");
+        }
+
+        let bundles_path = self.repo_path.join(BUNDLES_FILE);
+        if !bundles_path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = fs::read_to_string(&bundles_path)
+            .with_context(|| format!("Failed to read {}", bundles_path.display()))?;
+        let parsed: BTreeMap<String, BundleMembers> = sickle::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", bundles_path.display()))?;
+
+        parsed
+            .into_iter()
+            .map(|(name, BundleMembers(overlays))| {
+                if let Some(invalid) = overlays
+                    .iter()
+                    .find(|o| parse_overlay_reference(o).is_none())
+                {
+                    bail!(
+                        "Invalid overlay reference '{invalid}' in bundle '{name}' (expected org/repo/name)"
+                    );
+                }
+                Ok(OverlayBundle { name, overlays })
+            })
+            .collect()
+    }
+
+    /// Look up a bundle by name.
+    pub fn get_bundle(&self, name: &str) -> Result<OverlayBundle> {
+        let bundles = self.list_bundles()?;
+        let names: Vec<&str> = bundles.iter().map(|b| b.name.as_str()).collect();
+        if let Some(bundle) = bundles.iter().find(|b| b.name == name) {
+            return Ok(bundle.clone());
+        }
+        if names.is_empty() {
+            bail!("Bundle not found: {name} (the overlay repository defines no bundles)");
+        }
+        bail!(
+            "Bundle not found: {name}\nAvailable bundles: {}",
+            names.join(", ")
+        );
+    }
+
     /// Get the path to a specific overlay.
-    #[allow(dead_code)]
     pub fn get_overlay_path(&self, org: &str, repo: &str, name: &str) -> Result<PathBuf> {
         let path = self.repo_path.join(org).join(repo).join(name);
 
@@ -241,6 +448,36 @@ impl OverlayRepoManager {
         Ok(path)
     }
 
+    /// Delete an overlay directory, then any `org/repo` directories it
+    /// leaves empty so they aren't committed.
+    pub fn remove_overlay(&self, org: &str, repo: &str, name: &str) -> Result<()> {
+        let path = self.get_overlay_path(org, repo, name)?;
+        fs::remove_dir_all(&path)
+            .with_context(|| format!("Failed to remove overlay: {}", path.display()))?;
+        self.remove_empty_parents(&path);
+        Ok(())
+    }
+
+    /// Remove empty directories above `path`, stopping at the repo root.
+    fn remove_empty_parents(&self, path: &Path) {
+        let mut parent = path.parent();
+        while let Some(dir) = parent {
+            if dir == self.repo_path || !dir.starts_with(&self.repo_path) {
+                break;
+            }
+            if dir
+                .read_dir()
+                .map(|mut d| d.next().is_none())
+                .unwrap_or(false)
+            {
+                fs::remove_dir(dir).ok();
+                parent = dir.parent();
+            } else {
+                break;
+            }
+        }
+    }
+
     /// Get the path to a specific overlay with upstream fallback.
     ///
     /// Resolution order:
@@ -304,7 +541,7 @@ impl OverlayRepoManager {
             .args(["add", "."])
             .current_dir(&self.repo_path)
             .output()
-            .context("Failed to execute git add")?;
+            .map_err(|e| git_spawn_error(e, "add"))?;
 
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
@@ -320,19 +557,26 @@ impl OverlayRepoManager {
             .args(["diff", "--cached", "--quiet"])
             .current_dir(&self.repo_path)
             .output()
-            .context("Failed to execute git diff")?;
+            .map_err(|e| git_spawn_error(e, "diff"))?;
 
         // Exit code 0 means no changes, 1 means changes
         Ok(!output.status.success())
     }
 
-    /// Commit staged changes.
-    pub fn commit(&self, message: &str) -> Result<()> {
-        let output = Command::new("git")
-            .args(["commit", "-m", message])
+    /// Commit staged changes, as the configured author if there is one.
+    pub fn commit(&self, message: &str, author: &CommitConfig) -> Result<()> {
+        let mut command = Command::new("git");
+        command.args(["commit", "-m", message]);
+        if let Some(name) = &author.author_name {
+            command.env("GIT_AUTHOR_NAME", name);
+        }
+        if let Some(email) = &author.author_email {
+            command.env("GIT_AUTHOR_EMAIL", email);
+        }
+        let output = command
             .current_dir(&self.repo_path)
             .output()
-            .context("Failed to execute git commit")?;
+            .map_err(|e| git_spawn_error(e, "commit"))?;
 
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
@@ -345,21 +589,149 @@ impl OverlayRepoManager {
         Ok(())
     }
 
-    /// Push to remote.
-    pub fn push(&self) -> Result<()> {
+    /// Name of the checked-out branch.
+    pub fn current_branch(&self) -> Result<String> {
+        let output = self.git(&["rev-parse", "--abbrev-ref", "HEAD"], "rev-parse")?;
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// URL of the `origin` remote, if there is one.
+    pub fn remote_url(&self) -> Option<String> {
+        self.git(&["remote", "get-url", "origin"], "remote")
+            .ok()
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// Create `branch` at the current commit and check it out, keeping
+    /// staged changes.
+    pub fn create_branch(&self, branch: &str) -> Result<()> {
+        self.git(&["checkout", "-b", branch], "checkout").map(drop)
+    }
+
+    /// Check out an existing branch.
+    pub fn checkout(&self, branch: &str) -> Result<()> {
+        self.git(&["checkout", branch], "checkout").map(drop)
+    }
+
+    /// Push `branch` to `origin`, setting it as the upstream.
+    pub fn push_branch(&self, branch: &str) -> Result<()> {
+        offline::ensure_online("push")?;
+        self.git(&["push", "--set-upstream", "origin", branch], "push")
+            .map(drop)
+    }
+
+    /// Run git in the clone, failing with its stderr if it exits non-zero.
+    fn git(&self, args: &[&str], subcommand: &str) -> Result<std::process::Output> {
         let output = Command::new("git")
-            .args(["push"])
+            .args(args)
             .current_dir(&self.repo_path)
             .output()
-            .context("Failed to execute git push")?;
-
+            .map_err(|e| git_spawn_error(e, subcommand))?;
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
+            bail!("git {subcommand} failed: {}", stderr.trim());
+        }
+        Ok(output)
+    }
+
+    /// Push to remote.
+    ///
+    /// If the push is rejected because the remote has commits the clone
+    /// doesn't, rebases onto them once and pushes again. When that fails too,
+    /// the error shows the local and remote tips.
+    pub fn push(&self) -> Result<()> {
+        offline::ensure_online("push")?;
+        let output = self.git_unchecked(&["push"], "push")?;
+        if output.status.success() {
+            return Ok(());
+        }
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if !is_push_rejected_as_behind(&stderr) {
             bail!("Failed to push: {}", stderr.trim());
         }
 
+        eprintln!(
+            "{} The overlay repo remote has new commits; rebasing onto them and pushing again",
+            "Note:".yellow()
+        );
+        let pull = self.git_unchecked(&["pull", "--rebase", "--autostash"], "pull")?;
+        if !pull.status.success() {
+            // Leave the clone on its own commits rather than mid-rebase
+            let _ = self.git_unchecked(&["rebase", "--abort"], "rebase");
+            let stderr = String::from_utf8_lossy(&pull.stderr);
+            bail!(
+                "Failed to push: the remote has diverged and rebasing onto it failed: {}{}",
+                stderr.trim(),
+                self.divergence()
+            );
+        }
+
+        let output = self.git_unchecked(&["push"], "push")?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            bail!(
+                "Failed to push after rebasing onto the remote: {}{}",
+                stderr.trim(),
+                self.divergence()
+            );
+        }
+
         Ok(())
     }
+
+    /// Commits on the current branch that its upstream doesn't have, newest
+    /// first, as `<short sha> <subject>`. Compares against the remote as of
+    /// the last fetch.
+    pub fn unpushed_commits(&self) -> Result<Vec<String>> {
+        let output = self
+            .git(&["log", "--format=%h %s", "@{u}..HEAD"], "log")
+            .context("Could not compare with the upstream branch")?;
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// Run git in the clone, returning its output whether or not it succeeded.
+    fn git_unchecked(&self, args: &[&str], subcommand: &str) -> Result<std::process::Output> {
+        Command::new("git")
+            .args(args)
+            .current_dir(&self.repo_path)
+            .output()
+            .map_err(|e| git_spawn_error(e, subcommand))
+    }
+
+    /// Local and remote tips of the current branch, for push errors. Empty if
+    /// the branch has no upstream.
+    fn divergence(&self) -> String {
+        let rev = |spec: &str| {
+            self.git(&["rev-parse", "--short", spec], "rev-parse")
+                .ok()
+                .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        };
+        let counts = self
+            .git(
+                &["rev-list", "--left-right", "--count", "HEAD...@{u}"],
+                "rev-list",
+            )
+            .ok()
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string());
+        let (Some(local), Some(remote), Some(counts)) = (rev("HEAD"), rev("@{u}"), counts) else {
+            return String::new();
+        };
+        let (ahead, behind) = counts.split_once('\t').unwrap_or(("?", "?"));
+        format!(
+            "\n\n  Local:  {local} ({ahead} commit(s) not on the remote)\n  \
+             Remote: {remote} ({behind} commit(s) not in the local clone)"
+        )
+    }
+}
+
+/// Whether `git push` was rejected because the remote has commits that
+/// aren't in the local branch.
+fn is_push_rejected_as_behind(stderr: &str) -> bool {
+    stderr.contains("non-fast-forward") || stderr.contains("(fetch first)")
 }
 
 /// Get the default path for the overlay repository clone.
@@ -370,6 +742,81 @@ pub fn default_overlay_repo_path() -> Result<PathBuf> {
     Ok(crate::config::config_dir()?.join(OVERLAY_REPO_DIR))
 }
 
+/// README written into a new overlay repository by `repoverlay init`.
+const INIT_README: &str = "\
+# Overlay repository
+
+Overlays for [repoverlay](https://github.com/tylerbutler/repoverlay), stored
+by the repository they apply to:
+
+```
+<org>/<repo>/<overlay-name>/
+```
+
+For example, `acme/web/claude-config/` holds an overlay for
+`github.com/acme/web`. Apply it with:
+
+```
+repoverlay apply acme/web/claude-config
+```
+
+Create overlays from a checkout with `repoverlay create <overlay-name>`, or add
+the directories by hand. Each overlay may have a `repoverlay.ccl` describing
+it; see the repoverlay README for the options.
+";
+
+/// `bundles.ccl` written by `repoverlay init --bundles`.
+const INIT_BUNDLES: &str = "\
+/= Named groups of overlays, applied with `repoverlay apply-bundle <name>`.
+/= frontend-standard =
+/=   = acme/web/claude-config
+/=   = acme/web/editor
+";
+
+/// Create a new overlay repository at `path`: a git repository with a README
+/// explaining the `org/repo/name` layout (and a `bundles.ccl` when asked),
+/// committed.
+///
+/// # Errors
+///
+/// Returns an error if `path` exists and is not empty, or a git command fails.
+pub fn init_overlay_repo(path: &Path, with_bundles: bool) -> Result<PathBuf> {
+    if path.exists() && fs::read_dir(path)?.next().is_some() {
+        bail!(
+            "Directory is not empty: {}\n\nChoose a new directory for the overlay repository.",
+            path.display()
+        );
+    }
+    fs::create_dir_all(path).with_context(|| format!("Failed to create {}", path.display()))?;
+    let path = path.canonicalize()?;
+
+    fs::write(path.join("README.md"), INIT_README)?;
+    if with_bundles {
+        fs::write(path.join(BUNDLES_FILE), INIT_BUNDLES)?;
+    }
+
+    for (what, args) in [
+        ("init", vec!["init", "--quiet"]),
+        ("add", vec!["add", "."]),
+        (
+            "commit",
+            vec!["commit", "--quiet", "-m", "Initialize overlay repository"],
+        ),
+    ] {
+        let output = Command::new("git")
+            .args(&args)
+            .current_dir(&path)
+            .output()
+            .map_err(|e| git_spawn_error(e, what))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            bail!("git {what} failed: {}", stderr.trim());
+        }
+    }
+
+    Ok(path)
+}
+
 /// Copy a directory recursively.
 pub fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<()> {
     if !src.is_dir() {
@@ -396,6 +843,23 @@ pub fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Split a `source:org/repo/name` reference into the source name and the
+/// `org/repo/name` part. References without a source are returned as is.
+pub fn split_source_prefix(s: &str) -> (Option<&str>, &str) {
+    match s.split_once(':') {
+        Some((source, rest))
+            if !source.is_empty()
+                && source
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+                && parse_overlay_reference(rest).is_some() =>
+        {
+            (Some(source), rest)
+        }
+        _ => (None, s),
+    }
+}
+
 /// Parse an overlay reference in the format "org/repo/name".
 pub fn parse_overlay_reference(s: &str) -> Option<(String, String, String)> {
     // Must have exactly 3 parts separated by /
@@ -421,6 +885,18 @@ pub fn parse_overlay_reference(s: &str) -> Option<(String, String, String)> {
     ))
 }
 
+/// Read the metadata section of an overlay's `repoverlay.ccl`.
+///
+/// Unreadable or invalid configs yield empty metadata so one bad overlay
+/// doesn't break listing the rest.
+fn read_overlay_meta(config_path: &Path) -> OverlayConfigMeta {
+    fs::read_to_string(config_path)
+        .ok()
+        .and_then(|content| sickle::from_str::<OverlayConfig>(&content).ok())
+        .map(|config| config.overlay)
+        .unwrap_or_default()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -436,6 +912,61 @@ mod tests {
         assert_eq!(name, "claude-config");
     }
 
+    #[test]
+    fn test_split_source_prefix() {
+        assert_eq!(
+            split_source_prefix("private:acme/api/claude"),
+            (Some("private"), "acme/api/claude")
+        );
+        assert_eq!(
+            split_source_prefix("acme/api/claude"),
+            (None, "acme/api/claude")
+        );
+        // Not an overlay reference after the colon
+        assert_eq!(
+            split_source_prefix("https://github.com/acme/api"),
+            (None, "https://github.com/acme/api")
+        );
+        assert_eq!(
+            split_source_prefix("C:/dir/overlay"),
+            (None, "C:/dir/overlay")
+        );
+        assert_eq!(
+            split_source_prefix(":acme/api/claude"),
+            (None, ":acme/api/claude")
+        );
+    }
+
+    #[test]
+    fn test_configured_legacy_overlay_repo_is_default_source() {
+        let config = RepoverlayConfig {
+            overlay_repo: Some(OverlayRepoConfig {
+                url: "https://github.com/acme/overlays".to_string(),
+                local_path: Some(PathBuf::from("/tmp/overlays")),
+            }),
+            ..Default::default()
+        };
+        let manager = OverlayRepoManager::configured(&config, None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(manager.path(), Path::new("/tmp/overlays"));
+        assert!(
+            OverlayRepoManager::configured(&config, Some("default"))
+                .unwrap()
+                .is_some()
+        );
+
+        let err = OverlayRepoManager::configured(&config, Some("private"))
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("Unknown source: private"));
+        assert!(
+            OverlayRepoManager::configured(&RepoverlayConfig::default(), None)
+                .unwrap()
+                .is_none()
+        );
+    }
+
     #[test]
     fn test_parse_overlay_reference_invalid_path() {
         assert!(parse_overlay_reference("./local/path").is_none());
@@ -492,6 +1023,7 @@ mod tests {
             repo: "FluidFramework".to_string(),
             name: "claude-config".to_string(),
             has_config: true,
+            meta: OverlayConfigMeta::default(),
         };
 
         let cloned = overlay.clone();
@@ -727,7 +1259,7 @@ mod tests {
         // Add a config file to one overlay
         fs::write(
             repo_path.join("microsoft/FluidFramework/claude-config/repoverlay.ccl"),
-            "# config",
+            "overlay =\n  description = Claude settings\n  tags =\n    = ai\n",
         )
         .unwrap();
 
@@ -746,6 +1278,12 @@ mod tests {
         assert_eq!(overlays[0].repo, "FluidFramework");
         assert_eq!(overlays[0].name, "claude-config");
         assert!(overlays[0].has_config);
+        assert_eq!(
+            overlays[0].meta.description.as_deref(),
+            Some("Claude settings")
+        );
+        assert!(overlays[0].meta.has_tag("ai"));
+        assert!(overlays[1].meta.tags.is_empty());
 
         assert_eq!(overlays[1].org, "microsoft");
         assert_eq!(overlays[1].repo, "FluidFramework");
@@ -810,6 +1348,97 @@ mod tests {
         assert_eq!(overlays[0].name, "overlay");
     }
 
+    #[test]
+    fn test_validate_layout_reports_misplaced_files() {
+        let temp = TempDir::new().unwrap();
+        let repo_path = temp.path().join("overlay-repo");
+        fs::create_dir_all(repo_path.join(".git")).unwrap();
+        fs::create_dir_all(repo_path.join("org/repo/overlay")).unwrap();
+        fs::write(repo_path.join("org/repo/overlay/repoverlay.ccl"), "").unwrap();
+        fs::write(repo_path.join("org/repo/overlay/.envrc"), "").unwrap();
+        fs::write(repo_path.join("README.md"), "readme").unwrap();
+        fs::write(repo_path.join("org/README.md"), "readme").unwrap();
+
+        // An overlay committed one level too high, and a config at the root
+        fs::create_dir_all(repo_path.join("my-overlay")).unwrap();
+        fs::write(repo_path.join("my-overlay/repoverlay.ccl"), "").unwrap();
+        fs::write(repo_path.join("my-overlay/CLAUDE.md"), "").unwrap();
+        fs::write(repo_path.join("repoverlay.ccl"), "").unwrap();
+
+        let config = OverlayRepoConfig {
+            url: "https://github.com/org/overlays".to_string(),
+            local_path: Some(repo_path),
+        };
+        let manager = OverlayRepoManager::new(config).unwrap();
+        let report = manager.validate_layout().unwrap();
+
+        assert_eq!(report.overlays, 1);
+        assert_eq!(report.top_level_dirs, vec!["my-overlay", "org"]);
+        assert_eq!(
+            report.misplaced,
+            vec![
+                PathBuf::from("my-overlay/CLAUDE.md"),
+                PathBuf::from("my-overlay/repoverlay.ccl"),
+                PathBuf::from("repoverlay.ccl"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_layout_accepts_expected_layout() {
+        let temp = TempDir::new().unwrap();
+        let repo_path = temp.path().join("overlay-repo");
+        fs::create_dir_all(repo_path.join(".git")).unwrap();
+        fs::create_dir_all(repo_path.join("org/repo/overlay/nested")).unwrap();
+        fs::write(repo_path.join("org/repo/overlay/repoverlay.ccl"), "").unwrap();
+        fs::write(repo_path.join("org/repo/overlay/nested/file"), "").unwrap();
+        fs::write(repo_path.join("bundles.ccl"), "").unwrap();
+
+        let config = OverlayRepoConfig {
+            url: "https://github.com/org/overlays".to_string(),
+            local_path: Some(repo_path),
+        };
+        let manager = OverlayRepoManager::new(config).unwrap();
+        let report = manager.validate_layout().unwrap();
+
+        assert_eq!(report.overlays, 1);
+        assert!(report.misplaced.is_empty());
+    }
+
+    #[test]
+    fn test_remove_overlay_prunes_empty_parents() {
+        let temp = TempDir::new().unwrap();
+        let repo_path = temp.path().join("overlay-repo");
+        fs::create_dir_all(repo_path.join(".git")).unwrap();
+        fs::create_dir_all(repo_path.join("org/repo/first")).unwrap();
+        fs::create_dir_all(repo_path.join("org/repo/second")).unwrap();
+        fs::create_dir_all(repo_path.join("org/other/third")).unwrap();
+
+        let config = OverlayRepoConfig {
+            url: "https://github.com/org/overlays".to_string(),
+            local_path: Some(repo_path.clone()),
+        };
+        let manager = OverlayRepoManager::new(config).unwrap();
+
+        // A sibling overlay keeps org/repo/
+        manager.remove_overlay("org", "repo", "first").unwrap();
+        assert!(!repo_path.join("org/repo/first").exists());
+        assert!(repo_path.join("org/repo").exists());
+
+        // The last overlay in org/repo/ takes the directory with it
+        manager.remove_overlay("org", "repo", "second").unwrap();
+        assert!(!repo_path.join("org/repo").exists());
+        assert!(repo_path.join("org/other/third").exists());
+
+        // ...and the last repo in org/ takes org/, but never the root
+        manager.remove_overlay("org", "other", "third").unwrap();
+        assert!(!repo_path.join("org").exists());
+        assert!(repo_path.exists());
+
+        let err = manager.remove_overlay("org", "repo", "first").unwrap_err();
+        assert!(err.to_string().contains("Overlay not found"));
+    }
+
     #[test]
     fn test_list_overlays_for_repo_filters_correctly() {
         let temp = TempDir::new().unwrap();
@@ -982,4 +1611,198 @@ mod tests {
 
         assert!(result.is_err());
     }
+
+    fn manager_with_bundles(content: Option<&str>) -> (TempDir, OverlayRepoManager) {
+        let temp = TempDir::new().unwrap();
+        let repo_path = temp.path().join("overlay-repo");
+        fs::create_dir_all(repo_path.join(".git")).unwrap();
+        if let Some(content) = content {
+            fs::write(repo_path.join(BUNDLES_FILE), content).unwrap();
+        }
+
+        let config = OverlayRepoConfig {
+            url: "https://github.com/org/overlays".to_string(),
+            local_path: Some(repo_path),
+        };
+        (temp, OverlayRepoManager::new(config).unwrap())
+    }
+
+    #[test]
+    fn test_list_bundles_without_file() {
+        let (_temp, manager) = manager_with_bundles(None);
+        assert!(manager.list_bundles().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_list_bundles_parses_members_in_order() {
+        let (_temp, manager) = manager_with_bundles(Some(
+            "frontend =\n  = acme/web/prettier\n  = acme/web/eslint\nbackend = acme/api/env\n",
+        ));
+
+        let bundles = manager.list_bundles().unwrap();
+
+        assert_eq!(
+            bundles,
+            vec![
+                OverlayBundle {
+                    name: "backend".to_string(),
+                    overlays: vec!["acme/api/env".to_string()],
+                },
+                OverlayBundle {
+                    name: "frontend".to_string(),
+                    overlays: vec![
+                        "acme/web/prettier".to_string(),
+                        "acme/web/eslint".to_string(),
+                    ],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_list_bundles_rejects_invalid_reference() {
+        let (_temp, manager) = manager_with_bundles(Some("frontend =\n  = ./local\n"));
+
+        let err = manager.list_bundles().unwrap_err().to_string();
+
+        assert!(err.contains("'./local' in bundle 'frontend'"), "got: {err}");
+    }
+
+    #[test]
+    fn test_get_bundle_not_found() {
+        let (_temp, manager) = manager_with_bundles(Some("frontend = acme/web/eslint\n"));
+
+        assert_eq!(
+            manager.get_bundle("frontend").unwrap().overlays,
+            vec!["acme/web/eslint"]
+        );
+        let err = manager.get_bundle("backend").unwrap_err().to_string();
+        assert!(err.contains("Available bundles: frontend"), "got: {err}");
+    }
+
+    /// Run git in `dir` as a test user, panicking if it fails.
+    fn git_in(dir: &Path, args: &[&str]) {
+        let output = Command::new("git")
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "git {args:?} failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    /// A bare remote with one commit, a manager for one clone of it, and a
+    /// second clone to push competing commits from.
+    fn diverging_clones() -> (TempDir, OverlayRepoManager, PathBuf) {
+        let temp = TempDir::new().unwrap();
+        let remote = temp.path().join("remote.git");
+        fs::create_dir_all(&remote).unwrap();
+        git_in(&remote, &["init", "--quiet", "--bare"]);
+
+        let other = temp.path().join("other");
+        let clone = temp.path().join("clone");
+        for dir in [&other, &clone] {
+            git_in(
+                temp.path(),
+                &[
+                    "clone",
+                    "--quiet",
+                    remote.to_str().unwrap(),
+                    dir.to_str().unwrap(),
+                ],
+            );
+            git_in(dir, &["config", "user.name", "Test"]);
+            git_in(dir, &["config", "user.email", "test@example.com"]);
+        }
+        fs::write(other.join("README.md"), "overlays").unwrap();
+        git_in(&other, &["add", "."]);
+        git_in(&other, &["commit", "--quiet", "-m", "initial"]);
+        git_in(&other, &["push", "--quiet", "origin", "HEAD"]);
+        git_in(&clone, &["pull", "--quiet", "origin"]);
+        let branch = String::from_utf8(
+            Command::new("git")
+                .args(["rev-parse", "--abbrev-ref", "HEAD"])
+                .current_dir(&other)
+                .output()
+                .unwrap()
+                .stdout,
+        )
+        .unwrap();
+        git_in(&clone, &["checkout", "--quiet", branch.trim()]);
+
+        let manager = OverlayRepoManager::new(OverlayRepoConfig {
+            url: remote.display().to_string(),
+            local_path: Some(clone),
+        })
+        .unwrap();
+        (temp, manager, other)
+    }
+
+    #[test]
+    fn test_push_rebases_when_remote_moved() {
+        let (_temp, manager, other) = diverging_clones();
+
+        fs::write(other.join("other.txt"), "theirs").unwrap();
+        git_in(&other, &["add", "."]);
+        git_in(&other, &["commit", "--quiet", "-m", "theirs"]);
+        git_in(&other, &["push", "--quiet"]);
+
+        fs::write(manager.path().join("mine.txt"), "mine").unwrap();
+        git_in(manager.path(), &["add", "."]);
+        git_in(manager.path(), &["commit", "--quiet", "-m", "mine"]);
+
+        manager.push().unwrap();
+
+        git_in(&other, &["pull", "--quiet"]);
+        assert!(other.join("mine.txt").exists());
+        assert!(manager.path().join("other.txt").exists());
+    }
+
+    #[test]
+    fn test_push_shows_tips_when_rebase_conflicts() {
+        let (_temp, manager, other) = diverging_clones();
+
+        fs::write(other.join("README.md"), "theirs").unwrap();
+        git_in(&other, &["commit", "--quiet", "-am", "theirs"]);
+        git_in(&other, &["push", "--quiet"]);
+
+        fs::write(manager.path().join("README.md"), "mine").unwrap();
+        git_in(manager.path(), &["commit", "--quiet", "-am", "mine"]);
+
+        let err = manager.push().unwrap_err().to_string();
+        assert!(err.contains("rebasing onto it failed"), "got: {err}");
+        assert!(err.contains("Local:"), "got: {err}");
+        assert!(
+            err.contains("(1 commit(s) not on the remote)"),
+            "got: {err}"
+        );
+        assert!(
+            err.contains("(1 commit(s) not in the local clone)"),
+            "got: {err}"
+        );
+
+        // The clone is left on its own commit, not mid-rebase
+        assert!(!manager.path().join(".git/rebase-merge").exists());
+        assert_eq!(
+            fs::read_to_string(manager.path().join("README.md")).unwrap(),
+            "mine"
+        );
+    }
+
+    #[test]
+    fn test_is_push_rejected_as_behind() {
+        assert!(is_push_rejected_as_behind(
+            " ! [rejected]        main -> main (fetch first)\nerror: failed to push some refs"
+        ));
+        assert!(is_push_rejected_as_behind(
+            " ! [rejected]        main -> main (non-fast-forward)"
+        ));
+        assert!(!is_push_rejected_as_behind(
+            "fatal: could not read Username for 'https://github.com'"
+        ));
+    }
 }
@@ -0,0 +1,223 @@
+//! Retries for network operations (clones, fetches, downloads).
+//!
+//! GitHub throttles busy clients and connections drop mid-clone; those
+//! failures are retried with exponential backoff. Failures that won't go away
+//! by themselves (bad credentials, a missing repository or ref) fail at once.
+//! The number of retries is the `network_retries` global setting.
+
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use colored::Colorize;
+use log::debug;
+use std::time::Duration;
+
+/// Retries after the first attempt when `network_retries` isn't set.
+pub const DEFAULT_NETWORK_RETRIES: u32 = 3;
+
+/// Wait before the first retry; doubled for each one after.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Error message fragments of failures worth retrying.
+const TRANSIENT: [&str; 16] = [
+    "timed out",
+    "timeout",
+    "could not resolve host",
+    "connection reset",
+    "connection refused",
+    "connection was reset",
+    "failed to connect",
+    "early eof",
+    "unexpected disconnect",
+    "the remote end hung up",
+    "rpc failed",
+    "operation too slow",
+    "error: 500",
+    "error: 502",
+    "error: 503",
+    "error: 504",
+];
+
+/// How a failed network operation is handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Failure {
+    /// The server is throttling requests (HTTP 429 or a rate limit message)
+    RateLimited,
+    /// A network hiccup or server error that may succeed on a retry
+    Transient,
+    /// Anything else, including auth and not-found errors
+    Permanent,
+}
+
+impl Failure {
+    /// Classify an error message (git or curl stderr, possibly wrapped).
+    pub fn classify(message: &str) -> Self {
+        let lower = message.to_lowercase();
+        if lower.contains("rate limit")
+            || lower.contains("too many requests")
+            || lower.contains("error: 429")
+        {
+            return Self::RateLimited;
+        }
+        if TRANSIENT.iter().any(|pattern| lower.contains(pattern)) {
+            Self::Transient
+        } else {
+            Self::Permanent
+        }
+    }
+}
+
+/// Run `op`, retrying rate-limited and transient failures up to `retries`
+/// times with exponential backoff. `what` describes the operation in the
+/// retry messages, e.g. "clone owner/repo".
+pub fn retry<T>(what: &str, retries: u32, op: impl FnMut() -> Result<T>) -> Result<T> {
+    retry_with_backoff(what, retries, INITIAL_BACKOFF, op)
+}
+
+fn retry_with_backoff<T>(
+    what: &str,
+    retries: u32,
+    initial_backoff: Duration,
+    mut op: impl FnMut() -> Result<T>,
+) -> Result<T> {
+    let mut backoff = initial_backoff;
+    let mut attempt = 0;
+    loop {
+        let err = match op() {
+            Ok(value) => return Ok(value),
+            Err(err) => err,
+        };
+        let failure = Failure::classify(&format!("{err:#}"));
+        debug!(
+            "{what} failed ({failure:?}, attempt {}): {err:#}",
+            attempt + 1
+        );
+        if failure == Failure::Permanent || attempt >= retries {
+            return Err(err);
+        }
+
+        attempt += 1;
+        let reason = match failure {
+            Failure::RateLimited => "rate limited",
+            _ => "network error",
+        };
+        eprintln!(
+            "  {} Failed to {what} ({reason}), retrying in {}s ({attempt}/{retries})...",
+            "Warning:".yellow(),
+            backoff.as_secs()
+        );
+        std::thread::sleep(backoff);
+        backoff = backoff.saturating_mul(2);
+    }
+}
+
+/// When a rate limit resets, from HTTP response headers (`Retry-After` in
+/// seconds, or GitHub's `X-RateLimit-Reset` as a Unix timestamp).
+pub fn rate_limit_reset(headers: &str, now: DateTime<Local>) -> Option<DateTime<Local>> {
+    let header = |name: &str| {
+        headers.lines().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            key.trim()
+                .eq_ignore_ascii_case(name)
+                .then(|| value.trim().to_string())
+        })
+    };
+    if let Some(seconds) = header("retry-after").and_then(|v| v.parse::<i64>().ok()) {
+        return Some(now + chrono::Duration::seconds(seconds));
+    }
+    let reset = header("x-ratelimit-reset")?.parse::<i64>().ok()?;
+    DateTime::from_timestamp(reset, 0).map(|reset| reset.with_timezone(&Local))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::bail;
+    use chrono::TimeZone;
+    use std::cell::Cell;
+
+    #[test]
+    fn classifies_failures() {
+        assert_eq!(
+            Failure::classify("The requested URL returned error: 429"),
+            Failure::RateLimited
+        );
+        assert_eq!(
+            Failure::classify("API rate limit exceeded for 1.2.3.4"),
+            Failure::RateLimited
+        );
+        assert_eq!(
+            Failure::classify("fatal: unable to access '...': Could not resolve host: github.com"),
+            Failure::Transient
+        );
+        assert_eq!(
+            Failure::classify("error: RPC failed; curl 56 Recv failure: Connection reset by peer"),
+            Failure::Transient
+        );
+        assert_eq!(
+            Failure::classify("The requested URL returned error: 503"),
+            Failure::Transient
+        );
+        assert_eq!(
+            Failure::classify("Repository not found: owner/repo"),
+            Failure::Permanent
+        );
+        assert_eq!(
+            Failure::classify("fatal: Authentication failed for 'https://github.com/o/r'"),
+            Failure::Permanent
+        );
+    }
+
+    #[test]
+    fn retries_transient_failures_until_success() {
+        let calls = Cell::new(0);
+        let result = retry_with_backoff("clone o/r", 3, Duration::ZERO, || {
+            calls.set(calls.get() + 1);
+            if calls.get() < 3 {
+                bail!("fatal: the remote end hung up unexpectedly");
+            }
+            Ok(calls.get())
+        });
+        assert_eq!(result.unwrap(), 3);
+    }
+
+    #[test]
+    fn gives_up_after_retries() {
+        let calls = Cell::new(0);
+        let result: Result<()> = retry_with_backoff("clone o/r", 2, Duration::ZERO, || {
+            calls.set(calls.get() + 1);
+            bail!("The requested URL returned error: 429")
+        });
+        assert!(result.is_err());
+        assert_eq!(calls.get(), 3);
+    }
+
+    #[test]
+    fn permanent_failures_are_not_retried() {
+        let calls = Cell::new(0);
+        let result: Result<()> = retry_with_backoff("clone o/r", 3, Duration::ZERO, || {
+            calls.set(calls.get() + 1);
+            bail!("Repository not found: o/r")
+        });
+        assert!(result.is_err());
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn reads_rate_limit_reset_from_headers() {
+        let now = Local.timestamp_opt(1_700_000_000, 0).unwrap();
+
+        let headers = "HTTP/2 429\r\nretry-after: 60\r\n";
+        assert_eq!(
+            rate_limit_reset(headers, now),
+            Some(now + chrono::Duration::seconds(60))
+        );
+
+        let headers = "HTTP/2 403\r\nX-RateLimit-Remaining: 0\r\nX-RateLimit-Reset: 1700000120\r\n";
+        assert_eq!(
+            rate_limit_reset(headers, now),
+            Some(Local.timestamp_opt(1_700_000_120, 0).unwrap())
+        );
+
+        assert_eq!(rate_limit_reset("HTTP/2 404\r\n", now), None);
+    }
+}
@@ -15,7 +15,7 @@ use crossterm::{
     terminal::{self, ClearType},
 };
 
-use crate::detection::{DetectedFile, FileCategory};
+use crate::detection::{DetectedFile, DiscoveryEntry, FileCategory, group_untracked_by_dir};
 
 /// Format a number in a human-readable way (e.g., 1.2K, 3.5M).
 #[allow(clippy::cast_precision_loss)]
@@ -43,6 +43,8 @@ pub struct SelectionConfig {
     pub prompt: String,
     /// Categories to hide by default.
     pub default_hidden_categories: HashSet<FileCategory>,
+    /// Files chosen last time, selected along with the preselected ones.
+    pub previously_selected: HashSet<PathBuf>,
 }
 
 impl Default for SelectionConfig {
@@ -53,6 +55,7 @@ impl Default for SelectionConfig {
         Self {
             prompt: "Select files to include in overlay".to_string(),
             default_hidden_categories: hidden,
+            previously_selected: HashSet::new(),
         }
     }
 }
@@ -82,6 +85,8 @@ struct SelectionState {
     cursor: usize,
     /// Scroll offset for the file list.
     scroll_offset: usize,
+    /// Top-level directories whose untracked files are listed individually.
+    expanded_dirs: HashSet<PathBuf>,
 }
 
 impl SelectionState {
@@ -112,6 +117,7 @@ impl SelectionState {
             mode: Mode::Selection,
             cursor: 0,
             scroll_offset: 0,
+            expanded_dirs: HashSet::new(),
         }
     }
 
@@ -133,6 +139,24 @@ impl SelectionState {
             .collect()
     }
 
+    /// Get the rows of the file list: visible files, with untracked files
+    /// collapsed by top-level directory unless expanded or searching.
+    fn visible_rows(&self) -> Vec<DiscoveryEntry<'_>> {
+        let visible = self.visible_files();
+        if !self.search_query.is_empty() {
+            return visible.into_iter().map(DiscoveryEntry::File).collect();
+        }
+        group_untracked_by_dir(&visible)
+            .into_iter()
+            .flat_map(|entry| match entry {
+                DiscoveryEntry::Directory { path, files } if self.expanded_dirs.contains(&path) => {
+                    files.into_iter().map(DiscoveryEntry::File).collect()
+                }
+                entry => vec![entry],
+            })
+            .collect()
+    }
+
     /// Check if any filters are active.
     fn has_active_filters(&self) -> bool {
         !self.search_query.is_empty() || self.visible_categories.len() < 4 // Not all categories visible
@@ -167,15 +191,64 @@ impl SelectionState {
         }
     }
 
-    /// Toggle selection of the file at the current cursor position.
+    /// Toggle selection of the row at the current cursor position.
+    ///
+    /// A collapsed directory is selected as a whole, or deselected if all of
+    /// its files are selected already.
     fn toggle_current(&mut self) {
-        let visible = self.visible_files();
-        if let Some(file) = visible.get(self.cursor) {
-            let path = file.path.clone();
-            self.toggle_selection(&path);
+        let paths: Vec<PathBuf> = match self.visible_rows().get(self.cursor) {
+            Some(DiscoveryEntry::File(file)) => vec![file.path.clone()],
+            Some(DiscoveryEntry::Directory { files, .. }) => {
+                files.iter().map(|f| f.path.clone()).collect()
+            }
+            None => return,
+        };
+        self.toggle_all(paths);
+    }
+
+    /// Select all of `paths`, or deselect them if they are all selected.
+    fn toggle_all(&mut self, paths: Vec<PathBuf>) {
+        if paths.iter().all(|p| self.selections.contains(p)) {
+            for path in &paths {
+                self.selections.remove(path);
+            }
+        } else {
+            self.selections.extend(paths);
         }
     }
 
+    /// List the files of the collapsed directory under the cursor.
+    fn expand_current(&mut self) {
+        if let Some(DiscoveryEntry::Directory { path, .. }) = self.visible_rows().get(self.cursor) {
+            let path = path.clone();
+            self.expanded_dirs.insert(path);
+        }
+    }
+
+    /// Collapse the expanded directory containing the file under the cursor.
+    fn collapse_current(&mut self) {
+        let Some(DiscoveryEntry::File(file)) = self.visible_rows().get(self.cursor).cloned() else {
+            return;
+        };
+        let Some(dir) = self
+            .expanded_dirs
+            .iter()
+            .find(|dir| file.path.starts_with(dir))
+            .cloned()
+        else {
+            return;
+        };
+        self.expanded_dirs.remove(&dir);
+        if let Some(row) = self
+            .visible_rows()
+            .iter()
+            .position(|row| matches!(row, DiscoveryEntry::Directory { path, .. } if *path == dir))
+        {
+            self.cursor = row;
+        }
+        self.clamp_cursor();
+    }
+
     /// Select all visible files.
     fn select_all_visible(&mut self) {
         let paths: Vec<PathBuf> = self
@@ -207,6 +280,35 @@ impl SelectionState {
         }
     }
 
+    /// Invert the selection of all visible files.
+    fn invert_visible(&mut self) {
+        let paths: Vec<PathBuf> = self
+            .visible_files()
+            .iter()
+            .map(|f| f.path.clone())
+            .collect();
+        for path in paths {
+            self.toggle_selection(&path);
+        }
+    }
+
+    /// Select every file in the category of the row under the cursor, or
+    /// deselect them if they are all selected already.
+    fn toggle_current_category(&mut self) {
+        let category = match self.visible_rows().get(self.cursor) {
+            Some(DiscoveryEntry::File(file)) => file.category,
+            Some(DiscoveryEntry::Directory { .. }) => FileCategory::Untracked,
+            None => return,
+        };
+        let paths: Vec<PathBuf> = self
+            .all_files
+            .iter()
+            .filter(|f| f.category == category)
+            .map(|f| f.path.clone())
+            .collect();
+        self.toggle_all(paths);
+    }
+
     /// Get selection counts per category: (selected, total).
     fn selection_counts(&self) -> HashMap<FileCategory, (usize, usize)> {
         let mut counts = HashMap::new();
@@ -239,7 +341,7 @@ impl SelectionState {
 
     /// Move cursor down.
     fn cursor_down(&mut self) {
-        let visible_count = self.visible_files().len();
+        let visible_count = self.visible_rows().len();
         if self.cursor + 1 < visible_count {
             self.cursor += 1;
             self.adjust_scroll();
@@ -248,7 +350,7 @@ impl SelectionState {
 
     /// Clamp cursor to valid range after filter changes.
     fn clamp_cursor(&mut self) {
-        let visible_count = self.visible_files().len();
+        let visible_count = self.visible_rows().len();
         if visible_count == 0 {
             self.cursor = 0;
         } else if self.cursor >= visible_count {
@@ -285,11 +387,20 @@ impl SelectionState {
 /// # Non-TTY Fallback
 ///
 /// If stdin is not a TTY (e.g., piped input), this function falls back to
-/// returning all preselected files (AI configs) without showing the UI.
+/// returning all preselected files (AI configs and previously selected files)
+/// without showing the UI.
 pub fn select_files(
     files: &[DetectedFile],
     config: SelectionConfig,
 ) -> anyhow::Result<SelectionResult> {
+    let files: Vec<DetectedFile> = files
+        .iter()
+        .map(|f| DetectedFile {
+            preselected: f.preselected || config.previously_selected.contains(&f.path),
+            ..f.clone()
+        })
+        .collect();
+
     // Non-TTY fallback: return preselected files
     if !atty_is_interactive() {
         let selected: Vec<PathBuf> = files
@@ -311,7 +422,7 @@ pub fn select_files(
         });
     }
 
-    let mut state = SelectionState::new(files.to_vec(), config.default_hidden_categories);
+    let mut state = SelectionState::new(files, config.default_hidden_categories);
 
     // Enter raw mode for keyboard input
     terminal::enable_raw_mode()?;
@@ -341,7 +452,7 @@ pub fn select_files(
 /// - Running as a cargo test binary (executable in target/*/deps/)
 /// - TERM is unset or "dumb"
 /// - `REPOVERLAY_NON_INTERACTIVE` env var is set
-fn atty_is_interactive() -> bool {
+pub fn atty_is_interactive() -> bool {
     use std::io::IsTerminal;
 
     // Explicit non-interactive override
@@ -426,6 +537,8 @@ fn handle_selection_key(state: &mut SelectionState, key: KeyEvent) -> SelectionA
         // Navigation
         KeyCode::Up | KeyCode::Char('k') => state.cursor_up(),
         KeyCode::Down | KeyCode::Char('j') => state.cursor_down(),
+        KeyCode::Right | KeyCode::Char('l') => state.expand_current(),
+        KeyCode::Left | KeyCode::Char('h') => state.collapse_current(),
 
         // Selection
         KeyCode::Char(' ') => state.toggle_current(),
@@ -465,6 +578,13 @@ fn handle_selection_key(state: &mut SelectionState, key: KeyEvent) -> SelectionA
             state.select_all();
         }
 
+        // Select none / invert (visible files)
+        KeyCode::Char('n') => state.deselect_all_visible(),
+        KeyCode::Char('i') => state.invert_visible(),
+
+        // Select or deselect the whole category under the cursor
+        KeyCode::Char('c') => state.toggle_current_category(),
+
         _ => {}
     }
     SelectionAction::Continue
@@ -713,7 +833,7 @@ fn render_selection_summary(stdout: &mut io::Stdout, state: &SelectionState) ->
 
 /// Render the file list.
 fn render_file_list(stdout: &mut io::Stdout, state: &SelectionState) -> io::Result<()> {
-    let visible = state.visible_files();
+    let visible = state.visible_rows();
     let max_visible = 15;
 
     if visible.is_empty() {
@@ -739,14 +859,23 @@ fn render_file_list(stdout: &mut io::Stdout, state: &SelectionState) -> io::Resu
         )?;
     }
 
-    for (i, file) in visible
+    for (i, row) in visible
         .iter()
         .enumerate()
         .skip(state.scroll_offset)
         .take(max_visible)
     {
         let is_cursor = i == state.cursor;
-        let is_selected = state.selections.contains(&file.path);
+        let (selected, total) = match row {
+            DiscoveryEntry::File(file) => (usize::from(state.selections.contains(&file.path)), 1),
+            DiscoveryEntry::Directory { files, .. } => (
+                files
+                    .iter()
+                    .filter(|f| state.selections.contains(&f.path))
+                    .count(),
+                files.len(),
+            ),
+        };
 
         // Cursor indicator
         if is_cursor {
@@ -755,20 +884,31 @@ fn render_file_list(stdout: &mut io::Stdout, state: &SelectionState) -> io::Resu
             execute!(stdout, Print("  "))?;
         }
 
-        // Checkbox
-        if is_selected {
+        // Checkbox (partial for a directory with some files selected)
+        if selected == total {
             execute!(
                 stdout,
                 SetForegroundColor(Color::Green),
                 Print("[✓] "),
                 ResetColor
             )?;
+        } else if selected > 0 {
+            execute!(
+                stdout,
+                SetForegroundColor(Color::Green),
+                Print("[-] "),
+                ResetColor
+            )?;
         } else {
             execute!(stdout, Print("[ ] "))?;
         }
 
         // Category indicator
-        let cat_color = match file.category {
+        let category = match row {
+            DiscoveryEntry::File(file) => file.category,
+            DiscoveryEntry::Directory { .. } => FileCategory::Untracked,
+        };
+        let cat_color = match category {
             FileCategory::AiConfig => Color::Green,
             FileCategory::AiConfigDirectory => Color::Magenta,
             FileCategory::Gitignored => Color::Yellow,
@@ -777,10 +917,16 @@ fn render_file_list(stdout: &mut io::Stdout, state: &SelectionState) -> io::Resu
 
         // File path (highlight search match if any)
         // Add trailing slash for directories
-        let path_str = if file.category == FileCategory::AiConfigDirectory {
-            format!("{}/", file.path.to_string_lossy())
-        } else {
-            file.path.to_string_lossy().to_string()
+        let path_str = match row {
+            DiscoveryEntry::File(file) if file.category == FileCategory::AiConfigDirectory => {
+                format!("{}/", file.path.to_string_lossy())
+            }
+            DiscoveryEntry::File(file) => file.path.to_string_lossy().to_string(),
+            DiscoveryEntry::Directory { path, files } => format!(
+                "{}/ ({} files, → to expand)",
+                path.to_string_lossy(),
+                humanize_count(files.len())
+            ),
         };
         if is_cursor {
             execute!(
@@ -852,9 +998,13 @@ fn render_help_line(stdout: &mut io::Stdout, state: &SelectionState) -> io::Resu
         Ok(())
     } else {
         render_key_hint(stdout, "↑↓", "move")?;
+        render_key_hint(stdout, "←→", "fold")?;
         render_key_hint(stdout, "Space", "toggle")?;
         render_key_hint(stdout, "Enter", "confirm")?;
         render_key_hint(stdout, "a", "all")?;
+        render_key_hint(stdout, "n", "none")?;
+        render_key_hint(stdout, "i", "invert")?;
+        render_key_hint(stdout, "c", "category")?;
         render_key_hint(stdout, "1-4", "filter")?;
         render_key_hint(stdout, "/", "search")?;
         render_key_hint(stdout, "Esc", "cancel")?;
@@ -896,6 +1046,26 @@ mod tests {
         ]
     }
 
+    #[test]
+    fn test_select_files_includes_previous_selection() {
+        // Tests aren't interactive, so this takes the non-TTY fallback
+        let config = SelectionConfig {
+            previously_selected: [PathBuf::from(".envrc"), PathBuf::from("gone.txt")]
+                .into_iter()
+                .collect(),
+            ..SelectionConfig::default()
+        };
+        let result = select_files(&make_test_files(), config).unwrap();
+        assert_eq!(
+            result.selected_files,
+            vec![
+                PathBuf::from("CLAUDE.md"),
+                PathBuf::from(".claude/settings.json"),
+                PathBuf::from(".envrc"),
+            ]
+        );
+    }
+
     #[test]
     fn test_toggle_category_hides_files() {
         let files = make_test_files();
@@ -1198,6 +1368,90 @@ mod tests {
         assert!(state.selections.contains(Path::new(".envrc")));
     }
 
+    fn press(state: &mut SelectionState, c: char) {
+        handle_selection_key(state, KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+    }
+
+    #[test]
+    fn test_select_none_and_invert_keys() {
+        let mut state = SelectionState::new(make_test_files(), HashSet::new());
+
+        // Invert: AI configs were preselected, so everything else is now selected
+        press(&mut state, 'i');
+        assert_eq!(state.selections.len(), 3);
+        assert!(!state.selections.contains(Path::new("CLAUDE.md")));
+        assert!(state.selections.contains(Path::new("scratch.txt")));
+
+        // Invert only touches visible files
+        state.toggle_category(FileCategory::Untracked);
+        press(&mut state, 'i');
+        assert_eq!(state.selections.len(), 3);
+        assert!(state.selections.contains(Path::new("CLAUDE.md")));
+        assert!(state.selections.contains(Path::new("scratch.txt")));
+
+        press(&mut state, 'n');
+        assert_eq!(
+            state.selections,
+            HashSet::from([PathBuf::from("scratch.txt")])
+        );
+    }
+
+    #[test]
+    fn test_untracked_directories_collapse_and_expand() {
+        let mut files = make_test_files();
+        for path in ["build/a.o", "build/b.o", "build/sub/c.o"] {
+            files.push(DetectedFile {
+                path: PathBuf::from(path),
+                category: FileCategory::Untracked,
+                preselected: false,
+            });
+        }
+        let mut state = SelectionState::new(files, HashSet::new());
+        assert_eq!(state.visible_rows().len(), 6);
+
+        // Selecting the collapsed directory selects all of its files
+        state.cursor = 5;
+        state.toggle_current();
+        assert!(state.selections.contains(Path::new("build/sub/c.o")));
+        assert_eq!(state.selections.len(), 5);
+
+        state.expand_current();
+        assert_eq!(state.visible_rows().len(), 8);
+        state.cursor = 6;
+        state.toggle_current();
+        assert!(!state.selections.contains(Path::new("build/b.o")));
+
+        // Collapsing moves the cursor back to the directory; toggling a
+        // partly selected directory selects the rest
+        state.collapse_current();
+        assert_eq!(state.visible_rows().len(), 6);
+        assert_eq!(state.cursor, 5);
+        state.toggle_current();
+        assert!(state.selections.contains(Path::new("build/b.o")));
+
+        // Searching lists files individually
+        state.set_search("build");
+        assert_eq!(state.visible_rows().len(), 3);
+    }
+
+    #[test]
+    fn test_toggle_current_category_key() {
+        let mut state = SelectionState::new(make_test_files(), HashSet::new());
+
+        // Cursor on an AI config, which are all preselected: deselect the group
+        press(&mut state, 'c');
+        assert!(state.selections.is_empty());
+
+        // Move to the gitignored files and select the group
+        state.cursor = 2;
+        state.toggle_selection(Path::new(".envrc"));
+        press(&mut state, 'c');
+        assert_eq!(
+            state.selections,
+            HashSet::from([PathBuf::from(".envrc"), PathBuf::from(".env.local")])
+        );
+    }
+
     #[test]
     fn test_all_visible_selected_empty() {
         let files = make_test_files();
@@ -45,21 +45,23 @@ fn sources_cache_dir() -> Result<PathBuf> {
     Ok(base.cache_dir().join("sources"))
 }
 
+/// Manager for a single source, cloned to a subdirectory within the cache.
+pub fn source_repo_manager(source: &Source) -> Result<OverlayRepoManager> {
+    OverlayRepoManager::new(OverlayRepoConfig {
+        url: source.url.clone(),
+        local_path: Some(sources_cache_dir()?.join(&source.name)),
+    })
+}
+
 impl SourceManager {
     /// Create a new source manager from a list of sources.
     ///
     /// Each source is configured to clone to a subdirectory within the cache.
     pub fn new(sources: Vec<Source>) -> Result<Self> {
-        let cache_dir = sources_cache_dir()?;
         let managed_sources = sources
             .into_iter()
             .map(|source| {
-                let local_path = cache_dir.join(&source.name);
-                let config = OverlayRepoConfig {
-                    url: source.url.clone(),
-                    local_path: Some(local_path),
-                };
-                let manager = OverlayRepoManager::new(config)?;
+                let manager = source_repo_manager(&source)?;
                 Ok(ManagedSource { source, manager })
             })
             .collect::<Result<Vec<_>>>()?;
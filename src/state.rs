@@ -3,24 +3,39 @@
 //! Handles overlay state persistence, both in-repo (`.repoverlay/`) and external
 //! (`~/.local/share/repoverlay/`) for recovery after `git clean`.
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use chrono::{DateTime, Utc};
 use directories::ProjectDirs;
 use log::debug;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::collections::hash_map::DefaultHasher;
 use std::fs;
 use std::hash::{Hash, Hasher};
 use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::hash::sha256_hex;
 
 /// Constants for state directory structure
 pub const STATE_DIR: &str = ".repoverlay";
 pub const OVERLAYS_DIR: &str = "overlays";
+pub const BACKUPS_DIR: &str = "backups";
+/// Overlay files set aside while `update` re-applies the overlay.
+pub const STASH_DIR: &str = "stash";
 pub const META_FILE: &str = "meta.ccl";
+/// Extension of overlay state files, both in-repo and in the external backup.
+pub const STATE_EXT: &str = "ccl";
 pub const CONFIG_FILE: &str = "repoverlay.ccl";
 pub const GIT_EXCLUDE: &str = ".git/info/exclude";
 pub const MANAGED_SECTION_NAME: &str = "managed";
 
+/// Schema version of the state files this build reads and writes.
+///
+/// Bump this when a change to `OverlayState` or `FileEntry` can't be handled
+/// by `#[serde(default)]` alone, and add the upgrade step to [`migrate_state`].
+pub const STATE_SCHEMA_VERSION: u32 = 1;
+
 /// How an overlay was resolved from a reference.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
 #[serde(rename_all = "lowercase")]
@@ -31,6 +46,22 @@ pub enum ResolvedVia {
     Upstream,
 }
 
+/// What kind of ref a GitHub overlay was applied from.
+///
+/// Only branches move, so `update` leaves tag and commit pins alone unless
+/// forced.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RefKind {
+    /// A branch, or the repository's default branch
+    #[default]
+    Branch,
+    /// A tag
+    Tag,
+    /// A commit SHA
+    Commit,
+}
+
 /// Source of an overlay - can be local, from GitHub, or from a shared overlay repository.
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(tag = "type")]
@@ -50,6 +81,10 @@ pub enum OverlaySource {
         repo: String,
         /// Git ref (branch/tag name or commit SHA)
         git_ref: String,
+        /// Whether `git_ref` is a branch, tag, or commit (states written
+        /// before this was recorded read as a branch)
+        #[serde(default)]
+        ref_kind: RefKind,
         /// Resolved commit SHA at time of apply
         commit: String,
         /// Subdirectory within the repo (if any)
@@ -75,6 +110,13 @@ pub enum OverlaySource {
         #[serde(default, skip_serializing_if = "Option::is_none")]
         source_name: Option<String>,
     },
+    /// Archive overlay downloaded from a `.tar.gz`, `.tgz`, or `.zip` URL
+    Archive {
+        /// Archive URL
+        url: String,
+        /// SHA-256 of the downloaded archive at time of apply
+        sha256: String,
+    },
 }
 
 impl OverlaySource {
@@ -97,12 +139,22 @@ impl OverlaySource {
             owner,
             repo,
             git_ref,
+            ref_kind: RefKind::Branch,
             commit,
             subpath,
             cached_at: Utc::now(),
         }
     }
 
+    /// Record what kind of ref a GitHub source was applied from.
+    #[must_use]
+    pub const fn with_ref_kind(mut self, kind: RefKind) -> Self {
+        if let Self::GitHub { ref_kind, .. } = &mut self {
+            *ref_kind = kind;
+        }
+        self
+    }
+
     /// Create a new overlay repository source.
     #[allow(dead_code)]
     pub const fn overlay_repo(org: String, repo: String, name: String, commit: String) -> Self {
@@ -153,8 +205,12 @@ impl OverlaySource {
         }
     }
 
+    /// Create a new archive source.
+    pub const fn archive(url: String, sha256: String) -> Self {
+        Self::Archive { url, sha256 }
+    }
+
     /// Get a display string for the source.
-    #[allow(dead_code)]
     pub fn display(&self) -> String {
         match self {
             Self::Local { path } => path.display().to_string(),
@@ -191,6 +247,9 @@ impl OverlaySource {
                     &commit[..12.min(commit.len())]
                 )
             }
+            Self::Archive { url, sha256 } => {
+                format!("{} (sha256:{})", url, &sha256[..12.min(sha256.len())])
+            }
         }
     }
 
@@ -211,7 +270,34 @@ impl OverlaySource {
     pub fn local_path(&self) -> Option<&Path> {
         match self {
             Self::Local { path } => Some(path),
-            Self::GitHub { .. } | Self::OverlayRepo { .. } => None,
+            Self::GitHub { .. } | Self::OverlayRepo { .. } | Self::Archive { .. } => None,
+        }
+    }
+
+    /// Get the source string that re-resolves this source via `resolve_source`.
+    pub fn source_string(&self) -> String {
+        match self {
+            Self::Local { path } => path.to_string_lossy().to_string(),
+            Self::GitHub { url, .. } | Self::Archive { url, .. } => url.clone(),
+            Self::OverlayRepo {
+                org, repo, name, ..
+            } => format!("{org}/{repo}/{name}"),
+        }
+    }
+
+    /// Get the git ref to pass as an override when re-resolving (GitHub sources only).
+    pub const fn ref_override(&self) -> Option<&str> {
+        match self {
+            Self::GitHub { git_ref, .. } => Some(git_ref.as_str()),
+            Self::Local { .. } | Self::OverlayRepo { .. } | Self::Archive { .. } => None,
+        }
+    }
+
+    /// Get the configured source name to resolve against (overlay repo sources only).
+    pub fn source_filter(&self) -> Option<&str> {
+        match self {
+            Self::OverlayRepo { source_name, .. } => source_name.as_deref(),
+            Self::Local { .. } | Self::GitHub { .. } | Self::Archive { .. } => None,
         }
     }
 }
@@ -220,20 +306,94 @@ impl OverlaySource {
 #[derive(Debug, Deserialize, Serialize)]
 pub struct GlobalMeta {
     pub version: u32,
+    /// Schema version of the overlay state files. Missing in state written
+    /// before schemas were versioned, which is treated as version 1.
+    #[serde(default = "legacy_schema_version")]
+    pub schema_version: u32,
+    /// Version of repoverlay that first wrote the state directory.
+    /// Empty for state written before this was recorded.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub created_with_version: String,
 }
 
 impl Default for GlobalMeta {
     fn default() -> Self {
-        Self { version: 1 }
+        Self {
+            version: 1,
+            schema_version: STATE_SCHEMA_VERSION,
+            created_with_version: env!("CARGO_PKG_VERSION").to_string(),
+        }
     }
 }
 
+const fn legacy_schema_version() -> u32 {
+    1
+}
+
 /// State file tracking an applied overlay (`.repoverlay/overlays/<name>.ccl`).
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct OverlayState {
     pub name: String,
     pub applied_at: DateTime<Utc>,
     pub source: OverlaySource,
+    /// Description from the overlay's `repoverlay.ccl`, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Tags from the overlay's `repoverlay.ccl`.
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        deserialize_with = "string_or_list"
+    )]
+    pub tags: Vec<String>,
+    /// Template variables passed with `apply --set`, reused on restore and update.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub variables: BTreeMap<String, String>,
+    /// Names of the applied overlays this one requires.
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        deserialize_with = "string_or_list"
+    )]
+    pub requires: Vec<String>,
+    /// Patterns passed with `apply --only`, reused on restore and update.
+    /// Empty when the whole overlay was applied.
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        deserialize_with = "string_or_list"
+    )]
+    pub only: Vec<String>,
+    /// Directory the overlay was applied under with `apply --prefix`, relative
+    /// to the repository root; reused on restore and update.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<PathBuf>,
+    /// Files moved with `mv`, reused on restore and update.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub moved: Vec<MovedFile>,
+    /// Target paths left to the repo's own files by `apply --on-conflict skip`,
+    /// skipped again on restore and update.
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        deserialize_with = "path_or_list"
+    )]
+    pub skipped: Vec<PathBuf>,
+    /// Target paths dropped from the overlay with `unlink`, left out on
+    /// restore and update.
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        deserialize_with = "path_or_list"
+    )]
+    pub unlinked: Vec<PathBuf>,
+    /// Hook commands from the overlay's `repoverlay.ccl`.
+    #[serde(default, skip_serializing_if = "OverlayHooks::is_empty")]
+    pub hooks: OverlayHooks,
+    /// Version of repoverlay that applied the overlay.
+    /// Empty for state written before this was recorded.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub created_with_version: String,
     #[serde(default)]
     pub files: Vec<FileEntry>,
 }
@@ -245,6 +405,17 @@ impl OverlayState {
             name,
             applied_at: Utc::now(),
             source,
+            description: None,
+            tags: Vec::new(),
+            variables: BTreeMap::new(),
+            requires: Vec::new(),
+            only: Vec::new(),
+            prefix: None,
+            moved: Vec::new(),
+            skipped: Vec::new(),
+            unlinked: Vec::new(),
+            hooks: OverlayHooks::default(),
+            created_with_version: env!("CARGO_PKG_VERSION").to_string(),
             files: Vec::new(),
         }
     }
@@ -266,6 +437,14 @@ impl OverlayState {
     }
 }
 
+/// A file moved with `mv`: its path in the overlay source and where it now
+/// lives in the repository.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct MovedFile {
+    pub source: PathBuf,
+    pub target: PathBuf,
+}
+
 /// A file entry in the overlay state.
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct FileEntry {
@@ -276,6 +455,18 @@ pub struct FileEntry {
     /// Backwards compatible: missing field defaults to File.
     #[serde(default)]
     pub entry_type: EntryType,
+    /// Location of a repo file that was moved aside by `apply --force`,
+    /// relative to the target. Restored when the overlay is removed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backup: Option<PathBuf>,
+    /// SHA-256 of a copied file's content at apply time, used to detect
+    /// local edits. `None` for symlinks, directories, and older state files.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    /// Whether the file was rendered from a template rather than copied
+    /// verbatim, so its content is not expected to match the source.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub rendered: bool,
 }
 
 /// Type of file link.
@@ -286,13 +477,15 @@ pub enum LinkType {
     Copy,
 }
 
-/// Type of entry (file or directory).
+/// Type of entry (file, directory, or a symlink preserved from the source).
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum EntryType {
     #[default]
     File,
     Directory,
+    /// A symlink in the overlay source, recreated in the target with the same link path
+    Symlink,
 }
 
 /// Configuration file for an overlay source (repoverlay.ccl).
@@ -306,16 +499,160 @@ pub struct OverlayConfig {
     pub mappings: std::collections::HashMap<String, String>,
     /// Directories to symlink as a unit (not walk their contents).
     /// These directories will be symlinked directly instead of having
-    /// their individual files symlinked.
+    /// their individual files symlinked, unless their mode is `merge`.
+    #[serde(default)]
+    pub directories: Vec<DirectorySpec>,
+    /// Default values for `{{ name }}` placeholders in template files.
+    #[serde(default)]
+    pub variables: std::collections::HashMap<String, String>,
+    /// Overlay sources that must be applied along with this one.
+    #[serde(default, deserialize_with = "string_or_list")]
+    pub requires: Vec<String>,
+    /// Shell commands run around apply and remove when `--run-hooks` is given.
     #[serde(default)]
-    pub directories: Vec<String>,
+    pub hooks: OverlayHooks,
+}
+
+/// Shell commands an overlay runs in the target repository.
+///
+/// ```text
+/// hooks =
+///   post_apply = npm install
+///   pre_remove = ./scripts/teardown.sh
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct OverlayHooks {
+    /// Run after the overlay is applied and its state saved
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_apply: Option<String>,
+    /// Run before the overlay's files are removed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_remove: Option<String>,
+}
+
+impl OverlayHooks {
+    /// Whether no hooks are defined.
+    pub const fn is_empty(&self) -> bool {
+        self.post_apply.is_none() && self.pre_remove.is_none()
+    }
+}
+
+/// How a `directories` entry is applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DirectoryMode {
+    /// Symlink (or copy) the whole directory as one entry
+    #[default]
+    Unit,
+    /// Link each file individually so the repo can add files alongside them
+    Merge,
+}
+
+/// A `directories` entry, written either as a bare path or as a
+/// `path`/`mode` section:
+///
+/// ```text
+/// directories =
+///   = .claude
+///   =
+///     path = .vscode
+///     mode = merge
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(from = "DirectorySpecRepr")]
+pub struct DirectorySpec {
+    pub path: String,
+    pub mode: DirectoryMode,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DirectorySpecRepr {
+    Path(String),
+    Full {
+        path: String,
+        #[serde(default)]
+        mode: DirectoryMode,
+    },
+}
+
+impl From<DirectorySpecRepr> for DirectorySpec {
+    fn from(repr: DirectorySpecRepr) -> Self {
+        match repr {
+            DirectorySpecRepr::Path(path) => Self {
+                path,
+                mode: DirectoryMode::Unit,
+            },
+            DirectorySpecRepr::Full { path, mode } => Self { path, mode },
+        }
+    }
 }
 
 /// Metadata section of overlay config.
-#[derive(Debug, Deserialize, Serialize, Default)]
+#[derive(Debug, Deserialize, Serialize, Default, Clone)]
 pub struct OverlayConfigMeta {
     pub name: Option<String>,
     pub description: Option<String>,
+    /// Free-form labels used to filter `repoverlay list`.
+    #[serde(default, deserialize_with = "string_or_list")]
+    pub tags: Vec<String>,
+}
+
+/// Deserialize a list of paths that may also be written as a single value.
+fn path_or_list<'de, D>(deserializer: D) -> std::result::Result<Vec<PathBuf>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(string_or_list(deserializer)?
+        .into_iter()
+        .map(PathBuf::from)
+        .collect())
+}
+
+/// Deserialize a list of strings that may also be written as a single value.
+///
+/// CCL has no distinct syntax for a one-element list, so `tags = ai` and a
+/// nested `tags =` list of values are both accepted.
+pub fn string_or_list<'de, D>(deserializer: D) -> std::result::Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    struct StringOrList;
+
+    impl<'de> serde::de::Visitor<'de> for StringOrList {
+        type Value = Vec<String>;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str("a string or a list of strings")
+        }
+
+        fn visit_str<E: serde::de::Error>(
+            self,
+            value: &str,
+        ) -> std::result::Result<Self::Value, E> {
+            Ok(vec![value.to_string()])
+        }
+
+        fn visit_seq<A: serde::de::SeqAccess<'de>>(
+            self,
+            mut seq: A,
+        ) -> std::result::Result<Self::Value, A::Error> {
+            let mut values = Vec::new();
+            while let Some(value) = seq.next_element()? {
+                values.push(value);
+            }
+            Ok(values)
+        }
+    }
+
+    deserializer.deserialize_any(StringOrList)
+}
+
+impl OverlayConfigMeta {
+    /// Check whether the overlay is labeled with `tag` (case-insensitive).
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
 }
 
 /// Get the external state directory for storing backup state.
@@ -329,13 +666,212 @@ pub fn external_state_dir() -> Result<PathBuf> {
     Ok(proj_dirs.data_dir().join("applied"))
 }
 
+/// Suffix appended to an external state file when its overlay is removed.
+///
+/// The tombstone keeps `restore` from bringing back overlays that were removed
+/// on purpose, while still allowing `restore --include-removed`.
+const TOMBSTONE_SUFFIX: &str = ".removed";
+
+/// Marker recording that external state directories use [`target_key`].
+const KEY_VERSION_FILE: &str = ".key_version";
+
+/// Current version of the external state key scheme.
+const KEY_VERSION: &str = "3";
+
+/// Marker holding the normalized `origin` remote of a state directory's target.
+const REMOTE_MARKER: &str = ".remote";
+
+/// Marker holding the [`STATE_SCHEMA_VERSION`] of an external state directory.
+/// A missing marker means version 1.
+const SCHEMA_MARKER: &str = ".schema_version";
+
 /// Get the external state directory for a specific target repository.
 ///
-/// Uses a hash of the canonical target path to create a unique directory.
+/// The directory name is derived from [`target_key`], so the backup follows
+/// the repository when it is reached through a symlink. When a repository
+/// with a remote has been moved, the directory left at its old location is
+/// adopted (see [`state_dir_in`]).
 pub fn external_state_dir_for_target(target: &Path) -> Result<PathBuf> {
     let base = external_state_dir()?;
-    let target_hash = hash_path(target);
-    Ok(base.join(target_hash))
+    migrate_external_state_keys(&base);
+    Ok(state_dir_in(&base, target))
+}
+
+/// Get the state directory for `target` under `base`.
+///
+/// If `target` has no directory yet, a directory for the same remote and
+/// worktree name whose recorded path no longer exists is moved over, so the
+/// backup follows a moved repository without two clones sharing one key.
+fn state_dir_in(base: &Path, target: &Path) -> PathBuf {
+    let dir = base.join(target_key(target));
+    if !dir.exists()
+        && let Some(old_dir) = find_moved_state_dir(base, target)
+    {
+        debug!(
+            "adopting external state {} -> {}",
+            old_dir.display(),
+            dir.display()
+        );
+        if let Err(e) = merge_state_dir(&old_dir, &dir) {
+            debug!("failed to adopt {}: {e}", old_dir.display());
+        }
+    }
+    dir
+}
+
+/// Find the state directory of `target` from before it was moved.
+fn find_moved_state_dir(base: &Path, target: &Path) -> Option<PathBuf> {
+    let remote = repo_remote(target)?;
+    let toplevel = repo_toplevel(target)?;
+    let dir_name = toplevel.file_name()?;
+
+    fs::read_dir(base)
+        .ok()?
+        .filter_map(std::result::Result::ok)
+        .map(|entry| entry.path())
+        .find(|dir| {
+            let same_remote = fs::read_to_string(dir.join(REMOTE_MARKER))
+                .is_ok_and(|marker| marker.trim() == remote);
+            let moved_away = fs::read_to_string(dir.join(".target_path")).is_ok_and(|marker| {
+                let old = PathBuf::from(marker.trim());
+                old != toplevel && !old.exists() && old.file_name() == Some(dir_name)
+            });
+            same_remote && moved_away
+        })
+}
+
+/// Compute the external state key for a target repository.
+///
+/// The key is a hash of the canonical worktree path, so every clone gets its
+/// own key and symlinked paths share one. Repositories with an `origin`
+/// remote also hash the normalized remote URL.
+fn target_key(target: &Path) -> String {
+    let Some(toplevel) = repo_toplevel(target) else {
+        return hash_path(target);
+    };
+
+    repo_remote(target).map_or_else(
+        || hash_path(&toplevel),
+        |remote| {
+            let identity = format!("{remote}\n{}", toplevel.display());
+            sha256_hex(identity.as_bytes())[..16].to_string()
+        },
+    )
+}
+
+/// The canonical worktree root of the repository containing `target`.
+fn repo_toplevel(target: &Path) -> Option<PathBuf> {
+    git_output(target, &["rev-parse", "--show-toplevel"]).and_then(|top| fs::canonicalize(top).ok())
+}
+
+/// The normalized `origin` remote URL of the repository containing `target`.
+fn repo_remote(target: &Path) -> Option<String> {
+    git_output(target, &["remote", "get-url", "origin"]).map(|url| normalize_remote_url(&url))
+}
+
+/// Record which repository a state directory belongs to.
+///
+/// The target path is used for debugging and key migration; the remote lets
+/// a moved repository find its directory again.
+fn write_state_dir_markers(dir: &Path, target: &Path) -> Result<()> {
+    fs::write(dir.join(".target_path"), target.display().to_string())?;
+    if let Some(remote) = repo_remote(target) {
+        fs::write(dir.join(REMOTE_MARKER), remote)?;
+    }
+    Ok(())
+}
+
+/// Run a git command in `dir`, returning trimmed stdout on success.
+fn git_output(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8(output.stdout).ok()?;
+    let trimmed = stdout.trim();
+    (!trimmed.is_empty()).then(|| trimmed.to_string())
+}
+
+/// Normalize a git remote URL so HTTPS and SSH forms of the same repository
+/// compare equal (e.g. `git@github.com:Owner/Repo.git` -> `github.com/owner/repo`).
+fn normalize_remote_url(url: &str) -> String {
+    let url = url.trim();
+    let url = url
+        .split_once("://")
+        .map_or_else(|| url.replacen(':', "/", 1), |(_, rest)| rest.to_string());
+    let url = url.split_once('@').map_or(url.as_str(), |(_, rest)| rest);
+    let url = url.trim_end_matches('/');
+    let url = url.strip_suffix(".git").unwrap_or(url);
+    url.to_lowercase()
+}
+
+/// Re-key external state directories created by an older key scheme.
+///
+/// Each directory is moved to its current key using the `.target_path`
+/// marker, if that repository still exists. Runs once per [`KEY_VERSION`];
+/// failures are logged and left for the next run.
+fn migrate_external_state_keys(base: &Path) {
+    let version_file = base.join(KEY_VERSION_FILE);
+    let current =
+        fs::read_to_string(&version_file).is_ok_and(|version| version.trim() == KEY_VERSION);
+    if !base.exists() || current {
+        return;
+    }
+
+    let Ok(entries) = fs::read_dir(base) else {
+        return;
+    };
+    for entry in entries.filter_map(std::result::Result::ok) {
+        let old_dir = entry.path();
+        let Ok(marker) = fs::read_to_string(old_dir.join(".target_path")) else {
+            continue;
+        };
+        let target = PathBuf::from(marker.trim());
+        if !target.exists() {
+            continue;
+        }
+
+        let new_dir = base.join(target_key(&target));
+        if new_dir != old_dir {
+            debug!(
+                "migrating external state {} -> {}",
+                old_dir.display(),
+                new_dir.display()
+            );
+            if let Err(e) = merge_state_dir(&old_dir, &new_dir) {
+                debug!("failed to migrate {}: {e}", old_dir.display());
+                continue;
+            }
+        }
+        if let Err(e) = write_state_dir_markers(&new_dir, &target) {
+            debug!("failed to mark {}: {e}", new_dir.display());
+        }
+    }
+
+    if let Err(e) = fs::write(&version_file, KEY_VERSION) {
+        debug!("failed to write {}: {e}", version_file.display());
+    }
+}
+
+/// Move the contents of `from` into `to`, keeping files already in `to`.
+fn merge_state_dir(from: &Path, to: &Path) -> Result<()> {
+    if !to.exists() {
+        fs::rename(from, to)?;
+        return Ok(());
+    }
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let dest = to.join(entry.file_name());
+        if !dest.exists() {
+            fs::rename(entry.path(), dest)?;
+        }
+    }
+    fs::remove_dir_all(from)?;
+    Ok(())
 }
 
 /// Save overlay state to the external backup location.
@@ -343,47 +879,112 @@ pub fn save_external_state(target: &Path, overlay_name: &str, state: &OverlaySta
     debug!("save_external_state: {overlay_name}");
     let dir = external_state_dir_for_target(target)?;
     fs::create_dir_all(&dir)?;
+    upgrade_external_states(&dir)?;
 
-    // Also save a marker file with the original target path for debugging
-    let marker_path = dir.join(".target_path");
-    if !marker_path.exists() {
-        fs::write(&marker_path, target.display().to_string())?;
-    }
+    write_state_dir_markers(&dir, target)?;
 
-    let state_file = dir.join(format!("{overlay_name}.ccl"));
+    let state_file = dir.join(state_file_name(overlay_name));
     let content = sickle::to_string(state).context("Failed to serialize state to CCL")?;
     fs::write(&state_file, content)?;
 
+    // Re-applying an overlay supersedes an earlier removal
+    let tombstone = dir.join(format!(
+        "{}{TOMBSTONE_SUFFIX}",
+        state_file_name(overlay_name)
+    ));
+    if tombstone.exists() {
+        fs::remove_file(&tombstone)?;
+    }
+
     Ok(())
 }
 
-/// Remove overlay state from the external backup location.
+/// Mark overlay state in the external backup location as removed.
+///
+/// The state file is renamed to a tombstone rather than deleted, so an
+/// interrupted removal can't leave a backup that `restore` would re-apply.
 pub fn remove_external_state(target: &Path, overlay_name: &str) -> Result<()> {
     let dir = external_state_dir_for_target(target)?;
-    let state_file = dir.join(format!("{overlay_name}.ccl"));
+    let state_file = dir.join(state_file_name(overlay_name));
 
     if state_file.exists() {
-        fs::remove_file(&state_file)?;
+        fs::rename(
+            &state_file,
+            dir.join(format!(
+                "{}{TOMBSTONE_SUFFIX}",
+                state_file_name(overlay_name)
+            )),
+        )?;
     }
 
-    // Clean up the directory if empty (except for the marker file)
-    if dir.exists() {
-        let remaining: Vec<_> = fs::read_dir(&dir)?
-            .filter_map(std::result::Result::ok)
-            .filter(|e| e.file_name() != ".target_path")
-            .collect();
+    Ok(())
+}
 
-        if remaining.is_empty() {
-            fs::remove_dir_all(&dir)?;
-        }
-    }
+/// Permanently delete the tombstone for a removed overlay.
+///
+/// Returns `false` if there was no tombstone for `overlay_name`.
+pub fn forget_external_state(target: &Path, overlay_name: &str) -> Result<bool> {
+    let dir = external_state_dir_for_target(target)?;
+    let tombstone = dir.join(format!(
+        "{}{TOMBSTONE_SUFFIX}",
+        state_file_name(overlay_name)
+    ));
 
-    Ok(())
+    if !tombstone.exists() {
+        return Ok(false);
+    }
+    fs::remove_file(&tombstone)?;
+    Ok(true)
 }
 
 /// Load all overlay states from the external backup location for a target.
+///
+/// Overlays that were removed (see [`remove_external_state`]) are not included.
 pub fn load_external_states(target: &Path) -> Result<Vec<OverlayState>> {
     debug!("load_external_states: {}", target.display());
+    load_external_states_with_suffix(target, &format!(".{STATE_EXT}"))
+}
+
+/// Load the last known state of overlays that were removed from a target.
+pub fn load_removed_external_states(target: &Path) -> Result<Vec<OverlayState>> {
+    debug!("load_removed_external_states: {}", target.display());
+    load_external_states_with_suffix(target, &format!(".{STATE_EXT}{TOMBSTONE_SUFFIX}"))
+}
+
+/// Load the external states of every target repository.
+///
+/// Used to find which cached sources are still in use. Removed overlays and
+/// targets that no longer exist are not included.
+pub fn load_all_external_states() -> Result<Vec<OverlayState>> {
+    let base = external_state_dir()?;
+    migrate_external_state_keys(&base);
+    load_states_under(&base)
+}
+
+fn load_states_under(base: &Path) -> Result<Vec<OverlayState>> {
+    if !base.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut states = Vec::new();
+    for entry in fs::read_dir(base)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+        // Directories from before the marker existed are kept
+        let target_gone = fs::read_to_string(entry.path().join(".target_path"))
+            .is_ok_and(|marker| !Path::new(marker.trim()).exists());
+        if target_gone {
+            continue;
+        }
+        states.extend(read_states_in_dir(&entry.path(), &format!(".{STATE_EXT}"))?);
+    }
+
+    Ok(states)
+}
+
+fn load_external_states_with_suffix(target: &Path, suffix: &str) -> Result<Vec<OverlayState>> {
     let dir = external_state_dir_for_target(target)?;
 
     if !dir.exists() {
@@ -391,17 +992,22 @@ pub fn load_external_states(target: &Path) -> Result<Vec<OverlayState>> {
         return Ok(Vec::new());
     }
 
+    read_states_in_dir(&dir, suffix)
+}
+
+fn read_states_in_dir(dir: &Path, suffix: &str) -> Result<Vec<OverlayState>> {
+    let schema_version = external_schema_version(dir)?;
+    ensure_supported_schema(schema_version)
+        .with_context(|| format!("Failed to load external state in {}", dir.display()))?;
     let mut states = Vec::new();
 
-    for entry in fs::read_dir(&dir)? {
+    for entry in fs::read_dir(dir)? {
         let entry = entry?;
         let path = entry.path();
 
-        if path.extension().is_some_and(|e| e == "ccl")
-            && path.file_name() != Some(std::ffi::OsStr::new(".target_path"))
-        {
+        if entry.file_name().to_string_lossy().ends_with(suffix) {
             let content = fs::read_to_string(&path)?;
-            if let Ok(state) = sickle::from_str::<OverlayState>(&content) {
+            if let Ok(state) = migrate_state(&content, schema_version) {
                 states.push(state);
             }
         }
@@ -410,6 +1016,113 @@ pub fn load_external_states(target: &Path) -> Result<Vec<OverlayState>> {
     Ok(states)
 }
 
+/// Rewrite the states in an external state directory at the current schema.
+///
+/// Keeps every file in the directory on one schema version, so the directory's
+/// marker can be bumped before a new state is saved into it.
+fn upgrade_external_states(dir: &Path) -> Result<()> {
+    let schema_version = external_schema_version(dir)?;
+    ensure_supported_schema(schema_version)?;
+    if schema_version == STATE_SCHEMA_VERSION {
+        return Ok(());
+    }
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        if !name.ends_with(&format!(".{STATE_EXT}"))
+            && !name.ends_with(&format!(".{STATE_EXT}{TOMBSTONE_SUFFIX}"))
+        {
+            continue;
+        }
+        let content = fs::read_to_string(&path)?;
+        match migrate_state(&content, schema_version) {
+            Ok(state) => fs::write(
+                &path,
+                sickle::to_string(&state).context("Failed to serialize state to CCL")?,
+            )?,
+            Err(e) => debug!("leaving unreadable state {}: {e}", path.display()),
+        }
+    }
+    fs::write(dir.join(SCHEMA_MARKER), STATE_SCHEMA_VERSION.to_string())?;
+    Ok(())
+}
+
+/// Schema version of an external state directory, from its marker file.
+fn external_schema_version(dir: &Path) -> Result<u32> {
+    let marker = dir.join(SCHEMA_MARKER);
+    if !marker.exists() {
+        return Ok(legacy_schema_version());
+    }
+    let content = fs::read_to_string(&marker)
+        .with_context(|| format!("Failed to read {}", marker.display()))?;
+    content
+        .trim()
+        .parse()
+        .with_context(|| format!("Failed to parse {}", marker.display()))
+}
+
+/// Files chosen the last time `create` ran in a repository.
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct RememberedSelection {
+    #[serde(default, deserialize_with = "string_or_list")]
+    files: Vec<String>,
+}
+
+/// Get the directory storing remembered `create` selections.
+///
+/// Location: `~/.local/share/repoverlay/selections/` (Linux/macOS)
+/// or `%LOCALAPPDATA%\repoverlay\selections\` (Windows)
+fn selections_dir() -> Result<PathBuf> {
+    let proj_dirs = ProjectDirs::from("", "", "repoverlay")
+        .ok_or_else(|| anyhow::anyhow!("Could not determine data directory"))?;
+
+    Ok(proj_dirs.data_dir().join("selections"))
+}
+
+/// Load the files selected by the last `create` in `source` that still exist.
+///
+/// Selections are keyed like external state, by worktree path and remote, so
+/// a symlinked path shares one but a moved repository starts over. Missing or
+/// unreadable selections load as empty.
+pub fn load_remembered_selection(source: &Path) -> Vec<PathBuf> {
+    let Ok(dir) = selections_dir() else {
+        return Vec::new();
+    };
+    let path = dir.join(format!("{}.{STATE_EXT}", target_key(source)));
+    let Ok(content) = fs::read_to_string(&path) else {
+        return Vec::new();
+    };
+    let selection: RememberedSelection = sickle::from_str(&content).unwrap_or_else(|e| {
+        debug!("ignoring unreadable selection {}: {e}", path.display());
+        RememberedSelection::default()
+    });
+    selection
+        .files
+        .into_iter()
+        .map(PathBuf::from)
+        .filter(|file| source.join(file).exists())
+        .collect()
+}
+
+/// Remember the files selected by `create` in `source`.
+pub fn save_remembered_selection(source: &Path, files: &[PathBuf]) -> Result<()> {
+    let dir = selections_dir()?;
+    fs::create_dir_all(&dir)?;
+    let selection = RememberedSelection {
+        files: files
+            .iter()
+            .map(|file| file.to_string_lossy().replace('\\', "/"))
+            .collect(),
+    };
+    let content = sickle::to_string(&selection).context("Failed to serialize selection")?;
+    fs::write(
+        dir.join(format!("{}.{STATE_EXT}", target_key(source))),
+        content,
+    )?;
+    Ok(())
+}
+
 /// Hash a path to create a unique identifier.
 fn hash_path(path: &Path) -> String {
     let mut hasher = DefaultHasher::new();
@@ -427,6 +1140,11 @@ pub fn exclude_marker_end(name: &str) -> String {
     format!("# repoverlay:{name} end")
 }
 
+/// How [`normalize_overlay_name`] turns a name into a state file name, for
+/// error messages.
+pub const OVERLAY_NAME_RULES: &str = "Overlay names are lowercased, spaces become '-', \
+     and characters other than letters, digits, '-' and '_' are dropped.";
+
 /// Validate and normalize overlay name for use as filename.
 pub fn normalize_overlay_name(name: &str) -> Result<String> {
     let normalized: String = name
@@ -437,7 +1155,7 @@ pub fn normalize_overlay_name(name: &str) -> Result<String> {
         .collect();
 
     if normalized.is_empty() {
-        anyhow::bail!("Invalid overlay name: '{name}'");
+        anyhow::bail!("Invalid overlay name: '{name}'\n\n{OVERLAY_NAME_RULES}");
     }
     Ok(normalized)
 }
@@ -445,6 +1163,7 @@ pub fn normalize_overlay_name(name: &str) -> Result<String> {
 /// Load all target paths from all applied overlays, returning a map of path -> `overlay_name`.
 pub fn load_all_overlay_targets(
     target: &Path,
+    case_insensitive: bool,
 ) -> Result<std::collections::HashMap<String, String>> {
     let mut targets = std::collections::HashMap::new();
     let overlays_dir = target.join(STATE_DIR).join(OVERLAYS_DIR);
@@ -452,16 +1171,18 @@ pub fn load_all_overlay_targets(
     if !overlays_dir.exists() {
         return Ok(targets);
     }
+    let schema_version = state_schema_version(target)?;
+    ensure_supported_schema(schema_version)?;
 
     for entry in fs::read_dir(&overlays_dir)? {
         let entry = entry?;
         let path = entry.path();
-        if path.extension().is_some_and(|e| e == "ccl") {
+        if path.extension().is_some_and(|e| e == STATE_EXT) {
             let content = fs::read_to_string(&path)?;
-            if let Ok(state) = sickle::from_str::<OverlayState>(&content) {
+            if let Ok(state) = migrate_state(&content, schema_version) {
                 for file in &state.files {
                     targets.insert(
-                        file.target.to_string_lossy().to_string(),
+                        conflict_key(&file.target.to_string_lossy(), case_insensitive),
                         state.name.clone(),
                     );
                 }
@@ -472,6 +1193,16 @@ pub fn load_all_overlay_targets(
     Ok(targets)
 }
 
+/// Key for comparing overlay target paths. On a case-insensitive filesystem
+/// `README.md` and `readme.md` are the same file, so paths are lowercased.
+pub fn conflict_key(path: &str, case_insensitive: bool) -> String {
+    if case_insensitive {
+        path.to_lowercase()
+    } else {
+        path.to_string()
+    }
+}
+
 /// List all applied overlays, returning their normalized names.
 pub fn list_applied_overlays(target: &Path) -> Result<Vec<String>> {
     let overlays_dir = target.join(STATE_DIR).join(OVERLAYS_DIR);
@@ -482,7 +1213,7 @@ pub fn list_applied_overlays(target: &Path) -> Result<Vec<String>> {
 
     let mut names: Vec<String> = fs::read_dir(&overlays_dir)?
         .filter_map(std::result::Result::ok)
-        .filter(|e| e.path().extension().is_some_and(|ext| ext == "ccl"))
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == STATE_EXT))
         .filter_map(|e| {
             e.path()
                 .file_stem()
@@ -494,18 +1225,72 @@ pub fn list_applied_overlays(target: &Path) -> Result<Vec<String>> {
     Ok(names)
 }
 
+/// File name of an overlay's state file, e.g. `my-overlay.ccl`.
+pub fn state_file_name(name: &str) -> String {
+    format!("{name}.{STATE_EXT}")
+}
+
 /// Load an overlay state from the in-repo state file.
 pub fn load_overlay_state(target: &Path, name: &str) -> Result<OverlayState> {
     debug!("load_overlay_state: {name}");
     let state_file = target
         .join(STATE_DIR)
         .join(OVERLAYS_DIR)
-        .join(format!("{name}.ccl"));
+        .join(state_file_name(name));
 
     let content = fs::read_to_string(&state_file)
         .with_context(|| format!("Failed to read overlay state: {name}"))?;
 
-    sickle::from_str(&content).with_context(|| format!("Failed to parse overlay state: {name}"))
+    migrate_state(&content, state_schema_version(target)?)
+        .with_context(|| format!("Failed to parse overlay state: {name}"))
+}
+
+/// Schema version of the state directory, from `.repoverlay/meta.ccl`.
+///
+/// A missing meta file or `schema_version` field means version 1.
+pub fn state_schema_version(target: &Path) -> Result<u32> {
+    state_dir_schema_version(&target.join(STATE_DIR))
+}
+
+/// Schema version of a state directory laid out like `.repoverlay`, such as
+/// the copy in an export bundle.
+pub fn state_dir_schema_version(state_dir: &Path) -> Result<u32> {
+    let meta_path = state_dir.join(META_FILE);
+    if !meta_path.exists() {
+        return Ok(legacy_schema_version());
+    }
+
+    let content = fs::read_to_string(&meta_path)
+        .with_context(|| format!("Failed to read {}", meta_path.display()))?;
+    let meta: GlobalMeta = sickle::from_str(&content)
+        .with_context(|| format!("Failed to parse {}", meta_path.display()))?;
+    Ok(meta.schema_version)
+}
+
+/// Parse an overlay state file written with `schema_version`, upgrading it to
+/// the current schema.
+///
+/// State from a newer schema is rejected before parsing, since fields this
+/// build doesn't know about could otherwise fail with a confusing error or be
+/// silently dropped on the next save.
+pub fn migrate_state(content: &str, schema_version: u32) -> Result<OverlayState> {
+    ensure_supported_schema(schema_version)?;
+
+    // Upgrade steps for older schemas go here, each taking the state from
+    // version N to N + 1. Version 1 fields are all covered by serde defaults.
+    let state: OverlayState = sickle::from_str(content)?;
+    Ok(state)
+}
+
+/// Fail if state written with `schema_version` is newer than this build reads.
+fn ensure_supported_schema(schema_version: u32) -> Result<()> {
+    if schema_version > STATE_SCHEMA_VERSION {
+        bail!(
+            "Overlay state uses schema version {schema_version}, but this build of repoverlay \
+             only supports up to version {STATE_SCHEMA_VERSION}. Please upgrade repoverlay."
+        );
+    }
+    Ok(())
 }
 
 /// Save an overlay state to the in-repo state file.
@@ -514,7 +1299,7 @@ pub fn save_overlay_state(target: &Path, state: &OverlayState) -> Result<()> {
     fs::create_dir_all(&overlays_dir)?;
 
     let normalized_name = normalize_overlay_name(&state.name)?;
-    let state_file = overlays_dir.join(format!("{normalized_name}.ccl"));
+    let state_file = overlays_dir.join(state_file_name(&normalized_name));
 
     let content = sickle::to_string(state).context("Failed to serialize overlay state")?;
     fs::write(&state_file, content)?;
@@ -539,6 +1324,13 @@ mod tests {
         assert!(normalize_overlay_name("!!!").is_err());
     }
 
+    #[test]
+    fn test_normalize_overlay_name_error_explains_rules() {
+        let err = normalize_overlay_name("!!!").unwrap_err().to_string();
+        assert!(err.contains("Invalid overlay name: '!!!'"));
+        assert!(err.contains(OVERLAY_NAME_RULES));
+    }
+
     #[test]
     fn test_overlay_source_local() {
         let source = OverlaySource::local(PathBuf::from("/path/to/overlay"));
@@ -562,6 +1354,54 @@ mod tests {
         assert!(source.display().contains("github.com"));
     }
 
+    #[test]
+    fn test_overlay_source_source_string_and_ref_override() {
+        let local = OverlaySource::local(PathBuf::from("/path/to/overlay"));
+        assert_eq!(local.source_string(), "/path/to/overlay");
+        assert_eq!(local.ref_override(), None);
+
+        let github = OverlaySource::github(
+            "https://github.com/owner/repo".to_string(),
+            "owner".to_string(),
+            "repo".to_string(),
+            "develop".to_string(),
+            "abc123def456".to_string(),
+            None,
+        );
+        assert_eq!(github.source_string(), "https://github.com/owner/repo");
+        assert_eq!(github.ref_override(), Some("develop"));
+
+        let overlay_repo = OverlaySource::overlay_repo(
+            "org".to_string(),
+            "repo".to_string(),
+            "name".to_string(),
+            "abc123".to_string(),
+        );
+        assert_eq!(overlay_repo.source_string(), "org/repo/name");
+        assert_eq!(overlay_repo.ref_override(), None);
+    }
+
+    #[test]
+    fn test_overlay_source_serde_roundtrip_archive() {
+        let source = OverlaySource::archive(
+            "https://example.com/configs.tar.gz".to_string(),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855".to_string(),
+        );
+        let serialized = sickle::to_string(&source).unwrap();
+        let deserialized: OverlaySource = sickle::from_str(&serialized).unwrap();
+
+        match deserialized {
+            OverlaySource::Archive { url, sha256 } => {
+                assert_eq!(url, "https://example.com/configs.tar.gz");
+                assert!(sha256.starts_with("e3b0c442"));
+            }
+            _ => panic!("Expected Archive source"),
+        }
+        assert_eq!(source.source_string(), "https://example.com/configs.tar.gz");
+        assert_eq!(source.ref_override(), None);
+        assert!(source.display().contains("sha256:e3b0c44298fc"));
+    }
+
     #[test]
     fn test_overlay_source_serde_roundtrip_local() {
         let source = OverlaySource::local(PathBuf::from("/path/to/overlay"));
@@ -601,6 +1441,43 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_overlay_source_ref_kind_roundtrip() {
+        let source = OverlaySource::github(
+            "https://github.com/owner/repo/tree/v1.2.0".to_string(),
+            "owner".to_string(),
+            "repo".to_string(),
+            "v1.2.0".to_string(),
+            "abc123def456".to_string(),
+            None,
+        )
+        .with_ref_kind(RefKind::Tag);
+        let serialized = sickle::to_string(&source).unwrap();
+        let deserialized: OverlaySource = sickle::from_str(&serialized).unwrap();
+        assert!(matches!(
+            deserialized,
+            OverlaySource::GitHub {
+                ref_kind: RefKind::Tag,
+                ..
+            }
+        ));
+
+        // States written before ref_kind was recorded track a branch
+        let old = serialized
+            .lines()
+            .filter(|line| !line.contains("ref_kind"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let deserialized: OverlaySource = sickle::from_str(&old).unwrap();
+        assert!(matches!(
+            deserialized,
+            OverlaySource::GitHub {
+                ref_kind: RefKind::Branch,
+                ..
+            }
+        ));
+    }
+
     #[test]
     fn test_overlay_state_serde_roundtrip() {
         let mut state = OverlayState::new(
@@ -612,21 +1489,160 @@ mod tests {
             target: PathBuf::from(".envrc"),
             link_type: LinkType::Symlink,
             entry_type: EntryType::File,
+            backup: None,
+            sha256: None,
+            rendered: false,
         });
         state.add_file(FileEntry {
             source: PathBuf::from("config.json"),
             target: PathBuf::from(".config/app/config.json"),
             link_type: LinkType::Copy,
             entry_type: EntryType::File,
+            backup: None,
+            sha256: None,
+            rendered: false,
         });
 
         let serialized = sickle::to_string(&state).unwrap();
         let restored: OverlayState = sickle::from_str(&serialized).unwrap();
-
-        assert_eq!(restored.name, "test-overlay");
-        assert_eq!(restored.files.len(), 2);
-        assert_eq!(restored.files[0].link_type, LinkType::Symlink);
-        assert_eq!(restored.files[1].link_type, LinkType::Copy);
+
+        assert_eq!(restored.name, "test-overlay");
+        assert_eq!(restored.files.len(), 2);
+        assert_eq!(restored.files[0].link_type, LinkType::Symlink);
+        assert_eq!(restored.files[1].link_type, LinkType::Copy);
+        assert!(restored.description.is_none());
+        assert!(restored.tags.is_empty());
+        assert_eq!(restored.created_with_version, env!("CARGO_PKG_VERSION"));
+    }
+
+    #[test]
+    fn test_overlay_state_without_created_with_version() {
+        let content = r"
+name = old-overlay
+applied_at = 2024-01-01T00:00:00Z
+source =
+  type = Local
+  path = /overlay/source
+";
+        let state: OverlayState = sickle::from_str(content).unwrap();
+        assert!(state.created_with_version.is_empty());
+    }
+
+    #[test]
+    fn test_overlay_state_description_and_tags_roundtrip() {
+        let mut state = OverlayState::new(
+            "test-overlay".to_string(),
+            OverlaySource::local(PathBuf::from("/overlay/source")),
+        );
+        state.description = Some("Editor settings".to_string());
+        state.tags = vec!["editor".to_string(), "vscode".to_string()];
+
+        let serialized = sickle::to_string(&state).unwrap();
+        let restored: OverlayState = sickle::from_str(&serialized).unwrap();
+
+        assert_eq!(restored.description.as_deref(), Some("Editor settings"));
+        assert_eq!(restored.tags, vec!["editor", "vscode"]);
+
+        // A single tag serializes as a plain value and must still load
+        state.tags = vec!["editor".to_string()];
+        let serialized = sickle::to_string(&state).unwrap();
+        let restored: OverlayState = sickle::from_str(&serialized).unwrap();
+        assert_eq!(restored.tags, vec!["editor"]);
+    }
+
+    #[test]
+    fn test_overlay_state_hooks_roundtrip() {
+        let mut state = OverlayState::new(
+            "test-overlay".to_string(),
+            OverlaySource::local(PathBuf::from("/overlay/source")),
+        );
+        let serialized = sickle::to_string(&state).unwrap();
+        assert!(!serialized.contains("hooks"));
+
+        state.hooks.pre_remove = Some("echo bye".to_string());
+        let serialized = sickle::to_string(&state).unwrap();
+        let restored: OverlayState = sickle::from_str(&serialized).unwrap();
+        assert_eq!(restored.hooks, state.hooks);
+    }
+
+    #[test]
+    fn test_overlay_state_variables_and_rendered_roundtrip() {
+        let mut state = OverlayState::new(
+            "test-overlay".to_string(),
+            OverlaySource::local(PathBuf::from("/overlay/source")),
+        );
+        state
+            .variables
+            .insert("project".to_string(), "demo".to_string());
+        state.add_file(FileEntry {
+            source: PathBuf::from(".envrc.tmpl"),
+            target: PathBuf::from(".envrc"),
+            link_type: LinkType::Copy,
+            entry_type: EntryType::File,
+            backup: None,
+            sha256: None,
+            rendered: true,
+        });
+
+        let serialized = sickle::to_string(&state).unwrap();
+        let restored: OverlayState = sickle::from_str(&serialized).unwrap();
+
+        assert_eq!(
+            restored.variables.get("project").map(String::as_str),
+            Some("demo")
+        );
+        assert!(restored.files[0].rendered);
+    }
+
+    #[test]
+    fn test_overlay_state_only_roundtrip() {
+        let mut state = OverlayState::new(
+            "test-overlay".to_string(),
+            OverlaySource::local(PathBuf::from("/overlay/source")),
+        );
+        let serialized = sickle::to_string(&state).unwrap();
+        assert!(!serialized.contains("only"));
+
+        state.only = vec![".envrc".to_string()];
+        let serialized = sickle::to_string(&state).unwrap();
+        let restored: OverlayState = sickle::from_str(&serialized).unwrap();
+        assert_eq!(restored.only, vec![".envrc".to_string()]);
+    }
+
+    #[test]
+    fn test_overlay_state_skipped_roundtrip() {
+        let mut state = OverlayState::new(
+            "test-overlay".to_string(),
+            OverlaySource::local(PathBuf::from("/overlay/source")),
+        );
+        state.skipped = vec![PathBuf::from(".envrc")];
+        let serialized = sickle::to_string(&state).unwrap();
+        let restored: OverlayState = sickle::from_str(&serialized).unwrap();
+        assert_eq!(restored.skipped, state.skipped);
+
+        state.skipped.push(PathBuf::from("config/app.toml"));
+        let serialized = sickle::to_string(&state).unwrap();
+        let restored: OverlayState = sickle::from_str(&serialized).unwrap();
+        assert_eq!(restored.skipped, state.skipped);
+    }
+
+    #[test]
+    fn test_overlay_state_moved_roundtrip() {
+        let mut state = OverlayState::new(
+            "test-overlay".to_string(),
+            OverlaySource::local(PathBuf::from("/overlay/source")),
+        );
+        let serialized = sickle::to_string(&state).unwrap();
+        assert!(!serialized.contains("moved"));
+
+        let moved = vec![MovedFile {
+            source: PathBuf::from(".vscode/settings.json"),
+            target: PathBuf::from("editor/settings.json"),
+        }];
+        state.moved.clone_from(&moved);
+        let serialized = sickle::to_string(&state).unwrap();
+        let restored: OverlayState = sickle::from_str(&serialized).unwrap();
+        assert_eq!(restored.moved, moved);
     }
 
     #[test]
@@ -644,6 +1660,30 @@ mod tests {
         assert_ne!(hash1, hash2);
     }
 
+    #[test]
+    fn test_remembered_selection_roundtrip() {
+        let source = TempDir::new().unwrap();
+        fs::write(source.path().join(".envrc"), "export FOO=bar").unwrap();
+        fs::create_dir_all(source.path().join(".claude")).unwrap();
+        assert!(load_remembered_selection(source.path()).is_empty());
+
+        save_remembered_selection(
+            source.path(),
+            &[
+                PathBuf::from(".envrc"),
+                PathBuf::from(".claude"),
+                PathBuf::from("deleted.txt"),
+            ],
+        )
+        .unwrap();
+
+        // Files that no longer exist are dropped
+        assert_eq!(
+            load_remembered_selection(source.path()),
+            vec![PathBuf::from(".envrc"), PathBuf::from(".claude")]
+        );
+    }
+
     #[test]
     fn test_external_state_roundtrip() {
         let temp_target = TempDir::new().unwrap();
@@ -658,6 +1698,9 @@ mod tests {
             target: PathBuf::from(".envrc"),
             link_type: LinkType::Symlink,
             entry_type: EntryType::File,
+            backup: None,
+            sha256: None,
+            rendered: false,
         });
 
         // Save
@@ -779,6 +1822,9 @@ mod tests {
             target: PathBuf::from("a.txt"),
             link_type: LinkType::Symlink,
             entry_type: EntryType::File,
+            backup: None,
+            sha256: None,
+            rendered: false,
         });
 
         assert_eq!(state.file_count(), 1);
@@ -789,6 +1835,197 @@ mod tests {
     fn test_global_meta_default() {
         let meta = GlobalMeta::default();
         assert_eq!(meta.version, 1);
+        assert_eq!(meta.created_with_version, env!("CARGO_PKG_VERSION"));
+
+        // Meta files written before the version was recorded still load
+        let old: GlobalMeta = sickle::from_str("version = 1\n").unwrap();
+        assert!(old.created_with_version.is_empty());
+    }
+
+    /// State file as written before schemas were versioned: no entry types,
+    /// hashes, or version fields.
+    const V1_STATE_FIXTURE: &str = r"
+name = legacy
+applied_at = 2024-06-01T12:00:00Z
+source =
+  type = GitHub
+  url = https://github.com/owner/repo
+  owner = owner
+  repo = repo
+  git_ref = main
+  commit = abc123
+  cached_at = 2024-06-01T12:00:00Z
+files =
+  =
+    source = .envrc
+    target = .envrc
+    link_type = symlink
+  =
+    source = .claude
+    target = .claude
+    link_type = copy
+    entry_type = directory
+";
+
+    #[test]
+    fn test_migrate_state_loads_v1_fixture() {
+        let state = migrate_state(V1_STATE_FIXTURE, 1).unwrap();
+
+        assert_eq!(state.name, "legacy");
+        assert!(matches!(state.source, OverlaySource::GitHub { .. }));
+        assert!(state.created_with_version.is_empty());
+        assert!(state.requires.is_empty());
+        assert_eq!(state.files.len(), 2);
+        assert_eq!(state.files[0].entry_type, EntryType::File);
+        assert_eq!(state.files[0].sha256, None);
+        assert_eq!(state.files[1].entry_type, EntryType::Directory);
+        assert_eq!(state.files[1].link_type, LinkType::Copy);
+    }
+
+    #[test]
+    fn test_migrate_state_rejects_newer_schema() {
+        let err = migrate_state(V1_STATE_FIXTURE, STATE_SCHEMA_VERSION + 1)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Please upgrade repoverlay"), "got: {err}");
+    }
+
+    #[test]
+    fn test_state_schema_version_defaults_to_v1() {
+        let temp = TempDir::new().unwrap();
+        assert_eq!(state_schema_version(temp.path()).unwrap(), 1);
+
+        let state_dir = temp.path().join(STATE_DIR);
+        fs::create_dir_all(&state_dir).unwrap();
+        fs::write(state_dir.join(META_FILE), "version = 1\n").unwrap();
+        assert_eq!(state_schema_version(temp.path()).unwrap(), 1);
+
+        fs::write(
+            state_dir.join(META_FILE),
+            sickle::to_string(&GlobalMeta::default()).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            state_schema_version(temp.path()).unwrap(),
+            STATE_SCHEMA_VERSION
+        );
+    }
+
+    #[test]
+    fn test_load_overlay_state_from_newer_schema_fails() {
+        let temp = TempDir::new().unwrap();
+        let state_dir = temp.path().join(STATE_DIR);
+        fs::create_dir_all(state_dir.join(OVERLAYS_DIR)).unwrap();
+        fs::write(
+            state_dir.join(META_FILE),
+            format!(
+                "version = 1\nschema_version = {}\n",
+                STATE_SCHEMA_VERSION + 1
+            ),
+        )
+        .unwrap();
+        fs::write(
+            state_dir.join(OVERLAYS_DIR).join("legacy.ccl"),
+            V1_STATE_FIXTURE,
+        )
+        .unwrap();
+
+        let err = load_overlay_state(temp.path(), "legacy").unwrap_err();
+        assert!(format!("{err:#}").contains("Please upgrade repoverlay"));
+
+        fs::write(state_dir.join(META_FILE), "version = 1\n").unwrap();
+        assert_eq!(
+            load_overlay_state(temp.path(), "legacy").unwrap().name,
+            "legacy"
+        );
+    }
+
+    #[test]
+    fn test_load_all_overlay_targets_from_newer_schema_fails() {
+        let temp = TempDir::new().unwrap();
+        let state_dir = temp.path().join(STATE_DIR);
+        fs::create_dir_all(state_dir.join(OVERLAYS_DIR)).unwrap();
+        fs::write(
+            state_dir.join(OVERLAYS_DIR).join("legacy.ccl"),
+            V1_STATE_FIXTURE,
+        )
+        .unwrap();
+
+        let targets = load_all_overlay_targets(temp.path(), false).unwrap();
+        assert_eq!(targets.len(), 2);
+
+        fs::write(
+            state_dir.join(META_FILE),
+            format!(
+                "version = 1\nschema_version = {}\n",
+                STATE_SCHEMA_VERSION + 1
+            ),
+        )
+        .unwrap();
+        let err = load_all_overlay_targets(temp.path(), false).unwrap_err();
+        assert!(format!("{err:#}").contains("Please upgrade repoverlay"));
+    }
+
+    #[test]
+    fn test_read_external_states_checks_schema_marker() {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join("legacy.ccl"), V1_STATE_FIXTURE).unwrap();
+
+        // No marker: written before schemas were versioned
+        let states = read_states_in_dir(temp.path(), ".ccl").unwrap();
+        assert_eq!(states.len(), 1);
+        assert_eq!(states[0].name, "legacy");
+
+        fs::write(
+            temp.path().join(SCHEMA_MARKER),
+            (STATE_SCHEMA_VERSION + 1).to_string(),
+        )
+        .unwrap();
+        let err = read_states_in_dir(temp.path(), ".ccl").unwrap_err();
+        assert!(format!("{err:#}").contains("Please upgrade repoverlay"));
+        assert!(upgrade_external_states(temp.path()).is_err());
+    }
+
+    #[test]
+    fn test_load_states_under_skips_deleted_targets() {
+        let base = TempDir::new().unwrap();
+        let live = TempDir::new().unwrap();
+        for (dir, target) in [
+            ("live", live.path().to_path_buf()),
+            ("deleted", base.path().join("no-such-repo")),
+        ] {
+            let dir = base.path().join(dir);
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join(".target_path"), target.display().to_string()).unwrap();
+            fs::write(dir.join("legacy.ccl"), V1_STATE_FIXTURE).unwrap();
+        }
+
+        assert_eq!(load_states_under(base.path()).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_saved_state_file_name_matches_loader() {
+        let temp = TempDir::new().unwrap();
+        let state = OverlayState::new(
+            "my-overlay".to_string(),
+            OverlaySource::local(PathBuf::from("/overlay/source")),
+        );
+
+        save_overlay_state(temp.path(), &state).unwrap();
+
+        let written: Vec<String> = fs::read_dir(temp.path().join(STATE_DIR).join(OVERLAYS_DIR))
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(written, vec![state_file_name("my-overlay")]);
+        assert_eq!(
+            list_applied_overlays(temp.path()).unwrap(),
+            vec!["my-overlay"]
+        );
+        assert_eq!(
+            load_overlay_state(temp.path(), "my-overlay").unwrap().name,
+            "my-overlay"
+        );
     }
 
     #[test]
@@ -835,7 +2072,7 @@ mod tests {
     #[test]
     fn test_load_all_overlay_targets_empty() {
         let temp = TempDir::new().unwrap();
-        let targets = load_all_overlay_targets(temp.path()).unwrap();
+        let targets = load_all_overlay_targets(temp.path(), false).unwrap();
         assert!(targets.is_empty());
     }
 
@@ -850,25 +2087,42 @@ mod tests {
             name: "test-overlay".to_string(),
             applied_at: Utc::now(),
             source: OverlaySource::local(PathBuf::from("/path")),
+            description: None,
+            tags: Vec::new(),
+            variables: BTreeMap::new(),
+            requires: Vec::new(),
+            only: Vec::new(),
+            prefix: None,
+            moved: Vec::new(),
+            skipped: Vec::new(),
+            unlinked: Vec::new(),
+            hooks: OverlayHooks::default(),
+            created_with_version: String::new(),
             files: vec![
                 FileEntry {
                     source: PathBuf::from(".envrc"),
                     target: PathBuf::from(".envrc"),
                     link_type: LinkType::Symlink,
                     entry_type: EntryType::File,
+                    backup: None,
+                    sha256: None,
+                    rendered: false,
                 },
                 FileEntry {
                     source: PathBuf::from("config.json"),
                     target: PathBuf::from(".config/app.json"),
                     link_type: LinkType::Copy,
                     entry_type: EntryType::File,
+                    backup: None,
+                    sha256: None,
+                    rendered: false,
                 },
             ],
         };
         let content = sickle::to_string(&state).unwrap();
         fs::write(overlays_dir.join("test-overlay.ccl"), content).unwrap();
 
-        let targets = load_all_overlay_targets(temp.path()).unwrap();
+        let targets = load_all_overlay_targets(temp.path(), false).unwrap();
         assert_eq!(targets.len(), 2);
         assert_eq!(targets.get(".envrc"), Some(&"test-overlay".to_string()));
         assert_eq!(
@@ -877,6 +2131,42 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_load_all_overlay_targets_case_insensitive() {
+        let temp = TempDir::new().unwrap();
+        let overlays_dir = temp.path().join(STATE_DIR).join(OVERLAYS_DIR);
+        fs::create_dir_all(&overlays_dir).unwrap();
+
+        let mut state = OverlayState::new(
+            "docs".to_string(),
+            OverlaySource::local(PathBuf::from("/path")),
+        );
+        state.add_file(FileEntry {
+            source: PathBuf::from("readme.md"),
+            target: PathBuf::from("Docs/readme.md"),
+            link_type: LinkType::Symlink,
+            entry_type: EntryType::File,
+            backup: None,
+            sha256: None,
+            rendered: false,
+        });
+        fs::write(
+            overlays_dir.join("docs.ccl"),
+            sickle::to_string(&state).unwrap(),
+        )
+        .unwrap();
+
+        // Another overlay's README.md collides only when case is ignored
+        let key = "docs/README.md";
+        let sensitive = load_all_overlay_targets(temp.path(), false).unwrap();
+        assert!(!sensitive.contains_key(&conflict_key(key, false)));
+        let insensitive = load_all_overlay_targets(temp.path(), true).unwrap();
+        assert_eq!(
+            insensitive.get(&conflict_key(key, true)),
+            Some(&"docs".to_string())
+        );
+    }
+
     #[test]
     fn test_save_and_load_overlay_state() {
         let temp = TempDir::new().unwrap();
@@ -890,6 +2180,9 @@ mod tests {
             target: PathBuf::from(".envrc"),
             link_type: LinkType::Symlink,
             entry_type: EntryType::File,
+            backup: None,
+            sha256: None,
+            rendered: false,
         });
 
         // Save
@@ -960,6 +2253,54 @@ mod tests {
         assert_eq!(after[0].name, "overlay-b");
     }
 
+    #[test]
+    fn test_remove_external_state_leaves_tombstone() {
+        let temp_target = TempDir::new().unwrap();
+        let target_path = temp_target.path();
+        let state = OverlayState::new(
+            "test-overlay".to_string(),
+            OverlaySource::local(PathBuf::from("/overlay/source")),
+        );
+
+        save_external_state(target_path, "test-overlay", &state).unwrap();
+        remove_external_state(target_path, "test-overlay").unwrap();
+
+        assert!(load_external_states(target_path).unwrap().is_empty());
+        let removed = load_removed_external_states(target_path).unwrap();
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].name, "test-overlay");
+
+        // Saving again clears the tombstone
+        save_external_state(target_path, "test-overlay", &state).unwrap();
+        assert_eq!(load_external_states(target_path).unwrap().len(), 1);
+        assert!(
+            load_removed_external_states(target_path)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_forget_external_state() {
+        let temp_target = TempDir::new().unwrap();
+        let target_path = temp_target.path();
+        let state = OverlayState::new(
+            "test-overlay".to_string(),
+            OverlaySource::local(PathBuf::from("/overlay/source")),
+        );
+
+        assert!(!forget_external_state(target_path, "test-overlay").unwrap());
+
+        save_external_state(target_path, "test-overlay", &state).unwrap();
+        remove_external_state(target_path, "test-overlay").unwrap();
+        assert!(forget_external_state(target_path, "test-overlay").unwrap());
+        assert!(
+            load_removed_external_states(target_path)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
     #[test]
     fn test_remove_external_state_nonexistent() {
         let temp_target = TempDir::new().unwrap();
@@ -976,6 +2317,9 @@ mod tests {
             target: PathBuf::from("dst"),
             link_type: LinkType::Symlink,
             entry_type: EntryType::File,
+            backup: None,
+            sha256: None,
+            rendered: false,
         };
         let s = sickle::to_string(&entry).unwrap();
         assert!(s.contains("symlink"));
@@ -986,6 +2330,9 @@ mod tests {
             target: PathBuf::from("dst"),
             link_type: LinkType::Copy,
             entry_type: EntryType::File,
+            backup: None,
+            sha256: None,
+            rendered: false,
         };
         let s2 = sickle::to_string(&entry2).unwrap();
         assert!(s2.contains("copy"));
@@ -1027,6 +2374,9 @@ mod tests {
             target: PathBuf::from("dst"),
             link_type: LinkType::Symlink,
             entry_type: EntryType::File,
+            backup: None,
+            sha256: None,
+            rendered: false,
         };
         let s = sickle::to_string(&entry_file).unwrap();
         assert!(s.contains("file"));
@@ -1037,9 +2387,26 @@ mod tests {
             target: PathBuf::from("scratch"),
             link_type: LinkType::Symlink,
             entry_type: EntryType::Directory,
+            backup: None,
+            sha256: None,
+            rendered: false,
         };
         let s2 = sickle::to_string(&entry_dir).unwrap();
         assert!(s2.contains("directory"));
+
+        // Test preserved Symlink entry type
+        let entry_link = FileEntry {
+            source: PathBuf::from("link"),
+            target: PathBuf::from("link"),
+            link_type: LinkType::Copy,
+            entry_type: EntryType::Symlink,
+            backup: None,
+            sha256: None,
+            rendered: false,
+        };
+        let s3 = sickle::to_string(&entry_link).unwrap();
+        let restored: FileEntry = sickle::from_str(&s3).unwrap();
+        assert_eq!(restored.entry_type, EntryType::Symlink);
     }
 
     #[test]
@@ -1068,8 +2435,108 @@ directories =
         let config: OverlayConfig = sickle::from_str(config_str).unwrap();
         assert_eq!(config.overlay.name, Some("test-overlay".to_string()));
         assert_eq!(config.directories.len(), 2);
-        assert!(config.directories.contains(&"scratch".to_string()));
-        assert!(config.directories.contains(&".claude".to_string()));
+        assert!(config.directories.iter().any(|d| d.path == "scratch"));
+        assert!(config.directories.iter().any(|d| d.path == ".claude"));
+        assert!(
+            config
+                .directories
+                .iter()
+                .all(|d| d.mode == DirectoryMode::Unit)
+        );
+    }
+
+    #[test]
+    fn test_overlay_config_directory_modes() {
+        let config_str = r"
+directories =
+  = scratch
+  =
+    path = .vscode
+    mode = merge
+  =
+    path = .claude
+";
+        let config: OverlayConfig = sickle::from_str(config_str).unwrap();
+        assert_eq!(
+            config.directories,
+            vec![
+                DirectorySpec {
+                    path: "scratch".to_string(),
+                    mode: DirectoryMode::Unit,
+                },
+                DirectorySpec {
+                    path: ".vscode".to_string(),
+                    mode: DirectoryMode::Merge,
+                },
+                DirectorySpec {
+                    path: ".claude".to_string(),
+                    mode: DirectoryMode::Unit,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_overlay_config_requires() {
+        let config: OverlayConfig = sickle::from_str("requires = org/shared/base\n").unwrap();
+        assert_eq!(config.requires, vec!["org/shared/base"]);
+
+        let config_str = r"
+requires =
+  = org/shared/base
+  = ../lint-overlay
+";
+        let config: OverlayConfig = sickle::from_str(config_str).unwrap();
+        assert_eq!(config.requires, vec!["org/shared/base", "../lint-overlay"]);
+
+        let config: OverlayConfig = sickle::from_str("").unwrap();
+        assert!(config.requires.is_empty());
+    }
+
+    #[test]
+    fn test_overlay_config_hooks() {
+        let config_str = r"
+hooks =
+  post_apply = npm install
+  pre_remove = ./scripts/teardown.sh
+";
+        let config: OverlayConfig = sickle::from_str(config_str).unwrap();
+        assert_eq!(config.hooks.post_apply.as_deref(), Some("npm install"));
+        assert_eq!(
+            config.hooks.pre_remove.as_deref(),
+            Some("./scripts/teardown.sh")
+        );
+
+        let config: OverlayConfig = sickle::from_str("").unwrap();
+        assert!(config.hooks.is_empty());
+    }
+
+    #[test]
+    fn test_overlay_config_description_and_tags() {
+        let config_str = r"
+overlay =
+  name = test-overlay
+  description = Claude settings for the monorepo
+  tags =
+    = ai
+    = Claude
+";
+        let config: OverlayConfig = sickle::from_str(config_str).unwrap();
+        assert_eq!(
+            config.overlay.description.as_deref(),
+            Some("Claude settings for the monorepo")
+        );
+        assert_eq!(config.overlay.tags, vec!["ai", "Claude"]);
+        assert!(config.overlay.has_tag("claude"));
+        assert!(!config.overlay.has_tag("vscode"));
+
+        let config: OverlayConfig = sickle::from_str("overlay =\n  tags = solo\n").unwrap();
+        assert_eq!(config.overlay.tags, vec!["solo"]);
+
+        // Existing configs without the fields still parse
+        let config: OverlayConfig = sickle::from_str("overlay =\n  name = old\n").unwrap();
+        assert!(config.overlay.description.is_none());
+        assert!(config.overlay.tags.is_empty());
     }
 
     #[test]
@@ -1118,25 +2585,42 @@ mappings =
             name: "test-overlay".to_string(),
             source: OverlaySource::local(PathBuf::from("/source")),
             applied_at: chrono::Utc::now(),
+            description: None,
+            tags: Vec::new(),
+            variables: BTreeMap::new(),
+            requires: Vec::new(),
+            only: Vec::new(),
+            prefix: None,
+            moved: Vec::new(),
+            skipped: Vec::new(),
+            unlinked: Vec::new(),
+            hooks: OverlayHooks::default(),
+            created_with_version: String::new(),
             files: vec![
                 FileEntry {
                     source: PathBuf::from(".envrc"),
                     target: PathBuf::from(".envrc"),
                     link_type: LinkType::Symlink,
                     entry_type: EntryType::File,
+                    backup: None,
+                    sha256: None,
+                    rendered: false,
                 },
                 FileEntry {
                     source: PathBuf::from("scratch"),
                     target: PathBuf::from("scratch"),
                     link_type: LinkType::Symlink,
                     entry_type: EntryType::Directory,
+                    backup: None,
+                    sha256: None,
+                    rendered: false,
                 },
             ],
         };
         let content = sickle::to_string(&state).unwrap();
         fs::write(overlays_dir.join("test-overlay.ccl"), content).unwrap();
 
-        let targets = load_all_overlay_targets(temp.path()).unwrap();
+        let targets = load_all_overlay_targets(temp.path(), false).unwrap();
         assert_eq!(targets.len(), 2);
         assert_eq!(targets.get(".envrc"), Some(&"test-overlay".to_string()));
         assert_eq!(targets.get("scratch"), Some(&"test-overlay".to_string()));
@@ -1149,6 +2633,9 @@ mappings =
             target: PathBuf::from("scratch"),
             link_type: LinkType::Symlink,
             entry_type: EntryType::Directory,
+            backup: None,
+            sha256: None,
+            rendered: false,
         };
 
         let serialized = sickle::to_string(&entry).unwrap();
@@ -1172,6 +2659,31 @@ link_type = symlink
         assert_eq!(entry.entry_type, EntryType::File);
     }
 
+    #[test]
+    fn test_file_entry_sha256_roundtrip_and_default() {
+        let entry = FileEntry {
+            source: PathBuf::from(".envrc"),
+            target: PathBuf::from(".envrc"),
+            link_type: LinkType::Copy,
+            entry_type: EntryType::File,
+            backup: None,
+            sha256: Some("abc123".to_string()),
+            rendered: false,
+        };
+        let serialized = sickle::to_string(&entry).unwrap();
+        let deserialized: FileEntry = sickle::from_str(&serialized).unwrap();
+        assert_eq!(deserialized.sha256, Some("abc123".to_string()));
+
+        // Old state files without sha256 should load with no hash
+        let old_format = r"
+source = .envrc
+target = .envrc
+link_type = copy
+";
+        let entry: FileEntry = sickle::from_str(old_format).unwrap();
+        assert!(entry.sha256.is_none());
+    }
+
     // Additional configuration parsing edge case tests
     #[test]
     fn test_overlay_config_missing_optional_sections() {
@@ -1230,6 +2742,17 @@ directories =
             name: "test".to_string(),
             source,
             applied_at: chrono::Utc::now(),
+            description: None,
+            tags: Vec::new(),
+            variables: BTreeMap::new(),
+            requires: Vec::new(),
+            only: Vec::new(),
+            prefix: None,
+            moved: Vec::new(),
+            skipped: Vec::new(),
+            unlinked: Vec::new(),
+            hooks: OverlayHooks::default(),
+            created_with_version: String::new(),
             files: vec![],
         };
 
@@ -1266,6 +2789,17 @@ directories =
             name: "test".to_string(),
             source,
             applied_at: chrono::Utc::now(),
+            description: None,
+            tags: Vec::new(),
+            variables: BTreeMap::new(),
+            requires: Vec::new(),
+            only: Vec::new(),
+            prefix: None,
+            moved: Vec::new(),
+            skipped: Vec::new(),
+            unlinked: Vec::new(),
+            hooks: OverlayHooks::default(),
+            created_with_version: String::new(),
             files: vec![],
         };
 
@@ -1302,6 +2836,17 @@ directories =
             name: "valid".to_string(),
             source: OverlaySource::local(PathBuf::from("/source")),
             applied_at: chrono::Utc::now(),
+            description: None,
+            tags: Vec::new(),
+            variables: BTreeMap::new(),
+            requires: Vec::new(),
+            only: Vec::new(),
+            prefix: None,
+            moved: Vec::new(),
+            skipped: Vec::new(),
+            unlinked: Vec::new(),
+            hooks: OverlayHooks::default(),
+            created_with_version: String::new(),
             files: vec![],
         };
         fs::write(
@@ -1330,6 +2875,153 @@ directories =
         );
     }
 
+    #[test]
+    fn test_normalize_remote_url() {
+        assert_eq!(
+            normalize_remote_url("https://github.com/Owner/Repo.git"),
+            "github.com/owner/repo"
+        );
+        assert_eq!(
+            normalize_remote_url("git@github.com:Owner/Repo.git"),
+            "github.com/owner/repo"
+        );
+        assert_eq!(
+            normalize_remote_url("ssh://git@github.com/owner/repo/"),
+            "github.com/owner/repo"
+        );
+    }
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {args:?} failed");
+    }
+
+    #[test]
+    fn test_target_key_distinguishes_clones_of_one_remote() {
+        let temp = TempDir::new().unwrap();
+        let clone = |parent: &str| {
+            let repo = temp.path().join(parent).join("project");
+            fs::create_dir_all(&repo).unwrap();
+            git(&repo, &["init", "-q"]);
+            git(
+                &repo,
+                &[
+                    "remote",
+                    "add",
+                    "origin",
+                    "https://github.com/owner/project.git",
+                ],
+            );
+            repo
+        };
+        let first = clone("a");
+        let second = clone("b");
+
+        assert_ne!(target_key(&first), target_key(&second));
+        assert_eq!(
+            target_key(&first.join(".git").join("..")),
+            target_key(&first)
+        );
+    }
+
+    #[test]
+    fn test_state_dir_follows_moved_repo_with_remote() {
+        let temp = TempDir::new().unwrap();
+        let base = temp.path().join("applied");
+        let repo = temp.path().join("a").join("project");
+        fs::create_dir_all(&repo).unwrap();
+        git(&repo, &["init", "-q"]);
+        git(
+            &repo,
+            &[
+                "remote",
+                "add",
+                "origin",
+                "git@github.com:owner/project.git",
+            ],
+        );
+        let old_dir = state_dir_in(&base, &repo);
+        fs::create_dir_all(&old_dir).unwrap();
+        write_state_dir_markers(&old_dir, &fs::canonicalize(&repo).unwrap()).unwrap();
+        fs::write(old_dir.join("overlay.ccl"), "name = overlay").unwrap();
+
+        let moved = temp.path().join("b").join("project");
+        fs::create_dir_all(moved.parent().unwrap()).unwrap();
+        fs::rename(&repo, &moved).unwrap();
+
+        let new_dir = state_dir_in(&base, &moved);
+        assert_ne!(new_dir, old_dir);
+        assert!(!old_dir.exists());
+        assert!(new_dir.join("overlay.ccl").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_target_key_follows_symlinked_path_without_remote() {
+        let temp = TempDir::new().unwrap();
+        let repo = temp.path().join("project");
+        fs::create_dir_all(&repo).unwrap();
+        git(&repo, &["init", "-q"]);
+
+        let link = temp.path().join("link");
+        std::os::unix::fs::symlink(&repo, &link).unwrap();
+        assert_eq!(target_key(&link), target_key(&repo));
+    }
+
+    #[test]
+    fn test_merge_state_dir_keeps_existing_files() {
+        let temp = TempDir::new().unwrap();
+        let from = temp.path().join("old");
+        let to = temp.path().join("new");
+        fs::create_dir_all(&from).unwrap();
+        fs::create_dir_all(&to).unwrap();
+        fs::write(from.join("a.ccl"), "old a").unwrap();
+        fs::write(from.join("b.ccl"), "old b").unwrap();
+        fs::write(to.join("a.ccl"), "new a").unwrap();
+
+        merge_state_dir(&from, &to).unwrap();
+
+        assert!(!from.exists());
+        assert_eq!(fs::read_to_string(to.join("a.ccl")).unwrap(), "new a");
+        assert_eq!(fs::read_to_string(to.join("b.ccl")).unwrap(), "old b");
+    }
+
+    #[test]
+    fn test_migrate_external_state_keys_rekeys_once() {
+        let base = TempDir::new().unwrap();
+        let repo = TempDir::new().unwrap();
+        let legacy = base.path().join("legacy");
+        fs::create_dir_all(&legacy).unwrap();
+        fs::write(
+            legacy.join(".target_path"),
+            repo.path().display().to_string(),
+        )
+        .unwrap();
+        fs::write(legacy.join("overlay.ccl"), "name = overlay").unwrap();
+
+        migrate_external_state_keys(base.path());
+
+        let rekeyed = base.path().join(target_key(repo.path()));
+        assert!(!legacy.exists());
+        assert!(rekeyed.join("overlay.ccl").exists());
+        assert!(base.path().join(KEY_VERSION_FILE).exists());
+
+        // Already migrated: later directories are left alone
+        fs::create_dir_all(&legacy).unwrap();
+        fs::write(
+            legacy.join(".target_path"),
+            repo.path().display().to_string(),
+        )
+        .unwrap();
+        migrate_external_state_keys(base.path());
+        assert!(legacy.exists());
+    }
+
     #[test]
     fn test_external_state_dir_deterministic() {
         let temp = TempDir::new().unwrap();
@@ -0,0 +1,222 @@
+//! Template rendering for overlay files.
+//!
+//! Files named `*.tmpl` are rendered on apply and written without the suffix.
+//! In copy mode, other files are rendered too when they reference a known
+//! variable. Placeholders use `{{ name }}`, with values from the overlay's
+//! `variables` section and `apply --set` overrides.
+
+use anyhow::{Context, Result, bail};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Suffix marking a file as a template.
+pub const TEMPLATE_SUFFIX: &str = ".tmpl";
+
+/// Whether a source path is a `.tmpl` template.
+pub fn is_template(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| n.len() > TEMPLATE_SUFFIX.len() && n.ends_with(TEMPLATE_SUFFIX))
+}
+
+/// Target path for a template, with the `.tmpl` suffix removed.
+pub fn strip_template_suffix(path: &Path) -> PathBuf {
+    let name = path.to_string_lossy();
+    PathBuf::from(name.strip_suffix(TEMPLATE_SUFFIX).unwrap_or(&name))
+}
+
+/// Combine config variables with CLI overrides, which take precedence.
+pub fn merge_variables(
+    config: &HashMap<String, String>,
+    overrides: &BTreeMap<String, String>,
+) -> BTreeMap<String, String> {
+    let mut variables: BTreeMap<String, String> =
+        config.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+    variables.extend(overrides.iter().map(|(k, v)| (k.clone(), v.clone())));
+    variables
+}
+
+/// Parse a `key=value` argument for `apply --set`.
+pub fn parse_variable(arg: &str) -> Result<(String, String)> {
+    let Some((key, value)) = arg.split_once('=') else {
+        bail!("Invalid variable '{arg}': expected KEY=VALUE");
+    };
+    let key = key.trim();
+    if !is_variable_name(key) {
+        bail!("Invalid variable name '{key}': use letters, digits, '_', or '-'");
+    }
+    Ok((key.to_string(), value.to_string()))
+}
+
+/// Render a source file if it needs templating.
+///
+/// `.tmpl` files are always rendered and every placeholder must be defined.
+/// Other files are rendered only if they reference a defined variable;
+/// unknown placeholders (such as `${{ github.sha }}`) are left untouched.
+/// Returns `None` when the file should be copied as-is.
+pub fn render_file(path: &Path, variables: &BTreeMap<String, String>) -> Result<Option<String>> {
+    if is_template(path) {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read template: {}", path.display()))?;
+        return render(&content, variables, true)
+            .map(Some)
+            .with_context(|| format!("Failed to render template: {}", path.display()));
+    }
+
+    if variables.is_empty() {
+        return Ok(None);
+    }
+    let bytes = fs::read(path).with_context(|| format!("Failed to read: {}", path.display()))?;
+    let Ok(content) = String::from_utf8(bytes) else {
+        return Ok(None);
+    };
+    if !content.contains("{{") {
+        return Ok(None);
+    }
+    let rendered = render(&content, variables, false)?;
+    Ok((rendered != content).then_some(rendered))
+}
+
+/// Replace `{{ name }}` placeholders in `content`.
+///
+/// With `strict`, a placeholder naming an undefined variable is an error.
+/// Placeholders that aren't plain variable names are always left as-is.
+pub fn render(content: &str, variables: &BTreeMap<String, String>, strict: bool) -> Result<String> {
+    let mut output = String::with_capacity(content.len());
+    let mut rest = content;
+
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            output.push_str(&rest[start..]);
+            return Ok(output);
+        };
+
+        let name = after[..end].trim();
+        match variables.get(name) {
+            Some(value) => output.push_str(value),
+            None if strict && is_variable_name(name) => {
+                bail!("Undefined template variable '{name}'");
+            }
+            None => output.push_str(&rest[start..start + 2 + end + 2]),
+        }
+        rest = &after[end + 2..];
+    }
+
+    output.push_str(rest);
+    Ok(output)
+}
+
+fn is_variable_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn vars(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| ((*k).to_string(), (*v).to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn render_substitutes_variables() {
+        let result = render(
+            "name: {{ project }} by {{author}}",
+            &vars(&[("project", "demo"), ("author", "Ada")]),
+            true,
+        )
+        .unwrap();
+        assert_eq!(result, "name: demo by Ada");
+    }
+
+    #[test]
+    fn render_strict_fails_on_undefined_variable() {
+        let err = render("{{ missing }}", &vars(&[]), true).unwrap_err();
+        assert!(err.to_string().contains("missing"));
+    }
+
+    #[test]
+    fn render_lenient_keeps_unknown_placeholders() {
+        let content = "run: echo ${{ github.sha }} {{ other }} {{ project }}";
+        let result = render(content, &vars(&[("project", "demo")]), false).unwrap();
+        assert_eq!(result, "run: echo ${{ github.sha }} {{ other }} demo");
+    }
+
+    #[test]
+    fn render_strict_ignores_non_variable_placeholders() {
+        let result = render("${{ github.sha }}", &vars(&[]), true).unwrap();
+        assert_eq!(result, "${{ github.sha }}");
+    }
+
+    #[test]
+    fn render_leaves_unterminated_placeholder() {
+        let result = render("a {{ b", &vars(&[("b", "x")]), true).unwrap();
+        assert_eq!(result, "a {{ b");
+    }
+
+    #[test]
+    fn template_suffix_helpers() {
+        assert!(is_template(Path::new("dir/.envrc.tmpl")));
+        assert!(!is_template(Path::new(".tmpl")));
+        assert!(!is_template(Path::new("notes.txt")));
+        assert_eq!(
+            strip_template_suffix(Path::new("dir/.envrc.tmpl")),
+            PathBuf::from("dir/.envrc")
+        );
+    }
+
+    #[test]
+    fn merge_variables_prefers_overrides() {
+        let config: HashMap<String, String> = [
+            ("project".to_string(), "config".to_string()),
+            ("author".to_string(), "Ada".to_string()),
+        ]
+        .into_iter()
+        .collect();
+        let merged = merge_variables(&config, &vars(&[("project", "cli")]));
+        assert_eq!(merged, vars(&[("author", "Ada"), ("project", "cli")]));
+    }
+
+    #[test]
+    fn parse_variable_splits_on_first_equals() {
+        assert_eq!(
+            parse_variable("url=https://x?a=b").unwrap(),
+            ("url".to_string(), "https://x?a=b".to_string())
+        );
+        assert!(parse_variable("novalue").is_err());
+        assert!(parse_variable("bad key=1").is_err());
+    }
+
+    #[test]
+    fn render_file_only_touches_files_using_variables() {
+        let dir = TempDir::new().unwrap();
+        let plain = dir.path().join("plain.txt");
+        let used = dir.path().join("used.txt");
+        let template = dir.path().join("t.md.tmpl");
+        fs::write(&plain, "no placeholders ${{ env.X }}").unwrap();
+        fs::write(&used, "hello {{ project }}").unwrap();
+        fs::write(&template, "static").unwrap();
+
+        let variables = vars(&[("project", "demo")]);
+        assert_eq!(render_file(&plain, &variables).unwrap(), None);
+        assert_eq!(
+            render_file(&used, &variables).unwrap().as_deref(),
+            Some("hello demo")
+        );
+        // Templates are rendered even without variables
+        assert_eq!(
+            render_file(&template, &vars(&[])).unwrap().as_deref(),
+            Some("static")
+        );
+    }
+}
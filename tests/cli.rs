@@ -157,1166 +157,3899 @@ fn apply_and_remove_all_workflow() {
 }
 
 #[test]
-fn apply_with_copy_flag() {
+fn apply_multiple_sources() {
     let ctx = TestContext::new().with_overlay(&envrc_overlay());
+    let second = common::create_overlay_dir(&[(".editorconfig", "root = true")]);
 
     cargo_bin_cmd!("repoverlay")
-        .args(["apply", ctx.overlay_source()])
+        .args([
+            "apply",
+            ctx.overlay_source(),
+            second.path().to_str().unwrap(),
+        ])
         .args(["--target", ctx.repo_path().to_str().unwrap()])
-        .arg("--copy")
         .assert()
         .success();
 
     assert!(ctx.file_exists(".envrc"));
-    assert!(!ctx.is_symlink(".envrc"));
+    assert!(ctx.file_exists(".editorconfig"));
 }
 
 #[test]
-fn status_when_no_overlay() {
-    let ctx = TestContext::new();
+fn apply_multiple_sources_detects_conflicts_within_run() {
+    let ctx = TestContext::new().with_overlay(&envrc_overlay());
+    let second = common::create_overlay_dir(&envrc_overlay());
 
     cargo_bin_cmd!("repoverlay")
-        .args(["status", "--target", ctx.repo_path().to_str().unwrap()])
+        .args([
+            "apply",
+            ctx.overlay_source(),
+            second.path().to_str().unwrap(),
+        ])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
         .assert()
-        .success()
-        .stdout(predicate::str::contains("No overlay"));
+        .failure()
+        .stderr(predicate::str::contains("already managed by overlay"))
+        .stderr(predicate::str::contains("Applied before failure"));
+
+    // The first overlay stays applied
+    assert!(ctx.file_exists(".envrc"));
 }
 
 #[test]
-fn remove_when_no_overlay() {
-    let ctx = TestContext::new();
+fn apply_multiple_sources_rejects_name() {
+    let ctx = TestContext::new().with_overlay(&envrc_overlay());
+    let second = common::create_overlay_dir(&[(".editorconfig", "root = true")]);
 
     cargo_bin_cmd!("repoverlay")
         .args([
-            "remove",
-            "--all",
-            "--target",
-            ctx.repo_path().to_str().unwrap(),
+            "apply",
+            ctx.overlay_source(),
+            second.path().to_str().unwrap(),
         ])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .args(["--name", "combined"])
         .assert()
         .failure()
-        .stderr(predicate::str::contains("No overlay"));
+        .stderr(predicate::str::contains("single overlay"));
+
+    assert!(!ctx.file_exists(".envrc"));
 }
 
 #[test]
-fn cache_list_empty() {
+fn color_flag_controls_ansi_output() {
+    let overlay = common::create_overlay_dir(&envrc_overlay());
+
     cargo_bin_cmd!("repoverlay")
-        .args(["cache", "list"])
+        .args([
+            "info",
+            overlay.path().to_str().unwrap(),
+            "--color",
+            "always",
+        ])
         .assert()
-        .success();
+        .success()
+        .stdout(predicate::str::contains("\x1b["));
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["info", overlay.path().to_str().unwrap()])
+        .env("CLICOLOR_FORCE", "1")
+        .env("NO_COLOR", "1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\x1b[").not());
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["info", overlay.path().to_str().unwrap()])
+        .env_remove("NO_COLOR")
+        .env("CLICOLOR_FORCE", "1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\x1b["));
 }
 
 #[test]
-fn cache_path_shows_location() {
+fn info_lists_overlay_contents() {
+    let overlay = common::create_overlay_dir(&[
+        (
+            "repoverlay.ccl",
+            "overlay =\n  name = editor\n  description = Editor settings\n\ndirectories =\n  = .vscode\n\nmappings =\n  settings.json = .vscode/settings.json\n",
+        ),
+        ("settings.json", "{}"),
+        (".vscode/extensions.json", "{}"),
+    ]);
+
     cargo_bin_cmd!("repoverlay")
-        .args(["cache", "path"])
+        .args(["info", overlay.path().to_str().unwrap()])
         .assert()
         .success()
-        .stdout(predicate::str::contains("repoverlay"));
+        .stdout(predicate::str::contains("editor"))
+        .stdout(predicate::str::contains("Editor settings"))
+        .stdout(predicate::str::contains(".vscode/"))
+        .stdout(predicate::str::contains(
+            "settings.json -> .vscode/settings.json",
+        ))
+        .stdout(predicate::str::contains(".vscode/extensions.json"))
+        .stdout(predicate::str::contains("repoverlay.ccl").not());
 }
 
-// ============================================================================
-// Apply Command Tests
-// ============================================================================
+#[test]
+fn info_fails_for_missing_source() {
+    cargo_bin_cmd!("repoverlay")
+        .args(["info", "./does-not-exist"])
+        .assert()
+        .failure();
+}
 
 #[test]
-fn apply_creates_symlink_by_default() {
-    let ctx = TestContext::new().with_overlay(&envrc_overlay());
+fn completions_generates_script_for_each_shell() {
+    for shell in ["bash", "zsh", "fish", "powershell"] {
+        cargo_bin_cmd!("repoverlay")
+            .args(["completions", shell])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("apply"))
+            .stdout(predicate::str::contains("__overlay-names"));
+    }
 
     cargo_bin_cmd!("repoverlay")
-        .args(["apply", ctx.overlay_source()])
-        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .args(["completions", "tcsh"])
         .assert()
-        .success();
-
-    assert!(ctx.file_exists(".envrc"));
-    assert!(ctx.is_symlink(".envrc"), ".envrc should be a symlink");
+        .failure();
 }
 
 #[test]
-fn apply_creates_state_directory() {
+fn overlay_names_lists_applied_overlays() {
     let ctx = TestContext::new().with_overlay(&envrc_overlay());
 
     cargo_bin_cmd!("repoverlay")
         .args(["apply", ctx.overlay_source()])
         .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .args(["--name", "custom-name"])
         .assert()
         .success();
 
-    assert!(ctx.state_dir_exists(), ".repoverlay directory should exist");
+    cargo_bin_cmd!("repoverlay")
+        .args([
+            "__overlay-names",
+            "--target",
+            ctx.repo_path().to_str().unwrap(),
+        ])
+        .assert()
+        .success()
+        .stdout("custom-name\n");
 }
 
 #[test]
-fn apply_updates_git_exclude() {
-    let ctx = TestContext::new().with_overlay(&envrc_overlay());
+fn bootstrap_requires_github_remote() {
+    let ctx = TestContext::new();
 
     cargo_bin_cmd!("repoverlay")
-        .args(["apply", ctx.overlay_source()])
+        .args(["bootstrap", "--yes"])
         .args(["--target", ctx.repo_path().to_str().unwrap()])
         .assert()
-        .success();
+        .failure()
+        .stderr(predicate::str::contains(
+            "Could not detect target repository",
+        ));
+}
 
-    let exclude = ctx.git_exclude_content();
-    assert!(
-        exclude.contains("# repoverlay:"),
-        "git exclude should contain repoverlay section marker"
-    );
-    assert!(
-        exclude.contains(".envrc"),
-        "git exclude should list overlay files"
-    );
-    assert!(
-        exclude.contains(".repoverlay"),
-        "git exclude should list state directory"
-    );
+/// Create a committed overlay repository with two overlays and a bundle,
+/// plus a config directory pointing at it.
+fn overlay_repo_with_bundle() -> (tempfile::TempDir, tempfile::TempDir) {
+    let overlay_repo = common::create_overlay_dir(&[
+        ("acme/web/env/.envrc", "export FOO=bar"),
+        ("acme/web/editor/.editorconfig", "root = true"),
+        (
+            "bundles.ccl",
+            "frontend-standard =\n  = acme/web/env\n  = acme/web/editor\n",
+        ),
+    ]);
+    commit_all(overlay_repo.path());
+
+    let config_dir = tempfile::TempDir::new().unwrap();
+    fs::create_dir_all(config_dir.path().join("repoverlay")).unwrap();
+    fs::write(
+        config_dir.path().join("repoverlay/config.ccl"),
+        format!(
+            "overlay_repo =\n  url = https://example.com/overlays.git\n  local_path = {}\n",
+            overlay_repo.path().display()
+        ),
+    )
+    .unwrap();
+
+    (overlay_repo, config_dir)
+}
+
+/// Commit everything in `dir` to a new git repository.
+fn commit_all(dir: &std::path::Path) {
+    for args in [
+        vec!["init"],
+        vec!["add", "."],
+        vec![
+            "-c",
+            "user.name=Test",
+            "-c",
+            "user.email=test@example.com",
+            "commit",
+            "-m",
+            "overlays",
+        ],
+    ] {
+        std::process::Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+    }
 }
 
 #[test]
-fn apply_nested_files() {
-    let ctx = TestContext::new().with_overlay(&[
-        (".envrc", "export FOO=bar"),
-        (".vscode/settings.json", r#"{"editor.tabSize": 2}"#),
-    ]);
+fn apply_bundle_applies_each_member() {
+    let ctx = TestContext::new();
+    let (_overlay_repo, config_dir) = overlay_repo_with_bundle();
 
     cargo_bin_cmd!("repoverlay")
-        .args(["apply", ctx.overlay_source()])
+        .env("XDG_CONFIG_HOME", config_dir.path())
+        .args(["apply-bundle", "frontend-standard"])
         .args(["--target", ctx.repo_path().to_str().unwrap()])
         .assert()
         .success();
 
-    assert!(ctx.file_exists(".envrc"));
-    assert!(ctx.file_exists(".vscode/settings.json"));
+    assert!(ctx.is_symlink(".envrc"));
+    assert!(ctx.is_symlink(".editorconfig"));
+    assert!(ctx.overlay_state_exists("env"));
+    assert!(ctx.overlay_state_exists("editor"));
+
+    // Re-applying skips members that are already applied
+    cargo_bin_cmd!("repoverlay")
+        .env("XDG_CONFIG_HOME", config_dir.path())
+        .args(["apply-bundle", "frontend-standard"])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("'env' is already applied"));
 }
 
 #[test]
-fn apply_with_explicit_name() {
-    let ctx = TestContext::new().with_overlay(&envrc_overlay());
+fn apply_bundle_unknown_name_lists_available() {
+    let ctx = TestContext::new();
+    let (_overlay_repo, config_dir) = overlay_repo_with_bundle();
 
     cargo_bin_cmd!("repoverlay")
-        .args(["apply", ctx.overlay_source()])
+        .env("XDG_CONFIG_HOME", config_dir.path())
+        .args(["apply-bundle", "backend"])
         .args(["--target", ctx.repo_path().to_str().unwrap()])
-        .args(["--name", "custom-name"])
         .assert()
-        .success();
-
-    // Verify overlay exists with custom name
-    assert!(ctx.overlay_state_exists("custom-name"));
+        .failure()
+        .stderr(predicate::str::contains(
+            "Available bundles: frontend-standard",
+        ));
 }
 
 #[test]
-fn apply_with_copy_creates_regular_files() {
-    let ctx = TestContext::new().with_overlay(&envrc_overlay());
+fn sources_are_selected_by_prefix_and_from() {
+    let ctx = TestContext::new();
+    let (public_repo, config_dir) = overlay_repo_with_bundle();
+    let private_repo = common::create_overlay_dir(&[("acme/web/secrets/.env.local", "TOKEN=1")]);
+    commit_all(private_repo.path());
+    let cache_dir = tempfile::TempDir::new().unwrap();
+
+    fs::write(
+        config_dir.path().join("repoverlay/config.ccl"),
+        format!(
+            "sources =\n  =\n    name = public\n    url = {}\n  =\n    name = private\n    url = {}\n",
+            public_repo.path().display(),
+            private_repo.path().display()
+        ),
+    )
+    .unwrap();
+    let repoverlay = || {
+        let mut cmd = cargo_bin_cmd!("repoverlay");
+        cmd.env("XDG_CONFIG_HOME", config_dir.path())
+            .env("XDG_CACHE_HOME", cache_dir.path());
+        cmd
+    };
+
+    // Without --from, the highest-priority source is used
+    repoverlay()
+        .arg("list")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("editor"))
+        .stdout(predicate::str::contains("secrets").not());
 
-    cargo_bin_cmd!("repoverlay")
-        .args(["apply", ctx.overlay_source()])
+    repoverlay()
+        .args(["list", "--from", "private"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("secrets"))
+        .stdout(predicate::str::contains("editor").not());
+
+    repoverlay()
+        .args(["apply", "private:acme/web/secrets"])
         .args(["--target", ctx.repo_path().to_str().unwrap()])
-        .arg("--copy")
         .assert()
         .success();
+    assert!(ctx.file_exists(".env.local"));
 
-    assert!(ctx.file_exists(".envrc"));
-    assert!(
-        !ctx.is_symlink(".envrc"),
-        ".envrc should NOT be a symlink in copy mode"
-    );
-    assert_eq!(ctx.read_file(".envrc"), "export FOO=bar");
+    repoverlay()
+        .args(["apply", "private:acme/web/env", "--from", "public"])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("but --from names 'public'"));
+
+    repoverlay()
+        .args(["list", "--from", "missing"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown source: missing"));
 }
 
 #[test]
-fn apply_requires_valid_source() {
-    let ctx = TestContext::new();
+fn list_shows_bundles() {
+    let (_overlay_repo, config_dir) = overlay_repo_with_bundle();
 
     cargo_bin_cmd!("repoverlay")
-        .args(["apply", "/nonexistent/path"])
-        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .env("XDG_CONFIG_HOME", config_dir.path())
+        .arg("list")
         .assert()
-        .failure();
+        .success()
+        .stdout(predicate::str::contains("Bundles:"))
+        .stdout(predicate::str::contains("frontend-standard"));
 }
 
 #[test]
-fn apply_requires_git_repo() {
-    let ctx = TestContext::new().with_overlay(&envrc_overlay());
-    let temp_dir = tempfile::TempDir::new().unwrap();
+fn list_json_prints_filtered_overlays() {
+    let (_overlay_repo, config_dir) = overlay_repo_with_bundle();
+
+    let output = cargo_bin_cmd!("repoverlay")
+        .env("XDG_CONFIG_HOME", config_dir.path())
+        .args(["list", "--json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with('['));
+    assert!(stdout.contains(r#"{"org":"acme","repo":"web","name":"env","has_config":false}"#));
+    assert!(stdout.contains(r#""name":"editor""#));
+    assert!(!stdout.contains("frontend-standard"));
 
     cargo_bin_cmd!("repoverlay")
-        .args(["apply", ctx.overlay_source()])
-        .args(["--target", temp_dir.path().to_str().unwrap()])
+        .env("XDG_CONFIG_HOME", config_dir.path())
+        .args(["list", "--json", "--target", "acme/web", "--search", "env"])
         .assert()
-        .failure()
-        .stderr(predicate::str::contains("git"));
+        .success()
+        .stdout(
+            r#"[{"org":"acme","repo":"web","name":"env","has_config":false}]"#.to_string() + "\n",
+        );
+
+    cargo_bin_cmd!("repoverlay")
+        .env("XDG_CONFIG_HOME", config_dir.path())
+        .args(["list", "--json", "--target", "other/repo"])
+        .assert()
+        .success()
+        .stdout("[]\n");
 }
 
 #[test]
-fn apply_respects_path_mappings() {
-    let ctx = TestContext::new().with_overlay(&[
-        (".envrc", "export FOO=bar"),
-        (
-            "repoverlay.ccl",
-            r"mappings =
-  .envrc = .env
-",
-        ),
-    ]);
+fn list_applied_shows_one_line_per_overlay() {
+    let ctx = TestContext::new();
+    let target = ctx.repo_path().to_str().unwrap();
 
     cargo_bin_cmd!("repoverlay")
-        .args(["apply", ctx.overlay_source()])
-        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .args(["list", "--applied", target])
         .assert()
-        .success();
+        .success()
+        .stdout(predicate::str::contains(
+            "No overlays are currently applied",
+        ));
 
-    // File should be mapped to .env, not .envrc
-    assert!(
-        ctx.file_exists(".env"),
-        ".env should exist (mapped from .envrc)"
-    );
-    assert!(
-        !ctx.file_exists(".envrc"),
-        ".envrc should not exist (was mapped)"
-    );
-}
+    let env = common::create_overlay_dir(&[(".envrc", "export A=1")]);
+    let editor = common::create_overlay_dir(&[
+        (".editorconfig", "root = true"),
+        (".vscode/settings.json", "{}"),
+    ]);
+    for (source, name) in [(&env, "env"), (&editor, "editor")] {
+        cargo_bin_cmd!("repoverlay")
+            .args(["apply", source.path().to_str().unwrap()])
+            .args(["--target", target, "--name", name])
+            .assert()
+            .success();
+    }
 
-// ============================================================================
-// Remove Command Tests
-// ============================================================================
+    let output = cargo_bin_cmd!("repoverlay")
+        .args(["list", "--applied", target])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("editor  "));
+    assert!(lines[0].ends_with("2 file(s)"));
+    assert!(lines[1].starts_with("env     "));
+    assert!(lines[1].contains(&env.path().canonicalize().unwrap().display().to_string()));
+    assert!(lines[1].ends_with("1 file(s)"));
+}
+
+#[test]
+fn list_explains_unexpected_overlay_repo_layout() {
+    // Overlays committed at the top level instead of under <org>/<repo>/
+    let overlay_repo = common::create_overlay_dir(&[
+        ("env/.envrc", "export FOO=bar"),
+        ("env/repoverlay.ccl", "overlay =\n  name = env\n"),
+    ]);
+    commit_all(overlay_repo.path());
+
+    let config_dir = tempfile::TempDir::new().unwrap();
+    fs::create_dir_all(config_dir.path().join("repoverlay")).unwrap();
+    fs::write(
+        config_dir.path().join("repoverlay/config.ccl"),
+        format!(
+            "overlay_repo =\n  url = https://example.com/overlays.git\n  local_path = {}\n",
+            overlay_repo.path().display()
+        ),
+    )
+    .unwrap();
+
+    cargo_bin_cmd!("repoverlay")
+        .env("XDG_CONFIG_HOME", config_dir.path())
+        .arg("list")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No overlays found"))
+        .stdout(predicate::str::contains("<org>/<repo>/<overlay-name>/"))
+        .stdout(predicate::str::contains("Found top-level directories: env"))
+        .stderr(predicate::str::contains("env/repoverlay.ccl"));
+}
 
 #[test]
-fn remove_by_name() {
+fn force_apply_then_remove_restores_shadowed_file() {
     let ctx = TestContext::new().with_overlay(&envrc_overlay());
+    ctx.create_repo_file(".envrc", "committed content");
 
-    // Apply first
     cargo_bin_cmd!("repoverlay")
-        .args(["apply", ctx.overlay_source()])
+        .args(["apply", ctx.overlay_source(), "--force"])
         .args(["--target", ctx.repo_path().to_str().unwrap()])
-        .args(["--name", "my-overlay"])
+        .args(["--name", "env"])
         .assert()
         .success();
+    assert!(ctx.is_symlink(".envrc"));
 
-    assert!(ctx.file_exists(".envrc"));
-
-    // Remove by name
     cargo_bin_cmd!("repoverlay")
-        .args(["remove", "my-overlay"])
+        .args(["remove", "env"])
         .args(["--target", ctx.repo_path().to_str().unwrap()])
         .assert()
-        .success();
+        .success()
+        .stdout(predicate::str::contains("(original restored)"));
 
-    assert!(
-        !ctx.file_exists(".envrc"),
-        "overlay files should be removed"
+    assert!(!ctx.is_symlink(".envrc"));
+    assert_eq!(
+        fs::read_to_string(ctx.repo_path().join(".envrc")).unwrap(),
+        "committed content"
     );
+    assert!(!ctx.repo_path().join(".repoverlay").exists());
 }
 
 #[test]
-fn remove_all_removes_multiple_overlays() {
+fn force_apply_then_remove_restores_shadowed_directory() {
+    let overlay = common::create_overlay_dir(&[
+        ("scratch/notes.txt", "overlay notes"),
+        (
+            "repoverlay.ccl",
+            "overlay =\n  name = notes\n\ndirectories =\n  = scratch\n",
+        ),
+    ]);
     let ctx = TestContext::new();
-    let overlay1 = common::create_overlay_dir(&[(".envrc", "export FOO=1")]);
-    let overlay2 = common::create_overlay_dir(&[(".tool-versions", "nodejs 20.0.0")]);
+    ctx.create_repo_file("scratch/existing.txt", "committed");
 
-    // Apply first overlay
     cargo_bin_cmd!("repoverlay")
-        .args(["apply", overlay1.path().to_str().unwrap()])
+        .args(["apply", overlay.path().to_str().unwrap(), "--force"])
         .args(["--target", ctx.repo_path().to_str().unwrap()])
-        .args(["--name", "overlay1"])
         .assert()
         .success();
+    assert!(!ctx.file_exists("scratch/existing.txt"));
 
-    // Apply second overlay
     cargo_bin_cmd!("repoverlay")
-        .args(["apply", overlay2.path().to_str().unwrap()])
+        .args(["remove", "notes"])
         .args(["--target", ctx.repo_path().to_str().unwrap()])
-        .args(["--name", "overlay2"])
         .assert()
         .success();
 
-    assert!(ctx.file_exists(".envrc"));
-    assert!(ctx.file_exists(".tool-versions"));
+    assert_eq!(
+        fs::read_to_string(ctx.repo_path().join("scratch/existing.txt")).unwrap(),
+        "committed"
+    );
+    assert!(!ctx.file_exists("scratch/notes.txt"));
+}
 
-    // Remove all
-    cargo_bin_cmd!("repoverlay")
-        .args(["remove", "--all"])
+#[test]
+fn apply_with_copy_flag() {
+    let ctx = TestContext::new().with_overlay(&envrc_overlay());
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["apply", ctx.overlay_source()])
         .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .arg("--copy")
         .assert()
         .success();
 
-    assert!(!ctx.file_exists(".envrc"));
-    assert!(!ctx.file_exists(".tool-versions"));
+    assert!(ctx.file_exists(".envrc"));
+    assert!(!ctx.is_symlink(".envrc"));
 }
 
 #[test]
-fn remove_nonexistent_overlay_fails() {
-    let ctx = TestContext::new().with_overlay(&envrc_overlay());
+fn apply_only_links_matching_files() {
+    let ctx = TestContext::new().with_overlay(&[
+        (".envrc", "export FOO=bar"),
+        (".vscode/settings.json", "{}"),
+        (".vscode/launch.json", "{}"),
+    ]);
+    // Conflicts are only checked for included files
+    ctx.create_repo_file(".vscode/settings.json", "repo settings");
 
-    // Apply overlay
     cargo_bin_cmd!("repoverlay")
         .args(["apply", ctx.overlay_source()])
         .args(["--target", ctx.repo_path().to_str().unwrap()])
-        .args(["--name", "exists"])
+        .args(["--only", ".envrc", "--only", ".vscode/launch*"])
         .assert()
         .success();
 
-    // Try to remove nonexistent overlay
+    assert!(ctx.is_symlink(".envrc"));
+    assert!(ctx.is_symlink(".vscode/launch.json"));
+    assert_eq!(ctx.read_file(".vscode/settings.json"), "repo settings");
+
     cargo_bin_cmd!("repoverlay")
-        .args(["remove", "does-not-exist"])
-        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .args(["status", "--target", ctx.repo_path().to_str().unwrap()])
         .assert()
-        .failure()
-        .stderr(predicate::str::contains("not found").or(predicate::str::contains("No overlay")));
+        .success()
+        .stdout(predicate::str::contains("settings.json").not());
+
+    cargo_bin_cmd!("repoverlay")
+        .args([
+            "remove",
+            "--all",
+            "--target",
+            ctx.repo_path().to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+    assert!(!ctx.file_exists(".envrc"));
+    assert_eq!(ctx.read_file(".vscode/settings.json"), "repo settings");
 }
 
 #[test]
-fn remove_cleans_git_exclude() {
+fn apply_only_matching_nothing_fails() {
     let ctx = TestContext::new().with_overlay(&envrc_overlay());
 
-    // Apply
     cargo_bin_cmd!("repoverlay")
         .args(["apply", ctx.overlay_source()])
         .args(["--target", ctx.repo_path().to_str().unwrap()])
-        .args(["--name", "test"])
+        .args(["--only", "*.toml"])
         .assert()
-        .success();
+        .failure()
+        .stderr(predicate::str::contains("--only *.toml matched no files"));
 
-    let exclude_before = ctx.git_exclude_content();
-    assert!(exclude_before.contains("# repoverlay:"));
+    assert!(!ctx.state_dir_exists());
+}
+
+#[test]
+fn apply_prefix_places_files_in_subdirectory() {
+    let ctx = TestContext::new().with_overlay(&[
+        (".envrc", "export FOO=bar"),
+        (".vscode/settings.json", "{}"),
+    ]);
+    let target = ctx.repo_path().to_str().unwrap();
 
-    // Remove
     cargo_bin_cmd!("repoverlay")
-        .args(["remove", "test"])
-        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .args(["apply", ctx.overlay_source(), "--target", target])
+        .args(["--prefix", "packages/foo"])
         .assert()
         .success();
 
-    let exclude_after = ctx.git_exclude_content();
-    // The overlay-specific section should be gone
-    assert!(
-        !exclude_after.contains("# repoverlay:test"),
-        "git exclude should not contain the removed overlay section"
-    );
-}
+    assert!(ctx.is_symlink("packages/foo/.envrc"));
+    assert!(ctx.is_symlink("packages/foo/.vscode/settings.json"));
+    assert!(!ctx.file_exists(".envrc"));
+    assert!(ctx.git_exclude_content().contains("packages/foo/.envrc"));
 
-// ============================================================================
-// Status Command Tests
-// ============================================================================
+    cargo_bin_cmd!("repoverlay")
+        .args(["status", "--target", target])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Prefix:  packages/foo"));
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["remove", "--all", "--target", target])
+        .assert()
+        .success();
+    assert!(!ctx.file_exists("packages/foo/.envrc"));
+    assert!(!ctx.git_exclude_content().contains("packages/foo/.envrc"));
+}
 
 #[test]
-fn status_shows_applied_overlay() {
+fn apply_prefix_conflicts_with_repo_file_in_subdirectory() {
     let ctx = TestContext::new().with_overlay(&envrc_overlay());
+    ctx.create_repo_file("packages/foo/.envrc", "repo version");
 
-    // Apply
     cargo_bin_cmd!("repoverlay")
         .args(["apply", ctx.overlay_source()])
         .args(["--target", ctx.repo_path().to_str().unwrap()])
-        .args(["--name", "my-test-overlay"])
+        .args(["--prefix", "packages/foo"])
         .assert()
-        .success();
+        .failure()
+        .stderr(predicate::str::contains("packages/foo/.envrc"));
 
-    // Check status
-    cargo_bin_cmd!("repoverlay")
-        .args(["status"])
-        .args(["--target", ctx.repo_path().to_str().unwrap()])
-        .assert()
-        .success()
-        .stdout(predicate::str::contains("my-test-overlay"));
+    assert_eq!(ctx.read_file("packages/foo/.envrc"), "repo version");
 }
 
 #[test]
-fn status_shows_overlay_files() {
-    let ctx = TestContext::new().with_overlay(&[
-        (".envrc", "export FOO=bar"),
-        (".tool-versions", "nodejs 20.0.0"),
-    ]);
+fn apply_prefix_outside_repo_fails() {
+    let ctx = TestContext::new().with_overlay(&envrc_overlay());
 
-    // Apply
     cargo_bin_cmd!("repoverlay")
         .args(["apply", ctx.overlay_source()])
         .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .args(["--prefix", "../elsewhere"])
         .assert()
-        .success();
+        .failure()
+        .stderr(predicate::str::contains(
+            "--prefix must be a relative path inside the repository",
+        ));
+
+    assert!(!ctx.state_dir_exists());
+}
+
+#[test]
+fn status_when_no_overlay() {
+    let ctx = TestContext::new();
 
-    // Check status
     cargo_bin_cmd!("repoverlay")
-        .args(["status"])
-        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .args(["status", "--target", ctx.repo_path().to_str().unwrap()])
         .assert()
         .success()
-        .stdout(predicate::str::contains(".envrc"))
-        .stdout(predicate::str::contains(".tool-versions"));
+        .stdout(predicate::str::contains("No overlay"));
 }
 
 #[test]
-fn status_shows_multiple_overlays() {
+fn remove_when_no_overlay() {
     let ctx = TestContext::new();
-    let overlay1 = common::create_overlay_dir(&[(".envrc", "export FOO=1")]);
-    let overlay2 = common::create_overlay_dir(&[(".tool-versions", "nodejs 20.0.0")]);
 
-    // Apply both overlays
     cargo_bin_cmd!("repoverlay")
-        .args(["apply", overlay1.path().to_str().unwrap()])
-        .args(["--target", ctx.repo_path().to_str().unwrap()])
-        .args(["--name", "first-overlay"])
+        .args([
+            "remove",
+            "--all",
+            "--target",
+            ctx.repo_path().to_str().unwrap(),
+        ])
         .assert()
-        .success();
+        .failure()
+        .stderr(predicate::str::contains("No overlay"));
+}
 
+#[test]
+fn cache_list_empty() {
     cargo_bin_cmd!("repoverlay")
-        .args(["apply", overlay2.path().to_str().unwrap()])
-        .args(["--target", ctx.repo_path().to_str().unwrap()])
-        .args(["--name", "second-overlay"])
+        .args(["cache", "list"])
         .assert()
         .success();
+}
 
-    // Check status shows both
+#[test]
+fn cache_path_shows_location() {
     cargo_bin_cmd!("repoverlay")
-        .args(["status"])
-        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .args(["cache", "path"])
         .assert()
         .success()
-        .stdout(predicate::str::contains("first-overlay"))
-        .stdout(predicate::str::contains("second-overlay"));
+        .stdout(predicate::str::contains("repoverlay"));
 }
 
 // ============================================================================
-// Restore Command Tests
+// Apply Command Tests
 // ============================================================================
 
 #[test]
-fn restore_recreates_deleted_symlinks() {
+fn apply_creates_symlink_by_default() {
     let ctx = TestContext::new().with_overlay(&envrc_overlay());
 
-    // Apply
     cargo_bin_cmd!("repoverlay")
         .args(["apply", ctx.overlay_source()])
         .args(["--target", ctx.repo_path().to_str().unwrap()])
-        .args(["--name", "test-overlay"])
         .assert()
         .success();
 
     assert!(ctx.file_exists(".envrc"));
+    assert!(ctx.is_symlink(".envrc"), ".envrc should be a symlink");
+}
 
-    // Delete the symlink manually (simulating user deletion)
-    fs::remove_file(ctx.repo_path().join(".envrc")).unwrap();
-    assert!(!ctx.file_exists(".envrc"));
+#[test]
+#[cfg(unix)]
+fn apply_creates_relative_symlink_when_configured() {
+    let ctx = TestContext::new().with_overlay(&envrc_overlay());
+    let config_dir = tempfile::TempDir::new().unwrap();
+    fs::create_dir_all(config_dir.path().join("repoverlay")).unwrap();
+    fs::write(
+        config_dir.path().join("repoverlay/config.ccl"),
+        "symlink_style = relative\n",
+    )
+    .unwrap();
 
-    // Restore - note: restore currently reports "No overlays to restore" if files are just
-    // deleted but state exists. This is expected behavior when overlay state is intact.
-    let output = cargo_bin_cmd!("repoverlay")
-        .args(["restore"])
+    cargo_bin_cmd!("repoverlay")
+        .env("XDG_CONFIG_HOME", config_dir.path())
+        .args(["apply", ctx.overlay_source()])
         .args(["--target", ctx.repo_path().to_str().unwrap()])
         .assert()
         .success();
 
-    // The restore command should indicate what happened
-    output.stdout(
-        predicate::str::contains("restore")
-            .or(predicate::str::contains("Restore"))
-            .or(predicate::str::contains("No overlays")),
+    let link = fs::read_link(ctx.repo_path().join(".envrc")).unwrap();
+    assert!(
+        link.is_relative(),
+        "expected relative link, got {}",
+        link.display()
     );
+    assert_eq!(ctx.read_file(".envrc"), "export FOO=bar");
 }
 
 #[test]
-fn restore_all_overlays() {
-    let ctx = TestContext::new();
-    let overlay1 = common::create_overlay_dir(&[(".envrc", "export FOO=1")]);
-    let overlay2 = common::create_overlay_dir(&[(".tool-versions", "nodejs 20.0.0")]);
+fn apply_large_overlay_summarizes_instead_of_listing_files() {
+    let files: Vec<(String, &str)> = (0..60)
+        .map(|i| (format!("configs/file{i:02}.txt"), "content"))
+        .collect();
+    let file_refs: Vec<(&str, &str)> = files.iter().map(|(p, c)| (p.as_str(), *c)).collect();
+    let ctx = TestContext::new().with_overlay(&file_refs);
 
-    // Apply both overlays
     cargo_bin_cmd!("repoverlay")
-        .args(["apply", overlay1.path().to_str().unwrap()])
+        .args(["apply", ctx.overlay_source()])
         .args(["--target", ctx.repo_path().to_str().unwrap()])
-        .args(["--name", "overlay-a"])
         .assert()
-        .success();
+        .success()
+        .stdout(predicate::str::contains("configs/file00.txt").not())
+        .stdout(predicate::str::contains("Applied 60 file(s)"));
 
-    cargo_bin_cmd!("repoverlay")
-        .args(["apply", overlay2.path().to_str().unwrap()])
-        .args(["--target", ctx.repo_path().to_str().unwrap()])
-        .args(["--name", "overlay-b"])
-        .assert()
-        .success();
+    assert!(ctx.file_exists("configs/file59.txt"));
+}
 
-    // Delete both files
-    fs::remove_file(ctx.repo_path().join(".envrc")).unwrap();
-    fs::remove_file(ctx.repo_path().join(".tool-versions")).unwrap();
+#[test]
+fn verbose_apply_writes_debug_log_file() {
+    let ctx = TestContext::new().with_overlay(&envrc_overlay());
+    let log_dir = tempfile::TempDir::new().unwrap();
+    let log_file = log_dir.path().join("logs/apply.log");
 
-    // Restore all
     cargo_bin_cmd!("repoverlay")
-        .args(["restore"])
+        .env_remove("RUST_LOG")
+        .args(["-v", "apply", ctx.overlay_source()])
         .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .args(["--log-file", log_file.to_str().unwrap()])
         .assert()
-        .success();
+        .success()
+        .stderr(predicate::str::contains("resolve_source"));
+
+    let log = fs::read_to_string(&log_file).unwrap();
+    assert!(log.contains("DEBUG"), "log: {log}");
+    assert!(log.contains("resolve_source"), "log: {log}");
+    assert!(!log.contains("TRACE"), "log: {log}");
 }
 
 #[test]
-fn restore_when_no_overlays_shows_message() {
-    let ctx = TestContext::new();
+fn quiet_apply_and_remove_print_nothing_but_errors() {
+    let ctx = TestContext::new().with_overlay(&envrc_overlay());
+    let target = ctx.repo_path().to_str().unwrap();
 
-    // Restore with no overlays should succeed with informational message
     cargo_bin_cmd!("repoverlay")
-        .args(["restore"])
-        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .args(["--quiet", "apply", ctx.overlay_source(), "--target", target])
+        .args(["--name", "quiet"])
         .assert()
         .success()
-        .stdout(predicate::str::contains("No overlay"));
-}
+        .stdout(predicate::str::is_empty());
+    assert!(ctx.file_exists(".envrc"));
 
-// ============================================================================
-// Update Command Tests
-// ============================================================================
+    // Applying again conflicts: the error still reaches stderr
+    cargo_bin_cmd!("repoverlay")
+        .args(["apply", ctx.overlay_source(), "--target", target, "-q"])
+        .args(["--name", "again"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains("Error:"));
 
-#[test]
-fn update_help_shows_options() {
     cargo_bin_cmd!("repoverlay")
-        .args(["update", "--help"])
+        .args([
+            "mv",
+            "quiet",
+            ".envrc",
+            "env/.envrc",
+            "--target",
+            target,
+            "-q",
+        ])
         .assert()
         .success()
-        .stdout(predicate::str::contains("ref").or(predicate::str::contains("Update")));
-}
+        .stdout(predicate::str::is_empty());
+    assert!(ctx.file_exists("env/.envrc"));
 
-// ============================================================================
-// Switch Command Tests
-// ============================================================================
+    // Simulate git clean wiping the overlay and its in-repo state
+    fs::remove_file(ctx.repo_path().join("env/.envrc")).unwrap();
+    fs::remove_dir_all(ctx.repo_path().join(".repoverlay")).unwrap();
+    cargo_bin_cmd!("repoverlay")
+        .args(["restore", "--only", "quiet", "--target", target, "-q"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+    assert!(ctx.file_exists("env/.envrc"));
+
+    for args in [vec!["update"], vec!["sync", "--all"]] {
+        cargo_bin_cmd!("repoverlay")
+            .args(&args)
+            .args(["--target", target, "-q"])
+            .assert()
+            .success()
+            .stdout(predicate::str::is_empty());
+    }
 
-#[test]
-fn switch_help_shows_options() {
     cargo_bin_cmd!("repoverlay")
-        .args(["switch", "--help"])
+        .args(["remove", "--all", "--target", target, "-q"])
         .assert()
         .success()
-        .stdout(predicate::str::contains("Switch"));
+        .stdout(predicate::str::is_empty());
+    assert!(!ctx.file_exists("env/.envrc"));
 }
 
-// ============================================================================
-// Error Handling Tests
-// ============================================================================
-
 #[test]
-fn invalid_command_shows_error() {
+fn apply_large_overlay_verbose_lists_files() {
+    let files: Vec<(String, &str)> = (0..60)
+        .map(|i| (format!("configs/file{i:02}.txt"), "content"))
+        .collect();
+    let file_refs: Vec<(&str, &str)> = files.iter().map(|(p, c)| (p.as_str(), *c)).collect();
+    let ctx = TestContext::new().with_overlay(&file_refs);
+
     cargo_bin_cmd!("repoverlay")
-        .arg("nonexistent-command")
+        .args(["apply", ctx.overlay_source(), "--verbose"])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
         .assert()
-        .failure()
-        .stderr(predicate::str::contains("error"));
+        .success()
+        .stdout(predicate::str::contains("configs/file00.txt"))
+        .stdout(predicate::str::contains("configs/file59.txt"));
 }
 
 #[test]
-fn apply_conflicting_file_warns_or_fails() {
+fn apply_creates_state_directory() {
     let ctx = TestContext::new().with_overlay(&envrc_overlay());
 
-    // Create a pre-existing .envrc
-    ctx.create_repo_file(".envrc", "existing content");
-
     cargo_bin_cmd!("repoverlay")
         .args(["apply", ctx.overlay_source()])
         .args(["--target", ctx.repo_path().to_str().unwrap()])
         .assert()
-        .failure()
-        .stderr(predicate::str::contains("exists").or(predicate::str::contains("conflict")));
+        .success();
+
+    assert!(ctx.state_dir_exists(), ".repoverlay directory should exist");
 }
 
-// ============================================================================
-// Cache Command Tests
-// ============================================================================
-
 #[test]
-fn cache_clear_help() {
-    cargo_bin_cmd!("repoverlay")
-        .args(["cache", "clear", "--help"])
-        .assert()
-        .success()
-        .stdout(predicate::str::contains("Clear").or(predicate::str::contains("cache")));
-}
+fn apply_updates_git_exclude() {
+    let ctx = TestContext::new().with_overlay(&envrc_overlay());
 
-#[test]
-fn cache_remove_help() {
     cargo_bin_cmd!("repoverlay")
-        .args(["cache", "remove", "--help"])
+        .args(["apply", ctx.overlay_source()])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
         .assert()
         .success();
-}
-
-// ============================================================================
-// Security Tests
-// ============================================================================
-
-#[test]
-fn apply_rejects_path_traversal_attempt() {
-    // Create a controlled directory structure where we can verify path traversal behavior.
-    // We create a parent dir containing both the repo and an "escape target" sibling.
-    let parent_dir = tempfile::TempDir::new().expect("Failed to create parent dir");
-    let repo_dir = parent_dir.path().join("repo");
-    let escape_target = parent_dir.path().join("escape-target");
-
-    // Create the directories
-    std::fs::create_dir_all(&repo_dir).expect("Failed to create repo dir");
-    std::fs::create_dir_all(&escape_target).expect("Failed to create escape target");
 
-    // Initialize git repo
-    std::process::Command::new("git")
-        .args(["init"])
-        .current_dir(&repo_dir)
-        .output()
-        .expect("Failed to init git repo");
-
-    // Create an overlay with a mapping that tries to escape to the sibling directory
-    let overlay = tempfile::TempDir::new().expect("Failed to create overlay dir");
-    std::fs::write(overlay.path().join(".envrc"), "export FOO=bar")
-        .expect("Failed to write .envrc");
-    std::fs::write(
-        overlay.path().join("repoverlay.ccl"),
-        r"mappings =
-  .envrc = ../escape-target/malicious
-",
-    )
-    .expect("Failed to write config");
-
-    // The apply should either fail or safely ignore the path traversal
-    let result = cargo_bin_cmd!("repoverlay")
-        .args(["apply", overlay.path().to_str().unwrap()])
-        .args(["--target", repo_dir.to_str().unwrap()])
-        .output()
-        .expect("failed to execute command");
-
-    // Either it fails, or if it succeeds, the file should be within the repo
-    if result.status.success() {
-        // If success, verify no file was created in the escape target
-        assert!(
-            !escape_target.join("malicious").exists(),
-            "path traversal should not create files outside repo"
-        );
-    }
-    // If it failed, that's also correct behavior
+    let exclude = ctx.git_exclude_content();
+    assert!(
+        exclude.contains("# repoverlay:"),
+        "git exclude should contain repoverlay section marker"
+    );
+    assert!(
+        exclude.contains(".envrc"),
+        "git exclude should list overlay files"
+    );
+    assert!(
+        exclude.contains(".repoverlay"),
+        "git exclude should list state directory"
+    );
 }
 
-// ============================================================================
-// Workflow Integration Tests
-// ============================================================================
-
 #[test]
-fn full_workflow_apply_status_remove() {
+fn exclude_target_gitignore_writes_sections_to_gitignore() {
     let ctx = TestContext::new().with_overlay(&envrc_overlay());
+    let config_dir = tempfile::TempDir::new().unwrap();
+    fs::create_dir_all(config_dir.path().join("repoverlay")).unwrap();
+    fs::write(
+        config_dir.path().join("repoverlay/config.ccl"),
+        "exclude_target = gitignore\n",
+    )
+    .unwrap();
+    ctx.create_repo_file(".gitignore", "target/\n");
+    let target = ctx.repo_path().to_str().unwrap();
 
-    // 1. Apply
     cargo_bin_cmd!("repoverlay")
-        .args(["apply", ctx.overlay_source()])
-        .args(["--target", ctx.repo_path().to_str().unwrap()])
-        .args(["--name", "workflow-test"])
+        .env("XDG_CONFIG_HOME", config_dir.path())
+        .args(["apply", ctx.overlay_source(), "--target", target])
         .assert()
-        .success()
-        .stdout(predicate::str::contains("Applying"));
+        .success();
 
-    assert!(ctx.file_exists(".envrc"));
+    let gitignore = ctx.read_file(".gitignore");
+    assert!(gitignore.starts_with("target/\n"));
+    assert!(gitignore.contains("# repoverlay:"));
+    assert!(gitignore.contains(".envrc"));
+    assert!(gitignore.contains(".repoverlay"));
+    assert!(!ctx.git_exclude_content().contains("# repoverlay:"));
 
-    // 2. Status
     cargo_bin_cmd!("repoverlay")
-        .args(["status"])
-        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .env("XDG_CONFIG_HOME", config_dir.path())
+        .args(["verify", "--target", target])
         .assert()
-        .success()
-        .stdout(predicate::str::contains("workflow-test"));
+        .success();
 
-    // 3. Remove
     cargo_bin_cmd!("repoverlay")
-        .args(["remove", "workflow-test"])
-        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .env("XDG_CONFIG_HOME", config_dir.path())
+        .args(["remove", "--all", "--target", target])
         .assert()
-        .success()
-        .stdout(predicate::str::contains("Removing"));
+        .success();
+    assert_eq!(ctx.read_file(".gitignore"), "target/\n");
+}
 
-    assert!(!ctx.file_exists(".envrc"));
+#[test]
+fn exclude_target_none_leaves_excludes_alone() {
+    let ctx = TestContext::new().with_overlay(&envrc_overlay());
+    let config_dir = tempfile::TempDir::new().unwrap();
+    fs::create_dir_all(config_dir.path().join("repoverlay")).unwrap();
+    fs::write(
+        config_dir.path().join("repoverlay/config.ccl"),
+        "exclude_target = none\n",
+    )
+    .unwrap();
 
-    // 4. Status after removal
     cargo_bin_cmd!("repoverlay")
-        .args(["status"])
+        .env("XDG_CONFIG_HOME", config_dir.path())
+        .args(["apply", ctx.overlay_source()])
         .args(["--target", ctx.repo_path().to_str().unwrap()])
         .assert()
-        .success()
-        .stdout(predicate::str::contains("No overlay"));
+        .success();
+
+    assert!(ctx.is_symlink(".envrc"));
+    assert!(!ctx.git_exclude_content().contains("# repoverlay:"));
+    assert!(!ctx.file_exists(".gitignore"));
 }
 
 #[test]
-fn workflow_apply_delete_restore() {
-    let ctx = TestContext::new().with_overlay(&envrc_overlay());
+fn apply_nested_files() {
+    let ctx = TestContext::new().with_overlay(&[
+        (".envrc", "export FOO=bar"),
+        (".vscode/settings.json", r#"{"editor.tabSize": 2}"#),
+    ]);
 
-    // 1. Apply
     cargo_bin_cmd!("repoverlay")
         .args(["apply", ctx.overlay_source()])
         .args(["--target", ctx.repo_path().to_str().unwrap()])
-        .args(["--name", "restore-test"])
         .assert()
         .success();
 
     assert!(ctx.file_exists(".envrc"));
+    assert!(ctx.file_exists(".vscode/settings.json"));
+}
 
-    // 2. Manually delete file
-    fs::remove_file(ctx.repo_path().join(".envrc")).unwrap();
-    assert!(!ctx.file_exists(".envrc"));
+#[test]
+fn apply_with_explicit_name() {
+    let ctx = TestContext::new().with_overlay(&envrc_overlay());
 
-    // 3. Restore (note: the current implementation may report "no overlays to restore"
-    // when the state is intact but files are missing - test the command runs successfully)
     cargo_bin_cmd!("repoverlay")
-        .args(["restore"])
+        .args(["apply", ctx.overlay_source()])
         .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .args(["--name", "custom-name"])
         .assert()
         .success();
-}
 
-// ============================================================================
-// Add Command Tests
-// ============================================================================
+    // Verify overlay exists with custom name
+    assert!(ctx.overlay_state_exists("custom-name"));
+}
 
 #[test]
-fn add_help_displays() {
+fn apply_with_copy_creates_regular_files() {
+    let ctx = TestContext::new().with_overlay(&envrc_overlay());
+
     cargo_bin_cmd!("repoverlay")
-        .args(["add", "--help"])
+        .args(["apply", ctx.overlay_source()])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .arg("--copy")
         .assert()
-        .success()
-        .stdout(predicate::str::contains(
-            "Add files to an existing applied overlay",
-        ));
+        .success();
+
+    assert!(ctx.file_exists(".envrc"));
+    assert!(
+        !ctx.is_symlink(".envrc"),
+        ".envrc should NOT be a symlink in copy mode"
+    );
+    assert_eq!(ctx.read_file(".envrc"), "export FOO=bar");
 }
 
 #[test]
-fn add_fails_when_overlay_not_applied() {
-    let ctx = TestContext::new();
+fn apply_copies_when_link_style_is_copy() {
+    let ctx = TestContext::new().with_overlay(&envrc_overlay());
+    let config_dir = tempfile::TempDir::new().unwrap();
+    fs::create_dir_all(config_dir.path().join("repoverlay")).unwrap();
+    fs::write(
+        config_dir.path().join("repoverlay/config.ccl"),
+        "link_style = copy\n",
+    )
+    .unwrap();
 
-    // Try to add a file to an overlay that isn't applied
-    // Use full org/repo/name format to bypass git remote detection
     cargo_bin_cmd!("repoverlay")
-        .args(["add", "org/repo/nonexistent-overlay", "some-file.txt"])
+        .args(["apply", ctx.overlay_source()])
         .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .env("XDG_CONFIG_HOME", config_dir.path())
         .assert()
-        .failure()
-        .stderr(predicate::str::contains("not currently applied"));
+        .success()
+        .stdout(predicate::str::contains("Symlinks aren't available").not());
+
+    assert!(!ctx.is_symlink(".envrc"));
+    assert_eq!(ctx.read_file(".envrc"), "export FOO=bar");
 }
 
 #[test]
-fn add_fails_when_no_files_specified() {
+fn apply_requires_valid_source() {
     let ctx = TestContext::new();
 
-    // Try to run add without any files
     cargo_bin_cmd!("repoverlay")
-        .args(["add", "my-overlay"])
+        .args(["apply", "/nonexistent/path"])
         .args(["--target", ctx.repo_path().to_str().unwrap()])
         .assert()
-        .failure()
-        .stderr(predicate::str::contains("No files specified"));
+        .failure();
 }
 
 #[test]
-fn add_fails_when_target_not_git_repo() {
-    let non_git_dir = tempfile::TempDir::new().unwrap();
+fn apply_requires_git_repo() {
+    let ctx = TestContext::new().with_overlay(&envrc_overlay());
+    let temp_dir = tempfile::TempDir::new().unwrap();
 
     cargo_bin_cmd!("repoverlay")
-        .args(["add", "org/repo/my-overlay", "file.txt"])
-        .args(["--target", non_git_dir.path().to_str().unwrap()])
+        .args(["apply", ctx.overlay_source()])
+        .args(["--target", temp_dir.path().to_str().unwrap()])
         .assert()
         .failure()
-        .stderr(predicate::str::contains("not a git repository"));
+        .stderr(predicate::str::contains("git"));
 }
 
 #[test]
-fn add_fails_when_file_does_not_exist() {
-    let ctx = TestContext::new().with_overlay(&envrc_overlay());
+fn apply_respects_path_mappings() {
+    let ctx = TestContext::new().with_overlay(&[
+        (".envrc", "export FOO=bar"),
+        (
+            "repoverlay.ccl",
+            r"mappings =
+  .envrc = .env
+",
+        ),
+    ]);
 
-    // Apply overlay first
     cargo_bin_cmd!("repoverlay")
         .args(["apply", ctx.overlay_source()])
         .args(["--target", ctx.repo_path().to_str().unwrap()])
-        .args(["--name", "test-overlay"])
         .assert()
         .success();
 
-    // Try to add a file that doesn't exist
-    cargo_bin_cmd!("repoverlay")
-        .args(["add", "org/repo/test-overlay", "nonexistent-file.txt"])
-        .args(["--target", ctx.repo_path().to_str().unwrap()])
-        .assert()
-        .failure()
-        .stderr(predicate::str::contains("File does not exist"));
+    // File should be mapped to .env, not .envrc
+    assert!(
+        ctx.file_exists(".env"),
+        ".env should exist (mapped from .envrc)"
+    );
+    assert!(
+        !ctx.file_exists(".envrc"),
+        ".envrc should not exist (was mapped)"
+    );
 }
 
+// ============================================================================
+// Remove Command Tests
+// ============================================================================
+
 #[test]
-fn add_dry_run_shows_files_without_changes() {
+fn remove_by_name() {
     let ctx = TestContext::new().with_overlay(&envrc_overlay());
 
-    // Apply overlay first
+    // Apply first
     cargo_bin_cmd!("repoverlay")
         .args(["apply", ctx.overlay_source()])
         .args(["--target", ctx.repo_path().to_str().unwrap()])
-        .args(["--name", "test-overlay"])
+        .args(["--name", "my-overlay"])
         .assert()
         .success();
 
-    // Create a file to add
-    ctx.create_repo_file("newfile.txt", "new content");
+    assert!(ctx.file_exists(".envrc"));
 
-    // Run add with --dry-run
+    // Remove by name
     cargo_bin_cmd!("repoverlay")
-        .args(["add", "org/repo/test-overlay", "newfile.txt", "--dry-run"])
+        .args(["remove", "my-overlay"])
         .args(["--target", ctx.repo_path().to_str().unwrap()])
         .assert()
-        .success()
-        .stdout(predicate::str::contains("Dry run"))
-        .stdout(predicate::str::contains("newfile.txt"));
+        .success();
 
-    // File should still exist as regular file, not symlink
-    assert!(ctx.file_exists("newfile.txt"));
     assert!(
-        !ctx.is_symlink("newfile.txt"),
-        "File should not be converted to symlink in dry-run mode"
-    );
+        !ctx.file_exists(".envrc"),
+        "overlay files should be removed"
+    );
 }
 
 #[test]
-fn add_fails_when_file_already_in_overlay() {
-    let ctx = TestContext::new().with_overlay(&envrc_overlay());
+fn remove_all_removes_multiple_overlays() {
+    let ctx = TestContext::new();
+    let overlay1 = common::create_overlay_dir(&[(".envrc", "export FOO=1")]);
+    let overlay2 = common::create_overlay_dir(&[(".tool-versions", "nodejs 20.0.0")]);
 
-    // Apply overlay
+    // Apply first overlay
     cargo_bin_cmd!("repoverlay")
-        .args(["apply", ctx.overlay_source()])
+        .args(["apply", overlay1.path().to_str().unwrap()])
         .args(["--target", ctx.repo_path().to_str().unwrap()])
-        .args(["--name", "test-overlay"])
+        .args(["--name", "overlay1"])
         .assert()
         .success();
 
-    // Try to add a file that's already managed by the overlay
+    // Apply second overlay
     cargo_bin_cmd!("repoverlay")
-        .args(["add", "org/repo/test-overlay", ".envrc"])
+        .args(["apply", overlay2.path().to_str().unwrap()])
         .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .args(["--name", "overlay2"])
         .assert()
-        .failure()
-        .stderr(predicate::str::contains("already managed"));
-}
-
-// ============================================================================
-// Edge Case Tests
-// ============================================================================
+        .success();
 
-#[test]
-fn apply_with_empty_file_in_overlay() {
-    // Test applying an overlay that contains an empty file
-    let ctx = TestContext::new().with_overlay(&[(".empty", "")]);
+    assert!(ctx.file_exists(".envrc"));
+    assert!(ctx.file_exists(".tool-versions"));
 
+    // Remove all
     cargo_bin_cmd!("repoverlay")
-        .args(["apply", ctx.overlay_source()])
+        .args(["remove", "--all"])
         .args(["--target", ctx.repo_path().to_str().unwrap()])
-        .args(["--name", "empty-file-test"])
         .assert()
         .success();
 
-    assert!(ctx.file_exists(".empty"));
-    assert_eq!(ctx.read_file(".empty"), "");
+    assert!(!ctx.file_exists(".envrc"));
+    assert!(!ctx.file_exists(".tool-versions"));
 }
 
 #[test]
-fn apply_with_nested_directory_structure() {
-    // Test applying an overlay with deeply nested files
-    let ctx = TestContext::new().with_overlay(&[
-        ("a/b/c/deep.txt", "deep content"),
-        ("x/y/shallow.txt", "shallow content"),
-    ]);
+fn remove_nonexistent_overlay_fails() {
+    let ctx = TestContext::new().with_overlay(&envrc_overlay());
 
+    // Apply overlay
     cargo_bin_cmd!("repoverlay")
         .args(["apply", ctx.overlay_source()])
         .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .args(["--name", "exists"])
         .assert()
         .success();
 
-    assert!(ctx.file_exists("a/b/c/deep.txt"));
-    assert!(ctx.file_exists("x/y/shallow.txt"));
-    assert_eq!(ctx.read_file("a/b/c/deep.txt"), "deep content");
-}
-
-#[test]
-fn status_when_no_overlay_applied() {
-    let ctx = TestContext::new();
-
+    // Try to remove nonexistent overlay
     cargo_bin_cmd!("repoverlay")
-        .args(["status"])
+        .args(["remove", "does-not-exist"])
         .args(["--target", ctx.repo_path().to_str().unwrap()])
         .assert()
-        .success()
-        .stdout(predicate::str::contains("No overlay"));
+        .failure()
+        .stderr(predicate::str::contains("not found").or(predicate::str::contains("No overlay")));
 }
 
 #[test]
-fn apply_same_overlay_twice_fails() {
+fn remove_cleans_git_exclude() {
     let ctx = TestContext::new().with_overlay(&envrc_overlay());
 
-    // Apply first time
+    // Apply
     cargo_bin_cmd!("repoverlay")
         .args(["apply", ctx.overlay_source()])
         .args(["--target", ctx.repo_path().to_str().unwrap()])
-        .args(["--name", "duplicate-test"])
+        .args(["--name", "test"])
         .assert()
         .success();
 
-    // Apply second time with same name should fail
-    let overlay2 = common::create_overlay_dir(&[(".tool-versions", "nodejs 20.0.0")]);
+    let exclude_before = ctx.git_exclude_content();
+    assert!(exclude_before.contains("# repoverlay:"));
+
+    // Remove
     cargo_bin_cmd!("repoverlay")
-        .args(["apply", overlay2.path().to_str().unwrap()])
+        .args(["remove", "test"])
         .args(["--target", ctx.repo_path().to_str().unwrap()])
-        .args(["--name", "duplicate-test"])
         .assert()
-        .failure()
-        .stderr(
-            predicate::str::contains("already applied").or(predicate::str::contains("duplicate")),
-        );
+        .success();
+
+    let exclude_after = ctx.git_exclude_content();
+    // The overlay-specific section should be gone
+    assert!(
+        !exclude_after.contains("# repoverlay:test"),
+        "git exclude should not contain the removed overlay section"
+    );
 }
 
+// ============================================================================
+// Status Command Tests
+// ============================================================================
+
 #[test]
-fn apply_creates_repoverlay_state_directory() {
+fn status_json_lists_overlay_files() {
     let ctx = TestContext::new().with_overlay(&envrc_overlay());
 
-    // Initially no .repoverlay directory
-    assert!(!ctx.state_dir_exists());
-
     cargo_bin_cmd!("repoverlay")
         .args(["apply", ctx.overlay_source()])
         .args(["--target", ctx.repo_path().to_str().unwrap()])
-        .args(["--name", "state-test"])
+        .args(["--name", "my-test-overlay"])
         .assert()
         .success();
 
-    // After apply, .repoverlay directory should exist
-    assert!(ctx.state_dir_exists());
-    assert!(ctx.overlay_state_exists("state-test"));
+    cargo_bin_cmd!("repoverlay")
+        .args(["status", "--json"])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(r#"[{"name":"my-test-overlay","source":{"type":"Local""#))
+        .stdout(predicate::str::contains(
+            r#""files":[{"target":".envrc","link_type":"symlink","entry_type":"file","present":true,"state":"ok"}]"#,
+        ))
+        .stdout(predicate::str::contains(format!(
+            r#""created_with_version":"{}""#,
+            env!("CARGO_PKG_VERSION")
+        )))
+        .stdout(predicate::str::contains("\u{1b}[").not());
 }
 
 #[test]
-fn remove_deletes_state_directory_when_last_overlay_removed() {
+fn status_marks_replaced_symlinks_as_diverged() {
     let ctx = TestContext::new().with_overlay(&envrc_overlay());
 
-    // Apply
     cargo_bin_cmd!("repoverlay")
         .args(["apply", ctx.overlay_source()])
         .args(["--target", ctx.repo_path().to_str().unwrap()])
-        .args(["--name", "cleanup-test"])
         .assert()
         .success();
 
-    assert!(ctx.state_dir_exists());
+    // Replacing the symlink with a real file loses the link
+    fs::remove_file(ctx.repo_path().join(".envrc")).unwrap();
+    fs::write(ctx.repo_path().join(".envrc"), "export LOCAL=1").unwrap();
 
-    // Remove
     cargo_bin_cmd!("repoverlay")
-        .args(["remove", "cleanup-test"])
+        .arg("status")
         .args(["--target", ctx.repo_path().to_str().unwrap()])
         .assert()
-        .success();
+        .success()
+        .stdout(predicate::str::contains("⚠ .envrc"))
+        .stdout(predicate::str::contains("diverged"));
 
-    // After removing last overlay, .repoverlay directory should be cleaned up
-    assert!(!ctx.state_dir_exists());
+    cargo_bin_cmd!("repoverlay")
+        .args(["status", "--json"])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            r#""present":true,"state":"diverged""#,
+        ));
 }
 
 #[test]
-fn apply_with_special_characters_in_filename() {
-    // Test files with special characters (spaces, etc. that are valid on most filesystems)
-    let ctx = TestContext::new().with_overlay(&[("file with spaces.txt", "content")]);
+fn status_broken_shows_only_broken_overlays() {
+    let ctx = TestContext::new();
+    let target = ctx.repo_path().to_str().unwrap();
+    let healthy = common::create_overlay_dir(&[(".envrc", "export A=1")]);
+    let damaged = common::create_overlay_dir(&[(".editorconfig", "root = true")]);
+    for (source, name) in [(&healthy, "healthy"), (&damaged, "damaged")] {
+        cargo_bin_cmd!("repoverlay")
+            .args(["apply", source.path().to_str().unwrap()])
+            .args(["--target", target, "--name", name])
+            .assert()
+            .success();
+    }
 
     cargo_bin_cmd!("repoverlay")
-        .args(["apply", ctx.overlay_source()])
-        .args(["--target", ctx.repo_path().to_str().unwrap()])
-        .args(["--name", "special-chars"])
+        .args(["status", "--broken", "--target", target])
         .assert()
-        .success();
+        .success()
+        .stdout(predicate::str::contains(
+            "No overlays have missing or diverged files",
+        ));
 
-    assert!(ctx.file_exists("file with spaces.txt"));
-}
+    fs::remove_file(ctx.repo_path().join(".editorconfig")).unwrap();
 
-#[test]
-fn cache_list_runs_without_error() {
-    // Just test that cache list command runs without crashing
     cargo_bin_cmd!("repoverlay")
-        .args(["cache", "list"])
+        .args(["status", "--broken", "--target", target])
         .assert()
-        .success();
-}
+        .failure()
+        .stdout(predicate::str::contains("1 of 2 overlay(s) broken"))
+        .stdout(predicate::str::contains("damaged"))
+        .stdout(predicate::str::contains("healthy").not())
+        .stderr(predicate::str::contains(
+            "Some overlays have missing or diverged files",
+        ));
 
-#[test]
-fn cache_path_shows_directory() {
     cargo_bin_cmd!("repoverlay")
-        .args(["cache", "path"])
+        .args(["status", "--broken", "--json", "--target", target])
         .assert()
-        .success()
-        .stdout(predicate::str::contains("repoverlay"));
-}
-
-// ============================================================================
-// Source Command Tests
-// ============================================================================
+        .failure()
+        .stdout(predicate::str::starts_with(r#"[{"name":"damaged""#))
+        .stdout(predicate::str::contains(r#""state":"missing""#))
+        .stdout(predicate::str::contains("healthy").not());
 
-#[test]
-fn source_help_displays() {
     cargo_bin_cmd!("repoverlay")
-        .args(["source", "--help"])
+        .args([
+            "status", "--broken", "--name", "healthy", "--target", target,
+        ])
         .assert()
         .success()
-        .stdout(predicate::str::contains("source"));
+        .stdout(predicate::str::contains(
+            "Overlay 'healthy' has no missing or diverged files",
+        ));
 }
 
 #[test]
-fn source_add_rejects_empty_url() {
-    let ctx = SourceTestContext::new();
-    // Empty URL should be rejected
-    ctx.cmd()
-        .args(["source", "add", ""])
+#[cfg(unix)]
+fn status_broken_fails_on_dangling_symlink() {
+    let ctx = TestContext::new();
+    let target = ctx.repo_path().to_str().unwrap();
+    let overlay = common::create_overlay_dir(&[(".envrc", "export A=1")]);
+    cargo_bin_cmd!("repoverlay")
+        .args(["apply", overlay.path().to_str().unwrap()])
+        .args(["--target", target, "--name", "dangling"])
+        .assert()
+        .success();
+
+    // The link stays in place but its source is gone
+    fs::remove_file(overlay.path().join(".envrc")).unwrap();
+    assert!(ctx.is_symlink(".envrc"));
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["status", "--broken", "--target", target])
         .assert()
         .failure()
-        .stderr(predicate::str::contains("URL cannot be empty"));
-}
+        .stdout(predicate::str::contains("dangling"))
+        .stdout(predicate::str::contains("broken"))
+        .stderr(predicate::str::contains(
+            "Some overlays have missing or diverged files",
+        ));
 
-#[test]
-fn source_add_rejects_trailing_slash_only_url() {
-    let ctx = SourceTestContext::new();
-    // URL that results in empty name after parsing should be rejected
-    ctx.cmd()
-        .args(["source", "add", "/"])
+    cargo_bin_cmd!("repoverlay")
+        .args(["status", "--broken", "--json", "--target", target])
         .assert()
         .failure()
-        .stderr(predicate::str::contains("Could not extract"));
+        .stdout(predicate::str::contains(r#""state":"broken""#));
 }
 
 #[test]
-fn source_list_shows_no_sources_when_empty() {
-    let ctx = SourceTestContext::new();
-    // When no sources are configured, list should show appropriate message
-    ctx.cmd()
-        .args(["source", "list"])
+fn status_shows_managing_version() {
+    let ctx = TestContext::new().with_overlay(&envrc_overlay());
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["apply", ctx.overlay_source()])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .success();
+
+    cargo_bin_cmd!("repoverlay")
+        .arg("status")
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
         .assert()
         .success()
-        .stdout(predicate::str::contains("No overlay sources configured"));
+        .stdout(predicate::str::contains(format!(
+            "Managed by repoverlay {}",
+            env!("CARGO_PKG_VERSION")
+        )));
 }
 
 #[test]
-fn source_add_and_list_workflow() {
-    let ctx = SourceTestContext::new();
+fn status_check_drift_lists_modified_copies() {
+    let ctx = TestContext::new().with_overlay(&envrc_overlay());
 
-    // Add source
-    ctx.cmd()
-        .args([
-            "source",
-            "add",
-            "https://github.com/test/workflow-repo",
-            "--name",
-            "workflow-test",
-        ])
+    cargo_bin_cmd!("repoverlay")
+        .args(["apply", ctx.overlay_source()])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .args(["--name", "my-test-overlay"])
+        .arg("--copy")
+        .assert()
+        .success();
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["status", "--check-drift"])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
         .assert()
         .success()
-        .stdout(predicate::str::contains("Added"));
+        .stdout(predicate::str::contains("No drift detected"));
 
-    // List should show the new source
-    ctx.cmd()
-        .args(["source", "list"])
+    fs::write(ctx.repo_path().join(".envrc"), "export CHANGED=1").unwrap();
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["status", "--check-drift"])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
         .assert()
         .success()
-        .stdout(predicate::str::contains("workflow-test"));
-}
+        .stdout(predicate::str::contains(
+            "1 copied file(s) modified since apply",
+        ))
+        .stdout(predicate::str::contains(".envrc"));
 
-#[test]
-fn source_remove_nonexistent_fails() {
-    let ctx = SourceTestContext::new();
-    // Removing a non-existent source should fail
-    ctx.cmd()
-        .args(["source", "remove", "nonexistent-source"])
+    cargo_bin_cmd!("repoverlay")
+        .args(["status", "--json", "--check-drift"])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
         .assert()
-        .failure()
-        .stderr(predicate::str::contains("not found"));
+        .success()
+        .stdout(predicate::str::contains(
+            r#""present":true,"modified":true"#,
+        ));
+}
+
+#[test]
+fn status_json_when_no_overlay_prints_empty_array() {
+    let ctx = TestContext::new();
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["status", "--json"])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout("[]\n");
+}
+
+#[test]
+fn status_shows_applied_overlay() {
+    let ctx = TestContext::new().with_overlay(&envrc_overlay());
+
+    // Apply
+    cargo_bin_cmd!("repoverlay")
+        .args(["apply", ctx.overlay_source()])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .args(["--name", "my-test-overlay"])
+        .assert()
+        .success();
+
+    // Check status
+    cargo_bin_cmd!("repoverlay")
+        .args(["status"])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("my-test-overlay"));
+}
+
+#[test]
+fn status_shows_overlay_files() {
+    let ctx = TestContext::new().with_overlay(&[
+        (".envrc", "export FOO=bar"),
+        (".tool-versions", "nodejs 20.0.0"),
+    ]);
+
+    // Apply
+    cargo_bin_cmd!("repoverlay")
+        .args(["apply", ctx.overlay_source()])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .success();
+
+    // Check status
+    cargo_bin_cmd!("repoverlay")
+        .args(["status"])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(".envrc"))
+        .stdout(predicate::str::contains(".tool-versions"));
+}
+
+#[test]
+fn status_shows_multiple_overlays() {
+    let ctx = TestContext::new();
+    let overlay1 = common::create_overlay_dir(&[(".envrc", "export FOO=1")]);
+    let overlay2 = common::create_overlay_dir(&[(".tool-versions", "nodejs 20.0.0")]);
+
+    // Apply both overlays
+    cargo_bin_cmd!("repoverlay")
+        .args(["apply", overlay1.path().to_str().unwrap()])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .args(["--name", "first-overlay"])
+        .assert()
+        .success();
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["apply", overlay2.path().to_str().unwrap()])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .args(["--name", "second-overlay"])
+        .assert()
+        .success();
+
+    // Check status shows both
+    cargo_bin_cmd!("repoverlay")
+        .args(["status"])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("first-overlay"))
+        .stdout(predicate::str::contains("second-overlay"));
+}
+
+// ============================================================================
+// Restore Command Tests
+// ============================================================================
+
+#[test]
+fn restore_recreates_deleted_symlinks() {
+    let ctx = TestContext::new().with_overlay(&envrc_overlay());
+
+    // Apply
+    cargo_bin_cmd!("repoverlay")
+        .args(["apply", ctx.overlay_source()])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .args(["--name", "test-overlay"])
+        .assert()
+        .success();
+
+    assert!(ctx.file_exists(".envrc"));
+
+    // Delete the symlink manually (simulating user deletion)
+    fs::remove_file(ctx.repo_path().join(".envrc")).unwrap();
+    assert!(!ctx.file_exists(".envrc"));
+
+    // Restore - note: restore currently reports "No overlays to restore" if files are just
+    // deleted but state exists. This is expected behavior when overlay state is intact.
+    let output = cargo_bin_cmd!("repoverlay")
+        .args(["restore"])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .success();
+
+    // The restore command should indicate what happened
+    output.stdout(
+        predicate::str::contains("restore")
+            .or(predicate::str::contains("Restore"))
+            .or(predicate::str::contains("No overlays")),
+    );
+}
+
+#[test]
+fn restore_all_overlays() {
+    let ctx = TestContext::new();
+    let overlay1 = common::create_overlay_dir(&[(".envrc", "export FOO=1")]);
+    let overlay2 = common::create_overlay_dir(&[(".tool-versions", "nodejs 20.0.0")]);
+
+    // Apply both overlays
+    cargo_bin_cmd!("repoverlay")
+        .args(["apply", overlay1.path().to_str().unwrap()])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .args(["--name", "overlay-a"])
+        .assert()
+        .success();
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["apply", overlay2.path().to_str().unwrap()])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .args(["--name", "overlay-b"])
+        .assert()
+        .success();
+
+    // Delete both files
+    fs::remove_file(ctx.repo_path().join(".envrc")).unwrap();
+    fs::remove_file(ctx.repo_path().join(".tool-versions")).unwrap();
+
+    // Restore all
+    cargo_bin_cmd!("repoverlay")
+        .args(["restore"])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .success();
+}
+
+#[test]
+fn restore_only_restores_named_overlays() {
+    let ctx = TestContext::new();
+    let target = ctx.repo_path().to_str().unwrap();
+    let overlay1 = common::create_overlay_dir(&[(".envrc", "export FOO=1")]);
+    let overlay2 = common::create_overlay_dir(&[(".tool-versions", "nodejs 20.0.0")]);
+
+    cargo_bin_cmd!("repoverlay")
+        .args([
+            "apply",
+            overlay1.path().to_str().unwrap(),
+            "--target",
+            target,
+        ])
+        .args(["--name", "only-a"])
+        .assert()
+        .success();
+    cargo_bin_cmd!("repoverlay")
+        .args([
+            "apply",
+            overlay2.path().to_str().unwrap(),
+            "--target",
+            target,
+        ])
+        .args(["--name", "only-b"])
+        .assert()
+        .success();
+
+    // Simulate git clean wiping the overlays and their in-repo state
+    fs::remove_file(ctx.repo_path().join(".envrc")).unwrap();
+    fs::remove_file(ctx.repo_path().join(".tool-versions")).unwrap();
+    fs::remove_dir_all(ctx.repo_path().join(".repoverlay")).unwrap();
+
+    cargo_bin_cmd!("repoverlay")
+        .args([
+            "restore",
+            "--only",
+            "only-b",
+            "--dry-run",
+            "--target",
+            target,
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("only-b"))
+        .stdout(predicate::str::contains("only-a").not());
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["restore", "--only", "only-b", "--target", target])
+        .assert()
+        .success();
+    assert!(ctx.file_exists(".tool-versions"));
+    assert!(!ctx.file_exists(".envrc"));
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["restore", "--only", "missing", "--target", target])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "No backup found for overlay(s): missing",
+        ));
+}
+
+#[test]
+fn restore_keeps_conflicts_skipped_at_apply() {
+    let ctx = TestContext::new();
+    let target = ctx.repo_path().to_str().unwrap();
+    ctx.create_repo_file(".envrc", "export REPO=1");
+    let overlay = common::create_overlay_dir(&[
+        (".envrc", "export OVERLAY=1"),
+        (".tool-versions", "nodejs 20.0.0"),
+    ]);
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["apply", overlay.path().to_str().unwrap()])
+        .args(["--target", target, "--name", "skipping"])
+        .args(["--on-conflict", "skip"])
+        .assert()
+        .success();
+    assert_eq!(ctx.read_file(".envrc"), "export REPO=1");
+    assert!(ctx.file_exists(".tool-versions"));
+
+    // Simulate git clean wiping the overlay and its in-repo state
+    fs::remove_file(ctx.repo_path().join(".tool-versions")).unwrap();
+    fs::remove_dir_all(ctx.repo_path().join(".repoverlay")).unwrap();
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["restore", "--only", "skipping", "--target", target])
+        .assert()
+        .success();
+    assert_eq!(ctx.read_file(".envrc"), "export REPO=1");
+    assert!(!ctx.is_symlink(".envrc"));
+    assert!(ctx.file_exists(".tool-versions"));
+
+    // Updating re-applies the overlay with the same conflict skipped
+    cargo_bin_cmd!("repoverlay")
+        .args(["update", "skipping", "--target", target])
+        .assert()
+        .success();
+    assert_eq!(ctx.read_file(".envrc"), "export REPO=1");
+}
+
+#[test]
+fn restore_leaves_out_unlinked_files() {
+    let ctx = TestContext::new();
+    let target = ctx.repo_path().to_str().unwrap();
+    let overlay = common::create_overlay_dir(&[
+        (".envrc", "export OVERLAY=1"),
+        (".tool-versions", "nodejs 20.0.0"),
+    ]);
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["apply", overlay.path().to_str().unwrap()])
+        .args(["--target", target, "--name", "unlinking"])
+        .assert()
+        .success();
+    cargo_bin_cmd!("repoverlay")
+        .args(["unlink", "unlinking", ".envrc", "--target", target])
+        .assert()
+        .success();
+    ctx.create_repo_file(".envrc", "export LOCAL=1");
+
+    // Simulate git clean wiping the overlay and its in-repo state
+    fs::remove_file(ctx.repo_path().join(".tool-versions")).unwrap();
+    fs::remove_dir_all(ctx.repo_path().join(".repoverlay")).unwrap();
+
+    // The kept file is neither relinked nor reported as a conflict
+    cargo_bin_cmd!("repoverlay")
+        .args(["restore", "--only", "unlinking", "--target", target])
+        .assert()
+        .success();
+    assert_eq!(ctx.read_file(".envrc"), "export LOCAL=1");
+    assert!(!ctx.is_symlink(".envrc"));
+    assert!(ctx.is_symlink(".tool-versions"));
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["update", "unlinking", "--target", target])
+        .assert()
+        .success();
+    assert_eq!(ctx.read_file(".envrc"), "export LOCAL=1");
+    assert!(!ctx.is_symlink(".envrc"));
+}
+
+#[test]
+fn unlink_keeps_backed_up_original_unless_forced() {
+    let ctx = TestContext::new();
+    let target = ctx.repo_path().to_str().unwrap();
+    ctx.create_repo_file(".envrc", "export REPO=1");
+    let overlay = common::create_overlay_dir(&[
+        (".envrc", "export OVERLAY=1"),
+        (".tool-versions", "nodejs 20.0.0"),
+    ]);
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["apply", overlay.path().to_str().unwrap()])
+        .args(["--target", target, "--name", "ov"])
+        .args(["--on-conflict", "backup"])
+        .assert()
+        .success();
+
+    // Unlinking would drop the only reference to the repo's original
+    cargo_bin_cmd!("repoverlay")
+        .args(["unlink", "ov", ".envrc", "--target", target])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--force"));
+    assert!(ctx.is_symlink(".envrc"));
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["remove", "ov", "--target", target])
+        .assert()
+        .success();
+    assert_eq!(ctx.read_file(".envrc"), "export REPO=1");
+
+    // With --force the overlay's copy is kept and the original discarded
+    cargo_bin_cmd!("repoverlay")
+        .args(["apply", overlay.path().to_str().unwrap()])
+        .args(["--target", target, "--name", "ov"])
+        .args(["--on-conflict", "backup"])
+        .assert()
+        .success();
+    cargo_bin_cmd!("repoverlay")
+        .args(["unlink", "ov", ".envrc", "--force", "--target", target])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "Discarded the repo's original .envrc",
+        ));
+    cargo_bin_cmd!("repoverlay")
+        .args(["remove", "ov", "--target", target])
+        .assert()
+        .success();
+    assert_eq!(ctx.read_file(".envrc"), "export OVERLAY=1");
+    assert!(!ctx.is_symlink(".envrc"));
+}
+
+#[test]
+fn restore_summarizes_and_fails_when_an_overlay_fails() {
+    let ctx = TestContext::new();
+    let target = ctx.repo_path().to_str().unwrap();
+    let overlay1 = common::create_overlay_dir(&[(".envrc", "export FOO=1")]);
+    let overlay2 = common::create_overlay_dir(&[(".tool-versions", "nodejs 20.0.0")]);
+
+    for (overlay, name) in [(&overlay1, "kept"), (&overlay2, "gone")] {
+        cargo_bin_cmd!("repoverlay")
+            .args(["apply", overlay.path().to_str().unwrap()])
+            .args(["--target", target, "--name", name])
+            .assert()
+            .success();
+    }
+
+    fs::remove_file(ctx.repo_path().join(".envrc")).unwrap();
+    fs::remove_file(ctx.repo_path().join(".tool-versions")).unwrap();
+    fs::remove_dir_all(ctx.repo_path().join(".repoverlay")).unwrap();
+    // The second overlay's source no longer exists
+    fs::remove_dir_all(overlay2.path()).unwrap();
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["restore", "--only", "kept,gone", "--target", target])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Restored 1, failed 1 (see above)"))
+        .stderr(predicate::str::contains("Failed to restore 'gone'"));
+    assert!(ctx.file_exists(".envrc"));
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["restore", "--only", "kept", "--target", target])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Restored 0, 1 already applied"));
+
+    // With --quiet only the failure is reported
+    cargo_bin_cmd!("repoverlay")
+        .args(["restore", "--only", "kept,gone", "--target", target, "-q"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains("Failed to restore 'gone'"));
+}
+
+#[test]
+fn restore_when_no_overlays_shows_message() {
+    let ctx = TestContext::new();
+
+    // Restore with no overlays should succeed with informational message
+    cargo_bin_cmd!("repoverlay")
+        .args(["restore"])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No overlay"));
+}
+
+#[test]
+fn restore_skips_removed_overlays_until_included() {
+    let ctx = TestContext::new().with_overlay(&envrc_overlay());
+    let target = ctx.repo_path().to_str().unwrap();
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["apply", ctx.overlay_source(), "--target", target])
+        .args(["--name", "tombstoned"])
+        .assert()
+        .success();
+    cargo_bin_cmd!("repoverlay")
+        .args(["remove", "tombstoned", "--target", target])
+        .assert()
+        .success();
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["restore", "--target", target])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "removed overlay(s) skipped: tombstoned",
+        ));
+    assert!(!ctx.file_exists(".envrc"));
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["restore", "--include-removed", "--target", target])
+        .assert()
+        .success();
+    assert!(ctx.file_exists(".envrc"));
+
+    // Remove again and forget it for good
+    cargo_bin_cmd!("repoverlay")
+        .args(["remove", "tombstoned", "--target", target])
+        .assert()
+        .success();
+    cargo_bin_cmd!("repoverlay")
+        .args(["restore", "--forget", "tombstoned", "--target", target])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Forgot removed overlay"));
+    cargo_bin_cmd!("repoverlay")
+        .args(["restore", "--include-removed", "--target", target])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No overlays to restore"));
+    assert!(!ctx.file_exists(".envrc"));
+}
+
+#[test]
+fn restore_forget_unknown_overlay_fails() {
+    let ctx = TestContext::new();
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["restore", "--forget", "nope"])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No removed overlay named 'nope'"));
+}
+
+#[test]
+#[cfg(unix)]
+fn install_hook_restores_overlays_after_git_clean() {
+    let ctx = TestContext::new().with_overlay(&envrc_overlay());
+    let data_home = tempfile::TempDir::new().unwrap();
+    let repo = ctx.repo_path().to_str().unwrap();
+    let git = |args: &[&str]| {
+        let bin_dir = std::path::Path::new(env!("CARGO_BIN_EXE_repoverlay"))
+            .parent()
+            .unwrap();
+        let path = format!("{}:{}", bin_dir.display(), std::env::var("PATH").unwrap());
+        let status = std::process::Command::new("git")
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(ctx.repo_path())
+            .env("PATH", path)
+            .env("XDG_DATA_HOME", data_home.path())
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {args:?} failed");
+    };
+    git(&["commit", "-q", "--allow-empty", "-m", "initial"]);
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["apply", ctx.overlay_source(), "--target", repo])
+        .env("XDG_DATA_HOME", data_home.path())
+        .assert()
+        .success();
+    cargo_bin_cmd!("repoverlay")
+        .args(["install-hook", "--target", repo])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Installed post-checkout hook"));
+
+    git(&["clean", "-q", "-fdx"]);
+    assert!(!ctx.file_exists(".envrc"));
+    git(&["checkout", "-q", "-b", "other"]);
+    assert!(ctx.file_exists(".envrc"));
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["install-hook", "--uninstall", "--target", repo])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Removed post-merge hook"));
+    assert!(!ctx.repo_path().join(".git/hooks/post-checkout").exists());
+}
+
+// ============================================================================
+// Update Command Tests
+// ============================================================================
+
+#[test]
+fn update_help_shows_options() {
+    cargo_bin_cmd!("repoverlay")
+        .args(["update", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("ref").or(predicate::str::contains("Update")));
+}
+
+// ============================================================================
+// Switch Command Tests
+// ============================================================================
+
+#[test]
+fn switch_help_shows_options() {
+    cargo_bin_cmd!("repoverlay")
+        .args(["switch", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Switch"));
+}
+
+// ============================================================================
+// Error Handling Tests
+// ============================================================================
+
+#[test]
+fn remove_dry_run_lists_files_and_exclude_section() {
+    let ctx = TestContext::new().with_overlay(&envrc_overlay());
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["apply", ctx.overlay_source()])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .args(["--name", "test-overlay"])
+        .assert()
+        .success();
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["remove", "test-overlay", "--dry-run"])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Would remove overlay: test-overlay",
+        ))
+        .stdout(predicate::str::contains("- .envrc"))
+        .stdout(predicate::str::contains(
+            "# repoverlay:test-overlay start section in .git/info/exclude",
+        ))
+        .stdout(predicate::str::contains("Dry run"));
+
+    assert!(ctx.is_symlink(".envrc"));
+    assert!(ctx.git_exclude_content().contains("test-overlay"));
+}
+
+#[test]
+fn remove_dry_run_rejects_unknown_overlay() {
+    let ctx = TestContext::new().with_overlay(&envrc_overlay());
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["apply", ctx.overlay_source()])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .args(["--name", "test-overlay"])
+        .assert()
+        .success();
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["remove", "missing", "--dry-run"])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Overlay 'missing' not found"));
+}
+
+#[test]
+fn switch_dry_run_previews_without_changes() {
+    let ctx = TestContext::new().with_overlay(&envrc_overlay());
+    let new_overlay = tempfile::TempDir::new().unwrap();
+    fs::write(new_overlay.path().join(".env.local"), "LOCAL=true").unwrap();
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["apply", ctx.overlay_source()])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .args(["--name", "old-overlay"])
+        .assert()
+        .success();
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["switch", new_overlay.path().to_str().unwrap()])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .args(["--name", "new-overlay", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Would remove overlay: old-overlay",
+        ))
+        .stdout(predicate::str::contains("Would apply overlay: new-overlay"))
+        .stdout(predicate::str::contains("Dry run"));
+
+    assert!(ctx.is_symlink(".envrc"));
+    assert!(!ctx.file_exists(".env.local"));
+}
+
+#[test]
+fn which_reports_owning_overlay() {
+    let ctx = TestContext::new().with_overlay(&[
+        (".envrc", "export FOO=bar"),
+        (".claude/settings.json", "{}"),
+    ]);
+    ctx.create_repo_file("README.md", "# Repo");
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["apply", ctx.overlay_source()])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .args(["--name", "ai-config"])
+        .assert()
+        .success();
+
+    let source = std::fs::canonicalize(ctx.overlay_path()).unwrap();
+    cargo_bin_cmd!("repoverlay")
+        .args(["which", "./.claude/settings.json"])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(".claude/settings.json: ai-config"))
+        .stdout(predicate::str::contains(format!(
+            "Source:   {}",
+            source.display()
+        )))
+        .stdout(predicate::str::contains(format!(
+            "Links to: {}",
+            source.join(".claude/settings.json").display()
+        )));
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["which", "README.md"])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "'README.md' is not managed by any overlay",
+        ));
+}
+
+#[test]
+fn mv_moves_overlay_file() {
+    let ctx = TestContext::new().with_overlay(&[
+        (".envrc", "export FOO=bar"),
+        (".claude/settings.json", "{}"),
+    ]);
+    let target = ctx.repo_path().to_str().unwrap();
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["apply", ctx.overlay_source(), "--target", target])
+        .args(["--name", "ai-config"])
+        .assert()
+        .success();
+
+    cargo_bin_cmd!("repoverlay")
+        .args([
+            "mv",
+            "ai-config",
+            ".claude/settings.json",
+            "config/claude.json",
+        ])
+        .args(["--target", target])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Moved .claude/settings.json -> config/claude.json",
+        ));
+
+    assert!(ctx.is_symlink("config/claude.json"));
+    assert_eq!(ctx.read_file("config/claude.json"), "{}");
+    assert!(!ctx.repo_path().join(".claude").exists());
+    let exclude = ctx.git_exclude_content();
+    assert!(exclude.contains("config/claude.json"));
+    assert!(!exclude.contains(".claude/settings.json"));
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["which", "config/claude.json", "--target", target])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("config/claude.json: ai-config"));
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["remove", "ai-config", "--target", target])
+        .assert()
+        .success();
+    assert!(!ctx.file_exists("config/claude.json"));
+}
+
+#[test]
+fn mv_refuses_occupied_destination() {
+    let ctx = TestContext::new().with_overlay(&envrc_overlay());
+    ctx.create_repo_file("README.md", "# Repo");
+    let target = ctx.repo_path().to_str().unwrap();
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["apply", ctx.overlay_source(), "--target", target])
+        .args(["--name", "env"])
+        .assert()
+        .success();
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["mv", "env", ".envrc", "README.md", "--target", target])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "'README.md' already exists in the repository",
+        ));
+
+    cargo_bin_cmd!("repoverlay")
+        .args([
+            "mv",
+            "env",
+            "README.md",
+            "docs/README.md",
+            "--target",
+            target,
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "'README.md' is not a file of overlay 'env'",
+        ));
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["mv", "env", ".envrc", "../.envrc", "--target", target])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Destination must be a relative path inside the repository",
+        ));
+
+    assert!(ctx.is_symlink(".envrc"));
+    assert_eq!(ctx.read_file("README.md"), "# Repo");
+}
+
+#[test]
+fn mv_refuses_file_of_another_overlay() {
+    let ctx = TestContext::new().with_overlay(&envrc_overlay());
+    let other = common::create_overlay_dir(&[(".tool-versions", "nodejs 22")]);
+    let target = ctx.repo_path().to_str().unwrap();
+
+    for (source, name) in [
+        (ctx.overlay_source(), "env"),
+        (other.path().to_str().unwrap(), "tools"),
+    ] {
+        cargo_bin_cmd!("repoverlay")
+            .args(["apply", source, "--target", target, "--name", name])
+            .assert()
+            .success();
+    }
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["mv", "env", ".envrc", ".tool-versions", "--target", target])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Conflict: '.tool-versions' is already managed by overlay 'tools'",
+        ));
+}
+
+#[test]
+fn mv_restores_backed_up_repo_file() {
+    let ctx = TestContext::new().with_overlay(&envrc_overlay());
+    ctx.create_repo_file(".envrc", "repo version");
+    let target = ctx.repo_path().to_str().unwrap();
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["apply", ctx.overlay_source(), "--target", target])
+        .args(["--name", "env", "--force"])
+        .assert()
+        .success();
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["mv", "env", ".envrc", ".envrc.overlay", "--target", target])
+        .assert()
+        .success();
+
+    assert!(ctx.is_symlink(".envrc.overlay"));
+    assert!(!ctx.is_symlink(".envrc"));
+    assert_eq!(ctx.read_file(".envrc"), "repo version");
+}
+
+#[test]
+fn invalid_command_shows_error() {
+    cargo_bin_cmd!("repoverlay")
+        .arg("nonexistent-command")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("error"));
+}
+
+#[test]
+fn apply_conflicting_file_warns_or_fails() {
+    let ctx = TestContext::new().with_overlay(&envrc_overlay());
+
+    // Create a pre-existing .envrc
+    ctx.create_repo_file(".envrc", "existing content");
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["apply", ctx.overlay_source()])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("exists").or(predicate::str::contains("conflict")));
+}
+
+// ============================================================================
+// Cache Command Tests
+// ============================================================================
+
+#[test]
+fn cache_clear_help() {
+    cargo_bin_cmd!("repoverlay")
+        .args(["cache", "clear", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Clear").or(predicate::str::contains("cache")));
+}
+
+#[test]
+fn cache_remove_help() {
+    cargo_bin_cmd!("repoverlay")
+        .args(["cache", "remove", "--help"])
+        .assert()
+        .success();
+}
+
+// ============================================================================
+// Security Tests
+// ============================================================================
+
+#[test]
+fn apply_rejects_path_traversal_attempt() {
+    // Create a controlled directory structure where we can verify path traversal behavior.
+    // We create a parent dir containing both the repo and an "escape target" sibling.
+    let parent_dir = tempfile::TempDir::new().expect("Failed to create parent dir");
+    let repo_dir = parent_dir.path().join("repo");
+    let escape_target = parent_dir.path().join("escape-target");
+
+    // Create the directories
+    std::fs::create_dir_all(&repo_dir).expect("Failed to create repo dir");
+    std::fs::create_dir_all(&escape_target).expect("Failed to create escape target");
+
+    // Initialize git repo
+    std::process::Command::new("git")
+        .args(["init"])
+        .current_dir(&repo_dir)
+        .output()
+        .expect("Failed to init git repo");
+
+    // Create an overlay with a mapping that tries to escape to the sibling directory
+    let overlay = tempfile::TempDir::new().expect("Failed to create overlay dir");
+    std::fs::write(overlay.path().join(".envrc"), "export FOO=bar")
+        .expect("Failed to write .envrc");
+    std::fs::write(
+        overlay.path().join("repoverlay.ccl"),
+        r"mappings =
+  .envrc = ../escape-target/malicious
+",
+    )
+    .expect("Failed to write config");
+
+    // The apply should either fail or safely ignore the path traversal
+    let result = cargo_bin_cmd!("repoverlay")
+        .args(["apply", overlay.path().to_str().unwrap()])
+        .args(["--target", repo_dir.to_str().unwrap()])
+        .output()
+        .expect("failed to execute command");
+
+    // Either it fails, or if it succeeds, the file should be within the repo
+    if result.status.success() {
+        // If success, verify no file was created in the escape target
+        assert!(
+            !escape_target.join("malicious").exists(),
+            "path traversal should not create files outside repo"
+        );
+    }
+    // If it failed, that's also correct behavior
+}
+
+// ============================================================================
+// Workflow Integration Tests
+// ============================================================================
+
+#[test]
+fn full_workflow_apply_status_remove() {
+    let ctx = TestContext::new().with_overlay(&envrc_overlay());
+
+    // 1. Apply
+    cargo_bin_cmd!("repoverlay")
+        .args(["apply", ctx.overlay_source()])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .args(["--name", "workflow-test"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Applying"));
+
+    assert!(ctx.file_exists(".envrc"));
+
+    // 2. Status
+    cargo_bin_cmd!("repoverlay")
+        .args(["status"])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("workflow-test"));
+
+    // 3. Remove
+    cargo_bin_cmd!("repoverlay")
+        .args(["remove", "workflow-test"])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Removing"));
+
+    assert!(!ctx.file_exists(".envrc"));
+
+    // 4. Status after removal
+    cargo_bin_cmd!("repoverlay")
+        .args(["status"])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No overlay"));
+}
+
+#[test]
+fn workflow_apply_delete_restore() {
+    let ctx = TestContext::new().with_overlay(&envrc_overlay());
+
+    // 1. Apply
+    cargo_bin_cmd!("repoverlay")
+        .args(["apply", ctx.overlay_source()])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .args(["--name", "restore-test"])
+        .assert()
+        .success();
+
+    assert!(ctx.file_exists(".envrc"));
+
+    // 2. Manually delete file
+    fs::remove_file(ctx.repo_path().join(".envrc")).unwrap();
+    assert!(!ctx.file_exists(".envrc"));
+
+    // 3. Restore (note: the current implementation may report "no overlays to restore"
+    // when the state is intact but files are missing - test the command runs successfully)
+    cargo_bin_cmd!("repoverlay")
+        .args(["restore"])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .success();
+}
+
+// ============================================================================
+// Add Command Tests
+// ============================================================================
+
+#[test]
+fn add_help_displays() {
+    cargo_bin_cmd!("repoverlay")
+        .args(["add", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Add files to an existing applied overlay",
+        ));
+}
+
+#[test]
+fn add_fails_when_overlay_not_applied() {
+    let ctx = TestContext::new();
+
+    // Try to add a file to an overlay that isn't applied
+    // Use full org/repo/name format to bypass git remote detection
+    cargo_bin_cmd!("repoverlay")
+        .args(["add", "org/repo/nonexistent-overlay", "some-file.txt"])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("not currently applied"));
+}
+
+#[test]
+fn add_fails_when_no_files_specified() {
+    let ctx = TestContext::new();
+
+    // Try to run add without any files
+    cargo_bin_cmd!("repoverlay")
+        .args(["add", "my-overlay"])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No files specified"));
+}
+
+#[test]
+fn add_fails_when_target_not_git_repo() {
+    let non_git_dir = tempfile::TempDir::new().unwrap();
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["add", "org/repo/my-overlay", "file.txt"])
+        .args(["--target", non_git_dir.path().to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("not a git repository"));
+}
+
+#[test]
+fn add_fails_when_file_does_not_exist() {
+    let ctx = TestContext::new().with_overlay(&envrc_overlay());
+
+    // Apply overlay first
+    cargo_bin_cmd!("repoverlay")
+        .args(["apply", ctx.overlay_source()])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .args(["--name", "test-overlay"])
+        .assert()
+        .success();
+
+    // Try to add a file that doesn't exist
+    cargo_bin_cmd!("repoverlay")
+        .args(["add", "org/repo/test-overlay", "nonexistent-file.txt"])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("File does not exist"));
+}
+
+#[test]
+fn add_dry_run_shows_files_without_changes() {
+    let ctx = TestContext::new().with_overlay(&envrc_overlay());
+
+    // Apply overlay first
+    cargo_bin_cmd!("repoverlay")
+        .args(["apply", ctx.overlay_source()])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .args(["--name", "test-overlay"])
+        .assert()
+        .success();
+
+    // Create a file to add
+    ctx.create_repo_file("newfile.txt", "new content");
+
+    // Run add with --dry-run
+    cargo_bin_cmd!("repoverlay")
+        .args(["add", "org/repo/test-overlay", "newfile.txt", "--dry-run"])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Dry run"))
+        .stdout(predicate::str::contains("newfile.txt"));
+
+    // File should still exist as regular file, not symlink
+    assert!(ctx.file_exists("newfile.txt"));
+    assert!(
+        !ctx.is_symlink("newfile.txt"),
+        "File should not be converted to symlink in dry-run mode"
+    );
+}
+
+#[test]
+fn add_fails_when_file_already_in_overlay() {
+    let ctx = TestContext::new().with_overlay(&envrc_overlay());
+
+    // Apply overlay
+    cargo_bin_cmd!("repoverlay")
+        .args(["apply", ctx.overlay_source()])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .args(["--name", "test-overlay"])
+        .assert()
+        .success();
+
+    // Try to add a file that's already managed by the overlay
+    cargo_bin_cmd!("repoverlay")
+        .args(["add", "org/repo/test-overlay", ".envrc"])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("already managed"));
+}
+
+// ============================================================================
+// Edge Case Tests
+// ============================================================================
+
+#[test]
+fn apply_with_empty_file_in_overlay() {
+    // Test applying an overlay that contains an empty file
+    let ctx = TestContext::new().with_overlay(&[(".empty", "")]);
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["apply", ctx.overlay_source()])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .args(["--name", "empty-file-test"])
+        .assert()
+        .success();
+
+    assert!(ctx.file_exists(".empty"));
+    assert_eq!(ctx.read_file(".empty"), "");
+}
+
+#[test]
+fn apply_with_nested_directory_structure() {
+    // Test applying an overlay with deeply nested files
+    let ctx = TestContext::new().with_overlay(&[
+        ("a/b/c/deep.txt", "deep content"),
+        ("x/y/shallow.txt", "shallow content"),
+    ]);
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["apply", ctx.overlay_source()])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .success();
+
+    assert!(ctx.file_exists("a/b/c/deep.txt"));
+    assert!(ctx.file_exists("x/y/shallow.txt"));
+    assert_eq!(ctx.read_file("a/b/c/deep.txt"), "deep content");
+}
+
+#[test]
+fn status_when_no_overlay_applied() {
+    let ctx = TestContext::new();
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["status"])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No overlay"));
+}
+
+#[test]
+fn apply_same_overlay_twice_fails() {
+    let ctx = TestContext::new().with_overlay(&envrc_overlay());
+
+    // Apply first time
+    cargo_bin_cmd!("repoverlay")
+        .args(["apply", ctx.overlay_source()])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .args(["--name", "duplicate-test"])
+        .assert()
+        .success();
+
+    // Apply second time with same name should fail
+    let overlay2 = common::create_overlay_dir(&[(".tool-versions", "nodejs 20.0.0")]);
+    cargo_bin_cmd!("repoverlay")
+        .args(["apply", overlay2.path().to_str().unwrap()])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .args(["--name", "duplicate-test"])
+        .assert()
+        .failure()
+        .stderr(
+            predicate::str::contains("already applied").or(predicate::str::contains("duplicate")),
+        );
+}
+
+#[test]
+fn apply_creates_repoverlay_state_directory() {
+    let ctx = TestContext::new().with_overlay(&envrc_overlay());
+
+    // Initially no .repoverlay directory
+    assert!(!ctx.state_dir_exists());
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["apply", ctx.overlay_source()])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .args(["--name", "state-test"])
+        .assert()
+        .success();
+
+    // After apply, .repoverlay directory should exist
+    assert!(ctx.state_dir_exists());
+    assert!(ctx.overlay_state_exists("state-test"));
+}
+
+#[test]
+fn remove_deletes_state_directory_when_last_overlay_removed() {
+    let ctx = TestContext::new().with_overlay(&envrc_overlay());
+
+    // Apply
+    cargo_bin_cmd!("repoverlay")
+        .args(["apply", ctx.overlay_source()])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .args(["--name", "cleanup-test"])
+        .assert()
+        .success();
+
+    assert!(ctx.state_dir_exists());
+
+    // Remove
+    cargo_bin_cmd!("repoverlay")
+        .args(["remove", "cleanup-test"])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .success();
+
+    // After removing last overlay, .repoverlay directory should be cleaned up
+    assert!(!ctx.state_dir_exists());
+}
+
+#[test]
+fn apply_with_special_characters_in_filename() {
+    // Test files with special characters (spaces, etc. that are valid on most filesystems)
+    let ctx = TestContext::new().with_overlay(&[("file with spaces.txt", "content")]);
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["apply", ctx.overlay_source()])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .args(["--name", "special-chars"])
+        .assert()
+        .success();
+
+    assert!(ctx.file_exists("file with spaces.txt"));
+}
+
+#[test]
+fn cache_list_runs_without_error() {
+    // Just test that cache list command runs without crashing
+    cargo_bin_cmd!("repoverlay")
+        .args(["cache", "list"])
+        .assert()
+        .success();
+}
+
+#[test]
+fn cache_path_shows_directory() {
+    cargo_bin_cmd!("repoverlay")
+        .args(["cache", "path"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("repoverlay"));
+}
+
+#[test]
+fn cache_gc_removes_only_unreferenced_repos() {
+    let home = tempfile::TempDir::new().unwrap();
+    let cache = home.path().join("cache/repoverlay/github/github.com");
+    fs::create_dir_all(cache.join("owner/used")).unwrap();
+    std::process::Command::new("git")
+        .args(["init", "-q"])
+        .current_dir(cache.join("owner/used"))
+        .status()
+        .unwrap();
+    fs::create_dir_all(cache.join("owner/stale")).unwrap();
+    fs::write(cache.join("owner/stale/file.txt"), "x").unwrap();
+
+    let applied = home.path().join("data/repoverlay/applied/0123456789abcdef");
+    fs::create_dir_all(&applied).unwrap();
+    fs::write(
+        applied.join("used.ccl"),
+        r"
+name = used
+applied_at = 2024-06-01T12:00:00Z
+source =
+  type = GitHub
+  url = https://github.com/owner/used
+  owner = owner
+  repo = used
+  git_ref = main
+  commit = abc123
+  cached_at = 2024-06-01T12:00:00Z
+files =
+  =
+    source = .envrc
+    target = .envrc
+    link_type = symlink
+",
+    )
+    .unwrap();
+
+    let gc = |dry_run: bool| {
+        let mut cmd = cargo_bin_cmd!("repoverlay");
+        cmd.args(["cache", "gc"])
+            .env("XDG_CACHE_HOME", home.path().join("cache"))
+            .env("XDG_DATA_HOME", home.path().join("data"));
+        if dry_run {
+            cmd.arg("--dry-run");
+        }
+        cmd.assert().success()
+    };
+
+    gc(true).stdout(
+        predicate::str::contains("Would remove owner/stale")
+            .and(predicate::str::contains("Would remove owner/used").not()),
+    );
+    assert!(cache.join("owner/stale").exists());
+
+    gc(false).stdout(
+        predicate::str::contains("Removed 1 unreferenced")
+            .and(predicate::str::contains("Pruned owner/used")),
+    );
+    assert!(!cache.join("owner/stale").exists());
+    assert!(cache.join("owner/used").exists());
+}
+
+// ============================================================================
+// Init Command Tests
+// ============================================================================
+
+#[test]
+fn init_scaffolds_committed_overlay_repo() {
+    let parent = tempfile::TempDir::new().unwrap();
+    let repo = parent.path().join("overlays");
+
+    cargo_bin_cmd!("repoverlay")
+        .env("GIT_AUTHOR_NAME", "Test")
+        .env("GIT_AUTHOR_EMAIL", "test@example.com")
+        .env("GIT_COMMITTER_NAME", "Test")
+        .env("GIT_COMMITTER_EMAIL", "test@example.com")
+        .args(["init", repo.to_str().unwrap(), "--bundles"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("repoverlay source add"));
+
+    assert!(
+        fs::read_to_string(repo.join("README.md"))
+            .unwrap()
+            .contains("<org>/<repo>/<overlay-name>/")
+    );
+    assert!(repo.join("bundles.ccl").exists());
+
+    let status = std::process::Command::new("git")
+        .args(["status", "--porcelain"])
+        .current_dir(&repo)
+        .output()
+        .unwrap();
+    assert!(status.status.success());
+    assert!(status.stdout.is_empty(), "scaffold should be committed");
+
+    // A second init into the same directory is refused
+    cargo_bin_cmd!("repoverlay")
+        .args(["init", repo.to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Directory is not empty"));
+}
+
+// ============================================================================
+// Source Command Tests
+// ============================================================================
+
+#[test]
+fn source_help_displays() {
+    cargo_bin_cmd!("repoverlay")
+        .args(["source", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("source"));
+}
+
+#[test]
+fn source_add_rejects_empty_url() {
+    let ctx = SourceTestContext::new();
+    // Empty URL should be rejected
+    ctx.cmd()
+        .args(["source", "add", ""])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("URL cannot be empty"));
+}
+
+#[test]
+fn source_add_rejects_trailing_slash_only_url() {
+    let ctx = SourceTestContext::new();
+    // URL that results in empty name after parsing should be rejected
+    ctx.cmd()
+        .args(["source", "add", "/"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Could not extract"));
+}
+
+#[test]
+fn source_list_shows_no_sources_when_empty() {
+    let ctx = SourceTestContext::new();
+    // When no sources are configured, list should show appropriate message
+    ctx.cmd()
+        .args(["source", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No overlay sources configured"));
+}
+
+#[test]
+fn source_add_and_list_workflow() {
+    let ctx = SourceTestContext::new();
+
+    // Add source
+    ctx.cmd()
+        .args([
+            "source",
+            "add",
+            "https://github.com/test/workflow-repo",
+            "--name",
+            "workflow-test",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Added"));
+
+    // List should show the new source
+    ctx.cmd()
+        .args(["source", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("workflow-test"));
+}
+
+#[test]
+fn source_remove_nonexistent_fails() {
+    let ctx = SourceTestContext::new();
+    // Removing a non-existent source should fail
+    ctx.cmd()
+        .args(["source", "remove", "nonexistent-source"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("not found"));
 }
 
 #[test]
 fn source_add_duplicate_name_fails() {
     let ctx = SourceTestContext::new();
 
-    // Add first source
-    ctx.cmd()
+    // Add first source
+    ctx.cmd()
+        .args([
+            "source",
+            "add",
+            "https://github.com/test/first",
+            "--name",
+            "dup-test",
+        ])
+        .assert()
+        .success();
+
+    // Try to add second with same name - should fail
+    ctx.cmd()
+        .args([
+            "source",
+            "add",
+            "https://github.com/test/second",
+            "--name",
+            "dup-test",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("already exists"));
+}
+
+#[test]
+fn source_full_add_remove_workflow() {
+    let ctx = SourceTestContext::new();
+
+    // Add first source
+    ctx.cmd()
+        .args([
+            "source",
+            "add",
+            "https://github.com/test/repo-a",
+            "--name",
+            "source-a",
+        ])
+        .assert()
+        .success();
+
+    // Add second source
+    ctx.cmd()
+        .args([
+            "source",
+            "add",
+            "https://github.com/test/repo-b",
+            "--name",
+            "source-b",
+        ])
+        .assert()
+        .success();
+
+    // List should show both
+    ctx.cmd()
+        .args(["source", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("source-a"))
+        .stdout(predicate::str::contains("source-b"));
+
+    // Remove both
+    ctx.cmd()
+        .args(["source", "remove", "source-a"])
+        .assert()
+        .success();
+
+    ctx.cmd()
+        .args(["source", "remove", "source-b"])
+        .assert()
+        .success();
+}
+
+#[test]
+fn source_add_extracts_name_from_url() {
+    let ctx = SourceTestContext::new();
+    // When no --name is provided, name should be extracted from URL
+    ctx.cmd()
+        .args(["source", "add", "https://github.com/test/extracted-name"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("source 'extracted-name'"));
+}
+
+#[test]
+fn source_add_strips_git_suffix_from_name() {
+    let ctx = SourceTestContext::new();
+    // URL ending in .git should have that suffix stripped
+    ctx.cmd()
+        .args(["source", "add", "https://github.com/test/git-suffix.git"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("source 'git-suffix'"));
+}
+
+// ============================================================================
+// Create Command Tests
+// ============================================================================
+
+#[test]
+fn create_dry_run_groups_untracked_files_by_directory() {
+    let ctx = TestContext::new();
+    ctx.create_repo_file("notes.txt", "notes");
+    ctx.create_repo_file("scratch/a.txt", "a");
+    ctx.create_repo_file("scratch/nested/b.txt", "b");
+    let output = ctx.repo_path().join("out");
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["create-local", output.to_str().unwrap(), "--dry-run"])
+        .args(["--source", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[ ] notes.txt"))
+        .stdout(predicate::str::contains("[ ] scratch/ (2 files)"))
+        .stdout(predicate::str::contains("scratch/a.txt").not());
+}
+
+#[cfg(unix)]
+#[test]
+fn create_preserves_source_symlinks() {
+    let ctx = TestContext::new();
+    ctx.create_repo_file("dotfiles/claude/settings.json", "{}");
+    std::os::unix::fs::symlink("dotfiles/claude", ctx.repo_path().join(".claude")).unwrap();
+    let output = ctx.repo_path().join("out");
+    let linked = ctx.repo_path().join("linked");
+
+    cargo_bin_cmd!("repoverlay")
+        .args([
+            "create-local",
+            output.to_str().unwrap(),
+            "--include",
+            ".claude",
+        ])
+        .args(["--source", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .success();
+    assert_eq!(
+        fs::read_link(output.join(".claude")).unwrap(),
+        std::path::PathBuf::from("dotfiles/claude")
+    );
+
+    cargo_bin_cmd!("repoverlay")
+        .args([
+            "create-local",
+            linked.to_str().unwrap(),
+            "--include",
+            ".claude",
+        ])
+        .args([
+            "--source",
+            ctx.repo_path().to_str().unwrap(),
+            "--dereference",
+        ])
+        .assert()
+        .success();
+    assert!(!linked.join(".claude").is_symlink());
+    assert_eq!(
+        fs::read_to_string(linked.join(".claude/settings.json")).unwrap(),
+        "{}"
+    );
+}
+
+#[test]
+fn create_with_pr_pushes_a_review_branch() {
+    let ctx = TestContext::new();
+    ctx.create_repo_file(".envrc", "use nix");
+
+    // A remote overlay repo with one commit, and a config whose clone of it
+    // doesn't exist yet
+    let remote = common::create_overlay_dir(&[("README.md", "overlays")]);
+    commit_all(remote.path());
+    let config_dir = tempfile::TempDir::new().unwrap();
+    let clone = config_dir.path().join("clone");
+    fs::create_dir_all(config_dir.path().join("repoverlay")).unwrap();
+    fs::write(
+        config_dir.path().join("repoverlay/config.ccl"),
+        format!(
+            "overlay_repo =\n  url = {}\n  local_path = {}\n",
+            remote.path().display(),
+            clone.display()
+        ),
+    )
+    .unwrap();
+    cargo_bin_cmd!("repoverlay")
+        .env("XDG_CONFIG_HOME", config_dir.path())
+        .env("GIT_AUTHOR_NAME", "Test")
+        .env("GIT_AUTHOR_EMAIL", "test@example.com")
+        .env("GIT_COMMITTER_NAME", "Test")
+        .env("GIT_COMMITTER_EMAIL", "test@example.com")
+        .args(["create", "acme/web/env", "--include", ".envrc", "--pr"])
+        .args(["--source", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Pushed repoverlay/acme/web/env-"));
+
+    let branches = std::process::Command::new("git")
+        .args(["branch", "--list", "repoverlay/*"])
+        .current_dir(remote.path())
+        .output()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&branches.stdout).contains("repoverlay/acme/web/env-"));
+
+    // The clone is back on its branch, which doesn't have the overlay yet
+    assert!(clone.join("README.md").exists());
+    assert!(!clone.join("acme/web/env").exists());
+}
+
+#[test]
+fn create_with_target_skips_remote_detection() {
+    // The test repo has no remote, so a short name needs --target
+    let ctx = TestContext::new();
+    ctx.create_repo_file(".envrc", "use nix");
+
+    let remote = common::create_overlay_dir(&[("README.md", "overlays")]);
+    commit_all(remote.path());
+    let config_dir = tempfile::TempDir::new().unwrap();
+    let clone = config_dir.path().join("clone");
+    fs::create_dir_all(config_dir.path().join("repoverlay")).unwrap();
+    fs::write(
+        config_dir.path().join("repoverlay/config.ccl"),
+        format!(
+            "overlay_repo =\n  url = {}\n  local_path = {}\n",
+            remote.path().display(),
+            clone.display()
+        ),
+    )
+    .unwrap();
+
+    cargo_bin_cmd!("repoverlay")
+        .env("XDG_CONFIG_HOME", config_dir.path())
+        .args(["create", "env", "--include", ".envrc", "--dry-run"])
+        .args(["--source", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--target org/repo"));
+
+    cargo_bin_cmd!("repoverlay")
+        .env("XDG_CONFIG_HOME", config_dir.path())
+        .args(["create", "env", "--target", "acme/web"])
+        .args(["--include", ".envrc", "--dry-run"])
+        .args(["--source", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Creating overlay: acme/web/env"));
+}
+
+#[test]
+fn create_refuses_names_that_normalize_to_an_existing_overlay() {
+    let ctx = TestContext::new();
+    ctx.create_repo_file(".envrc", "use nix");
+
+    let remote = common::create_overlay_dir(&[("acme/web/my-overlay/.envrc", "use nix")]);
+    commit_all(remote.path());
+    let config_dir = tempfile::TempDir::new().unwrap();
+    let clone = config_dir.path().join("clone");
+    fs::create_dir_all(config_dir.path().join("repoverlay")).unwrap();
+    fs::write(
+        config_dir.path().join("repoverlay/config.ccl"),
+        format!(
+            "overlay_repo =\n  url = {}\n  local_path = {}\n",
+            remote.path().display(),
+            clone.display()
+        ),
+    )
+    .unwrap();
+
+    cargo_bin_cmd!("repoverlay")
+        .env("XDG_CONFIG_HOME", config_dir.path())
+        .args([
+            "create",
+            "acme/web/My Overlay",
+            "--include",
+            ".envrc",
+            "--dry-run",
+        ])
+        .args(["--source", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "would collide with 'acme/web/my-overlay': both are applied as 'my-overlay'",
+        ))
+        .stderr(predicate::str::contains("Overlay names are lowercased"));
+
+    cargo_bin_cmd!("repoverlay")
+        .env("XDG_CONFIG_HOME", config_dir.path())
+        .args(["create", "acme/web/My Overlay", "--include", ".envrc"])
+        .args(["--dry-run", "--force"])
+        .args(["--source", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "'My Overlay' is applied as 'my-overlay'",
+        ));
+}
+
+#[test]
+fn create_reads_includes_from_a_file() {
+    let ctx = TestContext::new();
+    ctx.create_repo_file(".envrc", "use nix");
+    ctx.create_repo_file(".github/workflows/ci.yml", "on: push");
+    ctx.create_repo_file(".github/workflows/notes.txt", "notes");
+    ctx.create_repo_file(
+        "overlay-files.txt",
+        "# Overlay manifest\n.envrc\n\n.github/workflows/*.yml\n",
+    );
+
+    let remote = common::create_overlay_dir(&[("README.md", "overlays")]);
+    commit_all(remote.path());
+    let config_dir = tempfile::TempDir::new().unwrap();
+    let clone = config_dir.path().join("clone");
+    fs::create_dir_all(config_dir.path().join("repoverlay")).unwrap();
+    fs::write(
+        config_dir.path().join("repoverlay/config.ccl"),
+        format!(
+            "overlay_repo =\n  url = {}\n  local_path = {}\n",
+            remote.path().display(),
+            clone.display()
+        ),
+    )
+    .unwrap();
+
+    cargo_bin_cmd!("repoverlay")
+        .env("XDG_CONFIG_HOME", config_dir.path())
+        .env("GIT_AUTHOR_NAME", "Test")
+        .env("GIT_AUTHOR_EMAIL", "test@example.com")
+        .env("GIT_COMMITTER_NAME", "Test")
+        .env("GIT_COMMITTER_EMAIL", "test@example.com")
+        .args(["create", "acme/web/env", "--no-fetch", "--include-from"])
+        .arg(ctx.repo_path().join("overlay-files.txt"))
+        .args(["--source", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .success();
+
+    let overlay = clone.join("acme/web/env");
+    assert!(overlay.join(".envrc").exists());
+    assert!(overlay.join(".github/workflows/ci.yml").exists());
+    assert!(!overlay.join(".github/workflows/notes.txt").exists());
+    assert!(!overlay.join("overlay-files.txt").exists());
+}
+
+#[test]
+fn create_with_no_fetch_commits_without_pushing() {
+    let ctx = TestContext::new();
+    ctx.create_repo_file(".envrc", "use nix");
+
+    let work = common::create_overlay_dir(&[("README.md", "overlays")]);
+    commit_all(work.path());
+    let remote = tempfile::TempDir::new().unwrap();
+    let status = std::process::Command::new("git")
+        .args(["clone", "--quiet", "--bare"])
+        .arg(work.path())
+        .arg(remote.path())
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let config_dir = tempfile::TempDir::new().unwrap();
+    let clone = config_dir.path().join("clone");
+    fs::create_dir_all(config_dir.path().join("repoverlay")).unwrap();
+    fs::write(
+        config_dir.path().join("repoverlay/config.ccl"),
+        format!(
+            "overlay_repo =\n  url = {}\n  local_path = {}\n",
+            remote.path().display(),
+            clone.display()
+        ),
+    )
+    .unwrap();
+
+    cargo_bin_cmd!("repoverlay")
+        .env("XDG_CONFIG_HOME", config_dir.path())
+        .env("GIT_AUTHOR_NAME", "Test")
+        .env("GIT_AUTHOR_EMAIL", "test@example.com")
+        .env("GIT_COMMITTER_NAME", "Test")
+        .env("GIT_COMMITTER_EMAIL", "test@example.com")
         .args([
-            "source",
-            "add",
-            "https://github.com/test/first",
-            "--name",
-            "dup-test",
+            "create",
+            "acme/web/env",
+            "--include",
+            ".envrc",
+            "--no-fetch",
         ])
+        .args(["--source", ctx.repo_path().to_str().unwrap()])
         .assert()
-        .success();
+        .success()
+        .stdout(predicate::str::contains("Overlay created locally"))
+        .stdout(predicate::str::contains("Pushing").not());
 
-    // Try to add second with same name - should fail
-    ctx.cmd()
+    let subject = |dir: &std::path::Path| {
+        let log = std::process::Command::new("git")
+            .args(["log", "-1", "--format=%s"])
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        String::from_utf8_lossy(&log.stdout).trim().to_string()
+    };
+    assert_eq!(subject(&clone), "Add overlay: acme/web/env");
+    assert_ne!(subject(remote.path()), "Add overlay: acme/web/env");
+}
+
+#[test]
+fn push_dry_run_lists_unpushed_commits() {
+    let ctx = TestContext::new();
+    ctx.create_repo_file(".envrc", "use nix");
+
+    let work = common::create_overlay_dir(&[("README.md", "overlays")]);
+    commit_all(work.path());
+    let remote = tempfile::TempDir::new().unwrap();
+    let status = std::process::Command::new("git")
+        .args(["clone", "--quiet", "--bare"])
+        .arg(work.path())
+        .arg(remote.path())
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let config_dir = tempfile::TempDir::new().unwrap();
+    fs::create_dir_all(config_dir.path().join("repoverlay")).unwrap();
+    fs::write(
+        config_dir.path().join("repoverlay/config.ccl"),
+        format!(
+            "overlay_repo =\n  url = {}\n  local_path = {}\n",
+            remote.path().display(),
+            config_dir.path().join("clone").display()
+        ),
+    )
+    .unwrap();
+    let repoverlay = || {
+        let mut cmd = cargo_bin_cmd!("repoverlay");
+        cmd.env("XDG_CONFIG_HOME", config_dir.path())
+            .env("GIT_AUTHOR_NAME", "Test")
+            .env("GIT_AUTHOR_EMAIL", "test@example.com")
+            .env("GIT_COMMITTER_NAME", "Test")
+            .env("GIT_COMMITTER_EMAIL", "test@example.com");
+        cmd
+    };
+
+    repoverlay()
         .args([
-            "source",
-            "add",
-            "https://github.com/test/second",
-            "--name",
-            "dup-test",
+            "create",
+            "acme/web/env",
+            "--include",
+            ".envrc",
+            "--no-fetch",
         ])
+        .args(["--source", ctx.repo_path().to_str().unwrap()])
         .assert()
-        .failure()
-        .stderr(predicate::str::contains("already exists"));
+        .success();
+
+    repoverlay()
+        .args(["push", "--all", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("default: 1 commit(s) to push"))
+        .stdout(predicate::str::contains("Add overlay: acme/web/env"))
+        .stdout(predicate::str::contains("nothing was pushed"));
+    let remote_subject = || {
+        let log = std::process::Command::new("git")
+            .args(["log", "-1", "--format=%s"])
+            .current_dir(remote.path())
+            .output()
+            .unwrap();
+        String::from_utf8_lossy(&log.stdout).trim().to_string()
+    };
+    assert_ne!(remote_subject(), "Add overlay: acme/web/env");
+
+    repoverlay()
+        .arg("push")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Pushed default"));
+    assert_eq!(remote_subject(), "Add overlay: acme/web/env");
+
+    repoverlay()
+        .args(["push", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("default: nothing to push"));
 }
 
 #[test]
-fn source_full_add_remove_workflow() {
-    let ctx = SourceTestContext::new();
+fn delete_removes_overlay_and_pushes_the_commit() {
+    let work = common::create_overlay_dir(&[
+        ("README.md", "overlays"),
+        ("acme/web/env/.envrc", "export FOO=bar"),
+    ]);
+    commit_all(work.path());
+    let remote = tempfile::TempDir::new().unwrap();
+    let status = std::process::Command::new("git")
+        .args(["clone", "--quiet", "--bare"])
+        .arg(work.path())
+        .arg(remote.path())
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let config_dir = tempfile::TempDir::new().unwrap();
+    let clone = config_dir.path().join("clone");
+    fs::create_dir_all(config_dir.path().join("repoverlay")).unwrap();
+    fs::write(
+        config_dir.path().join("repoverlay/config.ccl"),
+        format!(
+            "overlay_repo =\n  url = {}\n  local_path = {}\n",
+            remote.path().display(),
+            clone.display()
+        ),
+    )
+    .unwrap();
 
-    // Add first source
-    ctx.cmd()
-        .args([
-            "source",
-            "add",
-            "https://github.com/test/repo-a",
-            "--name",
-            "source-a",
-        ])
+    cargo_bin_cmd!("repoverlay")
+        .env("XDG_CONFIG_HOME", config_dir.path())
+        .env("XDG_DATA_HOME", config_dir.path())
+        .env("GIT_AUTHOR_NAME", "Test")
+        .env("GIT_AUTHOR_EMAIL", "test@example.com")
+        .env("GIT_COMMITTER_NAME", "Test")
+        .env("GIT_COMMITTER_EMAIL", "test@example.com")
+        .args(["delete", "acme/web/env", "--yes"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Overlay deleted: acme/web/env"));
+
+    // The empty acme/web/ directories go with the overlay
+    assert!(!clone.join("acme").exists());
+    assert!(clone.join("README.md").exists());
+
+    let log = std::process::Command::new("git")
+        .args(["log", "-1", "--format=%s"])
+        .current_dir(remote.path())
+        .output()
+        .unwrap();
+    assert_eq!(
+        String::from_utf8_lossy(&log.stdout).trim(),
+        "Delete overlay: acme/web/env"
+    );
+}
+
+#[test]
+fn commits_use_configured_author_and_message_template() {
+    let work = common::create_overlay_dir(&[
+        ("README.md", "overlays"),
+        ("acme/web/env/.envrc", "export FOO=bar"),
+    ]);
+    commit_all(work.path());
+    let remote = tempfile::TempDir::new().unwrap();
+    let status = std::process::Command::new("git")
+        .args(["clone", "--quiet", "--bare"])
+        .arg(work.path())
+        .arg(remote.path())
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let config_dir = tempfile::TempDir::new().unwrap();
+    fs::create_dir_all(config_dir.path().join("repoverlay")).unwrap();
+    fs::write(
+        config_dir.path().join("repoverlay/config.ccl"),
+        format!(
+            "overlay_repo =\n  url = {}\n  local_path = {}\n\
+             commit =\n  author_name = Overlay Bot\n  author_email = bot@example.com\n  \
+             message_template = chore(overlays): {{action}} {{org}}/{{repo}}/{{name}}\n",
+            remote.path().display(),
+            config_dir.path().join("clone").display()
+        ),
+    )
+    .unwrap();
+
+    cargo_bin_cmd!("repoverlay")
+        .env("XDG_CONFIG_HOME", config_dir.path())
+        .env("XDG_DATA_HOME", config_dir.path())
+        .env("GIT_COMMITTER_NAME", "Test")
+        .env("GIT_COMMITTER_EMAIL", "test@example.com")
+        .args(["delete", "acme/web/env", "--yes"])
         .assert()
         .success();
 
-    // Add second source
-    ctx.cmd()
-        .args([
-            "source",
-            "add",
-            "https://github.com/test/repo-b",
-            "--name",
-            "source-b",
-        ])
+    let log = std::process::Command::new("git")
+        .args(["log", "-1", "--format=%s|%an|%ae"])
+        .current_dir(remote.path())
+        .output()
+        .unwrap();
+    assert_eq!(
+        String::from_utf8_lossy(&log.stdout).trim(),
+        "chore(overlays): Delete acme/web/env|Overlay Bot|bot@example.com"
+    );
+}
+
+#[test]
+fn delete_unknown_overlay_fails() {
+    let (_overlay_repo, config_dir) = overlay_repo_with_bundle();
+
+    cargo_bin_cmd!("repoverlay")
+        .env("XDG_CONFIG_HOME", config_dir.path())
+        .args(["delete", "acme/web/missing", "--yes"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Overlay not found: acme/web/missing",
+        ));
+}
+
+#[test]
+#[cfg(unix)]
+fn delete_requires_force_while_applied_with_symlinks() {
+    let ctx = TestContext::new();
+    let (overlay_repo, config_dir) = overlay_repo_with_bundle();
+    let data_home = tempfile::TempDir::new().unwrap();
+    let repoverlay = || {
+        let mut cmd = cargo_bin_cmd!("repoverlay");
+        cmd.env("REPOVERLAY_OFFLINE", "1")
+            .env("XDG_CONFIG_HOME", config_dir.path())
+            .env("XDG_DATA_HOME", data_home.path());
+        cmd
+    };
+    repoverlay()
+        .args(["apply", "acme/web/env"])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
         .assert()
         .success();
+    assert!(ctx.is_symlink(".envrc"));
 
-    // List should show both
-    ctx.cmd()
-        .args(["source", "list"])
+    repoverlay()
+        .args(["delete", "acme/web/env", "--yes"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "is applied with symlinks in 1 place(s)",
+        ))
+        .stderr(predicate::str::contains("--force"));
+    assert!(overlay_repo.path().join("acme/web/env/.envrc").exists());
+
+    repoverlay()
+        .env("GIT_AUTHOR_NAME", "Test")
+        .env("GIT_AUTHOR_EMAIL", "test@example.com")
+        .env("GIT_COMMITTER_NAME", "Test")
+        .env("GIT_COMMITTER_EMAIL", "test@example.com")
+        .args(["delete", "acme/web/env", "--yes", "--force"])
         .assert()
         .success()
-        .stdout(predicate::str::contains("source-a"))
-        .stdout(predicate::str::contains("source-b"));
+        .stderr(predicate::str::contains("Those symlinked files will break"));
+    assert!(!overlay_repo.path().join("acme/web/env").exists());
+}
 
-    // Remove both
-    ctx.cmd()
-        .args(["source", "remove", "source-a"])
+// ============================================================================
+// Offline Mode Tests
+// ============================================================================
+
+#[test]
+fn offline_apply_uses_existing_overlay_repo_clone() {
+    let ctx = TestContext::new();
+    let (_overlay_repo, config_dir) = overlay_repo_with_bundle();
+
+    cargo_bin_cmd!("repoverlay")
+        .env("XDG_CONFIG_HOME", config_dir.path())
+        .args(["--offline", "apply", "acme/web/env", "--update"])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
         .assert()
         .success();
 
-    ctx.cmd()
-        .args(["source", "remove", "source-b"])
+    assert!(ctx.is_symlink(".envrc"));
+}
+
+#[test]
+fn offline_fails_when_source_is_not_cached() {
+    let ctx = TestContext::new();
+    let home = tempfile::TempDir::new().unwrap();
+
+    cargo_bin_cmd!("repoverlay")
+        .env("REPOVERLAY_OFFLINE", "1")
+        .env("XDG_CACHE_HOME", home.path().join("cache"))
+        .env("XDG_DATA_HOME", home.path().join("data"))
+        .args(["apply", "https://github.com/owner/repo"])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "owner/repo is not cached and can't be cloned in offline mode",
+        ));
+
+    // An overlay repo that was never cloned isn't cloned either
+    let config_dir = tempfile::TempDir::new().unwrap();
+    fs::create_dir_all(config_dir.path().join("repoverlay")).unwrap();
+    fs::write(
+        config_dir.path().join("repoverlay/config.ccl"),
+        format!(
+            "overlay_repo =\n  url = https://example.com/overlays.git\n  local_path = {}\n",
+            home.path().join("clone").display()
+        ),
+    )
+    .unwrap();
+    cargo_bin_cmd!("repoverlay")
+        .env("REPOVERLAY_OFFLINE", "1")
+        .env("XDG_CONFIG_HOME", config_dir.path())
+        .args(["apply", "acme/web/env"])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("can't be cloned in offline mode"));
+    assert!(!home.path().join("clone").exists());
+}
+
+#[test]
+fn owner_repo_shorthand_resolves_to_github() {
+    let ctx = TestContext::new();
+    let home = tempfile::TempDir::new().unwrap();
+
+    cargo_bin_cmd!("repoverlay")
+        .env("REPOVERLAY_OFFLINE", "1")
+        .env("XDG_CONFIG_HOME", home.path().join("config"))
+        .env("XDG_CACHE_HOME", home.path().join("cache"))
+        .env("XDG_DATA_HOME", home.path().join("data"))
+        .args(["apply", "owner/repo"])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "owner/repo is not cached and can't be cloned in offline mode",
+        ));
+}
+
+#[test]
+fn mistyped_relative_path_is_not_github_shorthand() {
+    let ctx = TestContext::new();
+    let cwd = tempfile::TempDir::new().unwrap();
+    fs::create_dir(cwd.path().join("configs")).unwrap();
+
+    for source in ["configs/dev", "./configs/dev", "../configs/dev"] {
+        cargo_bin_cmd!("repoverlay")
+            .current_dir(cwd.path())
+            .env("REPOVERLAY_OFFLINE", "1")
+            .args(["apply", source])
+            .args(["--target", ctx.repo_path().to_str().unwrap()])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(format!(
+                "Local path not found: {source}"
+            )))
+            .stderr(predicate::str::contains("github").not());
+    }
+}
+
+#[test]
+fn owner_repo_shorthand_with_overlays_in_overlay_repo_is_ambiguous() {
+    let ctx = TestContext::new();
+    let (_overlay_repo, config_dir) = overlay_repo_with_bundle();
+
+    cargo_bin_cmd!("repoverlay")
+        .env("XDG_CONFIG_HOME", config_dir.path())
+        .args(["apply", "acme/web"])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("'acme/web' is ambiguous"))
+        .stderr(predicate::str::contains("editor, env"))
+        .stderr(predicate::str::contains(
+            "repoverlay apply https://github.com/acme/web",
+        ));
+}
+
+#[test]
+fn apply_expands_home_and_variables_in_local_paths() {
+    let ctx = TestContext::new();
+    let home = common::create_overlay_dir(&[("overlays/foo/.envrc", "export FOO=1")]);
+    let target = ctx.repo_path().to_str().unwrap();
+
+    let canonical = home.path().join("overlays/foo").canonicalize().unwrap();
+
+    for source in [
+        "~/overlays/foo",
+        "$OVERLAY_HOME/overlays/foo",
+        "${OVERLAY_HOME}/overlays/foo",
+    ] {
+        cargo_bin_cmd!("repoverlay")
+            .env("HOME", home.path())
+            .env("OVERLAY_HOME", home.path())
+            .args(["apply", source, "--target", target])
+            .assert()
+            .success();
+        assert_eq!(ctx.read_file(".envrc"), "export FOO=1");
+
+        // The expanded, canonical path is recorded
+        let state =
+            fs::read_to_string(ctx.repo_path().join(".repoverlay/overlays/foo.ccl")).unwrap();
+        assert!(state.contains(&canonical.display().to_string()));
+
+        cargo_bin_cmd!("repoverlay")
+            .args(["remove", "foo", "--target", target])
+            .assert()
+            .success();
+    }
+}
+
+#[test]
+fn apply_subpath_selects_directory_in_github_repo() {
+    let ctx = TestContext::new();
+    let home = tempfile::TempDir::new().unwrap();
+
+    // Seed the cache with a clone so nothing is fetched
+    let upstream = common::create_overlay_dir(&[
+        ("overlays/rust/.envrc", "export RUST=1"),
+        ("docs/index.md", "docs"),
+    ]);
+    commit_all(upstream.path());
+    let clone = home
+        .path()
+        .join("cache/repoverlay/github/github.com/owner/repo");
+    fs::create_dir_all(clone.parent().unwrap()).unwrap();
+    std::process::Command::new("git")
+        .args(["clone", "-q", upstream.path().to_str().unwrap()])
+        .arg(&clone)
+        .output()
+        .unwrap();
+
+    let apply = |subpath: &str| {
+        let mut cmd = cargo_bin_cmd!("repoverlay");
+        cmd.env("REPOVERLAY_OFFLINE", "1")
+            .env("XDG_CONFIG_HOME", home.path().join("config"))
+            .env("XDG_CACHE_HOME", home.path().join("cache"))
+            .env("XDG_DATA_HOME", home.path().join("data"))
+            .args([
+                "apply",
+                "https://github.com/owner/repo",
+                "--subpath",
+                subpath,
+            ])
+            .args(["--target", ctx.repo_path().to_str().unwrap()]);
+        cmd.assert()
+    };
+
+    apply("overlays/go")
+        .failure()
+        .stderr(predicate::str::contains(
+            "Subpath 'overlays/go' not found in repository owner/repo",
+        ))
+        .stderr(predicate::str::contains(
+            "Available top-level directories: docs, overlays",
+        ));
+
+    apply("overlays/rust").success();
+    assert_eq!(ctx.read_file(".envrc"), "export RUST=1");
+    assert!(!ctx.file_exists("docs/index.md"));
+
+    let state = fs::read_to_string(ctx.repo_path().join(".repoverlay/overlays/rust.ccl")).unwrap();
+    assert!(state.contains("https://github.com/owner/repo/tree/HEAD/overlays/rust"));
+}
+
+#[test]
+fn apply_subpath_rejects_local_sources() {
+    let ctx = TestContext::new().with_overlay(&common::envrc_overlay());
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["apply", ctx.overlay_source(), "--subpath", "overlays"])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "--subpath only applies to GitHub sources",
+        ));
+    assert!(!ctx.file_exists(".envrc"));
+}
+
+#[test]
+fn offline_update_and_sync_refuse() {
+    let ctx = TestContext::new();
+
+    for args in [vec!["update"], vec!["sync", "--all"]] {
+        cargo_bin_cmd!("repoverlay")
+            .arg("--offline")
+            .args(&args)
+            .args(["--target", ctx.repo_path().to_str().unwrap()])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("in offline mode"));
+    }
+}
+
+// ============================================================================
+// Export / Import Tests
+// ============================================================================
+
+#[test]
+fn export_writes_bundle_with_dereferenced_files() {
+    let ctx = TestContext::new().with_overlay(&[(".envrc", "use nix")]);
+    cargo_bin_cmd!("repoverlay")
+        .args(["apply", ctx.overlay_source()])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .args(["--name", "dev"])
         .assert()
         .success();
+
+    let out = tempfile::TempDir::new().unwrap();
+    let bundle = out.path().join("overlays.tar.gz");
+    cargo_bin_cmd!("repoverlay")
+        .args(["export", bundle.to_str().unwrap()])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Exported 1 overlay(s) with 1 file(s)",
+        ));
+
+    let unpacked = out.path().join("unpacked");
+    fs::create_dir_all(&unpacked).unwrap();
+    let status = std::process::Command::new("tar")
+        .arg("-xzf")
+        .arg(&bundle)
+        .arg("-C")
+        .arg(&unpacked)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    assert!(unpacked.join("manifest.ccl").exists());
+    assert!(unpacked.join("state/overlays/dev.ccl").exists());
+    let envrc = unpacked.join("files/dev/.envrc");
+    assert!(!envrc.is_symlink());
+    assert_eq!(fs::read_to_string(envrc).unwrap(), "use nix");
 }
 
 #[test]
-fn source_add_extracts_name_from_url() {
-    let ctx = SourceTestContext::new();
-    // When no --name is provided, name should be extracted from URL
-    ctx.cmd()
-        .args(["source", "add", "https://github.com/test/extracted-name"])
+fn import_applies_exported_overlays_to_fresh_repo() {
+    let ctx = TestContext::new().with_overlay(&[(".envrc", "use nix"), ("cfg/a.toml", "a")]);
+    cargo_bin_cmd!("repoverlay")
+        .args(["apply", ctx.overlay_source()])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .args(["--name", "dev"])
+        .assert()
+        .success();
+
+    let home = tempfile::TempDir::new().unwrap();
+    let bundle = home.path().join("overlays.tar.gz");
+    cargo_bin_cmd!("repoverlay")
+        .args(["export", bundle.to_str().unwrap()])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .assert()
+        .success();
+
+    let fresh = TestContext::new();
+    cargo_bin_cmd!("repoverlay")
+        .args(["import", bundle.to_str().unwrap()])
+        .args(["--target", fresh.repo_path().to_str().unwrap()])
+        .env("XDG_DATA_HOME", home.path().join("data"))
         .assert()
         .success()
-        .stdout(predicate::str::contains("source 'extracted-name'"));
+        .stdout(predicate::str::contains("Imported 1 overlay(s)"));
+
+    assert_eq!(
+        fs::read_to_string(fresh.repo_path().join(".envrc")).unwrap(),
+        "use nix"
+    );
+    assert_eq!(
+        fs::read_to_string(fresh.repo_path().join("cfg/a.toml")).unwrap(),
+        "a"
+    );
+    let link = fs::read_link(fresh.repo_path().join(".envrc")).unwrap();
+    assert!(link.starts_with(home.path().join("data/repoverlay/imports")));
 }
 
 #[test]
-fn source_add_strips_git_suffix_from_name() {
-    let ctx = SourceTestContext::new();
-    // URL ending in .git should have that suffix stripped
-    ctx.cmd()
-        .args(["source", "add", "https://github.com/test/git-suffix.git"])
+fn import_rejects_corrupt_bundle() {
+    let ctx = TestContext::new();
+    let home = tempfile::TempDir::new().unwrap();
+    let bundle = home.path().join("broken.tar.gz");
+    fs::write(&bundle, "not a tarball").unwrap();
+
+    cargo_bin_cmd!("repoverlay")
+        .args(["import", bundle.to_str().unwrap()])
+        .args(["--target", ctx.repo_path().to_str().unwrap()])
+        .env("XDG_DATA_HOME", home.path().join("data"))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid bundle"));
+}
+
+// ============================================================================
+// Doctor Tests
+// ============================================================================
+
+#[test]
+fn doctor_reports_checklist_for_reachable_overlay_repo() {
+    let (overlay_repo, config_dir) = overlay_repo_with_bundle();
+    fs::write(
+        config_dir.path().join("repoverlay/config.ccl"),
+        format!(
+            "overlay_repo =\n  url = {}\n  local_path = {}\n",
+            overlay_repo.path().display(),
+            overlay_repo.path().display()
+        ),
+    )
+    .unwrap();
+    let home = tempfile::TempDir::new().unwrap();
+
+    cargo_bin_cmd!("repoverlay")
+        .arg("doctor")
+        .env("XDG_CONFIG_HOME", config_dir.path())
+        .env("XDG_CACHE_HOME", home.path().join("cache"))
+        .env("GITHUB_TOKEN", "test-token")
         .assert()
         .success()
-        .stdout(predicate::str::contains("source 'git-suffix'"));
+        .stdout(predicate::str::contains("git version"))
+        .stdout(predicate::str::contains("symlinks: can be created"))
+        .stdout(predicate::str::contains("(cloned)"))
+        .stdout(predicate::str::contains("is writable"))
+        .stdout(predicate::str::contains("found in $GITHUB_TOKEN"))
+        .stdout(predicate::str::contains("No problems found"));
+}
+
+#[test]
+fn doctor_fails_when_overlay_repo_is_unreachable() {
+    let config_dir = tempfile::TempDir::new().unwrap();
+    let home = tempfile::TempDir::new().unwrap();
+    fs::create_dir_all(config_dir.path().join("repoverlay")).unwrap();
+    fs::write(
+        config_dir.path().join("repoverlay/config.ccl"),
+        format!(
+            "overlay_repo =\n  url = {}\n  local_path = {}\n",
+            home.path().join("missing").display(),
+            home.path().join("clone").display()
+        ),
+    )
+    .unwrap();
+
+    cargo_bin_cmd!("repoverlay")
+        .arg("doctor")
+        .env("XDG_CONFIG_HOME", config_dir.path())
+        .env("XDG_CACHE_HOME", home.path().join("cache"))
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("is unreachable"))
+        .stderr(predicate::str::contains("1 check(s) failed"));
 }
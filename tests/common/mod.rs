@@ -104,6 +104,8 @@ impl TestContext {
     }
 
     /// Check if an overlay state file exists.
+    ///
+    /// The extension must match `STATE_EXT` in `src/state.rs`.
     pub fn overlay_state_exists(&self, name: &str) -> bool {
         self.repo
             .path()